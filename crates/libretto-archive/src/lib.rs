@@ -6,8 +6,14 @@
 //! - **TAR.GZ** - Native support via `flate2` crate
 //! - **TAR.BZ2** - Native support via `bzip2` crate
 //! - **TAR.XZ** - Native support via `xz2` crate
+//! - **TAR.ZST** - Native support via `zstd` crate
+//! - **GZIP** - Single gzip-compressed files (Composer `gzip` dists)
 //! - **7Z** - CLI support (requires `7z` or `7zz` binary)
 //! - **RAR** - CLI support (requires `unrar` binary)
+//!
+//! Entries that would escape the destination (`..` components, absolute
+//! paths, symlinks pointing outside of it or writes through symlinks) are
+//! rejected.
 
 #![warn(clippy::all)]
 #![allow(clippy::module_name_repetitions)]
@@ -17,7 +23,7 @@ use flate2::read::GzDecoder;
 use libretto_core::{Error, Result};
 use std::fs::File;
 use std::io::{Read, Seek};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
 use tracing::{debug, info, warn};
 use walkdir::WalkDir;
@@ -36,6 +42,10 @@ pub enum ArchiveType {
     TarBz2,
     /// XZ compressed tarball.
     TarXz,
+    /// Zstandard compressed tarball.
+    TarZst,
+    /// Single gzip-compressed file.
+    Gzip,
     /// 7-Zip archive (requires CLI tool).
     SevenZip,
     /// RAR archive (requires CLI tool).
@@ -64,6 +74,10 @@ impl ArchiveType {
             Some(Self::TarBz2)
         } else if lower.ends_with(".tar.xz") || lower.ends_with(".txz") {
             Some(Self::TarXz)
+        } else if lower.ends_with(".tar.zst") || lower.ends_with(".tzst") {
+            Some(Self::TarZst)
+        } else if lower.ends_with(".gz") {
+            Some(Self::Gzip)
         } else if lower.ends_with(".7z") {
             Some(Self::SevenZip)
         } else if lower.ends_with(".rar") {
@@ -75,6 +89,51 @@ impl ArchiveType {
         }
     }
 
+    /// Determine the archive type of a Composer `dist` from its `type` and URL.
+    ///
+    /// `tar` dists may be compressed; the URL extension picks the compression
+    /// and, failing that, the file's magic bytes are checked at extraction.
+    #[must_use]
+    pub fn from_dist(dist_type: Option<&str>, url: &str) -> Option<Self> {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        let from_url = Self::from_filename(path);
+        match dist_type.map(str::to_ascii_lowercase).as_deref() {
+            Some("zip") => Some(Self::Zip),
+            Some("tar") => Some(from_url.filter(|t| t.is_tarball()).unwrap_or(Self::Tar)),
+            Some("gzip") => Some(Self::Gzip),
+            Some("rar") => Some(Self::Rar),
+            _ => from_url,
+        }
+    }
+
+    /// Detect a ZIP archive or compressed tarball from its magic bytes.
+    #[must_use]
+    pub fn from_magic(path: &Path) -> Option<Self> {
+        let mut magic = Vec::with_capacity(6);
+        File::open(path)
+            .ok()?
+            .take(6)
+            .read_to_end(&mut magic)
+            .ok()?;
+        match magic.as_slice() {
+            [0x50, 0x4b, 0x03, 0x04, ..] => Some(Self::Zip),
+            [0x1f, 0x8b, ..] => Some(Self::TarGz),
+            [0x42, 0x5a, 0x68, ..] => Some(Self::TarBz2),
+            [0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00] => Some(Self::TarXz),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Self::TarZst),
+            _ => None,
+        }
+    }
+
+    /// Check if this is a (possibly compressed) tarball.
+    #[must_use]
+    pub const fn is_tarball(self) -> bool {
+        matches!(
+            self,
+            Self::Tar | Self::TarGz | Self::TarBz2 | Self::TarXz | Self::TarZst
+        )
+    }
+
     /// Get file extension.
     #[must_use]
     pub const fn extension(self) -> &'static str {
@@ -84,6 +143,8 @@ impl ArchiveType {
             Self::Tar => "tar",
             Self::TarBz2 => "tar.bz2",
             Self::TarXz => "tar.xz",
+            Self::TarZst => "tar.zst",
+            Self::Gzip => "gz",
             Self::SevenZip => "7z",
             Self::Rar => "rar",
         }
@@ -110,6 +171,9 @@ impl ArchiveType {
 #[derive(Debug, Default)]
 pub struct Extractor {
     strip_prefix: Option<usize>,
+    strip_root: bool,
+    executables: Vec<PathBuf>,
+    file_name: Option<String>,
 }
 
 impl Extractor {
@@ -126,6 +190,35 @@ impl Extractor {
         self
     }
 
+    /// Strip the single top-level directory GitHub and GitLab add to their
+    /// archives, if the archive has one.
+    #[must_use]
+    pub const fn strip_root(mut self) -> Self {
+        self.strip_root = true;
+        self
+    }
+
+    /// Mark files as executable after extraction (e.g. a package's `bin`
+    /// entries), relative to the extracted root.
+    #[must_use]
+    pub fn executables<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.executables = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Name of the file extracted from a single-file gzip archive.
+    ///
+    /// Defaults to the archive's file name without its `.gz` extension.
+    #[must_use]
+    pub fn file_name(mut self, name: impl Into<String>) -> Self {
+        self.file_name = Some(name.into());
+        self
+    }
+
     /// Extract archive to directory.
     ///
     /// # Errors
@@ -134,6 +227,28 @@ impl Extractor {
         let archive_type = ArchiveType::from_path(archive).ok_or_else(|| {
             Error::archive(format!("unknown archive type: {}", archive.display()))
         })?;
+        self.extract_as(archive, archive_type, dest)
+    }
+
+    /// Extract an archive of a known type to directory.
+    ///
+    /// Plain `tar` archives are checked for compression by their magic bytes.
+    ///
+    /// # Errors
+    /// Returns error if extraction fails or an entry would escape `dest`.
+    pub fn extract_as(
+        &self,
+        archive: &Path,
+        archive_type: ArchiveType,
+        dest: &Path,
+    ) -> Result<ExtractionResult> {
+        let archive_type = if archive_type == ArchiveType::Tar {
+            ArchiveType::from_magic(archive)
+                .filter(|t| t.is_tarball())
+                .unwrap_or(ArchiveType::Tar)
+        } else {
+            archive_type
+        };
 
         debug!(archive = ?archive, dest = ?dest, archive_type = ?archive_type, "extracting");
 
@@ -147,16 +262,26 @@ impl Extractor {
 
         std::fs::create_dir_all(dest).map_err(|e| Error::io(dest, e))?;
 
-        let result = match archive_type {
-            ArchiveType::Zip => self.extract_zip(archive, dest)?,
-            ArchiveType::TarGz => self.extract_tar_gz(archive, dest)?,
-            ArchiveType::Tar => self.extract_tar(archive, dest)?,
-            ArchiveType::TarBz2 => self.extract_tar_bz2(archive, dest)?,
-            ArchiveType::TarXz => self.extract_tar_xz(archive, dest)?,
-            ArchiveType::SevenZip => self.extract_7z(archive, dest)?,
-            ArchiveType::Rar => self.extract_rar(archive, dest)?,
+        let result = if self.strip_root {
+            // Extract next to the destination, then move the contents of the
+            // single top-level directory (or everything) into place
+            let parent = dest.parent().unwrap_or(dest);
+            let staging = tempfile::Builder::new()
+                .prefix(".libretto-extract-")
+                .tempdir_in(parent)
+                .map_err(|e| Error::io(parent, e))?;
+            let mut result = self.extract_into(archive, archive_type, staging.path())?;
+            let root =
+                find_root_dir(staging.path()).unwrap_or_else(|| staging.path().to_path_buf());
+            move_children(&root, dest)?;
+            result.root_dir = find_root_dir(dest);
+            result
+        } else {
+            self.extract_into(archive, archive_type, dest)?
         };
 
+        self.mark_executables(dest)?;
+
         info!(
             files = result.files_extracted,
             size = result.total_size,
@@ -166,6 +291,25 @@ impl Extractor {
         Ok(result)
     }
 
+    fn extract_into(
+        &self,
+        archive: &Path,
+        archive_type: ArchiveType,
+        dest: &Path,
+    ) -> Result<ExtractionResult> {
+        match archive_type {
+            ArchiveType::Zip => self.extract_zip(archive, dest),
+            ArchiveType::TarGz => self.extract_tar_gz(archive, dest),
+            ArchiveType::Tar => self.extract_tar(archive, dest),
+            ArchiveType::TarBz2 => self.extract_tar_bz2(archive, dest),
+            ArchiveType::TarXz => self.extract_tar_xz(archive, dest),
+            ArchiveType::TarZst => self.extract_tar_zst(archive, dest),
+            ArchiveType::Gzip => self.extract_gzip(archive, dest),
+            ArchiveType::SevenZip => self.extract_7z(archive, dest),
+            ArchiveType::Rar => self.extract_rar(archive, dest),
+        }
+    }
+
     fn extract_zip(&self, archive: &Path, dest: &Path) -> Result<ExtractionResult> {
         let file = File::open(archive).map_err(|e| Error::io(archive, e))?;
         let mut zip = zip::ZipArchive::new(file).map_err(|e| Error::archive(e.to_string()))?;
@@ -176,38 +320,39 @@ impl Extractor {
         for i in 0..zip.len() {
             let mut entry = zip.by_index(i).map_err(|e| Error::archive(e.to_string()))?;
 
-            let path = match entry.enclosed_name() {
-                Some(p) => p.clone(),
-                None => continue,
+            let Some(name) = entry.enclosed_name() else {
+                return Err(escape_error(Path::new(entry.name())));
             };
-
-            let out_path = self.apply_strip_prefix(&path, dest);
-            if out_path == dest {
+            let Some(relative) = self.entry_path(&name)? else {
                 continue;
-            }
+            };
 
             if entry.is_dir() {
+                let out_path = prepare_output(dest, &relative)?;
                 std::fs::create_dir_all(&out_path).map_err(|e| Error::io(&out_path, e))?;
-            } else {
-                if let Some(parent) = out_path.parent() {
-                    std::fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
-                }
+                continue;
+            }
 
-                let mut out_file = File::create(&out_path).map_err(|e| Error::io(&out_path, e))?;
-                let size = std::io::copy(&mut entry, &mut out_file)
-                    .map_err(|e| Error::io(&out_path, e))?;
+            let mode = entry.unix_mode();
+            if mode.is_some_and(|m| m & 0o170_000 == 0o120_000) {
+                let mut target = String::new();
+                entry
+                    .read_to_string(&mut target)
+                    .map_err(|e| Error::archive(e.to_string()))?;
+                create_symlink(dest, &relative, Path::new(&target))?;
+                continue;
+            }
 
-                files_extracted += 1;
-                total_size += size;
+            let out_path = prepare_output(dest, &relative)?;
+            let mut out_file = File::create(&out_path).map_err(|e| Error::io(&out_path, e))?;
+            let size =
+                std::io::copy(&mut entry, &mut out_file).map_err(|e| Error::io(&out_path, e))?;
 
-                #[cfg(unix)]
-                {
-                    use std::os::unix::fs::PermissionsExt;
-                    if let Some(mode) = entry.unix_mode() {
-                        std::fs::set_permissions(&out_path, std::fs::Permissions::from_mode(mode))
-                            .ok();
-                    }
-                }
+            files_extracted += 1;
+            total_size += size;
+
+            if let Some(mode) = mode {
+                set_mode(&out_path, mode);
             }
         }
 
@@ -245,20 +390,26 @@ impl Extractor {
                 .map_err(|e| Error::archive(e.to_string()))?
                 .into_owned();
 
-            let out_path = self.apply_strip_prefix(&path, dest);
-            if out_path == dest {
+            let Some(relative) = self.entry_path(&path)? else {
                 continue;
-            }
+            };
 
             let entry_type = entry.header().entry_type();
 
             if entry_type.is_dir() {
+                let out_path = prepare_output(dest, &relative)?;
                 std::fs::create_dir_all(&out_path).map_err(|e| Error::io(&out_path, e))?;
+            } else if entry_type.is_symlink() {
+                let target = entry
+                    .link_name()
+                    .map_err(|e| Error::archive(e.to_string()))?
+                    .ok_or_else(|| {
+                        Error::archive(format!("symlink without target: {}", path.display()))
+                    })?
+                    .into_owned();
+                create_symlink(dest, &relative, &target)?;
             } else if entry_type.is_file() {
-                if let Some(parent) = out_path.parent() {
-                    std::fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
-                }
-
+                let out_path = prepare_output(dest, &relative)?;
                 let mut out_file = File::create(&out_path).map_err(|e| Error::io(&out_path, e))?;
                 let size = std::io::copy(&mut entry, &mut out_file)
                     .map_err(|e| Error::io(&out_path, e))?;
@@ -266,14 +417,11 @@ impl Extractor {
                 files_extracted += 1;
                 total_size += size;
 
-                #[cfg(unix)]
-                {
-                    use std::os::unix::fs::PermissionsExt;
-                    if let Ok(mode) = entry.header().mode() {
-                        std::fs::set_permissions(&out_path, std::fs::Permissions::from_mode(mode))
-                            .ok();
-                    }
+                if let Ok(mode) = entry.header().mode() {
+                    set_mode(&out_path, mode);
                 }
+            } else {
+                debug!(path = ?path, entry_type = ?entry_type, "skipping unsupported tar entry");
             }
         }
 
@@ -296,6 +444,43 @@ impl Extractor {
         self.extract_tar_reader(decoder, dest)
     }
 
+    fn extract_tar_zst(&self, archive: &Path, dest: &Path) -> Result<ExtractionResult> {
+        let file = File::open(archive).map_err(|e| Error::io(archive, e))?;
+        let decoder = zstd::stream::read::Decoder::new(file)
+            .map_err(|e| Error::archive(format!("invalid zstd stream: {e}")))?;
+        self.extract_tar_reader(decoder, dest)
+    }
+
+    fn extract_gzip(&self, archive: &Path, dest: &Path) -> Result<ExtractionResult> {
+        let name = self
+            .file_name
+            .clone()
+            .or_else(|| {
+                let name = archive.file_name()?.to_str()?;
+                name.strip_suffix(".gz").map(String::from)
+            })
+            .ok_or_else(|| {
+                Error::archive(format!(
+                    "cannot determine file name for {}",
+                    archive.display()
+                ))
+            })?;
+        let relative = sanitize(Path::new(&name)).ok_or_else(|| escape_error(Path::new(&name)))?;
+
+        let file = File::open(archive).map_err(|e| Error::io(archive, e))?;
+        let mut decoder = GzDecoder::new(file);
+        let out_path = prepare_output(dest, &relative)?;
+        let mut out_file = File::create(&out_path).map_err(|e| Error::io(&out_path, e))?;
+        let size =
+            std::io::copy(&mut decoder, &mut out_file).map_err(|e| Error::io(&out_path, e))?;
+
+        Ok(ExtractionResult {
+            files_extracted: 1,
+            total_size: size,
+            root_dir: None,
+        })
+    }
+
     #[allow(clippy::unused_self)]
     fn extract_7z(&self, archive: &Path, dest: &Path) -> Result<ExtractionResult> {
         let cmd = find_7z_command()
@@ -354,16 +539,42 @@ impl Extractor {
         })
     }
 
-    fn apply_strip_prefix(&self, path: &Path, dest: &Path) -> PathBuf {
-        if let Some(n) = self.strip_prefix {
-            let components: Vec<_> = path.components().skip(n).collect();
-            if components.is_empty() {
-                return dest.to_path_buf();
+    /// Resolve an archive entry name to a path relative to the destination.
+    ///
+    /// Returns `None` for entries stripped away entirely and an error for
+    /// names that would escape the destination.
+    fn entry_path(&self, name: &Path) -> Result<Option<PathBuf>> {
+        let relative = sanitize(name).ok_or_else(|| escape_error(name))?;
+        let stripped: PathBuf = relative
+            .components()
+            .skip(self.strip_prefix.unwrap_or(0))
+            .collect();
+        Ok((!stripped.as_os_str().is_empty()).then_some(stripped))
+    }
+
+    /// Make the configured executables runnable.
+    fn mark_executables(&self, dest: &Path) -> Result<()> {
+        for path in &self.executables {
+            let Some(relative) = sanitize(path) else {
+                warn!(path = ?path, "ignoring executable outside of the package");
+                continue;
+            };
+            let target = prepare_output(dest, &relative)?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                if let Ok(meta) = std::fs::metadata(&target)
+                    && meta.is_file()
+                {
+                    let mode = meta.permissions().mode() | 0o111;
+                    std::fs::set_permissions(&target, std::fs::Permissions::from_mode(mode))
+                        .map_err(|e| Error::io(&target, e))?;
+                }
             }
-            dest.join(components.iter().collect::<PathBuf>())
-        } else {
-            dest.join(path)
+            #[cfg(not(unix))]
+            let _ = target;
         }
+        Ok(())
     }
 }
 
@@ -393,6 +604,119 @@ fn find_root_dir(dest: &Path) -> Option<PathBuf> {
     }
 }
 
+/// Normalize an archive path, rejecting absolute paths and `..` components.
+fn sanitize(path: &Path) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(relative)
+}
+
+fn escape_error(name: &Path) -> Error {
+    Error::archive(format!(
+        "refusing to extract '{}': path escapes the destination",
+        name.display()
+    ))
+}
+
+/// Prepare `dest/relative` for writing.
+///
+/// Creates missing parent directories and refuses to write through a
+/// symlink created by an earlier entry; an existing symlink at the path
+/// itself is replaced.
+fn prepare_output(dest: &Path, relative: &Path) -> Result<PathBuf> {
+    let mut current = dest.to_path_buf();
+    let mut components = relative.components().peekable();
+    while let Some(component) = components.next() {
+        current.push(component);
+        let is_symlink =
+            std::fs::symlink_metadata(&current).is_ok_and(|meta| meta.file_type().is_symlink());
+        if !is_symlink {
+            continue;
+        }
+        if components.peek().is_some() {
+            return Err(escape_error(relative));
+        }
+        std::fs::remove_file(&current).map_err(|e| Error::io(&current, e))?;
+    }
+    if let Some(parent) = current.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+    }
+    Ok(current)
+}
+
+/// Check that a symlink at `link` pointing to `target` resolves inside the
+/// destination.
+///
+/// Targets must be relative with `..` only as leading components, so the
+/// check holds even when the target passes through other symlinks.
+fn symlink_stays_inside(link: &Path, target: &Path) -> bool {
+    let mut depth = link.components().count().saturating_sub(1);
+    let mut descended = false;
+    for component in target.components() {
+        match component {
+            Component::Normal(_) => {
+                descended = true;
+                depth += 1;
+            }
+            Component::CurDir => {}
+            Component::ParentDir if !descended && depth > 0 => depth -= 1,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+/// Create a symlink entry after validating its target.
+fn create_symlink(dest: &Path, relative: &Path, target: &Path) -> Result<()> {
+    if !symlink_stays_inside(relative, target) {
+        return Err(Error::archive(format!(
+            "refusing to extract symlink '{}' -> '{}': target escapes the destination",
+            relative.display(),
+            target.display()
+        )));
+    }
+    let out_path = prepare_output(dest, relative)?;
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(target, &out_path).map_err(|e| Error::io(&out_path, e))?;
+    #[cfg(not(unix))]
+    debug!(link = ?out_path, target = ?target, "skipping symlink on this platform");
+    Ok(())
+}
+
+/// Apply an archived file mode, keeping permission bits only.
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn set_mode(path: &Path, mode: u32) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode & 0o777)).ok();
+    }
+}
+
+/// Move every entry of `from` into `to`, replacing existing entries.
+fn move_children(from: &Path, to: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(from).map_err(|e| Error::io(from, e))? {
+        let entry = entry.map_err(|e| Error::io(from, e))?;
+        let target = to.join(entry.file_name());
+        if let Ok(meta) = std::fs::symlink_metadata(&target) {
+            if meta.is_dir() {
+                std::fs::remove_dir_all(&target)
+            } else {
+                std::fs::remove_file(&target)
+            }
+            .map_err(|e| Error::io(&target, e))?;
+        }
+        std::fs::rename(entry.path(), &target).map_err(|e| Error::io(&target, e))?;
+    }
+    Ok(())
+}
+
 /// Count files and total size in a directory.
 fn count_files_in_dir(dir: &Path) -> (usize, u64) {
     let mut count = 0;
@@ -450,6 +774,8 @@ pub fn available_tools() -> Vec<(&'static str, bool)> {
         ("tar.gz", true),  // Always available (native)
         ("tar.bz2", true), // Always available (native)
         ("tar.xz", true),  // Always available (native)
+        ("tar.zst", true), // Always available (native)
+        ("gzip", true),    // Always available (native)
         ("7z", is_7z_available()),
         ("rar", is_unrar_available()),
     ]
@@ -592,4 +918,232 @@ mod tests {
                 .any(|(name, avail)| *name == "tar.xz" && *avail)
         );
     }
+
+    fn tar_bytes(entries: &[(&str, &[u8], u32)], symlinks: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, data, mode) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(*mode);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *data).unwrap();
+        }
+        for (path, target) in symlinks {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            builder.append_link(&mut header, path, target).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    /// A tar whose single entry is named `name` verbatim, bypassing the
+    /// path validation of `tar::Builder`.
+    fn raw_tar(name: &str) -> Vec<u8> {
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_size(4);
+        header.set_mode(0o644);
+        header.set_cksum();
+        let mut builder = tar::Builder::new(Vec::new());
+        builder.append(&header, &b"evil"[..]).unwrap();
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn dist_type_detection() {
+        let github = "https://api.github.com/repos/a/b/zipball/abc";
+        assert_eq!(
+            ArchiveType::from_dist(Some("zip"), github),
+            Some(ArchiveType::Zip)
+        );
+        assert_eq!(ArchiveType::from_dist(None, github), None);
+        assert_eq!(
+            ArchiveType::from_dist(Some("tar"), "https://satis.local/dist/a-b-1.0.tar.gz?t=1"),
+            Some(ArchiveType::TarGz)
+        );
+        assert_eq!(
+            ArchiveType::from_dist(Some("tar"), "https://gitlab.local/api/archive"),
+            Some(ArchiveType::Tar)
+        );
+        assert_eq!(
+            ArchiveType::from_dist(Some("tar"), "https://example.com/a.tar.zst"),
+            Some(ArchiveType::TarZst)
+        );
+        assert_eq!(
+            ArchiveType::from_dist(Some("gzip"), "https://example.com/tool.phar.gz"),
+            Some(ArchiveType::Gzip)
+        );
+        assert_eq!(
+            ArchiveType::from_filename("tool.phar.gz"),
+            Some(ArchiveType::Gzip)
+        );
+    }
+
+    #[test]
+    fn extracts_compressed_tar_with_strip_root_and_bin() {
+        use std::io::Write;
+
+        let tar = tar_bytes(
+            &[
+                ("acme-tool-1a2b3c/composer.json", b"{}", 0o644),
+                ("acme-tool-1a2b3c/bin/tool", b"#!/usr/bin/env php", 0o644),
+                ("acme-tool-1a2b3c/bin/helper", b"#!/bin/sh", 0o755),
+            ],
+            &[("acme-tool-1a2b3c/tool", "bin/tool")],
+        );
+        let dir = tempfile::tempdir().unwrap();
+
+        // Served as a `tar` dist without an extension: detected by magic bytes
+        let archive = dir.path().join("download");
+        let mut encoder = zstd::stream::write::Encoder::new(File::create(&archive).unwrap(), 0)
+            .unwrap()
+            .auto_finish();
+        encoder.write_all(&tar).unwrap();
+        drop(encoder);
+
+        let dest = dir.path().join("vendor/acme/tool");
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(dest.join("composer.json"), "stale").unwrap();
+
+        let result = Extractor::new()
+            .strip_root()
+            .executables(["bin/tool"])
+            .extract_as(&archive, ArchiveType::Tar, &dest)
+            .unwrap();
+
+        assert_eq!(result.files_extracted, 3);
+        assert_eq!(
+            std::fs::read_to_string(dest.join("composer.json")).unwrap(),
+            "{}"
+        );
+        assert!(!dest.join("acme-tool-1a2b3c").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |p: &str| {
+                std::fs::metadata(dest.join(p))
+                    .unwrap()
+                    .permissions()
+                    .mode()
+            };
+            assert_eq!(mode("bin/tool") & 0o111, 0o111);
+            assert_eq!(mode("bin/helper") & 0o777, 0o755);
+            assert_eq!(
+                std::fs::read_link(dest.join("tool")).unwrap(),
+                Path::new("bin/tool")
+            );
+        }
+        // No staging directories are left behind
+        assert_eq!(
+            std::fs::read_dir(dest.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn zip_strip_root_keeps_multi_root_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("pkg.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        let options = zip::write::SimpleFileOptions::default().unix_permissions(0o755);
+        zip.start_file("src/Foo.php", options).unwrap();
+        std::io::Write::write_all(&mut zip, b"<?php").unwrap();
+        zip.start_file("composer.json", options).unwrap();
+        std::io::Write::write_all(&mut zip, b"{}").unwrap();
+        zip.finish().unwrap();
+
+        let dest = dir.path().join("out");
+        Extractor::new()
+            .strip_root()
+            .extract(&archive, &dest)
+            .unwrap();
+
+        assert!(dest.join("src/Foo.php").is_file());
+        assert!(dest.join("composer.json").is_file());
+    }
+
+    #[test]
+    fn rejects_path_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("evil.tar");
+        std::fs::write(&archive, raw_tar("../evil.txt")).unwrap();
+
+        let dest = dir.path().join("out");
+        let err = Extractor::new().extract(&archive, &dest).unwrap_err();
+        assert!(err.to_string().contains("escapes the destination"));
+        assert!(!dir.path().join("evil.txt").exists());
+    }
+
+    #[test]
+    fn rejects_escaping_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("out");
+
+        for target in ["../../etc", "/etc", "lib/../../.."] {
+            let archive = dir.path().join("link.tar");
+            std::fs::write(&archive, tar_bytes(&[], &[("pkg/link", target)])).unwrap();
+            let err = Extractor::new().extract(&archive, &dest).unwrap_err();
+            assert!(err.to_string().contains("target escapes"), "{target}");
+        }
+
+        // Writing through a symlink created by an earlier entry
+        let archive = dir.path().join("through.tar");
+        let mut tar = tar_bytes(&[], &[("pkg/dir", ".")]);
+        tar.truncate(tar.len() - 1024);
+        let mut builder = tar::Builder::new(tar);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "pkg/dir/file", &b"data"[..])
+            .unwrap();
+        std::fs::write(&archive, builder.into_inner().unwrap()).unwrap();
+        let err = Extractor::new().extract(&archive, &dest).unwrap_err();
+        assert!(err.to_string().contains("escapes the destination"));
+    }
+
+    #[test]
+    fn symlink_containment() {
+        assert!(symlink_stays_inside(
+            Path::new("a/b/link"),
+            Path::new("../c")
+        ));
+        assert!(symlink_stays_inside(
+            Path::new("a/link"),
+            Path::new("./b/c")
+        ));
+        assert!(!symlink_stays_inside(
+            Path::new("a/link"),
+            Path::new("../../c")
+        ));
+        assert!(!symlink_stays_inside(
+            Path::new("a/link"),
+            Path::new("b/../../..")
+        ));
+        assert!(!symlink_stays_inside(Path::new("link"), Path::new("/etc")));
+    }
+
+    #[test]
+    fn extracts_single_gzip_file() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("download");
+        let mut encoder = GzEncoder::new(File::create(&archive).unwrap(), Compression::fast());
+        encoder.write_all(b"phar contents").unwrap();
+        encoder.finish().unwrap();
+
+        let dest = dir.path().join("out");
+        Extractor::new()
+            .file_name("tool.phar")
+            .extract_as(&archive, ArchiveType::Gzip, &dest)
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dest.join("tool.phar")).unwrap(),
+            "phar contents"
+        );
+    }
 }
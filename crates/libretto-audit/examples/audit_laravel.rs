@@ -1,7 +1,7 @@
 //! Example: Audit a Laravel project for security vulnerabilities.
 
 use libretto_audit::Auditor;
-use libretto_core::{PackageId, Version};
+use libretto_core::PackageId;
 use serde_json::Value;
 use std::path::Path;
 
@@ -26,7 +26,7 @@ async fn main() -> anyhow::Result<()> {
     let lock: Value = serde_json::from_str(&lock_content)?;
 
     // Extract packages
-    let mut packages: Vec<(PackageId, Version)> = Vec::new();
+    let mut packages: Vec<(PackageId, String)> = Vec::new();
 
     if let Some(pkgs) = lock.get("packages").and_then(|p| p.as_array()) {
         for pkg in pkgs {
@@ -35,11 +35,7 @@ async fn main() -> anyhow::Result<()> {
                 pkg.get("version").and_then(|v| v.as_str()),
             ) {
                 if let Some(package_id) = PackageId::parse(name) {
-                    // Clean version string (remove 'v' prefix if present)
-                    let version_str = version.trim_start_matches('v');
-                    if let Ok(ver) = Version::parse(version_str) {
-                        packages.push((package_id, ver));
-                    }
+                    packages.push((package_id, version.to_string()));
                 }
             }
         }
@@ -53,10 +49,7 @@ async fn main() -> anyhow::Result<()> {
                 pkg.get("version").and_then(|v| v.as_str()),
             ) {
                 if let Some(package_id) = PackageId::parse(name) {
-                    let version_str = version.trim_start_matches('v');
-                    if let Ok(ver) = Version::parse(version_str) {
-                        packages.push((package_id, ver));
-                    }
+                    packages.push((package_id, version.to_string()));
                 }
             }
         }
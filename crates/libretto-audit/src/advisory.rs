@@ -1,10 +1,11 @@
 //! Security advisory fetching and vulnerability matching.
 
+use crate::advisory_index::{AdvisoryIndex, IndexedAdvisory};
 use crate::{Severity, Vulnerability};
 use ahash::AHashMap;
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
-use libretto_core::{PackageId, Version};
+use libretto_core::PackageId;
 use libretto_resolver::version::{ComposerConstraint, ComposerVersion};
 use parking_lot::RwLock;
 use reqwest::Client;
@@ -66,6 +67,22 @@ struct RawAdvisory {
     severity: Option<String>,
 }
 
+impl From<&IndexedAdvisory> for RawAdvisory {
+    fn from(advisory: &IndexedAdvisory) -> Self {
+        Self {
+            advisory_id: advisory.advisory_id.clone(),
+            package_name: advisory.package_name.clone(),
+            affected_versions: advisory.affected_versions.clone(),
+            title: advisory.title.clone(),
+            link: advisory.link.clone(),
+            cve: advisory.cve.clone(),
+            reported_at: advisory.reported_at.clone(),
+            sources: Vec::new(),
+            severity: advisory.severity.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct SourceInfo {
     #[serde(rename = "name")]
@@ -92,11 +109,12 @@ impl VersionConstraint {
         Some(Self { constraint })
     }
 
-    /// Check if a `semver::Version` matches this constraint.
-    fn matches_semver(&self, version: &Version) -> bool {
-        // Convert semver::Version to ComposerVersion for matching
-        let composer_ver = ComposerVersion::new(version.major, version.minor, version.patch);
-        self.constraint.matches(&composer_ver)
+    /// Check if a version falls in this constraint's ranges.
+    ///
+    /// Stability is ignored so pre-release and `-dev` versions of affected
+    /// releases match; dev branches have no position in the ranges.
+    fn matches(&self, version: &ComposerVersion) -> bool {
+        !version.is_dev_branch && self.constraint.ranges().contains(version)
     }
 }
 
@@ -205,16 +223,18 @@ impl ProcessedVulnerability {
     }
 
     /// Check if version is affected.
+    ///
+    /// Accepts any Composer version, including four-part and `-dev` versions.
     #[must_use]
-    pub fn affects_version(&self, version: &Version) -> bool {
-        if self.constraints.is_empty() {
-            // If no constraints could be parsed, we cannot confirm this version
-            // is affected — return false to avoid false positives.
+    pub fn affects_version(&self, version: &str) -> bool {
+        // If the version or constraints cannot be parsed, we cannot confirm
+        // this version is affected — return false to avoid false positives.
+        let Some(version) = ComposerVersion::parse(version) else {
             return false;
-        }
+        };
 
         // Version is affected if it matches any constraint
-        self.constraints.iter().any(|c| c.matches_semver(version))
+        self.constraints.iter().any(|c| c.matches(&version))
     }
 
     /// Get underlying vulnerability.
@@ -232,6 +252,7 @@ pub struct AdvisoryDatabase {
     cache: Arc<DashMap<PackageId, Vec<ProcessedVulnerability>>>,
     cache_ttl: Duration,
     last_update: Arc<RwLock<Option<Instant>>>,
+    index: Option<Arc<AdvisoryIndex>>,
 }
 
 impl AdvisoryDatabase {
@@ -262,9 +283,36 @@ impl AdvisoryDatabase {
             cache: Arc::new(DashMap::new()),
             cache_ttl: Duration::from_secs(3600), // 1 hour
             last_update: Arc::new(RwLock::new(None)),
+            index: None,
         })
     }
 
+    /// Read advisories from an offline index instead of the API.
+    #[must_use]
+    pub fn with_index(mut self, index: AdvisoryIndex) -> Self {
+        self.index = Some(Arc::new(index));
+        self.clear_cache();
+        self
+    }
+
+    /// Check whether advisories come from an offline index.
+    #[must_use]
+    pub const fn is_offline(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// Advisories for `package` from the offline index.
+    fn indexed_advisories(
+        index: &AdvisoryIndex,
+        package: &PackageId,
+    ) -> Vec<ProcessedVulnerability> {
+        index
+            .advisories(&package.full_name())
+            .iter()
+            .filter_map(|a| ProcessedVulnerability::from_advisory(a.into()))
+            .collect()
+    }
+
     /// Set cache TTL.
     #[must_use]
     pub const fn with_cache_ttl(mut self, ttl: Duration) -> Self {
@@ -297,6 +345,12 @@ impl AdvisoryDatabase {
             return Ok(cached.clone());
         }
 
+        if let Some(index) = &self.index {
+            let vulnerabilities = Self::indexed_advisories(index, package);
+            self.cache.insert(package.clone(), vulnerabilities.clone());
+            return Ok(vulnerabilities);
+        }
+
         info!(package = %package, "fetching security advisories");
 
         let url = format!("{}?packages[]={}", self.base_url, package.full_name());
@@ -365,7 +419,7 @@ impl AdvisoryDatabase {
     pub async fn check_version(
         &self,
         package: &PackageId,
-        version: &Version,
+        version: &str,
     ) -> Result<Vec<Vulnerability>> {
         let advisories = self.fetch_advisories(package).await?;
 
@@ -404,6 +458,15 @@ impl AdvisoryDatabase {
             return Ok(());
         }
 
+        if let Some(index) = &self.index {
+            for pkg in packages {
+                self.cache
+                    .insert(pkg.clone(), Self::indexed_advisories(index, pkg));
+            }
+            *self.last_update.write() = Some(Instant::now());
+            return Ok(());
+        }
+
         info!(
             count = packages.len(),
             "fetching security advisories (bulk)"
@@ -478,7 +541,7 @@ impl AdvisoryDatabase {
     /// Returns error if fetch fails.
     pub async fn check_packages(
         &self,
-        packages: &[(PackageId, Version)],
+        packages: &[(PackageId, String)],
     ) -> Result<AHashMap<PackageId, Vec<Vulnerability>>> {
         // Fetch all advisories in one request
         let package_ids: Vec<PackageId> = packages.iter().map(|(id, _)| id.clone()).collect();
@@ -527,9 +590,33 @@ mod tests {
     #[test]
     fn test_version_matching() {
         let constraint = VersionConstraint::parse(">=1.0.0").unwrap();
-        assert!(constraint.matches_semver(&Version::parse("1.0.0").unwrap()));
-        assert!(constraint.matches_semver(&Version::parse("2.0.0").unwrap()));
-        assert!(!constraint.matches_semver(&Version::parse("0.9.0").unwrap()));
+        let version = |v| ComposerVersion::parse(v).unwrap();
+        assert!(constraint.matches(&version("1.0.0")));
+        assert!(constraint.matches(&version("2.0.0")));
+        assert!(!constraint.matches(&version("0.9.0")));
+    }
+
+    #[test]
+    fn test_non_semver_versions_are_matched() {
+        let advisory = ProcessedVulnerability::from_advisory(RawAdvisory {
+            advisory_id: "PKSA-test".to_string(),
+            package_name: "vendor/pkg".to_string(),
+            affected_versions: ">=1.0.0.0,<1.2.3.4".to_string(),
+            title: "test".to_string(),
+            link: None,
+            cve: None,
+            reported_at: None,
+            sources: Vec::new(),
+            severity: None,
+        })
+        .unwrap();
+
+        assert!(advisory.affects_version("1.2.3.3"));
+        assert!(advisory.affects_version("v1.1.0"));
+        assert!(advisory.affects_version("1.2.0-beta1"));
+        assert!(advisory.affects_version("1.1.0-dev"));
+        assert!(!advisory.affects_version("1.2.3.4"));
+        assert!(!advisory.affects_version("dev-main"));
     }
 
     #[test]
@@ -0,0 +1,677 @@
+//! Offline advisory sources and the compact advisory index.
+//!
+//! Air-gapped environments cannot reach the Packagist advisory API. Instead,
+//! advisories are imported from a local `FriendsOfPHP/security-advisories`
+//! checkout (YAML) or an OSV dump (directory or zip of JSON records) into an
+//! [`AdvisoryIndex`], stored in rkyv format for fast loading.
+
+use crate::advisory::{AdvisoryError, Result};
+use ahash::AHashMap;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+use tracing::{debug, info};
+
+/// Magic bytes for the index file (8 bytes to keep rkyv data aligned).
+const INDEX_MAGIC: &[u8; 8] = b"LADB\0\0\0\0";
+
+/// Format of an advisory source on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvisoryFormat {
+    /// `FriendsOfPHP/security-advisories` checkout with one YAML file per advisory.
+    FriendsOfPhp,
+    /// OSV records, as a directory or zip of JSON files.
+    Osv,
+    /// Libretto advisory index.
+    Index,
+}
+
+impl AdvisoryFormat {
+    /// Guess the format of `path`.
+    ///
+    /// Zip files and directories containing JSON are OSV; directories
+    /// containing YAML are `FriendsOfPHP`; other files must be an index.
+    #[must_use]
+    pub fn detect(path: &Path) -> Option<Self> {
+        if path.is_file() {
+            let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+            return match ext.as_str() {
+                "zip" | "json" => Some(Self::Osv),
+                _ => Some(Self::Index),
+            };
+        }
+
+        walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_entry(|e| !is_hidden(e.path()))
+            .filter_map(std::result::Result::ok)
+            .filter(|e| e.file_type().is_file())
+            .find_map(|e| match e.path().extension()?.to_str()? {
+                "yaml" | "yml" => Some(Self::FriendsOfPhp),
+                "json" => Some(Self::Osv),
+                _ => None,
+            })
+    }
+}
+
+impl fmt::Display for AdvisoryFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::FriendsOfPhp => "friendsofphp",
+            Self::Osv => "osv",
+            Self::Index => "index",
+        })
+    }
+}
+
+/// One advisory in the index, in Packagist API terms.
+#[derive(Archive, RkyvSerialize, RkyvDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct IndexedAdvisory {
+    /// Advisory identifier within its source.
+    pub advisory_id: String,
+    /// Lowercase package name.
+    pub package_name: String,
+    /// Composer constraint of affected versions.
+    pub affected_versions: String,
+    /// Title/summary.
+    pub title: String,
+    /// Advisory URL.
+    pub link: Option<String>,
+    /// CVE identifier.
+    pub cve: Option<String>,
+    /// Severity name (e.g. "high"), if known.
+    pub severity: Option<String>,
+    /// RFC 3339 report date.
+    pub reported_at: Option<String>,
+}
+
+#[derive(Archive, RkyvSerialize, RkyvDeserialize, Debug)]
+struct IndexData {
+    /// Version for format compatibility.
+    version: u32,
+    /// Where the advisories were imported from.
+    source: String,
+    /// Build time (seconds since epoch).
+    built_at: i64,
+    /// All advisories.
+    advisories: Vec<IndexedAdvisory>,
+}
+
+impl IndexData {
+    const CURRENT_VERSION: u32 = 1;
+}
+
+/// Advisories grouped by package, built from an offline source.
+#[derive(Debug, Clone)]
+pub struct AdvisoryIndex {
+    source: String,
+    built_at: DateTime<Utc>,
+    packages: AHashMap<String, Vec<IndexedAdvisory>>,
+}
+
+impl AdvisoryIndex {
+    /// Build an index from advisories, recording where they came from.
+    #[must_use]
+    pub fn new(source: impl Into<String>, advisories: Vec<IndexedAdvisory>) -> Self {
+        let mut packages: AHashMap<String, Vec<IndexedAdvisory>> = AHashMap::new();
+        for advisory in advisories {
+            packages
+                .entry(advisory.package_name.clone())
+                .or_default()
+                .push(advisory);
+        }
+        Self {
+            source: source.into(),
+            built_at: Utc::now(),
+            packages,
+        }
+    }
+
+    /// Import advisories from `path` in the given format.
+    ///
+    /// # Errors
+    /// Returns error if the source cannot be read or parsed.
+    pub fn import(path: &Path, format: AdvisoryFormat) -> Result<Self> {
+        let index = match format {
+            AdvisoryFormat::FriendsOfPhp => Self::from_friendsofphp(path)?,
+            AdvisoryFormat::Osv => Self::from_osv(path)?,
+            AdvisoryFormat::Index => Self::load(path)?,
+        };
+        info!(
+            source = %path.display(),
+            %format,
+            packages = index.package_count(),
+            advisories = index.advisory_count(),
+            "imported advisories"
+        );
+        Ok(index)
+    }
+
+    /// Import a `FriendsOfPHP/security-advisories` checkout.
+    ///
+    /// # Errors
+    /// Returns error if the directory cannot be read or a file is invalid.
+    pub fn from_friendsofphp(dir: &Path) -> Result<Self> {
+        let mut advisories = Vec::new();
+        for entry in walkdir::WalkDir::new(dir)
+            .into_iter()
+            .filter_entry(|e| !is_hidden(e.path()))
+        {
+            let entry = entry.map_err(|e| AdvisoryError::Database(e.to_string()))?;
+            let path = entry.path();
+            if !entry.file_type().is_file()
+                || !matches!(
+                    path.extension().and_then(|e| e.to_str()),
+                    Some("yaml" | "yml")
+                )
+            {
+                continue;
+            }
+
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| AdvisoryError::Database(format!("{}: {e}", path.display())))?;
+            let id = path
+                .strip_prefix(dir)
+                .unwrap_or(path)
+                .with_extension("")
+                .to_string_lossy()
+                .replace('\\', "/");
+            match parse_friendsofphp(&id, &content) {
+                Ok(Some(advisory)) => advisories.push(advisory),
+                Ok(None) => debug!(file = %path.display(), "skipping non-composer advisory"),
+                Err(e) => {
+                    return Err(AdvisoryError::Parse(format!("{}: {e}", path.display())));
+                }
+            }
+        }
+        Ok(Self::new(dir.display().to_string(), advisories))
+    }
+
+    /// Import OSV records from a directory or zip file.
+    ///
+    /// Only `Packagist` ecosystem entries are kept; withdrawn records are skipped.
+    ///
+    /// # Errors
+    /// Returns error if the source cannot be read or a record is invalid.
+    pub fn from_osv(path: &Path) -> Result<Self> {
+        let io_err =
+            |e: std::io::Error| AdvisoryError::Database(format!("{}: {e}", path.display()));
+
+        let mut advisories = Vec::new();
+        if path.is_dir() {
+            for entry in walkdir::WalkDir::new(path)
+                .into_iter()
+                .filter_entry(|e| !is_hidden(e.path()))
+            {
+                let entry = entry.map_err(|e| AdvisoryError::Database(e.to_string()))?;
+                if entry.file_type().is_file()
+                    && entry.path().extension().and_then(|e| e.to_str()) == Some("json")
+                {
+                    let content = std::fs::read(entry.path()).map_err(io_err)?;
+                    advisories.extend(parse_osv(&content).map_err(|e| {
+                        AdvisoryError::Parse(format!("{}: {e}", entry.path().display()))
+                    })?);
+                }
+            }
+        } else if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            let content = std::fs::read(path).map_err(io_err)?;
+            advisories = parse_osv(&content).map_err(AdvisoryError::Parse)?;
+        } else {
+            let file = std::fs::File::open(path).map_err(io_err)?;
+            advisories = read_osv_zip(file)?;
+        }
+        Ok(Self::new(path.display().to_string(), advisories))
+    }
+
+    /// Load an index written by [`save`](Self::save).
+    ///
+    /// # Errors
+    /// Returns error if the file is missing, corrupt or from another format version.
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read(path)
+            .map_err(|e| AdvisoryError::Database(format!("{}: {e}", path.display())))?;
+        if data.len() <= INDEX_MAGIC.len() || &data[..INDEX_MAGIC.len()] != INDEX_MAGIC {
+            return Err(AdvisoryError::Database(format!(
+                "{} is not an advisory index",
+                path.display()
+            )));
+        }
+
+        let index_data =
+            rkyv::from_bytes::<IndexData, rkyv::rancor::Error>(&data[INDEX_MAGIC.len()..])
+                .map_err(|e| AdvisoryError::Database(format!("corrupt advisory index: {e}")))?;
+        if index_data.version != IndexData::CURRENT_VERSION {
+            return Err(AdvisoryError::Database(format!(
+                "advisory index version {} is not supported, re-import it",
+                index_data.version
+            )));
+        }
+
+        let mut index = Self::new(index_data.source, index_data.advisories);
+        index.built_at = DateTime::from_timestamp(index_data.built_at, 0).unwrap_or_default();
+        Ok(index)
+    }
+
+    /// Write the index to `path` atomically.
+    ///
+    /// # Errors
+    /// Returns error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut advisories: Vec<IndexedAdvisory> =
+            self.packages.values().flatten().cloned().collect();
+        advisories.sort_by(|a, b| {
+            (&a.package_name, &a.advisory_id).cmp(&(&b.package_name, &b.advisory_id))
+        });
+        let index_data = IndexData {
+            version: IndexData::CURRENT_VERSION,
+            source: self.source.clone(),
+            built_at: self.built_at.timestamp(),
+            advisories,
+        };
+        let serialized = rkyv::to_bytes::<rkyv::rancor::Error>(&index_data)
+            .map_err(|e| AdvisoryError::Database(format!("rkyv serialization failed: {e}")))?;
+
+        let io_err =
+            |e: std::io::Error| AdvisoryError::Database(format!("{}: {e}", path.display()));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err)?;
+        }
+        let temp_path = path.with_extension("tmp");
+        let mut file = std::fs::File::create(&temp_path).map_err(io_err)?;
+        file.write_all(INDEX_MAGIC).map_err(io_err)?;
+        file.write_all(&serialized).map_err(io_err)?;
+        file.sync_all().map_err(io_err)?;
+        std::fs::rename(&temp_path, path).map_err(io_err)?;
+        Ok(())
+    }
+
+    /// Advisories for `package`.
+    #[must_use]
+    pub fn advisories(&self, package: &str) -> &[IndexedAdvisory] {
+        self.packages
+            .get(&package.to_ascii_lowercase())
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Where the advisories were imported from.
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// When the index was built.
+    #[must_use]
+    pub const fn built_at(&self) -> DateTime<Utc> {
+        self.built_at
+    }
+
+    /// Number of packages with advisories.
+    #[must_use]
+    pub fn package_count(&self) -> usize {
+        self.packages.len()
+    }
+
+    /// Total number of advisories.
+    #[must_use]
+    pub fn advisory_count(&self) -> usize {
+        self.packages.values().map(Vec::len).sum()
+    }
+}
+
+/// Read OSV records from a zip dump such as `Packagist/all.zip`.
+///
+/// # Errors
+/// Returns error if the archive or a record is invalid.
+pub fn read_osv_zip(reader: impl Read + Seek) -> Result<Vec<IndexedAdvisory>> {
+    let mut archive =
+        zip::ZipArchive::new(reader).map_err(|e| AdvisoryError::Database(e.to_string()))?;
+    let mut advisories = Vec::new();
+    let mut content = Vec::new();
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| AdvisoryError::Database(e.to_string()))?;
+        let is_json = Path::new(file.name())
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        if !file.is_file() || !is_json {
+            continue;
+        }
+        content.clear();
+        file.read_to_end(&mut content)
+            .map_err(|e| AdvisoryError::Database(format!("{}: {e}", file.name())))?;
+        advisories.extend(
+            parse_osv(&content)
+                .map_err(|e| AdvisoryError::Parse(format!("{}: {e}", file.name())))?,
+        );
+    }
+    Ok(advisories)
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.starts_with('.') && n.len() > 1)
+}
+
+// ============================================================================
+// FriendsOfPHP
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct FriendsOfPhpAdvisory {
+    title: String,
+    #[serde(default)]
+    link: Option<String>,
+    #[serde(default)]
+    cve: Option<String>,
+    #[serde(default)]
+    branches: BTreeMap<String, FriendsOfPhpBranch>,
+    reference: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct FriendsOfPhpBranch {
+    #[serde(default)]
+    time: Option<String>,
+    #[serde(default)]
+    versions: Vec<String>,
+}
+
+/// Parse one advisory file; `None` if it does not reference a Composer package.
+fn parse_friendsofphp(
+    id: &str,
+    content: &str,
+) -> std::result::Result<Option<IndexedAdvisory>, String> {
+    let raw: FriendsOfPhpAdvisory = serde_yaml::from_str(content).map_err(|e| e.to_string())?;
+    let Some(package) = raw.reference.strip_prefix("composer://") else {
+        return Ok(None);
+    };
+
+    // Each branch is an AND of bounds; branches are alternatives
+    let affected_versions = raw
+        .branches
+        .values()
+        .filter(|b| !b.versions.is_empty())
+        .map(|b| b.versions.join(","))
+        .collect::<Vec<_>>()
+        .join("|");
+
+    let reported_at = raw
+        .branches
+        .values()
+        .filter_map(|b| NaiveDateTime::parse_from_str(b.time.as_deref()?, "%Y-%m-%d %H:%M:%S").ok())
+        .min()
+        .map(|t| t.and_utc().to_rfc3339());
+
+    Ok(Some(IndexedAdvisory {
+        advisory_id: id.to_string(),
+        package_name: package.trim().to_ascii_lowercase(),
+        affected_versions,
+        title: raw.title,
+        link: raw.link,
+        cve: raw.cve.filter(|c| !c.trim().is_empty() && c != "~"),
+        severity: None,
+        reported_at,
+    }))
+}
+
+// ============================================================================
+// OSV
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct OsvRecord {
+    id: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    details: Option<String>,
+    #[serde(default)]
+    published: Option<String>,
+    #[serde(default)]
+    withdrawn: Option<String>,
+    #[serde(default)]
+    affected: Vec<OsvAffected>,
+    #[serde(default)]
+    references: Vec<OsvReference>,
+    #[serde(default)]
+    database_specific: Option<OsvDatabaseSpecific>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvAffected {
+    package: OsvPackage,
+    #[serde(default)]
+    ranges: Vec<OsvRange>,
+    #[serde(default)]
+    versions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvPackage {
+    ecosystem: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvRange {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    events: Vec<OsvEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvEvent {
+    #[serde(default)]
+    introduced: Option<String>,
+    #[serde(default)]
+    fixed: Option<String>,
+    #[serde(default)]
+    last_affected: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvReference {
+    #[serde(rename = "type")]
+    kind: String,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvDatabaseSpecific {
+    #[serde(default)]
+    severity: Option<String>,
+}
+
+/// Parse one OSV record into an advisory per affected Packagist package.
+fn parse_osv(content: &[u8]) -> std::result::Result<Vec<IndexedAdvisory>, String> {
+    let record: OsvRecord = sonic_rs::from_slice(content).map_err(|e| e.to_string())?;
+    if record.withdrawn.is_some() {
+        return Ok(Vec::new());
+    }
+
+    let title = record
+        .summary
+        .filter(|s| !s.is_empty())
+        .or_else(|| {
+            record
+                .details
+                .as_deref()
+                .and_then(|d| d.lines().next())
+                .map(ToString::to_string)
+        })
+        .unwrap_or_else(|| record.id.clone());
+    let link = record
+        .references
+        .iter()
+        .find(|r| r.kind == "ADVISORY")
+        .or_else(|| record.references.first())
+        .map(|r| r.url.clone());
+    let cve = record
+        .aliases
+        .iter()
+        .find(|a| a.starts_with("CVE-"))
+        .cloned();
+    let severity = record
+        .database_specific
+        .and_then(|d| d.severity)
+        .map(|s| s.to_ascii_lowercase());
+
+    Ok(record
+        .affected
+        .iter()
+        .filter(|a| a.package.ecosystem == "Packagist")
+        .filter_map(|affected| {
+            let affected_versions = osv_constraint(affected)?;
+            Some(IndexedAdvisory {
+                advisory_id: record.id.clone(),
+                package_name: affected.package.name.to_ascii_lowercase(),
+                affected_versions,
+                title: title.clone(),
+                link: link.clone(),
+                cve: cve.clone(),
+                severity: severity.clone(),
+                reported_at: record.published.clone(),
+            })
+        })
+        .collect())
+}
+
+/// Translate OSV ranges (or explicit versions) into a Composer constraint.
+fn osv_constraint(affected: &OsvAffected) -> Option<String> {
+    let mut alternatives = Vec::new();
+    for range in affected
+        .ranges
+        .iter()
+        .filter(|r| matches!(r.kind.as_str(), "ECOSYSTEM" | "SEMVER"))
+    {
+        let mut introduced: Option<&str> = None;
+        for event in &range.events {
+            if let Some(version) = &event.introduced {
+                introduced = Some(version);
+            } else if let Some(version) = &event.fixed {
+                alternatives.push(osv_interval(introduced.take(), &format!("<{version}")));
+            } else if let Some(version) = &event.last_affected {
+                alternatives.push(osv_interval(introduced.take(), &format!("<={version}")));
+            }
+        }
+        if let Some(version) = introduced {
+            alternatives.push(match version {
+                "0" => "*".to_string(),
+                _ => format!(">={version}"),
+            });
+        }
+    }
+
+    if alternatives.is_empty() {
+        alternatives.clone_from(&affected.versions);
+    }
+    (!alternatives.is_empty()).then(|| alternatives.join("|"))
+}
+
+fn osv_interval(introduced: Option<&str>, upper: &str) -> String {
+    match introduced {
+        Some(version) if version != "0" => format!(">={version},{upper}"),
+        _ => upper.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRIENDSOFPHP: &str = r"
+title:     'CVE-2019-10909: Escape validation messages in the PHP templating engine'
+link:      https://symfony.com/cve-2019-10909
+cve:       CVE-2019-10909
+branches:
+    3.4.x:
+        time:     2019-04-16 22:13:00
+        versions: ['>=3.4.0', '<3.4.26']
+    4.2.x:
+        time:     2019-04-16 22:13:00
+        versions: ['>=4.2.0', '<4.2.7']
+reference: composer://symfony/framework-bundle
+";
+
+    const OSV: &str = r#"{
+        "id": "GHSA-xxxx-yyyy-zzzz",
+        "aliases": ["CVE-2024-0001"],
+        "summary": "Remote code execution",
+        "published": "2024-01-02T03:04:05Z",
+        "affected": [
+            {
+                "package": {"ecosystem": "Packagist", "name": "Vendor/Pkg"},
+                "ranges": [{"type": "ECOSYSTEM", "events": [
+                    {"introduced": "0"}, {"fixed": "1.2.3"},
+                    {"introduced": "2.0.0"}, {"fixed": "2.0.1"}
+                ]}]
+            },
+            {"package": {"ecosystem": "npm", "name": "pkg"}, "versions": ["1.0.0"]}
+        ],
+        "references": [
+            {"type": "WEB", "url": "https://example.com"},
+            {"type": "ADVISORY", "url": "https://github.com/advisories/GHSA-xxxx-yyyy-zzzz"}
+        ],
+        "database_specific": {"severity": "HIGH"}
+    }"#;
+
+    #[test]
+    fn test_friendsofphp_branches_become_alternatives() {
+        let advisory = parse_friendsofphp("symfony/framework-bundle/CVE-2019-10909", FRIENDSOFPHP)
+            .unwrap()
+            .unwrap();
+        assert_eq!(advisory.package_name, "symfony/framework-bundle");
+        assert_eq!(advisory.affected_versions, ">=3.4.0,<3.4.26|>=4.2.0,<4.2.7");
+        assert_eq!(advisory.cve.as_deref(), Some("CVE-2019-10909"));
+        assert_eq!(
+            advisory.reported_at.as_deref(),
+            Some("2019-04-16T22:13:00+00:00")
+        );
+    }
+
+    #[test]
+    fn test_osv_ranges_become_constraint() {
+        let advisories = parse_osv(OSV.as_bytes()).unwrap();
+        assert_eq!(advisories.len(), 1);
+        let advisory = &advisories[0];
+        assert_eq!(advisory.package_name, "vendor/pkg");
+        assert_eq!(advisory.affected_versions, "<1.2.3|>=2.0.0,<2.0.1");
+        assert_eq!(advisory.cve.as_deref(), Some("CVE-2024-0001"));
+        assert_eq!(advisory.severity.as_deref(), Some("high"));
+        assert_eq!(
+            advisory.link.as_deref(),
+            Some("https://github.com/advisories/GHSA-xxxx-yyyy-zzzz")
+        );
+    }
+
+    #[test]
+    fn test_index_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("osv");
+        std::fs::create_dir(&source).unwrap();
+        std::fs::write(source.join("GHSA-xxxx-yyyy-zzzz.json"), OSV).unwrap();
+        assert_eq!(AdvisoryFormat::detect(&source), Some(AdvisoryFormat::Osv));
+
+        let index = AdvisoryIndex::import(&source, AdvisoryFormat::Osv).unwrap();
+        let path = dir.path().join("advisories.idx");
+        index.save(&path).unwrap();
+
+        let loaded = AdvisoryIndex::load(&path).unwrap();
+        assert_eq!(loaded.advisory_count(), 1);
+        assert_eq!(loaded.source(), index.source());
+        assert_eq!(loaded.built_at().timestamp(), index.built_at().timestamp());
+        assert_eq!(
+            loaded.advisories("vendor/pkg"),
+            index.advisories("Vendor/Pkg")
+        );
+    }
+}
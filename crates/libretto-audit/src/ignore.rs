@@ -0,0 +1,220 @@
+//! Accepted risks: advisories ignored with a reason and an expiry date.
+//!
+//! Rules come from `config.audit.ignore` and match an advisory ID, CVE or a
+//! whole package. A rule applies to audit reports, to security blocking
+//! during resolution, or both. Expired rules stop matching so accepted
+//! risks resurface once their acceptance runs out.
+
+use crate::{Severity, Vulnerability};
+use chrono::NaiveDate;
+
+/// Where an ignore rule applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IgnoreScope {
+    /// Only in audit reports.
+    Audit,
+    /// Only when blocking insecure versions during resolution.
+    Block,
+    /// Everywhere.
+    #[default]
+    All,
+}
+
+impl IgnoreScope {
+    /// Check whether the rule applies to audit reports.
+    #[must_use]
+    pub const fn applies_to_audit(self) -> bool {
+        matches!(self, Self::Audit | Self::All)
+    }
+
+    /// Check whether the rule applies to resolution blocking.
+    #[must_use]
+    pub const fn applies_to_block(self) -> bool {
+        matches!(self, Self::Block | Self::All)
+    }
+}
+
+/// An accepted advisory or package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreRule {
+    /// Advisory ID, CVE or package name.
+    pub target: String,
+    /// Why the risk is accepted.
+    pub reason: Option<String>,
+    /// Last day the rule applies.
+    pub until: Option<NaiveDate>,
+    /// Where the rule applies.
+    pub scope: IgnoreScope,
+}
+
+impl IgnoreRule {
+    /// Ignore `target` everywhere, indefinitely.
+    #[must_use]
+    pub fn new(target: impl Into<String>) -> Self {
+        Self {
+            target: target.into(),
+            reason: None,
+            until: None,
+            scope: IgnoreScope::All,
+        }
+    }
+
+    /// Check whether the rule has expired by `today`.
+    #[must_use]
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.until.is_some_and(|until| today > until)
+    }
+
+    /// Check whether the rule covers `advisory_id` of `package`.
+    #[must_use]
+    pub fn matches(&self, package: &str, advisory_id: &str) -> bool {
+        self.target.eq_ignore_ascii_case(advisory_id) || self.target.eq_ignore_ascii_case(package)
+    }
+
+    /// Description used in reports.
+    fn describe(&self) -> String {
+        let mut text = self
+            .reason
+            .clone()
+            .unwrap_or_else(|| format!("ignored by rule for {}", self.target));
+        if let Some(until) = self.until {
+            text.push_str(&format!(" (until {until})"));
+        }
+        text
+    }
+}
+
+/// Ignore rules plus severities that are ignored outright.
+#[derive(Debug, Clone, Default)]
+pub struct IgnorePolicy {
+    rules: Vec<IgnoreRule>,
+    severities: Vec<Severity>,
+}
+
+impl IgnorePolicy {
+    /// Create a policy from rules.
+    #[must_use]
+    pub const fn new(rules: Vec<IgnoreRule>) -> Self {
+        Self {
+            rules,
+            severities: Vec::new(),
+        }
+    }
+
+    /// Also ignore every finding of the given severities in audit reports.
+    #[must_use]
+    pub fn with_ignored_severities(mut self, severities: Vec<Severity>) -> Self {
+        self.severities = severities;
+        self
+    }
+
+    /// Configured rules.
+    #[must_use]
+    pub fn rules(&self) -> &[IgnoreRule] {
+        &self.rules
+    }
+
+    /// Rules whose `until` date has passed.
+    pub fn expired(&self, today: NaiveDate) -> impl Iterator<Item = &IgnoreRule> {
+        self.rules.iter().filter(move |r| r.is_expired(today))
+    }
+
+    /// Why `vulnerability` is left out of audit results, if it is.
+    #[must_use]
+    pub fn audit_reason(&self, vulnerability: &Vulnerability, today: NaiveDate) -> Option<String> {
+        let package = vulnerability.package.full_name();
+        if let Some(rule) = self
+            .active(today)
+            .find(|r| r.scope.applies_to_audit() && r.matches(&package, &vulnerability.advisory_id))
+        {
+            return Some(rule.describe());
+        }
+        self.severities
+            .contains(&vulnerability.severity)
+            .then(|| format!("{} severity ignored", vulnerability.severity))
+    }
+
+    /// Check whether `advisory_id` of `package` may still be installed.
+    #[must_use]
+    pub fn allows_install(&self, package: &str, advisory_id: &str, today: NaiveDate) -> bool {
+        self.active(today)
+            .any(|r| r.scope.applies_to_block() && r.matches(package, advisory_id))
+    }
+
+    fn active(&self, today: NaiveDate) -> impl Iterator<Item = &IgnoreRule> {
+        self.rules.iter().filter(move |r| !r.is_expired(today))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libretto_core::PackageId;
+
+    fn vulnerability(advisory_id: &str, severity: Severity) -> Vulnerability {
+        Vulnerability {
+            advisory_id: advisory_id.to_string(),
+            package: PackageId::parse("vendor/pkg").unwrap(),
+            affected_versions: "<2.0".to_string(),
+            fixed_version: None,
+            severity,
+            cvss_score: None,
+            title: "test".to_string(),
+            description: String::new(),
+            references: Vec::new(),
+            published_at: None,
+        }
+    }
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_rule_expires_after_until() {
+        let policy = IgnorePolicy::new(vec![IgnoreRule {
+            reason: Some("not reachable".to_string()),
+            until: Some(day("2026-12-31")),
+            ..IgnoreRule::new("CVE-2024-1234")
+        }]);
+        let vuln = vulnerability("CVE-2024-1234", Severity::High);
+
+        assert_eq!(
+            policy.audit_reason(&vuln, day("2026-12-31")).as_deref(),
+            Some("not reachable (until 2026-12-31)")
+        );
+        assert_eq!(policy.audit_reason(&vuln, day("2027-01-01")), None);
+        assert_eq!(policy.expired(day("2027-01-01")).count(), 1);
+    }
+
+    #[test]
+    fn test_scope_and_package_rules() {
+        let policy = IgnorePolicy::new(vec![IgnoreRule {
+            scope: IgnoreScope::Block,
+            ..IgnoreRule::new("Vendor/Pkg")
+        }]);
+        let vuln = vulnerability("CVE-2024-1", Severity::High);
+        let today = day("2026-01-01");
+
+        assert!(policy.allows_install("vendor/pkg", "CVE-2024-1", today));
+        assert!(!policy.allows_install("other/pkg", "CVE-2024-1", today));
+        assert_eq!(policy.audit_reason(&vuln, today), None);
+    }
+
+    #[test]
+    fn test_ignored_severities() {
+        let policy = IgnorePolicy::default().with_ignored_severities(vec![Severity::Low]);
+        let today = day("2026-01-01");
+
+        assert_eq!(
+            policy
+                .audit_reason(&vulnerability("A", Severity::Low), today)
+                .as_deref(),
+            Some("LOW severity ignored")
+        );
+        assert_eq!(
+            policy.audit_reason(&vulnerability("B", Severity::Medium), today),
+            None
+        );
+    }
+}
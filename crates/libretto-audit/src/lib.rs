@@ -3,8 +3,10 @@
 //! This crate provides comprehensive security features:
 //! - Package integrity verification (SHA-256, SHA-1, BLAKE3)
 //! - Signature verification (GPG/PGP, Ed25519)
-//! - Security advisory checking
+//! - Security advisory checking, online or from an offline index
 //! - Vulnerability analysis
+//! - SARIF, `JUnit`, JSON and GitHub annotation reports
+//! - `CycloneDX` and SPDX software bills of materials
 //! - Platform requirements validation
 //! - Secure credential management
 //! - Audit logging
@@ -21,15 +23,20 @@
 #![allow(clippy::module_name_repetitions)]
 
 mod advisory;
+mod advisory_index;
 mod audit_log;
 mod credentials;
+mod ignore;
 mod integrity;
+mod license;
 mod permissions;
 mod platform;
+mod report;
+mod sbom;
 mod secure;
 mod signature;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use libretto_core::{Error as CoreError, PackageId, Result as CoreResult, Version};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -41,12 +48,15 @@ use url::Url;
 
 // Re-exports
 pub use advisory::{AdvisoryDatabase, AdvisoryError};
+pub use advisory_index::{AdvisoryFormat, AdvisoryIndex, IndexedAdvisory, read_osv_zip};
 pub use audit_log::{AuditEntry, AuditLogError, AuditLogger, Operation};
 pub use credentials::{Credential, CredentialError, CredentialManager, CredentialType};
+pub use ignore::{IgnorePolicy, IgnoreRule, IgnoreScope};
 pub use integrity::{
     Hash, HashAlgorithm, IntegrityError, IntegrityVerifier, hash_file, hash_file_all,
     verify_blake3, verify_file, verify_sha1, verify_sha256,
 };
+pub use license::{is_copyleft, is_permissive, license_expression, normalize_license};
 #[cfg(unix)]
 pub use permissions::{
     PermissionError, PermissionMode, apply_umask, check_secure_permissions, effective_permissions,
@@ -62,6 +72,8 @@ pub use permissions::{
 pub use platform::{
     PhpPlatform, PlatformError, PlatformValidator, Requirement, RequirementType, ValidationMode,
 };
+pub use report::{LockLocation, ReportFormat};
+pub use sbom::{RootComponent, Sbom, SbomFormat, purl};
 pub use secure::{
     SecureClientBuilder, SecurityError, create_secure_temp, create_secure_temp_dir, mask_sensitive,
     sanitize_path, validate_package_name, validate_url,
@@ -131,6 +143,21 @@ impl std::fmt::Display for Severity {
     }
 }
 
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "critical" => Ok(Self::Critical),
+            "high" => Ok(Self::High),
+            "medium" | "moderate" => Ok(Self::Medium),
+            "low" => Ok(Self::Low),
+            "unknown" => Ok(Self::Unknown),
+            other => Err(format!("unknown severity: {other}")),
+        }
+    }
+}
+
 /// Security vulnerability.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Vulnerability {
@@ -173,8 +200,8 @@ impl Vulnerability {
 pub struct PackageAudit {
     /// Package identifier.
     pub package: PackageId,
-    /// Package version.
-    pub version: Version,
+    /// Package version, as locked.
+    pub version: String,
     /// Found vulnerabilities.
     pub vulnerabilities: Vec<Vulnerability>,
 }
@@ -197,11 +224,24 @@ impl PackageAudit {
     }
 }
 
+/// A finding left out of the report by an ignore rule.
+#[derive(Debug, Clone)]
+pub struct IgnoredVulnerability {
+    /// Package version, as locked.
+    pub version: String,
+    /// The ignored vulnerability.
+    pub vulnerability: Vulnerability,
+    /// Why it is ignored.
+    pub reason: String,
+}
+
 /// Full audit report.
 #[derive(Debug, Clone)]
 pub struct AuditReport {
     /// Audited packages.
     pub packages: Vec<PackageAudit>,
+    /// Findings removed by [`apply_ignores`](Self::apply_ignores).
+    pub ignored: Vec<IgnoredVulnerability>,
     /// When audit was performed.
     pub audited_at: DateTime<Utc>,
     /// Advisory database version.
@@ -209,6 +249,29 @@ pub struct AuditReport {
 }
 
 impl AuditReport {
+    /// Move findings covered by `policy` on `today` to [`ignored`](Self::ignored).
+    ///
+    /// Counts and [`passes`](Self::passes) then only consider the remaining findings.
+    pub fn apply_ignores(&mut self, policy: &IgnorePolicy, today: NaiveDate) {
+        for pkg in &mut self.packages {
+            let (ignored, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut pkg.vulnerabilities)
+                .into_iter()
+                .map(|v| (policy.audit_reason(&v, today), v))
+                .partition(|(reason, _)| reason.is_some());
+            pkg.vulnerabilities = kept.into_iter().map(|(_, v)| v).collect();
+            self.ignored
+                .extend(
+                    ignored
+                        .into_iter()
+                        .map(|(reason, vulnerability)| IgnoredVulnerability {
+                            version: pkg.version.clone(),
+                            vulnerability,
+                            reason: reason.unwrap_or_default(),
+                        }),
+                );
+        }
+    }
+
     /// Get total vulnerability count.
     #[must_use]
     pub fn vulnerability_count(&self) -> usize {
@@ -291,6 +354,13 @@ impl Auditor {
         })
     }
 
+    /// Use `advisory_db` for advisory lookups, e.g. one backed by an offline index.
+    #[must_use]
+    pub fn with_advisory_database(mut self, advisory_db: AdvisoryDatabase) -> Self {
+        self.advisory_db = advisory_db;
+        self
+    }
+
     /// Enable audit logging to file.
     #[must_use]
     pub fn with_audit_log(mut self, path: impl Into<PathBuf>) -> Self {
@@ -326,7 +396,7 @@ impl Auditor {
     ///
     /// # Errors
     /// Returns error if audit fails.
-    pub async fn audit(&self, packages: &[(PackageId, Version)]) -> CoreResult<AuditReport> {
+    pub async fn audit(&self, packages: &[(PackageId, String)]) -> CoreResult<AuditReport> {
         info!(packages = packages.len(), "starting security audit");
 
         // Fetch all advisories in a single bulk API call
@@ -347,6 +417,7 @@ impl Auditor {
 
         let report = AuditReport {
             packages: audits,
+            ignored: Vec::new(),
             audited_at: Utc::now(),
             database_version: None,
         };
@@ -476,6 +547,7 @@ mod tests {
     fn test_empty_audit_report() {
         let report = AuditReport {
             packages: Vec::new(),
+            ignored: Vec::new(),
             audited_at: Utc::now(),
             database_version: None,
         };
@@ -484,6 +556,43 @@ mod tests {
         assert!(report.passes());
     }
 
+    #[test]
+    fn test_ignored_findings_do_not_fail_report() {
+        let vulnerability = Vulnerability {
+            advisory_id: "CVE-2024-1234".to_string(),
+            package: PackageId::parse("vendor/pkg").unwrap(),
+            affected_versions: "<2.0".to_string(),
+            fixed_version: None,
+            severity: Severity::Critical,
+            cvss_score: None,
+            title: "test".to_string(),
+            description: String::new(),
+            references: Vec::new(),
+            published_at: None,
+        };
+        let mut report = AuditReport {
+            packages: vec![PackageAudit {
+                package: vulnerability.package.clone(),
+                version: "1.0.0".to_string(),
+                vulnerabilities: vec![vulnerability],
+            }],
+            ignored: Vec::new(),
+            audited_at: Utc::now(),
+            database_version: None,
+        };
+        assert!(!report.passes());
+
+        let policy = IgnorePolicy::new(vec![IgnoreRule {
+            reason: Some("not reachable".to_string()),
+            ..IgnoreRule::new("CVE-2024-1234")
+        }]);
+        report.apply_ignores(&policy, Utc::now().date_naive());
+        assert!(report.passes());
+        assert_eq!(report.vulnerability_count(), 0);
+        assert_eq!(report.ignored.len(), 1);
+        assert_eq!(report.ignored[0].reason, "not reachable");
+    }
+
     #[test]
     fn test_auditor_creation() {
         let auditor = Auditor::new();
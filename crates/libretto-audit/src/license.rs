@@ -0,0 +1,176 @@
+//! License identifier normalization and classification.
+//!
+//! Composer's `license` field holds SPDX identifiers, deprecated SPDX forms
+//! such as `GPL-2.0+`, loose spellings such as `mit`, or free-form names such
+//! as `proprietary`. Multiple entries are alternatives.
+
+/// SPDX identifiers commonly found on Packagist, in canonical case.
+const KNOWN_LICENSES: &[&str] = &[
+    "0BSD",
+    "AFL-3.0",
+    "Apache-2.0",
+    "Artistic-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "BSD-4-Clause",
+    "BSL-1.0",
+    "CC-BY-3.0",
+    "CC-BY-4.0",
+    "CC-BY-SA-4.0",
+    "CC0-1.0",
+    "CDDL-1.0",
+    "EPL-1.0",
+    "EPL-2.0",
+    "EUPL-1.1",
+    "EUPL-1.2",
+    "ISC",
+    "MIT",
+    "MIT-0",
+    "MPL-1.1",
+    "MPL-2.0",
+    "OSL-3.0",
+    "PHP-3.0",
+    "PHP-3.01",
+    "PostgreSQL",
+    "Unlicense",
+    "WTFPL",
+    "Zlib",
+];
+
+/// GNU license families whose bare versions are deprecated in SPDX.
+const GNU_FAMILIES: &[&str] = &["AGPL", "GPL", "LGPL", "GFDL"];
+
+/// Normalize a single license identifier to its canonical SPDX form.
+///
+/// Returns `None` for identifiers that are not SPDX licenses.
+#[must_use]
+pub fn normalize_license(license: &str) -> Option<String> {
+    let license = license.trim();
+    if let Some(known) = KNOWN_LICENSES
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(license))
+    {
+        return Some((*known).to_string());
+    }
+
+    let upper = license.to_ascii_uppercase();
+    let (base, suffix) = if let Some(base) = upper.strip_suffix('+') {
+        (base, "-or-later")
+    } else if let Some(base) = upper.strip_suffix("-OR-LATER") {
+        (base, "-or-later")
+    } else if let Some(base) = upper.strip_suffix("-ONLY") {
+        (base, "-only")
+    } else {
+        (upper.as_str(), "-only")
+    };
+
+    let (family, version) = base.split_once('-')?;
+    let valid_version = matches!(
+        version,
+        "1.0" | "2.0" | "2.1" | "3.0" | "1.1" | "1.2" | "1.3"
+    );
+    if GNU_FAMILIES.contains(&family) && valid_version {
+        return Some(format!("{family}-{version}{suffix}"));
+    }
+    None
+}
+
+/// Build an SPDX license expression from Composer `license` entries.
+///
+/// Entries are alternatives and are joined with `OR`; each entry may itself
+/// be a parenthesized `or`/`and` expression. Returns `None` if the list is
+/// empty or any identifier is not an SPDX license.
+#[must_use]
+pub fn license_expression(licenses: &[String]) -> Option<String> {
+    let parts = licenses
+        .iter()
+        .map(|license| normalize_expression(license))
+        .collect::<Option<Vec<_>>>()?;
+    match parts.len() {
+        0 => None,
+        1 => parts.into_iter().next(),
+        _ => Some(
+            parts
+                .iter()
+                .map(|part| {
+                    if part.contains(' ') {
+                        format!("({part})")
+                    } else {
+                        part.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" OR "),
+        ),
+    }
+}
+
+fn normalize_expression(expression: &str) -> Option<String> {
+    let inner = expression
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(')');
+    let mut normalized = Vec::new();
+    for token in inner.split_whitespace() {
+        if token.eq_ignore_ascii_case("or") {
+            normalized.push("OR".to_string());
+        } else if token.eq_ignore_ascii_case("and") {
+            normalized.push("AND".to_string());
+        } else {
+            normalized.push(normalize_license(token)?);
+        }
+    }
+    (!normalized.is_empty()).then(|| normalized.join(" "))
+}
+
+/// Whether a license is permissive (MIT, BSD, Apache and similar).
+#[must_use]
+pub fn is_permissive(license: &str) -> bool {
+    let lower = license.to_lowercase();
+    lower.contains("mit")
+        || lower.contains("bsd")
+        || lower.contains("apache")
+        || lower.contains("isc")
+        || lower.contains("unlicense")
+        || lower.contains("wtfpl")
+        || lower.contains("cc0")
+        || lower.contains("public domain")
+}
+
+/// Whether a license is copyleft (GPL, LGPL, AGPL).
+#[must_use]
+pub fn is_copyleft(license: &str) -> bool {
+    let lower = license.to_lowercase();
+    (lower.contains("gpl") || lower.contains("agpl") || lower.contains("lgpl"))
+        && !lower.contains("exception")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_case_and_deprecated_gnu_forms() {
+        assert_eq!(normalize_license("mit").as_deref(), Some("MIT"));
+        assert_eq!(
+            normalize_license("GPL-2.0+").as_deref(),
+            Some("GPL-2.0-or-later")
+        );
+        assert_eq!(
+            normalize_license("LGPL-3.0").as_deref(),
+            Some("LGPL-3.0-only")
+        );
+        assert_eq!(normalize_license("proprietary"), None);
+    }
+
+    #[test]
+    fn builds_expressions_from_alternatives() {
+        let licenses = vec!["mit".to_string(), "(GPL-3.0+ and BSD-3-Clause)".to_string()];
+        assert_eq!(
+            license_expression(&licenses).as_deref(),
+            Some("MIT OR (GPL-3.0-or-later AND BSD-3-Clause)")
+        );
+        assert_eq!(license_expression(&[]), None);
+        assert_eq!(license_expression(&["proprietary".to_string()]), None);
+    }
+}
@@ -0,0 +1,659 @@
+//! Machine-readable audit reporters.
+//!
+//! Renders an [`AuditReport`] as Composer-compatible JSON, SARIF 2.1.0,
+//! `JUnit` XML or GitHub Actions workflow annotations. SARIF results and
+//! annotations point at the `composer.lock` line declaring each package.
+
+use crate::{AuditReport, Severity, Vulnerability};
+use libretto_core::{PackageId, Result as CoreResult};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Output format of an audit report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Composer `audit --format=json` schema.
+    Json,
+    /// SARIF 2.1.0, for code scanning.
+    Sarif,
+    /// `JUnit` XML, for CI test report views.
+    Junit,
+    /// GitHub Actions `::error`/`::warning` workflow commands.
+    Github,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "sarif" => Ok(Self::Sarif),
+            "junit" => Ok(Self::Junit),
+            "github" => Ok(Self::Github),
+            other => Err(format!("unknown report format: {other}")),
+        }
+    }
+}
+
+/// Line index of the lock file a report was produced from.
+#[derive(Debug, Clone)]
+pub struct LockLocation {
+    path: String,
+    lines: HashMap<String, usize>,
+}
+
+impl LockLocation {
+    /// Index the `"name"` entry of every package in the lock file `content`.
+    #[must_use]
+    pub fn new(path: impl Into<String>, content: &str) -> Self {
+        let mut lines = HashMap::new();
+        for (number, line) in content.lines().enumerate() {
+            let Some(rest) = line.trim_start().strip_prefix("\"name\":") else {
+                continue;
+            };
+            let name = rest.trim().trim_end_matches(',').trim_matches('"');
+            // Authors also carry a "name"; the package's own entry comes first.
+            if name.contains('/') {
+                lines.entry(name.to_ascii_lowercase()).or_insert(number + 1);
+            }
+        }
+        Self {
+            path: path.into(),
+            lines,
+        }
+    }
+
+    /// Path of the lock file, as reported.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// 1-based line declaring `package`, or 1 if it is not in the lock file.
+    #[must_use]
+    pub fn line(&self, package: &PackageId) -> usize {
+        self.lines
+            .get(&package.to_string().to_ascii_lowercase())
+            .copied()
+            .unwrap_or(1)
+    }
+}
+
+impl AuditReport {
+    /// Render the report in a machine-readable `format`.
+    ///
+    /// # Errors
+    /// Returns an error if JSON serialization fails.
+    pub fn render(&self, format: ReportFormat, lock: &LockLocation) -> CoreResult<String> {
+        match format {
+            ReportFormat::Json => self.to_composer_json(),
+            ReportFormat::Sarif => self.to_sarif(lock),
+            ReportFormat::Junit => Ok(self.to_junit(lock)),
+            ReportFormat::Github => Ok(self.to_github_annotations(lock)),
+        }
+    }
+
+    /// Render the report using Composer's `audit --format=json` schema.
+    ///
+    /// # Errors
+    /// Returns an error if JSON serialization fails.
+    pub fn to_composer_json(&self) -> CoreResult<String> {
+        let mut advisories: BTreeMap<String, Vec<ComposerAdvisory<'_>>> = BTreeMap::new();
+        for pkg in &self.packages {
+            for vuln in &pkg.vulnerabilities {
+                advisories
+                    .entry(pkg.package.to_string())
+                    .or_default()
+                    .push(ComposerAdvisory::new(vuln));
+            }
+        }
+
+        let ignored = self
+            .ignored
+            .iter()
+            .map(|ignored| ComposerIgnored {
+                advisory_id: &ignored.vulnerability.advisory_id,
+                package_name: ignored.vulnerability.package.to_string(),
+                version: &ignored.version,
+                reason: &ignored.reason,
+            })
+            .collect();
+
+        let output = ComposerAudit {
+            advisories,
+            ignored_advisories: ignored,
+            abandoned: BTreeMap::new(),
+        };
+        Ok(sonic_rs::to_string_pretty(&output)?)
+    }
+
+    /// Render the report as a SARIF 2.1.0 log.
+    ///
+    /// # Errors
+    /// Returns an error if JSON serialization fails.
+    pub fn to_sarif(&self, lock: &LockLocation) -> CoreResult<String> {
+        let mut rules: BTreeMap<&str, SarifRule<'_>> = BTreeMap::new();
+        let mut results = Vec::new();
+
+        for pkg in &self.packages {
+            for vuln in &pkg.vulnerabilities {
+                rules
+                    .entry(vuln.advisory_id.as_str())
+                    .or_insert_with(|| SarifRule::new(vuln));
+                results.push(SarifResult {
+                    rule_id: &vuln.advisory_id,
+                    level: sarif_level(vuln.severity),
+                    message: SarifText {
+                        text: finding_message(vuln, &pkg.version),
+                    },
+                    locations: vec![SarifLocation {
+                        physical_location: SarifPhysicalLocation {
+                            artifact_location: SarifArtifactLocation { uri: lock.path() },
+                            region: SarifRegion {
+                                start_line: lock.line(&pkg.package),
+                            },
+                        },
+                    }],
+                });
+            }
+        }
+
+        let log = SarifLog {
+            schema: SARIF_SCHEMA,
+            version: "2.1.0",
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: "libretto",
+                        information_uri: env!("CARGO_PKG_REPOSITORY"),
+                        version: env!("CARGO_PKG_VERSION"),
+                        rules: rules.into_values().collect(),
+                    },
+                },
+                results,
+            }],
+        };
+        Ok(sonic_rs::to_string_pretty(&log)?)
+    }
+
+    /// Render the report as `JUnit` XML.
+    ///
+    /// Every package without findings is a passing test case, every finding a
+    /// failing one and every ignored finding a skipped one.
+    #[must_use]
+    pub fn to_junit(&self, lock: &LockLocation) -> String {
+        let mut cases = String::new();
+        let mut tests = 0;
+        let mut failures = 0;
+
+        for pkg in &self.packages {
+            let classname = xml_escape(&pkg.package.to_string());
+            if pkg.vulnerabilities.is_empty() {
+                tests += 1;
+                let _ = writeln!(
+                    cases,
+                    "    <testcase classname=\"{classname}\" name=\"{}\"/>",
+                    xml_escape(&pkg.version)
+                );
+            }
+            for vuln in &pkg.vulnerabilities {
+                tests += 1;
+                failures += 1;
+                let _ = writeln!(
+                    cases,
+                    "    <testcase classname=\"{classname}\" name=\"{}\" file=\"{}\" line=\"{}\">",
+                    xml_escape(&format!("{} {}", pkg.version, vuln.advisory_id)),
+                    xml_escape(lock.path()),
+                    lock.line(&pkg.package)
+                );
+                let _ = writeln!(
+                    cases,
+                    "      <failure type=\"{}\" message=\"{}\">{}</failure>",
+                    vuln.severity,
+                    xml_escape(&vuln.title),
+                    xml_escape(&finding_details(vuln))
+                );
+                cases.push_str("    </testcase>\n");
+            }
+        }
+
+        for ignored in &self.ignored {
+            tests += 1;
+            let vuln = &ignored.vulnerability;
+            let _ = writeln!(
+                cases,
+                "    <testcase classname=\"{}\" name=\"{}\">",
+                xml_escape(&vuln.package.to_string()),
+                xml_escape(&format!("{} {}", ignored.version, vuln.advisory_id))
+            );
+            let _ = writeln!(
+                cases,
+                "      <skipped message=\"{}\"/>",
+                xml_escape(&ignored.reason)
+            );
+            cases.push_str("    </testcase>\n");
+        }
+
+        let skipped = self.ignored.len();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"libretto audit\" tests=\"{tests}\" failures=\"{failures}\" skipped=\"{skipped}\">"
+        );
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{tests}\" failures=\"{failures}\" skipped=\"{skipped}\" timestamp=\"{}\">",
+            xml_escape(lock.path()),
+            self.audited_at.format("%Y-%m-%dT%H:%M:%S")
+        );
+        xml.push_str(&cases);
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+
+    /// Render the report as GitHub Actions workflow commands.
+    ///
+    /// Critical and high findings are `::error` annotations, the rest
+    /// `::warning` annotations.
+    #[must_use]
+    pub fn to_github_annotations(&self, lock: &LockLocation) -> String {
+        let mut out = String::new();
+        for pkg in &self.packages {
+            for vuln in &pkg.vulnerabilities {
+                let command = match vuln.severity {
+                    Severity::Critical | Severity::High => "error",
+                    _ => "warning",
+                };
+                let title = format!("{} ({} {})", vuln.advisory_id, pkg.package, vuln.severity);
+                let _ = writeln!(
+                    out,
+                    "::{command} file={},line={},title={}::{}",
+                    escape_property(lock.path()),
+                    lock.line(&pkg.package),
+                    escape_property(&title),
+                    escape_data(&finding_message(vuln, &pkg.version))
+                );
+            }
+        }
+        out
+    }
+}
+
+/// Composer `audit --format=json` output.
+#[derive(Serialize)]
+struct ComposerAudit<'a> {
+    advisories: BTreeMap<String, Vec<ComposerAdvisory<'a>>>,
+    #[serde(rename = "ignored-advisories", skip_serializing_if = "Vec::is_empty")]
+    ignored_advisories: Vec<ComposerIgnored<'a>>,
+    abandoned: BTreeMap<String, Option<String>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ComposerAdvisory<'a> {
+    advisory_id: &'a str,
+    package_name: String,
+    affected_versions: &'a str,
+    title: &'a str,
+    cve: Option<&'a str>,
+    link: Option<&'a str>,
+    reported_at: Option<String>,
+    sources: Vec<ComposerSource<'a>>,
+    severity: Severity,
+}
+
+impl<'a> ComposerAdvisory<'a> {
+    fn new(vuln: &'a Vulnerability) -> Self {
+        let cve = vuln
+            .advisory_id
+            .starts_with("CVE-")
+            .then_some(vuln.advisory_id.as_str());
+        Self {
+            advisory_id: &vuln.advisory_id,
+            package_name: vuln.package.to_string(),
+            affected_versions: &vuln.affected_versions,
+            title: &vuln.title,
+            cve,
+            link: vuln.references.first().map(url::Url::as_str),
+            reported_at: vuln
+                .published_at
+                .map(|at| at.format("%Y-%m-%d %H:%M:%S").to_string()),
+            sources: vec![ComposerSource {
+                name: "Libretto",
+                remote_id: &vuln.advisory_id,
+            }],
+            severity: vuln.severity,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ComposerSource<'a> {
+    name: &'static str,
+    remote_id: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ComposerIgnored<'a> {
+    advisory_id: &'a str,
+    package_name: String,
+    version: &'a str,
+    reason: &'a str,
+}
+
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun<'a>>,
+}
+
+#[derive(Serialize)]
+struct SarifRun<'a> {
+    tool: SarifTool<'a>,
+    results: Vec<SarifResult<'a>>,
+}
+
+#[derive(Serialize)]
+struct SarifTool<'a> {
+    driver: SarifDriver<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver<'a> {
+    name: &'static str,
+    information_uri: &'static str,
+    version: &'static str,
+    rules: Vec<SarifRule<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRule<'a> {
+    id: &'a str,
+    short_description: SarifText,
+    full_description: SarifText,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<&'a str>,
+    default_configuration: SarifConfiguration,
+    properties: SarifRuleProperties,
+}
+
+impl<'a> SarifRule<'a> {
+    fn new(vuln: &'a Vulnerability) -> Self {
+        let description = if vuln.description.is_empty() {
+            &vuln.title
+        } else {
+            &vuln.description
+        };
+        Self {
+            id: &vuln.advisory_id,
+            short_description: SarifText {
+                text: vuln.title.clone(),
+            },
+            full_description: SarifText {
+                text: description.clone(),
+            },
+            help_uri: vuln.references.first().map(url::Url::as_str),
+            default_configuration: SarifConfiguration {
+                level: sarif_level(vuln.severity),
+            },
+            properties: SarifRuleProperties {
+                tags: vec!["security", "vulnerability"],
+                security_severity: format!("{:.1}", security_severity(vuln)),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SarifText {
+    text: String,
+}
+
+#[derive(Serialize)]
+struct SarifConfiguration {
+    level: &'static str,
+}
+
+#[derive(Serialize)]
+struct SarifRuleProperties {
+    tags: Vec<&'static str>,
+    #[serde(rename = "security-severity")]
+    security_severity: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    rule_id: &'a str,
+    level: &'static str,
+    message: SarifText,
+    locations: Vec<SarifLocation<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation<'a> {
+    physical_location: SarifPhysicalLocation<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation<'a> {
+    artifact_location: SarifArtifactLocation<'a>,
+    region: SarifRegion,
+}
+
+#[derive(Serialize)]
+struct SarifArtifactLocation<'a> {
+    uri: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: usize,
+}
+
+const fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Unknown => "note",
+    }
+}
+
+/// CVSS-like score GitHub code scanning uses to rank findings.
+fn security_severity(vuln: &Vulnerability) -> f32 {
+    vuln.cvss_score.unwrap_or(match vuln.severity {
+        Severity::Critical => 9.5,
+        Severity::High => 8.0,
+        Severity::Medium => 5.5,
+        Severity::Low => 2.0,
+        Severity::Unknown => 0.0,
+    })
+}
+
+fn finding_message(vuln: &Vulnerability, version: &str) -> String {
+    let mut message = format!(
+        "{} {version} is affected by {}: {}",
+        vuln.package, vuln.advisory_id, vuln.title
+    );
+    if let Some(ref fixed) = vuln.fixed_version {
+        let _ = write!(message, " (fixed in {fixed})");
+    }
+    message
+}
+
+fn finding_details(vuln: &Vulnerability) -> String {
+    let mut details = format!(
+        "{} [{}]\nAffected versions: {}",
+        vuln.advisory_id, vuln.severity, vuln.affected_versions
+    );
+    if let Some(ref fixed) = vuln.fixed_version {
+        let _ = write!(details, "\nFixed in: {fixed}");
+    }
+    for reference in &vuln.references {
+        let _ = write!(details, "\n{reference}");
+    }
+    details
+}
+
+/// Escape text for use in XML element content and attribute values.
+#[allow(clippy::redundant_pub_crate)] // shared with `sbom`, not part of the crate API
+pub(crate) fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c if c.is_control() && !matches!(c, '\n' | '\t' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape the message of a workflow command.
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property value of a workflow command.
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IgnoredVulnerability, PackageAudit};
+    use chrono::Utc;
+
+    const LOCK: &str = r#"{
+    "packages": [
+        {
+            "name": "monolog/monolog",
+            "version": "2.0.0",
+            "authors": [
+                {
+                    "name": "Jordi Boggiano"
+                }
+            ]
+        },
+        {
+            "name": "symfony/http-kernel",
+            "version": "5.4.0"
+        }
+    ]
+}"#;
+
+    fn vulnerability(package: &PackageId, id: &str, severity: Severity) -> Vulnerability {
+        Vulnerability {
+            advisory_id: id.to_string(),
+            package: package.clone(),
+            affected_versions: "<5.4.20".to_string(),
+            fixed_version: None,
+            severity,
+            cvss_score: None,
+            title: "Cookie <injection> & more".to_string(),
+            description: String::new(),
+            references: vec!["https://example.com/advisory".parse().unwrap()],
+            published_at: None,
+        }
+    }
+
+    fn report() -> AuditReport {
+        let monolog = PackageId::parse("monolog/monolog").unwrap();
+        let kernel = PackageId::parse("symfony/http-kernel").unwrap();
+        AuditReport {
+            packages: vec![
+                PackageAudit {
+                    package: monolog.clone(),
+                    version: "2.0.0".to_string(),
+                    vulnerabilities: vec![],
+                },
+                PackageAudit {
+                    package: kernel.clone(),
+                    version: "5.4.0".to_string(),
+                    vulnerabilities: vec![vulnerability(&kernel, "CVE-2023-1", Severity::High)],
+                },
+            ],
+            ignored: vec![IgnoredVulnerability {
+                version: "2.0.0".to_string(),
+                vulnerability: vulnerability(&monolog, "GHSA-xxxx", Severity::Low),
+                reason: "not reachable".to_string(),
+            }],
+            audited_at: Utc::now(),
+            database_version: None,
+        }
+    }
+
+    #[test]
+    fn lock_location_finds_package_lines() {
+        let lock = LockLocation::new("composer.lock", LOCK);
+        assert_eq!(lock.line(&PackageId::parse("monolog/monolog").unwrap()), 4);
+        assert_eq!(
+            lock.line(&PackageId::parse("symfony/http-kernel").unwrap()),
+            13
+        );
+        assert_eq!(lock.line(&PackageId::parse("acme/missing").unwrap()), 1);
+    }
+
+    #[test]
+    fn composer_json_groups_advisories_by_package() {
+        let json: serde_json::Value =
+            serde_json::from_str(&report().to_composer_json().unwrap()).unwrap();
+        let advisory = &json["advisories"]["symfony/http-kernel"][0];
+        assert_eq!(advisory["advisoryId"], "CVE-2023-1");
+        assert_eq!(advisory["cve"], "CVE-2023-1");
+        assert_eq!(advisory["severity"], "high");
+        assert_eq!(json["ignored-advisories"][0]["reason"], "not reachable");
+    }
+
+    #[test]
+    fn sarif_points_at_lock_line() {
+        let lock = LockLocation::new("composer.lock", LOCK);
+        let json: serde_json::Value =
+            serde_json::from_str(&report().to_sarif(&lock).unwrap()).unwrap();
+        let run = &json["runs"][0];
+        assert_eq!(json["version"], "2.1.0");
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "CVE-2023-1");
+        assert_eq!(run["results"][0]["level"], "error");
+        let location = &run["results"][0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "composer.lock");
+        assert_eq!(location["region"]["startLine"], 13);
+    }
+
+    #[test]
+    fn junit_escapes_and_counts() {
+        let xml = report().to_junit(&LockLocation::new("composer.lock", LOCK));
+        assert!(xml.contains("tests=\"3\" failures=\"1\" skipped=\"1\""));
+        assert!(xml.contains("Cookie &lt;injection&gt; &amp; more"));
+        assert!(xml.contains("<skipped message=\"not reachable\"/>"));
+    }
+
+    #[test]
+    fn github_annotations_escape_properties() {
+        let out = report().to_github_annotations(&LockLocation::new("composer.lock", LOCK));
+        assert_eq!(out.lines().count(), 1);
+        assert!(out.starts_with(
+            "::error file=composer.lock,line=13,title=CVE-2023-1 (symfony/http-kernel HIGH)::"
+        ));
+    }
+}
@@ -0,0 +1,1278 @@
+//! Software bill of materials generation.
+//!
+//! Builds `CycloneDX` 1.5 (JSON or XML) and SPDX 2.3 (JSON) documents from a
+//! `composer.lock`, with purls, normalized licenses, dist hashes, source
+//! references and the dependency graph. Findings of an [`AuditReport`] can be
+//! embedded as `CycloneDX` VEX entries or SPDX security references.
+
+use crate::license::license_expression;
+use crate::report::xml_escape;
+use crate::{AuditReport, Severity, Vulnerability};
+use chrono::{DateTime, SecondsFormat, Utc};
+use libretto_core::Result as CoreResult;
+use libretto_lockfile::{ComposerLock, LockedPackage};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+
+const CYCLONEDX_XMLNS: &str = "http://cyclonedx.org/schema/bom/1.5";
+
+/// SBOM document format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SbomFormat {
+    /// `CycloneDX` 1.5 JSON.
+    CycloneDxJson,
+    /// `CycloneDX` 1.5 XML.
+    CycloneDxXml,
+    /// SPDX 2.3 JSON.
+    SpdxJson,
+}
+
+impl std::str::FromStr for SbomFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "cyclonedx" | "cyclonedx-json" => Ok(Self::CycloneDxJson),
+            "cyclonedx-xml" => Ok(Self::CycloneDxXml),
+            "spdx" | "spdx-json" => Ok(Self::SpdxJson),
+            other => Err(format!("unknown SBOM format: {other}")),
+        }
+    }
+}
+
+/// The project the lock file belongs to.
+#[derive(Debug, Clone, Default)]
+pub struct RootComponent {
+    /// Package name from `composer.json`.
+    pub name: String,
+    /// Version from `composer.json`, if set.
+    pub version: Option<String>,
+    /// Direct production requirements.
+    pub require: Vec<String>,
+    /// Direct development requirements.
+    pub require_dev: Vec<String>,
+}
+
+/// SBOM builder for a lock file.
+#[derive(Debug)]
+pub struct Sbom<'a> {
+    lock: &'a ComposerLock,
+    root: Option<RootComponent>,
+    include_dev: bool,
+    audit: Option<&'a AuditReport>,
+    created: DateTime<Utc>,
+}
+
+impl<'a> Sbom<'a> {
+    /// Create an SBOM of every package in `lock`.
+    #[must_use]
+    pub fn new(lock: &'a ComposerLock) -> Self {
+        Self {
+            lock,
+            root: None,
+            include_dev: true,
+            audit: None,
+            created: Utc::now(),
+        }
+    }
+
+    /// Describe `root` as the subject of the SBOM.
+    #[must_use]
+    pub fn with_root(mut self, root: RootComponent) -> Self {
+        self.root = Some(root);
+        self
+    }
+
+    /// Leave out `packages-dev`.
+    #[must_use]
+    pub const fn without_dev(mut self) -> Self {
+        self.include_dev = false;
+        self
+    }
+
+    /// Embed the findings of `report`.
+    #[must_use]
+    pub const fn with_vulnerabilities(mut self, report: &'a AuditReport) -> Self {
+        self.audit = Some(report);
+        self
+    }
+
+    /// Render the SBOM in `format`.
+    ///
+    /// # Errors
+    /// Returns an error if JSON serialization fails.
+    pub fn render(&self, format: SbomFormat) -> CoreResult<String> {
+        match format {
+            SbomFormat::CycloneDxJson => self.to_cyclonedx_json(),
+            SbomFormat::CycloneDxXml => Ok(self.to_cyclonedx_xml()),
+            SbomFormat::SpdxJson => self.to_spdx_json(),
+        }
+    }
+
+    /// Render a `CycloneDX` 1.5 JSON document.
+    ///
+    /// # Errors
+    /// Returns an error if JSON serialization fails.
+    pub fn to_cyclonedx_json(&self) -> CoreResult<String> {
+        Ok(sonic_rs::to_string_pretty(&self.cyclonedx())?)
+    }
+
+    /// Render a `CycloneDX` 1.5 XML document.
+    #[must_use]
+    pub fn to_cyclonedx_xml(&self) -> String {
+        let bom = self.cyclonedx();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<bom xmlns=\"{CYCLONEDX_XMLNS}\" serialNumber=\"{}\" version=\"{}\">",
+            bom.serial_number, bom.version
+        );
+
+        xml.push_str("  <metadata>\n");
+        let _ = writeln!(xml, "    <timestamp>{}</timestamp>", bom.metadata.timestamp);
+        xml.push_str("    <tools>\n      <components>\n");
+        for tool in &bom.metadata.tools.components {
+            let _ = writeln!(
+                xml,
+                "        <component type=\"{}\"><name>{}</name><version>{}</version></component>",
+                tool.kind,
+                xml_escape(tool.name),
+                xml_escape(tool.version)
+            );
+        }
+        xml.push_str("      </components>\n    </tools>\n");
+        if let Some(ref component) = bom.metadata.component {
+            write_xml_component(&mut xml, component, 4);
+        }
+        xml.push_str("  </metadata>\n");
+
+        xml.push_str("  <components>\n");
+        for component in &bom.components {
+            write_xml_component(&mut xml, component, 4);
+        }
+        xml.push_str("  </components>\n");
+
+        xml.push_str("  <dependencies>\n");
+        for dependency in &bom.dependencies {
+            if dependency.depends_on.is_empty() {
+                let _ = writeln!(
+                    xml,
+                    "    <dependency ref=\"{}\"/>",
+                    xml_escape(&dependency.reference)
+                );
+                continue;
+            }
+            let _ = writeln!(
+                xml,
+                "    <dependency ref=\"{}\">",
+                xml_escape(&dependency.reference)
+            );
+            for target in &dependency.depends_on {
+                let _ = writeln!(xml, "      <dependency ref=\"{}\"/>", xml_escape(target));
+            }
+            xml.push_str("    </dependency>\n");
+        }
+        xml.push_str("  </dependencies>\n");
+
+        if !bom.vulnerabilities.is_empty() {
+            xml.push_str("  <vulnerabilities>\n");
+            for vuln in &bom.vulnerabilities {
+                write_xml_vulnerability(&mut xml, vuln);
+            }
+            xml.push_str("  </vulnerabilities>\n");
+        }
+
+        xml.push_str("</bom>\n");
+        xml
+    }
+
+    /// Render an SPDX 2.3 JSON document.
+    ///
+    /// # Errors
+    /// Returns an error if JSON serialization fails.
+    pub fn to_spdx_json(&self) -> CoreResult<String> {
+        let packages = self.packages();
+        let purls = purl_index(&packages);
+        let findings = self.findings();
+
+        let mut spdx_packages = Vec::new();
+        let mut relationships = Vec::new();
+
+        let root_id = self.root.as_ref().map(|root| {
+            let id = spdx_id(&root.name);
+            spdx_packages.push(SpdxPackage {
+                name: root.name.clone(),
+                spdx_id: id.clone(),
+                version_info: root.version.clone(),
+                download_location: "NOASSERTION".to_string(),
+                files_analyzed: false,
+                license_concluded: "NOASSERTION",
+                license_declared: "NOASSERTION".to_string(),
+                copyright_text: "NOASSERTION",
+                primary_package_purpose: "APPLICATION",
+                description: None,
+                homepage: None,
+                source_info: None,
+                checksums: Vec::new(),
+                external_refs: vec![SpdxExternalRef::purl(root_purl(root))],
+            });
+            relationships.push(SpdxRelationship::new("SPDXRef-DOCUMENT", "DESCRIBES", &id));
+            id
+        });
+
+        for (pkg, dev) in &packages {
+            let id = spdx_id(&pkg.name);
+            let mut external_refs = vec![SpdxExternalRef::purl(purl(&pkg.name, &pkg.version))];
+            for (vuln, _) in findings
+                .iter()
+                .filter(|(v, _)| v.package.to_string().eq_ignore_ascii_case(&pkg.name))
+            {
+                external_refs.push(SpdxExternalRef {
+                    category: "SECURITY",
+                    kind: "advisory",
+                    locator: advisory_url(vuln),
+                });
+            }
+
+            spdx_packages.push(SpdxPackage {
+                name: pkg.name.clone(),
+                spdx_id: id.clone(),
+                version_info: Some(pkg.version.clone()),
+                download_location: download_location(pkg),
+                files_analyzed: false,
+                license_concluded: "NOASSERTION",
+                license_declared: license_expression(&pkg.license)
+                    .unwrap_or_else(|| "NOASSERTION".to_string()),
+                copyright_text: "NOASSERTION",
+                primary_package_purpose: "LIBRARY",
+                description: pkg.description.clone().filter(|d| !d.is_empty()),
+                homepage: pkg.homepage.clone(),
+                source_info: pkg.source.as_ref().map(|source| {
+                    format!(
+                        "acquired from {} repository {} at {}",
+                        source.source_type, source.url, source.reference
+                    )
+                }),
+                checksums: dist_shasum(pkg)
+                    .map(|sha| SpdxChecksum {
+                        algorithm: "SHA1",
+                        checksum_value: sha.to_string(),
+                    })
+                    .into_iter()
+                    .collect(),
+                external_refs,
+            });
+
+            match root_id {
+                Some(ref root_id) => {
+                    if self.is_direct(&pkg.name, true) {
+                        relationships.push(SpdxRelationship::new(root_id, "DEPENDS_ON", &id));
+                    }
+                    if self.is_direct(&pkg.name, false) {
+                        relationships.push(SpdxRelationship::new(
+                            &id,
+                            "DEV_DEPENDENCY_OF",
+                            root_id,
+                        ));
+                    }
+                }
+                None if !dev => {
+                    relationships.push(SpdxRelationship::new("SPDXRef-DOCUMENT", "DESCRIBES", &id));
+                }
+                None => {}
+            }
+
+            for target in dependencies_of(pkg, &purls) {
+                relationships.push(SpdxRelationship::new(
+                    &id,
+                    "DEPENDS_ON",
+                    &spdx_id(&target.name),
+                ));
+            }
+        }
+
+        let name = self
+            .root
+            .as_ref()
+            .map_or_else(|| "composer.lock".to_string(), |root| root.name.clone());
+        let document = SpdxDocument {
+            spdx_version: "SPDX-2.3",
+            data_license: "CC0-1.0",
+            spdx_id: "SPDXRef-DOCUMENT",
+            document_namespace: format!(
+                "https://spdx.org/spdxdocs/{}-{}",
+                name.replace('/', "-"),
+                self.document_uuid("spdx")
+            ),
+            name,
+            creation_info: SpdxCreationInfo {
+                created: self.timestamp(),
+                creators: vec![format!("Tool: libretto-{}", env!("CARGO_PKG_VERSION"))],
+            },
+            packages: spdx_packages,
+            relationships,
+        };
+        Ok(sonic_rs::to_string_pretty(&document)?)
+    }
+
+    fn cyclonedx(&self) -> CdxBom {
+        let packages = self.packages();
+        let purls = purl_index(&packages);
+
+        let mut components = Vec::with_capacity(packages.len());
+        let mut dependencies = Vec::with_capacity(packages.len() + 1);
+
+        let root = self.root.as_ref().map(|root| {
+            let bom_ref = root_purl(root);
+            let depends_on = packages
+                .iter()
+                .filter(|(pkg, _)| {
+                    self.is_direct(&pkg.name, true) || self.is_direct(&pkg.name, false)
+                })
+                .map(|(pkg, _)| purl(&pkg.name, &pkg.version))
+                .collect();
+            dependencies.push(CdxDependency {
+                reference: bom_ref.clone(),
+                depends_on,
+            });
+            let (group, name) = split_name(&root.name);
+            CdxComponent {
+                kind: "application",
+                bom_ref: bom_ref.clone(),
+                group,
+                name,
+                version: root.version.clone(),
+                description: None,
+                scope: None,
+                hashes: Vec::new(),
+                licenses: Vec::new(),
+                purl: bom_ref,
+                external_references: Vec::new(),
+            }
+        });
+
+        for (pkg, dev) in &packages {
+            let bom_ref = purl(&pkg.name, &pkg.version);
+            let (group, name) = split_name(&pkg.name);
+
+            let mut external_references = Vec::new();
+            if let Some(ref source) = pkg.source {
+                external_references.push(CdxReference {
+                    kind: "vcs",
+                    url: source.url.clone(),
+                    comment: Some(format!(
+                        "{} reference {}",
+                        source.source_type, source.reference
+                    )),
+                });
+            }
+            if let Some(ref dist) = pkg.dist
+                && !dist.url.is_empty()
+            {
+                external_references.push(CdxReference {
+                    kind: "distribution",
+                    url: dist.url.clone(),
+                    comment: None,
+                });
+            }
+            if let Some(ref homepage) = pkg.homepage {
+                external_references.push(CdxReference {
+                    kind: "website",
+                    url: homepage.clone(),
+                    comment: None,
+                });
+            }
+
+            dependencies.push(CdxDependency {
+                reference: bom_ref.clone(),
+                depends_on: dependencies_of(pkg, &purls)
+                    .map(|target| purl(&target.name, &target.version))
+                    .collect(),
+            });
+
+            components.push(CdxComponent {
+                kind: "library",
+                bom_ref: bom_ref.clone(),
+                group,
+                name,
+                version: Some(pkg.version.clone()),
+                description: pkg.description.clone().filter(|d| !d.is_empty()),
+                scope: dev.then_some("optional"),
+                hashes: dist_shasum(pkg)
+                    .map(|sha| CdxHash {
+                        alg: "SHA-1",
+                        content: sha.to_string(),
+                    })
+                    .into_iter()
+                    .collect(),
+                licenses: cdx_licenses(&pkg.license),
+                purl: bom_ref,
+                external_references,
+            });
+        }
+
+        let vulnerabilities = self
+            .findings()
+            .into_iter()
+            .filter_map(|(vuln, ignored)| {
+                let target = purls.get(&vuln.package.to_string().to_ascii_lowercase())?;
+                Some(cdx_vulnerability(
+                    vuln,
+                    purl(&target.name, &target.version),
+                    ignored,
+                ))
+            })
+            .collect();
+
+        CdxBom {
+            bom_format: "CycloneDX",
+            spec_version: "1.5",
+            serial_number: format!("urn:uuid:{}", self.document_uuid("cyclonedx")),
+            version: 1,
+            metadata: CdxMetadata {
+                timestamp: self.timestamp(),
+                tools: CdxTools {
+                    components: vec![CdxTool {
+                        kind: "application",
+                        name: "libretto",
+                        version: env!("CARGO_PKG_VERSION"),
+                    }],
+                },
+                component: root,
+            },
+            components,
+            dependencies,
+            vulnerabilities,
+        }
+    }
+
+    /// Locked packages in the SBOM, with whether each is a dev package.
+    fn packages(&self) -> Vec<(&'a LockedPackage, bool)> {
+        let mut packages: Vec<_> = self.lock.packages.iter().map(|pkg| (pkg, false)).collect();
+        if self.include_dev {
+            packages.extend(self.lock.packages_dev.iter().map(|pkg| (pkg, true)));
+        }
+        packages
+    }
+
+    /// Findings of the embedded report, with the ignore reason of ignored ones.
+    fn findings(&self) -> Vec<(&'a Vulnerability, Option<&'a str>)> {
+        let Some(report) = self.audit else {
+            return Vec::new();
+        };
+        report
+            .packages
+            .iter()
+            .flat_map(|pkg| pkg.vulnerabilities.iter().map(|v| (v, None)))
+            .chain(
+                report
+                    .ignored
+                    .iter()
+                    .map(|i| (&i.vulnerability, Some(i.reason.as_str()))),
+            )
+            .collect()
+    }
+
+    fn is_direct(&self, name: &str, production: bool) -> bool {
+        self.root.as_ref().is_some_and(|root| {
+            let require = if production {
+                &root.require
+            } else {
+                &root.require_dev
+            };
+            require.iter().any(|r| r.eq_ignore_ascii_case(name))
+        })
+    }
+
+    fn timestamp(&self) -> String {
+        self.created.to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+
+    /// Random-looking but reproducible UUID for this lock file and timestamp.
+    fn document_uuid(&self, kind: &str) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(kind.as_bytes());
+        hasher.update(self.lock.content_hash.as_bytes());
+        hasher.update(self.timestamp().as_bytes());
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&hasher.finalize().as_bytes()[..16]);
+        // RFC 4122 version 4, variant 1.
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        let hex = hex::encode(bytes);
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+    }
+}
+
+/// Package URL of a Composer package.
+#[must_use]
+pub fn purl(name: &str, version: &str) -> String {
+    format!(
+        "pkg:composer/{}@{}",
+        name.to_ascii_lowercase(),
+        purl_encode(version)
+    )
+}
+
+fn root_purl(root: &RootComponent) -> String {
+    root.version.as_deref().map_or_else(
+        || format!("pkg:composer/{}", root.name.to_ascii_lowercase()),
+        |version| purl(&root.name, version),
+    )
+}
+
+fn purl_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'.' | b'-' | b'_' | b'~' | b'+') {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+/// Map package names, including replaced and provided names, to locked packages.
+fn purl_index<'a>(packages: &[(&'a LockedPackage, bool)]) -> HashMap<String, &'a LockedPackage> {
+    let mut index = HashMap::new();
+    for (pkg, _) in packages {
+        index.insert(pkg.name.to_ascii_lowercase(), *pkg);
+    }
+    for (pkg, _) in packages {
+        for name in pkg.replace.keys().chain(pkg.provide.keys()) {
+            index.entry(name.to_ascii_lowercase()).or_insert(*pkg);
+        }
+    }
+    index
+}
+
+/// Locked packages `pkg` requires, skipping platform packages.
+fn dependencies_of<'a>(
+    pkg: &'a LockedPackage,
+    index: &'a HashMap<String, &'a LockedPackage>,
+) -> impl Iterator<Item = &'a LockedPackage> {
+    let mut seen = BTreeSet::new();
+    pkg.require
+        .keys()
+        .filter_map(|name| index.get(&name.to_ascii_lowercase()).copied())
+        .filter(move |target| target.name != pkg.name && seen.insert(target.name.as_str()))
+}
+
+fn split_name(name: &str) -> (Option<String>, String) {
+    match name.split_once('/') {
+        Some((vendor, name)) => (Some(vendor.to_string()), name.to_string()),
+        None => (None, name.to_string()),
+    }
+}
+
+fn dist_shasum(pkg: &LockedPackage) -> Option<&str> {
+    pkg.dist
+        .as_ref()
+        .and_then(|dist| dist.shasum.as_deref())
+        .filter(|sha| !sha.is_empty())
+}
+
+fn download_location(pkg: &LockedPackage) -> String {
+    if let Some(ref dist) = pkg.dist
+        && !dist.url.is_empty()
+    {
+        return dist.url.clone();
+    }
+    if let Some(ref source) = pkg.source {
+        return format!("{}+{}@{}", source.source_type, source.url, source.reference);
+    }
+    "NOASSERTION".to_string()
+}
+
+fn spdx_id(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("SPDXRef-Package-{sanitized}")
+}
+
+fn advisory_url(vuln: &Vulnerability) -> String {
+    vuln.references.first().map_or_else(
+        || {
+            if vuln.advisory_id.starts_with("CVE-") {
+                format!("https://nvd.nist.gov/vuln/detail/{}", vuln.advisory_id)
+            } else {
+                vuln.advisory_id.clone()
+            }
+        },
+        ToString::to_string,
+    )
+}
+
+fn cdx_licenses(licenses: &[String]) -> Vec<CdxLicenseChoice> {
+    match license_expression(licenses) {
+        Some(expression) if expression.contains(' ') => {
+            vec![CdxLicenseChoice::Expression { expression }]
+        }
+        Some(id) => vec![CdxLicenseChoice::License {
+            license: CdxLicense {
+                id: Some(id),
+                name: None,
+            },
+        }],
+        None => licenses
+            .iter()
+            .map(|name| CdxLicenseChoice::License {
+                license: CdxLicense {
+                    id: None,
+                    name: Some(name.clone()),
+                },
+            })
+            .collect(),
+    }
+}
+
+fn cdx_vulnerability(
+    vuln: &Vulnerability,
+    target: String,
+    ignored: Option<&str>,
+) -> CdxVulnerability {
+    let source = if vuln.advisory_id.starts_with("CVE-") {
+        Some(CdxSource {
+            name: "NVD",
+            url: format!("https://nvd.nist.gov/vuln/detail/{}", vuln.advisory_id),
+        })
+    } else if vuln.advisory_id.starts_with("GHSA-") {
+        Some(CdxSource {
+            name: "GitHub",
+            url: format!("https://github.com/advisories/{}", vuln.advisory_id),
+        })
+    } else {
+        None
+    };
+
+    let analysis = match ignored {
+        Some(reason) => CdxAnalysis {
+            state: "exploitable",
+            response: vec!["will_not_fix"],
+            detail: Some(reason.to_string()),
+        },
+        None => CdxAnalysis {
+            state: "exploitable",
+            response: if vuln.fixed_version.is_some() {
+                vec!["update"]
+            } else {
+                Vec::new()
+            },
+            detail: None,
+        },
+    };
+
+    CdxVulnerability {
+        id: vuln.advisory_id.clone(),
+        source,
+        ratings: vec![CdxRating {
+            score: vuln.cvss_score,
+            severity: cyclonedx_severity(vuln.severity),
+            method: if vuln.cvss_score.is_some() {
+                "CVSSv3"
+            } else {
+                "other"
+            },
+        }],
+        description: if vuln.description.is_empty() {
+            vuln.title.clone()
+        } else {
+            vuln.description.clone()
+        },
+        recommendation: vuln
+            .fixed_version
+            .as_ref()
+            .map(|fixed| format!("Upgrade {} to {fixed} or later", vuln.package)),
+        published: vuln
+            .published_at
+            .map(|at| at.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        analysis,
+        affects: vec![CdxAffect { reference: target }],
+    }
+}
+
+const fn cyclonedx_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "critical",
+        Severity::High => "high",
+        Severity::Medium => "medium",
+        Severity::Low => "low",
+        Severity::Unknown => "unknown",
+    }
+}
+
+fn write_xml_component(xml: &mut String, component: &CdxComponent, indent: usize) {
+    let pad = " ".repeat(indent);
+    let _ = writeln!(
+        xml,
+        "{pad}<component type=\"{}\" bom-ref=\"{}\">",
+        component.kind,
+        xml_escape(&component.bom_ref)
+    );
+    if let Some(ref group) = component.group {
+        let _ = writeln!(xml, "{pad}  <group>{}</group>", xml_escape(group));
+    }
+    let _ = writeln!(xml, "{pad}  <name>{}</name>", xml_escape(&component.name));
+    if let Some(ref version) = component.version {
+        let _ = writeln!(xml, "{pad}  <version>{}</version>", xml_escape(version));
+    }
+    if let Some(ref description) = component.description {
+        let _ = writeln!(
+            xml,
+            "{pad}  <description>{}</description>",
+            xml_escape(description)
+        );
+    }
+    if let Some(scope) = component.scope {
+        let _ = writeln!(xml, "{pad}  <scope>{scope}</scope>");
+    }
+    if !component.hashes.is_empty() {
+        let _ = writeln!(xml, "{pad}  <hashes>");
+        for hash in &component.hashes {
+            let _ = writeln!(
+                xml,
+                "{pad}    <hash alg=\"{}\">{}</hash>",
+                hash.alg,
+                xml_escape(&hash.content)
+            );
+        }
+        let _ = writeln!(xml, "{pad}  </hashes>");
+    }
+    if !component.licenses.is_empty() {
+        let _ = writeln!(xml, "{pad}  <licenses>");
+        for choice in &component.licenses {
+            match choice {
+                CdxLicenseChoice::Expression { expression } => {
+                    let _ = writeln!(
+                        xml,
+                        "{pad}    <expression>{}</expression>",
+                        xml_escape(expression)
+                    );
+                }
+                CdxLicenseChoice::License { license } => {
+                    let (tag, value) = match (&license.id, &license.name) {
+                        (Some(id), _) => ("id", id),
+                        (None, Some(name)) => ("name", name),
+                        (None, None) => continue,
+                    };
+                    let _ = writeln!(
+                        xml,
+                        "{pad}    <license><{tag}>{}</{tag}></license>",
+                        xml_escape(value)
+                    );
+                }
+            }
+        }
+        let _ = writeln!(xml, "{pad}  </licenses>");
+    }
+    let _ = writeln!(xml, "{pad}  <purl>{}</purl>", xml_escape(&component.purl));
+    if !component.external_references.is_empty() {
+        let _ = writeln!(xml, "{pad}  <externalReferences>");
+        for reference in &component.external_references {
+            let _ = write!(
+                xml,
+                "{pad}    <reference type=\"{}\"><url>{}</url>",
+                reference.kind,
+                xml_escape(&reference.url)
+            );
+            if let Some(ref comment) = reference.comment {
+                let _ = write!(xml, "<comment>{}</comment>", xml_escape(comment));
+            }
+            xml.push_str("</reference>\n");
+        }
+        let _ = writeln!(xml, "{pad}  </externalReferences>");
+    }
+    let _ = writeln!(xml, "{pad}</component>");
+}
+
+fn write_xml_vulnerability(xml: &mut String, vuln: &CdxVulnerability) {
+    xml.push_str("    <vulnerability>\n");
+    let _ = writeln!(xml, "      <id>{}</id>", xml_escape(&vuln.id));
+    if let Some(ref source) = vuln.source {
+        let _ = writeln!(
+            xml,
+            "      <source><name>{}</name><url>{}</url></source>",
+            source.name,
+            xml_escape(&source.url)
+        );
+    }
+    xml.push_str("      <ratings>\n");
+    for rating in &vuln.ratings {
+        xml.push_str("        <rating>");
+        if let Some(score) = rating.score {
+            let _ = write!(xml, "<score>{score}</score>");
+        }
+        let _ = writeln!(
+            xml,
+            "<severity>{}</severity><method>{}</method></rating>",
+            rating.severity, rating.method
+        );
+    }
+    xml.push_str("      </ratings>\n");
+    let _ = writeln!(
+        xml,
+        "      <description>{}</description>",
+        xml_escape(&vuln.description)
+    );
+    if let Some(ref recommendation) = vuln.recommendation {
+        let _ = writeln!(
+            xml,
+            "      <recommendation>{}</recommendation>",
+            xml_escape(recommendation)
+        );
+    }
+    if let Some(ref published) = vuln.published {
+        let _ = writeln!(xml, "      <published>{published}</published>");
+    }
+    xml.push_str("      <analysis>\n");
+    let _ = writeln!(xml, "        <state>{}</state>", vuln.analysis.state);
+    if !vuln.analysis.response.is_empty() {
+        xml.push_str("        <responses>");
+        for response in &vuln.analysis.response {
+            let _ = write!(xml, "<response>{response}</response>");
+        }
+        xml.push_str("</responses>\n");
+    }
+    if let Some(ref detail) = vuln.analysis.detail {
+        let _ = writeln!(xml, "        <detail>{}</detail>", xml_escape(detail));
+    }
+    xml.push_str("      </analysis>\n");
+    xml.push_str("      <affects>\n");
+    for affect in &vuln.affects {
+        let _ = writeln!(
+            xml,
+            "        <target><ref>{}</ref></target>",
+            xml_escape(&affect.reference)
+        );
+    }
+    xml.push_str("      </affects>\n");
+    xml.push_str("    </vulnerability>\n");
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CdxBom {
+    bom_format: &'static str,
+    spec_version: &'static str,
+    serial_number: String,
+    version: u32,
+    metadata: CdxMetadata,
+    components: Vec<CdxComponent>,
+    dependencies: Vec<CdxDependency>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    vulnerabilities: Vec<CdxVulnerability>,
+}
+
+#[derive(Serialize)]
+struct CdxMetadata {
+    timestamp: String,
+    tools: CdxTools,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    component: Option<CdxComponent>,
+}
+
+#[derive(Serialize)]
+struct CdxTools {
+    components: Vec<CdxTool>,
+}
+
+#[derive(Serialize)]
+struct CdxTool {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CdxComponent {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<&'static str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    hashes: Vec<CdxHash>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    licenses: Vec<CdxLicenseChoice>,
+    purl: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    external_references: Vec<CdxReference>,
+}
+
+#[derive(Serialize)]
+struct CdxHash {
+    alg: &'static str,
+    content: String,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum CdxLicenseChoice {
+    License { license: CdxLicense },
+    Expression { expression: String },
+}
+
+#[derive(Serialize)]
+struct CdxLicense {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CdxReference {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CdxDependency {
+    #[serde(rename = "ref")]
+    reference: String,
+    depends_on: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct CdxVulnerability {
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<CdxSource>,
+    ratings: Vec<CdxRating>,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recommendation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    published: Option<String>,
+    analysis: CdxAnalysis,
+    affects: Vec<CdxAffect>,
+}
+
+#[derive(Serialize)]
+struct CdxSource {
+    name: &'static str,
+    url: String,
+}
+
+#[derive(Serialize)]
+struct CdxRating {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<f32>,
+    severity: &'static str,
+    method: &'static str,
+}
+
+#[derive(Serialize)]
+struct CdxAnalysis {
+    state: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    response: Vec<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CdxAffect {
+    #[serde(rename = "ref")]
+    reference: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxDocument {
+    spdx_version: &'static str,
+    data_license: &'static str,
+    #[serde(rename = "SPDXID")]
+    spdx_id: &'static str,
+    name: String,
+    document_namespace: String,
+    creation_info: SpdxCreationInfo,
+    packages: Vec<SpdxPackage>,
+    relationships: Vec<SpdxRelationship>,
+}
+
+#[derive(Serialize)]
+struct SpdxCreationInfo {
+    created: String,
+    creators: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxPackage {
+    name: String,
+    #[serde(rename = "SPDXID")]
+    spdx_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_info: Option<String>,
+    download_location: String,
+    files_analyzed: bool,
+    license_concluded: &'static str,
+    license_declared: String,
+    copyright_text: &'static str,
+    primary_package_purpose: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    homepage: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_info: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    checksums: Vec<SpdxChecksum>,
+    external_refs: Vec<SpdxExternalRef>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxChecksum {
+    algorithm: &'static str,
+    checksum_value: String,
+}
+
+#[derive(Serialize)]
+struct SpdxExternalRef {
+    #[serde(rename = "referenceCategory")]
+    category: &'static str,
+    #[serde(rename = "referenceType")]
+    kind: &'static str,
+    #[serde(rename = "referenceLocator")]
+    locator: String,
+}
+
+impl SpdxExternalRef {
+    const fn purl(locator: String) -> Self {
+        Self {
+            category: "PACKAGE-MANAGER",
+            kind: "purl",
+            locator,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxRelationship {
+    spdx_element_id: String,
+    relationship_type: &'static str,
+    related_spdx_element: String,
+}
+
+impl SpdxRelationship {
+    fn new(element: &str, relationship_type: &'static str, related: &str) -> Self {
+        Self {
+            spdx_element_id: element.to_string(),
+            relationship_type,
+            related_spdx_element: related.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PackageAudit;
+    use libretto_core::PackageId;
+    use libretto_lockfile::{PackageDistInfo, PackageSourceInfo};
+
+    fn lock() -> ComposerLock {
+        let mut monolog = LockedPackage::new("monolog/monolog", "2.9.1")
+            .with_source(PackageSourceInfo::git(
+                "https://github.com/Seldaek/monolog.git",
+                "f259e2b1",
+            ))
+            .with_dist(
+                PackageDistInfo::zip(
+                    "https://api.github.com/repos/Seldaek/monolog/zipball/f259e2b1",
+                )
+                .with_shasum("abc123"),
+            );
+        monolog.license = vec!["MIT".to_string()];
+        monolog
+            .require
+            .insert("psr/log-implementation".to_string(), "^1.0".to_string());
+        monolog
+            .require
+            .insert("php".to_string(), ">=7.2".to_string());
+
+        let mut log = LockedPackage::new("acme/log", "1.0.0");
+        log.license = vec!["GPL-2.0+".to_string(), "proprietary".to_string()];
+        log.provide
+            .insert("psr/log-implementation".to_string(), "1.0".to_string());
+
+        let mut phpunit = LockedPackage::new("phpunit/phpunit", "10.5.0");
+        phpunit.license = vec!["BSD-3-Clause".to_string()];
+
+        ComposerLock {
+            packages: vec![monolog, log],
+            packages_dev: vec![phpunit],
+            ..Default::default()
+        }
+    }
+
+    fn root() -> RootComponent {
+        RootComponent {
+            name: "acme/app".to_string(),
+            version: None,
+            require: vec!["monolog/monolog".to_string()],
+            require_dev: vec!["phpunit/phpunit".to_string()],
+        }
+    }
+
+    #[test]
+    fn purls_encode_versions() {
+        assert_eq!(
+            purl("Monolog/Monolog", "2.9.1"),
+            "pkg:composer/monolog/monolog@2.9.1"
+        );
+        assert_eq!(
+            purl("acme/app", "dev-feature/x"),
+            "pkg:composer/acme/app@dev-feature%2Fx"
+        );
+    }
+
+    #[test]
+    fn cyclonedx_has_components_graph_and_hashes() {
+        let lock = lock();
+        let json: serde_json::Value = serde_json::from_str(
+            &Sbom::new(&lock)
+                .with_root(root())
+                .to_cyclonedx_json()
+                .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(json["specVersion"], "1.5");
+        assert_eq!(
+            json["metadata"]["component"]["bom-ref"],
+            "pkg:composer/acme/app"
+        );
+        let monolog = &json["components"][0];
+        assert_eq!(monolog["purl"], "pkg:composer/monolog/monolog@2.9.1");
+        assert_eq!(monolog["licenses"][0]["license"]["id"], "MIT");
+        assert_eq!(monolog["hashes"][0]["content"], "abc123");
+        assert_eq!(monolog["externalReferences"][0]["type"], "vcs");
+        assert_eq!(json["components"][2]["scope"], "optional");
+
+        let dependencies = json["dependencies"].as_array().unwrap();
+        let monolog_deps = dependencies
+            .iter()
+            .find(|d| d["ref"] == "pkg:composer/monolog/monolog@2.9.1")
+            .unwrap();
+        assert_eq!(
+            monolog_deps["dependsOn"],
+            serde_json::json!(["pkg:composer/acme/log@1.0.0"])
+        );
+        assert_eq!(dependencies[0]["dependsOn"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn non_spdx_licenses_keep_their_names() {
+        let lock = lock();
+        let json: serde_json::Value =
+            serde_json::from_str(&Sbom::new(&lock).without_dev().to_cyclonedx_json().unwrap())
+                .unwrap();
+        let licenses = &json["components"][1]["licenses"];
+        assert_eq!(licenses[0]["license"]["name"], "GPL-2.0+");
+        assert_eq!(licenses[1]["license"]["name"], "proprietary");
+        assert_eq!(json["components"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn vex_entries_reference_components() {
+        let lock = lock();
+        let package = PackageId::parse("monolog/monolog").unwrap();
+        let report = AuditReport {
+            packages: vec![PackageAudit {
+                package: package.clone(),
+                version: "2.9.1".to_string(),
+                vulnerabilities: vec![Vulnerability {
+                    advisory_id: "CVE-2024-1".to_string(),
+                    package,
+                    affected_versions: "<2.9.2".to_string(),
+                    fixed_version: None,
+                    severity: Severity::High,
+                    cvss_score: Some(7.5),
+                    title: "Log injection".to_string(),
+                    description: String::new(),
+                    references: Vec::new(),
+                    published_at: None,
+                }],
+            }],
+            ignored: Vec::new(),
+            audited_at: Utc::now(),
+            database_version: None,
+        };
+
+        let sbom = Sbom::new(&lock).with_vulnerabilities(&report);
+        let json: serde_json::Value =
+            serde_json::from_str(&sbom.to_cyclonedx_json().unwrap()).unwrap();
+        let vuln = &json["vulnerabilities"][0];
+        assert_eq!(vuln["id"], "CVE-2024-1");
+        assert_eq!(vuln["ratings"][0]["severity"], "high");
+        assert_eq!(
+            vuln["affects"][0]["ref"],
+            "pkg:composer/monolog/monolog@2.9.1"
+        );
+
+        let xml = sbom.to_cyclonedx_xml();
+        assert!(xml.contains("<id>CVE-2024-1</id>"));
+        assert!(xml.contains("<purl>pkg:composer/monolog/monolog@2.9.1</purl>"));
+    }
+
+    #[test]
+    fn spdx_declares_licenses_and_relationships() {
+        let lock = lock();
+        let json: serde_json::Value =
+            serde_json::from_str(&Sbom::new(&lock).with_root(root()).to_spdx_json().unwrap())
+                .unwrap();
+
+        assert_eq!(json["spdxVersion"], "SPDX-2.3");
+        let packages = json["packages"].as_array().unwrap();
+        assert_eq!(packages[0]["SPDXID"], "SPDXRef-Package-acme-app");
+        assert_eq!(packages[1]["licenseDeclared"], "MIT");
+        assert_eq!(packages[1]["checksums"][0]["algorithm"], "SHA1");
+        assert_eq!(packages[2]["licenseDeclared"], "NOASSERTION");
+
+        let relationships = json["relationships"].as_array().unwrap();
+        assert!(relationships.iter().any(|r| {
+            r["spdxElementId"] == "SPDXRef-Package-phpunit-phpunit"
+                && r["relationshipType"] == "DEV_DEPENDENCY_OF"
+        }));
+        assert!(relationships.iter().any(|r| {
+            r["spdxElementId"] == "SPDXRef-Package-monolog-monolog"
+                && r["relationshipType"] == "DEPENDS_ON"
+                && r["relatedSpdxElement"] == "SPDXRef-Package-acme-log"
+        }));
+    }
+}
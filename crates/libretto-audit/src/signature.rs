@@ -99,68 +99,79 @@ impl PgpVerifier {
         self.add_trusted_key(&data)
     }
 
-    /// Verify detached signature.
+    /// Verify a detached signature over `data`.
+    ///
+    /// The signature may be binary or ASCII armored. Only keys that are
+    /// valid for signing under the standard policy and that match one of the
+    /// signature's issuers are considered; the reported key ID is the
+    /// fingerprint of the primary key.
     ///
     /// # Errors
     /// Returns error if verification fails.
-    pub fn verify_detached(&self, _data: &[u8], signature: &[u8]) -> Result<VerifiedSignature> {
+    pub fn verify_detached(&self, data: &[u8], signature: &[u8]) -> Result<VerifiedSignature> {
         let packets = sequoia_openpgp::PacketPile::from_bytes(signature)
             .map_err(|e| SignatureError::VerificationFailed(e.to_string()))?;
 
-        // Find signature packet
-        let sig_packet = packets
+        let signatures: Vec<_> = packets
             .children()
-            .find_map(|p| {
-                if let Packet::Signature(sig) = p {
-                    Some(sig.clone())
-                } else {
-                    None
+            .filter_map(|p| match p {
+                Packet::Signature(sig)
+                    if matches!(sig.typ(), SignatureType::Binary | SignatureType::Text) =>
+                {
+                    Some(sig)
                 }
+                _ => None,
             })
-            .ok_or(SignatureError::InvalidSignature)?;
-
-        // Verify signature type
-        if sig_packet.typ() != SignatureType::Binary && sig_packet.typ() != SignatureType::Text {
+            .collect();
+        if signatures.is_empty() {
             return Err(SignatureError::InvalidSignature);
         }
 
-        // Get key ID
-        let issuers: Vec<_> = sig_packet.get_issuers().into_iter().collect();
-        if issuers.is_empty() {
-            return Err(SignatureError::UnknownKey(
-                "no issuer in signature".to_string(),
-            ));
-        }
-
-        let key_id = issuers[0].to_hex();
-
-        // Find matching trusted key
-        for cert in &self.trusted_keys {
-            // Get primary key with policy
-            let key_amalg = cert.keys().with_policy(&self.policy, None);
-
-            // Try each key
-            for key in key_amalg {
-                // Try to verify the signature with this key
-                // verify_direct_key requires both signing key and target key
-                let pk = cert.primary_key().key();
-                if sig_packet.verify_direct_key(key.key(), pk).is_ok() {
-                    // Get signer identity
-                    let signer = cert
-                        .userids()
-                        .next()
-                        .map(|uid| String::from_utf8_lossy(uid.userid().value()).to_string());
-
-                    return Ok(VerifiedSignature {
-                        sig_type: SignatureAlgorithm::Pgp,
-                        key_id,
-                        signer,
-                    });
+        let mut unknown = None;
+        let mut bad = None;
+        for sig in signatures {
+            let issuers = sig.get_issuers();
+            let Some(issuer) = issuers.first() else {
+                continue;
+            };
+
+            for cert in &self.trusted_keys {
+                let signing_keys = cert
+                    .keys()
+                    .with_policy(&self.policy, None)
+                    .alive()
+                    .revoked(false)
+                    .for_signing()
+                    .key_handles(issuers.iter());
+
+                for key in signing_keys {
+                    if sig.verify_message(key.key(), data).is_ok() {
+                        let signer = cert
+                            .userids()
+                            .next()
+                            .map(|uid| String::from_utf8_lossy(uid.userid().value()).to_string());
+
+                        return Ok(VerifiedSignature {
+                            sig_type: SignatureAlgorithm::Pgp,
+                            key_id: cert.fingerprint().to_hex(),
+                            signer,
+                        });
+                    }
+                    bad.get_or_insert_with(|| cert.fingerprint().to_hex());
                 }
             }
+            unknown.get_or_insert_with(|| issuer.to_hex());
         }
 
-        Err(SignatureError::UnknownKey(key_id))
+        // A known key that does not verify means the payload was tampered with
+        if let Some(fingerprint) = bad {
+            return Err(SignatureError::VerificationFailed(format!(
+                "bad signature from {fingerprint}"
+            )));
+        }
+        Err(SignatureError::UnknownKey(
+            unknown.unwrap_or_else(|| "no issuer in signature".to_string()),
+        ))
     }
 }
 
@@ -284,14 +295,11 @@ impl SignatureVerifier {
     /// # Errors
     /// Returns error if verification fails.
     pub fn verify(&self, data: &[u8], signature: &[u8]) -> Result<VerifiedSignature> {
-        // Try Ed25519 first (simpler check)
-        if signature.len() == 64
-            && let Ok(verified) = self.ed25519.verify(data, signature)
-        {
-            return Ok(verified);
+        // Raw Ed25519 signatures are exactly 64 bytes, shorter than any OpenPGP signature packet
+        if signature.len() == 64 {
+            return self.ed25519.verify(data, signature);
         }
 
-        // Try PGP
         self.pgp.verify_detached(data, signature)
     }
 }
@@ -656,4 +664,96 @@ mod tests {
         let result = verifier.add_trusted_key(&[0u8; 16]);
         assert!(matches!(result, Err(SignatureError::InvalidKey(_))));
     }
+
+    #[test]
+    fn test_ed25519_detached_signature() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let signature = signing_key.sign(b"archive bytes").to_bytes();
+
+        let mut verifier = SignatureVerifier::new();
+        verifier
+            .add_ed25519_key(signing_key.verifying_key().as_bytes())
+            .unwrap();
+
+        let verified = verifier.verify(b"archive bytes", &signature).unwrap();
+        assert_eq!(verified.sig_type, SignatureAlgorithm::Ed25519);
+        assert_eq!(
+            verified.key_id,
+            hex::encode(signing_key.verifying_key().as_bytes())
+        );
+        assert!(verifier.verify(b"tampered bytes", &signature).is_err());
+    }
+
+    fn pgp_sign(cert: &Cert, data: &[u8]) -> Vec<u8> {
+        use sequoia_openpgp::armor;
+        use sequoia_openpgp::serialize::stream::{Armorer, Message, Signer};
+        use std::io::Write;
+
+        let keypair = cert
+            .keys()
+            .secret()
+            .with_policy(&StandardPolicy::new(), None)
+            .for_signing()
+            .next()
+            .unwrap()
+            .key()
+            .clone()
+            .into_keypair()
+            .unwrap();
+
+        let mut sink = Vec::new();
+        let message = Message::new(&mut sink);
+        let message = Armorer::new(message)
+            .kind(armor::Kind::Signature)
+            .build()
+            .unwrap();
+        let mut signer = Signer::new(message, keypair)
+            .unwrap()
+            .detached()
+            .build()
+            .unwrap();
+        signer.write_all(data).unwrap();
+        signer.finalize().unwrap();
+        sink
+    }
+
+    #[test]
+    fn test_pgp_detached_signature_covers_payload() {
+        use sequoia_openpgp::cert::CertBuilder;
+        use sequoia_openpgp::serialize::SerializeInto;
+
+        let (cert, _) = CertBuilder::general_purpose(Some("Release <release@example.com>"))
+            .generate()
+            .unwrap();
+        let (other, _) = CertBuilder::general_purpose(Some("Other <other@example.com>"))
+            .generate()
+            .unwrap();
+        let signature = pgp_sign(&cert, b"archive bytes");
+
+        let mut verifier = PgpVerifier::new();
+        verifier
+            .add_trusted_key(&cert.armored().to_vec().unwrap())
+            .unwrap();
+
+        let verified = verifier
+            .verify_detached(b"archive bytes", &signature)
+            .unwrap();
+        assert_eq!(verified.sig_type, SignatureAlgorithm::Pgp);
+        assert_eq!(verified.key_id, cert.fingerprint().to_hex());
+        assert_eq!(
+            verified.signer.as_deref(),
+            Some("Release <release@example.com>")
+        );
+
+        assert!(matches!(
+            verifier.verify_detached(b"tampered bytes", &signature),
+            Err(SignatureError::VerificationFailed(_))
+        ));
+        assert!(matches!(
+            verifier.verify_detached(b"archive bytes", &pgp_sign(&other, b"archive bytes")),
+            Err(SignatureError::UnknownKey(_))
+        ));
+    }
 }
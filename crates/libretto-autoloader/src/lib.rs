@@ -7,17 +7,20 @@
 //! - Optimization levels (0, 1, 2) for different performance profiles
 //! - Incremental updates with mtime tracking and blake3 checksums
 //! - rkyv caching for fast startup
+//! - Class and function reference extraction for dependency analysis
 
 #![warn(clippy::all)]
 #![allow(clippy::module_name_repetitions)]
 
 mod fast_parser;
 mod parser;
+mod references;
 mod scanner;
 
 pub use fast_parser::FastScanner;
 
 pub use parser::{DefinitionKind, PhpDefinition, PhpParser};
+pub use references::{FileSymbols, SymbolKind, SymbolReference};
 pub use scanner::{ExcludePattern, FileScanResult, Scanner, build_classmap, build_namespace_map};
 
 use ahash::{AHashMap, AHashSet};
@@ -156,7 +156,7 @@ impl PhpParser {
     }
 
     /// Recursively collect all class-like definitions from the AST.
-    fn collect_definitions(
+    pub(crate) fn collect_definitions(
         &self,
         program: &Program<'_>,
         resolved_names: &ResolvedNames<'_>,
@@ -284,9 +284,10 @@ impl PhpParser {
     ) {
         for member in &class.members {
             if let mago_syntax::ast::ClassLikeMember::Method(method) = member
-                && let MethodBody::Concrete(block) = &method.body {
-                    self.visit_block(block, resolved_names, definitions, source);
-                }
+                && let MethodBody::Concrete(block) = &method.body
+            {
+                self.visit_block(block, resolved_names, definitions, source);
+            }
         }
     }
 
@@ -300,9 +301,10 @@ impl PhpParser {
     ) {
         for member in &interface.members {
             if let mago_syntax::ast::ClassLikeMember::Method(method) = member
-                && let MethodBody::Concrete(block) = &method.body {
-                    self.visit_block(block, resolved_names, definitions, source);
-                }
+                && let MethodBody::Concrete(block) = &method.body
+            {
+                self.visit_block(block, resolved_names, definitions, source);
+            }
         }
     }
 
@@ -316,9 +318,10 @@ impl PhpParser {
     ) {
         for member in &tr.members {
             if let mago_syntax::ast::ClassLikeMember::Method(method) = member
-                && let MethodBody::Concrete(block) = &method.body {
-                    self.visit_block(block, resolved_names, definitions, source);
-                }
+                && let MethodBody::Concrete(block) = &method.body
+            {
+                self.visit_block(block, resolved_names, definitions, source);
+            }
         }
     }
 
@@ -332,9 +335,10 @@ impl PhpParser {
     ) {
         for member in &en.members {
             if let mago_syntax::ast::ClassLikeMember::Method(method) = member
-                && let MethodBody::Concrete(block) = &method.body {
-                    self.visit_block(block, resolved_names, definitions, source);
-                }
+                && let MethodBody::Concrete(block) = &method.body
+            {
+                self.visit_block(block, resolved_names, definitions, source);
+            }
         }
     }
 
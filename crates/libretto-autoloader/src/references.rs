@@ -0,0 +1,396 @@
+//! Symbol reference extraction.
+//!
+//! Collects the classes and functions a PHP file refers to: `use` imports,
+//! `new`, static calls and property/constant access, `instanceof`, type
+//! hints, attributes, `extends`/`implements`/trait use and function calls.
+//! Names come from mago-names' resolution pass, so aliases and the current
+//! namespace are already applied.
+
+use crate::parser::{PhpDefinition, PhpParser};
+use bumpalo::Bump;
+use mago_database::file::FileId;
+use mago_names::ResolvedNames;
+use mago_names::resolver::NameResolver;
+use mago_span::HasSpan;
+use mago_syntax::ast::{
+    Attribute, Binary, BinaryOperator, ClassConstantAccess, Expression, Extends, Function,
+    FunctionCall, FunctionPartialApplication, Hint, Identifier, Implements, Instantiation,
+    StaticMethodCall, StaticMethodPartialApplication, StaticPropertyAccess, TraitUse, Use, UseItem,
+    UseItems, UseType,
+};
+use mago_syntax::parser::parse_file_content;
+use mago_syntax::walker::Walker;
+use std::path::Path;
+
+/// What kind of symbol a reference names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    /// Class, interface, trait or enum.
+    Class,
+    /// Function.
+    Function,
+    /// Constant imported with `use const`.
+    Constant,
+}
+
+/// A symbol referenced from a PHP file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolReference {
+    /// Fully qualified name, without a leading backslash.
+    pub name: String,
+    /// Symbol kind.
+    pub kind: SymbolKind,
+    /// Global name PHP falls back to when an unqualified function call does
+    /// not resolve inside the current namespace.
+    pub fallback: Option<String>,
+    /// Line number in the file.
+    pub line: usize,
+}
+
+/// Symbols a PHP file defines and references.
+#[derive(Debug, Clone, Default)]
+pub struct FileSymbols {
+    /// Class-like definitions.
+    pub definitions: Vec<PhpDefinition>,
+    /// Fully qualified names of the functions defined in the file.
+    pub functions: Vec<String>,
+    /// Referenced symbols, in source order.
+    pub references: Vec<SymbolReference>,
+}
+
+impl PhpParser {
+    /// Parse a PHP file and extract the symbols it defines and references.
+    ///
+    /// # Errors
+    /// Returns error if file cannot be read.
+    pub fn parse_symbols_file(&mut self, path: &Path) -> std::io::Result<FileSymbols> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(self.parse_symbols(&content))
+    }
+
+    /// Parse content and extract the symbols it defines and references.
+    #[must_use]
+    pub fn parse_symbols(&mut self, content: &str) -> FileSymbols {
+        let arena = Bump::new();
+        let (program, _error) = parse_file_content(&arena, FileId::zero(), content);
+        let resolved_names = NameResolver::new(&arena).resolve(program);
+
+        let mut symbols = FileSymbols::default();
+        self.collect_definitions(program, &resolved_names, &mut symbols.definitions, content);
+
+        let walker = ReferenceWalker {
+            names: &resolved_names,
+            source: content,
+        };
+        walker.walk_program(program, &mut symbols);
+        symbols
+    }
+}
+
+/// Walks the AST recording references into a [`FileSymbols`].
+struct ReferenceWalker<'a, 'arena> {
+    names: &'a ResolvedNames<'arena>,
+    source: &'a str,
+}
+
+impl ReferenceWalker<'_, '_> {
+    fn line(&self, offset: u32) -> usize {
+        let end = (offset as usize).min(self.source.len());
+        memchr::memchr_iter(b'\n', &self.source.as_bytes()[..end]).count() + 1
+    }
+
+    fn push(
+        &self,
+        symbols: &mut FileSymbols,
+        name: &str,
+        kind: SymbolKind,
+        fallback: Option<String>,
+        offset: u32,
+    ) {
+        let name = name.trim_start_matches('\\');
+        let short = name.rsplit('\\').next().unwrap_or(name);
+        if name.is_empty()
+            || (kind == SymbolKind::Class
+                && ["self", "static", "parent"]
+                    .iter()
+                    .any(|special| short.eq_ignore_ascii_case(special)))
+        {
+            return;
+        }
+        symbols.references.push(SymbolReference {
+            name: name.to_string(),
+            kind,
+            fallback,
+            line: self.line(offset),
+        });
+    }
+
+    /// Record a class name resolved at `identifier`.
+    fn class(&self, identifier: &Identifier<'_>, symbols: &mut FileSymbols) {
+        let start = identifier.span().start;
+        if let Some(name) = self.names.resolve(&start) {
+            self.push(symbols, name, SymbolKind::Class, None, start.offset);
+        }
+    }
+
+    /// Record a class name from an expression, if it is a plain name.
+    fn class_expression(&self, expression: &Expression<'_>, symbols: &mut FileSymbols) {
+        if let Expression::Identifier(identifier) = expression {
+            self.class(identifier, symbols);
+        }
+    }
+
+    /// Record a function called by name.
+    fn function(&self, expression: &Expression<'_>, symbols: &mut FileSymbols) {
+        let Expression::Identifier(identifier) = expression else {
+            return;
+        };
+        let start = identifier.span().start;
+        let Some(name) = self.names.resolve(&start) else {
+            return;
+        };
+        let fallback = (identifier.is_local() && !self.names.is_imported(&start))
+            .then(|| identifier.value().to_string())
+            .filter(|global| global != name);
+        self.push(symbols, name, SymbolKind::Function, fallback, start.offset);
+    }
+
+    fn use_item(
+        &self,
+        prefix: Option<&str>,
+        kind: SymbolKind,
+        item: &UseItem<'_>,
+        symbols: &mut FileSymbols,
+    ) {
+        let name = match prefix {
+            Some(prefix) => format!(
+                "{}\\{}",
+                prefix.trim_end_matches('\\'),
+                item.name.value().trim_start_matches('\\')
+            ),
+            None => item.name.value().to_string(),
+        };
+        self.push(symbols, &name, kind, None, item.name.span().start.offset);
+    }
+}
+
+const fn use_kind(use_type: Option<&UseType<'_>>) -> SymbolKind {
+    match use_type {
+        Some(UseType::Function(_)) => SymbolKind::Function,
+        Some(UseType::Const(_)) => SymbolKind::Constant,
+        None => SymbolKind::Class,
+    }
+}
+
+impl<'ast, 'arena> Walker<'ast, 'arena, FileSymbols> for ReferenceWalker<'_, 'arena> {
+    fn walk_in_use(&self, r#use: &'ast Use<'arena>, symbols: &mut FileSymbols) {
+        match &r#use.items {
+            UseItems::Sequence(sequence) => {
+                for item in &sequence.items {
+                    self.use_item(None, SymbolKind::Class, item, symbols);
+                }
+            }
+            UseItems::TypedSequence(sequence) => {
+                for item in &sequence.items {
+                    self.use_item(None, use_kind(Some(&sequence.r#type)), item, symbols);
+                }
+            }
+            UseItems::TypedList(list) => {
+                for item in &list.items {
+                    let kind = use_kind(Some(&list.r#type));
+                    self.use_item(Some(list.namespace.value()), kind, item, symbols);
+                }
+            }
+            UseItems::MixedList(list) => {
+                for item in &list.items {
+                    let kind = use_kind(item.r#type.as_ref());
+                    self.use_item(Some(list.namespace.value()), kind, &item.item, symbols);
+                }
+            }
+        }
+    }
+
+    fn walk_in_function(&self, function: &'ast Function<'arena>, symbols: &mut FileSymbols) {
+        if let Some(name) = self.names.resolve(&function.name.span.start) {
+            symbols
+                .functions
+                .push(name.trim_start_matches('\\').to_string());
+        }
+    }
+
+    fn walk_in_trait_use(&self, trait_use: &'ast TraitUse<'arena>, symbols: &mut FileSymbols) {
+        for name in &trait_use.trait_names {
+            self.class(name, symbols);
+        }
+    }
+
+    fn walk_in_extends(&self, extends: &'ast Extends<'arena>, symbols: &mut FileSymbols) {
+        for name in &extends.types {
+            self.class(name, symbols);
+        }
+    }
+
+    fn walk_in_implements(&self, implements: &'ast Implements<'arena>, symbols: &mut FileSymbols) {
+        for name in &implements.types {
+            self.class(name, symbols);
+        }
+    }
+
+    fn walk_in_hint(&self, hint: &'ast Hint<'arena>, symbols: &mut FileSymbols) {
+        if let Hint::Identifier(identifier) = hint {
+            self.class(identifier, symbols);
+        }
+    }
+
+    fn walk_in_attribute(&self, attribute: &'ast Attribute<'arena>, symbols: &mut FileSymbols) {
+        self.class(&attribute.name, symbols);
+    }
+
+    fn walk_in_function_call(&self, call: &'ast FunctionCall<'arena>, symbols: &mut FileSymbols) {
+        self.function(call.function, symbols);
+    }
+
+    fn walk_in_function_partial_application(
+        &self,
+        application: &'ast FunctionPartialApplication<'arena>,
+        symbols: &mut FileSymbols,
+    ) {
+        self.function(application.function, symbols);
+    }
+
+    fn walk_in_instantiation(
+        &self,
+        instantiation: &'ast Instantiation<'arena>,
+        symbols: &mut FileSymbols,
+    ) {
+        self.class_expression(instantiation.class, symbols);
+    }
+
+    fn walk_in_static_method_call(
+        &self,
+        call: &'ast StaticMethodCall<'arena>,
+        symbols: &mut FileSymbols,
+    ) {
+        self.class_expression(call.class, symbols);
+    }
+
+    fn walk_in_static_method_partial_application(
+        &self,
+        application: &'ast StaticMethodPartialApplication<'arena>,
+        symbols: &mut FileSymbols,
+    ) {
+        self.class_expression(application.class, symbols);
+    }
+
+    fn walk_in_static_property_access(
+        &self,
+        access: &'ast StaticPropertyAccess<'arena>,
+        symbols: &mut FileSymbols,
+    ) {
+        self.class_expression(access.class, symbols);
+    }
+
+    fn walk_in_class_constant_access(
+        &self,
+        access: &'ast ClassConstantAccess<'arena>,
+        symbols: &mut FileSymbols,
+    ) {
+        self.class_expression(access.class, symbols);
+    }
+
+    fn walk_in_binary(&self, binary: &'ast Binary<'arena>, symbols: &mut FileSymbols) {
+        if let BinaryOperator::Instanceof(_) = binary.operator {
+            self.class_expression(binary.rhs, symbols);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn references(content: &str) -> Vec<(String, SymbolKind)> {
+        PhpParser::new()
+            .parse_symbols(content)
+            .references
+            .into_iter()
+            .map(|r| (r.name, r.kind))
+            .collect()
+    }
+
+    #[test]
+    fn collects_class_references_through_aliases() {
+        let content = r"<?php
+namespace App;
+
+use Psr\Log\LoggerInterface as Logger;
+use Symfony\Component\Console\{Command\Command, Input\InputInterface};
+
+#[Route('/')]
+final class Job extends Command implements \Countable
+{
+    use Concerns\Retries;
+
+    public function __construct(private Logger $logger) {}
+
+    public function run(InputInterface $input): ?Result
+    {
+        if ($input instanceof \Acme\Input) {
+            return new Result(Carbon\Carbon::now(), Status::Done, self::class);
+        }
+        return static::make();
+    }
+}
+";
+        let refs = references(content);
+        let classes: Vec<_> = refs
+            .iter()
+            .filter(|(_, kind)| *kind == SymbolKind::Class)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        for expected in [
+            "Psr\\Log\\LoggerInterface",
+            "Symfony\\Component\\Console\\Command\\Command",
+            "Symfony\\Component\\Console\\Input\\InputInterface",
+            "App\\Route",
+            "Countable",
+            "App\\Concerns\\Retries",
+            "App\\Result",
+            "Acme\\Input",
+            "App\\Carbon\\Carbon",
+            "App\\Status",
+        ] {
+            assert!(
+                classes.contains(&expected),
+                "missing {expected}: {classes:?}"
+            );
+        }
+        assert!(!classes.iter().any(|name| name.ends_with("self")));
+    }
+
+    #[test]
+    fn unqualified_function_calls_fall_back_to_global_names() {
+        let content = r"<?php
+namespace App;
+
+use function Amp\async;
+
+function helper(): void {}
+
+async(fn () => strlen(\GuzzleHttp\Psr7\uri_for('x')));
+";
+        let symbols = PhpParser::new().parse_symbols(content);
+        assert_eq!(symbols.functions, ["App\\helper"]);
+
+        let calls: Vec<_> = symbols
+            .references
+            .iter()
+            .filter(|r| r.kind == SymbolKind::Function)
+            .map(|r| (r.name.as_str(), r.fallback.as_deref(), r.line))
+            .collect();
+        assert!(calls.contains(&("Amp\\async", None, 4)));
+        assert!(calls.contains(&("Amp\\async", None, 8)));
+        assert!(calls.contains(&("App\\strlen", Some("strlen"), 8)));
+        assert!(calls.contains(&("GuzzleHttp\\Psr7\\uri_for", None, 8)));
+    }
+}
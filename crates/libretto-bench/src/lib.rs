@@ -164,10 +164,10 @@ pub fn peak_memory_bytes() -> Option<u64> {
     for line in status.lines() {
         if line.starts_with("VmPeak:") {
             let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 2 {
-                if let Ok(kb) = parts[1].parse::<u64>() {
-                    return Some(kb * 1024);
-                }
+            if parts.len() >= 2
+                && let Ok(kb) = parts[1].parse::<u64>()
+            {
+                return Some(kb * 1024);
             }
         }
     }
@@ -187,10 +187,10 @@ pub fn current_rss_bytes() -> Option<u64> {
     for line in status.lines() {
         if line.starts_with("VmRSS:") {
             let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 2 {
-                if let Ok(kb) = parts[1].parse::<u64>() {
-                    return Some(kb * 1024);
-                }
+            if parts.len() >= 2
+                && let Ok(kb) = parts[1].parse::<u64>()
+            {
+                return Some(kb * 1024);
             }
         }
     }
@@ -231,6 +231,8 @@ pub enum CacheEntryType {
     Autoloader,
     /// VCS clone (bare repository).
     VcsClone,
+    /// Dependency resolution, keyed by its inputs.
+    Resolution,
 }
 
 impl CacheEntryType {
@@ -244,6 +246,7 @@ impl CacheEntryType {
             Self::DependencyGraph => config.graph_ttl,
             Self::Autoloader => config.default_ttl,
             Self::VcsClone => config.default_ttl,
+            Self::Resolution => config.graph_ttl,
         }
     }
 
@@ -257,6 +260,7 @@ impl CacheEntryType {
             Self::DependencyGraph => "graphs",
             Self::Autoloader => "autoload",
             Self::VcsClone => "vcs",
+            Self::Resolution => "resolutions",
         }
     }
 }
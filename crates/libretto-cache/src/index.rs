@@ -76,6 +76,7 @@ impl IndexEntry {
             3 => CacheEntryType::DependencyGraph,
             4 => CacheEntryType::Autoloader,
             5 => CacheEntryType::VcsClone,
+            6 => CacheEntryType::Resolution,
             _ => CacheEntryType::Package,
         }
     }
@@ -103,6 +104,7 @@ impl ArchivedIndexEntry {
             3 => CacheEntryType::DependencyGraph,
             4 => CacheEntryType::Autoloader,
             5 => CacheEntryType::VcsClone,
+            6 => CacheEntryType::Resolution,
             _ => CacheEntryType::Package,
         }
     }
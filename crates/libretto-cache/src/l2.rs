@@ -50,6 +50,7 @@ impl L2Cache {
             CacheEntryType::DependencyGraph,
             CacheEntryType::Autoloader,
             CacheEntryType::VcsClone,
+            CacheEntryType::Resolution,
         ] {
             let subdir = root.join(entry_type.subdir());
             fs::create_dir_all(&subdir).map_err(|e| Error::io(&subdir, e))?;
@@ -350,6 +351,7 @@ impl L2Cache {
             CacheEntryType::DependencyGraph,
             CacheEntryType::Autoloader,
             CacheEntryType::VcsClone,
+            CacheEntryType::Resolution,
         ] {
             let subdir = self.root.join(entry_type.subdir());
             if subdir.exists() {
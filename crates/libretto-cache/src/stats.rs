@@ -124,11 +124,9 @@ impl CacheStats {
 
         let lookup_count = self.lookup_count.load(Ordering::Relaxed);
         let total_lookup_time_us = self.total_lookup_time_us.load(Ordering::Relaxed);
-        let avg_lookup_time = if lookup_count > 0 {
-            Duration::from_micros(total_lookup_time_us / lookup_count)
-        } else {
-            Duration::ZERO
-        };
+        let avg_lookup_time = total_lookup_time_us
+            .checked_div(lookup_count)
+            .map_or(Duration::ZERO, Duration::from_micros);
 
         let bloom_tn = self.bloom_true_negatives.load(Ordering::Relaxed);
         let bloom_fp = self.bloom_false_positives.load(Ordering::Relaxed);
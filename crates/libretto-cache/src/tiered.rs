@@ -40,6 +40,8 @@ pub enum ClearPattern {
     Graphs,
     /// Clear all autoloaders.
     Autoloaders,
+    /// Clear all cached resolutions.
+    Resolutions,
     /// Clear everything.
     All,
 }
@@ -55,6 +57,7 @@ impl ClearPattern {
             Self::Vcs => vec![CacheEntryType::VcsClone],
             Self::Graphs => vec![CacheEntryType::DependencyGraph],
             Self::Autoloaders => vec![CacheEntryType::Autoloader],
+            Self::Resolutions => vec![CacheEntryType::Resolution],
             Self::All => vec![
                 CacheEntryType::Package,
                 CacheEntryType::Metadata,
@@ -62,6 +65,7 @@ impl ClearPattern {
                 CacheEntryType::VcsClone,
                 CacheEntryType::DependencyGraph,
                 CacheEntryType::Autoloader,
+                CacheEntryType::Resolution,
             ],
         }
     }
@@ -78,6 +82,7 @@ impl std::str::FromStr for ClearPattern {
             "vcs" | "git" => Ok(Self::Vcs),
             "graphs" | "graph" => Ok(Self::Graphs),
             "autoloaders" | "autoload" => Ok(Self::Autoloaders),
+            "resolutions" | "resolution" => Ok(Self::Resolutions),
             "all" | "*" => Ok(Self::All),
             _ => Err(()),
         }
@@ -547,7 +552,7 @@ impl TieredCache {
 
         // Sort by access time (most recent first)
         let mut sorted = entries;
-        sorted.sort_by(|a, b| b.accessed_at.cmp(&a.accessed_at));
+        sorted.sort_by_key(|e| std::cmp::Reverse(e.accessed_at));
 
         for entry in sorted.into_iter().take(max_entries) {
             if entry.is_expired() {
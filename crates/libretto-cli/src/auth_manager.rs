@@ -525,36 +525,20 @@ impl GitHubRateLimitInfo {
     }
 }
 
-/// Parse rate limit information from HTTP response headers.
-pub fn parse_rate_limit_headers(
-    url: &str,
-    headers: &reqwest::header::HeaderMap,
-) -> Option<GitHubRateLimitInfo> {
-    let limit = headers
-        .get("x-ratelimit-limit")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(60);
-
-    let reset = headers
-        .get("x-ratelimit-reset")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(0);
-
-    Some(GitHubRateLimitInfo {
-        url: url.to_string(),
-        limit,
-        reset,
-    })
+/// Hosts a GitHub OAuth token is sent to.
+const GITHUB_HOSTS: &[&str] = &["github.com", "api.github.com", "codeload.github.com"];
+
+/// Check if `url` points at GitHub (by host, not by substring).
+pub fn is_github_url(url: &str) -> bool {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+        .is_some_and(|host| GITHUB_HOSTS.contains(&host.as_str()))
 }
 
 /// Check if an error is a GitHub rate limit error.
 pub fn is_github_rate_limit_error(url: &str, status: u16) -> bool {
-    (url.contains("api.github.com")
-        || url.contains("github.com")
-        || url.contains("codeload.github.com"))
-        && (status == 403 || status == 429)
+    is_github_url(url) && (status == 403 || status == 429)
 }
 
 #[cfg(test)]
@@ -592,4 +576,16 @@ mod tests {
             200
         ));
     }
+
+    #[test]
+    fn github_urls_are_matched_by_host() {
+        assert!(is_github_url("https://github.com/acme/lib/archive/v1.zip"));
+        assert!(is_github_url(
+            "https://codeload.github.com/acme/lib/zip/abc"
+        ));
+        assert!(!is_github_url(
+            "https://mirror.example/github.com/acme/lib.zip"
+        ));
+        assert!(!is_github_url("https://github.com.example/acme/lib.zip"));
+    }
 }
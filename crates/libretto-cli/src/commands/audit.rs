@@ -1,12 +1,13 @@
 //! Audit command implementation.
 
+use crate::output::warning;
+use crate::security::{audit_configs, ignore_policy};
 use anyhow::Result;
 use clap::Args;
 use console::style;
-use libretto_audit::Auditor;
+use libretto_audit::{AuditReport, Auditor, IgnorePolicy, LockLocation, ReportFormat, Severity};
 use libretto_core::PackageId;
 use libretto_repository::Repository;
-use semver::Version;
 use serde::Deserialize;
 use sonic_rs::{JsonContainerTrait, JsonValueTrait};
 use std::collections::HashMap;
@@ -15,8 +16,8 @@ use tracing::info;
 /// Arguments for the audit command.
 #[derive(Args, Debug, Clone)]
 pub struct AuditArgs {
-    /// Output format (table, json)
-    #[arg(short, long, default_value = "table")]
+    /// Output format
+    #[arg(short, long, default_value = "table", value_parser = ["table", "json", "sarif", "junit", "github"])]
     pub format: String,
 
     /// Don't fail on vulnerabilities
@@ -34,6 +35,18 @@ pub struct AuditArgs {
     /// Suggest safe versions for vulnerable packages
     #[arg(long)]
     pub suggest_versions: bool,
+
+    /// Ignore advisories of these severities (e.g. low,medium)
+    #[arg(long, value_delimiter = ',', value_parser = parse_severity)]
+    pub ignore_severity: Vec<Severity>,
+
+    /// Read advisories from the offline database (see `audit:db`)
+    #[arg(long)]
+    pub offline: bool,
+}
+
+fn parse_severity(value: &str) -> Result<Severity, String> {
+    value.parse()
 }
 
 #[derive(Debug, Deserialize)]
@@ -54,11 +67,16 @@ struct LockPackage {
 pub async fn run(args: AuditArgs) -> Result<()> {
     info!("running audit command");
 
-    println!(
-        "{} {}",
-        style("Libretto").cyan().bold(),
-        style("Checking for security vulnerabilities...").dim()
-    );
+    // Machine-readable reports own stdout, progress output is skipped.
+    let report_format = args.format.parse::<ReportFormat>().ok();
+
+    if report_format.is_none() {
+        println!(
+            "{} {}",
+            style("Libretto").cyan().bold(),
+            style("Checking for security vulnerabilities...").dim()
+        );
+    }
 
     let lock_path = std::path::Path::new("composer.lock");
     if !lock_path.exists() {
@@ -73,61 +91,67 @@ pub async fn run(args: AuditArgs) -> Result<()> {
     let content = std::fs::read_to_string(lock_path)?;
     let lock: LockFile = sonic_rs::from_str(&content)?;
 
-    let mut packages_to_audit: Vec<(PackageId, Version)> = Vec::new();
+    let mut packages_to_audit: Vec<(PackageId, String)> = Vec::new();
     let mut package_constraints: HashMap<String, String> = HashMap::new();
 
+    let composer_path = std::path::Path::new("composer.json");
+    let composer: sonic_rs::Value = if composer_path.exists() {
+        sonic_rs::from_str(&std::fs::read_to_string(composer_path)?)?
+    } else {
+        sonic_rs::Value::new()
+    };
+
+    let mut policy = ignore_policy(&audit_configs(&composer));
+    if !args.ignore_severity.is_empty() {
+        policy = policy.with_ignored_severities(args.ignore_severity.clone());
+    }
+
     // Load constraints from composer.json if not in locked mode
     if !args.locked {
-        let composer_path = std::path::Path::new("composer.json");
-        if composer_path.exists() {
-            let composer_content = std::fs::read_to_string(composer_path)?;
-            let composer: sonic_rs::Value = sonic_rs::from_str(&composer_content)?;
-
-            if let Some(require) = composer.get("require").and_then(|v| v.as_object()) {
-                for (name, constraint) in require {
-                    if let Some(c) = constraint.as_str() {
-                        package_constraints.insert(name.to_string(), c.to_string());
-                    }
+        if let Some(require) = composer.get("require").and_then(|v| v.as_object()) {
+            for (name, constraint) in require {
+                if let Some(c) = constraint.as_str() {
+                    package_constraints.insert(name.to_string(), c.to_string());
                 }
             }
+        }
 
-            if let Some(require_dev) = composer.get("require-dev").and_then(|v| v.as_object()) {
-                for (name, constraint) in require_dev {
-                    if let Some(c) = constraint.as_str() {
-                        package_constraints.insert(name.to_string(), c.to_string());
-                    }
+        if let Some(require_dev) = composer.get("require-dev").and_then(|v| v.as_object()) {
+            for (name, constraint) in require_dev {
+                if let Some(c) = constraint.as_str() {
+                    package_constraints.insert(name.to_string(), c.to_string());
                 }
             }
         }
     }
 
-    // Collect packages from lock file
-    for pkg in &lock.packages {
+    // Collect packages from lock file, including dev packages
+    for pkg in lock.packages.iter().chain(&lock.packages_dev) {
         if let Some(id) = PackageId::parse(&pkg.name) {
-            let version_str = pkg.version.trim_start_matches('v');
-            if let Ok(ver) = Version::parse(version_str) {
-                packages_to_audit.push((id, ver));
-            }
+            packages_to_audit.push((id, pkg.version.clone()));
         }
     }
 
-    // Include dev packages
-    for pkg in &lock.packages_dev {
-        if let Some(id) = PackageId::parse(&pkg.name) {
-            let version_str = pkg.version.trim_start_matches('v');
-            if let Ok(ver) = Version::parse(version_str) {
-                packages_to_audit.push((id, ver));
-            }
-        }
+    if report_format.is_none() {
+        println!(
+            "{}",
+            style(format!("Auditing {} packages...", packages_to_audit.len())).dim()
+        );
     }
 
-    println!(
-        "{}",
-        style(format!("Auditing {} packages...", packages_to_audit.len())).dim()
-    );
+    let auditor =
+        Auditor::new()?.with_advisory_database(super::audit_db::advisory_database(args.offline)?);
+    let mut report = auditor.audit(&packages_to_audit).await?;
+    apply_ignores(&mut report, &policy);
 
-    let auditor = Auditor::new()?;
-    let report = auditor.audit(&packages_to_audit).await?;
+    if let Some(format) = report_format {
+        let lock = LockLocation::new("composer.lock", &content);
+        println!("{}", report.render(format, &lock)?.trim_end());
+        if !report.passes() && !args.no_fail {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
     println!();
 
@@ -136,6 +160,7 @@ pub async fn run(args: AuditArgs) -> Result<()> {
             "{} No security vulnerabilities found",
             style("Success:").green().bold()
         );
+        print_ignored(&report);
         return Ok(());
     }
 
@@ -197,18 +222,54 @@ pub async fn run(args: AuditArgs) -> Result<()> {
         }
     }
 
-    if report.has_critical() && !args.no_fail {
+    print_ignored(&report);
+
+    if !report.passes() && !args.no_fail {
         println!();
         println!(
-            "{} Critical vulnerabilities found",
+            "{} Critical or high severity vulnerabilities found",
             style("Error:").red().bold()
         );
         std::process::exit(1);
     }
 
-    if !report.passes() && !args.no_fail {
-        std::process::exit(1);
+    Ok(())
+}
+
+/// Remove findings accepted by `policy` from `report`, warning about expired rules.
+pub fn apply_ignores(report: &mut AuditReport, policy: &IgnorePolicy) {
+    let today = chrono::Utc::now().date_naive();
+    for rule in policy.expired(today) {
+        if let Some(until) = rule.until {
+            warning(&format!(
+                "audit.ignore rule for {} expired on {until}, its advisories are reported again",
+                rule.target
+            ));
+        }
     }
+    report.apply_ignores(policy, today);
+}
 
-    Ok(())
+/// List findings left out by ignore rules.
+pub fn print_ignored(report: &AuditReport) {
+    if report.ignored.is_empty() {
+        return;
+    }
+
+    println!();
+    println!(
+        "{}",
+        style(format!("Ignored {} advisories:", report.ignored.len())).dim()
+    );
+    for ignored in &report.ignored {
+        let vuln = &ignored.vulnerability;
+        println!(
+            "  {} {} ({} {}): {}",
+            style(format!("[{}]", vuln.severity)).dim(),
+            vuln.advisory_id,
+            vuln.package,
+            ignored.version,
+            ignored.reason
+        );
+    }
 }
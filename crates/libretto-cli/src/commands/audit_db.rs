@@ -0,0 +1,221 @@
+//! Audit database commands - manage the offline advisory database.
+//!
+//! Advisories come from the Packagist advisory API. An imported database is
+//! read instead only when asked for: by `audit --offline`, or when the
+//! network is disabled with `COMPOSER_DISABLE_NETWORK`.
+
+use crate::output::{format_bytes, header, info, success, warning};
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use console::style;
+use futures::StreamExt;
+use libretto_audit::{AdvisoryDatabase, AdvisoryFormat, AdvisoryIndex, read_osv_zip};
+use libretto_config::ComposerEnvVar;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// OSV dump of all Packagist advisories.
+const OSV_PACKAGIST_URL: &str =
+    "https://osv-vulnerabilities.storage.googleapis.com/Packagist/all.zip";
+
+/// Age after which the offline database is reported as stale.
+const STALE_AFTER_DAYS: i64 = 7;
+
+/// Arguments for the audit:db command
+#[derive(Args, Debug, Clone)]
+pub struct AuditDbArgs {
+    #[command(subcommand)]
+    pub action: AuditDbAction,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum AuditDbAction {
+    /// Download an OSV advisory dump and rebuild the database
+    Update {
+        /// URL of an OSV zip dump
+        #[arg(long, default_value = OSV_PACKAGIST_URL)]
+        url: String,
+    },
+
+    /// Build the database from a local `FriendsOfPHP` checkout, OSV directory or zip
+    Import {
+        /// Path to the advisory source
+        #[arg(required = true)]
+        path: PathBuf,
+
+        /// Source format
+        #[arg(short = 'f', long, value_enum, default_value = "auto")]
+        format: SourceFormat,
+    },
+
+    /// Show which advisory database is in use
+    Status,
+}
+
+/// Format of an advisory source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SourceFormat {
+    /// Detect from the path
+    Auto,
+    /// `FriendsOfPHP/security-advisories` YAML checkout
+    Friendsofphp,
+    /// OSV JSON directory or zip
+    Osv,
+    /// Libretto advisory index
+    Index,
+}
+
+/// Run the audit:db command
+pub async fn run(args: AuditDbArgs) -> Result<()> {
+    let db_path = database_path()?;
+
+    match args.action {
+        AuditDbAction::Update { url } => {
+            header("Updating advisory database");
+            info(&format!("Downloading {url}"));
+            let (file, size) = download(&url)
+                .await
+                .with_context(|| format!("Failed to download {url}"))?;
+            info(&format!("Downloaded {}", format_bytes(size)));
+
+            let advisories = read_osv_zip(std::io::BufReader::new(file))?;
+            let index = AdvisoryIndex::new(url, advisories);
+            index.save(&db_path)?;
+            report_saved(&index, &db_path);
+        }
+        AuditDbAction::Import { path, format } => {
+            header("Importing advisory database");
+            let format = match format {
+                SourceFormat::Auto => AdvisoryFormat::detect(&path).with_context(|| {
+                    format!(
+                        "Could not detect the advisory format of {}, use --format",
+                        path.display()
+                    )
+                })?,
+                SourceFormat::Friendsofphp => AdvisoryFormat::FriendsOfPhp,
+                SourceFormat::Osv => AdvisoryFormat::Osv,
+                SourceFormat::Index => AdvisoryFormat::Index,
+            };
+            info(&format!("Reading {} ({format})", path.display()));
+
+            let index = AdvisoryIndex::import(&path, format)?;
+            index.save(&db_path)?;
+            report_saved(&index, &db_path);
+        }
+        AuditDbAction::Status => {
+            header("Advisory database");
+            if !db_path.exists() {
+                info("No offline advisory database, advisories are fetched from Packagist");
+                info("Run `libretto audit:db import <path>` or `libretto audit:db update`");
+                return Ok(());
+            }
+
+            let index = AdvisoryIndex::load(&db_path)?;
+            let age = chrono::Utc::now() - index.built_at();
+            println!("  {:<12} {}", style("Path").dim(), db_path.display());
+            println!("  {:<12} {}", style("Source").dim(), index.source());
+            println!(
+                "  {:<12} {} ({} days ago)",
+                style("Built").dim(),
+                index.built_at().format("%Y-%m-%d %H:%M:%S UTC"),
+                age.num_days()
+            );
+            println!(
+                "  {:<12} {}",
+                style("Packages").dim(),
+                index.package_count()
+            );
+            println!(
+                "  {:<12} {}",
+                style("Advisories").dim(),
+                index.advisory_count()
+            );
+            println!();
+            info("Used by `audit --offline` and when COMPOSER_DISABLE_NETWORK is set");
+            if age.num_days() > STALE_AFTER_DAYS {
+                warning("The advisory database is stale, run `libretto audit:db update`");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Open the advisory database: the Packagist advisory API, or the imported
+/// offline index when `offline` is set or the network is disabled.
+pub fn advisory_database(offline: bool) -> Result<AdvisoryDatabase> {
+    let network_disabled = ComposerEnvVar::DisableNetwork.as_bool().unwrap_or(false);
+    if offline || network_disabled {
+        if let Some(index) = offline_index() {
+            return Ok(AdvisoryDatabase::new()?.with_index(index));
+        }
+        if offline {
+            anyhow::bail!(
+                "No offline advisory database, run `libretto audit:db update` or `libretto audit:db import <path>`"
+            );
+        }
+    }
+    Ok(AdvisoryDatabase::new()?)
+}
+
+/// Check whether an offline advisory database has been imported.
+pub fn has_offline_database() -> bool {
+    database_path().is_ok_and(|path| path.exists())
+}
+
+fn offline_index() -> Option<AdvisoryIndex> {
+    let path = database_path().ok().filter(|path| path.exists())?;
+    match AdvisoryIndex::load(&path) {
+        Ok(index) => {
+            let age = (chrono::Utc::now() - index.built_at()).num_days();
+            if age > STALE_AFTER_DAYS {
+                warning(&format!(
+                    "The offline advisory database is {age} days old, run `libretto audit:db update`"
+                ));
+            }
+            Some(index)
+        }
+        Err(e) => {
+            warning(&format!("Ignoring offline advisory database: {e}"));
+            None
+        }
+    }
+}
+
+/// Stream `url` into a temporary file, returning it rewound with its size.
+async fn download(url: &str) -> Result<(std::fs::File, u64)> {
+    let client = reqwest::Client::builder()
+        .user_agent(format!("libretto/{}", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(Duration::from_secs(10))
+        .read_timeout(Duration::from_secs(60))
+        .build()?;
+    let response = client.get(url).send().await?.error_for_status()?;
+
+    let mut file = tempfile::tempfile()?;
+    let mut size = 0u64;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk)?;
+        size += chunk.len() as u64;
+    }
+    file.rewind()?;
+    Ok((file, size))
+}
+
+fn report_saved(index: &AdvisoryIndex, path: &Path) {
+    success(&format!(
+        "Imported {} advisories for {} packages into {}",
+        index.advisory_count(),
+        index.package_count(),
+        path.display()
+    ));
+}
+
+/// Location of the offline advisory database.
+fn database_path() -> Result<PathBuf> {
+    let dirs = directories::ProjectDirs::from("", "", "libretto")
+        .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    Ok(dirs.data_dir().join("advisories.idx"))
+}
@@ -350,7 +350,7 @@ fn mask_token(token: &str) -> String {
     }
 }
 
-fn get_global_config_path() -> Result<PathBuf> {
+pub fn get_global_config_path() -> Result<PathBuf> {
     let home = std::env::var("COMPOSER_HOME")
         .map(PathBuf::from)
         .or_else(|_| {
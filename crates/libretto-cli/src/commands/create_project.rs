@@ -259,9 +259,12 @@ pub async fn run(args: CreateProjectArgs) -> Result<()> {
                 concurrency: 64,
                 audit: false,
                 fail_on_audit: false,
+                no_security_blocking: false,
                 verify_checksums: false,
+                strict_lock: false,
                 php_version: None,
                 no_php_check: false,
+                download: crate::commands::install::DownloadArgs::default(),
             };
 
             crate::commands::install::run(install_args).await?;
@@ -281,27 +284,30 @@ pub async fn run(args: CreateProjectArgs) -> Result<()> {
         let script_config = ScriptConfig {
             working_dir: current_dir.clone(),
             dev_mode: !args.no_dev,
+            timeout: scripts::process_timeout(&composer_json),
             ..Default::default()
         };
 
         // Run post-root-package-install scripts
         if let Some(result) =
             scripts::run_root_package_install_scripts(&composer_json, &script_config)?
-            && !result.success {
-                warning(&format!(
-                    "Post-root-package-install script warning: {}",
-                    result.error.unwrap_or_default()
-                ));
-            }
+            && !result.success
+        {
+            warning(&format!(
+                "Post-root-package-install script warning: {}",
+                result.error.unwrap_or_default()
+            ));
+        }
 
         // Run post-create-project-cmd scripts
         if let Some(result) = scripts::run_create_project_scripts(&composer_json, &script_config)?
-            && !result.success {
-                warning(&format!(
-                    "Post-create-project script warning: {}",
-                    result.error.unwrap_or_default()
-                ));
-            }
+            && !result.success
+        {
+            warning(&format!(
+                "Post-create-project script warning: {}",
+                result.error.unwrap_or_default()
+            ));
+        }
     }
 
     success(&format!("Project '{project_name}' created successfully!"));
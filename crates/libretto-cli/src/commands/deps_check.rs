@@ -0,0 +1,652 @@
+//! Dependency check command - find unused and undeclared dependencies.
+//!
+//! Parses the PHP files in the root package's `autoload` paths, resolves
+//! every class and function they reference to the installed package that
+//! autoloads it, and compares the result with composer.json:
+//!
+//! - `require` entries no production code references are unused;
+//! - packages referenced but only installed transitively are undeclared;
+//! - packages referenced from production code but only in `require-dev`
+//!   will be missing from `--no-dev` installs.
+//!
+//! Findings can be silenced per package in composer.json:
+//!
+//! ```json
+//! {
+//!     "extra": {
+//!         "deps-check": {
+//!             "ignore-unused": ["symfony/polyfill-*"],
+//!             "ignore-undeclared": ["psr/log"],
+//!             "ignore-dev": []
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::fetcher::matches_pattern;
+use crate::output::table::Table;
+use crate::output::{error, header, info, success};
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use libretto_autoloader::{ExcludePattern, FileSymbols, PhpParser, SymbolKind, SymbolReference};
+use libretto_lockfile::{ComposerLock, LockedPackage};
+use rayon::prelude::*;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Package types that ship no code for the project to reference.
+const CODELESS_TYPES: &[&str] = &["metapackage", "composer-plugin", "composer-installer"];
+
+/// Arguments for the deps:check command
+#[derive(Args, Debug, Clone)]
+pub struct DepsCheckArgs {
+    /// Output format (text, json)
+    #[arg(short = 'f', long, default_value = "text")]
+    pub format: String,
+}
+
+/// An installed package symbols can belong to.
+#[derive(Debug, Clone)]
+struct Owner {
+    name: String,
+    /// Names the package replaces or provides, which a requirement on
+    /// counts as a requirement on the package itself.
+    aliases: Vec<String>,
+}
+
+/// Maps class and function names to the packages that autoload them.
+#[derive(Debug, Default)]
+struct SymbolIndex {
+    owners: Vec<Owner>,
+    /// Lowercased class and function names found in classmap and files
+    /// autoload entries.
+    exact: HashMap<String, usize>,
+    /// Lowercased PSR-4/PSR-0 prefixes, longest first.
+    prefixes: Vec<(String, usize)>,
+}
+
+impl SymbolIndex {
+    fn add_owner(&mut self, pkg: &LockedPackage) -> usize {
+        self.owners.push(Owner {
+            name: pkg.name.to_ascii_lowercase(),
+            aliases: pkg
+                .replace
+                .keys()
+                .chain(pkg.provide.keys())
+                .map(|name| name.to_ascii_lowercase())
+                .collect(),
+        });
+        self.owners.len() - 1
+    }
+
+    fn add_prefix(&mut self, prefix: &str, owner: usize) {
+        if !prefix.is_empty() {
+            self.prefixes.push((prefix.to_ascii_lowercase(), owner));
+        }
+    }
+
+    fn add_symbols(&mut self, symbols: &FileSymbols, owner: usize) {
+        let names = symbols
+            .definitions
+            .iter()
+            .map(|def| &def.fqcn)
+            .chain(&symbols.functions);
+        for name in names {
+            self.exact.entry(name.to_ascii_lowercase()).or_insert(owner);
+        }
+    }
+
+    fn finish(&mut self) {
+        self.prefixes
+            .sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+    }
+
+    /// Owner of a referenced symbol, if an installed package autoloads it.
+    fn owner(&self, reference: &SymbolReference) -> Option<usize> {
+        let name = reference.name.to_ascii_lowercase();
+        match reference.kind {
+            SymbolKind::Class => self.exact.get(&name).copied().or_else(|| {
+                self.prefixes
+                    .iter()
+                    .find(|(prefix, _)| name.starts_with(prefix.as_str()))
+                    .map(|&(_, owner)| owner)
+            }),
+            SymbolKind::Function | SymbolKind::Constant => {
+                self.exact.get(&name).copied().or_else(|| {
+                    let fallback = reference.fallback.as_ref()?.to_ascii_lowercase();
+                    self.exact.get(&fallback).copied()
+                })
+            }
+        }
+    }
+}
+
+/// Where a package is first referenced, and how often.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Usage {
+    package: String,
+    symbol: String,
+    file: String,
+    line: usize,
+    references: usize,
+}
+
+/// Findings of a dependency check.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Report {
+    unused: Vec<String>,
+    undeclared: Vec<Usage>,
+    dev_in_production: Vec<Usage>,
+}
+
+impl Report {
+    fn is_clean(&self) -> bool {
+        self.unused.is_empty() && self.undeclared.is_empty() && self.dev_in_production.is_empty()
+    }
+}
+
+/// Per-finding allowlists from `extra.deps-check`.
+#[derive(Debug, Default)]
+struct Allowlist {
+    unused: Vec<String>,
+    undeclared: Vec<String>,
+    dev: Vec<String>,
+}
+
+impl Allowlist {
+    fn from_composer(composer: &Value) -> Self {
+        let list = |key: &str| -> Vec<String> {
+            composer
+                .get("extra")
+                .and_then(|extra| extra.get("deps-check"))
+                .and_then(|config| config.get(key))
+                .and_then(|v| v.as_array())
+                .map(|patterns| {
+                    patterns
+                        .iter()
+                        .filter_map(|p| p.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Self {
+            unused: list("ignore-unused"),
+            undeclared: list("ignore-undeclared"),
+            dev: list("ignore-dev"),
+        }
+    }
+}
+
+/// Run the deps:check command
+pub async fn run(args: DepsCheckArgs) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let composer_path = cwd.join("composer.json");
+    if !composer_path.exists() {
+        bail!("composer.json not found");
+    }
+    let composer: Value = sonic_rs::from_str(
+        &std::fs::read_to_string(&composer_path).context("Failed to read composer.json")?,
+    )
+    .context("Failed to parse composer.json")?;
+    let lock_path = cwd.join("composer.lock");
+    if !lock_path.exists() {
+        bail!("composer.lock not found - run 'libretto install' first");
+    }
+    let lock: ComposerLock = sonic_rs::from_str(
+        &std::fs::read_to_string(&lock_path).context("Failed to read composer.lock")?,
+    )
+    .context("Failed to parse composer.lock")?;
+
+    if args.format != "json" {
+        header("Checking dependency usage");
+    }
+
+    let files = root_files(&composer, &cwd);
+    let scanned: Vec<(PathBuf, FileSymbols)> = files
+        .par_iter()
+        .filter_map(|path| {
+            let symbols = PhpParser::new().parse_symbols_file(path).ok()?;
+            Some((path.clone(), symbols))
+        })
+        .collect();
+    let index = build_index(
+        lock.packages.iter().chain(&lock.packages_dev),
+        &cwd.join("vendor"),
+    );
+
+    let require = requirement_names(&composer, "require");
+    let require_dev = requirement_names(&composer, "require-dev");
+    let checked: Vec<String> = lock
+        .packages
+        .iter()
+        .chain(&lock.packages_dev)
+        .filter(|pkg| require.contains(&pkg.name.to_ascii_lowercase()))
+        .filter(|pkg| {
+            pkg.autoload.is_some()
+                && !CODELESS_TYPES.contains(&pkg.package_type.as_deref().unwrap_or("library"))
+        })
+        .map(|pkg| pkg.name.to_ascii_lowercase())
+        .collect();
+
+    let report = analyze(
+        &scanned
+            .iter()
+            .map(|(path, symbols)| {
+                let relative = path.strip_prefix(&cwd).unwrap_or(path);
+                (relative.display().to_string(), symbols)
+            })
+            .collect::<Vec<_>>(),
+        &index,
+        &require,
+        &require_dev,
+        &checked,
+        &Allowlist::from_composer(&composer),
+    );
+
+    if args.format == "json" {
+        let usages = |usages: &[Usage]| -> Vec<Value> {
+            usages
+                .iter()
+                .map(|usage| {
+                    sonic_rs::json!({
+                        "package": usage.package,
+                        "symbol": usage.symbol,
+                        "file": usage.file,
+                        "line": usage.line,
+                        "references": usage.references,
+                    })
+                })
+                .collect()
+        };
+        let output = sonic_rs::json!({
+            "files": scanned.len(),
+            "unused": report.unused,
+            "undeclared": usages(&report.undeclared),
+            "dev-in-production": usages(&report.dev_in_production),
+        });
+        println!("{}", sonic_rs::to_string_pretty(&output)?);
+    } else {
+        print_report(
+            &report,
+            scanned.len(),
+            require.len().saturating_sub(checked.len()),
+        );
+    }
+
+    if !report.is_clean() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// PHP files in the root package's production autoload paths.
+fn root_files(composer: &Value, cwd: &Path) -> Vec<PathBuf> {
+    let Some(autoload) = composer.get("autoload") else {
+        return Vec::new();
+    };
+    let strings = |value: &Value| -> Vec<String> {
+        match value.as_str() {
+            Some(s) => vec![s.to_string()],
+            None => value
+                .as_array()
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|item| item.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    };
+
+    let mut paths = Vec::new();
+    for key in ["psr-4", "psr-0"] {
+        if let Some(mappings) = autoload.get(key).and_then(|v| v.as_object()) {
+            for (_, value) in mappings {
+                paths.extend(strings(value));
+            }
+        }
+    }
+    for key in ["classmap", "files"] {
+        if let Some(value) = autoload.get(key) {
+            paths.extend(strings(value));
+        }
+    }
+    let exclude = ExcludePattern::from_patterns(
+        &autoload
+            .get("exclude-from-classmap")
+            .map(strings)
+            .unwrap_or_default(),
+    );
+
+    let files: BTreeSet<PathBuf> = paths
+        .iter()
+        .flat_map(|path| php_files(&cwd.join(path)))
+        .filter(|path| !exclude.should_exclude_relative(path, cwd))
+        .collect();
+    files.into_iter().collect()
+}
+
+/// PHP files at `path`, recursing into directories.
+fn php_files(path: &Path) -> Vec<PathBuf> {
+    if path.is_file() {
+        return vec![path.to_path_buf()];
+    }
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "php"))
+        .map(walkdir::DirEntry::into_path)
+        .collect()
+}
+
+/// Index the symbols each locked package autoloads.
+///
+/// PSR-4 and PSR-0 prefixes come from the lock file alone; classmap and
+/// files entries are parsed from vendor/ when the package is installed.
+fn build_index<'a>(
+    packages: impl Iterator<Item = &'a LockedPackage>,
+    vendor_dir: &Path,
+) -> SymbolIndex {
+    let mut index = SymbolIndex::default();
+    let mut parsed: Vec<(usize, PathBuf)> = Vec::new();
+
+    for pkg in packages {
+        let owner = index.add_owner(pkg);
+        let Some(autoload) = &pkg.autoload else {
+            continue;
+        };
+        for prefix in autoload.psr4.keys().chain(autoload.psr0.keys()) {
+            index.add_prefix(prefix, owner);
+        }
+        let package_dir = vendor_dir.join(&pkg.name);
+        for path in autoload.classmap.iter().chain(&autoload.files) {
+            parsed.extend(
+                php_files(&package_dir.join(path))
+                    .into_iter()
+                    .map(|file| (owner, file)),
+            );
+        }
+    }
+
+    let symbols: Vec<(usize, FileSymbols)> = parsed
+        .par_iter()
+        .filter_map(|(owner, path)| Some((*owner, PhpParser::new().parse_symbols_file(path).ok()?)))
+        .collect();
+    for (owner, symbols) in &symbols {
+        index.add_symbols(symbols, *owner);
+    }
+    index.finish();
+    index
+}
+
+/// Lowercased non-platform package names in a composer.json link section.
+fn requirement_names(composer: &Value, key: &str) -> HashSet<String> {
+    composer
+        .get(key)
+        .and_then(|v| v.as_object())
+        .map(|links| {
+            links
+                .iter()
+                .map(|(name, _)| name.to_ascii_lowercase())
+                .filter(|name| !libretto_core::is_platform_package_name(name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Compare the symbols `files` reference with the declared requirements.
+///
+/// `checked` lists the `require` entries that ship code and can therefore
+/// be reported as unused.
+fn analyze(
+    files: &[(String, &FileSymbols)],
+    index: &SymbolIndex,
+    require: &HashSet<String>,
+    require_dev: &HashSet<String>,
+    checked: &[String],
+    allow: &Allowlist,
+) -> Report {
+    // Symbols the root package defines itself are never attributed to a
+    // dependency, even if a vendor prefix would match them.
+    let own: HashSet<String> = files
+        .iter()
+        .flat_map(|(_, symbols)| {
+            symbols
+                .definitions
+                .iter()
+                .map(|def| &def.fqcn)
+                .chain(&symbols.functions)
+        })
+        .map(|name| name.to_ascii_lowercase())
+        .collect();
+
+    let mut usages: BTreeMap<usize, Usage> = BTreeMap::new();
+    for (file, symbols) in files {
+        for reference in &symbols.references {
+            if own.contains(&reference.name.to_ascii_lowercase()) {
+                continue;
+            }
+            let Some(owner) = index.owner(reference) else {
+                continue;
+            };
+            usages
+                .entry(owner)
+                .and_modify(|usage| usage.references += 1)
+                .or_insert_with(|| Usage {
+                    package: index.owners[owner].name.clone(),
+                    symbol: reference.name.clone(),
+                    file: file.clone(),
+                    line: reference.line,
+                    references: 1,
+                });
+        }
+    }
+
+    let declared_in = |owner: &Owner, names: &HashSet<String>| {
+        names.contains(&owner.name) || owner.aliases.iter().any(|alias| names.contains(alias))
+    };
+
+    let mut report = Report::default();
+    let mut used: HashSet<&str> = HashSet::new();
+    for (&owner, usage) in &usages {
+        let owner = &index.owners[owner];
+        used.insert(&owner.name);
+        used.extend(owner.aliases.iter().map(String::as_str));
+
+        if declared_in(owner, require) {
+            continue;
+        }
+        if declared_in(owner, require_dev) {
+            if !is_allowed(&allow.dev, &owner.name) {
+                report.dev_in_production.push(usage.clone());
+            }
+        } else if !is_allowed(&allow.undeclared, &owner.name) {
+            report.undeclared.push(usage.clone());
+        }
+    }
+
+    report.unused = checked
+        .iter()
+        .filter(|name| !used.contains(name.as_str()) && !is_allowed(&allow.unused, name))
+        .cloned()
+        .collect();
+    report.unused.sort();
+    report
+}
+
+fn is_allowed(patterns: &[String], name: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| matches_pattern(pattern, name))
+}
+
+fn print_report(report: &Report, files: usize, unchecked: usize) {
+    info(&format!(
+        "Scanned {files} file{} in the root autoload paths",
+        if files == 1 { "" } else { "s" }
+    ));
+    if unchecked > 0 {
+        info(&format!(
+            "{unchecked} required package{} without autoloadable code not checked for use",
+            if unchecked == 1 { "" } else { "s" }
+        ));
+    }
+    println!();
+
+    if !report.unused.is_empty() {
+        error(&format!(
+            "{} required package{} not referenced by any code:",
+            report.unused.len(),
+            if report.unused.len() == 1 {
+                " is"
+            } else {
+                "s are"
+            }
+        ));
+        for name in &report.unused {
+            println!("  - {name}");
+        }
+        println!();
+    }
+
+    for (usages, message) in [
+        (
+            &report.undeclared,
+            "used but only installed as a dependency of another package",
+        ),
+        (
+            &report.dev_in_production,
+            "used by production code but only required in require-dev",
+        ),
+    ] {
+        if usages.is_empty() {
+            continue;
+        }
+        error(&format!(
+            "{} package{} {message}:",
+            usages.len(),
+            if usages.len() == 1 { " is" } else { "s are" }
+        ));
+        let mut table = Table::new();
+        table.headers(["Package", "First use", "Location", "References"]);
+        for usage in usages {
+            table.row([
+                usage.package.clone(),
+                usage.symbol.clone(),
+                format!("{}:{}", usage.file, usage.line),
+                usage.references.to_string(),
+            ]);
+        }
+        table.print();
+        println!();
+    }
+
+    if report.is_clean() {
+        success("All dependencies are declared and used");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locked(name: &str, prefix: &str) -> LockedPackage {
+        sonic_rs::from_str(&format!(
+            r#"{{"name":"{name}","version":"1.0.0","type":"library",
+                "autoload":{{"psr-4":{{"{prefix}":"src/"}}}}}}"#,
+            prefix = prefix.replace('\\', "\\\\")
+        ))
+        .unwrap()
+    }
+
+    fn names(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| (*name).to_string()).collect()
+    }
+
+    fn project() -> (SymbolIndex, FileSymbols) {
+        let packages = [
+            locked("psr/log", "Psr\\Log\\"),
+            locked("monolog/monolog", "Monolog\\"),
+            locked("guzzlehttp/guzzle", "GuzzleHttp\\"),
+            locked("phpunit/phpunit", "PHPUnit\\"),
+        ];
+        let mut index = build_index(packages.iter(), Path::new("/nonexistent"));
+        index
+            .exact
+            .insert("guzzlehttp\\describe_type".to_string(), 2);
+
+        let symbols = PhpParser::new().parse_symbols(
+            r"<?php
+namespace App;
+
+use Psr\Log\LoggerInterface;
+use PHPUnit\Framework\Assert;
+
+final class Service
+{
+    public function __construct(private LoggerInterface $logger) {}
+
+    public function check(): void
+    {
+        Assert::assertTrue(true);
+        \GuzzleHttp\describe_type($this);
+        new Service($this->logger);
+    }
+}
+",
+        );
+        (index, symbols)
+    }
+
+    #[test]
+    fn reports_unused_undeclared_and_dev_only_packages() {
+        let (index, symbols) = project();
+        let report = analyze(
+            &[("src/Service.php".to_string(), &symbols)],
+            &index,
+            &names(&["monolog/monolog"]),
+            &names(&["phpunit/phpunit"]),
+            &["monolog/monolog".to_string()],
+            &Allowlist::default(),
+        );
+
+        assert_eq!(report.unused, ["monolog/monolog"]);
+        let undeclared: Vec<_> = report
+            .undeclared
+            .iter()
+            .map(|u| (u.package.as_str(), u.symbol.as_str(), u.line))
+            .collect();
+        assert_eq!(
+            undeclared,
+            [
+                ("psr/log", "Psr\\Log\\LoggerInterface", 4),
+                ("guzzlehttp/guzzle", "GuzzleHttp\\describe_type", 14),
+            ]
+        );
+        assert_eq!(report.undeclared[0].references, 2);
+        assert_eq!(report.dev_in_production.len(), 1);
+        assert_eq!(report.dev_in_production[0].package, "phpunit/phpunit");
+    }
+
+    #[test]
+    fn allowlist_silences_findings() {
+        let (index, symbols) = project();
+        let allow = Allowlist {
+            unused: vec!["monolog/*".to_string()],
+            undeclared: vec!["psr/log".to_string(), "guzzlehttp/*".to_string()],
+            dev: vec!["phpunit/phpunit".to_string()],
+        };
+        let report = analyze(
+            &[("src/Service.php".to_string(), &symbols)],
+            &index,
+            &names(&["monolog/monolog"]),
+            &names(&["phpunit/phpunit"]),
+            &["monolog/monolog".to_string()],
+            &allow,
+        );
+        assert!(report.is_clean(), "{report:?}");
+    }
+}
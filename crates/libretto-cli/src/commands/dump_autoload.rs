@@ -1,11 +1,16 @@
 //! Dump-autoload command implementation.
 
-use crate::output::warning;
-use crate::scripts::{ScriptConfig, run_post_autoload_scripts, run_pre_autoload_scripts};
+use crate::output::events::{self, Event};
+use crate::output::{out, warning};
+use crate::plugins::PluginRuntime;
+use crate::scripts::{
+    ScriptConfig, process_timeout, run_post_autoload_scripts, run_pre_autoload_scripts,
+};
 use anyhow::Result;
 use clap::Args;
 use console::style;
 use libretto_autoloader::{AutoloadConfig, AutoloaderGenerator, OptimizationLevel};
+use libretto_plugin_system::Hook;
 use serde::Deserialize;
 use sonic_rs::Value;
 use std::collections::HashMap;
@@ -117,13 +122,13 @@ pub async fn run(args: DumpAutoloadArgs) -> Result<()> {
     let is_optimized = optimization_level >= OptimizationLevel::Optimized;
 
     if is_optimized {
-        println!(
+        out!(
             "{} Generating {}autoload files",
             style("Libretto").cyan().bold(),
             style("optimized ").yellow()
         );
     } else {
-        println!(
+        out!(
             "{} Generating autoload files",
             style("Libretto").cyan().bold()
         );
@@ -179,12 +184,29 @@ pub async fn run(args: DumpAutoloadArgs) -> Result<()> {
 
     info!("Loaded autoload configs from {} packages", package_count);
 
+    let cwd = std::env::current_dir()?;
+    let plugins = match composer_json_value.as_ref() {
+        Some(composer_json) => {
+            PluginRuntime::load(
+                composer_json,
+                &cwd,
+                &cwd.join(&vendor_dir),
+                "dump-autoload",
+                true,
+            )
+            .await
+        }
+        None => PluginRuntime::disabled(&cwd, &cwd.join(&vendor_dir), "dump-autoload"),
+    };
+    plugins.dispatch(Hook::PreAutoloadDump).await;
+
     // Pre-autoload-dump scripts
     if !args.no_scripts
         && let Some(composer_json) = composer_json_value.as_ref()
     {
         let script_config = ScriptConfig {
             working_dir: std::env::current_dir()?,
+            timeout: process_timeout(composer_json),
             ..Default::default()
         };
 
@@ -202,12 +224,15 @@ pub async fn run(args: DumpAutoloadArgs) -> Result<()> {
                 warning(psr_warning);
             }
 
+            plugins.dispatch(Hook::PostAutoloadDump).await;
+
             // Post-autoload-dump scripts
             if !args.no_scripts
                 && let Some(composer_json) = composer_json_value.as_ref()
             {
                 let script_config = ScriptConfig {
                     working_dir: std::env::current_dir()?,
+                    timeout: process_timeout(composer_json),
                     ..Default::default()
                 };
 
@@ -221,11 +246,12 @@ pub async fn run(args: DumpAutoloadArgs) -> Result<()> {
 
             let stats = generator.stats();
             let elapsed = start_time.elapsed();
+            events::emit(Event::autoload_dump(&stats, elapsed));
 
             // Calculate total classes (PSR-4 classes are lazily loaded, so we show classmap count)
             let total_classes = stats.classmap_entries;
 
-            println!(
+            out!(
                 "Generated {}autoload files containing {} classes",
                 if is_optimized {
                     style("optimized ").yellow().to_string()
@@ -236,28 +262,28 @@ pub async fn run(args: DumpAutoloadArgs) -> Result<()> {
             );
 
             // Show detailed breakdown
-            println!();
-            println!(
+            out!();
+            out!(
                 "   {} PSR-4 namespaces registered",
                 style(format!("{:>4}", stats.psr4_namespaces)).cyan()
             );
-            println!(
+            out!(
                 "   {} classmap entries generated",
                 style(format!("{:>4}", stats.classmap_entries)).cyan()
             );
-            println!(
+            out!(
                 "   {} files to include",
                 style(format!("{:>4}", stats.files_count)).cyan()
             );
-            println!(
+            out!(
                 "   {} packages scanned",
                 style(format!("{package_count:>4}")).cyan()
             );
 
             // Show timing
             let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
-            println!();
-            println!("   {} {:.1}ms", style("Done in").dim(), elapsed_ms);
+            out!();
+            out!("   {} {:.1}ms", style("Done in").dim(), elapsed_ms);
         }
         Err(e) => {
             eprintln!();
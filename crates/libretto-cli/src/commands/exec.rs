@@ -83,7 +83,10 @@ fn find_binary(vendor_bin: &PathBuf, name: &str) -> Result<PathBuf> {
         .filter_map(std::result::Result::ok)
         .filter_map(|e| {
             let name = e.file_name().to_string_lossy().to_string();
-            if name.ends_with(".bat") {
+            if std::path::Path::new(&name)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("bat"))
+            {
                 None
             } else {
                 Some(name)
@@ -128,7 +131,10 @@ fn list_binaries() -> Result<()> {
 
         // Skip batch files on Unix
         #[cfg(unix)]
-        if name.ends_with(".bat") {
+        if std::path::Path::new(&name)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("bat"))
+        {
             continue;
         }
 
@@ -161,6 +161,6 @@ fn count_funding_types(funds: &[FundingInfo]) -> Vec<(String, usize)> {
     }
 
     let mut result: Vec<_> = counts.into_iter().collect();
-    result.sort_by(|a, b| b.1.cmp(&a.1));
+    result.sort_by_key(|entry| std::cmp::Reverse(entry.1));
     result
 }
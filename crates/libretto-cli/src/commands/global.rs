@@ -139,6 +139,8 @@ async fn run_subcommand(command: &str, args: &[String]) -> Result<()> {
                 lock: args.contains(&"--lock".to_string()),
                 audit: args.contains(&"--audit".to_string()),
                 fail_on_audit: args.contains(&"--fail-on-audit".to_string()),
+                no_security_blocking: args.contains(&"--no-security-blocking".to_string()),
+                download: commands::install::DownloadArgs::default(),
             };
 
             commands::update::run(update_args).await
@@ -165,9 +167,12 @@ async fn run_subcommand(command: &str, args: &[String]) -> Result<()> {
                 concurrency: 64,
                 audit: args.contains(&"--audit".to_string()),
                 fail_on_audit: args.contains(&"--fail-on-audit".to_string()),
+                no_security_blocking: args.contains(&"--no-security-blocking".to_string()),
                 verify_checksums: args.contains(&"--verify-checksums".to_string()),
+                strict_lock: args.contains(&"--strict-lock".to_string()),
                 php_version: None,
                 no_php_check: false,
+                download: commands::install::DownloadArgs::default(),
             };
 
             commands::install::run(install_args).await
@@ -192,16 +197,30 @@ async fn run_subcommand(command: &str, args: &[String]) -> Result<()> {
         }
 
         "outdated" => {
+            let ignore: Vec<String> = args
+                .windows(2)
+                .filter(|pair| pair[0] == "--ignore")
+                .map(|pair| pair[1].clone())
+                .chain(
+                    args.iter()
+                        .filter_map(|a| a.strip_prefix("--ignore="))
+                        .map(String::from),
+                )
+                .collect();
             let outdated_args = commands::outdated::OutdatedArgs {
                 packages: args
                     .iter()
-                    .filter(|a| !a.starts_with('-'))
+                    .filter(|a| !a.starts_with('-') && !ignore.contains(a))
                     .cloned()
                     .collect(),
                 all: args.contains(&"--all".to_string()) || args.contains(&"-a".to_string()),
                 direct: args.contains(&"--direct".to_string()) || args.contains(&"-D".to_string()),
                 minor_only: args.contains(&"--minor-only".to_string())
                     || args.contains(&"-m".to_string()),
+                major_only: args.contains(&"--major-only".to_string())
+                    || args.contains(&"-M".to_string()),
+                ignore,
+                locked: args.contains(&"--locked".to_string()),
                 format: "text".to_string(),
                 strict: args.contains(&"--strict".to_string()),
             };
@@ -0,0 +1,840 @@
+//! Graph command - export the dependency graph.
+//!
+//! The graph is built from composer.lock, or from a fresh resolution of
+//! composer.json with `--resolve`, and rendered as Graphviz DOT, Mermaid or
+//! a JSON node/edge list. Nodes carry version, dev status, license and
+//! installed size; edges say whether they come from `require`,
+//! `require-dev`, `replace` or `provide`. Dependency cycles are
+//! highlighted.
+
+use crate::fetcher::{Fetcher, inspection_config, minimum_stability, resolution_cache};
+use crate::output::{format_bytes, success};
+use crate::security::SecurityPolicy;
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use libretto_lockfile::{ComposerLock, LockedPackage};
+use libretto_resolver::turbo::TurboResolver;
+use libretto_resolver::{ComposerConstraint, Dependency, PackageName, Resolution};
+use petgraph::Direction;
+use petgraph::graph::{DiGraph, NodeIndex};
+use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Arguments for the graph command
+#[derive(Args, Debug, Clone)]
+pub struct GraphArgs {
+    /// Output format
+    #[arg(
+        short = 'f',
+        long,
+        default_value = "dot",
+        value_parser = ["dot", "mermaid", "json"]
+    )]
+    pub format: String,
+
+    /// Write the graph to a file instead of stdout
+    #[arg(short = 'o', long)]
+    pub output: Option<PathBuf>,
+
+    /// Only show the part of the graph reachable from this package
+    #[arg(long, value_name = "PACKAGE")]
+    pub focus: Option<String>,
+
+    /// Maximum number of edges from the root or focused package
+    #[arg(long, value_name = "N")]
+    pub depth: Option<usize>,
+
+    /// Follow dependents of the focused package instead of its dependencies
+    #[arg(long, requires = "focus")]
+    pub reverse: bool,
+
+    /// Leave out dev dependencies
+    #[arg(long)]
+    pub no_dev: bool,
+
+    /// Resolve composer.json instead of reading composer.lock
+    #[arg(long)]
+    pub resolve: bool,
+}
+
+/// A package and the links it declares.
+#[derive(Debug, Clone, Default)]
+struct GraphPackage {
+    name: String,
+    version: Option<String>,
+    root: bool,
+    dev: bool,
+    license: Vec<String>,
+    require: Vec<(String, String)>,
+    require_dev: Vec<(String, String)>,
+    replace: Vec<(String, String)>,
+    provide: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    /// The project itself.
+    Root,
+    /// An installed package.
+    Package,
+    /// A name only satisfied through another package's `replace` or `provide`.
+    Virtual,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    name: String,
+    version: Option<String>,
+    kind: NodeKind,
+    dev: bool,
+    license: Vec<String>,
+    /// Installed size in bytes, if the package is in vendor/.
+    size: Option<u64>,
+    cycle: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgeKind {
+    Require,
+    RequireDev,
+    Replace,
+    Provide,
+}
+
+impl EdgeKind {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Require => "require",
+            Self::RequireDev => "require-dev",
+            Self::Replace => "replace",
+            Self::Provide => "provide",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Edge {
+    kind: EdgeKind,
+    constraint: String,
+    cycle: bool,
+}
+
+/// Run the graph command
+pub async fn run(args: GraphArgs) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let composer_path = cwd.join("composer.json");
+    let composer: Option<Value> = if composer_path.exists() {
+        Some(sonic_rs::from_str(&std::fs::read_to_string(
+            &composer_path,
+        )?)?)
+    } else {
+        None
+    };
+
+    let mut packages = if args.resolve {
+        let Some(composer) = &composer else {
+            bail!("composer.json not found");
+        };
+        resolve(composer, args.no_dev).await?
+    } else {
+        let lock_path = cwd.join("composer.lock");
+        if !lock_path.exists() {
+            bail!("composer.lock not found - run 'libretto install' first");
+        }
+        let lock: ComposerLock = sonic_rs::from_str(
+            &std::fs::read_to_string(&lock_path).context("Failed to read composer.lock")?,
+        )
+        .context("Failed to parse composer.lock")?;
+        lock.packages
+            .iter()
+            .map(|pkg| from_locked(pkg, false))
+            .chain(
+                lock.packages_dev
+                    .iter()
+                    .filter(|_| !args.no_dev)
+                    .map(|pkg| from_locked(pkg, true)),
+            )
+            .collect()
+    };
+    if let Some(composer) = &composer {
+        packages.insert(0, root_package(composer, args.no_dev));
+    }
+
+    let mut graph = build_graph(&packages);
+    let vendor_dir = cwd.join("vendor");
+    for node in graph.node_weights_mut() {
+        if node.kind == NodeKind::Package {
+            node.size = installed_size(&vendor_dir.join(&node.name));
+        }
+    }
+
+    let start: Vec<NodeIndex> = match &args.focus {
+        Some(focus) => {
+            let focus = focus.to_ascii_lowercase();
+            let Some(index) = graph.node_indices().find(|&i| graph[i].name == focus) else {
+                bail!("package {focus} is not in the dependency graph");
+            };
+            vec![index]
+        }
+        None => graph
+            .node_indices()
+            .filter(|&i| {
+                graph
+                    .neighbors_directed(i, Direction::Incoming)
+                    .next()
+                    .is_none()
+            })
+            .collect(),
+    };
+    let direction = if args.reverse {
+        Direction::Incoming
+    } else {
+        Direction::Outgoing
+    };
+    let keep = reachable(&graph, &start, direction, args.depth);
+    let mut graph = graph.filter_map(
+        |i, node| keep.contains(&i).then(|| node.clone()),
+        |_, edge| Some(edge.clone()),
+    );
+    let cycles = mark_cycles(&mut graph);
+
+    let document = match args.format.as_str() {
+        "mermaid" => to_mermaid(&graph),
+        "json" => to_json(&graph, &cycles)?,
+        _ => to_dot(&graph),
+    };
+
+    match args.output {
+        Some(path) => {
+            std::fs::write(&path, &document)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            success(&format!(
+                "Wrote {} graph with {} packages and {} edges to {}",
+                args.format,
+                graph.node_count(),
+                graph.edge_count(),
+                path.display()
+            ));
+        }
+        None => println!("{}", document.trim_end()),
+    }
+
+    Ok(())
+}
+
+fn from_locked(pkg: &LockedPackage, dev: bool) -> GraphPackage {
+    let links = |map: &std::collections::BTreeMap<String, String>| -> Vec<(String, String)> {
+        map.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+    };
+    GraphPackage {
+        name: pkg.name.to_ascii_lowercase(),
+        version: Some(pkg.version.clone()),
+        dev,
+        license: pkg.license.clone(),
+        require: links(&pkg.require),
+        replace: links(&pkg.replace),
+        provide: links(&pkg.provide),
+        ..GraphPackage::default()
+    }
+}
+
+/// The project itself, with its direct requirements.
+fn root_package(composer: &Value, no_dev: bool) -> GraphPackage {
+    let links = |key: &str| -> Vec<(String, String)> {
+        composer
+            .get(key)
+            .and_then(|v| v.as_object())
+            .map(|links| {
+                links
+                    .iter()
+                    .map(|(name, c)| (name.to_string(), c.as_str().unwrap_or("*").to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    GraphPackage {
+        name: composer
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("__root__")
+            .to_ascii_lowercase(),
+        version: composer
+            .get("version")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        root: true,
+        require: links("require"),
+        require_dev: if no_dev {
+            Vec::new()
+        } else {
+            links("require-dev")
+        },
+        replace: links("replace"),
+        provide: links("provide"),
+        ..GraphPackage::default()
+    }
+}
+
+/// Resolve composer.json afresh, as `update` would.
+async fn resolve(composer: &Value, no_dev: bool) -> Result<Vec<GraphPackage>> {
+    use crate::output::progress::Spinner;
+
+    let deps = |key: &str| -> Vec<Dependency> {
+        composer
+            .get(key)
+            .and_then(|v| v.as_object())
+            .map(|require| {
+                require
+                    .iter()
+                    .filter(|(name, _)| !libretto_core::is_platform_package_name(name))
+                    .filter_map(|(name, constraint)| {
+                        Some(Dependency::new(
+                            PackageName::parse(name)?,
+                            ComposerConstraint::parse(constraint.as_str().unwrap_or("*"))?,
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let min_stability = minimum_stability(composer);
+
+    let fetcher = Arc::new(
+        Fetcher::new_with_composer_repositories(composer)
+            .map_err(|e| anyhow::anyhow!("Failed to create fetcher: {e}"))?,
+    );
+    let security = SecurityPolicy::load(composer, false);
+    let mut resolver = security.apply(TurboResolver::new(
+        fetcher,
+        inspection_config(min_stability, !no_dev, security.block_abandoned),
+    ));
+    if let Some(cache) = resolution_cache(composer) {
+        resolver = resolver.with_cache(cache);
+    }
+
+    let spinner = Spinner::new("Resolving dependencies...");
+    let resolution = resolver
+        .resolve(&deps("require"), &deps("require-dev"))
+        .await
+        .map_err(|e| anyhow::anyhow!("Resolution failed: {e}"))?;
+    spinner.finish_and_clear();
+
+    Ok(from_resolution(&resolution))
+}
+
+fn from_resolution(resolution: &Resolution) -> Vec<GraphPackage> {
+    resolution
+        .packages
+        .iter()
+        .map(|pkg| GraphPackage {
+            name: pkg.name.as_str().to_string(),
+            version: Some(pkg.version.to_string()),
+            dev: pkg.is_dev,
+            license: pkg.license.clone().unwrap_or_default(),
+            require: pkg.require.clone().unwrap_or_default(),
+            replace: pkg.replace.clone().unwrap_or_default(),
+            provide: pkg.provide.clone().unwrap_or_default(),
+            ..GraphPackage::default()
+        })
+        .collect()
+}
+
+/// Build the graph, edges pointing from a package to what it links to.
+///
+/// Platform requirements are left out. A required name that no package
+/// installs becomes a virtual node when some package replaces or provides
+/// it, and is dropped otherwise.
+fn build_graph(packages: &[GraphPackage]) -> DiGraph<Node, Edge> {
+    let mut graph = DiGraph::new();
+    let mut indices: HashMap<String, NodeIndex> = HashMap::new();
+
+    for pkg in packages {
+        let index = graph.add_node(Node {
+            name: pkg.name.clone(),
+            version: pkg.version.clone(),
+            kind: if pkg.root {
+                NodeKind::Root
+            } else {
+                NodeKind::Package
+            },
+            dev: pkg.dev,
+            license: pkg.license.clone(),
+            size: None,
+            cycle: false,
+        });
+        indices.insert(pkg.name.clone(), index);
+    }
+
+    let linked: HashSet<String> = packages
+        .iter()
+        .flat_map(|pkg| pkg.replace.iter().chain(&pkg.provide))
+        .map(|(name, _)| name.to_ascii_lowercase())
+        .collect();
+
+    let requirements = packages.iter().flat_map(|pkg| {
+        pkg.require
+            .iter()
+            .map(|link| (EdgeKind::Require, link))
+            .chain(
+                pkg.require_dev
+                    .iter()
+                    .map(|link| (EdgeKind::RequireDev, link)),
+            )
+            .map(move |(kind, link)| (&pkg.name, kind, link))
+    });
+    let links = packages.iter().flat_map(|pkg| {
+        pkg.replace
+            .iter()
+            .map(|link| (EdgeKind::Replace, link))
+            .chain(pkg.provide.iter().map(|link| (EdgeKind::Provide, link)))
+            .map(move |(kind, link)| (&pkg.name, kind, link))
+    });
+
+    for (from, kind, (target, constraint)) in requirements {
+        let target = target.to_ascii_lowercase();
+        if libretto_core::is_platform_package_name(&target) {
+            continue;
+        }
+        let to = match indices.get(&target) {
+            Some(&index) => index,
+            None if linked.contains(&target) => {
+                let index = graph.add_node(Node {
+                    name: target.clone(),
+                    version: None,
+                    kind: NodeKind::Virtual,
+                    dev: false,
+                    license: Vec::new(),
+                    size: None,
+                    cycle: false,
+                });
+                indices.insert(target, index);
+                index
+            }
+            None => continue,
+        };
+        graph.add_edge(
+            indices[from],
+            to,
+            Edge {
+                kind,
+                constraint: constraint.clone(),
+                cycle: false,
+            },
+        );
+    }
+
+    // Only names something requires are worth a replace or provide edge
+    for (from, kind, (target, constraint)) in links {
+        if let Some(&to) = indices.get(&target.to_ascii_lowercase())
+            && graph[to].kind == NodeKind::Virtual
+        {
+            graph.add_edge(
+                indices[from],
+                to,
+                Edge {
+                    kind,
+                    constraint: constraint.clone(),
+                    cycle: false,
+                },
+            );
+        }
+    }
+
+    graph
+}
+
+/// Nodes within `depth` edges of `start`, following `direction`.
+fn reachable(
+    graph: &DiGraph<Node, Edge>,
+    start: &[NodeIndex],
+    direction: Direction,
+    depth: Option<usize>,
+) -> HashSet<NodeIndex> {
+    let mut seen: HashSet<NodeIndex> = start.iter().copied().collect();
+    let mut queue: VecDeque<(NodeIndex, usize)> = start.iter().map(|&i| (i, 0)).collect();
+
+    while let Some((index, distance)) = queue.pop_front() {
+        if depth.is_some_and(|depth| distance >= depth) {
+            continue;
+        }
+        for next in graph.neighbors_directed(index, direction) {
+            if seen.insert(next) {
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    seen
+}
+
+/// Mark nodes and edges on dependency cycles, returning each cycle's
+/// package names.
+fn mark_cycles(graph: &mut DiGraph<Node, Edge>) -> Vec<Vec<String>> {
+    let mut cycles = Vec::new();
+    for component in petgraph::algo::tarjan_scc(&*graph) {
+        let cyclic = component.len() > 1 || graph.contains_edge(component[0], component[0]);
+        if !cyclic {
+            continue;
+        }
+        let members: HashSet<NodeIndex> = component.iter().copied().collect();
+        for &index in &component {
+            graph[index].cycle = true;
+        }
+        let edges: Vec<_> = graph
+            .edge_indices()
+            .filter(|&e| {
+                graph
+                    .edge_endpoints(e)
+                    .is_some_and(|(a, b)| members.contains(&a) && members.contains(&b))
+            })
+            .collect();
+        for edge in edges {
+            graph[edge].cycle = true;
+        }
+
+        let mut names: Vec<_> = component.iter().map(|&i| graph[i].name.clone()).collect();
+        names.sort();
+        cycles.push(names);
+    }
+    cycles.sort();
+    cycles
+}
+
+/// Installed size of a package directory, `None` if it is not installed.
+fn installed_size(dir: &Path) -> Option<u64> {
+    if !dir.is_dir() {
+        return None;
+    }
+    Some(
+        walkdir::WalkDir::new(dir)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter_map(|entry| entry.metadata().ok())
+            .filter(std::fs::Metadata::is_file)
+            .map(|metadata| metadata.len())
+            .sum(),
+    )
+}
+
+/// Label lines for a node: name, version, then license and size.
+fn label_lines(node: &Node) -> Vec<String> {
+    let mut lines = vec![node.name.clone()];
+    match node.kind {
+        NodeKind::Root => lines.push("root".to_string()),
+        NodeKind::Virtual => lines.push("virtual".to_string()),
+        NodeKind::Package => {}
+    }
+    if let Some(version) = &node.version {
+        lines.push(version.clone());
+    }
+    let details: Vec<String> = node
+        .license
+        .iter()
+        .cloned()
+        .chain(node.size.map(format_bytes))
+        .chain(node.dev.then(|| "dev".to_string()))
+        .collect();
+    if !details.is_empty() {
+        lines.push(details.join(", "));
+    }
+    lines
+}
+
+fn to_dot(graph: &DiGraph<Node, Edge>) -> String {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut out = String::from("digraph dependencies {\n");
+    out.push_str("    rankdir=LR;\n");
+    out.push_str("    node [shape=box, fontname=\"Helvetica\"];\n");
+    out.push_str("    edge [fontname=\"Helvetica\", fontsize=10];\n");
+
+    for index in graph.node_indices() {
+        let node = &graph[index];
+        let mut attributes = vec![format!(
+            "label=\"{}\"",
+            label_lines(node)
+                .iter()
+                .map(|line| escape(line))
+                .collect::<Vec<_>>()
+                .join("\\n")
+        )];
+        match node.kind {
+            NodeKind::Root => attributes.push("style=bold".to_string()),
+            NodeKind::Virtual => attributes.push("style=dotted".to_string()),
+            NodeKind::Package if node.dev => attributes.push("style=dashed".to_string()),
+            NodeKind::Package => {}
+        }
+        if node.cycle {
+            attributes.push("color=red".to_string());
+        }
+        let _ = writeln!(
+            out,
+            "    \"{}\" [{}];",
+            escape(&node.name),
+            attributes.join(", ")
+        );
+    }
+
+    for edge in graph.edge_indices() {
+        let Some((from, to)) = graph.edge_endpoints(edge) else {
+            continue;
+        };
+        let weight = &graph[edge];
+        let mut attributes = vec![format!("label=\"{}\"", escape(&weight.constraint))];
+        match weight.kind {
+            EdgeKind::Require => {}
+            EdgeKind::RequireDev => attributes.push("style=dashed".to_string()),
+            EdgeKind::Replace | EdgeKind::Provide => {
+                attributes[0] = format!(
+                    "label=\"{} {}\"",
+                    weight.kind.as_str(),
+                    escape(&weight.constraint)
+                );
+                attributes.push("style=dotted".to_string());
+            }
+        }
+        if weight.cycle {
+            attributes.push("color=red".to_string());
+        }
+        let _ = writeln!(
+            out,
+            "    \"{}\" -> \"{}\" [{}];",
+            escape(&graph[from].name),
+            escape(&graph[to].name),
+            attributes.join(", ")
+        );
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn to_mermaid(graph: &DiGraph<Node, Edge>) -> String {
+    let escape = |s: &str| s.replace('"', "#quot;");
+    let mut out = String::from("graph LR\n");
+
+    for index in graph.node_indices() {
+        let node = &graph[index];
+        let label = label_lines(node)
+            .iter()
+            .map(|line| escape(line))
+            .collect::<Vec<_>>()
+            .join("<br/>");
+        let _ = writeln!(out, "    n{}[\"{label}\"]", index.index());
+    }
+
+    let mut cycle_links = Vec::new();
+    for (position, edge) in graph.edge_indices().enumerate() {
+        let Some((from, to)) = graph.edge_endpoints(edge) else {
+            continue;
+        };
+        let weight = &graph[edge];
+        let (arrow, label) = match weight.kind {
+            EdgeKind::Require => ("-->", escape(&weight.constraint)),
+            EdgeKind::RequireDev => ("-.->", escape(&weight.constraint)),
+            EdgeKind::Replace | EdgeKind::Provide => (
+                "-.->",
+                format!("{} {}", weight.kind.as_str(), escape(&weight.constraint)),
+            ),
+        };
+        let _ = writeln!(
+            out,
+            "    n{} {arrow}|\"{label}\"| n{}",
+            from.index(),
+            to.index()
+        );
+        if weight.cycle {
+            cycle_links.push(position.to_string());
+        }
+    }
+
+    out.push_str("    classDef root stroke-width:3px\n");
+    out.push_str("    classDef dev stroke-dasharray:5 5\n");
+    out.push_str("    classDef virtual stroke-dasharray:2 2\n");
+    out.push_str("    classDef cycle stroke:#d00,color:#d00\n");
+    for (class, matches) in [
+        (
+            "root",
+            &(|node: &Node| node.kind == NodeKind::Root) as &dyn Fn(&Node) -> bool,
+        ),
+        ("dev", &|node: &Node| node.dev),
+        ("virtual", &|node: &Node| node.kind == NodeKind::Virtual),
+        ("cycle", &|node: &Node| node.cycle),
+    ] {
+        let members: Vec<_> = graph
+            .node_indices()
+            .filter(|&i| matches(&graph[i]))
+            .map(|i| format!("n{}", i.index()))
+            .collect();
+        if !members.is_empty() {
+            let _ = writeln!(out, "    class {} {class}", members.join(","));
+        }
+    }
+    if !cycle_links.is_empty() {
+        let _ = writeln!(out, "    linkStyle {} stroke:#d00", cycle_links.join(","));
+    }
+
+    out
+}
+
+fn to_json(graph: &DiGraph<Node, Edge>, cycles: &[Vec<String>]) -> Result<String> {
+    let nodes: Vec<_> = graph
+        .node_weights()
+        .map(|node| {
+            sonic_rs::json!({
+                "name": node.name,
+                "version": node.version,
+                "type": match node.kind {
+                    NodeKind::Root => "root",
+                    NodeKind::Package => "package",
+                    NodeKind::Virtual => "virtual",
+                },
+                "dev": node.dev,
+                "license": node.license,
+                "size": node.size,
+                "cycle": node.cycle,
+            })
+        })
+        .collect();
+    let edges: Vec<_> = graph
+        .edge_indices()
+        .filter_map(|edge| {
+            let (from, to) = graph.edge_endpoints(edge)?;
+            let weight = &graph[edge];
+            Some(sonic_rs::json!({
+                "from": graph[from].name,
+                "to": graph[to].name,
+                "type": weight.kind.as_str(),
+                "constraint": weight.constraint,
+                "cycle": weight.cycle,
+            }))
+        })
+        .collect();
+
+    Ok(sonic_rs::to_string_pretty(&sonic_rs::json!({
+        "nodes": nodes,
+        "edges": edges,
+        "cycles": cycles,
+    }))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, require: &[&str]) -> GraphPackage {
+        GraphPackage {
+            name: name.to_string(),
+            version: Some("1.0.0".to_string()),
+            license: vec!["MIT".to_string()],
+            require: require
+                .iter()
+                .map(|dep| ((*dep).to_string(), "^1.0".to_string()))
+                .collect(),
+            ..GraphPackage::default()
+        }
+    }
+
+    fn names(graph: &DiGraph<Node, Edge>, nodes: &HashSet<NodeIndex>) -> Vec<String> {
+        let mut names: Vec<_> = nodes.iter().map(|&i| graph[i].name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn replaced_names_become_virtual_nodes_and_platform_links_are_dropped() {
+        let mut framework = package("acme/framework", &[]);
+        framework.replace = vec![
+            ("acme/support".to_string(), "self.version".to_string()),
+            ("acme/unused".to_string(), "self.version".to_string()),
+        ];
+        let packages = vec![framework, package("acme/plugin", &["acme/support", "php"])];
+
+        let graph = build_graph(&packages);
+        let kinds: Vec<_> = graph
+            .node_weights()
+            .map(|n| (n.name.as_str(), n.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("acme/framework", NodeKind::Package),
+                ("acme/plugin", NodeKind::Package),
+                ("acme/support", NodeKind::Virtual),
+            ]
+        );
+        let edges: Vec<_> = graph
+            .edge_indices()
+            .map(|e| {
+                let (a, b) = graph.edge_endpoints(e).unwrap();
+                (
+                    graph[a].name.as_str(),
+                    graph[b].name.as_str(),
+                    graph[e].kind,
+                )
+            })
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                ("acme/plugin", "acme/support", EdgeKind::Require),
+                ("acme/framework", "acme/support", EdgeKind::Replace),
+            ]
+        );
+    }
+
+    #[test]
+    fn focus_depth_and_reverse_limit_the_graph() {
+        let packages = vec![
+            package("acme/a", &["acme/b"]),
+            package("acme/b", &["acme/c"]),
+            package("acme/c", &[]),
+            package("acme/d", &["acme/c"]),
+        ];
+        let graph = build_graph(&packages);
+        let index = |name: &str| {
+            graph
+                .node_indices()
+                .find(|&i| graph[i].name == name)
+                .unwrap()
+        };
+
+        let forward = reachable(&graph, &[index("acme/a")], Direction::Outgoing, Some(1));
+        assert_eq!(names(&graph, &forward), ["acme/a", "acme/b"]);
+
+        let reverse = reachable(&graph, &[index("acme/c")], Direction::Incoming, None);
+        assert_eq!(
+            names(&graph, &reverse),
+            ["acme/a", "acme/b", "acme/c", "acme/d"]
+        );
+    }
+
+    #[test]
+    fn cycles_are_marked_on_nodes_and_edges() {
+        let packages = vec![
+            package("acme/a", &["acme/b"]),
+            package("acme/b", &["acme/a", "acme/c"]),
+            package("acme/c", &[]),
+        ];
+        let mut graph = build_graph(&packages);
+
+        let cycles = mark_cycles(&mut graph);
+        assert_eq!(cycles, [["acme/a", "acme/b"]]);
+        let cyclic: Vec<_> = graph
+            .node_weights()
+            .filter(|n| n.cycle)
+            .map(|n| n.name.as_str())
+            .collect();
+        assert_eq!(cyclic, ["acme/a", "acme/b"]);
+        assert_eq!(graph.edge_weights().filter(|e| e.cycle).count(), 2);
+
+        let dot = to_dot(&graph);
+        assert!(dot.contains("\"acme/a\" -> \"acme/b\" [label=\"^1.0\", color=red];"));
+        assert!(dot.contains("\"acme/b\" -> \"acme/c\" [label=\"^1.0\"];"));
+        let mermaid = to_mermaid(&graph);
+        assert!(mermaid.contains("n0[\"acme/a<br/>1.0.0<br/>MIT\"]"));
+        assert!(mermaid.contains("class n0,n1 cycle"));
+        assert!(mermaid.contains("linkStyle 0,1 stroke:#d00"));
+    }
+}
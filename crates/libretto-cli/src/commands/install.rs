@@ -3,30 +3,43 @@
 //! High-performance package installation using parallel resolution and downloads.
 
 use crate::auth_manager::{
-    AuthManager, GitHubRateLimitInfo, is_github_rate_limit_error, parse_rate_limit_headers,
+    AuthManager, GitHubRateLimitInfo, is_github_rate_limit_error, is_github_url,
 };
 use crate::cas_cache;
-use crate::fetcher::Fetcher;
+use crate::fetcher::{Fetcher, minimum_stability, parse_stability, resolution_cache};
 use crate::installer_paths::InstallerPaths;
+use crate::mirrors::MirrorSet;
+use crate::output::events::{self, Event};
 use crate::output::format_bytes;
 use crate::output::live::LiveProgress;
 use crate::output::table::Table;
-use crate::output::{error, header, info, success, warning};
+use crate::output::{error, header, info, out, success, warning};
 use crate::platform::PlatformValidator;
+use crate::plugins::PluginRuntime;
 use crate::scripts::{
-    ScriptConfig, run_post_autoload_scripts, run_post_install_scripts, run_pre_autoload_scripts,
-    run_pre_install_scripts,
+    ScriptConfig, ScriptResult, process_timeout, run_post_autoload_scripts,
+    run_post_install_scripts, run_pre_autoload_scripts, run_pre_install_scripts,
 };
+use crate::security::SecurityPolicy;
+use crate::signing::{SignatureCheck, SigningPolicy, signature_url};
+use crate::workspace::{Workspace, link_member};
 use anyhow::{Context, Result, bail};
 use clap::Args;
+use dashmap::DashMap;
 use futures::stream::{FuturesUnordered, StreamExt};
+use libretto_archive::{ArchiveType, Extractor};
 use libretto_audit::Auditor;
+use libretto_config::SigningMode;
 use libretto_config::auth::Credential;
 use libretto_core::PackageId;
-use libretto_resolver::Stability;
+use libretto_downloader::{
+    BandwidthThrottler, CircuitBreaker, DistMirror, DownloadConfig, DownloadError, DownloadedFile,
+    ExpectedChecksum, HttpClient, MirrorStats, ProgressTracker, RetryConfig, StreamDownloader,
+    with_retry,
+};
+use libretto_plugin_system::Hook;
 use libretto_resolver::turbo::{TurboConfig, TurboResolver};
 use libretto_resolver::{ComposerConstraint, Dependency, PackageName, ResolutionMode};
-use semver::Version;
 use sonic_rs::{JsonContainerTrait, JsonValueMutTrait, JsonValueTrait, Value};
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
@@ -37,6 +50,63 @@ use tracing::debug;
 
 const INSTALL_MARKER_FILE: &str = ".libretto-installed.json";
 
+/// Largest detached signature accepted for a dist archive.
+const MAX_SIGNATURE_SIZE: usize = 64 * 1024;
+
+/// Default number of retries for a failed package download.
+const DEFAULT_DOWNLOAD_RETRIES: u32 = 3;
+
+/// Network options shared by commands that download packages.
+#[derive(Args, Debug, Clone)]
+pub struct DownloadArgs {
+    /// Limit download bandwidth in bytes per second (accepts K, M and G suffixes)
+    #[arg(long, value_name = "RATE", value_parser = parse_bandwidth)]
+    pub max_bandwidth: Option<u64>,
+
+    /// Number of times to retry a failed download
+    #[arg(long, value_name = "COUNT", default_value_t = DEFAULT_DOWNLOAD_RETRIES)]
+    pub retries: u32,
+
+    /// Download a source VCS archive when a dist and its mirrors are unreachable
+    #[arg(long)]
+    pub source_fallback: bool,
+}
+
+impl Default for DownloadArgs {
+    fn default() -> Self {
+        Self {
+            max_bandwidth: None,
+            retries: DEFAULT_DOWNLOAD_RETRIES,
+            source_fallback: false,
+        }
+    }
+}
+
+/// Parse a bandwidth limit such as `512K`, `2M` or `1G` into bytes per second.
+fn parse_bandwidth(value: &str) -> std::result::Result<u64, String> {
+    let trimmed = value.trim();
+    let unit_start = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(unit_start);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid bandwidth '{value}'"))?;
+    let unit = unit.trim_end_matches("/s").trim_end_matches(['B', 'b']);
+    let multiplier: u64 = match unit.to_ascii_uppercase().as_str() {
+        "" => 1,
+        "K" => 1024,
+        "M" => 1024 * 1024,
+        "G" => 1024 * 1024 * 1024,
+        _ => return Err(format!("unknown bandwidth unit in '{value}'")),
+    };
+    match number.checked_mul(multiplier) {
+        Some(0) => Err("bandwidth limit must be greater than zero".to_string()),
+        Some(bytes) => Ok(bytes),
+        None => Err(format!("bandwidth '{value}' is too large")),
+    }
+}
+
 /// Arguments for the install command.
 #[derive(Args, Debug, Clone)]
 pub struct InstallArgs {
@@ -112,6 +182,14 @@ pub struct InstallArgs {
     #[arg(long)]
     pub verify_checksums: bool,
 
+    /// Fail if composer.lock is out of date with composer.json
+    #[arg(long)]
+    pub strict_lock: bool,
+
+    /// Don't block versions with security advisories or abandoned packages
+    #[arg(long)]
+    pub no_security_blocking: bool,
+
     /// Specify PHP version to use for this operation
     #[arg(long, value_name = "VERSION")]
     pub php_version: Option<String>,
@@ -119,10 +197,22 @@ pub struct InstallArgs {
     /// Skip PHP version requirement check
     #[arg(long)]
     pub no_php_check: bool,
+
+    #[command(flatten)]
+    pub download: DownloadArgs,
 }
 
 /// Run the install command.
 pub async fn run(args: InstallArgs) -> Result<()> {
+    execute(args, true).await
+}
+
+/// Run the install step of `update`, which dispatches its own command events.
+pub async fn run_for_update(args: InstallArgs) -> Result<()> {
+    execute(args, false).await
+}
+
+async fn execute(args: InstallArgs, command_events: bool) -> Result<()> {
     let start = Instant::now();
     header("Installing dependencies");
 
@@ -142,14 +232,36 @@ pub async fn run(args: InstallArgs) -> Result<()> {
     let composer: Value =
         sonic_rs::from_str(&composer_content).context("Failed to parse composer.json")?;
 
+    if let Some(workspace) = Workspace::load(&cwd, &composer)? {
+        if args.dry_run {
+            warning("Dry run mode - no changes will be made");
+        }
+        return install_workspace(&workspace, &args, false, false).await;
+    }
+
+    if composer_lock_path.exists() {
+        check_lock_freshness(&composer_lock_path, &composer, args.strict_lock)?;
+    }
+
     if args.dry_run {
         warning("Dry run mode - no changes will be made");
     }
 
+    // Plugins installed by a previous run
+    let mut plugins = if args.dry_run {
+        PluginRuntime::disabled(&cwd, &vendor_dir, "install")
+    } else {
+        PluginRuntime::load(&composer, &cwd, &vendor_dir, "install", !args.no_dev).await
+    };
+    if command_events {
+        plugins.dispatch(Hook::PreInstallCmd).await;
+    }
+
     // Script config for lifecycle hooks
     let script_config = ScriptConfig {
         working_dir: cwd.clone(),
         dev_mode: !args.no_dev,
+        timeout: process_timeout(&composer),
         ..Default::default()
     };
 
@@ -170,7 +282,7 @@ pub async fn run(args: InstallArgs) -> Result<()> {
     }
 
     // Create live progress display
-    let progress = if !args.no_progress && !args.dry_run {
+    let progress = if !args.no_progress && !args.dry_run && !events::is_enabled() {
         Some(LiveProgress::new())
     } else {
         None
@@ -178,6 +290,7 @@ pub async fn run(args: InstallArgs) -> Result<()> {
 
     // Parse installer-paths from composer.json for custom installation locations
     let installer_paths = InstallerPaths::from_composer(&composer);
+    let signing = SigningPolicy::load(&composer, &cwd);
 
     // Check for lock file
     let has_lock = composer_lock_path.exists();
@@ -197,8 +310,10 @@ pub async fn run(args: InstallArgs) -> Result<()> {
             &vendor_dir,
             &cwd,
             &installer_paths,
+            &signing,
             &args,
             progress.as_ref(),
+            &plugins,
         )
         .await
     } else {
@@ -208,8 +323,10 @@ pub async fn run(args: InstallArgs) -> Result<()> {
             &vendor_dir,
             &cwd,
             &installer_paths,
+            &signing,
             &args,
             progress.as_ref(),
+            &plugins,
         )
         .await
     };
@@ -243,10 +360,17 @@ pub async fn run(args: InstallArgs) -> Result<()> {
         write_composer_installed_metadata(
             &composer_lock_path,
             &vendor_dir,
+            &cwd,
+            &installer_paths,
             &composer,
             !args.no_dev,
         )?;
 
+        // Activate plugins installed by this run
+        plugins.reload(&composer).await;
+
+        plugins.dispatch(Hook::PreAutoloadDump).await;
+
         // Pre-autoload-dump scripts
         if !args.no_scripts
             && let Some(result) = run_pre_autoload_scripts(&composer, &script_config)?
@@ -256,7 +380,9 @@ pub async fn run(args: InstallArgs) -> Result<()> {
             warning(&format!("Pre-autoload script warning: {err}"));
         }
 
-        generate_autoloader(&vendor_dir, &args)?;
+        generate_autoloader(&cwd, &composer, &vendor_dir, &args)?;
+
+        plugins.dispatch(Hook::PostAutoloadDump).await;
 
         // Post-autoload-dump scripts
         if !args.no_scripts
@@ -268,6 +394,10 @@ pub async fn run(args: InstallArgs) -> Result<()> {
         }
     }
 
+    if command_events {
+        plugins.dispatch(Hook::PostInstallCmd).await;
+    }
+
     // Run post-install scripts
     if !args.no_scripts
         && !args.dry_run
@@ -290,14 +420,310 @@ pub async fn run(args: InstallArgs) -> Result<()> {
 
     // Run security audit if requested
     if args.audit && !args.dry_run {
-        run_security_audit(&composer_lock_path, &args).await?;
+        run_security_audit(&composer_lock_path, &composer, &args).await?;
     }
 
     Ok(())
 }
 
+/// Warn, as Composer does, when composer.json changed since the lock was written.
+///
+/// With `--strict-lock` a stale lock is an error instead, so CI catches it.
+fn check_lock_freshness(lock_path: &Path, composer: &Value, strict: bool) -> Result<()> {
+    let drift = match super::lock_generator::lock_drift(lock_path, composer) {
+        Ok(drift) => drift,
+        Err(e) if strict => return Err(e),
+        Err(e) => {
+            debug!("Skipping lock freshness check: {e:#}");
+            return Ok(());
+        }
+    };
+    if drift.is_current {
+        return Ok(());
+    }
+
+    if strict {
+        bail!(
+            "composer.lock is out of date with composer.json ({}).\nRun `libretto update` to refresh it.",
+            drift.summary()
+        );
+    }
+    warning(
+        "The lock file is not up to date with the latest changes in composer.json. \
+         You may be getting outdated dependencies. Run `libretto update` to update them.",
+    );
+    debug!("Lock drift: {}", drift.summary());
+    Ok(())
+}
+
+/// Install every project of a workspace from one shared lock.
+///
+/// The lock at the workspace root is resolved from the merged requirements
+/// when `update` is set or when there is none yet; `lock_only` stops once it
+/// is written. Each project then gets its own `vendor/` holding the locked
+/// packages it needs, with the members it requires symlinked in, and runs its
+/// own scripts and plugins.
+pub async fn install_workspace(
+    workspace: &Workspace,
+    args: &InstallArgs,
+    update: bool,
+    lock_only: bool,
+) -> Result<()> {
+    let start = Instant::now();
+    let root = &workspace.root.dir;
+    let lock_path = root.join("composer.lock");
+    let manifest = workspace.manifest();
+    let operation = if update { "update" } else { "install" };
+    let (pre_command, post_command) = if update {
+        (Hook::PreUpdateCmd, Hook::PostUpdateCmd)
+    } else {
+        (Hook::PreInstallCmd, Hook::PostInstallCmd)
+    };
+
+    info(&format!(
+        "Workspace with {} member(s)",
+        workspace.members.len()
+    ));
+
+    if update || !lock_path.exists() {
+        if !update {
+            warning("No composer.lock found; resolving dependencies of all workspace projects.");
+        }
+        let (root_deps, dev_deps) = workspace.dependencies()?;
+        let plugins = if args.dry_run {
+            PluginRuntime::disabled(root, &root.join("vendor"), operation)
+        } else {
+            PluginRuntime::load(
+                &workspace.root.manifest,
+                root,
+                &root.join("vendor"),
+                operation,
+                !args.no_dev,
+            )
+            .await
+        };
+        let resolution =
+            resolve_dependencies(&manifest, &root_deps, &dev_deps, args, None, &plugins).await?;
+        if args.dry_run {
+            info(&format!(
+                "Would lock {} package(s)",
+                resolution.packages.len()
+            ));
+            return Ok(());
+        }
+        generate_lock_file(&lock_path, &resolution, &manifest)?;
+        success("Writing lock file");
+    } else {
+        check_lock_freshness(&lock_path, &manifest, args.strict_lock)?;
+    }
+
+    if lock_only {
+        return Ok(());
+    }
+
+    let lock: Value = sonic_rs::from_str(
+        &std::fs::read_to_string(&lock_path).context("Failed to read composer.lock")?,
+    )
+    .context("Failed to parse composer.lock")?;
+    if !args.ignore_platform_reqs {
+        validate_platform_from_lock(&lock, args)?;
+    }
+
+    let lock_section = |section: &str| -> Vec<Value> {
+        lock.get(section)
+            .and_then(|v| v.as_array())
+            .map(|pkgs| pkgs.iter().cloned().collect())
+            .unwrap_or_default()
+    };
+    let prod = lock_section("packages");
+    let dev = if args.no_dev {
+        Vec::new()
+    } else {
+        lock_section("packages-dev")
+    };
+    let locked: Vec<Value> = prod.iter().chain(&dev).cloned().collect();
+    let lock_name = |pkg: &Value| {
+        pkg.get("name")
+            .and_then(|v| v.as_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default()
+    };
+
+    for project in workspace.projects() {
+        let (names, members) = workspace.closure(project, &locked, !args.no_dev);
+        let project_prod: Vec<Value> = prod
+            .iter()
+            .filter(|pkg| names.contains(&lock_name(pkg)))
+            .cloned()
+            .collect();
+        let project_dev: Vec<Value> = dev
+            .iter()
+            .filter(|pkg| names.contains(&lock_name(pkg)))
+            .cloned()
+            .collect();
+        let packages: Vec<PackageInfo> = project_prod
+            .iter()
+            .filter_map(|pkg| parse_lock_package(pkg, false))
+            .chain(
+                project_dev
+                    .iter()
+                    .filter_map(|pkg| parse_lock_package(pkg, true)),
+            )
+            .collect();
+
+        if args.dry_run {
+            info(&format!(
+                "Would install {} package(s) and link {} member(s) into {}",
+                packages.len(),
+                members.len(),
+                project.label()
+            ));
+            continue;
+        }
+
+        info(&format!(
+            "Installing {} ({} package(s), {} linked member(s))",
+            project.label(),
+            packages.len(),
+            members.len()
+        ));
+
+        let vendor_dir = project.dir.join("vendor");
+        std::fs::create_dir_all(&vendor_dir)?;
+        let installer_paths = InstallerPaths::from_composer(&project.manifest);
+        let signing = SigningPolicy::load(&project.manifest, &project.dir);
+        let mut plugins = PluginRuntime::load(
+            &project.manifest,
+            &project.dir,
+            &vendor_dir,
+            operation,
+            !args.no_dev,
+        )
+        .await;
+        plugins.dispatch(pre_command).await;
+
+        let script_config = ScriptConfig {
+            working_dir: project.dir.clone(),
+            dev_mode: !args.no_dev,
+            timeout: process_timeout(&project.manifest),
+            ..Default::default()
+        };
+        if !args.no_scripts {
+            warn_script_failure(
+                &format!("Pre-{operation}"),
+                run_pre_install_scripts(&project.manifest, &script_config, update)?,
+            );
+        }
+
+        if !packages.is_empty() {
+            install_packages(
+                &packages,
+                &vendor_dir,
+                &project.dir,
+                &installer_paths,
+                &signing,
+                args,
+                None,
+                &plugins,
+            )
+            .await?;
+        }
+
+        let mut linked = Vec::new();
+        for name in &members {
+            let Some(member) = workspace.member(name) else {
+                continue;
+            };
+            link_member(&vendor_dir, name, &member.dir)
+                .with_context(|| format!("Failed to link {name} into {}", project.label()))?;
+
+            let mut entry = member.manifest.clone();
+            if let Some(obj) = entry.as_object_mut() {
+                if !obj.contains_key(&"version") {
+                    obj.insert("version", Value::from("dev-main"));
+                }
+                let url = member.dir.strip_prefix(root).unwrap_or(&member.dir);
+                obj.insert(
+                    "dist",
+                    sonic_rs::json!({
+                        "type": "path",
+                        "url": url.to_string_lossy().replace('\\', "/"),
+                    }),
+                );
+            }
+            linked.push(entry);
+        }
+
+        let mut project_lock = BTreeMap::new();
+        project_lock.insert(
+            "packages".to_string(),
+            Value::from(project_prod.into_iter().chain(linked).collect::<Vec<_>>()),
+        );
+        project_lock.insert("packages-dev".to_string(), Value::from(project_dev));
+        write_installed_metadata(
+            &sonic_rs::to_value(&project_lock)?,
+            &vendor_dir,
+            &project.dir,
+            &installer_paths,
+            &project.manifest,
+            !args.no_dev,
+        )?;
+
+        // Activate plugins installed by this run
+        plugins.reload(&project.manifest).await;
+
+        plugins.dispatch(Hook::PreAutoloadDump).await;
+        if !args.no_scripts {
+            warn_script_failure(
+                "Pre-autoload",
+                run_pre_autoload_scripts(&project.manifest, &script_config)?,
+            );
+        }
+        generate_autoloader(&project.dir, &project.manifest, &vendor_dir, args)?;
+        plugins.dispatch(Hook::PostAutoloadDump).await;
+        if !args.no_scripts {
+            warn_script_failure(
+                "Post-autoload",
+                run_post_autoload_scripts(&project.manifest, &script_config)?,
+            );
+        }
+
+        plugins.dispatch(post_command).await;
+        if !args.no_scripts {
+            warn_script_failure(
+                &format!("Post-{operation}"),
+                run_post_install_scripts(&project.manifest, &script_config, update)?,
+            );
+        }
+    }
+
+    success(&format!(
+        "Workspace installed ({})",
+        crate::output::format_duration(start.elapsed())
+    ));
+
+    if args.audit && !args.dry_run {
+        run_security_audit(&lock_path, &manifest, args).await?;
+    }
+    Ok(())
+}
+
+/// Warn about a lifecycle script that did not succeed.
+fn warn_script_failure(label: &str, result: Option<ScriptResult>) {
+    if let Some(result) = result
+        && !result.success
+        && let Some(err) = result.error
+    {
+        warning(&format!("{label} script warning: {err}"));
+    }
+}
+
 /// Run security audit on installed packages.
-async fn run_security_audit(lock_path: &PathBuf, args: &InstallArgs) -> Result<()> {
+async fn run_security_audit(
+    lock_path: &PathBuf,
+    composer: &Value,
+    args: &InstallArgs,
+) -> Result<()> {
     if !lock_path.exists() {
         return Ok(());
     }
@@ -307,23 +733,19 @@ async fn run_security_audit(lock_path: &PathBuf, args: &InstallArgs) -> Result<(
     let lock_content = std::fs::read_to_string(lock_path)?;
     let lock: Value = sonic_rs::from_str(&lock_content)?;
 
-    let mut packages_to_audit: Vec<(PackageId, Version)> = Vec::new();
+    let mut packages_to_audit: Vec<(PackageId, String)> = Vec::new();
 
     // Collect packages from lock file
     for key in ["packages", "packages-dev"] {
         if let Some(pkgs) = lock.get(key).and_then(|v| v.as_array()) {
             for pkg in pkgs {
                 let name = pkg.get("name").and_then(|v| v.as_str()).unwrap_or("");
-                let version_str = pkg
-                    .get("version")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .trim_start_matches('v');
+                let version = pkg.get("version").and_then(|v| v.as_str()).unwrap_or("");
 
                 if let Some(id) = PackageId::parse(name)
-                    && let Ok(ver) = Version::parse(version_str)
+                    && !version.is_empty()
                 {
-                    packages_to_audit.push((id, ver));
+                    packages_to_audit.push((id, version.to_string()));
                 }
             }
         }
@@ -333,14 +755,19 @@ async fn run_security_audit(lock_path: &PathBuf, args: &InstallArgs) -> Result<(
         return Ok(());
     }
 
-    let auditor = Auditor::new().map_err(|e| anyhow::anyhow!("Failed to create auditor: {e}"))?;
-    let report = auditor
+    let auditor = Auditor::new()
+        .map_err(|e| anyhow::anyhow!("Failed to create auditor: {e}"))?
+        .with_advisory_database(crate::commands::audit_db::advisory_database(false)?);
+    let mut report = auditor
         .audit(&packages_to_audit)
         .await
         .map_err(|e| anyhow::anyhow!("Audit failed: {e}"))?;
+    let policy = crate::security::ignore_policy(&crate::security::audit_configs(composer));
+    crate::commands::audit::apply_ignores(&mut report, &policy);
 
     if report.vulnerability_count() == 0 {
         success("No security vulnerabilities found");
+        crate::commands::audit::print_ignored(&report);
         return Ok(());
     }
 
@@ -356,22 +783,21 @@ async fn run_security_audit(lock_path: &PathBuf, args: &InstallArgs) -> Result<(
         let reset = "\x1b[0m";
 
         for vuln in vulns {
-            println!(
+            out!(
                 "  {color}[{}]{reset} {} ({})",
-                severity, vuln.advisory_id, vuln.package
+                severity,
+                vuln.advisory_id,
+                vuln.package
             );
-            println!("    {}", vuln.title);
+            out!("    {}", vuln.title);
             if let Some(ref fixed) = vuln.fixed_version {
-                println!("    Fixed in: {fixed}");
+                out!("    Fixed in: {fixed}");
             }
         }
     }
+    crate::commands::audit::print_ignored(&report);
 
     // Fail if requested and critical/high vulnerabilities found
-    if args.fail_on_audit && report.has_critical() {
-        bail!("Critical security vulnerabilities found. Use --no-fail to continue anyway.");
-    }
-
     if args.fail_on_audit && !report.passes() {
         bail!("Security vulnerabilities found. Use --no-fail to continue anyway.");
     }
@@ -510,13 +936,16 @@ fn collect_lock_package_notes(lock_path: &PathBuf, include_dev: bool) -> Result<
 }
 
 /// Install from an existing lock file.
+#[allow(clippy::too_many_arguments)]
 async fn install_from_lock(
     lock_path: &PathBuf,
     vendor_dir: &PathBuf,
     base_dir: &std::path::Path,
     installer_paths: &InstallerPaths,
+    signing: &SigningPolicy,
     args: &InstallArgs,
     progress: Option<&LiveProgress>,
+    plugins: &PluginRuntime,
 ) -> Result<()> {
     let lock_content = std::fs::read_to_string(lock_path)?;
     let lock: Value = sonic_rs::from_str(&lock_content)?;
@@ -567,8 +996,10 @@ async fn install_from_lock(
         vendor_dir,
         base_dir,
         installer_paths,
+        signing,
         args,
         progress,
+        plugins,
     )
     .await?;
 
@@ -576,14 +1007,17 @@ async fn install_from_lock(
 }
 
 /// Resolve dependencies and install.
+#[allow(clippy::too_many_arguments)]
 async fn resolve_and_install(
     composer: &Value,
     lock_path: &PathBuf,
     vendor_dir: &PathBuf,
     base_dir: &std::path::Path,
     installer_paths: &InstallerPaths,
+    signing: &SigningPolicy,
     args: &InstallArgs,
     progress: Option<&LiveProgress>,
+    plugins: &PluginRuntime,
 ) -> Result<()> {
     // Collect requirements from composer.json
     let mut require: HashMap<String, String> = HashMap::new();
@@ -616,39 +1050,6 @@ async fn resolve_and_install(
         require_dev.len()
     ));
 
-    // Parse minimum stability
-    let min_stability = args
-        .minimum_stability
-        .as_deref()
-        .and_then(parse_stability)
-        .or_else(|| {
-            composer
-                .get("minimum-stability")
-                .and_then(|v| v.as_str())
-                .and_then(parse_stability)
-        })
-        .unwrap_or(Stability::Stable);
-
-    // Create fetcher
-    let fetcher = Arc::new(
-        Fetcher::new_with_composer_repositories(composer)
-            .map_err(|e| anyhow::anyhow!("Failed to create fetcher: {e}"))?,
-    );
-
-    // Configure resolver
-    let config = TurboConfig {
-        max_concurrent: args.concurrency.max(32),
-        request_timeout: std::time::Duration::from_secs(10),
-        mode: if args.prefer_lowest {
-            ResolutionMode::PreferLowest
-        } else {
-            // Default to PreferStable like Composer does
-            ResolutionMode::PreferStable
-        },
-        min_stability,
-        include_dev: !args.no_dev,
-    };
-
     // Parse dependencies
     let mut root_deps = Vec::new();
     let mut dev_deps = Vec::new();
@@ -677,26 +1078,8 @@ async fn resolve_and_install(
         }
     }
 
-    // Resolve dependencies
-    if let Some(p) = progress {
-        p.set_resolving();
-    }
-
-    let resolver = TurboResolver::new(fetcher.clone(), config);
-    let resolution = resolver
-        .resolve(&root_deps, &dev_deps)
-        .await
-        .map_err(|e| anyhow::anyhow!("Resolution failed: {e}"))?;
-
-    // Log fetcher statistics
-    let stats = fetcher.stats();
-    tracing::debug!(
-        requests = stats.requests,
-        bytes = stats.bytes_downloaded,
-        cache_hits = stats.cache_hits,
-        cache_hit_rate = format!("{:.1}%", stats.cache_hit_rate()),
-        "resolution fetch statistics"
-    );
+    let resolution =
+        resolve_dependencies(composer, &root_deps, &dev_deps, args, progress, plugins).await?;
 
     // Convert to package info
     let packages: Vec<PackageInfo> = resolution
@@ -707,11 +1090,25 @@ async fn resolve_and_install(
             version: p.version.to_string(),
             is_dev: p.is_dev,
             dist_url: p.dist_url.clone(),
+            dist_type: p.dist_type.clone(),
+            dist_reference: p.source_reference.clone(),
             dist_shasum: p.dist_shasum.clone(),
+            dist_signature: p.dist_signature.clone(),
+            source_url: p.source_url.clone(),
+            source_reference: p.source_reference.clone(),
             package_type: p.package_type.clone(),
+            bin: p.bin.clone().unwrap_or_default(),
         })
         .collect();
 
+    let resolved: Vec<(String, String)> = packages
+        .iter()
+        .map(|p| (p.name.clone(), p.version.clone()))
+        .collect();
+    plugins
+        .dispatch_packages(Hook::PostDependenciesSolving, &resolved)
+        .await;
+
     if args.dry_run {
         info(&format!("Would install {} package(s)", packages.len()));
         show_packages_table(&packages);
@@ -727,8 +1124,10 @@ async fn resolve_and_install(
         vendor_dir,
         base_dir,
         installer_paths,
+        signing,
         args,
         progress,
+        plugins,
     )
     .await?;
 
@@ -738,15 +1137,81 @@ async fn resolve_and_install(
     Ok(())
 }
 
-fn parse_stability(s: &str) -> Option<Stability> {
-    match s.to_lowercase().as_str() {
-        "dev" => Some(Stability::Dev),
-        "alpha" => Some(Stability::Alpha),
-        "beta" => Some(Stability::Beta),
-        "rc" => Some(Stability::RC),
-        "stable" => Some(Stability::Stable),
-        _ => None,
+/// Run the resolver over the root requirements.
+async fn resolve_dependencies(
+    composer: &Value,
+    root_deps: &[Dependency],
+    dev_deps: &[Dependency],
+    args: &InstallArgs,
+    progress: Option<&LiveProgress>,
+    plugins: &PluginRuntime,
+) -> Result<libretto_resolver::Resolution> {
+    // Parse minimum stability
+    let min_stability = args
+        .minimum_stability
+        .as_deref()
+        .and_then(parse_stability)
+        .unwrap_or_else(|| minimum_stability(composer));
+
+    // Create fetcher
+    let fetcher = Arc::new(
+        Fetcher::new_with_composer_repositories(composer)
+            .map_err(|e| anyhow::anyhow!("Failed to create fetcher: {e}"))?,
+    );
+
+    // Configure resolver
+    let security = SecurityPolicy::load(composer, args.no_security_blocking);
+    let config = TurboConfig {
+        max_concurrent: args.concurrency.max(32),
+        request_timeout: std::time::Duration::from_secs(10),
+        mode: if args.prefer_lowest {
+            ResolutionMode::PreferLowest
+        } else {
+            // Default to PreferStable like Composer does
+            ResolutionMode::PreferStable
+        },
+        min_stability,
+        include_dev: !args.no_dev,
+        block_abandoned: security.block_abandoned,
+    };
+
+    // Resolve dependencies
+    if let Some(p) = progress {
+        p.set_resolving();
+    }
+
+    plugins.dispatch(Hook::PreDependenciesSolving).await;
+
+    let mut resolver = security.apply(TurboResolver::new(fetcher.clone(), config));
+    if let Some(cache) = resolution_cache(composer) {
+        resolver = resolver.with_cache(cache);
     }
+    let resolve_start = Instant::now();
+    events::emit(Event::ResolutionStart {
+        requirements: root_deps.len(),
+        dev_requirements: dev_deps.len(),
+    });
+    let resolution = resolver
+        .resolve(root_deps, dev_deps)
+        .await
+        .map_err(|e| anyhow::anyhow!("Resolution failed: {e}"))?;
+    events::emit(Event::ResolutionFinish {
+        packages: resolution.packages.len(),
+        duration_ms: events::millis(resolve_start.elapsed()),
+        stats: resolver.stats().into(),
+    });
+
+    // Log fetcher statistics
+    let stats = fetcher.stats();
+    tracing::debug!(
+        requests = stats.requests,
+        bytes = stats.bytes_downloaded,
+        cache_hits = stats.cache_hits,
+        cache_hit_rate = format!("{:.1}%", stats.cache_hit_rate()),
+        "resolution fetch statistics"
+    );
+
+    Ok(resolution)
 }
 
 fn is_platform_package(name: &str) -> bool {
@@ -762,6 +1227,7 @@ fn is_package_already_installed(
     version: &str,
     dist_url: &str,
     dist_shasum: Option<&str>,
+    require_signature: bool,
 ) -> bool {
     if !dest.is_dir() {
         return false;
@@ -786,7 +1252,10 @@ fn is_package_already_installed(
         .unwrap_or("");
     let expected_shasum = dist_shasum.unwrap_or("");
 
-    marker_version == version && marker_url == dist_url && marker_shasum == expected_shasum
+    marker_version == version
+        && marker_url == dist_url
+        && marker_shasum == expected_shasum
+        && (!require_signature || SignatureCheck::is_verified_record(marker.get("signature")))
 }
 
 fn write_install_marker(
@@ -794,6 +1263,7 @@ fn write_install_marker(
     version: &str,
     dist_url: &str,
     dist_shasum: Option<&str>,
+    signature: Option<&SignatureCheck>,
 ) -> Result<()> {
     if !dest.is_dir() {
         return Ok(());
@@ -806,6 +1276,9 @@ fn write_install_marker(
         "dist_shasum".to_string(),
         Value::from(dist_shasum.unwrap_or("")),
     );
+    if let Some(signature) = signature {
+        marker.insert("signature".to_string(), signature.to_value());
+    }
 
     let marker_content = sonic_rs::to_string(&marker)?;
     std::fs::write(install_marker_path(dest), marker_content)?;
@@ -819,9 +1292,19 @@ struct PackageInfo {
     version: String,
     is_dev: bool,
     dist_url: Option<String>,
+    /// Dist archive type (`zip`, `tar`, `gzip`, ...)
+    dist_type: Option<String>,
+    dist_reference: Option<String>,
     dist_shasum: Option<String>,
+    /// Detached signature URL of the dist archive (`dist.signature`)
+    dist_signature: Option<String>,
+    /// VCS repository, used when no dist URL can be downloaded
+    source_url: Option<String>,
+    source_reference: Option<String>,
     /// Package type (e.g., "library", "wordpress-plugin", "drupal-module")
     package_type: Option<String>,
+    /// Binaries declared by the package, relative to its root
+    bin: Vec<String>,
 }
 
 fn parse_lock_package(pkg: &Value, is_dev: bool) -> Option<PackageInfo> {
@@ -832,20 +1315,63 @@ fn parse_lock_package(pkg: &Value, is_dev: bool) -> Option<PackageInfo> {
         .and_then(|d| d.get("url"))
         .and_then(|u| u.as_str())
         .map(String::from);
+    let dist_type = pkg
+        .get("dist")
+        .and_then(|d| d.get("type"))
+        .and_then(|t| t.as_str())
+        .map(String::from);
+    let dist_reference = pkg
+        .get("dist")
+        .and_then(|d| d.get("reference"))
+        .and_then(|r| r.as_str())
+        .map(String::from);
     let dist_shasum = pkg
         .get("dist")
         .and_then(|d| d.get("shasum"))
         .and_then(|u| u.as_str())
         .map(String::from);
+    let dist_signature = pkg
+        .get("dist")
+        .and_then(|d| d.get("signature"))
+        .and_then(|u| u.as_str())
+        .map(String::from);
+    let source_url = pkg
+        .get("source")
+        .and_then(|s| s.get("url"))
+        .and_then(|u| u.as_str())
+        .map(String::from);
+    let source_reference = pkg
+        .get("source")
+        .and_then(|s| s.get("reference"))
+        .and_then(|r| r.as_str())
+        .map(String::from);
     let package_type = pkg.get("type").and_then(|t| t.as_str()).map(String::from);
+    let bin = match pkg.get("bin") {
+        Some(bin) if bin.is_str() => bin.as_str().map(String::from).into_iter().collect(),
+        Some(bin) => bin
+            .as_array()
+            .map(|bins| {
+                bins.iter()
+                    .filter_map(|b| b.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
 
     Some(PackageInfo {
         name: name.to_string(),
         version: version.to_string(),
         is_dev,
         dist_url,
+        dist_type,
+        dist_reference,
         dist_shasum,
+        dist_signature,
+        source_url,
+        source_reference,
         package_type,
+        bin,
     })
 }
 
@@ -907,13 +1433,19 @@ fn show_packages_table(packages: &[PackageInfo]) {
 }
 
 /// Install packages with parallel downloads and CAS cache.
+///
+/// Packages whose signing mode is `require` are always downloaded, since the
+/// cache keeps extracted files and their archive signature cannot be checked.
+#[allow(clippy::too_many_arguments)]
 async fn install_packages(
     packages: &[PackageInfo],
     vendor_dir: &std::path::Path,
     base_dir: &std::path::Path,
     installer_paths: &InstallerPaths,
+    signing: &SigningPolicy,
     args: &InstallArgs,
     progress: Option<&LiveProgress>,
+    plugins: &PluginRuntime,
 ) -> Result<()> {
     let start = Instant::now();
 
@@ -930,25 +1462,10 @@ async fn install_packages(
         debug!("Using existing GitHub OAuth token");
     }
 
-    // Build HTTP client with optimized settings
-    let client = reqwest::Client::builder()
-        .pool_max_idle_per_host(100)
-        .pool_idle_timeout(std::time::Duration::from_secs(90))
-        .http2_adaptive_window(true)
-        .http2_initial_stream_window_size(Some(4 * 1024 * 1024))
-        .http2_initial_connection_window_size(Some(8 * 1024 * 1024))
-        .http2_keep_alive_interval(Some(std::time::Duration::from_secs(15)))
-        .http2_keep_alive_timeout(std::time::Duration::from_secs(20))
-        .http2_keep_alive_while_idle(true)
-        .connect_timeout(std::time::Duration::from_secs(10))
-        .timeout(std::time::Duration::from_secs(60))
-        .tcp_nodelay(true)
-        .tcp_keepalive(std::time::Duration::from_secs(30))
-        .gzip(true)
-        .brotli(true)
-        .deflate(true)
-        .build()
-        .context("Failed to create HTTP client")?;
+    let downloader = Arc::new(PackageDownloader::new(&args.download)?);
+    let source_fallback = args.download.source_fallback;
+    let by_name: HashMap<&str, &PackageInfo> =
+        packages.iter().map(|p| (p.name.as_str(), p)).collect();
 
     // Separate cached vs need-download
     let mut to_download: Vec<(String, String, String, PathBuf, Option<String>)> = Vec::new();
@@ -967,14 +1484,21 @@ async fn install_packages(
 
         if let Some(ref url_str) = pkg.dist_url {
             let url = convert_github_api_url(url_str);
-
-            if is_package_already_installed(&dest, &pkg.version, &url, pkg.dist_shasum.as_deref()) {
+            let require_signature = signing.mode_for(&pkg.name) == SigningMode::Require;
+
+            if is_package_already_installed(
+                &dest,
+                &pkg.version,
+                &url,
+                pkg.dist_shasum.as_deref(),
+                require_signature,
+            ) {
                 unchanged += 1;
                 continue;
             }
 
             // Use is_cached for quick check, then get_cached_path for the actual path
-            if cas_cache::is_cached(&url) {
+            if !require_signature && cas_cache::is_cached(&url) {
                 if let Some(cache_path) = cas_cache::get_cached_path(&url) {
                     from_cache.push((
                         pkg.name.clone(),
@@ -1012,6 +1536,13 @@ async fn install_packages(
     let download_count = to_download.len();
     let total = cached_count + download_count;
 
+    events::emit(Event::InstallPlan {
+        packages: packages.len(),
+        download: download_count,
+        cached: cached_count,
+        unchanged,
+    });
+
     if total == 0 {
         if unchanged > 0 {
             info(&format!(
@@ -1038,12 +1569,34 @@ async fn install_packages(
         if let Some(p) = progress {
             p.set_current(name);
         }
+        plugins
+            .dispatch_package(Hook::PrePackageInstall, name, version)
+            .await;
+        let linked = Instant::now();
         if let Err(e) = cas_cache::link_from_cache(cache_path, dest) {
             warning(&format!("Cache link failed for {name}: {e}"));
-        } else if let Err(e) = write_install_marker(dest, version, url, shasum.as_deref()) {
-            warning(&format!(
-                "Installed package marker write failed for {name}: {e}"
-            ));
+        } else {
+            events::emit(Event::Download {
+                package: name.clone(),
+                version: version.clone(),
+                url: url.clone(),
+                mirror: None,
+                bytes: 0,
+                cache_hit: true,
+                duration_ms: events::millis(linked.elapsed()),
+            });
+            let signature =
+                (signing.mode_for(name) != SigningMode::Off).then_some(SignatureCheck::Unchecked);
+            if let Err(e) =
+                write_install_marker(dest, version, url, shasum.as_deref(), signature.as_ref())
+            {
+                warning(&format!(
+                    "Installed package marker write failed for {name}: {e}"
+                ));
+            }
+            plugins
+                .dispatch_package(Hook::PostPackageInstall, name, version)
+                .await;
         }
         if let Some(p) = progress {
             p.inc_completed();
@@ -1054,6 +1607,14 @@ async fn install_packages(
         return Ok(());
     }
 
+    let mirrors = MirrorSet::load(base_dir, downloader.client.inner(), |url| {
+        extract_domain_from_url(url)
+            .and_then(|domain| auth_manager.get_credential(&domain))
+            .map(|credential| credential_headers(&credential, url))
+            .unwrap_or_default()
+    })
+    .await;
+
     // Adaptive concurrency based on CPU cores
     let cpu_cores = std::thread::available_parallelism()
         .map(std::num::NonZero::get)
@@ -1073,39 +1634,51 @@ async fn install_packages(
     let mut errors: Vec<String> = Vec::new();
     let verify_checksums = args.verify_checksums;
 
-    // Track credentials for retry - convert GitHub token to Credential if present
-    let mut credential_for_retry: Option<Credential> = github_token.map(Credential::GitHubOAuth);
+    let github_credential = github_token.map(Credential::GitHubOAuth);
     let mut rate_limited_packages: Vec<(String, String, String, PathBuf, Option<String>)> =
         Vec::new();
 
     while !pending.is_empty() || !in_flight.is_empty() {
         while in_flight.len() < max_concurrent && !pending.is_empty() {
             let (name, version, url, dest, shasum) = pending.pop().unwrap();
-            let client = client.clone();
+            let downloader = Arc::clone(&downloader);
             let total_bytes = Arc::clone(&total_bytes);
-
-            // Get credential for this URL's domain
-            let credential = credential_for_retry.clone().or_else(|| {
-                extract_domain_from_url(&url)
-                    .and_then(|domain| auth_manager.get_credential(&domain))
-            });
+            let package = by_name[name.as_str()];
 
             // Update progress with current package
             if let Some(p) = progress {
                 p.set_current(&name);
             }
 
+            plugins
+                .dispatch_package(Hook::PrePackageInstall, &name, &version)
+                .await;
+            // Plugins may point the download at a mirror; the cache stays keyed by the original URL
+            let download_url = plugins.rewrite_download_url(&name, &url).await;
+            let mut candidates = dist_candidates(
+                package,
+                &download_url,
+                mirrors.for_dist_url(&url),
+                source_fallback,
+            );
+            for candidate in &mut candidates {
+                candidate.credential = credential_for_url(
+                    &candidate.url,
+                    &auth_manager,
+                    github_credential.as_ref(),
+                    None,
+                );
+            }
+
             in_flight.push(async move {
                 let result = download_and_extract_with_credential(
-                    &client,
-                    &name,
-                    &version,
-                    &url,
+                    &downloader,
+                    package,
+                    candidates,
                     &dest,
                     &total_bytes,
-                    shasum.as_deref(),
                     verify_checksums,
-                    credential.as_ref(),
+                    signing,
                 )
                 .await;
                 (name, version, url, dest, shasum, result)
@@ -1114,36 +1687,41 @@ async fn install_packages(
 
         if let Some((name, version, url, dest, shasum, result)) = in_flight.next().await {
             match result {
-                Ok(dest_path) => {
+                Ok((dest_path, signature)) => {
                     completed.fetch_add(1, Ordering::Relaxed);
                     if let Some(p) = progress {
                         p.inc_completed();
                         p.add_bytes(total_bytes.load(Ordering::Relaxed));
                     }
                     let _ = cas_cache::store_in_cache(&url, &dest_path);
-                    if let Err(e) =
-                        write_install_marker(&dest_path, &version, &url, shasum.as_deref())
-                    {
+                    if let Err(e) = write_install_marker(
+                        &dest_path,
+                        &version,
+                        &url,
+                        shasum.as_deref(),
+                        signature.as_ref(),
+                    ) {
                         warning(&format!(
                             "Installed package marker write failed for {name}: {e}"
                         ));
                     }
+                    plugins
+                        .dispatch_package(Hook::PostPackageInstall, &name, &version)
+                        .await;
                 }
                 Err(e) => {
-                    let error_str = e.to_string();
                     // Check if this is an authentication/rate limit error that can be retried
-                    let is_github_rate_limit = error_str.contains("rate limit")
-                        && (url.contains("github.com") || url.contains("codeload.github.com"));
-                    let is_auth_failure = error_str.contains("HTTP 401")
-                        || error_str.contains("HTTP 403")
-                        || error_str.contains("Unauthorized")
-                        || error_str.contains("Forbidden");
+                    let status = download_error_status(&e);
+                    let is_github_rate_limit =
+                        status.is_some_and(|status| is_github_rate_limit_error(&url, status));
+                    let is_auth_failure = matches!(status, Some(401 | 403));
 
                     if is_github_rate_limit || is_auth_failure {
                         // Queue for retry after getting credentials
                         rate_limited_packages.push((name, version, url, dest, shasum));
                     } else {
                         failed_count.fetch_add(1, Ordering::Relaxed);
+                        package_failed(&name, &version, &e.to_string());
                         errors.push(format!("{name}: {e}"));
                     }
                 }
@@ -1152,12 +1730,17 @@ async fn install_packages(
     }
 
     // Handle packages that failed due to authentication/rate limit issues
-    if !rate_limited_packages.is_empty() && credential_for_retry.is_none() {
+    let had_credential = rate_limited_packages
+        .first()
+        .is_some_and(|(_, _, url, _, _)| {
+            credential_for_url(url, &auth_manager, github_credential.as_ref(), None).is_some()
+        });
+    if !rate_limited_packages.is_empty() && !had_credential {
         // Detect auth type based on first URL's domain
         let first_url = &rate_limited_packages[0].2;
         let (domain, auth_type) = detect_auth_type_for_url(first_url);
 
-        let reason = if first_url.contains("github.com") {
+        let reason = if is_github_url(first_url) {
             let rate_limit_info = GitHubRateLimitInfo {
                 url: first_url.clone(),
                 limit: 60,
@@ -1167,8 +1750,9 @@ async fn install_packages(
                     .unwrap_or(0),
             };
             format!(
-                "GitHub API limit (60 calls/hr) is exhausted, could not fetch {}.\n\
+                "GitHub API limit ({} calls/hr) is exhausted, could not fetch {}.\n\
                  You can also wait until {} for the rate limit to reset.",
+                rate_limit_info.limit,
                 rate_limit_info.url,
                 rate_limit_info.reset_time_string()
             )
@@ -1181,7 +1765,7 @@ async fn install_packages(
 
         match auth_manager.prompt_for_auth_type(&domain, auth_type, &reason) {
             Ok(Some(cred)) => {
-                let _ = credential_for_retry.insert(cred.clone());
+                let prompted = (domain, cred);
                 info(&format!(
                     "Retrying {} packages with credentials...",
                     rate_limited_packages.len()
@@ -1192,37 +1776,59 @@ async fn install_packages(
                     if let Some(p) = progress {
                         p.set_current(&name);
                     }
+                    let package = by_name[name.as_str()];
+                    let download_url = plugins.rewrite_download_url(&name, &url).await;
+                    let mut candidates = dist_candidates(
+                        package,
+                        &download_url,
+                        mirrors.for_dist_url(&url),
+                        source_fallback,
+                    );
+                    for candidate in &mut candidates {
+                        candidate.credential = credential_for_url(
+                            &candidate.url,
+                            &auth_manager,
+                            github_credential.as_ref(),
+                            Some(&prompted),
+                        );
+                    }
 
                     match download_and_extract_with_credential(
-                        &client,
-                        &name,
-                        &version,
-                        &url,
+                        &downloader,
+                        package,
+                        candidates,
                         &dest,
                         &total_bytes,
-                        shasum.as_deref(),
                         verify_checksums,
-                        Some(&cred),
+                        signing,
                     )
                     .await
                     {
-                        Ok(dest_path) => {
+                        Ok((dest_path, signature)) => {
                             completed.fetch_add(1, Ordering::Relaxed);
                             if let Some(p) = progress {
                                 p.inc_completed();
                                 p.add_bytes(total_bytes.load(Ordering::Relaxed));
                             }
                             let _ = cas_cache::store_in_cache(&url, &dest_path);
-                            if let Err(e) =
-                                write_install_marker(&dest_path, &version, &url, shasum.as_deref())
-                            {
+                            if let Err(e) = write_install_marker(
+                                &dest_path,
+                                &version,
+                                &url,
+                                shasum.as_deref(),
+                                signature.as_ref(),
+                            ) {
                                 warning(&format!(
                                     "Installed package marker write failed for {name}: {e}"
                                 ));
                             }
+                            plugins
+                                .dispatch_package(Hook::PostPackageInstall, &name, &version)
+                                .await;
                         }
                         Err(e) => {
                             failed_count.fetch_add(1, Ordering::Relaxed);
+                            package_failed(&name, &version, &e.to_string());
                             errors.push(format!("{name}: {e}"));
                         }
                     }
@@ -1230,16 +1836,20 @@ async fn install_packages(
             }
             Ok(None) => {
                 // User declined to provide credentials, mark all as failed
-                for (name, _, url, _, _) in rate_limited_packages {
+                for (name, version, url, _, _) in rate_limited_packages {
                     failed_count.fetch_add(1, Ordering::Relaxed);
-                    errors.push(format!("{name}: Authentication required for {url}"));
+                    let error = format!("Authentication required for {url}");
+                    package_failed(&name, &version, &error);
+                    errors.push(format!("{name}: {error}"));
                 }
             }
             Err(e) => {
                 warning(&format!("Failed to prompt for credentials: {e}"));
-                for (name, _, url, _, _) in rate_limited_packages {
+                for (name, version, url, _, _) in rate_limited_packages {
                     failed_count.fetch_add(1, Ordering::Relaxed);
-                    errors.push(format!("{name}: Authentication required for {url}"));
+                    let error = format!("Authentication required for {url}");
+                    package_failed(&name, &version, &error);
+                    errors.push(format!("{name}: {error}"));
                 }
             }
         }
@@ -1248,12 +1858,24 @@ async fn install_packages(
         warning(
             "Authentication failed despite having credentials. They may be invalid or expired.",
         );
-        for (name, _, url, _, _) in rate_limited_packages {
+        for (name, version, url, _, _) in rate_limited_packages {
             failed_count.fetch_add(1, Ordering::Relaxed);
-            errors.push(format!("{name}: Authentication failed for {url}"));
+            let error = format!("Authentication failed for {url}");
+            package_failed(&name, &version, &error);
+            errors.push(format!("{name}: {error}"));
         }
     }
 
+    for stats in downloader.mirror_stats.snapshot() {
+        debug!(
+            mirror = %stats.mirror,
+            attempts = stats.attempts,
+            successes = stats.successes,
+            avg_latency_ms = stats.average_latency().as_millis(),
+            "mirror stats"
+        );
+    }
+
     let elapsed = start.elapsed();
     let installed = completed.load(Ordering::Relaxed) + cached_count as u64;
     let failed = failed_count.load(Ordering::Relaxed);
@@ -1287,7 +1909,7 @@ async fn install_packages(
         );
 
         if !args.no_progress {
-            println!(
+            out!(
                 "  Installed {} packages in {:.2}s ({} downloaded{}, {} from cache, {} already installed)",
                 installed,
                 elapsed.as_secs_f64(),
@@ -1298,7 +1920,7 @@ async fn install_packages(
             );
         }
     } else if unchanged_count > 0 && !args.no_progress {
-        println!(
+        out!(
             "  Nothing to install, update or remove ({unchanged_count} package(s) already installed)"
         );
     }
@@ -1306,6 +1928,256 @@ async fn install_packages(
     Ok(())
 }
 
+/// Report a package that could not be installed on the event stream.
+fn package_failed(name: &str, version: &str, error: &str) {
+    events::emit(Event::PackageFailed {
+        package: name.to_string(),
+        version: version.to_string(),
+        error: error.to_string(),
+    });
+}
+
+/// Package archive downloader shared by all downloads of an install run.
+///
+/// Archives are streamed to disk with bounded memory, resumed from partial
+/// files, retried with jittered exponential backoff and throttled to the
+/// configured bandwidth. Hosts that keep failing trip a per-host circuit
+/// breaker so the remaining packages from that host fail fast.
+struct PackageDownloader {
+    client: HttpClient,
+    throttler: BandwidthThrottler,
+    retry: RetryConfig,
+    circuit_breakers: DashMap<String, CircuitBreaker>,
+    mirror_stats: MirrorStats,
+}
+
+impl PackageDownloader {
+    fn new(args: &DownloadArgs) -> Result<Self> {
+        let config = DownloadConfig::builder()
+            .connect_timeout(std::time::Duration::from_secs(10))
+            .max_connections_per_host(100)
+            .max_retries(args.retries)
+            .bandwidth_limit(args.max_bandwidth)
+            .show_progress(false)
+            .build();
+        let retry = RetryConfig {
+            max_retries: config.max_retries,
+            base_delay: config.retry_base_delay,
+            max_delay: config.retry_max_delay,
+            jitter: 0.1,
+        };
+        let throttler = BandwidthThrottler::new(config.bandwidth_limit);
+        let client = HttpClient::new(config, None).context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            throttler,
+            retry,
+            circuit_breakers: DashMap::new(),
+            mirror_stats: MirrorStats::new(),
+        })
+    }
+
+    /// Download `url` to `archive`, resuming a previous partial download.
+    async fn fetch(
+        &self,
+        name: &str,
+        url: &str,
+        archive: &Path,
+        checksums: &[ExpectedChecksum],
+        credential: Option<&Credential>,
+    ) -> std::result::Result<DownloadedFile, DownloadError> {
+        let url = url::Url::parse(url)?;
+        let host = url.host_str().unwrap_or("unknown").to_string();
+        if self
+            .circuit_breakers
+            .entry(host.clone())
+            .or_default()
+            .is_open()
+        {
+            return Err(DownloadError::network(format!(
+                "circuit breaker open for {host}"
+            )));
+        }
+
+        let client = match credential {
+            Some(cred) => self
+                .client
+                .clone()
+                .with_headers(credential_headers(cred, url.as_str())),
+            None => self.client.clone(),
+        };
+        let stream = StreamDownloader::new(client, self.throttler.clone());
+        let progress = ProgressTracker::disabled().start_download(name, name, None);
+
+        let result = with_retry(&self.retry, || {
+            stream.download(&url, archive, checksums, &progress)
+        })
+        .await;
+
+        let breaker = self.circuit_breakers.entry(host).or_default();
+        match &result {
+            Ok(_) => breaker.record_success(),
+            // Only transport and server failures count against the host
+            Err(e) if e.is_retryable() => breaker.record_failure(),
+            Err(_) => {}
+        }
+        result
+    }
+
+    /// Download a detached signature, or `None` if none is published at `url`.
+    async fn fetch_signature(&self, url: &str, credential: Option<&Credential>) -> Option<Vec<u8>> {
+        let url = url::Url::parse(url).ok()?;
+        let client = match credential {
+            Some(cred) => self
+                .client
+                .clone()
+                .with_headers(credential_headers(cred, url.as_str())),
+            None => self.client.clone(),
+        };
+        let mut response = client
+            .get(&url)
+            .await
+            .inspect_err(|e| debug!(url = %url, error = %e, "signature download failed"))
+            .ok()?;
+        if response
+            .content_length()
+            .is_some_and(|len| len > MAX_SIGNATURE_SIZE as u64)
+        {
+            return None;
+        }
+        // Content-Length may be missing or wrong, so the body is capped too
+        let mut signature = Vec::new();
+        while let Some(chunk) = response.chunk().await.ok()? {
+            if signature.len() + chunk.len() > MAX_SIGNATURE_SIZE {
+                return None;
+            }
+            signature.extend_from_slice(&chunk);
+        }
+        Some(signature)
+    }
+}
+
+/// HTTP status behind a failed download, when the server sent one.
+fn download_error_status(error: &anyhow::Error) -> Option<u16> {
+    match error.downcast_ref::<DownloadError>()? {
+        DownloadError::Authentication { message, .. } => {
+            message.strip_prefix("HTTP ")?.parse().ok()
+        }
+        DownloadError::RateLimited { .. } => Some(429),
+        DownloadError::ServerError { status, .. } => Some(*status),
+        DownloadError::Network { status_code, .. } => *status_code,
+        _ => None,
+    }
+}
+
+/// Where a dist candidate URL comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DistOrigin {
+    /// A repository dist mirror
+    Mirror,
+    /// The package's own dist URL
+    Dist,
+    /// An archive of the source VCS repository
+    Source,
+}
+
+/// Why a dist candidate could not be installed.
+#[derive(Debug)]
+enum DistFailure {
+    /// The archive could not be downloaded or unpacked
+    Transport(anyhow::Error),
+    /// The archive failed its checksum or signature check
+    Integrity(anyhow::Error),
+}
+
+/// One URL to try when downloading a package's dist archive.
+#[derive(Debug)]
+struct DistCandidate {
+    url: String,
+    origin: DistOrigin,
+    archive_type: ArchiveType,
+    credential: Option<Credential>,
+}
+
+/// URLs to try for `package`, in order: preferred mirrors, the canonical
+/// dist URL, the remaining mirrors and, with `source_fallback`, a source VCS
+/// archive.
+///
+/// Mirrors addressing dists by `%reference%` are skipped when the package
+/// has no dist reference.
+fn dist_candidates(
+    package: &PackageInfo,
+    url: &str,
+    mirrors: &[DistMirror],
+    source_fallback: bool,
+) -> Vec<DistCandidate> {
+    let archive_type =
+        ArchiveType::from_dist(package.dist_type.as_deref(), url).unwrap_or(ArchiveType::Zip);
+    let dist_type = package.dist_type.as_deref().unwrap_or("zip");
+    let reference = package.dist_reference.as_deref();
+    let usable = |mirror: &&DistMirror| reference.is_some() || !mirror.url.contains("%reference%");
+    let mirror = |mirror: &DistMirror| DistCandidate {
+        url: mirror.process_url(&package.name, &package.version, reference, dist_type),
+        origin: DistOrigin::Mirror,
+        archive_type,
+        credential: None,
+    };
+
+    let mut candidates: Vec<DistCandidate> = mirrors
+        .iter()
+        .filter(usable)
+        .filter(|m| m.preferred)
+        .map(mirror)
+        .collect();
+    candidates.push(DistCandidate {
+        url: url.to_string(),
+        origin: DistOrigin::Dist,
+        archive_type,
+        credential: None,
+    });
+    candidates.extend(
+        mirrors
+            .iter()
+            .filter(usable)
+            .filter(|m| !m.preferred)
+            .map(mirror),
+    );
+    if source_fallback && let Some(source) = source_archive_url(package) {
+        candidates.push(DistCandidate {
+            url: source,
+            origin: DistOrigin::Source,
+            archive_type: ArchiveType::Zip,
+            credential: None,
+        });
+    }
+
+    let mut seen = BTreeSet::new();
+    candidates.retain(|candidate| seen.insert(candidate.url.clone()));
+    candidates
+}
+
+/// Zip archive URL of the package's source repository on a known VCS host.
+fn source_archive_url(package: &PackageInfo) -> Option<String> {
+    use libretto_repository::providers::{ProviderType, detect_provider, parse_vcs_url};
+
+    let url = url::Url::parse(package.source_url.as_deref()?).ok()?;
+    let reference = package.source_reference.as_deref()?;
+    let (owner, repo) = parse_vcs_url(&url)?;
+    match detect_provider(&url)? {
+        ProviderType::GitHub => Some(format!(
+            "https://api.github.com/repos/{owner}/{repo}/zipball/{reference}"
+        )),
+        ProviderType::GitLab => Some(format!(
+            "https://{}/api/v4/projects/{owner}%2F{repo}/repository/archive.zip?sha={reference}",
+            url.host_str()?
+        )),
+        ProviderType::Bitbucket => Some(format!(
+            "https://bitbucket.org/{owner}/{repo}/get/{reference}.zip"
+        )),
+    }
+}
+
 /// Download and extract a package archive with credential-based authentication.
 ///
 /// Supports all Composer authentication types:
@@ -1316,185 +2188,275 @@ async fn install_packages(
 /// - Bearer tokens
 /// - Forgejo/Gitea tokens
 /// - Custom HTTP headers
-#[allow(clippy::too_many_arguments)]
+///
+/// Candidates are tried in order until one downloads, verifies and
+/// extracts. The archive format is taken from `dist.type`, falling back to
+/// the URL extension, and the single top-level directory that GitHub/GitLab
+/// archives wrap their contents in is stripped. A canonical dist that fails
+/// its checksum or signature check stops the install of the package, and a
+/// source archive is only tried when every earlier failure was a transport
+/// error. When every candidate fails the canonical dist URL's error is
+/// returned.
+///
+/// Returns the install path and, unless signing is off for the package, the
+/// outcome of its signature check.
 async fn download_and_extract_with_credential(
-    client: &reqwest::Client,
-    name: &str,
-    _version: &str,
-    url: &str,
+    downloader: &PackageDownloader,
+    package: &PackageInfo,
+    candidates: Vec<DistCandidate>,
     dest: &std::path::Path,
     total_bytes: &AtomicU64,
-    expected_shasum: Option<&str>,
     verify_checksums: bool,
-    credential: Option<&Credential>,
-) -> Result<PathBuf> {
-    let mut request = client.get(url);
-
-    // Apply credential-based authentication
-    if let Some(cred) = credential {
-        request = apply_credential_to_request(request, cred, url);
+    signing: &SigningPolicy,
+) -> Result<(PathBuf, Option<SignatureCheck>)> {
+    let mut failure: Option<anyhow::Error> = None;
+    let mut integrity_failed = false;
+    for candidate in candidates {
+        let origin = candidate.origin;
+        if origin == DistOrigin::Source && integrity_failed {
+            break;
+        }
+        match download_candidate(
+            downloader,
+            package,
+            candidate,
+            dest,
+            total_bytes,
+            verify_checksums,
+            signing,
+        )
+        .await
+        {
+            Ok(installed) => return Ok(installed),
+            Err(failed) => {
+                let (e, integrity) = match failed {
+                    DistFailure::Integrity(e) => (e, true),
+                    DistFailure::Transport(e) => (e, false),
+                };
+                debug!(package = %package.name, ?origin, error = %e, "dist download failed");
+                // The canonical archive was served but is not the one that was locked
+                if integrity && origin == DistOrigin::Dist {
+                    return Err(e);
+                }
+                integrity_failed |= integrity;
+                if failure.is_none() || origin == DistOrigin::Dist {
+                    failure = Some(e);
+                }
+            }
+        }
     }
+    Err(failure.unwrap_or_else(|| anyhow::anyhow!("No download URL for {}", package.name)))
+}
 
-    let response = request
-        .send()
-        .await
-        .with_context(|| format!("Failed to fetch {name}"))?;
+/// Download, verify and extract a single dist candidate.
+async fn download_candidate(
+    downloader: &PackageDownloader,
+    package: &PackageInfo,
+    candidate: DistCandidate,
+    dest: &std::path::Path,
+    total_bytes: &AtomicU64,
+    verify_checksums: bool,
+    signing: &SigningPolicy,
+) -> std::result::Result<(PathBuf, Option<SignatureCheck>), DistFailure> {
+    let name = package.name.as_str();
+    let url = candidate.url.as_str();
+    // Source archives are generated by the VCS host and never match the dist shasum
+    let checksums: Vec<ExpectedChecksum> = package
+        .dist_shasum
+        .as_deref()
+        .filter(|sum| verify_checksums && candidate.origin != DistOrigin::Source && !sum.is_empty())
+        .map(|sum| ExpectedChecksum::sha1(sum.to_ascii_lowercase()))
+        .into_iter()
+        .collect();
 
-    let status = response.status();
+    // Keyed by URL so a partial download is only ever resumed for the same archive
+    let archive = dest.with_extension(format!("{}.download", &url_digest(url)[..12]));
 
-    // Check for GitHub rate limit
-    if is_github_rate_limit_error(url, status.as_u16()) {
-        let rate_limit = parse_rate_limit_headers(url, response.headers()).unwrap_or_else(|| {
-            GitHubRateLimitInfo {
-                url: url.to_string(),
-                limit: 60,
-                reset: 0,
+    let started = Instant::now();
+    let file = downloader
+        .fetch(
+            name,
+            url,
+            &archive,
+            &checksums,
+            candidate.credential.as_ref(),
+        )
+        .await
+        .and_then(|file| match file.verify(&checksums, name) {
+            Ok(()) => Ok(file),
+            Err(e) => {
+                let _ = std::fs::remove_file(&archive);
+                Err(e)
             }
         });
-
-        anyhow::bail!(
-            "GitHub rate limit exceeded for {}: {} calls/hr limit, resets at {}",
-            name,
-            rate_limit.limit,
-            rate_limit.reset_time_string()
-        );
-    }
-
-    if !status.is_success() {
-        anyhow::bail!("HTTP {status}");
-    }
-
-    let bytes = response
-        .bytes()
+    let host = extract_domain_from_url(url).unwrap_or_else(|| "unknown".to_string());
+    downloader
+        .mirror_stats
+        .record(&host, file.is_ok(), started.elapsed());
+    let file = file.map_err(|e| match e {
+        DownloadError::ChecksumMismatch { .. } => DistFailure::Integrity(e.into()),
+        e => DistFailure::Transport(e.into()),
+    })?;
+
+    total_bytes.fetch_add(file.size, Ordering::Relaxed);
+    events::emit(Event::Download {
+        package: name.to_string(),
+        version: package.version.clone(),
+        url: url.to_string(),
+        mirror: (candidate.origin == DistOrigin::Mirror).then(|| host.clone()),
+        bytes: file.size,
+        cache_hit: false,
+        duration_ms: events::millis(started.elapsed()),
+    });
+
+    let signature = check_signature(downloader, signing, package, &candidate, &archive)
         .await
-        .with_context(|| format!("Failed to read response for {name}"))?;
-
-    total_bytes.fetch_add(bytes.len() as u64, Ordering::Relaxed);
-
-    // Verify checksum if provided and verification is enabled
-    if verify_checksums
-        && let Some(expected) = expected_shasum
-        && !expected.is_empty()
-    {
-        let actual = compute_sha1(&bytes);
-        if !constant_time_eq(&actual, expected) {
-            anyhow::bail!("Checksum mismatch: expected {expected}, got {actual}");
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&archive);
+            DistFailure::Integrity(e)
+        })?;
+
+    let archive_type = candidate.archive_type;
+    let mut extractor = Extractor::new()
+        .strip_root()
+        .executables(package.bin.clone());
+    if archive_type == ArchiveType::Gzip {
+        // A bare gzip dist holds a single file named after the download
+        let file_name = url
+            .split(['?', '#'])
+            .next()
+            .and_then(|path| path.rsplit('/').next())
+            .and_then(|file| file.strip_suffix(".gz"))
+            .filter(|file| !file.is_empty());
+        if let Some(file_name) = file_name {
+            extractor = extractor.file_name(file_name);
         }
     }
 
     // Extract in blocking task to not block async runtime
     let dest = dest.to_path_buf();
     let name = name.to_string();
+    let version = package.version.clone();
     tokio::task::spawn_blocking(move || {
-        use std::io::Write;
-
-        if let Some(parent) = dest.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        let temp_path = dest.with_extension("download.zip");
-        {
-            let mut file = std::fs::File::create(&temp_path)?;
-            file.write_all(&bytes)?;
+        let extract_start = Instant::now();
+        let result = extractor
+            .extract_as(&archive, archive_type, &dest)
+            .with_context(|| format!("Failed to extract {name}"));
+        let _ = std::fs::remove_file(&archive);
+        if result.is_ok() {
+            events::emit(Event::Extract {
+                package: name,
+                version,
+                path: dest.display().to_string(),
+                duration_ms: events::millis(extract_start.elapsed()),
+            });
         }
-
-        extract_zip(&temp_path, &dest).with_context(|| format!("Failed to extract {name}"))?;
-        let _ = std::fs::remove_file(&temp_path);
-
-        Ok(dest)
+        result.map(|_| (dest, signature))
     })
     .await
-    .context("Extraction task failed")?
-}
-
-/// Compute SHA-1 hash of bytes and return as hex string.
-fn compute_sha1(data: &[u8]) -> String {
-    use sha1::{Digest, Sha1};
-    let mut hasher = Sha1::new();
-    hasher.update(data);
-    hex::encode(hasher.finalize())
-}
-
-/// Constant-time string comparison to prevent timing attacks.
-fn constant_time_eq(a: &str, b: &str) -> bool {
-    if a.len() != b.len() {
-        return false;
-    }
-    let mut result = 0u8;
-    for (x, y) in a.bytes().zip(b.bytes()) {
-        result |= x ^ y;
-    }
-    result == 0
+    .context("Extraction task failed")
+    .and_then(|result| result)
+    .map_err(DistFailure::Transport)
 }
 
-fn extract_zip(zip_path: &std::path::Path, dest: &std::path::Path) -> Result<()> {
-    use std::io::Read;
-
-    let file = std::fs::File::open(zip_path)?;
-    let mut archive = zip::ZipArchive::new(file)?;
-
-    // Find common prefix (GitHub zips have vendor-repo-hash/ prefix)
-    let mut common_prefix: Option<String> = None;
-    for i in 0..archive.len() {
-        let entry = archive.by_index(i)?;
-        let path = entry.name();
-        if let Some(first_component) = path.split('/').next()
-            && !first_component.is_empty()
-        {
-            match &common_prefix {
-                None => common_prefix = Some(format!("{first_component}/")),
-                Some(p) if !path.starts_with(p) => {
-                    common_prefix = None;
-                    break;
-                }
-                _ => {}
-            }
-        }
-    }
-
-    let prefix_len = common_prefix.as_ref().map_or(0, std::string::String::len);
-
-    for i in 0..archive.len() {
-        let mut entry = archive.by_index(i)?;
-        let entry_path = entry.name();
-
-        if entry_path.len() <= prefix_len {
-            continue;
+/// Check the detached signature of a downloaded archive against the signing policy.
+///
+/// Returns `None` when signing is off for the package. Under `require` a
+/// missing or invalid signature fails the download; under `warn` it is
+/// reported and the archive is installed anyway.
+async fn check_signature(
+    downloader: &PackageDownloader,
+    signing: &SigningPolicy,
+    package: &PackageInfo,
+    candidate: &DistCandidate,
+    archive: &Path,
+) -> Result<Option<SignatureCheck>> {
+    let name = package.name.as_str();
+    let mode = signing.mode_for(name);
+    if mode == SigningMode::Off {
+        return Ok(None);
+    }
+
+    let mut signature_source = None;
+    let check = match package.dist_url.as_deref() {
+        // Source archives are generated by the VCS host and are never signed
+        Some(dist_url) if candidate.origin != DistOrigin::Source => {
+            let url = signature_url(package.dist_signature.as_deref(), dist_url);
+            let same_host =
+                extract_domain_from_url(&url) == extract_domain_from_url(&candidate.url);
+            let credential = candidate.credential.as_ref().filter(|_| same_host);
+            let check = match downloader.fetch_signature(&url, credential).await {
+                Some(signature) => verify_archive(signing, name, archive, &signature)
+                    .with_context(|| format!("Failed to read archive of {name}"))?,
+                None => SignatureCheck::Unsigned,
+            };
+            signature_source = Some(url);
+            check
         }
+        _ => SignatureCheck::Unsigned,
+    };
 
-        let relative_path = &entry_path[prefix_len..];
-        if relative_path.is_empty() {
-            continue;
+    let problem = match &check {
+        SignatureCheck::Verified { key, .. } => {
+            debug!(package = %name, key = %key, "signature verified");
+            return Ok(Some(check));
         }
+        SignatureCheck::Invalid(reason) => format!("invalid signature: {reason}"),
+        _ => match signature_source {
+            Some(url) => format!("no signature found at {url}"),
+            None => "no signature for source archive".to_string(),
+        },
+    };
+    if mode == SigningMode::Require {
+        bail!("{name}: {problem}");
+    }
+    warning(&format!("{name}: {problem}"));
+    Ok(Some(check))
+}
 
-        let out_path = dest.join(relative_path);
-
-        if entry.is_dir() {
-            std::fs::create_dir_all(&out_path)?;
-        } else {
-            if let Some(parent) = out_path.parent() {
-                std::fs::create_dir_all(parent)?;
-            }
-            let mut outfile = std::fs::File::create(&out_path)?;
-            let mut buffer = Vec::new();
-            entry.read_to_end(&mut buffer)?;
-            std::io::Write::write_all(&mut outfile, &buffer)?;
+/// Verify `archive` against a detached signature without reading it into memory.
+#[allow(unsafe_code)]
+fn verify_archive(
+    signing: &SigningPolicy,
+    name: &str,
+    archive: &Path,
+    signature: &[u8],
+) -> std::io::Result<SignatureCheck> {
+    let file = std::fs::File::open(archive)?;
+    // SAFETY: the archive is this install's own finished download and nothing
+    // writes to it while it is mapped
+    let data = unsafe { memmap2::Mmap::map(&file)? };
+    Ok(signing.verify(name, &data, signature))
+}
 
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                if let Some(mode) = entry.unix_mode() {
-                    let _ =
-                        std::fs::set_permissions(&out_path, std::fs::Permissions::from_mode(mode));
-                }
-            }
-        }
-    }
+/// Hex SHA-1 of a download URL.
+fn url_digest(url: &str) -> String {
+    use sha1::{Digest, Sha1};
+    hex::encode(Sha1::digest(url.as_bytes()))
+}
 
-    Ok(())
+/// Credential for a download from `url`, chosen by its host.
+///
+/// A credential prompted for during this run (for the same host, or a GitHub
+/// token for any GitHub host) comes first, then the host's own auth.json
+/// entry. The configured GitHub token is only used for GitHub hosts.
+fn credential_for_url(
+    url: &str,
+    auth_manager: &AuthManager,
+    github_token: Option<&Credential>,
+    prompted: Option<&(String, Credential)>,
+) -> Option<Credential> {
+    let host = extract_domain_from_url(url)?;
+    let prompted = prompted.and_then(|(domain, credential)| {
+        let github = matches!(credential, Credential::GitHubOAuth(_)) && is_github_url(url);
+        (github || domain.eq_ignore_ascii_case(&host)).then(|| credential.clone())
+    });
+    prompted
+        .or_else(|| auth_manager.get_credential(&host))
+        .or_else(|| github_token.filter(|_| is_github_url(url)).cloned())
 }
 
-/// Apply authentication credentials to an HTTP request.
+/// Build the headers that authenticate a request to `url` with `credential`.
 ///
 /// Handles all Composer-supported authentication types:
 /// - GitHub OAuth: Bearer token for github.com URLs
@@ -1504,53 +2466,55 @@ fn extract_zip(zip_path: &std::path::Path, dest: &std::path::Path) -> Result<()>
 /// - Bearer: Generic bearer token
 /// - Forgejo: Token-based auth
 /// - Custom Headers: Raw header injection
-fn apply_credential_to_request(
-    request: reqwest::RequestBuilder,
-    credential: &Credential,
-    url: &str,
-) -> reqwest::RequestBuilder {
-    match credential {
-        Credential::GitHubOAuth(token) => {
-            // Only apply to GitHub URLs
-            if url.contains("github.com") || url.contains("api.github.com") {
-                request.header("Authorization", format!("Bearer {token}"))
-            } else {
-                request
-            }
+fn credential_headers(credential: &Credential, url: &str) -> reqwest::header::HeaderMap {
+    use base64::Engine;
+    use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+
+    let basic = |username: &str, password: &str| {
+        let encoded =
+            base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"));
+        format!("Basic {encoded}")
+    };
+    let host = extract_domain_from_url(url).unwrap_or_default();
+    let authorization = match credential {
+        // Only apply to GitHub hosts
+        Credential::GitHubOAuth(token) if is_github_url(url) => Some(format!("Bearer {token}")),
+        // Apply to GitLab hosts
+        Credential::GitLabOAuth(token) | Credential::GitLabToken(token)
+            if host.to_ascii_lowercase().contains("gitlab") =>
+        {
+            Some(format!("Bearer {token}"))
         }
-        Credential::GitLabOAuth(token) | Credential::GitLabToken(token) => {
-            // Apply to GitLab URLs
-            if url.contains("gitlab") {
-                request.header("Authorization", format!("Bearer {token}"))
-            } else {
-                request
-            }
+        Credential::GitHubOAuth(_) | Credential::GitLabOAuth(_) | Credential::GitLabToken(_) => {
+            None
         }
+        // Bitbucket uses Basic auth with OAuth credentials
         Credential::BitbucketOAuth {
             consumer_key,
             consumer_secret,
-        } => {
-            // Bitbucket uses Basic auth with OAuth credentials
-            request.basic_auth(consumer_key, Some(consumer_secret))
-        }
-        Credential::HttpBasic { username, password } => {
-            request.basic_auth(username, Some(password))
-        }
-        Credential::Bearer(token) => request.header("Authorization", format!("Bearer {token}")),
-        Credential::ForgejoToken { token, .. } => {
-            request.header("Authorization", format!("token {token}"))
-        }
-        Credential::CustomHeaders(headers) => {
-            let mut req = request;
-            for header in headers {
-                // Parse "Header-Name: value" format
-                if let Some((name, value)) = header.split_once(':') {
-                    req = req.header(name.trim(), value.trim());
-                }
+        } => Some(basic(consumer_key, consumer_secret)),
+        Credential::HttpBasic { username, password } => Some(basic(username, password)),
+        Credential::Bearer(token) => Some(format!("Bearer {token}")),
+        Credential::ForgejoToken { token, .. } => Some(format!("token {token}")),
+        Credential::CustomHeaders(_) => None,
+    };
+
+    let mut headers = HeaderMap::new();
+    if let Some(value) = authorization.and_then(|v| HeaderValue::from_str(&v).ok()) {
+        headers.insert(AUTHORIZATION, value);
+    }
+    if let Credential::CustomHeaders(custom) = credential {
+        for header in custom {
+            // Parse "Header-Name: value" format
+            if let Some((name, value)) = header.split_once(':')
+                && let Ok(name) = HeaderName::from_bytes(name.trim().as_bytes())
+                && let Ok(value) = HeaderValue::from_str(value.trim())
+            {
+                headers.insert(name, value);
             }
-            req
         }
     }
+    headers
 }
 
 /// Extract domain from URL for credential lookup.
@@ -1567,7 +2531,7 @@ fn detect_auth_type_for_url(url: &str) -> (String, crate::auth_manager::Promptab
     let domain = extract_domain_from_url(url).unwrap_or_else(|| "unknown".to_string());
     let domain_lower = domain.to_lowercase();
 
-    let auth_type = if domain_lower.contains("github.com") {
+    let auth_type = if is_github_url(url) {
         PromptableAuthType::GitHubOAuth
     } else if domain_lower == "gitlab.com" {
         // GitLab.com prefers OAuth tokens
@@ -1614,6 +2578,13 @@ fn normalize_version_for_composer(version: &str) -> String {
     version.to_string()
 }
 
+/// Signature check recorded in a package's install marker.
+fn installed_signature(dest: &Path) -> Option<Value> {
+    let marker = std::fs::read_to_string(install_marker_path(dest)).ok()?;
+    let marker: Value = sonic_rs::from_str(&marker).ok()?;
+    marker.get("signature").cloned()
+}
+
 fn php_single_quote_escape(s: &str) -> String {
     s.replace('\\', "\\\\").replace('\'', "\\'")
 }
@@ -1621,6 +2592,8 @@ fn php_single_quote_escape(s: &str) -> String {
 fn write_composer_installed_metadata(
     lock_path: &Path,
     vendor_dir: &Path,
+    base_dir: &Path,
+    installer_paths: &InstallerPaths,
     composer: &Value,
     include_dev: bool,
 ) -> Result<()> {
@@ -1630,6 +2603,25 @@ fn write_composer_installed_metadata(
 
     let lock_content = std::fs::read_to_string(lock_path)?;
     let lock: Value = sonic_rs::from_str(&lock_content)?;
+    write_installed_metadata(
+        &lock,
+        vendor_dir,
+        base_dir,
+        installer_paths,
+        composer,
+        include_dev,
+    )
+}
+
+/// Write `vendor/composer/installed.{json,php}` for the packages of `lock`.
+fn write_installed_metadata(
+    lock: &Value,
+    vendor_dir: &Path,
+    base_dir: &Path,
+    installer_paths: &InstallerPaths,
+    composer: &Value,
+    include_dev: bool,
+) -> Result<()> {
     let composer_dir = vendor_dir.join("composer");
     std::fs::create_dir_all(&composer_dir)?;
 
@@ -1676,6 +2668,11 @@ fn write_composer_installed_metadata(
             .map(String::from);
         let has_dist = pkg.get("dist").is_some();
         let install_path_rel = format!("../{name}");
+        let signature = installed_signature(
+            &installer_paths
+                .get_path(base_dir, &name, Some(&pkg_type))
+                .unwrap_or_else(|| vendor_dir.join(&name)),
+        );
 
         let Some(obj) = pkg.as_object_mut() else {
             continue;
@@ -1693,6 +2690,9 @@ fn write_composer_installed_metadata(
             let source = if has_dist { "dist" } else { "source" };
             obj.insert(&"installation-source".to_string(), Value::from(source));
         }
+        if let Some(signature) = signature {
+            obj.insert(&"signature-verification".to_string(), signature);
+        }
 
         if is_dev {
             dev_package_names.push(name.clone());
@@ -1808,7 +2808,12 @@ fn write_composer_installed_metadata(
     Ok(())
 }
 
-fn generate_autoloader(vendor_dir: &PathBuf, args: &InstallArgs) -> Result<()> {
+fn generate_autoloader(
+    project_dir: &Path,
+    composer: &Value,
+    vendor_dir: &PathBuf,
+    args: &InstallArgs,
+) -> Result<()> {
     use libretto_autoloader::{AutoloadConfig, AutoloaderGenerator, OptimizationLevel};
     use serde::Deserialize;
     use std::collections::HashMap;
@@ -1861,10 +2866,7 @@ fn generate_autoloader(vendor_dir: &PathBuf, args: &InstallArgs) -> Result<()> {
         }
     }
 
-    fn load_autoload_config(path: &std::path::Path) -> Option<AutoloadConfig> {
-        let content = std::fs::read_to_string(path).ok()?;
-        let composer: ComposerJson = sonic_rs::from_str(&content).ok()?;
-
+    fn autoload_config(composer: ComposerJson, with_dev: bool) -> AutoloadConfig {
         let mut config = AutoloadConfig::default();
 
         for (namespace, paths) in composer.autoload.psr4 {
@@ -1877,24 +2879,9 @@ fn generate_autoloader(vendor_dir: &PathBuf, args: &InstallArgs) -> Result<()> {
         config.files.files = composer.autoload.files;
         config.exclude.patterns = composer.autoload.exclude;
 
-        Some(config)
-    }
-
-    fn load_autoload_config_with_dev(path: &std::path::Path) -> Option<AutoloadConfig> {
-        let content = std::fs::read_to_string(path).ok()?;
-        let composer: ComposerJson = sonic_rs::from_str(&content).ok()?;
-
-        let mut config = AutoloadConfig::default();
-
-        for (namespace, paths) in composer.autoload.psr4 {
-            config.psr4.mappings.insert(namespace, paths.to_vec());
-        }
-        for (namespace, paths) in composer.autoload.psr0 {
-            config.psr0.mappings.insert(namespace, paths.to_vec());
+        if !with_dev {
+            return config;
         }
-        config.classmap.paths = composer.autoload.classmap;
-        config.files.files = composer.autoload.files;
-        config.exclude.patterns = composer.autoload.exclude;
 
         for (namespace, paths) in composer.autoload_dev.psr4 {
             config
@@ -1919,21 +2906,24 @@ fn generate_autoloader(vendor_dir: &PathBuf, args: &InstallArgs) -> Result<()> {
             .patterns
             .extend(composer.autoload_dev.exclude);
 
-        Some(config)
+        config
     }
 
-    let composer_config = std::fs::read_to_string("composer.json")
-        .ok()
-        .and_then(|content| sonic_rs::from_str::<ComposerJson>(&content).ok())
-        .map(|composer| composer.config)
-        .unwrap_or_default();
+    fn load_autoload_config(path: &std::path::Path) -> Option<AutoloadConfig> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let composer: ComposerJson = sonic_rs::from_str(&content).ok()?;
+        Some(autoload_config(composer, false))
+    }
+
+    let root_composer = sonic_rs::from_value::<ComposerJson>(composer).ok();
+    let composer_config = root_composer.as_ref().map(|composer| &composer.config);
 
-    let classmap_authoritative =
-        args.classmap_authoritative || composer_config.classmap_authoritative;
+    let classmap_authoritative = args.classmap_authoritative
+        || composer_config.is_some_and(|config| config.classmap_authoritative);
     let optimize_autoloader = if classmap_authoritative {
         true
     } else {
-        args.optimize_autoloader || composer_config.optimize_autoloader
+        args.optimize_autoloader || composer_config.is_some_and(|config| config.optimize_autoloader)
     };
 
     let level = if classmap_authoritative {
@@ -1975,21 +2965,17 @@ fn generate_autoloader(vendor_dir: &PathBuf, args: &InstallArgs) -> Result<()> {
         }
     }
 
-    // Load root project's autoload config (including dev if not --no-dev)
-    let root_composer_json = std::path::PathBuf::from("composer.json");
-    if root_composer_json.exists() {
-        let config = if args.no_dev {
-            load_autoload_config(&root_composer_json)
-        } else {
-            load_autoload_config_with_dev(&root_composer_json)
-        };
-        if let Some(config) = config {
-            let project_root = std::path::PathBuf::from(".");
-            generator.add_package(&project_root, &config);
-        }
+    // Root project's autoload config (including dev if not --no-dev)
+    if let Some(root_composer) = root_composer {
+        generator.add_package(project_dir, &autoload_config(root_composer, !args.no_dev));
     }
 
+    let dump_start = Instant::now();
     generator.generate()?;
+    events::emit(Event::autoload_dump(
+        &generator.stats(),
+        dump_start.elapsed(),
+    ));
     for psr_warning in generator.warnings() {
         warning(psr_warning);
     }
@@ -1998,7 +2984,7 @@ fn generate_autoloader(vendor_dir: &PathBuf, args: &InstallArgs) -> Result<()> {
 }
 
 /// Convert GitHub API URLs to codeload URLs to avoid rate limits.
-fn convert_github_api_url(url: &str) -> String {
+pub fn convert_github_api_url(url: &str) -> String {
     if !url.starts_with("https://api.github.com/repos/") {
         return url.to_string();
     }
@@ -2023,3 +3009,199 @@ fn convert_github_api_url(url: &str) -> String {
         url.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bandwidth_units() {
+        assert_eq!(parse_bandwidth("4096"), Ok(4096));
+        assert_eq!(parse_bandwidth("512K"), Ok(512 * 1024));
+        assert_eq!(parse_bandwidth("2MB"), Ok(2 * 1024 * 1024));
+        assert_eq!(parse_bandwidth("1g/s"), Ok(1024 * 1024 * 1024));
+        assert!(parse_bandwidth("0").is_err());
+        assert!(parse_bandwidth("fast").is_err());
+        assert!(parse_bandwidth("10T").is_err());
+    }
+
+    #[test]
+    fn dist_candidates_order() {
+        let lock: Value = sonic_rs::from_str(
+            r#"{
+                "name": "psr/log",
+                "version": "3.0.0",
+                "dist": {
+                    "type": "zip",
+                    "url": "https://api.github.com/repos/php-fig/log/zipball/f16e1d5",
+                    "reference": "f16e1d5"
+                },
+                "source": {
+                    "type": "git",
+                    "url": "https://github.com/php-fig/log.git",
+                    "reference": "f16e1d5"
+                }
+            }"#,
+        )
+        .unwrap();
+        let package = parse_lock_package(&lock, false).unwrap();
+        let mirrors = [
+            DistMirror::new("https://backup.test/%package%/%reference%.%type%", false),
+            DistMirror::new("https://cache.test/%package%/%version%.%type%", true),
+        ];
+
+        let candidates = dist_candidates(
+            &package,
+            package.dist_url.as_deref().unwrap(),
+            &mirrors,
+            false,
+        );
+        let urls: Vec<_> = candidates
+            .iter()
+            .map(|c| (c.origin, c.url.as_str()))
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                (DistOrigin::Mirror, "https://cache.test/psr/log/3.0.0.zip"),
+                (
+                    DistOrigin::Dist,
+                    "https://api.github.com/repos/php-fig/log/zipball/f16e1d5"
+                ),
+                (
+                    DistOrigin::Mirror,
+                    "https://backup.test/psr/log/f16e1d5.zip"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reference_mirrors_need_a_dist_reference() {
+        let lock: Value = sonic_rs::from_str(
+            r#"{
+                "name": "acme/lib",
+                "version": "1.0.0",
+                "dist": {"type": "zip", "url": "https://repo.acme.test/dists/acme-lib.zip"},
+                "source": {"type": "git", "url": "https://gitlab.com/acme/lib.git", "reference": "abc123"}
+            }"#,
+        )
+        .unwrap();
+        let package = parse_lock_package(&lock, false).unwrap();
+        let mirrors = [
+            DistMirror::new("https://backup.test/%package%/%reference%.%type%", true),
+            DistMirror::new("https://cache.test/%package%/%version%.%type%", false),
+        ];
+
+        let candidates = dist_candidates(
+            &package,
+            package.dist_url.as_deref().unwrap(),
+            &mirrors,
+            false,
+        );
+        let urls: Vec<_> = candidates.iter().map(|c| c.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://repo.acme.test/dists/acme-lib.zip",
+                "https://cache.test/acme/lib/1.0.0.zip"
+            ]
+        );
+    }
+
+    #[test]
+    fn source_archive_fallback() {
+        let lock: Value = sonic_rs::from_str(
+            r#"{
+                "name": "acme/lib",
+                "version": "1.0.0",
+                "dist": {"type": "zip", "url": "https://repo.acme.test/dists/acme-lib.zip"},
+                "source": {"type": "git", "url": "https://gitlab.com/acme/lib.git", "reference": "abc123"}
+            }"#,
+        )
+        .unwrap();
+        let package = parse_lock_package(&lock, false).unwrap();
+
+        let url = package.dist_url.as_deref().unwrap();
+        assert_eq!(dist_candidates(&package, url, &[], false).len(), 1);
+
+        let candidates = dist_candidates(&package, url, &[], true);
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[1].origin, DistOrigin::Source);
+        assert_eq!(
+            candidates[1].url,
+            "https://gitlab.com/api/v4/projects/acme%2Flib/repository/archive.zip?sha=abc123"
+        );
+    }
+
+    #[test]
+    fn credentials_are_chosen_per_candidate_host() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("auth.json"),
+            r#"{"http-basic": {"mirror.example": {"username": "u", "password": "p"}}}"#,
+        )
+        .unwrap();
+        let auth_manager = AuthManager::with_project_root(Some(dir.path()));
+        let token = Credential::GitHubOAuth("ghp_token".into());
+        let mirror = "https://mirror.example/github.com/acme/lib/zipball/abc";
+
+        assert!(matches!(
+            credential_for_url(mirror, &auth_manager, Some(&token), None),
+            Some(Credential::HttpBasic { .. })
+        ));
+        assert!(matches!(
+            credential_for_url(
+                "https://codeload.github.com/acme/lib/zip/abc",
+                &auth_manager,
+                Some(&token),
+                None
+            ),
+            Some(Credential::GitHubOAuth(_))
+        ));
+        assert!(
+            credential_for_url(
+                "https://github.com.example/acme/lib.zip",
+                &auth_manager,
+                Some(&token),
+                None
+            )
+            .is_none()
+        );
+
+        let prompted = ("github.com".to_string(), token.clone());
+        assert!(matches!(
+            credential_for_url(mirror, &auth_manager, None, Some(&prompted)),
+            Some(Credential::HttpBasic { .. })
+        ));
+        assert!(credential_headers(&token, mirror).is_empty());
+    }
+
+    #[tokio::test]
+    async fn oversized_signatures_are_rejected() {
+        use wiremock::matchers::path;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(path("/ok.sig"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"signature".to_vec()))
+            .mount(&server)
+            .await;
+        Mock::given(path("/big.sig"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_bytes(vec![b'a'; MAX_SIGNATURE_SIZE + 1]),
+            )
+            .mount(&server)
+            .await;
+
+        let downloader = PackageDownloader::new(&DownloadArgs::default()).unwrap();
+        let ok = downloader
+            .fetch_signature(&format!("{}/ok.sig", server.uri()), None)
+            .await;
+        assert_eq!(ok.as_deref(), Some(&b"signature"[..]));
+        let big = downloader
+            .fetch_signature(&format!("{}/big.sig", server.uri()), None)
+            .await;
+        assert!(big.is_none());
+    }
+}
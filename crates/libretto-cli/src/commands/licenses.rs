@@ -2,6 +2,7 @@
 
 use anyhow::Result;
 use clap::Args;
+use libretto_audit::{is_copyleft, is_permissive};
 use sonic_rs::{JsonContainerTrait, JsonValueTrait};
 use std::collections::HashMap;
 
@@ -193,7 +194,7 @@ fn output_summary(licenses: &[PackageLicense]) -> Result<()> {
 
     // Sort by total count
     let mut sorted: Vec<_> = counts.into_iter().collect();
-    sorted.sort_by(|a, b| (b.1.0 + b.1.1).cmp(&(a.1.0 + a.1.1)));
+    sorted.sort_by_key(|entry| std::cmp::Reverse(entry.1.0 + entry.1.1));
 
     let mut table = Table::new();
     table.headers(["License", "Production", "Dev", "Total"]);
@@ -249,21 +250,3 @@ fn output_summary(licenses: &[PackageLicense]) -> Result<()> {
 
     Ok(())
 }
-
-fn is_permissive(license: &str) -> bool {
-    let lower = license.to_lowercase();
-    lower.contains("mit")
-        || lower.contains("bsd")
-        || lower.contains("apache")
-        || lower.contains("isc")
-        || lower.contains("unlicense")
-        || lower.contains("wtfpl")
-        || lower.contains("cc0")
-        || lower.contains("public domain")
-}
-
-fn is_copyleft(license: &str) -> bool {
-    let lower = license.to_lowercase();
-    (lower.contains("gpl") || lower.contains("agpl") || lower.contains("lgpl"))
-        && !lower.contains("exception")
-}
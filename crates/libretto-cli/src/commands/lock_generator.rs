@@ -1,11 +1,12 @@
 //! Lock file generation using `sonic_rs` for maximum performance.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use libretto_core::is_platform_package_name;
+use libretto_lockfile::{ComposerLock, DriftDetector, DriftResult};
 use libretto_resolver::Resolution;
 use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
 use std::collections::BTreeMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Generate a composer.lock file from resolution results.
 pub fn generate_lock_file(
@@ -59,6 +60,9 @@ pub fn generate_lock_file(
                 "shasum".to_string(),
                 Value::from(pkg.dist_shasum.as_deref().unwrap_or("")),
             );
+            if let Some(ref signature) = pkg.dist_signature {
+                dist.insert("signature".to_string(), Value::from(signature.as_str()));
+            }
             entry.insert(
                 "dist".to_string(),
                 sonic_rs::to_value(&dist).unwrap_or_default(),
@@ -260,8 +264,7 @@ pub fn generate_lock_file(
         }
     }
 
-    let content_hash =
-        libretto_core::ContentHash::from_bytes(sonic_rs::to_string(composer)?.as_bytes());
+    let content_hash = libretto_lockfile::ContentHasher::compute_manifest_hash(composer);
 
     let min_stability = composer
         .get("minimum-stability")
@@ -286,7 +289,7 @@ pub fn generate_lock_file(
     );
     lock.insert(
         "content-hash".to_string(),
-        Value::from(content_hash.to_hex().as_str()),
+        Value::from(content_hash.as_str()),
     );
     lock.insert(
         "packages".to_string(),
@@ -322,3 +325,75 @@ pub fn generate_lock_file(
 
     Ok(())
 }
+
+/// Compare an existing composer.lock with composer.json.
+///
+/// The content-hash is Composer's, so locks written by either tool compare
+/// the same way.
+pub fn lock_drift(lock_path: &Path, composer: &Value) -> Result<DriftResult> {
+    let content = std::fs::read_to_string(lock_path).context("Failed to read composer.lock")?;
+    let lock: ComposerLock =
+        sonic_rs::from_str(&content).context("Failed to parse composer.lock")?;
+    Ok(DriftDetector::check_drift(&lock, composer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK: &str = r#"{
+        "_readme": ["This file is @generated automatically"],
+        "content-hash": "HASH",
+        "packages": [
+            {
+                "name": "psr/log",
+                "version": "3.0.0",
+                "dist": {"type": "zip", "url": "https://example.com/log.zip", "reference": "abc", "shasum": ""},
+                "type": "library",
+                "autoload": {"psr-4": {"Psr\\Log\\": "src"}},
+                "license": ["MIT"]
+            }
+        ],
+        "packages-dev": [],
+        "aliases": [],
+        "minimum-stability": "stable",
+        "stability-flags": [],
+        "prefer-stable": false,
+        "prefer-lowest": false,
+        "platform": [],
+        "platform-dev": [],
+        "plugin-api-version": "2.6.0"
+    }"#;
+
+    fn write_lock(dir: &Path, composer: &Value) -> PathBuf {
+        let hash = libretto_lockfile::ContentHasher::compute_manifest_hash(composer);
+        let path = dir.join("composer.lock");
+        std::fs::write(&path, LOCK.replace("HASH", &hash)).unwrap();
+        path
+    }
+
+    #[test]
+    fn lock_drift_accepts_composer_written_locks() {
+        let dir = tempfile::tempdir().unwrap();
+        let composer: Value = sonic_rs::from_str(r#"{"require": {"psr/log": "^3.0"}}"#).unwrap();
+        let lock_path = write_lock(dir.path(), &composer);
+
+        let drift = lock_drift(&lock_path, &composer).unwrap();
+        assert!(drift.is_current);
+        assert!(!drift.has_changes());
+    }
+
+    #[test]
+    fn lock_drift_reports_edited_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let composer: Value = sonic_rs::from_str(r#"{"require": {"psr/log": "^3.0"}}"#).unwrap();
+        let lock_path = write_lock(dir.path(), &composer);
+
+        let edited: Value =
+            sonic_rs::from_str(r#"{"require": {"psr/log": "^3.0", "psr/container": "^2.0"}}"#)
+                .unwrap();
+        let drift = lock_drift(&lock_path, &edited).unwrap();
+        assert!(!drift.is_current);
+        assert_eq!(drift.added_deps, vec!["psr/container".to_string()]);
+    }
+}
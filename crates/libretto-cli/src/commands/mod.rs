@@ -2,6 +2,7 @@
 
 // Core commands
 pub mod audit;
+pub mod audit_db;
 pub mod dump_autoload;
 pub mod init;
 pub mod install;
@@ -24,19 +25,24 @@ pub mod completion;
 pub mod config;
 pub mod create_project;
 pub mod depends;
+pub mod deps_check;
 pub mod diagnose;
 pub mod exec;
 pub mod fund;
 pub mod global;
+pub mod graph;
 pub mod licenses;
 pub mod outdated;
 pub mod prohibits;
 pub mod reinstall;
+pub mod repo_build;
 pub mod repository;
 pub mod run_script;
+pub mod sbom;
 pub mod self_update;
 pub mod status;
 pub mod suggests;
+pub mod workspace;
 
 use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
@@ -92,8 +98,15 @@ pub struct Cli {
     pub no_cache: bool,
 
     /// Output format (text, json, or table)
-    #[arg(long, global = true, value_enum, default_value = "text")]
-    pub format: OutputFormat,
+    // A string rather than `OutputFormat`: commands with their own `--format`
+    // share this argument. Read it through `Cli::output_format`.
+    #[arg(long, global = true, default_value = "text", value_parser = output_format_values())]
+    pub format: String,
+
+    /// Progress log format; ndjson streams one JSON event per line on stdout
+    /// (install, update, require and remove only)
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
 
     /// Increase the verbosity of messages: -v for verbose, -vv for very verbose, -vvv for debug
     #[arg(short = 'v', long, global = true, action = clap::ArgAction::Count)]
@@ -103,6 +116,24 @@ pub struct Cli {
     pub command: Commands,
 }
 
+fn output_format_values() -> clap::builder::PossibleValuesParser {
+    OutputFormat::value_variants()
+        .iter()
+        .filter_map(ValueEnum::to_possible_value)
+        .into()
+}
+
+impl Commands {
+    /// Whether the command writes the `--log-format=ndjson` event stream.
+    #[must_use]
+    pub const fn emits_events(&self) -> bool {
+        matches!(
+            self,
+            Self::Install(_) | Self::Update(_) | Self::Require(_) | Self::Remove(_)
+        )
+    }
+}
+
 impl Cli {
     /// Convert to context args
     pub fn to_context_args(&self) -> crate::context::ContextArgs {
@@ -133,6 +164,10 @@ pub enum Commands {
     /// Checks for security vulnerability advisories for installed packages
     Audit(audit::AuditArgs),
 
+    /// Manages the offline security advisory database
+    #[command(name = "audit:db")]
+    AuditDb(audit_db::AuditDbArgs),
+
     /// Opens the package's repository URL or homepage in your browser
     #[command(alias = "home")]
     Browse(browse::BrowseArgs),
@@ -166,6 +201,10 @@ pub enum Commands {
     #[command(alias = "why")]
     Depends(depends::DependsArgs),
 
+    /// Finds required packages the code does not use and used packages it does not require
+    #[command(name = "deps:check")]
+    DepsCheck(deps_check::DepsCheckArgs),
+
     /// Diagnoses the system to identify common errors
     Diagnose(diagnose::DiagnoseArgs),
 
@@ -182,6 +221,9 @@ pub enum Commands {
     /// Allows running commands in the global composer dir ($`COMPOSER_HOME`)
     Global(global::GlobalArgs),
 
+    /// Exports the dependency graph as DOT, Mermaid or JSON
+    Graph(graph::GraphArgs),
+
     /// Creates a basic composer.json file in current directory
     Init(init::InitArgs),
 
@@ -206,6 +248,10 @@ pub enum Commands {
     #[command(visible_alias = "rm", alias = "uninstall")]
     Remove(remove::RemoveArgs),
 
+    /// Builds a static Composer repository from a Satis-style config
+    #[command(name = "repo:build")]
+    RepoBuild(repo_build::RepoBuildArgs),
+
     /// Manages repositories (add, remove, list)
     #[command(alias = "repo")]
     Repository(repository::RepositoryArgs),
@@ -218,6 +264,9 @@ pub enum Commands {
     #[command(name = "run-script", alias = "run")]
     RunScript(run_script::RunScriptArgs),
 
+    /// Generates a software bill of materials (SBOM) from composer.lock
+    Sbom(sbom::SbomArgs),
+
     /// Searches for packages
     Search(search::SearchArgs),
 
@@ -241,6 +290,45 @@ pub enum Commands {
 
     /// Validates a composer.json and composer.lock
     Validate(validate::ValidateArgs),
+
+    /// Lists workspace members and runs scripts across them
+    Workspace(workspace::WorkspaceArgs),
+
+    /// A script from composer.json, run as `libretto <script> [-- <args>...]`
+    #[command(external_subcommand)]
+    Script(Vec<String>),
+}
+
+impl Cli {
+    /// Parse the command line, listing the project's scripts in `--help`.
+    #[must_use]
+    pub fn parse_with_scripts() -> Self {
+        use clap::{CommandFactory, FromArgMatches};
+
+        let mut command = Self::command();
+        if let Some(help) = run_script::scripts_help(&command) {
+            command = command.after_help(help);
+        }
+        let matches = command.get_matches_mut();
+        let cli =
+            Self::from_arg_matches(&matches).unwrap_or_else(|e| e.format(&mut command).exit());
+        if cli.log_format != LogFormat::Text && !cli.command.emits_events() {
+            command
+                .error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "--log-format=ndjson is only supported by install, update, require and remove",
+                )
+                .exit();
+        }
+        cli
+    }
+
+    /// Global output format, or the default when a command-specific
+    /// `--format` value was given.
+    #[must_use]
+    pub fn output_format(&self) -> OutputFormat {
+        OutputFormat::from_str(&self.format, true).unwrap_or_default()
+    }
 }
 
 /// Output format for commands that support it
@@ -255,6 +343,14 @@ pub enum OutputFormat {
     Text,
 }
 
+/// Progress log format for install, update, require and remove
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Ndjson,
+}
+
 /// Sort order for list outputs
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
 pub enum SortOrder {
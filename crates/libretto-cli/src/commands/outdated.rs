@@ -1,8 +1,28 @@
 //! Outdated command - show packages with available updates.
-
+//!
+//! Every row compares three versions: the installed one, the newest one the
+//! current root constraints allow, and the newest one published. Whether the
+//! latter is reachable at all is decided by a trial resolution that pins it.
+
+use crate::fetcher::{
+    Fetcher, inspection_config, matches_pattern, minimum_stability, resolution_cache,
+};
+use crate::security::SecurityPolicy;
 use anyhow::Result;
 use clap::Args;
-use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use futures::stream::{self, StreamExt};
+use libretto_resolver::turbo::{TurboFetcher, TurboResolver};
+use libretto_resolver::{
+    ComposerConstraint, ComposerVersion, Dependency, FetchedPackage, PackageName, ResolveError,
+    Stability,
+};
+use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Trial resolutions run at the same time.
+const TRIAL_CONCURRENCY: usize = 8;
 
 /// Arguments for the outdated command
 #[derive(Args, Debug, Clone)]
@@ -20,9 +40,21 @@ pub struct OutdatedArgs {
     pub direct: bool,
 
     /// Only show minor and patch updates
-    #[arg(short = 'm', long)]
+    #[arg(short = 'm', long, conflicts_with = "major_only")]
     pub minor_only: bool,
 
+    /// Only show major updates
+    #[arg(short = 'M', long)]
+    pub major_only: bool,
+
+    /// Skip a package (repeatable, `*` wildcards allowed)
+    #[arg(long, value_name = "PACKAGE")]
+    pub ignore: Vec<String>,
+
+    /// Check the versions in composer.lock instead of vendor/
+    #[arg(long)]
+    pub locked: bool,
+
     /// Output format (text, json)
     #[arg(short = 'f', long, default_value = "text")]
     pub format: String,
@@ -37,11 +69,18 @@ pub struct OutdatedArgs {
 struct PackageUpdate {
     name: String,
     current: String,
+    /// Newest version the current root constraints resolve to.
+    latest_allowed: Option<String>,
     latest: String,
     constraint: String,
     is_direct: bool,
     is_dev: bool,
     update_type: UpdateType,
+    status: LatestStatus,
+    /// Packages whose requirements exclude the latest version.
+    blockers: Vec<Blocker>,
+    /// Solver explanation when the latest version cannot be installed.
+    explanation: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -52,179 +91,296 @@ enum UpdateType {
     UpToDate,
 }
 
-/// Run the outdated command
-pub async fn run(args: OutdatedArgs) -> Result<()> {
-    use crate::output::progress::Spinner;
-    use crate::output::{header, info, warning};
-    use libretto_repository::Repository;
+impl UpdateType {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Major => "major",
+            Self::Minor => "minor",
+            Self::Patch => "patch",
+            Self::UpToDate => "up-to-date",
+        }
+    }
+}
 
-    header("Checking for updates");
+/// How the latest version relates to the current constraints.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LatestStatus {
+    /// The latest version is installed.
+    UpToDate,
+    /// `update` installs the latest version as is.
+    SemverSafe,
+    /// The latest version resolves once root constraints are widened.
+    UpdatePossible,
+    /// Other packages' requirements rule the latest version out.
+    Blocked,
+}
 
-    let lock_path = std::env::current_dir()?.join("composer.lock");
-    let composer_path = std::env::current_dir()?.join("composer.json");
+impl LatestStatus {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::UpToDate => "up-to-date",
+            Self::SemverSafe => "semver-safe-update",
+            Self::UpdatePossible => "update-possible",
+            Self::Blocked => "blocked",
+        }
+    }
+}
 
-    if !lock_path.exists() {
-        anyhow::bail!("composer.lock not found - run 'libretto install' first");
+/// A requirement that excludes the latest version of a package.
+#[derive(Debug, Clone, PartialEq)]
+struct Blocker {
+    /// Requiring package, `root` for composer.json.
+    package: String,
+    /// Version of the requiring package, unset for the root.
+    version: Option<String>,
+    constraint: String,
+}
+
+impl std::fmt::Display for Blocker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{} {version} ({})", self.package, self.constraint),
+            None => write!(f, "{} ({})", self.package, self.constraint),
+        }
     }
+}
 
-    let lock_content = std::fs::read_to_string(&lock_path)?;
-    let lock: sonic_rs::Value = sonic_rs::from_str(&lock_content)?;
+/// Run the outdated command
+pub async fn run(args: OutdatedArgs) -> Result<()> {
+    use crate::output::progress::Spinner;
+    use crate::output::{header, info, warning};
 
-    // Get direct dependencies from composer.json
-    let mut direct_deps: std::collections::HashSet<String> = std::collections::HashSet::new();
-    let mut constraints: std::collections::HashMap<String, String> =
-        std::collections::HashMap::new();
+    let json = args.format == "json";
+    if !json {
+        header("Checking for updates");
+    }
 
-    if composer_path.exists() {
-        let composer_content = std::fs::read_to_string(&composer_path)?;
-        let composer: sonic_rs::Value = sonic_rs::from_str(&composer_content)?;
+    let cwd = std::env::current_dir()?;
+    let composer_path = cwd.join("composer.json");
+    if !composer_path.exists() {
+        anyhow::bail!("composer.json not found");
+    }
+    let composer: Value = sonic_rs::from_str(&std::fs::read_to_string(&composer_path)?)?;
 
-        if let Some(require) = composer.get("require").and_then(|v| v.as_object()) {
+    // Direct dependencies and their constraints from composer.json
+    let mut constraints: HashMap<String, String> = HashMap::new();
+    let mut root_require: Vec<(String, String)> = Vec::new();
+    for key in ["require", "require-dev"] {
+        if let Some(require) = composer.get(key).and_then(|v| v.as_object()) {
             for (name, constraint) in require {
-                direct_deps.insert(name.to_string());
-                if let Some(c) = constraint.as_str() {
-                    constraints.insert(name.to_string(), c.to_string());
-                }
-            }
-        }
-
-        if let Some(require_dev) = composer.get("require-dev").and_then(|v| v.as_object()) {
-            for (name, constraint) in require_dev {
-                direct_deps.insert(name.to_string());
                 if let Some(c) = constraint.as_str() {
                     constraints.insert(name.to_string(), c.to_string());
+                    root_require.push((name.to_string(), c.to_string()));
                 }
             }
         }
     }
 
-    // Collect installed packages
-    let mut installed: Vec<(String, String, bool)> = Vec::new();
-
-    for (key, is_dev) in [("packages", false), ("packages-dev", true)] {
-        if let Some(packages) = lock.get(key).and_then(|v| v.as_array()) {
-            for pkg in packages {
-                let name = pkg.get("name").and_then(|v| v.as_str()).unwrap_or("");
-                let version = pkg.get("version").and_then(|v| v.as_str()).unwrap_or("");
-
-                if !name.is_empty() && !version.is_empty() {
-                    // Filter by package names if specified
-                    if args.packages.is_empty() || args.packages.iter().any(|p| name.contains(p)) {
-                        // Filter by direct only if requested
-                        if !args.direct || direct_deps.contains(name) {
-                            installed.push((name.to_string(), version.to_string(), is_dev));
-                        }
-                    }
-                }
-            }
-        }
-    }
+    let installed: Vec<(String, String, bool)> = read_installed(&cwd, args.locked)?
+        .into_iter()
+        .filter(|(name, _, _)| {
+            (args.packages.is_empty() || args.packages.iter().any(|p| name.contains(p)))
+                && (!args.direct || constraints.contains_key(name))
+                && !args
+                    .ignore
+                    .iter()
+                    .any(|pattern| matches_pattern(pattern, name))
+        })
+        .collect();
 
     if installed.is_empty() {
+        if json {
+            return output_json(&[]);
+        }
         info("No packages to check");
         return Ok(());
     }
 
-    // Check for updates
     let spinner = Spinner::new(format!("Checking {} packages...", installed.len()));
-    let repo = Repository::packagist()?;
-    repo.init_packagist().await?;
 
-    let mut updates: Vec<PackageUpdate> = Vec::new();
+    let min_stability = minimum_stability(&composer);
+    let fetcher = Arc::new(
+        Fetcher::new_with_composer_repositories(&composer)
+            .map_err(|e| anyhow::anyhow!("Failed to create fetcher: {e}"))?,
+    );
+    let security = SecurityPolicy::load(&composer, false);
+    let resolver = || {
+        security.apply(TurboResolver::new(
+            fetcher.clone(),
+            inspection_config(min_stability, true, security.block_abandoned),
+        ))
+    };
+
+    // What `update` would pick with the current constraints
+    let (root_deps, dev_deps) = root_dependencies(&composer, None);
+    let mut current_resolver = resolver();
+    if let Some(cache) = resolution_cache(&composer) {
+        current_resolver = current_resolver.with_cache(cache);
+    }
+    let allowed = match current_resolver.resolve(&root_deps, &dev_deps).await {
+        Ok(resolution) => Some(resolution),
+        Err(e) => {
+            warning(&format!(
+                "Current constraints cannot be resolved, latest allowed versions are unknown: {e}"
+            ));
+            None
+        }
+    };
+
+    let metadata: HashMap<String, FetchedPackage> =
+        stream::iter(installed.iter().map(|(name, _, _)| name.clone()))
+            .map(|name| {
+                let fetcher = fetcher.clone();
+                async move { fetcher.fetch(name).await }
+            })
+            .buffer_unordered(32)
+            .filter_map(|package| async move { package })
+            .map(|package| (package.name.clone(), package))
+            .collect()
+            .await;
 
+    let mut updates: Vec<PackageUpdate> = Vec::new();
     for (name, current, is_dev) in &installed {
-        let package_id = match libretto_core::PackageId::parse(name) {
-            Some(id) => id,
-            None => continue,
-        };
+        let current_version = ComposerVersion::parse(current);
+        let floor = current_version
+            .as_ref()
+            .map_or(min_stability, |v| v.stability.min(min_stability));
+        let latest = metadata
+            .get(name)
+            .and_then(|package| {
+                latest_version(package.versions.iter().map(|v| v.version.as_str()), floor)
+            })
+            .filter(|latest| current_version.as_ref().is_none_or(|c| latest > c));
 
-        // Fetch latest version
-        let latest = match repo.get_package(&package_id).await {
-            Ok(versions) => {
-                let versions: Vec<_> = versions.into_iter().collect();
-                versions
-                    .iter()
-                    .filter(|v| {
-                        let ver_str = v.version.to_string();
-                        !ver_str.contains("dev")
-                            && !ver_str.contains("alpha")
-                            && !ver_str.contains("beta")
-                    })
-                    .map(|v| v.version.to_string())
-                    .next()
-                    .or_else(|| versions.first().map(|v| v.version.to_string()))
-                    .unwrap_or_else(|| current.clone())
-            }
-            Err(_) => current.clone(),
+        let update_type = match (&current_version, &latest) {
+            (Some(current), Some(latest)) => classify(current, latest),
+            _ => UpdateType::UpToDate,
         };
 
-        let update_type = compare_versions(current, &latest);
-
-        // Filter by update type
         if args.minor_only && update_type == UpdateType::Major {
             continue;
         }
-
-        // Filter out up-to-date packages unless --all
+        if args.major_only && update_type != UpdateType::Major {
+            continue;
+        }
         if !args.all && update_type == UpdateType::UpToDate {
             continue;
         }
 
-        let constraint = constraints
-            .get(name)
-            .cloned()
-            .unwrap_or_else(|| "*".to_string());
+        let latest_allowed = allowed
+            .as_ref()
+            .and_then(|resolution| resolution.get(name))
+            .map(|pkg| pkg.version.to_string());
+        let latest = latest.map_or_else(|| current.clone(), |v| v.to_string());
+        let status = if update_type == UpdateType::UpToDate {
+            LatestStatus::UpToDate
+        } else if latest_allowed.as_deref() == Some(latest.as_str()) {
+            LatestStatus::SemverSafe
+        } else {
+            // Settled by a trial resolution below
+            LatestStatus::Blocked
+        };
 
         updates.push(PackageUpdate {
             name: name.clone(),
             current: current.clone(),
+            latest_allowed,
             latest,
-            constraint,
-            is_direct: direct_deps.contains(name),
+            constraint: constraints
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| "*".to_string()),
+            is_direct: constraints.contains_key(name),
             is_dev: *is_dev,
             update_type,
+            status,
+            blockers: Vec::new(),
+            explanation: None,
         });
     }
 
+    // Trial resolutions: is the latest version reachable at all?
+    let trials: Vec<(usize, Result<(), ResolveError>)> = stream::iter(
+        updates
+            .iter()
+            .enumerate()
+            .filter(|(_, update)| update.status == LatestStatus::Blocked),
+    )
+    .map(|(index, update)| {
+        let trial = resolver();
+        let (root_deps, dev_deps) =
+            root_dependencies(&composer, Some((&update.name, &update.latest)));
+        async move {
+            let result = trial.resolve(&root_deps, &dev_deps).await.map(|_| ());
+            (index, result)
+        }
+    })
+    .buffer_unordered(TRIAL_CONCURRENCY)
+    .collect()
+    .await;
+
+    for (index, result) in trials {
+        let update = &mut updates[index];
+        match result {
+            Ok(()) => update.status = LatestStatus::UpdatePossible,
+            Err(e) => {
+                if let Some(latest) = ComposerVersion::parse(&update.latest) {
+                    let root = std::iter::once(("root", None, root_require.as_slice()));
+                    let dependents = allowed
+                        .iter()
+                        .flat_map(|resolution| &resolution.packages)
+                        .filter_map(|pkg| {
+                            Some((
+                                pkg.name.as_str(),
+                                Some(pkg.version.to_string()),
+                                pkg.require.as_deref()?,
+                            ))
+                        });
+                    update.blockers = find_blockers(root.chain(dependents), &update.name, &latest);
+                }
+                update.explanation = Some(match e {
+                    ResolveError::Conflict { explanation, .. } => explanation,
+                    other => other.to_string(),
+                });
+            }
+        }
+    }
+
     spinner.finish_and_clear();
 
-    // Sort by name
     updates.sort_by(|a, b| a.name.cmp(&b.name));
 
+    if json {
+        return output_json(&updates);
+    }
+
     if updates.is_empty() {
         info("All packages are up to date!");
         return Ok(());
     }
 
-    // Output results
-    if args.format == "json" {
-        return output_json(&updates);
-    }
-
     output_text(&updates, args.all)?;
 
     // Summary
-    let outdated_count = updates
-        .iter()
-        .filter(|u| u.update_type != UpdateType::UpToDate)
-        .count();
-    let major_count = updates
-        .iter()
-        .filter(|u| u.update_type == UpdateType::Major)
-        .count();
-    let minor_count = updates
-        .iter()
-        .filter(|u| u.update_type == UpdateType::Minor)
-        .count();
-    let patch_count = updates
+    let count = |update_type: UpdateType| {
+        updates
+            .iter()
+            .filter(|u| u.update_type == update_type)
+            .count()
+    };
+    let outdated_count = updates.len() - count(UpdateType::UpToDate);
+    let blocked_count = updates
         .iter()
-        .filter(|u| u.update_type == UpdateType::Patch)
+        .filter(|u| u.status == LatestStatus::Blocked)
         .count();
 
     println!();
     if outdated_count > 0 {
         warning(&format!(
-            "{outdated_count} outdated package(s): {major_count} major, {minor_count} minor, {patch_count} patch"
+            "{outdated_count} outdated package(s): {} major, {} minor, {} patch ({blocked_count} blocked)",
+            count(UpdateType::Major),
+            count(UpdateType::Minor),
+            count(UpdateType::Patch),
         ));
 
         if args.strict {
@@ -237,42 +393,146 @@ pub async fn run(args: OutdatedArgs) -> Result<()> {
     Ok(())
 }
 
-fn compare_versions(current: &str, latest: &str) -> UpdateType {
-    let current_clean = current.trim_start_matches('v');
-    let latest_clean = latest.trim_start_matches('v');
-
-    if current_clean == latest_clean {
-        return UpdateType::UpToDate;
+/// Installed packages as `(name, version, is_dev)`.
+///
+/// Reads `vendor/composer/installed.json` unless `locked` is set or nothing
+/// is installed, then falls back to composer.lock.
+fn read_installed(cwd: &Path, locked: bool) -> Result<Vec<(String, String, bool)>> {
+    let installed_path = cwd.join("vendor").join("composer").join("installed.json");
+    if !locked && installed_path.exists() {
+        let installed: Value = sonic_rs::from_str(&std::fs::read_to_string(&installed_path)?)?;
+        let dev_names: HashSet<&str> = installed
+            .get("dev-package-names")
+            .and_then(|v| v.as_array())
+            .map(|names| names.iter().filter_map(|n| n.as_str()).collect())
+            .unwrap_or_default();
+        let packages = installed
+            .get("packages")
+            .and_then(|v| v.as_array())
+            .map(|packages| {
+                packages
+                    .iter()
+                    .filter_map(|pkg| {
+                        let name = pkg.get("name").and_then(|v| v.as_str())?;
+                        let version = pkg.get("version").and_then(|v| v.as_str())?;
+                        Some((
+                            name.to_string(),
+                            version.to_string(),
+                            dev_names.contains(name),
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        return Ok(packages);
     }
 
-    let current_parts: Vec<u64> = current_clean
-        .split('.')
-        .filter_map(|s| s.split('-').next()?.parse().ok())
-        .collect();
-    let latest_parts: Vec<u64> = latest_clean
-        .split('.')
-        .filter_map(|s| s.split('-').next()?.parse().ok())
-        .collect();
-
-    if current_parts.is_empty() || latest_parts.is_empty() {
-        return UpdateType::Patch;
+    let lock_path = cwd.join("composer.lock");
+    if !lock_path.exists() {
+        anyhow::bail!("composer.lock not found - run 'libretto install' first");
     }
+    let lock: Value = sonic_rs::from_str(&std::fs::read_to_string(&lock_path)?)?;
 
-    let current_major = current_parts.first().copied().unwrap_or(0);
-    let latest_major = latest_parts.first().copied().unwrap_or(0);
+    let mut packages = Vec::new();
+    for (key, is_dev) in [("packages", false), ("packages-dev", true)] {
+        if let Some(entries) = lock.get(key).and_then(|v| v.as_array()) {
+            for pkg in entries {
+                let name = pkg.get("name").and_then(|v| v.as_str()).unwrap_or("");
+                let version = pkg.get("version").and_then(|v| v.as_str()).unwrap_or("");
+                if !name.is_empty() && !version.is_empty() {
+                    packages.push((name.to_string(), version.to_string(), is_dev));
+                }
+            }
+        }
+    }
+    Ok(packages)
+}
 
-    if latest_major > current_major {
-        return UpdateType::Major;
+/// Root requirements of `composer`, optionally pinning one package to an
+/// exact version in place of its root constraint.
+fn root_dependencies(
+    composer: &Value,
+    pin: Option<(&str, &str)>,
+) -> (Vec<Dependency>, Vec<Dependency>) {
+    let parse = |key: &str| -> Vec<Dependency> {
+        composer
+            .get(key)
+            .and_then(|v| v.as_object())
+            .map(|require| {
+                require
+                    .iter()
+                    .filter(|(name, _)| !libretto_core::is_platform_package_name(name))
+                    .filter(|(name, _)| pin.is_none_or(|(pinned, _)| pinned != *name))
+                    .filter_map(|(name, constraint)| {
+                        Some(Dependency::new(
+                            PackageName::parse(name)?,
+                            ComposerConstraint::parse(constraint.as_str().unwrap_or("*"))?,
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut root_deps = parse("require");
+    let dev_deps = parse("require-dev");
+    if let Some((name, version)) = pin
+        && let (Some(name), Some(constraint)) =
+            (PackageName::parse(name), ComposerConstraint::parse(version))
+    {
+        root_deps.push(Dependency::new(name, constraint));
     }
+    (root_deps, dev_deps)
+}
 
-    let current_minor = current_parts.get(1).copied().unwrap_or(0);
-    let latest_minor = latest_parts.get(1).copied().unwrap_or(0);
+/// Newest of the published `versions` at or above `floor` stability.
+fn latest_version<'a>(
+    versions: impl IntoIterator<Item = &'a str>,
+    floor: Stability,
+) -> Option<ComposerVersion> {
+    versions
+        .into_iter()
+        .filter_map(ComposerVersion::parse)
+        .filter(|v| !v.is_dev_branch && v.stability >= floor)
+        .max()
+}
 
-    if latest_minor > current_minor {
-        return UpdateType::Minor;
+fn classify(current: &ComposerVersion, latest: &ComposerVersion) -> UpdateType {
+    if latest <= current {
+        UpdateType::UpToDate
+    } else if latest.major > current.major {
+        UpdateType::Major
+    } else if latest.minor > current.minor {
+        UpdateType::Minor
+    } else {
+        UpdateType::Patch
     }
+}
 
-    UpdateType::Patch
+/// Dependents, as `(name, version, require)`, whose requirement on `name`
+/// excludes `latest`. The root requirements come first, as in `prohibits`.
+fn find_blockers<'a>(
+    dependents: impl IntoIterator<Item = (&'a str, Option<String>, &'a [(String, String)])>,
+    name: &str,
+    latest: &ComposerVersion,
+) -> Vec<Blocker> {
+    let mut blockers: Vec<Blocker> = dependents
+        .into_iter()
+        .filter_map(|(package, version, require)| {
+            let (_, constraint) = require
+                .iter()
+                .find(|(dep, _)| dep.eq_ignore_ascii_case(name))?;
+            let parsed = ComposerConstraint::parse(constraint)?;
+            (!parsed.ranges().contains(latest)).then(|| Blocker {
+                package: package.to_string(),
+                version,
+                constraint: constraint.clone(),
+            })
+        })
+        .collect();
+    blockers
+        .sort_by(|a, b| (a.version.is_some(), &a.package).cmp(&(b.version.is_some(), &b.package)));
+    blockers
 }
 
 fn output_text(updates: &[PackageUpdate], show_all: bool) -> Result<()> {
@@ -293,16 +553,17 @@ fn output_text(updates: &[PackageUpdate], show_all: bool) -> Result<()> {
         println!();
 
         let mut table = Table::new();
-        table.headers(["Package", "Current", "Latest", "Type"]);
+        table.headers([
+            "Package",
+            "Current",
+            "Latest allowed",
+            "Latest",
+            "Type",
+            "Status",
+        ]);
 
         for pkg in &outdated {
-            let type_str = match pkg.update_type {
-                UpdateType::Major => "major",
-                UpdateType::Minor => "minor",
-                UpdateType::Patch => "patch",
-                UpdateType::UpToDate => "up-to-date",
-            };
-
+            let type_str = pkg.update_type.as_str();
             let type_cell = match pkg.update_type {
                 UpdateType::Major => table.error_cell(type_str),
                 UpdateType::Minor => table.warning_cell(type_str),
@@ -310,11 +571,30 @@ fn output_text(updates: &[PackageUpdate], show_all: bool) -> Result<()> {
                 UpdateType::UpToDate => comfy_table::Cell::new(type_str),
             };
 
+            let status_cell = match pkg.status {
+                LatestStatus::Blocked if pkg.blockers.is_empty() => {
+                    table.error_cell("blocked by a conflict")
+                }
+                LatestStatus::Blocked => {
+                    let blockers: Vec<String> =
+                        pkg.blockers.iter().map(ToString::to_string).collect();
+                    table.error_cell(format!("blocked by {}", blockers.join(", ")))
+                }
+                LatestStatus::UpdatePossible => {
+                    table.warning_cell(format!("needs a constraint other than {}", pkg.constraint))
+                }
+                LatestStatus::SemverSafe | LatestStatus::UpToDate => {
+                    table.success_cell(pkg.status.as_str())
+                }
+            };
+
             table.styled_row(vec![
                 comfy_table::Cell::new(&pkg.name),
                 comfy_table::Cell::new(&pkg.current),
+                comfy_table::Cell::new(pkg.latest_allowed.as_deref().unwrap_or("-")),
                 comfy_table::Cell::new(&pkg.latest),
                 type_cell,
+                status_cell,
             ]);
         }
 
@@ -340,26 +620,127 @@ fn output_text(updates: &[PackageUpdate], show_all: bool) -> Result<()> {
 }
 
 fn output_json(updates: &[PackageUpdate]) -> Result<()> {
-    let output: Vec<_> = updates
+    let installed: Vec<_> = updates
         .iter()
         .map(|pkg| {
+            let blockers: Vec<_> = pkg
+                .blockers
+                .iter()
+                .map(|b| {
+                    sonic_rs::json!({
+                        "package": b.package,
+                        "version": b.version,
+                        "constraint": b.constraint,
+                    })
+                })
+                .collect();
             sonic_rs::json!({
                 "name": pkg.name,
-                "current": pkg.current,
+                "version": pkg.current,
+                "latest-allowed": pkg.latest_allowed,
                 "latest": pkg.latest,
+                "latest-status": pkg.status.as_str(),
+                "update-type": pkg.update_type.as_str(),
                 "constraint": pkg.constraint,
                 "direct": pkg.is_direct,
                 "dev": pkg.is_dev,
-                "update_type": match pkg.update_type {
-                    UpdateType::Major => "major",
-                    UpdateType::Minor => "minor",
-                    UpdateType::Patch => "patch",
-                    UpdateType::UpToDate => "up-to-date",
-                }
+                "blockers": blockers,
+                "explanation": pkg.explanation,
             })
         })
         .collect();
 
+    let count = |status: LatestStatus| updates.iter().filter(|u| u.status == status).count();
+    let output = sonic_rs::json!({
+        "installed": installed,
+        "summary": {
+            "checked": updates.len(),
+            "up-to-date": count(LatestStatus::UpToDate),
+            "semver-safe-update": count(LatestStatus::SemverSafe),
+            "update-possible": count(LatestStatus::UpdatePossible),
+            "blocked": count(LatestStatus::Blocked),
+        },
+    });
+
     println!("{}", sonic_rs::to_string_pretty(&output)?);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(v: &str) -> ComposerVersion {
+        ComposerVersion::parse(v).expect("valid version")
+    }
+
+    #[test]
+    fn classifies_by_the_first_differing_component() {
+        assert_eq!(
+            classify(&version("1.2.3"), &version("2.0.0")),
+            UpdateType::Major
+        );
+        assert_eq!(
+            classify(&version("1.2.3"), &version("1.3.0")),
+            UpdateType::Minor
+        );
+        assert_eq!(
+            classify(&version("1.2.3"), &version("1.2.4")),
+            UpdateType::Patch
+        );
+        assert_eq!(
+            classify(&version("v1.2.3"), &version("1.2.3")),
+            UpdateType::UpToDate
+        );
+    }
+
+    #[test]
+    fn latest_version_respects_the_stability_floor() {
+        let versions = ["dev-main", "2.0.0-beta1", "1.4.0", "1.3.0"];
+        assert_eq!(
+            latest_version(versions, Stability::Stable).map(|v| v.to_string()),
+            Some("1.4.0".to_string())
+        );
+        assert_eq!(
+            latest_version(versions, Stability::Beta).map(|v| v.to_string()),
+            Some("2.0.0-beta1".to_string())
+        );
+    }
+
+    #[test]
+    fn blockers_are_requirements_excluding_the_latest_version() {
+        let app = vec![("vendor/lib".to_string(), "^1.0".to_string())];
+        let tool = vec![("vendor/lib".to_string(), ">=1.0".to_string())];
+        let dependents = [
+            ("vendor/app", Some("1.0.0".to_string()), app.as_slice()),
+            ("vendor/tool", Some("1.0.0".to_string()), tool.as_slice()),
+        ];
+
+        let blockers = find_blockers(dependents, "vendor/lib", &version("2.0.0"));
+        assert_eq!(
+            blockers,
+            vec![Blocker {
+                package: "vendor/app".to_string(),
+                version: Some("1.0.0".to_string()),
+                constraint: "^1.0".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn root_requirement_is_a_blocker() {
+        let root = vec![("vendor/lib".to_string(), "~1.2".to_string())];
+        let app = vec![("vendor/lib".to_string(), "^1.0".to_string())];
+        let dependents = [
+            ("vendor/app", Some("1.0.0".to_string()), app.as_slice()),
+            ("root", None, root.as_slice()),
+        ];
+
+        let blockers = find_blockers(dependents.clone(), "vendor/lib", &version("2.0.0"));
+        assert_eq!(
+            blockers.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["root (~1.2)", "vendor/app 1.0.0 (^1.0)"]
+        );
+        assert!(find_blockers(dependents, "vendor/lib", &version("1.2.5")).is_empty());
+    }
+}
@@ -1,298 +1,586 @@
 //! Prohibits command - show what prevents a package from being installed.
-
-use anyhow::Result;
+//!
+//! The answer comes from a trial resolution: the project's requirements with
+//! the requested version pinned, resolved against the platform. When it
+//! fails, the solver's derivation is shown as a tree of root constraints,
+//! transitive requirements, package conflicts, unusable versions and platform
+//! mismatches. The root's own conflict declarations are not passed to the
+//! solver and are checked against the packages the trial selects.
+
+use crate::fetcher::{Fetcher, inspection_config, minimum_stability, resolution_cache};
+use crate::platform::PlatformValidator;
+use crate::security::SecurityPolicy;
+use anyhow::{Result, anyhow, bail};
 use clap::Args;
-use sonic_rs::{JsonContainerTrait, JsonValueTrait};
-use std::collections::HashMap;
+use libretto_resolver::turbo::TurboResolver;
+use libretto_resolver::{
+    ComposerConstraint, ComposerVersion, Dependency, Derivation, PackageName, Resolution,
+    ResolveError,
+};
+use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Arc;
+use tracing::debug;
 
 /// Arguments for the prohibits command
 #[derive(Args, Debug, Clone)]
 pub struct ProhibitsArgs {
-    /// Package to check (vendor/name format)
+    /// Package to check (vendor/name format, or a platform package such as php)
     #[arg(required = true, value_name = "PACKAGE")]
     pub package: String,
 
-    /// Version to check
+    /// Version or constraint to check (any version if omitted)
     #[arg(value_name = "VERSION", name = "pkg_version")]
     pub version: Option<String>,
 
-    /// Recursively resolve up to the root packages
-    #[arg(short = 'r', long)]
+    /// Only use cached package metadata
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Output format (text, json)
+    #[arg(short = 'f', long, default_value = "text")]
+    pub format: String,
+
+    /// Accepted for Composer compatibility; the derivation is always complete
+    #[arg(short = 'r', long, hide = true)]
     pub recursive: bool,
 
-    /// Show tree view
-    #[arg(short = 't', long)]
+    /// Accepted for Composer compatibility; the derivation is always a tree
+    #[arg(short = 't', long, hide = true)]
     pub tree: bool,
 }
 
+/// Something that keeps the requested version out of the project.
+#[derive(Debug, Clone)]
+struct Reason {
+    kind: &'static str,
+    message: String,
+    /// Solver derivation for a failed trial resolution.
+    derivation: Option<Derivation>,
+}
+
+impl Reason {
+    fn new(kind: &'static str, message: String) -> Self {
+        Self {
+            kind,
+            message,
+            derivation: None,
+        }
+    }
+}
+
 /// Run the prohibits command
 pub async fn run(args: ProhibitsArgs) -> Result<()> {
-    use crate::output::table::Table;
     use crate::output::{header, info, warning};
+    use owo_colors::OwoColorize;
 
-    header("Conflict analysis");
-
-    let lock_path = std::env::current_dir()?.join("composer.lock");
-    let composer_path = std::env::current_dir()?.join("composer.json");
-
-    // Build constraint map from all packages
-    let mut constraints: HashMap<String, Vec<(String, String, bool)>> = HashMap::new();
-
-    // Check root composer.json
-    if composer_path.exists() {
-        let composer_content = std::fs::read_to_string(&composer_path)?;
-        let composer: sonic_rs::Value = sonic_rs::from_str(&composer_content)?;
-        let root_name = composer
-            .get("name")
-            .and_then(|v| v.as_str())
-            .unwrap_or("__root__");
-
-        // Check require
-        if let Some(require) = composer.get("require").and_then(|v| v.as_object()) {
-            for (dep_name, constraint) in require {
-                let constraint_str = constraint.as_str().unwrap_or("*");
-                constraints.entry(dep_name.to_string()).or_default().push((
-                    root_name.to_string(),
-                    constraint_str.to_string(),
-                    false,
-                ));
-            }
-        }
+    let json = args.format == "json";
+    if !json {
+        header("Conflict analysis");
+    }
 
-        // Check require-dev
-        if let Some(require_dev) = composer.get("require-dev").and_then(|v| v.as_object()) {
-            for (dep_name, constraint) in require_dev {
-                let constraint_str = constraint.as_str().unwrap_or("*");
-                constraints.entry(dep_name.to_string()).or_default().push((
-                    root_name.to_string(),
-                    constraint_str.to_string(),
-                    true,
-                ));
-            }
+    let cwd = std::env::current_dir()?;
+    let composer_path = cwd.join("composer.json");
+    if !composer_path.exists() {
+        bail!("composer.json not found");
+    }
+    let composer: Value = sonic_rs::from_str(&std::fs::read_to_string(&composer_path)?)?;
+
+    let target = args.package.trim().to_ascii_lowercase();
+    let requested = args.version.as_deref().unwrap_or("*");
+    let is_platform = libretto_core::is_platform_package_name(&target);
+
+    let mut platform = detect_platform(&composer);
+    let constraint = if is_platform {
+        // Checking a platform package means pretending it is installed at that version
+        let version = args
+            .version
+            .as_deref()
+            .and_then(parse_platform_version)
+            .ok_or_else(|| anyhow!("a version is required to check platform package {target}"))?;
+        platform.insert(target.clone(), version.clone());
+        ComposerConstraint::exact(version)
+    } else {
+        if PackageName::parse(&target).is_none() {
+            bail!("invalid package name: {}", args.package);
         }
+        ComposerConstraint::parse(requested)
+            .ok_or_else(|| anyhow!("invalid version constraint: {requested}"))?
+    };
+    if !json && !platform.contains_key("php") {
+        warning("PHP not detected, platform requirements are not checked");
+    }
 
-        // Check conflict section
-        if let Some(conflict) = composer.get("conflict").and_then(|v| v.as_object()) {
-            for (dep_name, constraint) in conflict {
-                let constraint_str = constraint.as_str().unwrap_or("*");
-                constraints.entry(dep_name.to_string()).or_default().push((
-                    format!("{root_name} (conflict)"),
-                    format!("conflicts with {constraint_str}"),
-                    false,
-                ));
-            }
-        }
+    let mut reasons = root_reasons(&composer, &target, &constraint);
+
+    let min_stability = minimum_stability(&composer);
+    let mut fetcher = Fetcher::new_with_composer_repositories(&composer)
+        .map_err(|e| anyhow!("Failed to create fetcher: {e}"))?;
+    if args.offline {
+        fetcher = fetcher.with_offline(true);
+    }
+    let mut resolver = TurboResolver::new(
+        Arc::new(fetcher),
+        inspection_config(min_stability, true, false),
+    )
+    .with_platform(platform.clone());
+    if args.offline {
+        debug!("offline, security advisories are not checked");
+    } else {
+        resolver = SecurityPolicy::load(&composer, false).apply(resolver);
+    }
+    if let Some(cache) = resolution_cache(&composer) {
+        resolver = resolver.with_cache(cache);
     }
 
-    // Check composer.lock
-    if lock_path.exists() {
-        let lock_content = std::fs::read_to_string(&lock_path)?;
-        let lock: sonic_rs::Value = sonic_rs::from_str(&lock_content)?;
-
-        for (packages_key, is_dev) in [("packages", false), ("packages-dev", true)] {
-            if let Some(packages) = lock.get(packages_key).and_then(|v| v.as_array()) {
-                for pkg in packages {
-                    let name = pkg.get("name").and_then(|v| v.as_str()).unwrap_or("");
-
-                    // Check require
-                    if let Some(require) = pkg.get("require").and_then(|v| v.as_object()) {
-                        for (dep_name, constraint) in require {
-                            let constraint_str = constraint.as_str().unwrap_or("*");
-                            constraints.entry(dep_name.to_string()).or_default().push((
-                                name.to_string(),
-                                constraint_str.to_string(),
-                                is_dev,
-                            ));
-                        }
-                    }
-
-                    // Check conflict
-                    if let Some(conflict) = pkg.get("conflict").and_then(|v| v.as_object()) {
-                        for (dep_name, constraint) in conflict {
-                            let constraint_str = constraint.as_str().unwrap_or("*");
-                            constraints.entry(dep_name.to_string()).or_default().push((
-                                format!("{name} (conflict)"),
-                                format!("conflicts with {constraint_str}"),
-                                is_dev,
-                            ));
-                        }
-                    }
-
-                    // Check replace
-                    if let Some(replace) = pkg.get("replace").and_then(|v| v.as_object()) {
-                        for (dep_name, _) in replace {
-                            constraints.entry(dep_name.to_string()).or_default().push((
-                                format!("{name} (replace)"),
-                                "replaced by this package".to_string(),
-                                is_dev,
-                            ));
-                        }
-                    }
-                }
-            }
+    let pin = (!is_platform).then_some(&constraint);
+    let (root_deps, dev_deps) = root_dependencies(&composer, &target, pin);
+    let resolution = match resolver.resolve(&root_deps, &dev_deps).await {
+        Ok(resolution) => {
+            reasons.extend(conflict_reasons(&composer, &resolution));
+            Some(resolution)
+        }
+        Err(ResolveError::Conflict { derivation, .. }) => {
+            reasons.extend(replacement_reasons(&cwd.join("composer.lock"), &target));
+            reasons.push(Reason {
+                kind: "unresolvable",
+                message: derivation.message.clone(),
+                derivation: Some(*derivation),
+            });
+            None
         }
+        Err(e) => bail!("Trial resolution failed: {e}"),
+    };
+    let resolved_version = resolution
+        .as_ref()
+        .and_then(|resolution| resolution.get(&target))
+        .map(|pkg| pkg.version.to_string());
+
+    if json {
+        return output_json(
+            &target,
+            requested,
+            resolved_version.as_deref(),
+            &platform,
+            &reasons,
+        );
     }
 
-    // Find constraints that affect the target package
-    let target = args.package.to_lowercase();
-    let target_constraints = constraints.get(&target);
-
-    if target_constraints.is_none() || target_constraints.is_none_or(std::vec::Vec::is_empty) {
+    if reasons.is_empty() {
+        let resolves_to = resolved_version
+            .map(|version| format!(", it resolves to {version}"))
+            .unwrap_or_default();
         info(&format!(
-            "No constraints found for '{}' - it could be installed freely",
-            args.package
+            "Nothing prohibits installing {target} {requested}{resolves_to}"
         ));
         return Ok(());
     }
 
-    let target_constraints = target_constraints.unwrap();
-
-    // Check which constraints are problematic for the requested version
-    let requested_version = args.version.as_deref().unwrap_or("latest");
-    info(&format!(
-        "Checking what prevents installing {} {}",
-        args.package, requested_version
-    ));
+    let title = format!("{target} {requested} cannot be installed because:");
+    if crate::output::colors_enabled() {
+        println!("{}", title.cyan().bold());
+    } else {
+        println!("{title}");
+    }
+    print!(
+        "{}",
+        render_tree(&reasons, crate::output::unicode_enabled())
+    );
     println!();
+    warning(&format!(
+        "{} reason(s) prevent installing {target} {requested}",
+        reasons.len()
+    ));
 
-    let colors = crate::output::colors_enabled();
+    Ok(())
+}
 
-    // Find conflicting constraints
-    let mut conflicts: Vec<(&str, &str, bool, bool)> = Vec::new();
+/// Root requirements excluding `constraint` and root conflicts matching it.
+fn root_reasons(composer: &Value, target: &str, constraint: &ComposerConstraint) -> Vec<Reason> {
+    let mut reasons = Vec::new();
+    let declared = |key: &str| {
+        composer
+            .get(key)
+            .and_then(|v| v.as_object())
+            .and_then(|links| {
+                links
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(target))
+                    .and_then(|(_, c)| ComposerConstraint::parse(c.as_str().unwrap_or("*")))
+            })
+    };
+
+    for key in ["require", "require-dev"] {
+        if let Some(required) = declared(key)
+            && required.intersection(constraint).is_empty()
+        {
+            reasons.push(Reason::new(
+                "root-requires",
+                format!("root requires {target} {} ({key})", required.as_str()),
+            ));
+        }
+    }
+    if let Some(conflict) = declared("conflict")
+        && !conflict.intersection(constraint).is_empty()
+    {
+        reasons.push(Reason::new(
+            "conflict",
+            format!("root conflicts with {target} {}", conflict.as_str()),
+        ));
+    }
 
-    for (source, constraint, is_dev) in target_constraints {
-        let is_conflict = constraint.contains("conflict")
-            || constraint.contains("replace")
-            || !version_satisfies(requested_version, constraint);
+    reasons
+}
 
-        if is_conflict || args.tree {
-            conflicts.push((source.as_str(), constraint.as_str(), *is_dev, is_conflict));
+/// Root conflict declarations that match selected packages.
+fn conflict_reasons(composer: &Value, resolution: &Resolution) -> Vec<Reason> {
+    let selected: BTreeMap<&str, &ComposerVersion> = resolution
+        .packages
+        .iter()
+        .map(|pkg| (pkg.name.as_str(), &pkg.version))
+        .collect();
+    let Some(conflicts) = composer.get("conflict").and_then(|v| v.as_object()) else {
+        return Vec::new();
+    };
+
+    let mut reasons = Vec::new();
+    for (name, constraint) in conflicts {
+        let name = name.to_ascii_lowercase();
+        let Some(constraint) = constraint.as_str() else {
+            continue;
+        };
+        let (Some(version), Some(parsed)) = (
+            selected.get(name.as_str()),
+            ComposerConstraint::parse(constraint),
+        ) else {
+            continue;
+        };
+        if parsed.matches(version) {
+            reasons.push(Reason::new(
+                "conflict",
+                format!("root conflicts with {name} {constraint} ({name} {version} is selected)"),
+            ));
         }
     }
+    reasons
+}
 
-    if conflicts.is_empty() {
-        info(&format!(
-            "No constraints prevent installing {} {}",
-            args.package, requested_version
-        ));
-        return Ok(());
+/// Locked packages that replace or provide `target`, which the solver then
+/// never selects on its own.
+fn replacement_reasons(lock_path: &std::path::Path, target: &str) -> Vec<Reason> {
+    let Some(lock) = std::fs::read_to_string(lock_path)
+        .ok()
+        .and_then(|content| sonic_rs::from_str::<Value>(&content).ok())
+    else {
+        return Vec::new();
+    };
+
+    let mut reasons = Vec::new();
+    for key in ["packages", "packages-dev"] {
+        for pkg in lock
+            .get(key)
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let name = pkg.get("name").and_then(|v| v.as_str()).unwrap_or_default();
+            let version = pkg
+                .get("version")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            for (link, verb) in [("replace", "replaces"), ("provide", "provides")] {
+                let Some(constraint) = pkg
+                    .get(link)
+                    .and_then(|v| v.as_object())
+                    .and_then(|links| {
+                        links
+                            .iter()
+                            .find(|(linked, _)| linked.eq_ignore_ascii_case(target))
+                    })
+                    .map(|(_, c)| c.as_str().unwrap_or("*").to_string())
+                else {
+                    continue;
+                };
+                reasons.push(Reason::new(
+                    "replaced",
+                    format!("{name} {version} {verb} {target} {constraint} (composer.lock)"),
+                ));
+            }
+        }
     }
+    reasons
+}
 
-    // Output conflicts
-    if args.tree {
-        print_tree(&args.package, &conflicts, colors);
-    } else {
-        let mut table = Table::new();
-        table.headers(["Package", "Constraint", "Type", "Blocks?"]);
-
-        for (source, constraint, is_dev, is_conflict) in &conflicts {
-            let pkg_type = if *is_dev { "dev" } else { "prod" };
-            let blocks = if *is_conflict { "Yes" } else { "No" };
-
-            let blocks_cell = if *is_conflict {
-                table.error_cell(blocks)
-            } else {
-                table.success_cell(blocks)
-            };
-
-            table.styled_row(vec![
-                comfy_table::Cell::new(source),
-                comfy_table::Cell::new(constraint),
-                comfy_table::Cell::new(pkg_type),
-                blocks_cell,
-            ]);
+/// Root requirements for the trial, platform packages included, with the
+/// root requirement on `target` replaced by `pin`.
+fn root_dependencies(
+    composer: &Value,
+    target: &str,
+    pin: Option<&ComposerConstraint>,
+) -> (Vec<Dependency>, Vec<Dependency>) {
+    let parse = |key: &str| -> Vec<Dependency> {
+        composer
+            .get(key)
+            .and_then(|v| v.as_object())
+            .map(|require| {
+                require
+                    .iter()
+                    .filter(|(name, _)| !name.eq_ignore_ascii_case(target))
+                    .filter_map(|(name, constraint)| {
+                        Some(Dependency::new(
+                            PackageName::parse(name).or_else(|| PackageName::platform(name))?,
+                            ComposerConstraint::parse(constraint.as_str().unwrap_or("*"))?,
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut root_deps = parse("require");
+    let dev_deps = parse("require-dev");
+    if let (Some(pin), Some(name)) = (pin, PackageName::parse(target)) {
+        root_deps.push(Dependency::new(name, pin.clone()));
+    }
+    (root_deps, dev_deps)
+}
+
+/// Installed platform packages: the detected PHP version and extensions,
+/// overridden by `config.platform`.
+fn detect_platform(composer: &Value) -> BTreeMap<String, ComposerVersion> {
+    let mut platform = BTreeMap::new();
+
+    let mut validator = PlatformValidator::new();
+    match validator.detect() {
+        Ok(detected) => {
+            if let Some(php) = detected
+                .php_version
+                .as_deref()
+                .and_then(parse_platform_version)
+            {
+                for (extension, version) in &detected.extensions {
+                    // Bundled extensions report no version of their own
+                    let version = version
+                        .as_deref()
+                        .and_then(parse_platform_version)
+                        .unwrap_or_else(|| php.clone());
+                    platform.insert(format!("ext-{extension}"), version);
+                }
+                platform.insert("php".to_string(), php);
+            }
         }
+        Err(e) => debug!(error = %e, "platform detection failed"),
+    }
 
-        table.print();
+    if let Some(overrides) = composer
+        .get("config")
+        .and_then(|config| config.get("platform"))
+        .and_then(|platform| platform.as_object())
+    {
+        for (name, version) in overrides {
+            if let Some(version) = version.as_str().and_then(parse_platform_version) {
+                platform.insert(name.to_ascii_lowercase(), version);
+            }
+        }
     }
 
-    // Count blocking constraints
-    let blocking_count = conflicts.iter().filter(|(_, _, _, c)| *c).count();
-    println!();
+    platform
+}
 
-    if blocking_count > 0 {
-        warning(&format!(
-            "{} package(s) have constraints that may prevent installing {} {}",
-            blocking_count, args.package, requested_version
-        ));
-    } else {
-        info(&format!(
-            "All constraints allow installing {} {}",
-            args.package, requested_version
-        ));
+/// Parse a platform version, dropping distribution suffixes such as
+/// `-1ubuntu2`.
+fn parse_platform_version(version: &str) -> Option<ComposerVersion> {
+    ComposerVersion::parse(version).or_else(|| {
+        let end = version
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(version.len());
+        ComposerVersion::parse(version[..end].trim_end_matches('.'))
+    })
+}
+
+/// Render `reasons` as a tree, expanding solver derivations.
+fn render_tree(reasons: &[Reason], unicode: bool) -> String {
+    let mut out = String::new();
+    for (i, reason) in reasons.iter().enumerate() {
+        let last = i + 1 == reasons.len();
+        match &reason.derivation {
+            Some(derivation) => render_node(&mut out, derivation, "", last, unicode),
+            None => push_line(&mut out, "", last, unicode, &reason.message),
+        }
     }
+    out
+}
 
-    Ok(())
+fn render_node(out: &mut String, node: &Derivation, indent: &str, last: bool, unicode: bool) {
+    if node.is_repeated() {
+        push_line(
+            out,
+            indent,
+            last,
+            unicode,
+            &format!("{} (see above)", node.message),
+        );
+        return;
+    }
+    push_line(out, indent, last, unicode, &node.message);
+
+    let continuation = match (last, unicode) {
+        (true, _) => "    ",
+        (false, true) => "\u{2502}   ",
+        (false, false) => "|   ",
+    };
+    let indent = format!("{indent}{continuation}");
+    for (i, cause) in node.causes.iter().enumerate() {
+        render_node(out, cause, &indent, i + 1 == node.causes.len(), unicode);
+    }
 }
 
-fn print_tree(package: &str, conflicts: &[(&str, &str, bool, bool)], colors: bool) {
-    use owo_colors::OwoColorize;
+fn push_line(out: &mut String, indent: &str, last: bool, unicode: bool, message: &str) {
+    let branch = match (last, unicode) {
+        (true, true) => "\u{2514}\u{2500}\u{2500}",
+        (false, true) => "\u{251C}\u{2500}\u{2500}",
+        (true, false) => "`--",
+        (false, false) => "|--",
+    };
+    let _ = writeln!(out, "{indent}{branch} {message}");
+}
+
+fn output_json(
+    target: &str,
+    requested: &str,
+    resolved_version: Option<&str>,
+    platform: &BTreeMap<String, ComposerVersion>,
+    reasons: &[Reason],
+) -> Result<()> {
+    let reasons: Vec<_> = reasons
+        .iter()
+        .map(|reason| {
+            let derivation = reason
+                .derivation
+                .as_ref()
+                .map(sonic_rs::to_value)
+                .transpose()?;
+            Ok(sonic_rs::json!({
+                "kind": reason.kind,
+                "message": reason.message,
+                "derivation": derivation,
+            }))
+        })
+        .collect::<Result<_, sonic_rs::Error>>()?;
+    let platform: BTreeMap<&str, String> = platform
+        .iter()
+        .map(|(name, version)| (name.as_str(), version.to_string()))
+        .collect();
+
+    let output = sonic_rs::json!({
+        "package": target,
+        "constraint": requested,
+        "prohibited": !reasons.is_empty(),
+        "resolved-version": resolved_version,
+        "platform": platform,
+        "reasons": reasons,
+    });
+
+    println!("{}", sonic_rs::to_string_pretty(&output)?);
+    Ok(())
+}
 
-    let unicode = crate::output::unicode_enabled();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if colors {
-        println!("{}", package.cyan().bold());
-    } else {
-        println!("{package}");
+    fn constraint(c: &str) -> ComposerConstraint {
+        ComposerConstraint::parse(c).unwrap()
     }
 
-    for (i, (source, constraint, is_dev, is_conflict)) in conflicts.iter().enumerate() {
-        let is_last = i == conflicts.len() - 1;
-        let prefix = if unicode {
-            if is_last {
-                "\u{2514}\u{2500}\u{2500}"
-            } else {
-                "\u{251C}\u{2500}\u{2500}"
-            }
-        } else if is_last {
-            "`--"
-        } else {
-            "|--"
-        };
-
-        let dev_marker = if *is_dev { " (dev)" } else { "" };
-        let block_marker = if *is_conflict { " [BLOCKS]" } else { "" };
-
-        if colors {
-            let constraint_colored = if *is_conflict {
-                constraint.red().to_string()
-            } else {
-                constraint.green().to_string()
-            };
-            println!(
-                " {} {} {} {}{}",
-                prefix,
-                source.green(),
-                constraint_colored,
-                dev_marker.dimmed(),
-                if *is_conflict {
-                    block_marker.red().bold().to_string()
-                } else {
-                    String::new()
-                }
-            );
-        } else {
-            println!(" {prefix} {source} {constraint} {dev_marker}{block_marker}");
-        }
+    #[test]
+    fn root_requirements_excluding_the_version_are_reasons() {
+        let composer: Value = sonic_rs::from_str(
+            r#"{
+                "require": {"acme/lib": "^2.0"},
+                "require-dev": {"acme/lib": ">=1.0"},
+                "conflict": {"acme/lib": ">=3.1"}
+            }"#,
+        )
+        .unwrap();
+
+        let reasons = root_reasons(&composer, "acme/lib", &constraint("3.0.0"));
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].message, "root requires acme/lib ^2.0 (require)");
+
+        let reasons = root_reasons(&composer, "acme/lib", &constraint("3.2.0"));
+        let kinds: Vec<_> = reasons.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds, ["root-requires", "conflict"]);
     }
-}
 
-fn version_satisfies(version: &str, constraint: &str) -> bool {
-    // Simplified version matching
-    if constraint == "*" || version == "latest" {
-        return true;
+    #[test]
+    fn trial_replaces_the_root_requirement_and_keeps_platform_packages() {
+        let composer: Value = sonic_rs::from_str(
+            r#"{"require": {"php": "^8.1", "acme/lib": "^2.0", "acme/other": "^1.0"}}"#,
+        )
+        .unwrap();
+
+        let (root_deps, _) = root_dependencies(&composer, "acme/lib", Some(&constraint("3.0")));
+        let deps: Vec<_> = root_deps
+            .iter()
+            .map(|d| format!("{} {}", d.name, d.constraint.as_str()))
+            .collect();
+        assert!(deps.contains(&"php ^8.1".to_string()));
+        assert!(deps.contains(&"acme/other ^1.0".to_string()));
+        assert!(deps.contains(&"acme/lib 3.0".to_string()));
+        assert_eq!(deps.len(), 3);
     }
 
-    if constraint.contains("conflict") || constraint.contains("replace") {
-        return false;
+    #[test]
+    fn platform_versions_drop_distribution_suffixes() {
+        assert_eq!(
+            parse_platform_version("8.1.2-1ubuntu2.14").map(|v| v.to_string()),
+            Some("8.1.2".to_string())
+        );
+        assert_eq!(
+            parse_platform_version("8.3.0").map(|v| v.to_string()),
+            Some("8.3.0".to_string())
+        );
     }
 
-    // Try to parse and compare
-    let constraint = libretto_core::VersionConstraint::new(constraint);
-    if let Ok(version) = semver::Version::parse(version.trim_start_matches('v')) {
-        constraint.matches(&version)
-    } else {
-        // If we can't parse the version, assume it might match
-        true
+    #[test]
+    fn derivations_render_as_an_ascii_tree() {
+        let leaf = |message: &str| Derivation {
+            kind: libretto_resolver::DerivationKind::Requires,
+            message: message.to_string(),
+            terms: Vec::new(),
+            shared_id: None,
+            causes: Vec::new(),
+        };
+        let derivation = Derivation {
+            kind: libretto_resolver::DerivationKind::Derived,
+            message: "the root requirements cannot be satisfied".to_string(),
+            terms: Vec::new(),
+            shared_id: None,
+            causes: vec![
+                leaf("root requires acme/lib ==3.0.0"),
+                leaf("acme/lib ==3.0.0 requires php >=8.2 (the platform provides 8.1.0)"),
+            ],
+        };
+        let reasons = vec![
+            Reason::new(
+                "root-requires",
+                "root requires acme/lib ^2.0 (require)".to_string(),
+            ),
+            Reason {
+                kind: "unresolvable",
+                message: derivation.message.clone(),
+                derivation: Some(derivation),
+            },
+        ];
+
+        assert_eq!(
+            render_tree(&reasons, false),
+            "|-- root requires acme/lib ^2.0 (require)\n\
+             `-- the root requirements cannot be satisfied\n    \
+             |-- root requires acme/lib ==3.0.0\n    \
+             `-- acme/lib ==3.0.0 requires php >=8.2 (the platform provides 8.1.0)\n"
+        );
     }
 }
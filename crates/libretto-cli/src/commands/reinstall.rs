@@ -122,9 +122,12 @@ pub async fn run(args: ReinstallArgs) -> Result<()> {
         concurrency: 64,
         audit: false,
         fail_on_audit: false,
+        no_security_blocking: false,
         verify_checksums: false,
+        strict_lock: false,
         php_version: None,
         no_php_check: false,
+        download: crate::commands::install::DownloadArgs::default(),
     };
 
     spinner.finish_and_clear();
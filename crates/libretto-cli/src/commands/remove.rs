@@ -1,9 +1,14 @@
 //! Remove command implementation.
 
-use crate::scripts::{ScriptConfig, ScriptEvent, run_package_scripts};
+use crate::output::out;
+use crate::plugins::PluginRuntime;
+use crate::scripts::{ScriptConfig, ScriptEvent, process_timeout, run_package_scripts};
 use anyhow::Result;
 use clap::Args;
-use sonic_rs::{JsonValueMutTrait, JsonValueTrait};
+use libretto_plugin_system::Hook;
+use sonic_rs::{JsonContainerTrait, JsonValueMutTrait, JsonValueTrait};
+use std::collections::HashMap;
+use std::path::Path;
 
 /// Arguments for the remove command.
 #[derive(Args, Debug, Clone)]
@@ -48,9 +53,13 @@ pub async fn run(args: RemoveArgs) -> Result<()> {
     let script_config = ScriptConfig {
         working_dir: cwd.clone(),
         dev_mode: !args.dev,
+        timeout: process_timeout(&composer),
         ..Default::default()
     };
 
+    let plugins = PluginRuntime::load(&composer, &cwd, &vendor_dir, "remove", true).await;
+    let locked_versions = locked_versions(&cwd.join("composer.lock"));
+
     let colors = crate::output::colors_enabled();
     let mut removed: Vec<String> = Vec::new();
     let mut not_found: Vec<String> = Vec::new();
@@ -84,12 +93,20 @@ pub async fn run(args: RemoveArgs) -> Result<()> {
 
         if found {
             if colors {
-                println!("  {} {}", "-".red(), package.red());
+                out!("  {} {}", "-".red(), package.red());
             } else {
-                println!("  - {package}");
+                out!("  - {package}");
             }
             removed.push(package.clone());
 
+            // Plugins only hear about packages that are actually installed
+            let installed = locked_versions.get(package);
+            if let Some(version) = installed {
+                plugins
+                    .dispatch_package(Hook::PrePackageUninstall, package, version)
+                    .await;
+            }
+
             // Remove from vendor directory
             let pkg_dir = vendor_dir.join(package.replace('/', std::path::MAIN_SEPARATOR_STR));
             if pkg_dir.exists()
@@ -105,13 +122,18 @@ pub async fn run(args: RemoveArgs) -> Result<()> {
                 ScriptEvent::PostPackageUninstall,
                 package,
             );
+            if let Some(version) = installed {
+                plugins
+                    .dispatch_package(Hook::PostPackageUninstall, package, version)
+                    .await;
+            }
         } else {
             not_found.push(package.clone());
         }
     }
 
     if !not_found.is_empty() {
-        println!();
+        out!();
         for package in &not_found {
             warning(&format!("Package '{package}' not found in dependencies"));
         }
@@ -130,7 +152,7 @@ pub async fn run(args: RemoveArgs) -> Result<()> {
 
     // Update lock file
     if !args.no_update {
-        println!();
+        out!();
         info("Updating lock file...");
 
         let lock_path = cwd.join("composer.lock");
@@ -150,10 +172,9 @@ pub async fn run(args: RemoveArgs) -> Result<()> {
             }
 
             // Update content hash
-            let content_hash =
-                libretto_core::ContentHash::from_bytes(sonic_rs::to_string(&composer)?.as_bytes());
+            let content_hash = libretto_lockfile::ContentHasher::compute_manifest_hash(&composer);
             if let Some(obj) = lock.as_object_mut() {
-                obj.insert("content-hash", sonic_rs::json!(content_hash.to_hex()));
+                obj.insert("content-hash", sonic_rs::json!(content_hash));
             }
 
             let output = sonic_rs::to_string_pretty(&lock)?;
@@ -185,3 +206,25 @@ pub async fn run(args: RemoveArgs) -> Result<()> {
 
     Ok(())
 }
+
+/// Read package versions from composer.lock, if present.
+fn locked_versions(lock_path: &Path) -> HashMap<String, String> {
+    let Some(lock) = std::fs::read_to_string(lock_path)
+        .ok()
+        .and_then(|content| sonic_rs::from_str::<sonic_rs::Value>(&content).ok())
+    else {
+        return HashMap::new();
+    };
+
+    ["packages", "packages-dev"]
+        .iter()
+        .filter_map(|key| lock.get(*key).and_then(|v| v.as_array()))
+        .flatten()
+        .filter_map(|pkg| {
+            Some((
+                pkg.get("name")?.as_str()?.to_string(),
+                pkg.get("version")?.as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,886 @@
+//! Repo:build command - build a static Composer repository (a Satis replacement).
+//!
+//! Reads a Satis-style config, selects every version of the requested
+//! packages matching their constraints (and, optionally, of their
+//! dependencies), archives their dists into the output directory and writes
+//! Composer 2 metadata pointing at them:
+//!
+//! ```json
+//! {
+//!     "name": "acme/mirror",
+//!     "homepage": "https://packages.example.com",
+//!     "require": { "psr/log": "^3.0", "monolog/monolog": "^3.5" },
+//!     "require-dependencies": true,
+//!     "archive": { "directory": "dist", "skip-dev": true }
+//! }
+//! ```
+//!
+//! Rebuilds are incremental: `.libretto-build.json` in the output directory
+//! records the SHA-1 of every archive and metadata file, so unchanged
+//! archives are not downloaded again and unchanged metadata keeps its mtime.
+
+use crate::cas_cache;
+use crate::commands::install::convert_github_api_url;
+use crate::fetcher::Fetcher;
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use libretto_archive::{ArchiveType, Extractor, create_zip};
+use libretto_resolver::turbo::{FetchedVersion, TurboFetcher};
+use libretto_resolver::{ComposerConstraint, ComposerVersion};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use sonic_rs::{JsonContainerTrait, JsonValueMutTrait, JsonValueTrait, Value};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Build state file kept in the output directory.
+const STATE_FILE: &str = ".libretto-build.json";
+
+/// Concurrent dist downloads.
+const DOWNLOAD_CONCURRENCY: usize = 8;
+
+/// Time allowed to connect to a dist host.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time allowed between reads of a dist download.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Arguments for the repo:build command
+#[derive(Args, Debug, Clone)]
+pub struct RepoBuildArgs {
+    /// Build configuration (Satis format)
+    #[arg(short = 'c', long, default_value = "satis.json", value_name = "FILE")]
+    pub config: PathBuf,
+
+    /// Directory to write the repository to
+    #[arg(short = 'o', long, value_name = "DIR")]
+    pub output: PathBuf,
+
+    /// Base URL the repository is served from; overrides `homepage`
+    #[arg(long, value_name = "URL")]
+    pub base_url: Option<String>,
+}
+
+/// Settings read from the build configuration.
+#[derive(Debug, Clone)]
+struct BuildConfig {
+    /// The config itself, for the fetcher's repository settings.
+    raw: Value,
+    /// Base URL the repository is served from.
+    base_url: String,
+    /// Requested packages and constraints.
+    require: BTreeMap<String, String>,
+    /// Also select versions of the packages' dependencies.
+    require_dependencies: bool,
+    /// Also select versions of the packages' dev dependencies.
+    require_dev_dependencies: bool,
+    /// Archive directory, relative to the output directory.
+    archive_dir: String,
+    /// URL the archives are served from.
+    archive_url: String,
+    /// Keep the upstream dist of dev versions instead of archiving them.
+    skip_dev: bool,
+}
+
+impl BuildConfig {
+    fn from_value(raw: Value, base_url: Option<&str>) -> Result<Self> {
+        let base_url = base_url
+            .or_else(|| raw.get("homepage").and_then(|v| v.as_str()))
+            .context("The build config has no `homepage`; pass --base-url")?
+            .trim_end_matches('/')
+            .to_string();
+
+        let require = raw
+            .get("require")
+            .and_then(|v| v.as_object())
+            .map(|map| {
+                map.iter()
+                    .filter_map(|(name, constraint)| {
+                        Some((name.to_ascii_lowercase(), constraint.as_str()?.to_string()))
+                    })
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .unwrap_or_default();
+        if require.is_empty() {
+            bail!("The build config requires no packages");
+        }
+
+        let flag = |key: &str| raw.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+        let archive = raw.get("archive");
+        let archive_dir = archive
+            .and_then(|a| a.get("directory"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("dist")
+            .trim_matches('/')
+            .to_string();
+        let archive_url = archive
+            .and_then(|a| a.get("prefix-url"))
+            .and_then(|v| v.as_str())
+            .unwrap_or(&base_url)
+            .trim_end_matches('/')
+            .to_string();
+        let skip_dev = archive
+            .and_then(|a| a.get("skip-dev"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        Ok(Self {
+            require_dependencies: flag("require-dependencies"),
+            require_dev_dependencies: flag("require-dev-dependencies"),
+            raw,
+            base_url,
+            require,
+            archive_dir,
+            archive_url,
+            skip_dev,
+        })
+    }
+}
+
+/// Hashes recorded by the previous build.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BuildState {
+    /// Archives by path relative to the output directory.
+    #[serde(default)]
+    dists: BTreeMap<String, DistState>,
+    /// SHA-1 of each metadata file, by path relative to the output directory.
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+}
+
+/// A stored archive and the upstream dist it was built from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DistState {
+    source: String,
+    sha1: String,
+}
+
+impl BuildState {
+    fn load(output: &Path) -> Self {
+        std::fs::read_to_string(output.join(STATE_FILE))
+            .ok()
+            .and_then(|content| sonic_rs::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, output: &Path) -> Result<()> {
+        std::fs::write(output.join(STATE_FILE), sonic_rs::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// An archive to produce for a selected version.
+#[derive(Debug, Clone)]
+struct DistJob {
+    package: String,
+    version: String,
+    /// Upstream dist URL.
+    source: String,
+    dist_type: Option<String>,
+    /// Upstream SHA-1 of the dist, when the repository publishes one.
+    shasum: Option<String>,
+    /// Archive path relative to the output directory.
+    path: String,
+}
+
+/// Run the repo:build command
+pub async fn run(args: RepoBuildArgs) -> Result<()> {
+    use crate::output::{header, info, success, warning};
+
+    header("Building repository");
+
+    let content = std::fs::read_to_string(&args.config)
+        .with_context(|| format!("Failed to read {}", args.config.display()))?;
+    let raw: Value = sonic_rs::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", args.config.display()))?;
+    let config = BuildConfig::from_value(raw, args.base_url.as_deref())?;
+
+    let fetcher = Fetcher::new_with_composer_repositories(&config.raw)
+        .map_err(|e| anyhow::anyhow!("Failed to create fetcher: {e}"))?;
+    let mut packages = select_versions(&fetcher, &config).await?;
+    let version_count: usize = packages.values().map(Vec::len).sum();
+    info(&format!(
+        "Selected {version_count} version(s) of {} package(s)",
+        packages.len()
+    ));
+
+    std::fs::create_dir_all(&args.output)?;
+    let previous = BuildState::load(&args.output);
+    let mut state = BuildState::default();
+
+    // Archive dists and point the metadata at them
+    let mut jobs = Vec::new();
+    for (name, versions) in &packages {
+        for version in versions {
+            if let Some(job) = dist_job(&config, name, version) {
+                jobs.push(job);
+            }
+        }
+    }
+    let results = build_dists(&args.output, &previous, jobs).await;
+
+    let mut archived = BTreeMap::new();
+    let (mut reused, mut built, mut failed) = (0, 0, 0);
+    for (job, result) in results {
+        match result {
+            Ok((sha1, fresh)) => {
+                if fresh {
+                    built += 1;
+                } else {
+                    reused += 1;
+                }
+                state.dists.insert(
+                    job.path.clone(),
+                    DistState {
+                        source: job.source.clone(),
+                        sha1: sha1.clone(),
+                    },
+                );
+                archived.insert((job.package, job.version), (job.path, sha1));
+            }
+            Err(e) => {
+                failed += 1;
+                warning(&format!(
+                    "Keeping the upstream dist of {} {}: {e:#}",
+                    job.package, job.version
+                ));
+            }
+        }
+    }
+
+    for (name, versions) in &mut packages {
+        for version in versions.iter_mut() {
+            let key = (
+                name.clone(),
+                version
+                    .get("version")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+            );
+            if let Some((path, sha1)) = archived.get(&key) {
+                rewrite_dist(version, &format!("{}/{path}", config.archive_url), sha1);
+            }
+        }
+    }
+
+    // Metadata
+    let mut written = 0;
+    let mut files: Vec<(String, String)> = Vec::new();
+    for (name, versions) in &packages {
+        let (tagged, dev): (Vec<Value>, Vec<Value>) = versions
+            .iter()
+            .cloned()
+            .partition(|v| !is_dev_version(v.get("version").and_then(|v| v.as_str())));
+        for (suffix, versions) in [("", tagged), ("~dev", dev)] {
+            let document = sonic_rs::json!({ "packages": { name.as_str(): versions } });
+            files.push((
+                format!("p2/{name}{suffix}.json"),
+                sonic_rs::to_string(&document)?,
+            ));
+        }
+    }
+    let names: Vec<&String> = packages.keys().collect();
+    let mut root = sonic_rs::json!({
+        "packages": [],
+        "metadata-url": metadata_url(&config.base_url),
+        "available-packages": names,
+    });
+    if let Some(name) = config.raw.get("name").and_then(|v| v.as_str())
+        && let Some(obj) = root.as_object_mut()
+    {
+        obj.insert("name", Value::from(name));
+    }
+    files.push((
+        "packages.json".to_string(),
+        sonic_rs::to_string_pretty(&root)?,
+    ));
+
+    for (path, content) in files {
+        let sha1 = hex::encode(Sha1::digest(content.as_bytes()));
+        let target = args.output.join(&path);
+        if previous.metadata.get(&path) != Some(&sha1) || !target.exists() {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&target, content)?;
+            written += 1;
+        }
+        state.metadata.insert(path, sha1);
+    }
+
+    // Drop files the previous build wrote that this one no longer lists
+    let stale: Vec<&String> = previous
+        .dists
+        .keys()
+        .filter(|path| !state.dists.contains_key(*path))
+        .chain(
+            previous
+                .metadata
+                .keys()
+                .filter(|path| !state.metadata.contains_key(*path)),
+        )
+        .collect();
+    for path in &stale {
+        let _ = std::fs::remove_file(args.output.join(path));
+    }
+
+    state.save(&args.output)?;
+
+    info(&format!(
+        "Archives: {built} built, {reused} unchanged, {failed} failed; metadata: {written} file(s) written, {} removed",
+        stale.len()
+    ));
+    success(&format!("Repository written to {}", args.output.display()));
+    Ok(())
+}
+
+/// Select every version of the required packages matching their
+/// constraints, following dependencies when configured.
+async fn select_versions(
+    fetcher: &Fetcher,
+    config: &BuildConfig,
+) -> Result<BTreeMap<String, Vec<Value>>> {
+    use crate::output::warning;
+
+    let mut selected: BTreeMap<String, BTreeMap<String, Value>> = BTreeMap::new();
+    let mut seen: HashSet<(String, String)> = HashSet::new();
+    let mut metadata: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    let mut pending: Vec<(String, String)> = config
+        .require
+        .iter()
+        .map(|(name, constraint)| (name.clone(), constraint.clone()))
+        .collect();
+
+    while !pending.is_empty() {
+        let requests: Vec<(String, String)> = std::mem::take(&mut pending)
+            .into_iter()
+            .filter(|(name, _)| !libretto_core::is_platform_package_name(name))
+            .filter(|request| seen.insert(request.clone()))
+            .collect();
+
+        let missing: BTreeSet<&String> = requests
+            .iter()
+            .map(|(name, _)| name)
+            .filter(|name| !metadata.contains_key(*name))
+            .collect();
+        let fetched = futures::future::join_all(
+            missing
+                .into_iter()
+                .map(|name| async move { (name.clone(), package_versions(fetcher, name).await) }),
+        )
+        .await;
+        for (name, versions) in fetched {
+            if versions.is_empty() {
+                warning(&format!("Package {name} not found"));
+            }
+            metadata.insert(name, versions);
+        }
+
+        for (name, constraint) in requests {
+            let Some(parsed) = ComposerConstraint::parse(&constraint) else {
+                warning(&format!("Invalid constraint {constraint} for {name}"));
+                continue;
+            };
+            for version in metadata.get(&name).into_iter().flatten() {
+                let Some(pretty) = version.get("version").and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                if !ComposerVersion::parse(pretty).is_some_and(|v| parsed.matches(&v)) {
+                    continue;
+                }
+                let versions = selected.entry(name.clone()).or_default();
+                if versions.contains_key(pretty) {
+                    continue;
+                }
+                versions.insert(pretty.to_string(), version.clone());
+
+                let mut sections = Vec::new();
+                if config.require_dependencies {
+                    sections.push("require");
+                }
+                if config.require_dev_dependencies {
+                    sections.push("require-dev");
+                }
+                for section in sections {
+                    if let Some(links) = version.get(section).and_then(|v| v.as_object()) {
+                        for (dep, dep_constraint) in links {
+                            if let Some(dep_constraint) = dep_constraint.as_str() {
+                                pending
+                                    .push((dep.to_ascii_lowercase(), dep_constraint.to_string()));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(selected
+        .into_iter()
+        .map(|(name, versions)| (name, versions.into_values().collect()))
+        .collect())
+}
+
+/// Version documents for a package, from the metadata cache when the
+/// repository serves one and rebuilt from the fetched versions otherwise.
+async fn package_versions(fetcher: &Fetcher, name: &str) -> Vec<Value> {
+    let mut versions = match fetcher.package_metadata(name).await {
+        Some(versions) => versions,
+        None => fetcher
+            .fetch(name.to_string())
+            .await
+            .map(|package| package.versions.iter().map(version_document).collect())
+            .unwrap_or_default(),
+    };
+    for version in &mut versions {
+        if let Some(obj) = version.as_object_mut()
+            && !obj.contains_key(&"name")
+        {
+            obj.insert("name", Value::from(name));
+        }
+    }
+    versions
+}
+
+/// A metadata document for a version fetched from a VCS repository.
+fn version_document(version: &FetchedVersion) -> Value {
+    let links = |links: &[(String, String)]| -> BTreeMap<String, String> {
+        links.iter().cloned().collect()
+    };
+    let mut document = sonic_rs::json!({
+        "version": version.version,
+        "require": links(&version.require),
+        "require-dev": links(&version.require_dev),
+        "replace": links(&version.replace),
+        "provide": links(&version.provide),
+        "conflict": links(&version.conflict),
+        "suggest": links(&version.suggest),
+    });
+    let Some(obj) = document.as_object_mut() else {
+        return document;
+    };
+    let optional: [(&str, Option<Value>); 11] = [
+        ("type", version.package_type.as_deref().map(Value::from)),
+        (
+            "description",
+            version.description.as_deref().map(Value::from),
+        ),
+        ("homepage", version.homepage.as_deref().map(Value::from)),
+        ("time", version.time.as_deref().map(Value::from)),
+        (
+            "license",
+            version
+                .license
+                .as_ref()
+                .and_then(|l| sonic_rs::to_value(l).ok()),
+        ),
+        ("authors", version.authors.clone()),
+        ("autoload", version.autoload.clone()),
+        ("autoload-dev", version.autoload_dev.clone()),
+        ("extra", version.extra.clone()),
+        (
+            "bin",
+            version
+                .bin
+                .as_ref()
+                .and_then(|b| sonic_rs::to_value(b).ok()),
+        ),
+        (
+            "source",
+            version.source_url.as_ref().map(|url| {
+                sonic_rs::json!({
+                    "type": version.source_type.as_deref().unwrap_or("git"),
+                    "url": url,
+                    "reference": version.source_reference,
+                })
+            }),
+        ),
+    ];
+    for (key, value) in optional {
+        if let Some(value) = value {
+            obj.insert(key, value);
+        }
+    }
+    if let Some(url) = &version.dist_url {
+        obj.insert(
+            "dist",
+            sonic_rs::json!({
+                "type": version.dist_type.as_deref().unwrap_or("zip"),
+                "url": url,
+                "reference": version.source_reference,
+                "shasum": version.dist_shasum.as_deref().unwrap_or_default(),
+            }),
+        );
+    }
+    document
+}
+
+/// Whether a version is a development version, listed in `~dev.json`.
+fn is_dev_version(version: Option<&str>) -> bool {
+    version.is_some_and(|v| v.starts_with("dev-") || v.ends_with("-dev"))
+}
+
+/// The archive to build for a version, if it has a dist to mirror.
+fn dist_job(config: &BuildConfig, name: &str, version: &Value) -> Option<DistJob> {
+    let pretty = version.get("version").and_then(|v| v.as_str())?;
+    if config.skip_dev && is_dev_version(Some(pretty)) {
+        return None;
+    }
+    let dist = version.get("dist")?;
+    let url = dist.get("url").and_then(|v| v.as_str())?;
+    let reference = dist
+        .get("reference")
+        .and_then(|v| v.as_str())
+        .filter(|r| !r.is_empty());
+    Some(DistJob {
+        package: name.to_string(),
+        version: pretty.to_string(),
+        source: url.to_string(),
+        dist_type: dist.get("type").and_then(|v| v.as_str()).map(String::from),
+        shasum: dist
+            .get("shasum")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_ascii_lowercase),
+        path: dist_path(&config.archive_dir, name, pretty, reference),
+    })
+}
+
+/// Archive path for a version, in Satis's layout:
+/// `dist/vendor/name/vendor-name-version-reference.zip`.
+fn dist_path(directory: &str, name: &str, version: &str, reference: Option<&str>) -> String {
+    let safe = |s: &str| {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect::<String>()
+    };
+    let mut file = format!("{}-{}", safe(name), safe(version));
+    if let Some(reference) = reference {
+        file.push('-');
+        file.push_str(&safe(reference));
+    }
+    format!("{directory}/{name}/{file}.zip")
+}
+
+/// Point a version's dist at its archive in the repository.
+fn rewrite_dist(version: &mut Value, url: &str, sha1: &str) {
+    let reference = version
+        .get("dist")
+        .and_then(|d| d.get("reference"))
+        .cloned()
+        .unwrap_or_default();
+    if let Some(obj) = version.as_object_mut() {
+        obj.insert(
+            "dist",
+            sonic_rs::json!({
+                "type": "zip",
+                "url": url,
+                "reference": reference,
+                "shasum": sha1,
+            }),
+        );
+    }
+}
+
+/// URL template for per-package metadata, relative to the host so the
+/// repository can be served from any scheme.
+fn metadata_url(base_url: &str) -> String {
+    let path = url::Url::parse(base_url)
+        .map(|url| url.path().trim_end_matches('/').to_string())
+        .unwrap_or_default();
+    format!("{path}/p2/%package%.json")
+}
+
+/// Build the archives, reusing those whose stored hash still matches.
+///
+/// Each result carries the archive's SHA-1 and whether it was built anew.
+async fn build_dists(
+    output: &Path,
+    previous: &BuildState,
+    jobs: Vec<DistJob>,
+) -> Vec<(DistJob, Result<(String, bool)>)> {
+    use futures::stream::StreamExt;
+
+    let client = reqwest::Client::builder()
+        .user_agent(format!("Libretto/{}", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT)
+        .build();
+
+    futures::stream::iter(jobs)
+        .map(|job| {
+            let client = client.as_ref().map_err(|e| anyhow::anyhow!("{e}"));
+            let target = output.join(&job.path);
+            let stored = previous
+                .dists
+                .get(&job.path)
+                .filter(|stored| stored.source == job.source)
+                .cloned();
+            async move {
+                if let Some(stored) = stored
+                    && file_sha1(&target).is_ok_and(|sha1| sha1 == stored.sha1)
+                {
+                    return (job, Ok((stored.sha1, false)));
+                }
+                let result = match client {
+                    Ok(client) => build_dist(client, &job, &target).await,
+                    Err(e) => Err(e),
+                };
+                (job, result.map(|sha1| (sha1, true)))
+            }
+        })
+        .buffer_unordered(DOWNLOAD_CONCURRENCY)
+        .collect()
+        .await
+}
+
+/// Archive a version's contents into `target`, downloading them into the
+/// CAS cache first when they are not already there.
+async fn build_dist(client: &reqwest::Client, job: &DistJob, target: &Path) -> Result<String> {
+    let url = convert_github_api_url(&job.source);
+    let contents = match cas_cache::get_cached_path(&cache_key(job, &url)) {
+        Some(path) => path,
+        None => download_to_cache(client, job, &url).await?,
+    };
+
+    let target = target.to_path_buf();
+    let prefix = format!("{}-{}", job.package.replace('/', "-"), job.version);
+    tokio::task::spawn_blocking(move || -> Result<String> {
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Stage hardlinks so the cache's bookkeeping files stay out of the archive
+        let staging = tempfile::tempdir()?;
+        let root = staging.path().join("package");
+        cas_cache::link_from_cache(&contents, &root)?;
+
+        let partial = target.with_extension("zip.part");
+        create_zip(std::fs::File::create(&partial)?, &root, Some(&prefix))?;
+        std::fs::rename(&partial, &target)?;
+        file_sha1(&target)
+    })
+    .await
+    .context("Archive task failed")?
+}
+
+/// Download and extract a dist into the CAS cache.
+///
+/// The archive is streamed to a temporary file and checked against the
+/// upstream `shasum`, when there is one, before anything is cached under
+/// [`cache_key`].
+async fn download_to_cache(client: &reqwest::Client, job: &DistJob, url: &str) -> Result<PathBuf> {
+    use futures::stream::StreamExt;
+    use std::io::Write;
+
+    let archive_type = ArchiveType::from_dist(job.dist_type.as_deref(), url)
+        .with_context(|| format!("Unsupported dist type for {}", job.package))?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .with_context(|| format!("Failed to download {url}"))?;
+
+    let temp = tempfile::tempdir()?;
+    let archive = temp.path().join("dist");
+    let mut file = std::fs::File::create(&archive)?;
+    let mut hasher = Sha1::new();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.with_context(|| format!("Failed to download {url}"))?;
+        hasher.update(&chunk);
+        file.write_all(&chunk)?;
+    }
+    file.flush()?;
+    drop(file);
+
+    if let Some(expected) = &job.shasum {
+        let actual = hex::encode(hasher.finalize());
+        if &actual != expected {
+            bail!(
+                "Checksum mismatch for {} {}: expected {expected}, got {actual}",
+                job.package,
+                job.version
+            );
+        }
+    }
+
+    let key = cache_key(job, url);
+    tokio::task::spawn_blocking(move || -> Result<PathBuf> {
+        let extracted = temp.path().join("contents");
+        Extractor::new()
+            .strip_root()
+            .extract_as(&archive, archive_type, &extracted)?;
+        cas_cache::store_in_cache(&key, &extracted)
+    })
+    .await
+    .context("Extraction task failed")?
+}
+
+/// CAS cache key for a dist's contents.
+///
+/// Dists with an upstream `shasum` are keyed on it, so an entry found under
+/// that key was checked when it was stored. Entries an install caches under
+/// the URL only serve dists without one.
+fn cache_key(job: &DistJob, url: &str) -> String {
+    job.shasum
+        .as_ref()
+        .map_or_else(|| url.to_string(), |shasum| format!("sha1:{shasum}"))
+}
+
+/// Hex SHA-1 of a file.
+fn file_sha1(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha1::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> BuildConfig {
+        BuildConfig::from_value(sonic_rs::from_str(json).unwrap(), None).unwrap()
+    }
+
+    #[test]
+    fn config_defaults() {
+        let config = config(
+            r#"{"homepage": "https://packages.example.com/mirror/", "require": {"PSR/Log": "^3.0"}}"#,
+        );
+        assert_eq!(config.base_url, "https://packages.example.com/mirror");
+        assert_eq!(config.archive_url, "https://packages.example.com/mirror");
+        assert_eq!(config.archive_dir, "dist");
+        assert_eq!(config.require["psr/log"], "^3.0");
+        assert!(!config.require_dependencies);
+        assert_eq!(metadata_url(&config.base_url), "/mirror/p2/%package%.json");
+
+        let missing = BuildConfig::from_value(
+            sonic_rs::from_str(r#"{"require": {"psr/log": "^3.0"}}"#).unwrap(),
+            None,
+        );
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn dist_paths_follow_satis_layout() {
+        assert_eq!(
+            dist_path("dist", "psr/log", "3.0.2", Some("f16e1d5")),
+            "dist/psr/log/psr-log-3.0.2-f16e1d5.zip"
+        );
+        assert_eq!(
+            dist_path("archives", "acme/app", "dev-feature/x", None),
+            "archives/acme/app/acme-app-dev-feature-x.zip"
+        );
+    }
+
+    #[test]
+    fn dist_jobs_skip_dev_versions_when_asked() {
+        let config = config(
+            r#"{"homepage": "https://example.com", "require": {"psr/log": "*"}, "archive": {"skip-dev": true}}"#,
+        );
+        let tagged: Value = sonic_rs::from_str(
+            r#"{"version": "3.0.2", "dist": {"type": "zip", "url": "https://example.org/log.zip", "reference": "abc"}}"#,
+        )
+        .unwrap();
+        let dev: Value = sonic_rs::from_str(
+            r#"{"version": "dev-main", "dist": {"type": "zip", "url": "https://example.org/main.zip"}}"#,
+        )
+        .unwrap();
+
+        let job = dist_job(&config, "psr/log", &tagged).unwrap();
+        assert_eq!(job.path, "dist/psr/log/psr-log-3.0.2-abc.zip");
+        assert!(dist_job(&config, "psr/log", &dev).is_none());
+    }
+
+    #[test]
+    fn rewritten_dist_keeps_reference() {
+        let mut version: Value = sonic_rs::from_str(
+            r#"{"version": "3.0.2", "dist": {"type": "tar", "url": "https://example.org/log.tar", "reference": "abc"}}"#,
+        )
+        .unwrap();
+        rewrite_dist(
+            &mut version,
+            "https://example.com/dist/psr/log/x.zip",
+            "0123",
+        );
+        assert_eq!(version["dist"]["type"].as_str(), Some("zip"));
+        assert_eq!(
+            version["dist"]["url"].as_str(),
+            Some("https://example.com/dist/psr/log/x.zip")
+        );
+        assert_eq!(version["dist"]["reference"].as_str(), Some("abc"));
+        assert_eq!(version["dist"]["shasum"].as_str(), Some("0123"));
+    }
+
+    #[tokio::test]
+    async fn download_rejects_checksum_mismatch() {
+        use wiremock::matchers::path;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(path("/log.zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"PK\x05\x06".to_vec()))
+            .mount(&server)
+            .await;
+
+        let job = DistJob {
+            package: "psr/log".to_string(),
+            version: "3.0.2".to_string(),
+            source: format!("{}/log.zip", server.uri()),
+            dist_type: Some("zip".to_string()),
+            shasum: Some("0000000000000000000000000000000000000000".to_string()),
+            path: "dist/psr/log/psr-log-3.0.2.zip".to_string(),
+        };
+        let err = download_to_cache(&reqwest::Client::new(), &job, &job.source)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn unverified_cache_entries_are_not_archived() {
+        use wiremock::matchers::path;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(path("/log.zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"PK\x05\x06".to_vec()))
+            .mount(&server)
+            .await;
+
+        let job = DistJob {
+            package: "psr/log".to_string(),
+            version: "3.0.2".to_string(),
+            source: format!("{}/log.zip", server.uri()),
+            dist_type: Some("zip".to_string()),
+            shasum: Some("0000000000000000000000000000000000000000".to_string()),
+            path: "dist/psr/log/psr-log-3.0.2.zip".to_string(),
+        };
+        // Contents an install cached under the URL, never checked against the shasum
+        let contents = tempfile::tempdir().unwrap();
+        std::fs::write(contents.path().join("composer.json"), "{}").unwrap();
+        let cached = cas_cache::store_in_cache(&job.source, contents.path()).unwrap();
+
+        let output = tempfile::tempdir().unwrap();
+        let result = build_dist(
+            &reqwest::Client::new(),
+            &job,
+            &output.path().join(&job.path),
+        )
+        .await;
+        std::fs::remove_dir_all(cached).unwrap();
+
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"), "{err}");
+    }
+}
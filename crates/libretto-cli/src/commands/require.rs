@@ -1,5 +1,6 @@
 //! Require command implementation.
 
+use crate::output::out;
 use anyhow::Result;
 use clap::Args;
 use sonic_rs::JsonValueMutTrait;
@@ -110,7 +111,7 @@ pub async fn run(args: RequireArgs) -> Result<()> {
 
                 let colors = crate::output::colors_enabled();
                 if colors {
-                    println!(
+                    out!(
                         "  {} {} {} as {}",
                         "+".green(),
                         name.green(),
@@ -118,7 +119,7 @@ pub async fn run(args: RequireArgs) -> Result<()> {
                         dep_label
                     );
                 } else {
-                    println!("  + {name} {save_constraint} as {dep_label}");
+                    out!("  + {name} {save_constraint} as {dep_label}");
                 }
 
                 resolved.push((name, save_constraint));
@@ -139,7 +140,7 @@ pub async fn run(args: RequireArgs) -> Result<()> {
     }
 
     if args.dry_run {
-        println!();
+        out!();
         warning("Dry run - no changes made");
         return Ok(());
     }
@@ -185,7 +186,7 @@ pub async fn run(args: RequireArgs) -> Result<()> {
 
     // Run update unless --no-update
     if !args.no_update {
-        println!();
+        out!();
         info("Running update to install new packages...");
 
         let update_args = crate::commands::update::UpdateArgs {
@@ -198,6 +199,8 @@ pub async fn run(args: RequireArgs) -> Result<()> {
             lock: false,
             audit: false,
             fail_on_audit: false,
+            no_security_blocking: false,
+            download: crate::commands::install::DownloadArgs::default(),
         };
 
         crate::commands::update::run(update_args).await?;
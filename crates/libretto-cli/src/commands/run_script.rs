@@ -1,11 +1,14 @@
 //! Run-script command - execute scripts from composer.json.
+//!
+//! Scripts also run as top-level subcommands (`libretto test -- --filter Foo`):
+//! a subcommand Libretto does not know is looked up among the project's
+//! scripts and their `scripts-aliases`.
 
-use anyhow::{Context, Result, bail};
+use crate::scripts::{ScriptConfig, ScriptEvent, ScriptExecutor, process_timeout};
+use anyhow::{Context, Result};
 use clap::Args;
 use sonic_rs::{JsonContainerTrait, JsonValueTrait};
-use std::collections::HashMap;
-use std::process::Stdio;
-use std::time::{Duration, Instant};
+use std::process::ExitCode;
 
 /// Arguments for the run-script command
 #[derive(Args, Debug, Clone)]
@@ -22,9 +25,9 @@ pub struct RunScriptArgs {
     #[arg(short = 'l', long)]
     pub list: bool,
 
-    /// Set script timeout in seconds (0 for no timeout)
-    #[arg(long, default_value = "300")]
-    pub timeout: u64,
+    /// Set script timeout in seconds (0 for no timeout); defaults to the process timeout from the environment or config
+    #[arg(long)]
+    pub timeout: Option<u64>,
 
     /// Run in dev mode (includes dev dependencies in path)
     #[arg(long)]
@@ -36,236 +39,174 @@ pub struct RunScriptArgs {
 }
 
 /// Run the run-script command
-pub async fn run(args: RunScriptArgs) -> Result<()> {
-    use crate::output::{error, header, info, success};
-
-    let composer_path = std::env::current_dir()?.join("composer.json");
-    if !composer_path.exists() {
-        anyhow::bail!("composer.json not found in current directory");
-    }
-
-    let composer_content = std::fs::read_to_string(&composer_path)?;
-    let composer: sonic_rs::Value = sonic_rs::from_str(&composer_content)?;
+pub async fn run(args: RunScriptArgs) -> Result<ExitCode> {
+    use crate::output::header;
 
-    // Get scripts section
-    let scripts = composer.get("scripts").and_then(|s| s.as_object());
+    let composer = read_composer_json()?.context("composer.json not found in current directory")?;
 
     // Handle list mode
-    if args.list || args.script.is_none() {
+    let Some(script_name) = args.script.as_deref().filter(|_| !args.list) else {
         header("Available scripts");
-        return list_scripts(&composer);
-    }
-
-    let script_name = args.script.as_ref().unwrap();
-
-    // Check if script exists
-    let script = scripts
-        .and_then(|s| s.get(script_name))
-        .context(format!("Script '{script_name}' not found"))?;
+        list_scripts(&composer);
+        return Ok(ExitCode::SUCCESS);
+    };
 
     header(&format!("Running script: {script_name}"));
+    run_named(
+        &composer,
+        script_name,
+        args.args.clone(),
+        args.timeout,
+        !args.no_dev,
+    )
+}
 
-    // Collect commands to run
-    let commands: Vec<String> = if let Some(cmd) = script.as_str() {
-        vec![cmd.to_string()]
-    } else if let Some(arr) = script.as_array() {
-        arr.iter()
-            .filter_map(|v| v.as_str().map(String::from))
-            .collect()
-    } else {
-        anyhow::bail!("Invalid script format for '{script_name}'")
+/// Run a script invoked as a top-level subcommand.
+///
+/// `argv` is the subcommand name followed by its arguments; a `--`
+/// separating Libretto's arguments from the script's is dropped.
+pub async fn run_external(argv: &[String]) -> Result<ExitCode> {
+    let Some((name, rest)) = argv.split_first() else {
+        return Ok(ExitCode::SUCCESS);
     };
 
-    // Set up environment
-    let mut env: HashMap<String, String> = std::env::vars().collect();
-
-    // Add vendor/bin to PATH
-    let vendor_bin = std::env::current_dir()?.join("vendor").join("bin");
-    if vendor_bin.exists() {
-        let path = env.get("PATH").cloned().unwrap_or_default();
-        let new_path = format!(
-            "{}{}{}",
-            vendor_bin.display(),
-            if cfg!(windows) { ";" } else { ":" },
-            path
-        );
-        env.insert("PATH".to_string(), new_path);
-    }
-
-    // Add COMPOSER_* environment variables
-    env.insert(
-        "COMPOSER_BINARY".to_string(),
-        std::env::current_exe()?.to_string_lossy().to_string(),
-    );
-    env.insert(
-        "COMPOSER_DEV_MODE".to_string(),
-        if args.dev { "1" } else { "0" }.to_string(),
-    );
-
-    // Run each command
-    let mut success_count = 0;
-    let mut failed = false;
-
-    for cmd in &commands {
-        // Check for @reference syntax
-        if let Some(ref_script) = cmd.strip_prefix('@') {
-            // Run referenced script
-            let ref_args = RunScriptArgs {
-                script: Some(ref_script.to_string()),
-                args: args.args.clone(),
-                list: false,
-                timeout: args.timeout,
-                dev: args.dev,
-                no_dev: args.no_dev,
-            };
-            if let Err(e) = Box::pin(run(ref_args)).await {
-                error(&format!("Referenced script '{ref_script}' failed: {e}"));
-                failed = true;
-                break;
-            }
-            success_count += 1;
-            continue;
-        }
+    let composer = read_composer_json()?;
+    let known = composer.as_ref().is_some_and(|c| {
+        ScriptExecutor::new(c, ScriptConfig::default())
+            .resolve(name)
+            .is_some()
+    });
+    let Some(composer) = composer.filter(|_| known) else {
+        use clap::CommandFactory;
+        // Parse again with only the built-in commands, so a typo gets clap's suggestions
+        let cli = super::Cli::command();
+        let error = clap::Command::new(cli.get_name().to_owned())
+            .args(cli.get_arguments().cloned())
+            .subcommands(cli.get_subcommands().cloned())
+            .subcommand_required(true)
+            .try_get_matches_from(std::env::args_os())
+            .err()
+            .unwrap_or_else(|| {
+                cli.clone().error(
+                    clap::error::ErrorKind::InvalidSubcommand,
+                    format!("unrecognized subcommand '{name}'"),
+                )
+            });
+        error.exit();
+    };
 
-        // Check for @php or @composer
-        let actual_cmd = if cmd.starts_with("@php ") {
-            cmd.replacen("@php ", "php ", 1)
-        } else if cmd.starts_with("@composer ") {
-            cmd.replacen(
-                "@composer ",
-                &format!("{} ", std::env::current_exe()?.display()),
-                1,
-            )
-        } else if cmd.starts_with("@putenv ") {
-            // Handle @putenv directive
-            let putenv = cmd.strip_prefix("@putenv ").unwrap();
-            if let Some((key, value)) = putenv.split_once('=') {
-                env.insert(key.to_string(), value.to_string());
-            }
-            continue;
-        } else {
-            cmd.clone()
-        };
+    let mut forwarded = rest.to_vec();
+    if let Some(separator) = forwarded.iter().position(|arg| arg == "--") {
+        forwarded.remove(separator);
+    }
 
-        // Append script arguments
-        let full_cmd = if args.args.is_empty() {
-            actual_cmd
-        } else {
-            format!("{} {}", actual_cmd, args.args.join(" "))
-        };
+    run_named(&composer, name, forwarded, None, true)
+}
 
-        info(&format!("> {full_cmd}"));
-
-        // Execute command with timeout enforcement
-        let shell = if cfg!(windows) { "cmd" } else { "sh" };
-        let shell_arg = if cfg!(windows) { "/C" } else { "-c" };
-
-        let status = if args.timeout > 0 {
-            // Spawn process and enforce timeout
-            let mut child = std::process::Command::new(shell)
-                .arg(shell_arg)
-                .arg(&full_cmd)
-                .envs(&env)
-                .stdin(Stdio::inherit())
-                .stdout(Stdio::inherit())
-                .stderr(Stdio::inherit())
-                .spawn()
-                .context(format!("Failed to spawn: {full_cmd}"))?;
-
-            let timeout_duration = Duration::from_secs(args.timeout);
-            let start = Instant::now();
-
-            let mut timed_out = false;
-            let status = loop {
-                match child.try_wait() {
-                    Ok(Some(status)) => break Some(status),
-                    Ok(None) => {
-                        if start.elapsed() >= timeout_duration {
-                            let _ = child.kill();
-                            let _ = child.wait();
-                            error(&format!(
-                                "Script timed out after {}s: {}",
-                                args.timeout, full_cmd
-                            ));
-                            timed_out = true;
-                            break None;
-                        }
-                        std::thread::sleep(Duration::from_millis(100));
-                    }
-                    Err(e) => {
-                        bail!("Failed to wait for process: {e}");
-                    }
-                }
-            };
-
-            if timed_out {
-                failed = true;
-                break;
-            }
+/// Help section listing the project's scripts, for `libretto --help`.
+///
+/// Scripts shadowed by a built-in command and lifecycle event scripts are
+/// left out, since neither can be run as a subcommand.
+pub fn scripts_help(cli: &clap::Command) -> Option<String> {
+    let composer = read_composer_json().ok().flatten()?;
+    let executor = ScriptExecutor::new(&composer, ScriptConfig::default());
+
+    let mut names: Vec<&str> = executor
+        .available_scripts()
+        .into_iter()
+        .filter(|name| ScriptEvent::from_str(name).is_none())
+        .filter(|name| cli.find_subcommand(name).is_none())
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+    names.sort_unstable();
 
-            status.unwrap()
-        } else {
-            // No timeout, wait indefinitely
-            std::process::Command::new(shell)
-                .arg(shell_arg)
-                .arg(&full_cmd)
-                .envs(&env)
-                .status()
-                .context(format!("Failed to execute: {full_cmd}"))?
-        };
+    let styles = super::get_styles();
+    let (heading, literal) = (styles.get_header(), styles.get_literal());
+    let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
 
-        if !status.success() {
-            error(&format!(
-                "Command failed with exit code {}",
-                status.code().unwrap_or(-1)
-            ));
-            failed = true;
-            break;
+    let mut help = format!("{heading}Scripts:{heading:#}");
+    for name in names {
+        let mut about = executor.description(name).map_or_else(
+            || format!("Runs the {name} script as defined in composer.json"),
+            String::from,
+        );
+        let aliases = executor.aliases_of(name);
+        if !aliases.is_empty() {
+            about.push(' ');
+            about.push_str(&format!("[aliases: {}]", aliases.join(", ")));
         }
-
-        success_count += 1;
+        help.push_str(&format!(
+            "\n  {literal}{name}{literal:#}{}  {about}",
+            " ".repeat(width - name.len())
+        ));
     }
+    Some(help)
+}
 
-    if failed {
-        anyhow::bail!("Script '{script_name}' failed");
+/// Read composer.json from the current directory, if there is one.
+fn read_composer_json() -> Result<Option<sonic_rs::Value>> {
+    let composer_path = std::env::current_dir()?.join("composer.json");
+    if !composer_path.exists() {
+        return Ok(None);
     }
 
-    success(&format!(
-        "Script '{script_name}' completed ({success_count} command(s) executed)"
-    ));
+    let composer_content = std::fs::read_to_string(&composer_path)?;
+    Ok(Some(sonic_rs::from_str(&composer_content)?))
+}
 
-    Ok(())
+/// Run a script by name or alias, exiting with its last command's exit code.
+fn run_named(
+    composer: &sonic_rs::Value,
+    name: &str,
+    additional_args: Vec<String>,
+    timeout: Option<u64>,
+    dev_mode: bool,
+) -> Result<ExitCode> {
+    use crate::output::{error, info, success};
+
+    let config = ScriptConfig {
+        dev_mode,
+        timeout: timeout.unwrap_or_else(|| process_timeout(composer)),
+        additional_args,
+        echo: true,
+        ..Default::default()
+    };
+    let mut executor = ScriptExecutor::new(composer, config);
+    let script_name = executor
+        .resolve(name)
+        .context(format!("Script '{name}' not found"))?
+        .to_string();
+
+    match executor.run_script(&script_name)? {
+        None => {
+            info(&format!("Script '{script_name}' has no commands"));
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(result) if result.success => {
+            success(&format!(
+                "Script '{script_name}' completed ({} command(s) executed)",
+                result.commands_executed
+            ));
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(result) => {
+            let code = result.exit_code.unwrap_or(1);
+            error(&format!("Command failed with exit code {code}"));
+            Ok(ExitCode::from(u8::try_from(code).unwrap_or(1)))
+        }
+    }
 }
 
-fn list_scripts(composer: &sonic_rs::Value) -> Result<()> {
+fn list_scripts(composer: &sonic_rs::Value) {
     use crate::output::table::Table;
 
-    let scripts = composer.get("scripts").and_then(|s| s.as_object());
-
-    if scripts.is_none() {
+    let Some(scripts) = composer.get("scripts").and_then(|s| s.as_object()) else {
         crate::output::info("No scripts defined in composer.json");
-        return Ok(());
-    }
-
-    let scripts = scripts.unwrap();
-
-    // Separate event scripts from custom scripts
-    let event_scripts = [
-        "pre-install-cmd",
-        "post-install-cmd",
-        "pre-update-cmd",
-        "post-update-cmd",
-        "pre-status-cmd",
-        "post-status-cmd",
-        "pre-archive-cmd",
-        "post-archive-cmd",
-        "pre-autoload-dump",
-        "post-autoload-dump",
-        "post-root-package-install",
-        "post-create-project-cmd",
-        "pre-operations-exec",
-        "pre-pool-create",
-    ];
+        return;
+    };
+    let executor = ScriptExecutor::new(composer, ScriptConfig::default());
 
     let mut custom: Vec<(String, String)> = Vec::new();
     let mut events: Vec<(String, String)> = Vec::new();
@@ -287,47 +228,35 @@ fn list_scripts(composer: &sonic_rs::Value) -> Result<()> {
             continue;
         };
 
-        // Skip description fields
-        if name.ends_with("-descriptions") {
-            continue;
-        }
-
         let name_str = name.to_string();
-        if event_scripts.contains(&name_str.as_str()) {
+        if ScriptEvent::from_str(&name_str).is_some() {
             events.push((name_str, cmd_str));
         } else {
             custom.push((name_str, cmd_str));
         }
     }
 
-    // Get descriptions if available
-    let descriptions = composer
-        .get("scripts-descriptions")
-        .and_then(|d| d.as_object());
-
     if !custom.is_empty() {
-        println!("Custom scripts:");
+        crate::output::line("Custom scripts:");
 
         let mut table = Table::new();
-        table.headers(["Script", "Description"]);
+        table.headers(["Script", "Description", "Aliases"]);
 
         custom.sort_by(|a, b| a.0.cmp(&b.0));
 
         for (name, cmd) in &custom {
-            let desc = descriptions
-                .and_then(|d| d.get(&name))
-                .and_then(|d| d.as_str())
-                .unwrap_or(cmd);
+            let desc = executor.description(name).unwrap_or(cmd);
+            let aliases = executor.aliases_of(name).join(", ");
 
-            table.row([name.as_str(), desc]);
+            table.row([name.as_str(), desc, aliases.as_str()]);
         }
 
         table.print();
     }
 
     if !events.is_empty() {
-        println!();
-        println!("Event scripts:");
+        crate::output::line("");
+        crate::output::line("Event scripts:");
 
         let mut table = Table::new();
         table.headers(["Event", "Commands"]);
@@ -344,6 +273,4 @@ fn list_scripts(composer: &sonic_rs::Value) -> Result<()> {
     if custom.is_empty() && events.is_empty() {
         crate::output::info("No scripts defined");
     }
-
-    Ok(())
 }
@@ -0,0 +1,127 @@
+//! SBOM command - generate a software bill of materials from composer.lock.
+
+use crate::output::success;
+use crate::security::{audit_configs, ignore_policy};
+use anyhow::{Context, Result};
+use clap::Args;
+use libretto_audit::{Auditor, RootComponent, Sbom, SbomFormat};
+use libretto_core::PackageId;
+use libretto_lockfile::ComposerLock;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use std::path::PathBuf;
+
+/// Arguments for the sbom command
+#[derive(Args, Debug, Clone)]
+pub struct SbomArgs {
+    /// Document format
+    #[arg(
+        short = 'f',
+        long,
+        default_value = "cyclonedx-json",
+        value_parser = ["cyclonedx-json", "cyclonedx-xml", "spdx-json"]
+    )]
+    pub format: String,
+
+    /// Write the SBOM to a file instead of stdout
+    #[arg(short = 'o', long)]
+    pub output: Option<PathBuf>,
+
+    /// Leave out dev dependencies
+    #[arg(long)]
+    pub no_dev: bool,
+
+    /// Audit the locked packages and embed the findings as VEX entries
+    #[arg(long)]
+    pub vex: bool,
+}
+
+/// Run the sbom command
+pub async fn run(args: SbomArgs) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let lock_path = cwd.join("composer.lock");
+    if !lock_path.exists() {
+        anyhow::bail!("composer.lock not found - run 'libretto install' first");
+    }
+
+    let lock: ComposerLock = sonic_rs::from_str(
+        &std::fs::read_to_string(&lock_path).context("Failed to read composer.lock")?,
+    )
+    .context("Failed to parse composer.lock")?;
+
+    let composer_path = cwd.join("composer.json");
+    let composer: Option<sonic_rs::Value> = if composer_path.exists() {
+        Some(sonic_rs::from_str(&std::fs::read_to_string(
+            &composer_path,
+        )?)?)
+    } else {
+        None
+    };
+
+    let report = if args.vex {
+        let packages: Vec<(PackageId, String)> = lock
+            .packages
+            .iter()
+            .chain(lock.packages_dev.iter().filter(|_| !args.no_dev))
+            .filter_map(|pkg| PackageId::parse(&pkg.name).map(|id| (id, pkg.version.clone())))
+            .collect();
+        let auditor =
+            Auditor::new()?.with_advisory_database(super::audit_db::advisory_database(false)?);
+        let mut report = auditor.audit(&packages).await?;
+        if let Some(ref composer) = composer {
+            super::audit::apply_ignores(&mut report, &ignore_policy(&audit_configs(composer)));
+        }
+        Some(report)
+    } else {
+        None
+    };
+
+    let mut sbom = Sbom::new(&lock);
+    if let Some(ref composer) = composer {
+        sbom = sbom.with_root(root_component(composer));
+    }
+    if args.no_dev {
+        sbom = sbom.without_dev();
+    }
+    if let Some(ref report) = report {
+        sbom = sbom.with_vulnerabilities(report);
+    }
+
+    let format: SbomFormat = args.format.parse().map_err(anyhow::Error::msg)?;
+    let document = sbom.render(format)?;
+
+    match args.output {
+        Some(path) => {
+            std::fs::write(&path, document)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            success(&format!("Wrote {} SBOM to {}", args.format, path.display()));
+        }
+        None => println!("{}", document.trim_end()),
+    }
+
+    Ok(())
+}
+
+/// Describe the root package from composer.json.
+fn root_component(composer: &sonic_rs::Value) -> RootComponent {
+    let keys = |field: &str| -> Vec<String> {
+        composer
+            .get(field)
+            .and_then(|v| v.as_object())
+            .map(|require| require.iter().map(|(name, _)| name.to_string()).collect())
+            .unwrap_or_default()
+    };
+
+    RootComponent {
+        name: composer
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("__root__")
+            .to_string(),
+        version: composer
+            .get("version")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        require: keys("require"),
+        require_dev: keys("require-dev"),
+    }
+}
@@ -133,7 +133,7 @@ fn output_full(results: &[libretto_repository::PackageSearchResult]) -> Result<(
                 description,
             );
         } else {
-            println!("{}{}{}{}", result.name, padding, warning, description,);
+            println!("{}{}{}{}", result.name, padding, warning, description);
         }
     }
 
@@ -1,12 +1,19 @@
 //! Update command implementation.
 
-use crate::fetcher::Fetcher;
-use crate::output::{info, success};
-use crate::scripts::{ScriptConfig, run_post_install_scripts, run_pre_install_scripts};
+use crate::fetcher::{Fetcher, minimum_stability, resolution_cache};
+use crate::output::events::{self, Event};
+use crate::output::{info, out, success};
+use crate::plugins::PluginRuntime;
+use crate::scripts::{
+    ScriptConfig, process_timeout, run_post_install_scripts, run_pre_install_scripts,
+};
+use crate::security::SecurityPolicy;
+use crate::workspace::Workspace;
 use anyhow::Result;
 use clap::Args;
+use libretto_plugin_system::Hook;
 use libretto_resolver::turbo::{TurboConfig, TurboResolver};
-use libretto_resolver::{ComposerConstraint, Dependency, PackageName, ResolutionMode, Stability};
+use libretto_resolver::{ComposerConstraint, Dependency, PackageName, ResolutionMode};
 use owo_colors::OwoColorize;
 use sonic_rs::{JsonContainerTrait, JsonValueTrait};
 use std::collections::{HashMap, HashSet};
@@ -50,6 +57,13 @@ pub struct UpdateArgs {
     /// Fail update if security vulnerabilities are found
     #[arg(long)]
     pub fail_on_audit: bool,
+
+    /// Don't block versions with security advisories or abandoned packages
+    #[arg(long)]
+    pub no_security_blocking: bool,
+
+    #[command(flatten)]
+    pub download: crate::commands::install::DownloadArgs,
 }
 
 /// A categorized lock file change.
@@ -81,19 +95,19 @@ impl LockOp {
         match self {
             Self::Install { name, version } => {
                 if colors {
-                    println!(
+                    out!(
                         "    {} {} {}",
                         "+".green().bold(),
                         name.green(),
                         format!("({version})").dimmed(),
                     );
                 } else {
-                    println!("    + {name} ({version})");
+                    out!("    + {name} ({version})");
                 }
             }
             Self::Upgrade { name, from, to } => {
                 if colors {
-                    println!(
+                    out!(
                         "    {} {} {} {} {}",
                         "^".cyan().bold(),
                         name.cyan(),
@@ -102,12 +116,12 @@ impl LockOp {
                         to.green(),
                     );
                 } else {
-                    println!("    ^ {name} {from} -> {to}");
+                    out!("    ^ {name} {from} -> {to}");
                 }
             }
             Self::Downgrade { name, from, to } => {
                 if colors {
-                    println!(
+                    out!(
                         "    {} {} {} {} {}",
                         "v".yellow().bold(),
                         name.yellow(),
@@ -116,19 +130,19 @@ impl LockOp {
                         to.yellow(),
                     );
                 } else {
-                    println!("    v {name} {from} -> {to}");
+                    out!("    v {name} {from} -> {to}");
                 }
             }
             Self::Remove { name, version } => {
                 if colors {
-                    println!(
+                    out!(
                         "    {} {} {}",
                         "-".red().bold(),
                         name.red(),
                         format!("({version})").dimmed(),
                     );
                 } else {
-                    println!("    - {name} ({version})");
+                    out!("    - {name} ({version})");
                 }
             }
         }
@@ -157,22 +171,52 @@ pub async fn run(args: UpdateArgs) -> Result<()> {
         warning("Dry run mode - no changes will be made");
     }
 
+    if let Some(workspace) = Workspace::load(&cwd, &composer)? {
+        if !args.packages.is_empty() {
+            anyhow::bail!(
+                "Partial updates are not supported in a workspace; run `libretto update` without package names"
+            );
+        }
+        let install_args = crate::commands::install::InstallArgs {
+            dry_run: args.dry_run,
+            prefer_lowest: args.prefer_lowest,
+            ..install_args(&args)
+        };
+        return crate::commands::install::install_workspace(
+            &workspace,
+            &install_args,
+            true,
+            args.lock,
+        )
+        .await;
+    }
+
+    let vendor_dir = cwd.join("vendor");
+    let mut plugins = if args.dry_run {
+        PluginRuntime::disabled(&cwd, &vendor_dir, "update")
+    } else {
+        PluginRuntime::load(&composer, &cwd, &vendor_dir, "update", !args.no_dev).await
+    };
+    plugins.dispatch(Hook::PreUpdateCmd).await;
+
     // Set up script configuration
     let script_config = ScriptConfig {
         working_dir: cwd.clone(),
         dev_mode: !args.no_dev,
+        timeout: process_timeout(&composer),
         ..Default::default()
     };
 
     // Run pre-update-cmd scripts
     if !args.dry_run
         && let Some(result) = run_pre_install_scripts(&composer, &script_config, true)?
-            && !result.success {
-                warning(&format!(
-                    "Pre-update script warning: {}",
-                    result.error.unwrap_or_default()
-                ));
-            }
+        && !result.success
+    {
+        warning(&format!(
+            "Pre-update script warning: {}",
+            result.error.unwrap_or_default()
+        ));
+    }
 
     // Collect current locked versions
     let mut current_versions: HashMap<String, String> = HashMap::new();
@@ -192,11 +236,8 @@ pub async fn run(args: UpdateArgs) -> Result<()> {
     }
 
     // Configure resolver
-    let min_stability = composer
-        .get("minimum-stability")
-        .and_then(|v| v.as_str())
-        .and_then(parse_stability)
-        .unwrap_or(Stability::Stable);
+    let security = SecurityPolicy::load(&composer, args.no_security_blocking);
+    let min_stability = minimum_stability(&composer);
 
     let config = TurboConfig {
         max_concurrent: 64,
@@ -208,6 +249,7 @@ pub async fn run(args: UpdateArgs) -> Result<()> {
         },
         min_stability,
         include_dev: !args.no_dev,
+        block_abandoned: security.block_abandoned,
     };
 
     // Parse dependencies
@@ -229,33 +271,58 @@ pub async fn run(args: UpdateArgs) -> Result<()> {
     }
 
     if !args.no_dev
-        && let Some(require_dev) = composer.get("require-dev").and_then(|v| v.as_object()) {
-            for (name, constraint) in require_dev {
-                if is_platform_package(name) {
-                    continue;
-                }
-                if let (Some(n), Some(c)) = (
-                    PackageName::parse(name),
-                    ComposerConstraint::parse(constraint.as_str().unwrap_or("*")),
-                ) {
-                    dev_deps.push(Dependency::new(n, c));
-                }
+        && let Some(require_dev) = composer.get("require-dev").and_then(|v| v.as_object())
+    {
+        for (name, constraint) in require_dev {
+            if is_platform_package(name) {
+                continue;
+            }
+            if let (Some(n), Some(c)) = (
+                PackageName::parse(name),
+                ComposerConstraint::parse(constraint.as_str().unwrap_or("*")),
+            ) {
+                dev_deps.push(Dependency::new(n, c));
             }
         }
+    }
 
     // Resolve dependencies
     let fetcher = Arc::new(
         Fetcher::new_with_composer_repositories(&composer)
             .map_err(|e| anyhow::anyhow!("Failed to create fetcher: {e}"))?,
     );
-    let resolver = TurboResolver::new(fetcher.clone(), config);
+    let mut resolver = security.apply(TurboResolver::new(fetcher.clone(), config));
+    if let Some(cache) = resolution_cache(&composer) {
+        resolver = resolver.with_cache(cache);
+    }
+
+    plugins.dispatch(Hook::PreDependenciesSolving).await;
 
     let spinner = Spinner::new("Resolving dependencies...");
+    let resolve_start = std::time::Instant::now();
+    events::emit(Event::ResolutionStart {
+        requirements: root_deps.len(),
+        dev_requirements: dev_deps.len(),
+    });
     let resolution = resolver
         .resolve(&root_deps, &dev_deps)
         .await
         .map_err(|e| anyhow::anyhow!("Resolution failed: {e}"))?;
     spinner.finish_and_clear();
+    events::emit(Event::ResolutionFinish {
+        packages: resolution.packages.len(),
+        duration_ms: events::millis(resolve_start.elapsed()),
+        stats: resolver.stats().into(),
+    });
+
+    let resolved: Vec<(String, String)> = resolution
+        .packages
+        .iter()
+        .map(|p| (p.name.as_str().to_string(), p.version.to_string()))
+        .collect();
+    plugins
+        .dispatch_packages(Hook::PostDependenciesSolving, &resolved)
+        .await;
 
     // Build set of resolved package names for removal detection
     let resolved_names: HashSet<String> = resolution
@@ -339,7 +406,7 @@ pub async fn run(args: UpdateArgs) -> Result<()> {
         .count();
 
     if ops.is_empty() {
-        println!();
+        out!();
         success("Nothing to modify in lock file");
     } else {
         // Summary line
@@ -369,22 +436,22 @@ pub async fn run(args: UpdateArgs) -> Result<()> {
             ));
         }
 
-        println!();
+        out!();
         if crate::output::colors_enabled() {
-            println!(
+            out!(
                 "  {} {}",
                 "Lock file operations:".white().bold(),
                 parts.join(", ").dimmed(),
             );
         } else {
-            println!("  Lock file operations: {}", parts.join(", "));
+            out!("  Lock file operations: {}", parts.join(", "));
         }
-        println!();
+        out!();
 
         for op in &ops {
             op.print();
         }
-        println!();
+        out!();
     }
 
     if args.dry_run {
@@ -400,40 +467,25 @@ pub async fn run(args: UpdateArgs) -> Result<()> {
 
     // Install packages
     if !args.lock {
-        let install_args = crate::commands::install::InstallArgs {
-            no_dev: args.no_dev,
-            prefer_dist: true,
-            prefer_source: false,
-            dry_run: false,
-            ignore_platform_reqs: false,
-            ignore_platform_req: vec![],
-            optimize_autoloader: false,
-            classmap_authoritative: false,
-            apcu_autoloader: false,
-            no_scripts: false,
-            prefer_lowest: false,
-            prefer_stable: true,
-            minimum_stability: None,
-            no_progress: false,
-            concurrency: 64,
-            audit: false,
-            fail_on_audit: false,
-            verify_checksums: false,
-            php_version: None,
-            no_php_check: false,
-        };
+        let install_args = install_args(&args);
+
+        crate::commands::install::run_for_update(install_args).await?;
 
-        crate::commands::install::run(install_args).await?;
+        // Activate plugins installed by this update
+        plugins.reload(&composer).await;
     }
 
+    plugins.dispatch(Hook::PostUpdateCmd).await;
+
     // Run post-update-cmd scripts
     if let Some(result) = run_post_install_scripts(&composer, &script_config, true)?
-        && !result.success {
-            warning(&format!(
-                "Post-update script warning: {}",
-                result.error.unwrap_or_default()
-            ));
-        }
+        && !result.success
+    {
+        warning(&format!(
+            "Post-update script warning: {}",
+            result.error.unwrap_or_default()
+        ));
+    }
 
     // Show funding info (like Composer)
     let funded_count = resolution
@@ -447,7 +499,7 @@ pub async fn run(args: UpdateArgs) -> Result<()> {
         .count();
     if funded_count > 0 {
         if crate::output::colors_enabled() {
-            println!(
+            out!(
                 "{} {}",
                 format!(
                     "{funded_count} package{} you are using {} looking for funding.",
@@ -458,7 +510,7 @@ pub async fn run(args: UpdateArgs) -> Result<()> {
                 "Use the `libretto fund` command to find out more.".dimmed(),
             );
         } else {
-            println!(
+            out!(
                 "{funded_count} package{} you are using {} looking for funding.\n\
                  Use the `libretto fund` command to find out more.",
                 if funded_count == 1 { "" } else { "s" },
@@ -468,16 +520,49 @@ pub async fn run(args: UpdateArgs) -> Result<()> {
     }
 
     // Always run security audit (like Composer does by default)
-    run_security_audit(&lock_path, args.fail_on_audit).await?;
+    let policy = crate::security::ignore_policy(&crate::security::audit_configs(&composer));
+    run_security_audit(&lock_path, args.fail_on_audit, &policy).await?;
 
     Ok(())
 }
 
+/// Arguments for the install step that follows the lock update.
+fn install_args(args: &UpdateArgs) -> crate::commands::install::InstallArgs {
+    crate::commands::install::InstallArgs {
+        no_dev: args.no_dev,
+        prefer_dist: true,
+        prefer_source: false,
+        dry_run: false,
+        ignore_platform_reqs: false,
+        ignore_platform_req: vec![],
+        optimize_autoloader: false,
+        classmap_authoritative: false,
+        apcu_autoloader: false,
+        no_scripts: false,
+        prefer_lowest: false,
+        prefer_stable: true,
+        minimum_stability: None,
+        no_progress: false,
+        concurrency: 64,
+        audit: false,
+        fail_on_audit: false,
+        no_security_blocking: args.no_security_blocking,
+        verify_checksums: false,
+        strict_lock: false,
+        php_version: None,
+        no_php_check: false,
+        download: args.download.clone(),
+    }
+}
+
 /// Run security audit on packages in lock file.
-async fn run_security_audit(lock_path: &std::path::Path, fail_on_audit: bool) -> Result<()> {
+async fn run_security_audit(
+    lock_path: &std::path::Path,
+    fail_on_audit: bool,
+    policy: &libretto_audit::IgnorePolicy,
+) -> Result<()> {
     use libretto_audit::Auditor;
     use libretto_core::PackageId;
-    use semver::Version;
 
     if !lock_path.exists() {
         return Ok(());
@@ -488,22 +573,18 @@ async fn run_security_audit(lock_path: &std::path::Path, fail_on_audit: bool) ->
     let lock_content = std::fs::read_to_string(lock_path)?;
     let lock: sonic_rs::Value = sonic_rs::from_str(&lock_content)?;
 
-    let mut packages_to_audit: Vec<(PackageId, Version)> = Vec::new();
+    let mut packages_to_audit: Vec<(PackageId, String)> = Vec::new();
 
     for key in ["packages", "packages-dev"] {
         if let Some(pkgs) = lock.get(key).and_then(|v| v.as_array()) {
             for pkg in pkgs {
                 let name = pkg.get("name").and_then(|v| v.as_str()).unwrap_or("");
-                let version_str = pkg
-                    .get("version")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .trim_start_matches('v');
+                let version = pkg.get("version").and_then(|v| v.as_str()).unwrap_or("");
 
                 if let Some(id) = PackageId::parse(name)
-                    && let Ok(ver) = Version::parse(version_str)
+                    && !version.is_empty()
                 {
-                    packages_to_audit.push((id, ver));
+                    packages_to_audit.push((id, version.to_string()));
                 }
             }
         }
@@ -513,14 +594,24 @@ async fn run_security_audit(lock_path: &std::path::Path, fail_on_audit: bool) ->
         return Ok(());
     }
 
-    let auditor = Auditor::new().map_err(|e| anyhow::anyhow!("Failed to create auditor: {e}"))?;
-    let report = auditor
+    let auditor = Auditor::new()
+        .map_err(|e| anyhow::anyhow!("Failed to create auditor: {e}"))?
+        .with_advisory_database(crate::commands::audit_db::advisory_database(false)?);
+    let mut report = auditor
         .audit(&packages_to_audit)
         .await
         .map_err(|e| anyhow::anyhow!("Audit failed: {e}"))?;
+    crate::commands::audit::apply_ignores(&mut report, policy);
 
     if report.vulnerability_count() == 0 {
-        success("No security vulnerability advisories found");
+        if report.ignored.is_empty() {
+            success("No security vulnerability advisories found");
+        } else {
+            success(&format!(
+                "No security vulnerability advisories found ({} ignored)",
+                report.ignored.len()
+            ));
+        }
         return Ok(());
     }
 
@@ -540,7 +631,7 @@ async fn run_security_audit(lock_path: &std::path::Path, fail_on_audit: bool) ->
     let pkg_count = report.vulnerable_package_count();
 
     if colors {
-        println!(
+        out!(
             "  {} Found {} {} affecting {} {}: {}",
             "!".yellow().bold(),
             vuln_count,
@@ -558,7 +649,7 @@ async fn run_security_audit(lock_path: &std::path::Path, fail_on_audit: bool) ->
             sev_parts.join(", "),
         );
     } else {
-        println!(
+        out!(
             "  ! Found {} {} affecting {} {}: {}",
             vuln_count,
             if vuln_count == 1 {
@@ -577,13 +668,9 @@ async fn run_security_audit(lock_path: &std::path::Path, fail_on_audit: bool) ->
     }
 
     if colors {
-        println!("    {}", "Run `libretto audit` for details.".dimmed(),);
+        out!("    {}", "Run `libretto audit` for details.".dimmed());
     } else {
-        println!("    Run `libretto audit` for details.");
-    }
-
-    if fail_on_audit && report.has_critical() {
-        anyhow::bail!("Critical security vulnerabilities found.");
+        out!("    Run `libretto audit` for details.");
     }
 
     if fail_on_audit && !report.passes() {
@@ -593,17 +680,6 @@ async fn run_security_audit(lock_path: &std::path::Path, fail_on_audit: bool) ->
     Ok(())
 }
 
-fn parse_stability(s: &str) -> Option<Stability> {
-    match s.to_lowercase().as_str() {
-        "dev" => Some(Stability::Dev),
-        "alpha" => Some(Stability::Alpha),
-        "beta" => Some(Stability::Beta),
-        "rc" => Some(Stability::RC),
-        "stable" => Some(Stability::Stable),
-        _ => None,
-    }
-}
-
 fn is_platform_package(name: &str) -> bool {
     libretto_core::is_platform_package_name(name)
 }
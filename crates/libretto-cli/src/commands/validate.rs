@@ -1,6 +1,6 @@
 //! Validate command implementation.
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use clap::Args;
 use console::style;
 use libretto_core::from_json;
@@ -23,6 +23,10 @@ pub struct ValidateArgs {
     /// Don't validate require(-dev) versions
     #[arg(long)]
     pub no_check_version: bool,
+
+    /// Fail if composer.lock is out of date with composer.json
+    #[arg(long)]
+    pub check_lock: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -81,6 +85,33 @@ pub async fn run(args: ValidateArgs) -> Result<()> {
         }
     }
 
+    // Compare the lock with composer.json like `install --strict-lock`
+    let mut lock_stale = false;
+    if args.check_lock {
+        let lock_path = Path::new("composer.lock");
+        if lock_path.exists() {
+            match sonic_rs::from_str(&content)
+                .map_err(anyhow::Error::from)
+                .and_then(|composer| super::lock_generator::lock_drift(lock_path, &composer))
+            {
+                Ok(drift) if drift.is_current => {}
+                Ok(drift) => {
+                    lock_stale = true;
+                    errors.push(format!(
+                        "The lock file is not up to date with the latest changes in composer.json ({}); run `libretto update`",
+                        drift.summary()
+                    ));
+                }
+                Err(e) => {
+                    lock_stale = true;
+                    errors.push(format!("{e:#}"));
+                }
+            }
+        } else {
+            warnings.push("composer.lock not found".to_string());
+        }
+    }
+
     // Report results
     println!();
     if errors.is_empty() && warnings.is_empty() {
@@ -102,6 +133,9 @@ pub async fn run(args: ValidateArgs) -> Result<()> {
         }
     }
 
+    if lock_stale {
+        bail!("composer.lock is out of date with composer.json");
+    }
     Ok(())
 }
 
@@ -0,0 +1,162 @@
+//! Workspace command - inspect workspace members and run their scripts.
+
+use crate::scripts::{ScriptConfig, ScriptExecutor, process_timeout};
+use crate::workspace::Workspace;
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use sonic_rs::Value;
+use std::process::ExitCode;
+
+/// Arguments for the workspace command
+#[derive(Args, Debug, Clone)]
+pub struct WorkspaceArgs {
+    #[command(subcommand)]
+    pub action: WorkspaceAction,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum WorkspaceAction {
+    /// List the workspace members
+    List {
+        /// Output format (text, json)
+        #[arg(short = 'f', long, default_value = "text")]
+        format: String,
+    },
+
+    /// Run a script in every member that defines it
+    Run {
+        /// Script name to run
+        #[arg(required = true, value_name = "SCRIPT")]
+        script: String,
+
+        /// Arguments to pass to the script
+        #[arg(trailing_var_arg = true, value_name = "ARGS")]
+        args: Vec<String>,
+    },
+}
+
+/// Run the workspace command
+pub async fn run(args: WorkspaceArgs) -> Result<ExitCode> {
+    let cwd = std::env::current_dir()?;
+    let composer_path = cwd.join("composer.json");
+    let composer_content = std::fs::read_to_string(&composer_path)
+        .context("composer.json not found in current directory")?;
+    let composer: Value =
+        sonic_rs::from_str(&composer_content).context("Failed to parse composer.json")?;
+    let workspace = Workspace::load(&cwd, &composer)?
+        .context("composer.json declares no workspaces (extra.libretto.workspaces)")?;
+
+    match args.action {
+        WorkspaceAction::List { format } => {
+            list(&workspace, &cwd, &format)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        WorkspaceAction::Run { script, args } => run_everywhere(&workspace, &script, &args),
+    }
+}
+
+fn list(workspace: &Workspace, cwd: &std::path::Path, format: &str) -> Result<()> {
+    use crate::output::table::Table;
+
+    let relative = |dir: &std::path::Path| {
+        dir.strip_prefix(cwd)
+            .unwrap_or(dir)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+
+    if format == "json" {
+        let members: Vec<Value> = workspace
+            .members
+            .iter()
+            .map(|member| {
+                let requires: Vec<String> = member
+                    .requires(true)
+                    .into_iter()
+                    .map(|(name, _)| name)
+                    .filter(|name| workspace.member(name).is_some())
+                    .collect();
+                sonic_rs::json!({
+                    "name": member.name,
+                    "path": relative(&member.dir),
+                    "workspace_requires": requires,
+                })
+            })
+            .collect();
+        crate::output::line(&sonic_rs::to_string_pretty(&members)?);
+        return Ok(());
+    }
+
+    if workspace.members.is_empty() {
+        crate::output::info("No workspace members found");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.headers(["Member", "Path", "Requires members"]);
+    for member in &workspace.members {
+        let requires: Vec<String> = member
+            .requires(true)
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| workspace.member(name).is_some())
+            .collect();
+        table.row([member.label(), relative(&member.dir), requires.join(", ")]);
+    }
+    table.print();
+    Ok(())
+}
+
+/// Run `script` in each member defining it, in directory order.
+fn run_everywhere(workspace: &Workspace, script: &str, args: &[String]) -> Result<ExitCode> {
+    use crate::output::{error, header, info, success};
+
+    let mut ran = 0;
+    let mut failed = Vec::new();
+    for member in &workspace.members {
+        let config = ScriptConfig {
+            working_dir: member.dir.clone(),
+            timeout: process_timeout(&member.manifest),
+            additional_args: args.to_vec(),
+            echo: true,
+            ..Default::default()
+        };
+        let mut executor = ScriptExecutor::new(&member.manifest, config);
+        let Some(name) = executor.resolve(script).map(String::from) else {
+            continue;
+        };
+
+        header(&format!("{}: {name}", member.label()));
+        ran += 1;
+        match executor.run_script(&name) {
+            Ok(Some(result)) if !result.success => {
+                error(&format!(
+                    "Command failed with exit code {}",
+                    result.exit_code.unwrap_or(1)
+                ));
+                failed.push(member.label());
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error(&format!("{e:#}"));
+                failed.push(member.label());
+            }
+        }
+    }
+
+    if ran == 0 {
+        info(&format!("No workspace member defines a '{script}' script"));
+        return Ok(ExitCode::SUCCESS);
+    }
+    if failed.is_empty() {
+        success(&format!("Script '{script}' succeeded in {ran} member(s)"));
+        Ok(ExitCode::SUCCESS)
+    } else {
+        error(&format!(
+            "Script '{script}' failed in {} of {ran} member(s): {}",
+            failed.len(),
+            failed.join(", ")
+        ));
+        Ok(ExitCode::FAILURE)
+    }
+}
@@ -3,16 +3,21 @@
 //! Uses reqwest with HTTP/2, connection pooling, and aggressive timeouts.
 //! Caches package metadata locally for fast resolution on subsequent runs.
 
+use libretto_cache::TieredCache;
+use libretto_config::ComposerEnvVar;
 use libretto_repository::providers::{
     BitbucketClient, GitHubClient, GitLabClient, ProviderType, VcsProvider, detect_provider,
     parse_vcs_url,
 };
+use libretto_resolver::turbo::TurboConfig;
 use libretto_resolver::turbo::{FetchedPackage, FetchedVersion, TurboFetcher};
+use libretto_resolver::{AbandonedField, ResolutionCache, ResolutionMode, Stability};
 use reqwest::Client;
 use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 use tracing::{debug, trace};
@@ -55,6 +60,7 @@ pub struct Fetcher {
     vcs_owner_index: HashMap<String, Vec<Url>>,
     root_constraints: HashMap<String, String>,
     vcs_cache: dashmap::DashMap<String, Option<FetchedPackage>>,
+    offline: bool,
     requests: AtomicU64,
     bytes: AtomicU64,
     cache_hits: AtomicU64,
@@ -135,12 +141,21 @@ impl Fetcher {
             vcs_owner_index,
             root_constraints,
             vcs_cache: dashmap::DashMap::new(),
+            offline: ComposerEnvVar::DisableNetwork.as_bool().unwrap_or(false),
             requests: AtomicU64::new(0),
             bytes: AtomicU64::new(0),
             cache_hits: AtomicU64::new(0),
         })
     }
 
+    /// Serve metadata from the local cache only, however old; packages
+    /// that were never cached are reported as missing.
+    #[must_use]
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
     /// Get the total number of HTTP requests made.
     ///
     /// This can be used for statistics reporting after fetching operations.
@@ -186,6 +201,12 @@ impl Fetcher {
         self.cache_dir.join(format!("{safe_name}.etag"))
     }
 
+    /// Get `Last-Modified` cache path
+    fn last_modified_path(&self, name: &str) -> PathBuf {
+        let safe_name = name.replace('/', "~");
+        self.cache_dir.join(format!("{safe_name}.last-modified"))
+    }
+
     /// Read cached data if it exists (regardless of age).
     fn read_cache(&self, name: &str) -> Option<Vec<u8>> {
         std::fs::read(self.cache_path(name)).ok()
@@ -209,11 +230,30 @@ impl Fetcher {
         std::fs::read_to_string(self.etag_path(name)).ok()
     }
 
-    /// Write response data + `ETag` to cache, touch the modification time.
-    fn write_cache(&self, name: &str, data: &[u8], etag: Option<&str>) {
+    /// Write response data + validators to cache, touch the modification time.
+    ///
+    /// Validators missing from the response are removed so they never
+    /// describe older data.
+    fn write_cache(
+        &self,
+        name: &str,
+        data: &[u8],
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+    ) {
         let _ = std::fs::write(self.cache_path(name), data);
-        if let Some(etag) = etag {
-            let _ = std::fs::write(self.etag_path(name), etag);
+        for (path, value) in [
+            (self.etag_path(name), etag),
+            (self.last_modified_path(name), last_modified),
+        ] {
+            match value {
+                Some(value) => {
+                    let _ = std::fs::write(path, value);
+                }
+                None => {
+                    let _ = std::fs::remove_file(path);
+                }
+            }
         }
     }
 
@@ -370,10 +410,12 @@ impl Fetcher {
                 require_dev: parse_string_map(&composer, "require-dev"),
                 replace: parse_string_map(&composer, "replace"),
                 provide: parse_string_map(&composer, "provide"),
+                conflict: parse_string_map(&composer, "conflict"),
                 suggest: parse_string_map(&composer, "suggest"),
                 dist_url: vcs_dist_url(provider.name(), url, &reference),
                 dist_type: Some("zip".to_string()),
                 dist_shasum: None,
+                dist_signature: None,
                 source_url: Some(url.to_string()),
                 source_type: Some("git".to_string()),
                 source_reference: Some(reference.clone()),
@@ -406,6 +448,9 @@ impl Fetcher {
                     .and_then(Value::as_str)
                     .map(ToString::to_string),
                 bin: parse_string_or_array(&composer, "bin"),
+                abandoned: composer
+                    .get("abandoned")
+                    .and_then(|v| sonic_rs::from_value(v).ok()),
             });
 
             // Composer compatibility: when a repository's default branch was
@@ -489,10 +534,12 @@ impl Fetcher {
                 require_dev: parse_string_map(&composer, "require-dev"),
                 replace: parse_string_map(&composer, "replace"),
                 provide: parse_string_map(&composer, "provide"),
+                conflict: parse_string_map(&composer, "conflict"),
                 suggest: parse_string_map(&composer, "suggest"),
                 dist_url: vcs_dist_url(provider.name(), url, &reference),
                 dist_type: Some("zip".to_string()),
                 dist_shasum: None,
+                dist_signature: None,
                 source_url: Some(url.to_string()),
                 source_type: Some("git".to_string()),
                 source_reference: Some(reference.clone()),
@@ -525,6 +572,9 @@ impl Fetcher {
                     .and_then(Value::as_str)
                     .map(ToString::to_string),
                 bin: parse_string_or_array(&composer, "bin"),
+                abandoned: composer
+                    .get("abandoned")
+                    .and_then(|v| sonic_rs::from_value(v).ok()),
             });
 
             // Composer compatibility: when a repository's default branch was
@@ -588,6 +638,16 @@ impl Fetcher {
     }
 
     async fn fetch_impl(&self, name: &str) -> Option<FetchedPackage> {
+        if self.offline {
+            let package = self
+                .read_cache(name)
+                .and_then(|cached| self.parse_response(name, &cached));
+            if package.is_some() {
+                self.cache_hits.fetch_add(1, Ordering::Relaxed);
+            }
+            return package;
+        }
+
         // If cache is very fresh (< 5 min), skip network entirely
         if self.is_cache_fresh(name)
             && let Some(cached) = self.read_cache(name)
@@ -657,12 +717,16 @@ impl Fetcher {
             return self.fetch_from_vcs(name).await;
         }
 
-        // Save ETag from response
-        let etag = response
-            .headers()
-            .get("etag")
-            .and_then(|v| v.to_str().ok())
-            .map(String::from);
+        // Save validators from response
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        };
+        let etag = header("etag");
+        let last_modified = header("last-modified");
 
         let bytes = match response.bytes().await {
             Ok(b) => b,
@@ -675,7 +739,7 @@ impl Fetcher {
         self.bytes.fetch_add(bytes.len() as u64, Ordering::Relaxed);
 
         // Cache the response
-        self.write_cache(name, &bytes, etag.as_deref());
+        self.write_cache(name, &bytes, etag.as_deref(), last_modified.as_deref());
 
         let bytes = bytes.to_vec();
         if let Some(package) = self.parse_response(name, &bytes) {
@@ -685,6 +749,85 @@ impl Fetcher {
         self.fetch_from_vcs(name).await
     }
 
+    /// Check whether the cached document for `name` still has `validator`
+    /// with a conditional request, without downloading it again.
+    ///
+    /// A 304 refreshes the cache so the next fetch is served from disk; a
+    /// changed document is cached as it arrives.
+    async fn revalidate_impl(&self, name: &str, validator: &str) -> bool {
+        if self.validator(name).as_deref() != Some(validator) {
+            return false;
+        }
+        if self.offline || self.is_cache_fresh(name) {
+            return true;
+        }
+
+        let url = format!("{}/{}.json", self.base_url, name);
+        let request = match validator.split_once(':') {
+            Some(("etag", etag)) => self.client.get(&url).header("If-None-Match", etag),
+            Some(("last-modified", date)) => {
+                self.client.get(&url).header("If-Modified-Since", date)
+            }
+            _ => return false,
+        };
+        self.requests.fetch_add(1, Ordering::Relaxed);
+
+        let response = match request.send().await {
+            Ok(r) => r,
+            Err(e) => {
+                debug!(package = %name, error = %e, "revalidation failed");
+                return false;
+            }
+        };
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+            self.touch_cache(name);
+            trace!(package = %name, "304 not modified");
+            return true;
+        }
+        if response.status().is_success() {
+            let header = |name: &str| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(String::from)
+            };
+            let etag = header("etag");
+            let last_modified = header("last-modified");
+            if let Ok(bytes) = response.bytes().await {
+                self.bytes.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+                self.write_cache(name, &bytes, etag.as_deref(), last_modified.as_deref());
+            }
+        }
+        false
+    }
+
+    /// Version documents for a package as its repository serves them, with
+    /// minified metadata expanded.
+    ///
+    /// Returns `None` for packages that are missing or only found in a VCS
+    /// repository, which have no cached document.
+    pub async fn package_metadata(&self, name: &str) -> Option<Vec<Value>> {
+        self.fetch_impl(name).await?;
+        if self
+            .vcs_cache
+            .get(name)
+            .is_some_and(|package| package.is_some())
+        {
+            return None;
+        }
+
+        let json: Value = sonic_rs::from_slice(&self.read_cache(name)?).ok()?;
+        let minified = json.get("minified").and_then(Value::as_str) == Some("composer/2.0");
+        let versions = json
+            .get("packages")
+            .and_then(Value::as_object)
+            .and_then(|packages| packages.get(&name.to_string()))
+            .and_then(Value::as_array)?;
+        Some(expand_versions_for_deserialize(versions, minified))
+    }
+
     fn parse_response(&self, name: &str, bytes: &[u8]) -> Option<FetchedPackage> {
         let json: Value = match sonic_rs::from_slice(bytes) {
             Ok(j) => j,
@@ -745,6 +888,11 @@ impl Fetcher {
                         .iter()
                         .map(|(k, v)| (k.clone(), v.clone()))
                         .collect(),
+                    conflict: v
+                        .conflict
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect(),
                     suggest: v
                         .suggest
                         .iter()
@@ -753,6 +901,7 @@ impl Fetcher {
                     dist_url: v.dist.as_ref().map(|d| d.url.clone()),
                     dist_type: v.dist.as_ref().map(|d| d.dist_type.clone()),
                     dist_shasum: v.dist.as_ref().and_then(|d| d.shasum.clone()),
+                    dist_signature: v.dist.as_ref().and_then(|d| d.signature.clone()),
                     source_url: v.source.as_ref().map(|s| s.url.clone()),
                     source_type: v.source.as_ref().map(|s| s.source_type.clone()),
                     source_reference: v.source.as_ref().map(|s| s.reference.clone()),
@@ -774,6 +923,7 @@ impl Fetcher {
                     funding: v.funding.as_ref().and_then(|f| sonic_rs::to_value(f).ok()),
                     notification_url: v.notification_url.clone(),
                     bin: v.bin.clone(),
+                    abandoned: v.abandoned.clone(),
                 })
             })
             .collect();
@@ -827,6 +977,31 @@ impl TurboFetcher for Fetcher {
     ) -> Pin<Box<dyn std::future::Future<Output = Option<FetchedPackage>> + Send + '_>> {
         Box::pin(async move { self.fetch_impl(&name).await })
     }
+
+    fn validator(&self, name: &str) -> Option<String> {
+        // Packages served from VCS repositories have no cached document
+        if self
+            .vcs_cache
+            .get(name)
+            .is_some_and(|package| package.is_some())
+        {
+            return None;
+        }
+        if let Some(etag) = self.read_etag(name) {
+            return Some(format!("etag:{etag}"));
+        }
+        std::fs::read_to_string(self.last_modified_path(name))
+            .ok()
+            .map(|date| format!("last-modified:{date}"))
+    }
+
+    fn revalidate(
+        &self,
+        name: String,
+        validator: String,
+    ) -> Pin<Box<dyn std::future::Future<Output = bool> + Send + '_>> {
+        Box::pin(async move { self.revalidate_impl(&name, &validator).await })
+    }
 }
 
 fn parse_string_map(json: &Value, key: &str) -> Vec<(String, String)> {
@@ -868,6 +1043,93 @@ fn vcs_dist_url(provider_name: &str, url: &Url, reference: &str) -> Option<Strin
     }
 }
 
+/// Resolution cache for the project, keyed on its platform overrides and
+/// repositories. `None` when the cache cannot be opened.
+pub fn resolution_cache(composer: &Value) -> Option<ResolutionCache> {
+    let cache = match TieredCache::new() {
+        Ok(cache) => cache,
+        Err(e) => {
+            debug!(error = %e, "resolution cache unavailable");
+            return None;
+        }
+    };
+    let platform = composer
+        .get("config")
+        .and_then(|config| config.get("platform"))
+        .map(|platform| platform.to_string())
+        .unwrap_or_default();
+    let repositories = composer
+        .get("repositories")
+        .map(|repositories| repositories.to_string())
+        .unwrap_or_default();
+    Some(
+        ResolutionCache::new(Arc::new(cache))
+            .with_platform(&platform)
+            .with_repositories(repositories),
+    )
+}
+
+/// Parse a stability flag (`dev`, `alpha`, `beta`, `rc`, `stable`).
+pub fn parse_stability(s: &str) -> Option<Stability> {
+    match s.to_lowercase().as_str() {
+        "dev" => Some(Stability::Dev),
+        "alpha" => Some(Stability::Alpha),
+        "beta" => Some(Stability::Beta),
+        "rc" => Some(Stability::RC),
+        "stable" => Some(Stability::Stable),
+        _ => None,
+    }
+}
+
+/// The project's `minimum-stability`, stable when unset or invalid.
+pub fn minimum_stability(composer: &Value) -> Stability {
+    composer
+        .get("minimum-stability")
+        .and_then(|v| v.as_str())
+        .and_then(parse_stability)
+        .unwrap_or(Stability::Stable)
+}
+
+/// Resolver settings for commands that inspect a resolution without
+/// installing it, such as `outdated`, `prohibits` and `graph`.
+pub const fn inspection_config(
+    min_stability: Stability,
+    include_dev: bool,
+    block_abandoned: bool,
+) -> TurboConfig {
+    TurboConfig {
+        max_concurrent: 64,
+        request_timeout: Duration::from_secs(10),
+        mode: ResolutionMode::PreferStable,
+        min_stability,
+        include_dev,
+        block_abandoned,
+    }
+}
+
+/// Match a package name against a pattern where `*` matches any run of
+/// characters, ignoring case.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    let name = name.to_ascii_lowercase();
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
 fn extract_vcs_repository_urls(composer: &Value) -> Vec<String> {
     let mut urls = Vec::new();
 
@@ -947,6 +1209,8 @@ struct PackagistVersion {
     #[serde(default, deserialize_with = "deserialize_deps")]
     provide: HashMap<String, String>,
     #[serde(default, deserialize_with = "deserialize_deps")]
+    conflict: HashMap<String, String>,
+    #[serde(default, deserialize_with = "deserialize_deps")]
     suggest: HashMap<String, String>,
     #[serde(default)]
     dist: Option<PackagistDist>,
@@ -989,6 +1253,8 @@ struct PackagistVersion {
     notification_url: Option<String>,
     #[serde(default, deserialize_with = "deserialize_string_vec")]
     bin: Option<Vec<String>>,
+    #[serde(default)]
+    abandoned: Option<AbandonedField>,
 }
 
 fn deserialize_deps<'de, D>(deserializer: D) -> Result<HashMap<String, String>, D::Error>
@@ -1048,6 +1314,8 @@ struct PackagistDist {
     dist_type: String,
     url: String,
     shasum: Option<String>,
+    #[serde(default)]
+    signature: Option<String>,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -1468,6 +1736,15 @@ mod tests {
         assert!(!constraints.contains_key("ext-json"));
     }
 
+    #[test]
+    fn package_patterns_support_wildcards() {
+        assert!(matches_pattern("vendor/lib", "vendor/lib"));
+        assert!(matches_pattern("Symfony/*", "symfony/console"));
+        assert!(matches_pattern("*/polyfill-*", "symfony/polyfill-mbstring"));
+        assert!(!matches_pattern("symfony/*", "laravel/framework"));
+        assert!(!matches_pattern("vendor/lib", "vendor/library"));
+    }
+
     #[test]
     fn detects_dev_constraints() {
         assert!(is_dev_constraint("dev-main"));
@@ -1560,4 +1837,60 @@ mod tests {
         assert_eq!(package.versions[2].version, "1.8.0");
         assert!(package.versions[2].require.is_empty());
     }
+
+    #[tokio::test]
+    async fn revalidation_sends_the_stored_validator() {
+        use wiremock::matchers::{header, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(path("/vendor/lib.json"))
+            .and(header("If-None-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .mount(&server)
+            .await;
+        Mock::given(path("/vendor/lib.json"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v2\"")
+                    .set_body_string(r#"{"packages":{}}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut fetcher = Fetcher::new().unwrap().with_offline(false);
+        fetcher.base_url = server.uri();
+        fetcher.cache_dir = dir.path().to_path_buf();
+        let stale = |fetcher: &Fetcher| {
+            std::fs::File::options()
+                .write(true)
+                .open(fetcher.cache_path("vendor/lib"))
+                .unwrap()
+                .set_modified(std::time::SystemTime::now() - 2 * METADATA_SKIP_TTL)
+                .unwrap();
+        };
+
+        fetcher.write_cache("vendor/lib", b"{}", Some("\"v1\""), None);
+        stale(&fetcher);
+        assert!(fetcher.revalidate_impl("vendor/lib", "etag:\"v1\"").await);
+        assert_eq!(fetcher.request_count(), 1);
+        // The 304 refreshed the cache, so the next check needs no request
+        assert!(fetcher.revalidate_impl("vendor/lib", "etag:\"v1\"").await);
+        assert_eq!(fetcher.request_count(), 1);
+
+        // A validator the cache no longer has is changed without asking
+        assert!(!fetcher.revalidate_impl("vendor/lib", "etag:\"v0\"").await);
+        assert_eq!(fetcher.request_count(), 1);
+
+        // A changed document is cached as it arrives
+        fetcher.write_cache("vendor/lib", b"{}", Some("\"v0\""), None);
+        stale(&fetcher);
+        assert!(!fetcher.revalidate_impl("vendor/lib", "etag:\"v0\"").await);
+        assert_eq!(fetcher.request_count(), 2);
+        assert_eq!(
+            fetcher.validator("vendor/lib").as_deref(),
+            Some("etag:\"v2\"")
+        );
+    }
 }
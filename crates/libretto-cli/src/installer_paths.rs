@@ -47,24 +47,25 @@ impl InstallerPaths {
 
         if let Some(extra) = composer.get("extra").and_then(|e| e.as_object())
             && let Some(installer_paths) = extra.get(&"installer-paths").and_then(|p| p.as_object())
-            {
-                for (path_template, matchers) in installer_paths {
-                    let mut path_matchers = Vec::new();
-
-                    if let Some(arr) = matchers.as_array() {
-                        for matcher in arr {
-                            if let Some(m) = matcher.as_str()
-                                && let Some(matcher) = parse_matcher(m) {
-                                    path_matchers.push(matcher);
-                                }
+        {
+            for (path_template, matchers) in installer_paths {
+                let mut path_matchers = Vec::new();
+
+                if let Some(arr) = matchers.as_array() {
+                    for matcher in arr {
+                        if let Some(m) = matcher.as_str()
+                            && let Some(matcher) = parse_matcher(m)
+                        {
+                            path_matchers.push(matcher);
                         }
                     }
+                }
 
-                    if !path_matchers.is_empty() {
-                        paths.push((path_template.to_string(), path_matchers));
-                    }
+                if !path_matchers.is_empty() {
+                    paths.push((path_template.to_string(), path_matchers));
                 }
             }
+        }
 
         debug!(
             "Parsed {} installer-paths rules",
@@ -13,21 +13,27 @@ mod commands;
 mod context;
 mod fetcher;
 mod installer_paths;
+mod mirrors;
 mod output;
 mod platform;
+mod plugins;
+mod sandbox;
 mod scripts;
+mod security;
+mod signing;
+mod workspace;
 
-use clap::Parser;
 use commands::{Cli, Commands};
 use context::Context;
 use std::process::ExitCode;
 use std::time::Instant;
 use tracing::Level;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
 fn main() -> ExitCode {
     let start = Instant::now();
-    let cli = Cli::parse();
+    let cli = Cli::parse_with_scripts();
 
     // Initialize tracing based on verbosity
     let log_level = match cli.verbose {
@@ -42,17 +48,38 @@ fn main() -> ExitCode {
         .with_default_directive(log_level.into())
         .from_env_lossy();
 
+    // Logs go to stderr while the NDJSON event stream owns stdout
+    let events = cli.log_format == commands::LogFormat::Ndjson;
+    let writer = if events {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+
     tracing_subscriber::fmt()
         .with_env_filter(filter)
         .with_target(false)
         .without_time()
+        .with_writer(writer)
         .init();
 
     // Enable JSON output if requested
-    if matches!(cli.format, commands::OutputFormat::Json) {
+    if matches!(cli.output_format(), commands::OutputFormat::Json) {
         output::json::enable();
     }
 
+    if events {
+        output::events::enable();
+    }
+
+    if cli.no_plugins {
+        plugins::disable();
+    }
+
+    if cli.no_interaction {
+        output::prompt::disable_interaction();
+    }
+
     // Create context
     let ctx = match Context::new(&cli.to_context_args()) {
         Ok(ctx) => ctx,
@@ -92,19 +119,21 @@ async fn run_command(cli: &Cli, _ctx: &Context) -> anyhow::Result<ExitCode> {
     match &cli.command {
         // Core commands
         Commands::Install(args) => {
-            commands::install::run(args.clone()).await?;
+            output::events::track("install", Box::pin(commands::install::run(args.clone())))
+                .await?;
             Ok(ExitCode::SUCCESS)
         }
         Commands::Update(args) => {
-            commands::update::run(args.clone()).await?;
+            output::events::track("update", Box::pin(commands::update::run(args.clone()))).await?;
             Ok(ExitCode::SUCCESS)
         }
         Commands::Require(args) => {
-            commands::require::run(args.clone()).await?;
+            output::events::track("require", Box::pin(commands::require::run(args.clone())))
+                .await?;
             Ok(ExitCode::SUCCESS)
         }
         Commands::Remove(args) => {
-            commands::remove::run(args.clone()).await?;
+            output::events::track("remove", Box::pin(commands::remove::run(args.clone()))).await?;
             Ok(ExitCode::SUCCESS)
         }
         Commands::Search(args) => {
@@ -131,6 +160,10 @@ async fn run_command(cli: &Cli, _ctx: &Context) -> anyhow::Result<ExitCode> {
             commands::audit::run(args.clone()).await?;
             Ok(ExitCode::SUCCESS)
         }
+        Commands::AuditDb(args) => {
+            commands::audit_db::run(args.clone()).await?;
+            Ok(ExitCode::SUCCESS)
+        }
 
         // Additional commands
         Commands::About(args) => {
@@ -177,6 +210,10 @@ async fn run_command(cli: &Cli, _ctx: &Context) -> anyhow::Result<ExitCode> {
             commands::depends::run(args.clone()).await?;
             Ok(ExitCode::SUCCESS)
         }
+        Commands::DepsCheck(args) => {
+            commands::deps_check::run(args.clone()).await?;
+            Ok(ExitCode::SUCCESS)
+        }
         Commands::Diagnose(args) => {
             commands::diagnose::run(args.clone()).await?;
             Ok(ExitCode::SUCCESS)
@@ -193,6 +230,10 @@ async fn run_command(cli: &Cli, _ctx: &Context) -> anyhow::Result<ExitCode> {
             commands::global::run(args.clone()).await?;
             Ok(ExitCode::SUCCESS)
         }
+        Commands::Graph(args) => {
+            commands::graph::run(args.clone()).await?;
+            Ok(ExitCode::SUCCESS)
+        }
         Commands::Licenses(args) => {
             commands::licenses::run(args.clone()).await?;
             Ok(ExitCode::SUCCESS)
@@ -209,12 +250,17 @@ async fn run_command(cli: &Cli, _ctx: &Context) -> anyhow::Result<ExitCode> {
             commands::reinstall::run(args.clone()).await?;
             Ok(ExitCode::SUCCESS)
         }
+        Commands::RepoBuild(args) => {
+            commands::repo_build::run(args.clone()).await?;
+            Ok(ExitCode::SUCCESS)
+        }
         Commands::Repository(args) => {
             commands::repository::run(args.clone()).await?;
             Ok(ExitCode::SUCCESS)
         }
-        Commands::RunScript(args) => {
-            commands::run_script::run(args.clone()).await?;
+        Commands::RunScript(args) => commands::run_script::run(args.clone()).await,
+        Commands::Sbom(args) => {
+            commands::sbom::run(args.clone()).await?;
             Ok(ExitCode::SUCCESS)
         }
         Commands::SelfUpdate(args) => {
@@ -229,5 +275,7 @@ async fn run_command(cli: &Cli, _ctx: &Context) -> anyhow::Result<ExitCode> {
             commands::suggests::run(args.clone()).await?;
             Ok(ExitCode::SUCCESS)
         }
+        Commands::Workspace(args) => commands::workspace::run(args.clone()).await,
+        Commands::Script(argv) => commands::run_script::run_external(argv).await,
     }
 }
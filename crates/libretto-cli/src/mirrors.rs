@@ -0,0 +1,394 @@
+//! Dist mirrors from repository metadata and user configuration.
+//!
+//! Composer repositories advertise `mirrors` in their `packages.json`. Users
+//! can add their own per repository in `composer.json` or the global
+//! `config.json`; configured mirrors are tried before advertised ones:
+//!
+//! ```json
+//! {
+//!     "type": "composer",
+//!     "url": "https://repo.packagist.org",
+//!     "mirrors": [
+//!         {"dist-url": "https://artifacts.internal/%package%/%reference%.%type%", "preferred": true}
+//!     ]
+//! }
+//! ```
+
+use libretto_config::{ComposerEnvVar, RepositoryDefinition, RepositoryType};
+use libretto_downloader::DistMirror;
+use reqwest::header::HeaderMap;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::debug;
+
+/// Packagist's repository URL.
+const PACKAGIST_URL: &str = "https://repo.packagist.org";
+
+/// Hosts serving the dists of packages published on Packagist.
+const PACKAGIST_DIST_HOSTS: &[&str] = &[
+    "api.github.com",
+    "codeload.github.com",
+    "github.com",
+    "gitlab.com",
+    "bitbucket.org",
+];
+
+/// How long advertised mirrors are cached before `packages.json` is fetched again.
+const ADVERTISED_MIRRORS_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Mirrors of one Composer repository.
+#[derive(Debug)]
+struct RepositoryMirrors {
+    /// Host serving the repository's dists; `None` for Packagist, whose
+    /// dists live on the VCS hosts.
+    host: Option<String>,
+    mirrors: Vec<DistMirror>,
+}
+
+/// Dist mirrors of every configured Composer repository.
+#[derive(Debug, Default)]
+pub struct MirrorSet {
+    repositories: Vec<RepositoryMirrors>,
+}
+
+impl MirrorSet {
+    /// Collect mirrors for the project in `base_dir`.
+    ///
+    /// `headers` supplies the credentials for a repository URL. Failing to
+    /// fetch a repository's `packages.json` only loses its advertised mirrors.
+    pub async fn load(
+        base_dir: &Path,
+        client: &reqwest::Client,
+        headers: impl Fn(&str) -> HeaderMap,
+    ) -> Self {
+        let configs: Vec<Value> = [
+            Some(base_dir.join("composer.json")),
+            crate::commands::config::get_global_config_path().ok(),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .filter_map(|content| sonic_rs::from_str(&content).ok())
+        .collect();
+
+        let offline = ComposerEnvVar::DisableNetwork.as_bool().unwrap_or(false);
+        let mut repositories = Vec::new();
+        for (url, configured) in configured_repositories(&configs) {
+            let mut mirrors = configured;
+            if !offline {
+                for mirror in advertised_mirrors(client, &url, headers(&url)).await {
+                    if !mirrors.iter().any(|m| m.url == mirror.url) {
+                        mirrors.push(mirror);
+                    }
+                }
+            }
+            if mirrors.is_empty() {
+                continue;
+            }
+            let host = if is_packagist(&url) {
+                None
+            } else {
+                url::Url::parse(&url)
+                    .ok()
+                    .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+            };
+            debug!(repository = %url, mirrors = mirrors.len(), "dist mirrors configured");
+            repositories.push(RepositoryMirrors { host, mirrors });
+        }
+
+        Self { repositories }
+    }
+
+    /// Mirrors for a package whose canonical dist is `dist_url`.
+    ///
+    /// Dists hosted by a configured repository use that repository's
+    /// mirrors, and dists on the VCS hosts Packagist links to use Packagist's.
+    /// Any other host, such as a private server, gets no mirrors so its
+    /// package names and references are never sent elsewhere.
+    pub fn for_dist_url(&self, dist_url: &str) -> &[DistMirror] {
+        let Some(host) = url::Url::parse(dist_url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+        else {
+            return &[];
+        };
+        self.repositories
+            .iter()
+            .find(|repo| repo.host.as_deref() == Some(host.as_str()))
+            .or_else(|| {
+                PACKAGIST_DIST_HOSTS
+                    .contains(&host.as_str())
+                    .then(|| self.repositories.iter().find(|repo| repo.host.is_none()))
+                    .flatten()
+            })
+            .map_or(&[], |repo| repo.mirrors.as_slice())
+    }
+}
+
+/// Composer repositories with their user-configured mirrors, project config first.
+///
+/// Packagist is included unless disabled with `"packagist.org": false`.
+fn configured_repositories(configs: &[Value]) -> Vec<(String, Vec<DistMirror>)> {
+    let mut repositories: Vec<(String, Vec<DistMirror>)> = Vec::new();
+    let mut packagist_enabled = true;
+
+    for config in configs {
+        let Some(repos) = config.get("repositories") else {
+            continue;
+        };
+        let entries: Vec<(Option<&str>, &Value)> = if let Some(arr) = repos.as_array() {
+            arr.iter().map(|repo| (None, repo)).collect()
+        } else if let Some(obj) = repos.as_object() {
+            obj.iter().map(|(name, repo)| (Some(name), repo)).collect()
+        } else {
+            Vec::new()
+        };
+
+        for (name, repo) in entries {
+            if is_packagist_disabled(name, repo) {
+                packagist_enabled = false;
+                continue;
+            }
+            let Ok(definition) = sonic_rs::from_value::<RepositoryDefinition>(repo) else {
+                continue;
+            };
+            let Some(url) = definition
+                .url
+                .filter(|_| definition.repo_type == RepositoryType::Composer)
+            else {
+                continue;
+            };
+            let url = normalize_url(&url);
+            let mirrors = definition
+                .mirrors
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|m| {
+                    m.dist_url
+                        .map(|dist_url| DistMirror::new(dist_url, m.preferred))
+                });
+
+            match repositories
+                .iter_mut()
+                .find(|(existing, _)| *existing == url)
+            {
+                Some((_, existing)) => existing.extend(mirrors),
+                None => repositories.push((url, mirrors.collect())),
+            }
+        }
+    }
+
+    if packagist_enabled && !repositories.iter().any(|(url, _)| is_packagist(url)) {
+        repositories.push((PACKAGIST_URL.to_string(), Vec::new()));
+    }
+    if !packagist_enabled {
+        repositories.retain(|(url, _)| !is_packagist(url));
+    }
+    repositories
+}
+
+/// `"packagist.org": false` in object form, or `{"packagist.org": false}` in array form.
+fn is_packagist_disabled(name: Option<&str>, repo: &Value) -> bool {
+    match name {
+        Some(name) => name == "packagist.org" && repo.as_bool() == Some(false),
+        None => repo.get("packagist.org").and_then(JsonValueTrait::as_bool) == Some(false),
+    }
+}
+
+fn normalize_url(url: &str) -> String {
+    url.trim_end_matches('/').to_string()
+}
+
+fn is_packagist(url: &str) -> bool {
+    matches!(
+        url.trim_end_matches('/'),
+        "https://repo.packagist.org" | "https://packagist.org" | "http://repo.packagist.org"
+    )
+}
+
+/// Mirrors advertised in a repository's `packages.json`, cached for a day.
+async fn advertised_mirrors(
+    client: &reqwest::Client,
+    repo_url: &str,
+    headers: HeaderMap,
+) -> Vec<DistMirror> {
+    let cache_path = advertised_cache_path(repo_url);
+    let cached = cache_path
+        .as_ref()
+        .and_then(|path| std::fs::read(path).ok().map(|bytes| (path, bytes)));
+
+    if let Some((path, bytes)) = &cached {
+        let fresh = std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age < ADVERTISED_MIRRORS_TTL);
+        if fresh && let Ok(json) = sonic_rs::from_slice::<Value>(bytes) {
+            return parse_mirrors(&json);
+        }
+    }
+
+    let url = format!("{repo_url}/packages.json");
+    let response = client
+        .get(&url)
+        .headers(headers)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status);
+    let body = match response {
+        Ok(response) => response.bytes().await.ok(),
+        Err(e) => {
+            debug!(url = %url, error = %e, "could not fetch repository mirrors");
+            None
+        }
+    };
+
+    let Some(json) = body.and_then(|body| sonic_rs::from_slice::<Value>(&body).ok()) else {
+        // Keep using stale mirrors rather than none at all
+        return cached
+            .and_then(|(_, bytes)| sonic_rs::from_slice::<Value>(&bytes).ok())
+            .map(|json| parse_mirrors(&json))
+            .unwrap_or_default();
+    };
+
+    let mirrors = json
+        .get("mirrors")
+        .cloned()
+        .unwrap_or_else(|| sonic_rs::json!([]));
+    if let Some(path) = &cache_path
+        && let Ok(serialized) = sonic_rs::to_string(&sonic_rs::json!({ "mirrors": mirrors }))
+    {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(path, serialized);
+    }
+    parse_mirrors(&json)
+}
+
+fn advertised_cache_path(repo_url: &str) -> Option<PathBuf> {
+    use sha1::{Digest, Sha1};
+
+    let home = directories::BaseDirs::new()?.home_dir().to_path_buf();
+    let digest = hex::encode(Sha1::digest(repo_url.as_bytes()));
+    Some(
+        home.join(".libretto")
+            .join("metadata")
+            .join("mirrors")
+            .join(format!("{}.json", &digest[..16])),
+    )
+}
+
+/// Parse the `mirrors` array of a `packages.json` document.
+fn parse_mirrors(json: &Value) -> Vec<DistMirror> {
+    json.get("mirrors")
+        .and_then(|mirrors| mirrors.as_array())
+        .map(|mirrors| {
+            mirrors
+                .iter()
+                .filter_map(|mirror| {
+                    let url = mirror.get("dist-url").and_then(|u| u.as_str())?;
+                    let preferred = mirror
+                        .get("preferred")
+                        .and_then(JsonValueTrait::as_bool)
+                        .unwrap_or(false);
+                    Some(DistMirror::new(url, preferred))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> Value {
+        sonic_rs::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn parses_advertised_mirrors() {
+        let json = config(
+            r#"{"mirrors": [
+                {"dist-url": "https://a.example/%package%.%type%", "preferred": true},
+                {"git-url": "https://b.example/%package%.git"},
+                {"dist-url": "https://c.example/%package%.%type%"}
+            ]}"#,
+        );
+        assert_eq!(
+            parse_mirrors(&json),
+            vec![
+                DistMirror::new("https://a.example/%package%.%type%", true),
+                DistMirror::new("https://c.example/%package%.%type%", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_project_and_global_repositories() {
+        let project = config(
+            r#"{"repositories": [
+                {"type": "vcs", "url": "https://github.com/acme/fork"},
+                {"type": "composer", "url": "https://repo.packagist.org/", "mirrors": [
+                    {"dist-url": "https://cache.internal/%package%/%reference%.%type%", "preferred": true}
+                ]}
+            ]}"#,
+        );
+        let global = config(
+            r#"{"repositories": {"private": {"type": "composer", "url": "https://repo.acme.test",
+                "mirrors": [{"dist-url": "https://backup.acme.test/%package%.%type%"}]}}}"#,
+        );
+
+        let repositories = configured_repositories(&[project, global]);
+        assert_eq!(repositories.len(), 2);
+        assert_eq!(repositories[0].0, "https://repo.packagist.org");
+        assert!(repositories[0].1[0].preferred);
+        assert_eq!(repositories[1].0, "https://repo.acme.test");
+        assert!(!repositories[1].1[0].preferred);
+    }
+
+    #[test]
+    fn packagist_can_be_disabled() {
+        let object = config(r#"{"repositories": {"packagist.org": false}}"#);
+        let array = config(r#"{"repositories": [{"packagist.org": false}]}"#);
+        assert!(configured_repositories(&[object]).is_empty());
+        assert!(configured_repositories(&[array]).is_empty());
+        assert_eq!(configured_repositories(&[]).len(), 1);
+    }
+
+    #[test]
+    fn selects_mirrors_by_dist_host() {
+        let set = MirrorSet {
+            repositories: vec![
+                RepositoryMirrors {
+                    host: None,
+                    mirrors: vec![DistMirror::new(
+                        "https://packagist-mirror.test/%package%",
+                        true,
+                    )],
+                },
+                RepositoryMirrors {
+                    host: Some("repo.acme.test".to_string()),
+                    mirrors: vec![DistMirror::new("https://acme-mirror.test/%package%", true)],
+                },
+            ],
+        };
+
+        let private = set.for_dist_url("https://repo.acme.test/dists/acme/lib.zip");
+        assert_eq!(private[0].url, "https://acme-mirror.test/%package%");
+        let public = set.for_dist_url("https://api.github.com/repos/psr/log/zipball/abc");
+        assert_eq!(public[0].url, "https://packagist-mirror.test/%package%");
+        assert!(
+            set.for_dist_url("https://git.internal.test/acme/secret.zip")
+                .is_empty()
+        );
+        assert!(
+            MirrorSet::default()
+                .for_dist_url("https://x.test/a.zip")
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Structured NDJSON event stream for install, update, require and remove.
+//!
+//! With `--log-format=ndjson` these commands write one JSON object per line
+//! to stdout as they progress, and all human-readable output moves to stderr
+//! so the stream stays parseable. Every line carries `schema` (bumped on any
+//! incompatible change to an event), `event` and `timestamp` (milliseconds
+//! since the Unix epoch); the remaining fields depend on the event. Event
+//! names and field names are both `snake_case`.
+
+use libretto_autoloader::{AutoloaderStats, OptimizationLevel};
+use libretto_resolver::ResolverStats;
+use serde::Serialize;
+use std::future::Future;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Version of the event schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Global event stream mode
+static EVENTS: AtomicBool = AtomicBool::new(false);
+
+/// Running totals for the final `summary` event.
+static DOWNLOADED: AtomicU64 = AtomicU64::new(0);
+static CACHED: AtomicU64 = AtomicU64::new(0);
+static BYTES: AtomicU64 = AtomicU64::new(0);
+static FAILED: AtomicU64 = AtomicU64::new(0);
+static SCRIPTS: AtomicU64 = AtomicU64::new(0);
+
+/// Enable the NDJSON event stream.
+pub fn enable() {
+    EVENTS.store(true, Ordering::Relaxed);
+}
+
+/// Check if the NDJSON event stream is enabled.
+pub fn is_enabled() -> bool {
+    EVENTS.load(Ordering::Relaxed)
+}
+
+/// A progress event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A command started.
+    CommandStart { command: String },
+    /// Dependency resolution started.
+    ResolutionStart {
+        requirements: usize,
+        dev_requirements: usize,
+    },
+    /// Dependency resolution finished.
+    ResolutionFinish {
+        packages: usize,
+        duration_ms: u64,
+        stats: ResolutionStats,
+    },
+    /// Packages to install, after skipping those already in place.
+    InstallPlan {
+        packages: usize,
+        download: usize,
+        cached: usize,
+        unchanged: usize,
+    },
+    /// A package archive was downloaded or linked from the cache.
+    Download {
+        package: String,
+        version: String,
+        url: String,
+        /// Host of the repository mirror the archive came from, if any.
+        mirror: Option<String>,
+        /// Bytes transferred; zero for cache hits.
+        bytes: u64,
+        cache_hit: bool,
+        duration_ms: u64,
+    },
+    /// A downloaded archive was extracted into the vendor directory.
+    Extract {
+        package: String,
+        version: String,
+        path: String,
+        duration_ms: u64,
+    },
+    /// A package could not be installed.
+    PackageFailed {
+        package: String,
+        version: String,
+        error: String,
+    },
+    /// A script started.
+    ScriptStart { script: String, commands: usize },
+    /// A script finished.
+    ScriptFinish {
+        script: String,
+        commands: usize,
+        success: bool,
+        exit_code: Option<i32>,
+        duration_ms: u64,
+    },
+    /// The autoloader was written.
+    AutoloadDump {
+        psr4_namespaces: usize,
+        psr0_namespaces: usize,
+        classmap_entries: usize,
+        files: usize,
+        optimization_level: String,
+        duration_ms: u64,
+    },
+    /// The command finished; always the last event.
+    Summary {
+        command: String,
+        success: bool,
+        error: Option<String>,
+        downloaded: u64,
+        cached: u64,
+        bytes: u64,
+        failed: u64,
+        scripts: u64,
+        duration_ms: u64,
+    },
+}
+
+impl Event {
+    /// The `autoload_dump` event for a finished autoloader generation.
+    pub fn autoload_dump(stats: &AutoloaderStats, duration: Duration) -> Self {
+        let level = match stats.optimization_level {
+            OptimizationLevel::None => "none",
+            OptimizationLevel::Optimized => "optimized",
+            OptimizationLevel::Authoritative => "authoritative",
+        };
+        Self::AutoloadDump {
+            psr4_namespaces: stats.psr4_namespaces,
+            psr0_namespaces: stats.psr0_namespaces,
+            classmap_entries: stats.classmap_entries,
+            files: stats.files_count,
+            optimization_level: level.to_string(),
+            duration_ms: millis(duration),
+        }
+    }
+}
+
+/// Resolver counters reported with `resolution_finish`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResolutionStats {
+    pub packages_fetched: u64,
+    pub versions_total: u64,
+    pub fetch_time_ms: u64,
+    pub solver_time_ms: u64,
+    pub packages_prefetched: u64,
+    pub packages_avoided: u64,
+    pub solver_rounds: u64,
+    pub cache_hits: u64,
+    pub requests_total: u64,
+    pub requests_failed: u64,
+}
+
+impl From<&ResolverStats> for ResolutionStats {
+    fn from(stats: &ResolverStats) -> Self {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        Self {
+            packages_fetched: load(&stats.packages_fetched),
+            versions_total: load(&stats.versions_total),
+            fetch_time_ms: load(&stats.fetch_time_ms),
+            solver_time_ms: load(&stats.solver_time_ms),
+            packages_prefetched: load(&stats.packages_prefetched),
+            packages_avoided: load(&stats.packages_avoided),
+            solver_rounds: load(&stats.solver_rounds),
+            cache_hits: load(&stats.cache_hits),
+            requests_total: load(&stats.requests_total),
+            requests_failed: load(&stats.requests_failed),
+        }
+    }
+}
+
+/// One line of the stream.
+#[derive(Serialize)]
+struct Envelope<'a> {
+    schema: u32,
+    timestamp: u64,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+/// Write an event to stdout if the stream is enabled.
+pub fn emit(event: Event) {
+    if !is_enabled() {
+        return;
+    }
+
+    match &event {
+        Event::Download {
+            bytes, cache_hit, ..
+        } => {
+            let counter = if *cache_hit { &CACHED } else { &DOWNLOADED };
+            counter.fetch_add(1, Ordering::Relaxed);
+            BYTES.fetch_add(*bytes, Ordering::Relaxed);
+        }
+        Event::PackageFailed { .. } => {
+            FAILED.fetch_add(1, Ordering::Relaxed);
+        }
+        Event::ScriptFinish { .. } => {
+            SCRIPTS.fetch_add(1, Ordering::Relaxed);
+        }
+        _ => {}
+    }
+
+    let Ok(line) = encode(&event) else {
+        return;
+    };
+    let mut out = std::io::stdout().lock();
+    let _ = writeln!(out, "{line}");
+    let _ = out.flush();
+}
+
+/// Serialize an event into its envelope.
+fn encode(event: &Event) -> sonic_rs::Result<String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+    sonic_rs::to_string(&Envelope {
+        schema: SCHEMA_VERSION,
+        timestamp,
+        event,
+    })
+}
+
+/// Run a command between `command_start` and `summary` events.
+pub async fn track<F>(command: &str, future: F) -> anyhow::Result<()>
+where
+    F: Future<Output = anyhow::Result<()>>,
+{
+    let start = Instant::now();
+    emit(Event::CommandStart {
+        command: command.to_string(),
+    });
+
+    let result = future.await;
+
+    emit(Event::Summary {
+        command: command.to_string(),
+        success: result.is_ok(),
+        error: result.as_ref().err().map(|e| format!("{e:#}")),
+        downloaded: DOWNLOADED.load(Ordering::Relaxed),
+        cached: CACHED.load(Ordering::Relaxed),
+        bytes: BYTES.load(Ordering::Relaxed),
+        failed: FAILED.load(Ordering::Relaxed),
+        scripts: SCRIPTS.load(Ordering::Relaxed),
+        duration_ms: millis(start.elapsed()),
+    });
+    result
+}
+
+/// Milliseconds in a duration, for event fields.
+pub fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sonic_rs::JsonValueTrait;
+
+    #[test]
+    fn envelope_flattens_event() {
+        let line = encode(&Event::ScriptFinish {
+            script: "post-install-cmd".to_string(),
+            commands: 2,
+            success: false,
+            exit_code: Some(3),
+            duration_ms: 15,
+        })
+        .unwrap();
+        assert!(!line.contains('\n'));
+
+        let value: sonic_rs::Value = sonic_rs::from_str(&line).unwrap();
+        assert_eq!(value["schema"].as_u64(), Some(u64::from(SCHEMA_VERSION)));
+        assert!(value["timestamp"].as_u64().is_some());
+        assert_eq!(value["event"].as_str(), Some("script_finish"));
+        assert_eq!(value["script"].as_str(), Some("post-install-cmd"));
+        assert_eq!(value["exit_code"].as_i64(), Some(3));
+        assert_eq!(value["success"].as_bool(), Some(false));
+    }
+
+    #[test]
+    fn resolution_stats_nest() {
+        let stats = ResolverStats::default();
+        stats.solver_rounds.store(2, Ordering::Relaxed);
+        let line = encode(&Event::ResolutionFinish {
+            packages: 4,
+            duration_ms: 120,
+            stats: ResolutionStats::from(&stats),
+        })
+        .unwrap();
+
+        let value: sonic_rs::Value = sonic_rs::from_str(&line).unwrap();
+        assert_eq!(value["event"].as_str(), Some("resolution_finish"));
+        assert_eq!(value["stats"]["solver_rounds"].as_u64(), Some(2));
+        assert_eq!(value["packages"].as_u64(), Some(4));
+    }
+}
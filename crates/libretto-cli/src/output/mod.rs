@@ -9,6 +9,7 @@
 #![allow(dead_code)]
 
 pub mod colors;
+pub mod events;
 pub mod json;
 pub mod live;
 pub mod progress;
@@ -22,6 +23,19 @@ pub use style::{Icon, OutputMode, Theme};
 use std::io::{IsTerminal, stderr, stdout};
 use std::sync::atomic::{AtomicBool, Ordering};
 
+/// Print a line of human-readable output to stdout, or to stderr while the
+/// NDJSON event stream owns stdout.
+macro_rules! out {
+    ($($arg:tt)*) => {
+        if $crate::output::events::is_enabled() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+pub(crate) use out;
+
 /// Global color configuration
 static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
 static UNICODE_ENABLED: AtomicBool = AtomicBool::new(true);
@@ -66,13 +80,18 @@ pub fn is_tty() -> bool {
     *IS_TTY
 }
 
+/// Print a line of command output, moved to stderr in NDJSON mode.
+pub fn line(text: &str) {
+    out!("{text}");
+}
+
 /// Print a styled header
 pub fn header(text: &str) {
     use owo_colors::OwoColorize;
     if colors_enabled() {
-        println!("{} {}", "Libretto".cyan().bold(), text.dimmed());
+        out!("{} {}", "Libretto".cyan().bold(), text.dimmed());
     } else {
-        println!("Libretto {text}");
+        out!("Libretto {text}");
     }
 }
 
@@ -85,9 +104,9 @@ pub fn success(text: &str) {
         Icon::Success.ascii()
     };
     if colors_enabled() {
-        println!("{} {}", icon.green(), text);
+        out!("{} {}", icon.green(), text);
     } else {
-        println!("{icon} {text}");
+        out!("{icon} {text}");
     }
 }
 
@@ -130,9 +149,9 @@ pub fn info(text: &str) {
         Icon::Info.ascii()
     };
     if colors_enabled() {
-        println!("{} {}", icon.blue(), text);
+        out!("{} {}", icon.blue(), text);
     } else {
-        println!("{icon} {text}");
+        out!("{icon} {text}");
     }
 }
 
@@ -151,14 +170,14 @@ pub fn package(name: &str, version: Option<&str>) {
     use owo_colors::OwoColorize;
     if colors_enabled() {
         if let Some(v) = version {
-            println!("  {} {}", name.green(), v.yellow());
+            out!("  {} {}", name.green(), v.yellow());
         } else {
-            println!("  {}", name.green());
+            out!("  {}", name.green());
         }
     } else if let Some(v) = version {
-        println!("  {name} {v}");
+        out!("  {name} {v}");
     } else {
-        println!("  {name}");
+        out!("  {name}");
     }
 }
 
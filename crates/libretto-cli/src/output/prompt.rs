@@ -5,10 +5,21 @@ use dialoguer::{
     Select as DialoguerSelect, theme::ColorfulTheme,
 };
 use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Global `--no-interaction` state.
+static NO_INTERACTION: AtomicBool = AtomicBool::new(false);
+
+/// Disable interactive prompts (`--no-interaction`).
+pub fn disable_interaction() {
+    NO_INTERACTION.store(true, Ordering::Relaxed);
+}
 
 /// Check if interactive prompts are available
 pub fn is_interactive() -> bool {
-    io::stdin().is_terminal() && io::stdout().is_terminal()
+    !NO_INTERACTION.load(Ordering::Relaxed)
+        && io::stdin().is_terminal()
+        && io::stdout().is_terminal()
 }
 
 /// Get the dialoguer theme based on color settings
@@ -168,7 +168,7 @@ impl Table {
 
     /// Print the table to stdout
     pub fn print(&self) {
-        println!("{}", self.inner);
+        super::line(&self.inner.to_string());
     }
 
     /// Check if the table is empty
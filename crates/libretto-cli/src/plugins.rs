@@ -0,0 +1,386 @@
+//! Plugin system integration for CLI commands.
+//!
+//! Discovers Composer plugins installed in the vendor directory, enforces
+//! `allow-plugins` (asking the user about unknown plugins like Composer does)
+//! and dispatches lifecycle hooks to them, together with the built-in native
+//! replacements from `libretto-plugin-system`.
+
+use crate::output::prompt::{Input, is_interactive};
+use crate::output::warning;
+use crate::sandbox::SandboxPolicy;
+use libretto_core::PackageId;
+use libretto_plugin_system::builtin::is_builtin;
+use libretto_plugin_system::{
+    ComposerPluginConfig, EventContext, EventResult, Hook, PluginManager, PluginManagerConfig,
+};
+use sonic_rs::{JsonValueMutTrait, JsonValueTrait, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use tracing::debug;
+
+/// Global `--no-plugins` state.
+static PLUGINS_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Answers given at the `allow-plugins` prompt during this run.
+///
+/// Keeps plugins from being asked about twice when the runtime is reloaded
+/// after installing packages, including plugins discarded with `d`.
+static SESSION_DECISIONS: LazyLock<Mutex<HashMap<String, bool>>> = LazyLock::new(Mutex::default);
+
+/// Disable all plugins (`--no-plugins`).
+pub fn disable() {
+    PLUGINS_DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Check if plugins are enabled.
+pub fn is_enabled() -> bool {
+    !PLUGINS_DISABLED.load(Ordering::Relaxed)
+}
+
+/// Read the plugin-related `config` section of composer.json.
+pub fn composer_plugin_config(composer: &Value) -> ComposerPluginConfig {
+    composer
+        .get("config")
+        .and_then(|config| sonic_rs::from_str(&config.to_string()).ok())
+        .unwrap_or_default()
+}
+
+/// Plugins loaded for a single command run.
+///
+/// All dispatch methods are no-ops when plugins are disabled. Plugin failures
+/// are reported as warnings; a broken plugin never fails the command.
+pub struct PluginRuntime {
+    manager: Option<PluginManager>,
+    project_root: PathBuf,
+    vendor_dir: PathBuf,
+    operation: &'static str,
+    dev_mode: bool,
+}
+
+impl PluginRuntime {
+    /// Create a runtime that dispatches nothing.
+    pub fn disabled(project_root: &Path, vendor_dir: &Path, operation: &'static str) -> Self {
+        Self {
+            manager: None,
+            project_root: project_root.to_path_buf(),
+            vendor_dir: vendor_dir.to_path_buf(),
+            operation,
+            dev_mode: true,
+        }
+    }
+
+    /// Discover and load the plugins installed in `vendor_dir`.
+    pub async fn load(
+        composer: &Value,
+        project_root: &Path,
+        vendor_dir: &Path,
+        operation: &'static str,
+        dev_mode: bool,
+    ) -> Self {
+        let mut runtime = Self::disabled(project_root, vendor_dir, operation);
+        runtime.dev_mode = dev_mode;
+        if !is_enabled() {
+            return runtime;
+        }
+
+        let mut config = composer_plugin_config(composer);
+        // PHP plugin processes may only touch the project (see `crate::sandbox`)
+        let mut manager = PluginManager::with_config(PluginManagerConfig {
+            sandbox: SandboxPolicy::load(composer, project_root).config,
+            ..PluginManagerConfig::default()
+        });
+
+        let discovered = match manager.discover(vendor_dir).await {
+            Ok(discovered) => discovered,
+            Err(e) => {
+                warning(&format!("Failed to discover plugins: {e}"));
+                Vec::new()
+            }
+        };
+
+        for handle in discovered {
+            // Replaced by a native implementation registered below
+            if is_builtin(&handle.id) {
+                continue;
+            }
+            if !check_allowed(&handle.id, &mut config, project_root) {
+                handle.disable();
+                continue;
+            }
+            if let Err(e) = manager.load(&handle.id).await {
+                warning(&format!("Failed to load plugin {}: {e}", handle.id));
+            }
+        }
+
+        match manager.register_builtins(vendor_dir, |name| {
+            check_allowed(name, &mut config, project_root)
+        }) {
+            Ok(registered) if !registered.is_empty() => {
+                debug!(plugins = ?registered, "registered built-in plugins");
+            }
+            Ok(_) => {}
+            Err(e) => warning(&format!("Failed to load built-in plugins: {e}")),
+        }
+
+        runtime.manager = Some(manager);
+        runtime
+    }
+
+    /// Reload plugins, activating any that were just installed.
+    pub async fn reload(&mut self, composer: &Value) {
+        if self.manager.is_none() && !is_enabled() {
+            return;
+        }
+        *self = Self::load(
+            composer,
+            &self.project_root,
+            &self.vendor_dir,
+            self.operation,
+            self.dev_mode,
+        )
+        .await;
+    }
+
+    /// Base event context for this command.
+    pub fn context(&self) -> EventContext {
+        EventContext::new()
+            .with_operation(self.operation)
+            .with_project_root(&self.project_root)
+            .with_vendor_dir(&self.vendor_dir)
+            .with_dev_mode(self.dev_mode)
+            .with_verbose(tracing::enabled!(tracing::Level::INFO))
+    }
+
+    /// Dispatch a hook with the base context.
+    pub async fn dispatch(&self, hook: Hook) {
+        self.dispatch_with(hook, self.context()).await;
+    }
+
+    /// Dispatch a hook concerning a single package.
+    pub async fn dispatch_package(&self, hook: Hook, name: &str, version: &str) {
+        if self.manager.is_none() {
+            return;
+        }
+        let mut context = self.context().with_data("package", name);
+        if let Some(id) = PackageId::parse(name) {
+            context = context.with_packages(vec![id]);
+        }
+        context
+            .versions
+            .insert(name.to_string(), version.to_string());
+        self.dispatch_with(hook, context).await;
+    }
+
+    /// Dispatch a hook concerning a set of packages (name, version).
+    pub async fn dispatch_packages(&self, hook: Hook, packages: &[(String, String)]) {
+        if self.manager.is_none() {
+            return;
+        }
+        let mut context = self.context().with_packages(
+            packages
+                .iter()
+                .filter_map(|(name, _)| PackageId::parse(name))
+                .collect(),
+        );
+        context.versions = packages.iter().cloned().collect();
+        self.dispatch_with(hook, context).await;
+    }
+
+    /// Dispatch a hook with a custom context.
+    pub async fn dispatch_with(&self, hook: Hook, context: EventContext) {
+        let Some(manager) = &self.manager else {
+            return;
+        };
+
+        match manager.emit(hook, &context).await {
+            Ok(results) => report(hook, &results),
+            Err(e) => warning(&format!("Plugin execution failed ({}): {e}", hook.as_str())),
+        }
+    }
+
+    /// Let plugins rewrite a package download URL (`pre-file-download`).
+    pub async fn rewrite_download_url(&self, name: &str, url: &str) -> String {
+        let Some(manager) = &self.manager else {
+            return url.to_string();
+        };
+
+        let context = self.context().with_data("package", name);
+        match manager.rewrite_download_url(url, &context).await {
+            Ok(rewritten) => rewritten,
+            Err(e) => {
+                warning(&format!("Plugin execution failed (pre-file-download): {e}"));
+                url.to_string()
+            }
+        }
+    }
+}
+
+/// Print plugin messages, warnings and errors.
+fn report(hook: Hook, results: &[EventResult]) {
+    for result in results {
+        for message in &result.messages {
+            debug!(hook = hook.as_str(), "{message}");
+        }
+        for w in &result.warnings {
+            warning(w);
+        }
+        if let Some(err) = &result.error {
+            warning(&format!("Plugin error ({}): {err}", hook.as_str()));
+        }
+    }
+}
+
+/// Check `allow-plugins` for a discovered plugin, asking the user if unset.
+fn check_allowed(name: &str, config: &mut ComposerPluginConfig, project_root: &Path) -> bool {
+    if config.disabled.iter().any(|d| d == name) {
+        return false;
+    }
+    if let Some(allowed) = config.plugin_allowance(name) {
+        if !allowed {
+            debug!(plugin = name, "plugin blocked by allow-plugins");
+        }
+        return allowed;
+    }
+    if let Some(&allowed) = SESSION_DECISIONS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get(name)
+    {
+        return allowed;
+    }
+
+    if !is_interactive() {
+        warning(&format!(
+            "{name} contains a Composer plugin which is blocked by your allow-plugins config. \
+             You may add it to the list if you consider it safe. \
+             See https://getcomposer.org/allow-plugins"
+        ));
+        return false;
+    }
+
+    warning(&format!(
+        "{name} contains a Composer plugin which is currently not in your allow-plugins config. \
+         See https://getcomposer.org/allow-plugins"
+    ));
+    let answer = Input::new(format!(
+        "Do you trust \"{name}\" to execute code and wish to enable it now? \
+         (writes \"allow-plugins\" to composer.json) [y,n,d,?]"
+    ))
+    .validate(|input| match input.trim().to_lowercase().as_str() {
+        "y" | "n" | "d" => Ok(()),
+        _ => Err("y - add package to allow-plugins in composer.json and let it run immediately\n\
+                  n - add package (as disallowed) to allow-plugins in composer.json to suppress further prompts\n\
+                  d - discard this, do not change composer.json and do not allow the plugin to run\n\
+                  ? - print help"
+            .to_string()),
+    })
+    .prompt();
+
+    let allowed = match answer.as_deref().map(str::trim) {
+        Ok("y" | "Y") => Some(true),
+        Ok("n" | "N") => Some(false),
+        _ => None,
+    };
+    SESSION_DECISIONS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .insert(name.to_string(), allowed.unwrap_or(false));
+    let Some(allowed) = allowed else {
+        return false;
+    };
+
+    config
+        .allowed
+        .get_or_insert_with(Default::default)
+        .insert(name.to_string(), allowed);
+    if let Err(e) = persist_allow_plugin(&project_root.join("composer.json"), name, allowed) {
+        warning(&format!(
+            "Failed to update allow-plugins in composer.json: {e}"
+        ));
+    }
+    allowed
+}
+
+/// Write an `allow-plugins` entry to composer.json, preserving key order.
+fn persist_allow_plugin(composer_path: &Path, name: &str, allowed: bool) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(composer_path)?;
+    let mut composer: Value = sonic_rs::from_str(&content)?;
+    let Some(root) = composer.as_object_mut() else {
+        anyhow::bail!("composer.json is not an object");
+    };
+
+    if root.get(&"config").is_none() {
+        root.insert("config", sonic_rs::json!({}));
+    }
+    let Some(config) = root.get_mut(&"config").and_then(|c| c.as_object_mut()) else {
+        anyhow::bail!("composer.json config is not an object");
+    };
+    if !config.get(&"allow-plugins").is_some_and(|a| a.is_object()) {
+        config.insert("allow-plugins", sonic_rs::json!({}));
+    }
+    if let Some(allow) = config
+        .get_mut(&"allow-plugins")
+        .and_then(|a| a.as_object_mut())
+    {
+        allow.insert(name, sonic_rs::json!(allowed));
+    }
+
+    let output = sonic_rs::to_string_pretty(&composer)?;
+    std::fs::write(composer_path, format!("{output}\n"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn persist_allow_plugin_preserves_order() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("composer.json");
+        std::fs::write(
+            &path,
+            r#"{"name": "acme/app", "require": {}, "config": {"sort-packages": true}}"#,
+        )
+        .unwrap();
+
+        persist_allow_plugin(&path, "acme/plugin", true).unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        let name_pos = written.find("\"name\"").unwrap();
+        let config_pos = written.find("\"config\"").unwrap();
+        assert!(name_pos < config_pos);
+        let composer: Value = sonic_rs::from_str(&written).unwrap();
+        let config = composer_plugin_config(&composer);
+        assert_eq!(config.plugin_allowance("acme/plugin"), Some(true));
+    }
+
+    #[test]
+    fn explicit_allowance_skips_prompt() {
+        let temp = TempDir::new().unwrap();
+        let composer: Value = sonic_rs::from_str(
+            r#"{"config": {"allow-plugins": {"acme/evil": false, "acme/*": true}}}"#,
+        )
+        .unwrap();
+        let mut config = composer_plugin_config(&composer);
+
+        assert!(check_allowed("acme/good", &mut config, temp.path()));
+        assert!(!check_allowed("acme/evil", &mut config, temp.path()));
+    }
+
+    #[tokio::test]
+    async fn disabled_runtime_is_a_no_op() {
+        let temp = TempDir::new().unwrap();
+        let runtime = PluginRuntime::disabled(temp.path(), &temp.path().join("vendor"), "install");
+        runtime.dispatch(Hook::PreInstallCmd).await;
+        assert_eq!(
+            runtime
+                .rewrite_download_url("acme/lib", "https://example.com/a.zip")
+                .await,
+            "https://example.com/a.zip"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! OS-level isolation of lifecycle scripts and PHP plugins.
+//!
+//! PHP plugin processes always run sandboxed. Lifecycle scripts are not
+//! sandboxed by default, since they commonly need the network or write outside
+//! the project; `config.sandbox.scripts` opts in. Both may read and write below
+//! the project directory, and scripts additionally run without network access:
+//!
+//! ```json
+//! {
+//!     "config": {
+//!         "sandbox": {
+//!             "scripts": true,
+//!             "memory-limit": "512M",
+//!             "read-paths": ["../shared"],
+//!             "write-paths": ["/var/cache/app"],
+//!             "require": true
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Relative paths are resolved against the project directory. When the system
+//! cannot enforce a feature (e.g. no Landlock), a warning is shown; with
+//! `require` the process is not started instead.
+
+use crate::output::warning;
+use anyhow::{Result, bail};
+use libretto_config::{SandboxSettings, parse_byte_size};
+use libretto_plugin_system::{
+    DEFAULT_MEMORY_LIMIT, IsolationReport, ProcessIsolation, SandboxConfig,
+};
+use sonic_rs::{JsonValueTrait, Value};
+use std::path::Path;
+
+/// Sandbox settings for a project.
+#[derive(Debug, Clone, Default)]
+pub struct SandboxPolicy {
+    /// Run lifecycle scripts in the sandbox.
+    pub scripts: bool,
+    /// Limits shared by scripts and PHP plugins.
+    pub config: SandboxConfig,
+}
+
+impl SandboxPolicy {
+    /// Load the policy from composer.json and the global config, project first.
+    pub fn load(composer: &Value, project_root: &Path) -> Self {
+        let global = crate::commands::config::get_global_config_path()
+            .ok()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| sonic_rs::from_str::<Value>(&content).ok());
+        let settings: Vec<SandboxSettings> = [Some(composer), global.as_ref()]
+            .into_iter()
+            .flatten()
+            .filter_map(|config| config.get("config")?.get("sandbox"))
+            .filter_map(|sandbox| sonic_rs::from_value(sandbox).ok())
+            .collect();
+        Self::from_settings(&settings, project_root)
+    }
+
+    fn from_settings(settings: &[SandboxSettings], project_root: &Path) -> Self {
+        let memory_limit = settings
+            .iter()
+            .find_map(|s| s.memory_limit.as_deref())
+            .and_then(|limit| match parse_byte_size(limit) {
+                Ok(bytes) => usize::try_from(bytes).ok(),
+                Err(e) => {
+                    warning(&format!("Ignoring sandbox memory-limit \"{limit}\": {e}"));
+                    None
+                }
+            })
+            .unwrap_or(DEFAULT_MEMORY_LIMIT);
+        let paths = |select: fn(&SandboxSettings) -> Option<&Vec<String>>| {
+            std::iter::once(project_root.to_path_buf())
+                .chain(
+                    settings
+                        .iter()
+                        .filter_map(select)
+                        .flatten()
+                        .map(|path| project_root.join(path)),
+                )
+                .collect()
+        };
+
+        Self {
+            scripts: settings.iter().find_map(|s| s.scripts).unwrap_or(false),
+            config: SandboxConfig {
+                memory_limit,
+                allowed_read_paths: paths(|s| s.read_paths.as_ref()),
+                allowed_write_paths: paths(|s| s.write_paths.as_ref()),
+                require_isolation: settings.iter().find_map(|s| s.require).unwrap_or(false),
+                ..SandboxConfig::default()
+            },
+        }
+    }
+
+    /// Isolation for lifecycle scripts, if they are sandboxed.
+    ///
+    /// Scripts may run other programs but get no network access.
+    pub fn script_isolation(&self) -> Option<ProcessIsolation> {
+        if !self.scripts {
+            return None;
+        }
+        ProcessIsolation::from_config(&self.config, "scripts")
+            .map(|isolation| isolation.with_block_network(true).with_deny_exec(false))
+    }
+
+    /// Warn about requested features the system could not enforce, or fail
+    /// when isolation is required.
+    pub fn check(&self, what: &str, report: &IsolationReport) -> Result<()> {
+        if self.config.require_isolation
+            && let Some((feature, reason)) = report.unavailable.first()
+        {
+            bail!(
+                "Refusing to run {what}: required sandbox feature {feature} is unavailable: {reason}"
+            );
+        }
+        for (feature, reason) in &report.unavailable {
+            warning(&format!(
+                "Sandbox feature {feature} is unavailable for {what}: {reason}"
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_settings_override_global_ones() {
+        let root = Path::new("/project");
+        let policy = SandboxPolicy::from_settings(
+            &[
+                SandboxSettings {
+                    memory_limit: Some("512M".into()),
+                    write_paths: Some(vec!["var".into()]),
+                    ..SandboxSettings::default()
+                },
+                SandboxSettings {
+                    scripts: Some(true),
+                    memory_limit: Some("1G".into()),
+                    read_paths: Some(vec!["/opt/shared".into()]),
+                    ..SandboxSettings::default()
+                },
+            ],
+            root,
+        );
+
+        assert!(policy.scripts);
+        assert_eq!(policy.config.memory_limit, 512 * 1024 * 1024);
+        assert_eq!(
+            policy.config.allowed_read_paths,
+            vec![root.to_path_buf(), Path::new("/opt/shared").to_path_buf()]
+        );
+        assert_eq!(
+            policy.config.allowed_write_paths,
+            vec![root.to_path_buf(), root.join("var")]
+        );
+        assert!(!policy.config.require_isolation);
+    }
+
+    #[test]
+    fn defaults_leave_scripts_unsandboxed() {
+        let policy = SandboxPolicy::from_settings(&[], Path::new("/project"));
+
+        assert!(!policy.scripts);
+        assert!(policy.script_isolation().is_none());
+        assert_eq!(policy.config.memory_limit, DEFAULT_MEMORY_LIMIT);
+    }
+
+    #[test]
+    fn zero_memory_limit_removes_the_limit() {
+        let policy = SandboxPolicy::from_settings(
+            &[SandboxSettings {
+                scripts: Some(true),
+                memory_limit: Some("0".into()),
+                ..SandboxSettings::default()
+            }],
+            Path::new("/project"),
+        );
+
+        assert_eq!(policy.config.memory_limit, 0);
+        assert_eq!(
+            policy.script_isolation(),
+            Some(
+                ProcessIsolation::new()
+                    .with_read_path("/project")
+                    .with_write_path("/project")
+                    .with_block_network(true)
+            )
+        );
+    }
+}
@@ -35,7 +35,13 @@
 //!     }
 //! }
 //! ```
+//!
+//! Scripts are not sandboxed unless `config.sandbox.scripts` is set; they then
+//! run without network access and confined to the project directory (see
+//! [`crate::sandbox`]).
 
+use crate::output::events::{self, Event};
+use crate::sandbox::SandboxPolicy;
 use anyhow::{Context, Result, bail};
 use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
 use std::collections::HashMap;
@@ -222,6 +228,27 @@ pub struct ScriptResult {
     pub duration: Duration,
 }
 
+/// Report a finished script on the event stream.
+fn emit_finish(result: &ScriptResult) {
+    events::emit(Event::ScriptFinish {
+        script: result.name.clone(),
+        commands: result.commands_executed,
+        success: result.success,
+        exit_code: result.exit_code,
+        duration_ms: events::millis(result.duration),
+    });
+}
+
+/// Standard output for script processes; stderr while the NDJSON event
+/// stream owns stdout.
+fn script_stdout() -> Stdio {
+    if events::is_enabled() {
+        Stdio::from(std::io::stderr())
+    } else {
+        Stdio::inherit()
+    }
+}
+
 impl ScriptResult {
     fn success(name: &str, commands: usize, duration: Duration) -> Self {
         Self {
@@ -264,6 +291,11 @@ pub struct ScriptConfig {
     pub env: HashMap<String, String>,
     /// Whether to stop on first error.
     pub stop_on_error: bool,
+    /// Arguments forwarded from the command line, appended to each command
+    /// or substituted for `@additional_args` where a command contains it.
+    pub additional_args: Vec<String>,
+    /// Echo each command before running it.
+    pub echo: bool,
 }
 
 impl Default for ScriptConfig {
@@ -276,20 +308,51 @@ impl Default for ScriptConfig {
                 |p| p.to_string_lossy().to_string(),
             ),
             dev_mode: true,
-            timeout: 300,
+            timeout: env_process_timeout().unwrap_or(DEFAULT_PROCESS_TIMEOUT),
             env: HashMap::new(),
             stop_on_error: true,
+            additional_args: Vec::new(),
+            echo: false,
         }
     }
 }
 
+/// Script timeout in seconds when neither the environment nor composer.json sets one.
+const DEFAULT_PROCESS_TIMEOUT: u64 = 300;
+
+/// Script timeout from `COMPOSER_PROCESS_TIMEOUT`, if set.
+fn env_process_timeout() -> Option<u64> {
+    std::env::var("COMPOSER_PROCESS_TIMEOUT")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// Script timeout for a project, as Composer resolves it: `COMPOSER_PROCESS_TIMEOUT`,
+/// then `config.process-timeout`, then 300 seconds. Zero disables the timeout.
+pub fn process_timeout(composer_json: &Value) -> u64 {
+    env_process_timeout()
+        .or_else(|| {
+            composer_json
+                .get("config")
+                .and_then(|c| c.get("process-timeout"))
+                .and_then(sonic_rs::JsonValueTrait::as_u64)
+        })
+        .unwrap_or(DEFAULT_PROCESS_TIMEOUT)
+}
+
 /// Script executor for running Composer scripts.
 #[allow(dead_code)]
 pub struct ScriptExecutor {
     /// Parsed scripts from composer.json.
     scripts: HashMap<String, Vec<String>>,
+    /// Script names by alias, from `scripts-aliases`.
+    aliases: HashMap<String, String>,
+    /// Script descriptions, from `scripts-descriptions`.
+    descriptions: HashMap<String, String>,
     /// Configuration.
     config: ScriptConfig,
+    /// Sandbox settings, from `config.sandbox`.
+    sandbox: SandboxPolicy,
     /// Script call stack (for detecting recursion).
     call_stack: Vec<String>,
 }
@@ -299,8 +362,33 @@ impl ScriptExecutor {
     /// Create a new script executor from composer.json content.
     pub fn new(composer_json: &Value, config: ScriptConfig) -> Self {
         let scripts = Self::parse_scripts(composer_json);
+        let mut aliases = HashMap::new();
+        if let Some(obj) = composer_json
+            .get("scripts-aliases")
+            .and_then(|v| v.as_object())
+        {
+            for (name, list) in obj {
+                for alias in list.as_array().into_iter().flatten() {
+                    if let Some(alias) = alias.as_str() {
+                        aliases.insert(alias.to_string(), name.to_string());
+                    }
+                }
+            }
+        }
+        let descriptions = composer_json
+            .get("scripts-descriptions")
+            .and_then(|v| v.as_object())
+            .map(|obj| {
+                obj.iter()
+                    .filter_map(|(name, desc)| Some((name.to_string(), desc.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
         Self {
             scripts,
+            aliases,
+            descriptions,
+            sandbox: SandboxPolicy::load(composer_json, &config.working_dir),
             config,
             call_stack: Vec::new(),
         }
@@ -335,6 +423,37 @@ impl ScriptExecutor {
         self.scripts.contains_key(name)
     }
 
+    /// Resolve a script name or alias to the script name.
+    #[must_use]
+    pub fn resolve(&self, name: &str) -> Option<&str> {
+        if let Some((name, _)) = self.scripts.get_key_value(name) {
+            return Some(name);
+        }
+        self.aliases
+            .get(name)
+            .filter(|script| self.scripts.contains_key(*script))
+            .map(String::as_str)
+    }
+
+    /// Description of a script from `scripts-descriptions`.
+    #[must_use]
+    pub fn description(&self, name: &str) -> Option<&str> {
+        self.descriptions.get(name).map(String::as_str)
+    }
+
+    /// Aliases of a script from `scripts-aliases`, sorted.
+    #[must_use]
+    pub fn aliases_of(&self, name: &str) -> Vec<&str> {
+        let mut aliases: Vec<&str> = self
+            .aliases
+            .iter()
+            .filter(|(_, script)| *script == name)
+            .map(|(alias, _)| alias.as_str())
+            .collect();
+        aliases.sort_unstable();
+        aliases
+    }
+
     /// Check if an event script exists.
     #[must_use]
     pub fn has_event(&self, event: ScriptEvent) -> bool {
@@ -381,6 +500,10 @@ impl ScriptExecutor {
         let mut executed = 0;
 
         info!(script = %name, commands = commands.len(), "executing script");
+        events::emit(Event::ScriptStart {
+            script: name.to_string(),
+            commands: commands.len(),
+        });
 
         for cmd in &commands {
             let result = self.execute_command(cmd)?;
@@ -391,19 +514,23 @@ impl ScriptExecutor {
             {
                 let code = status.code().unwrap_or(-1);
                 self.call_stack.pop();
-                return Ok(Some(ScriptResult::failure(
+                let result = ScriptResult::failure(
                     name,
                     executed,
                     code,
                     &format!("Command failed: {cmd}"),
                     start.elapsed(),
-                )));
+                );
+                emit_finish(&result);
+                return Ok(Some(result));
             }
         }
 
         self.call_stack.pop();
 
-        Ok(Some(ScriptResult::success(name, executed, start.elapsed())))
+        let result = ScriptResult::success(name, executed, start.elapsed());
+        emit_finish(&result);
+        Ok(Some(result))
     }
 
     /// Execute a single command.
@@ -415,13 +542,13 @@ impl ScriptExecutor {
             // Handle special directives
             if ref_name.starts_with("php ") {
                 let php_cmd = ref_name.strip_prefix("php ").unwrap();
-                let args = parse_command_args(php_cmd)?;
+                let args = self.forward_args(parse_command_args(php_cmd)?);
                 return self.execute_program(&self.config.php_binary, &args);
             }
 
             if ref_name.starts_with("composer ") {
                 let composer_cmd = ref_name.strip_prefix("composer ").unwrap();
-                let args = parse_command_args(composer_cmd)?;
+                let args = self.forward_args(parse_command_args(composer_cmd)?);
                 return self.execute_program(&self.config.composer_binary, &args);
             }
 
@@ -446,13 +573,48 @@ impl ScriptExecutor {
             return Ok(None);
         }
 
+        // Lifts the timeout for the rest of the run, as in Composer
+        if cmd
+            .replace('/', "\\")
+            .trim_start_matches('\\')
+            .eq_ignore_ascii_case("Composer\\Config::disableProcessTimeout")
+        {
+            debug!("Disabling script timeout");
+            self.config.timeout = 0;
+            return Ok(None);
+        }
+
         // Check for PHP class method syntax: Namespace\Class::method
         // This pattern matches class names like "Illuminate\Foundation\ComposerScripts::postAutoloadDump"
         if is_php_class_method(cmd) {
             return self.execute_php_callback(cmd);
         }
 
-        self.execute_shell(cmd)
+        let additional = self
+            .config
+            .additional_args
+            .iter()
+            .map(|arg| shell_escape(arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if cmd.contains("@additional_args") {
+            self.execute_shell(&cmd.replace("@additional_args", &additional))
+        } else if additional.is_empty() {
+            self.execute_shell(cmd)
+        } else {
+            self.execute_shell(&format!("{cmd} {additional}"))
+        }
+    }
+
+    /// Place the forwarded arguments at `@additional_args`, or after `args`.
+    fn forward_args(&self, mut args: Vec<String>) -> Vec<String> {
+        let forwarded = &self.config.additional_args;
+        if let Some(pos) = args.iter().position(|arg| arg == "@additional_args") {
+            args.splice(pos..=pos, forwarded.iter().cloned());
+        } else {
+            args.extend(forwarded.iter().cloned());
+        }
+        args
     }
 
     /// Execute a PHP class static method callback.
@@ -495,12 +657,6 @@ impl ScriptExecutor {
             }
         }
 
-        // Composer\Config::disableProcessTimeout - this is a no-op for us
-        if normalized == "composer\\config::disableprocesstimeout" {
-            debug!("Ignoring Composer\\Config::disableProcessTimeout");
-            return Ok(Some(dummy_success_status()));
-        }
-
         Ok(None)
     }
 
@@ -669,6 +825,9 @@ try {{
     /// Execute a shell command with optional timeout enforcement.
     fn execute_shell(&self, cmd: &str) -> Result<Option<ExitStatus>> {
         debug!(command = %cmd, timeout = self.config.timeout, "executing shell command");
+        if self.config.echo {
+            crate::output::info(&format!("> {cmd}"));
+        }
 
         // Build environment
         let mut env: HashMap<String, String> = std::env::vars().collect();
@@ -709,8 +868,9 @@ try {{
             .current_dir(&self.config.working_dir)
             .envs(&env)
             .stdin(Stdio::inherit())
-            .stdout(Stdio::inherit())
+            .stdout(script_stdout())
             .stderr(Stdio::inherit());
+        self.isolate(&mut command, cmd)?;
 
         // If timeout is set (> 0), enforce it
         if self.config.timeout > 0 {
@@ -750,6 +910,15 @@ try {{
         }
     }
 
+    /// Apply OS-level isolation to a script process when sandboxing is enabled.
+    fn isolate(&self, command: &mut Command, cmd: &str) -> Result<()> {
+        let Some(isolation) = self.sandbox.script_isolation() else {
+            return Ok(());
+        };
+        let report = isolation.apply(command);
+        self.sandbox.check(&format!("\"{cmd}\""), &report)
+    }
+
     /// Execute a program directly with arguments, bypassing shell parsing.
     fn execute_program(&self, program: &str, args: &[String]) -> Result<Option<ExitStatus>> {
         debug!(program = %program, args = ?args, timeout = self.config.timeout, "executing program");
@@ -785,10 +954,14 @@ try {{
             .current_dir(&self.config.working_dir)
             .envs(&env)
             .stdin(Stdio::inherit())
-            .stdout(Stdio::inherit())
+            .stdout(script_stdout())
             .stderr(Stdio::inherit());
+        self.isolate(&mut command, program)?;
 
         let display_cmd = format!("{program} {}", args.join(" "));
+        if self.config.echo {
+            crate::output::info(&format!("> {display_cmd}"));
+        }
 
         // If timeout is set (> 0), enforce it
         if self.config.timeout > 0 {
@@ -1185,7 +1358,6 @@ fn parse_command_args(input: &str) -> Result<Vec<String>> {
 }
 
 /// Escape a string for use in shell commands.
-#[allow(dead_code)]
 fn shell_escape(s: &str) -> String {
     // For single-quoted strings in shell, we need to:
     // 1. Replace ' with '\'' (end quote, escaped quote, start quote)
@@ -1301,6 +1473,47 @@ mod tests {
         assert!(!is_php_class_method("MyClass::"));
     }
 
+    #[test]
+    fn test_resolve_aliases() {
+        let json = sonic_rs::json!({
+            "scripts": { "test": "phpunit", "lint": "phpcs" },
+            "scripts-aliases": { "test": ["t", "tests"], "missing": ["m"] },
+            "scripts-descriptions": { "test": "Run the test suite" }
+        });
+        let executor = ScriptExecutor::new(&json, ScriptConfig::default());
+
+        assert_eq!(executor.resolve("test"), Some("test"));
+        assert_eq!(executor.resolve("tests"), Some("test"));
+        assert_eq!(executor.resolve("m"), None);
+        assert_eq!(executor.aliases_of("test"), vec!["t", "tests"]);
+        assert_eq!(executor.description("test"), Some("Run the test suite"));
+        assert_eq!(executor.description("lint"), None);
+    }
+
+    #[test]
+    fn test_forward_args_placeholder() {
+        let json = create_test_composer_json();
+        let config = ScriptConfig {
+            additional_args: vec!["--filter".to_string(), "Foo".to_string()],
+            ..Default::default()
+        };
+        let executor = ScriptExecutor::new(&json, config);
+
+        let args = vec![
+            "-d".to_string(),
+            "@additional_args".to_string(),
+            "x".to_string(),
+        ];
+        assert_eq!(
+            executor.forward_args(args),
+            vec!["-d", "--filter", "Foo", "x"]
+        );
+        assert_eq!(
+            executor.forward_args(vec!["run".to_string()]),
+            vec!["run", "--filter", "Foo"]
+        );
+    }
+
     #[test]
     fn test_shell_escape() {
         assert_eq!(shell_escape("hello"), "'hello'");
@@ -0,0 +1,350 @@
+//! Security blocking during dependency resolution.
+//!
+//! Like Composer's `audit.block-insecure`, versions affected by a security
+//! advisory are kept out of `update` resolutions: the newest safe version is
+//! chosen, or resolution fails naming the advisory. With `audit.abandoned`
+//! set to `fail`, abandoned packages are rejected the same way.
+//!
+//! Advisories accepted in `audit.ignore` are honored both here and in audit
+//! reports, depending on each rule's `apply` scope.
+
+use crate::commands::audit_db;
+use crate::output::warning;
+use chrono::{NaiveDate, Utc};
+use libretto_audit::{AdvisoryDatabase, IgnorePolicy, IgnoreRule, IgnoreScope, Severity};
+use libretto_config::{
+    AbandonedHandling, AuditConfig, AuditIgnore, AuditIgnoreApply, AuditIgnoreEntry, ComposerEnvVar,
+};
+use libretto_core::PackageId;
+use libretto_resolver::{
+    ComposerConstraint, PackageFetcher, Resolver, VersionBlocklist, VersionFilter,
+};
+use sonic_rs::{JsonValueTrait, Value};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Resolution-time security settings from `config.audit`.
+#[derive(Debug, Clone, Default)]
+pub struct SecurityPolicy {
+    /// Block versions with security advisories.
+    pub block_insecure: bool,
+    /// Block abandoned packages.
+    pub block_abandoned: bool,
+    /// Accepted advisories from `audit.ignore`.
+    pub ignore: IgnorePolicy,
+}
+
+impl SecurityPolicy {
+    /// Read the policy from composer.json, falling back to the global config.
+    ///
+    /// `disabled` is `--no-security-blocking`, which turns all blocking off.
+    pub fn load(composer: &Value, disabled: bool) -> Self {
+        if disabled {
+            return Self::default();
+        }
+
+        Self::from_audit_configs(&audit_configs(composer))
+    }
+
+    /// Merge `config.audit` sections, earlier ones taking precedence.
+    fn from_audit_configs(audit: &[AuditConfig]) -> Self {
+        Self {
+            block_insecure: audit.iter().find_map(|a| a.block_insecure).unwrap_or(true),
+            block_abandoned: audit.iter().find_map(|a| a.abandoned)
+                == Some(AbandonedHandling::Fail),
+            ignore: ignore_policy(audit),
+        }
+    }
+
+    /// Attach the advisory filter to `resolver` when blocking is enabled.
+    pub fn apply<F: PackageFetcher>(&self, resolver: Resolver<F>) -> Resolver<F> {
+        if !self.block_insecure {
+            return resolver;
+        }
+        if ComposerEnvVar::DisableNetwork.as_bool().unwrap_or(false)
+            && !audit_db::has_offline_database()
+        {
+            debug!("network disabled, security advisories are not checked");
+            return resolver;
+        }
+        match audit_db::advisory_database(false) {
+            Ok(db) => resolver.with_version_filter(Arc::new(AdvisoryFilter {
+                db,
+                ignore: self.ignore.clone(),
+            })),
+            Err(e) => {
+                warning(&format!(
+                    "Security advisories unavailable, versions are not filtered: {e}"
+                ));
+                resolver
+            }
+        }
+    }
+}
+
+/// `config.audit` sections of composer.json and the global config, project first.
+pub fn audit_configs(composer: &Value) -> Vec<AuditConfig> {
+    let global = crate::commands::config::get_global_config_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| sonic_rs::from_str::<Value>(&content).ok());
+    parse_audit_configs(
+        &[Some(composer), global.as_ref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn parse_audit_configs(configs: &[&Value]) -> Vec<AuditConfig> {
+    configs
+        .iter()
+        .filter_map(|config| config.get("config")?.get("audit"))
+        .filter_map(|audit| sonic_rs::from_value(audit).ok())
+        .collect()
+}
+
+/// Build the ignore policy from the first `audit.ignore` and
+/// `audit.ignore-severity` set.
+///
+/// Rules with an invalid `until` date are skipped with a warning, so a typo
+/// never silently accepts a risk forever.
+pub fn ignore_policy(audit: &[AuditConfig]) -> IgnorePolicy {
+    let rules = match audit.iter().find_map(|a| a.ignore.as_ref()) {
+        None => Vec::new(),
+        Some(AuditIgnore::List(targets)) => targets.iter().map(IgnoreRule::new).collect(),
+        Some(AuditIgnore::Map(entries)) => entries
+            .iter()
+            .filter_map(|(target, entry)| ignore_rule(target, entry))
+            .collect(),
+    };
+
+    let severities = audit
+        .iter()
+        .find_map(|a| a.ignore_severity.as_ref())
+        .into_iter()
+        .flatten()
+        .filter_map(|name| match name.parse::<Severity>() {
+            Ok(severity) => Some(severity),
+            Err(e) => {
+                warning(&format!("Ignoring audit.ignore-severity entry: {e}"));
+                None
+            }
+        })
+        .collect();
+
+    IgnorePolicy::new(rules).with_ignored_severities(severities)
+}
+
+fn ignore_rule(target: &str, entry: &AuditIgnoreEntry) -> Option<IgnoreRule> {
+    let details = match entry {
+        AuditIgnoreEntry::Reason(reason) => {
+            return Some(IgnoreRule {
+                reason: Some(reason.clone()),
+                ..IgnoreRule::new(target)
+            });
+        }
+        AuditIgnoreEntry::Details(details) => details,
+    };
+
+    let until = match details.until.as_deref() {
+        None => None,
+        Some(date) => {
+            let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+                warning(&format!(
+                    "Skipping audit.ignore rule for {target}: invalid until date '{date}' (expected YYYY-MM-DD)"
+                ));
+                return None;
+            };
+            Some(date)
+        }
+    };
+
+    Some(IgnoreRule {
+        target: target.to_string(),
+        reason: details.reason.clone(),
+        until,
+        scope: match details.apply.unwrap_or_default() {
+            AuditIgnoreApply::Audit => IgnoreScope::Audit,
+            AuditIgnoreApply::Block => IgnoreScope::Block,
+            AuditIgnoreApply::All => IgnoreScope::All,
+        },
+    })
+}
+
+/// Blocks every version matched by an advisory that is not ignored.
+struct AdvisoryFilter {
+    db: AdvisoryDatabase,
+    ignore: IgnorePolicy,
+}
+
+impl VersionFilter for AdvisoryFilter {
+    fn blocklist(
+        &self,
+        packages: Vec<String>,
+    ) -> Pin<Box<dyn Future<Output = VersionBlocklist> + Send + '_>> {
+        Box::pin(async move {
+            let mut blocklist = VersionBlocklist::new();
+            let ids: Vec<PackageId> = packages
+                .iter()
+                .filter_map(|name| PackageId::parse(name))
+                .collect();
+            if let Err(e) = self.db.fetch_advisories_bulk(&ids).await {
+                warning(&format!(
+                    "Could not fetch security advisories, versions are not filtered: {e}"
+                ));
+                return blocklist;
+            }
+
+            let today = Utc::now().date_naive();
+            for id in &ids {
+                let name = id.full_name();
+                // Served from the cache the bulk request just filled
+                let Ok(advisories) = self.db.fetch_advisories(id).await else {
+                    continue;
+                };
+                for advisory in &advisories {
+                    let vulnerability = advisory.vulnerability();
+                    if self
+                        .ignore
+                        .allows_install(&name, &vulnerability.advisory_id, today)
+                    {
+                        debug!(
+                            package = %name,
+                            advisory = %vulnerability.advisory_id,
+                            "advisory ignored"
+                        );
+                        continue;
+                    }
+                    let Some(constraint) = affected_versions(
+                        &name,
+                        &vulnerability.advisory_id,
+                        &vulnerability.affected_versions,
+                    ) else {
+                        continue;
+                    };
+                    blocklist.block(
+                        &name,
+                        constraint,
+                        format!(
+                            "because it is affected by security advisory {} ({})",
+                            vulnerability.advisory_id, vulnerability.title
+                        ),
+                    );
+                }
+            }
+            blocklist
+        })
+    }
+}
+
+/// Versions of `package` an advisory affects.
+///
+/// An unparsable constraint cannot be enforced, so it is reported instead of
+/// silently letting every version through.
+fn affected_versions(
+    package: &str,
+    advisory: &str,
+    constraint: &str,
+) -> Option<ComposerConstraint> {
+    let parsed = ComposerConstraint::parse(constraint);
+    if parsed.is_none() {
+        warn!(
+            package,
+            advisory,
+            constraint,
+            "cannot parse the affected versions of a security advisory, it is not enforced"
+        );
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libretto_resolver::ComposerVersion;
+
+    fn config(json: &str) -> Value {
+        sonic_rs::from_str(json).unwrap()
+    }
+
+    fn policy(configs: &[&Value]) -> SecurityPolicy {
+        SecurityPolicy::from_audit_configs(&parse_audit_configs(configs))
+    }
+
+    fn day(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn policy_defaults_to_blocking_insecure_versions() {
+        let policy = policy(&[&config("{}")]);
+        assert!(policy.block_insecure);
+        assert!(!policy.block_abandoned);
+        assert!(policy.ignore.rules().is_empty());
+    }
+
+    #[test]
+    fn project_audit_config_overrides_global() {
+        let project =
+            config(r#"{"config": {"audit": {"abandoned": "fail", "ignored": ["CVE-2024-1"]}}}"#);
+        let global = config(
+            r#"{"config": {"audit": {"block-insecure": false, "abandoned": "report", "ignored": ["PKSA-x"]}}}"#,
+        );
+        let policy = policy(&[&project, &global]);
+        assert!(!policy.block_insecure);
+        assert!(policy.block_abandoned);
+        assert_eq!(policy.ignore.rules(), [IgnoreRule::new("CVE-2024-1")]);
+    }
+
+    #[test]
+    fn ignore_map_accepts_reasons_expiry_and_scope() {
+        let project = config(
+            r#"{"config": {"audit": {
+                "ignore": {
+                    "CVE-2024-1234": {"reason": "not reachable", "until": "2026-12-31", "apply": "block"},
+                    "vendor/pkg": "dev-only tool",
+                    "CVE-2024-9999": {"until": "31/12/2026"}
+                },
+                "ignore-severity": ["low"]
+            }}}"#,
+        );
+        let ignore = policy(&[&project]).ignore;
+
+        let rules = ignore.rules();
+        assert_eq!(rules.len(), 2, "rule with an invalid date is skipped");
+        let cve = rules.iter().find(|r| r.target == "CVE-2024-1234").unwrap();
+        assert_eq!(cve.reason.as_deref(), Some("not reachable"));
+        assert_eq!(cve.until, Some(day("2026-12-31")));
+        assert_eq!(cve.scope, IgnoreScope::Block);
+
+        assert!(ignore.allows_install("other/pkg", "CVE-2024-1234", day("2026-06-01")));
+        assert!(!ignore.allows_install("other/pkg", "CVE-2024-1234", day("2027-01-01")));
+        assert!(ignore.allows_install("vendor/pkg", "CVE-2024-5", day("2027-01-01")));
+    }
+
+    #[test]
+    fn advisory_ranges_with_several_intervals_are_parsed() {
+        let affected =
+            affected_versions("vendor/pkg", "CVE-2024-1", ">=1.0,<1.2|>=2.0,<2.1").unwrap();
+        let matches = |version: &str| affected.matches(&ComposerVersion::parse(version).unwrap());
+
+        assert!(matches("1.0.0"));
+        assert!(matches("1.1.9"));
+        assert!(matches("2.0.5"));
+        assert!(!matches("1.2.0"));
+        assert!(!matches("1.5.0"));
+        assert!(!matches("2.1.0"));
+        assert!(affected_versions("vendor/pkg", "CVE-2024-2", ">=1.0,<<2").is_none());
+    }
+
+    #[test]
+    fn escape_hatch_disables_blocking() {
+        let project = config(r#"{"config": {"audit": {"abandoned": "fail"}}}"#);
+        let policy = SecurityPolicy::load(&project, true);
+        assert!(!policy.block_insecure);
+        assert!(!policy.block_abandoned);
+    }
+}
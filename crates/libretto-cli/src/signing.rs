@@ -0,0 +1,388 @@
+//! Dist archive signature verification during installs.
+//!
+//! `config.signing` selects, per vendor, whether detached signatures over
+//! downloaded archives are ignored (`off`), checked with a warning on failure
+//! (`warn`) or required (`require`). Signatures come from the lock's
+//! `dist.signature` URL, falling back to a `.sig` file next to the dist URL,
+//! and are checked against the keys in the keyring directory before the
+//! archive is extracted.
+//!
+//! Keyring files ending in `.asc`, `.gpg` or `.pgp` hold `OpenPGP`
+//! certificates; `.pub` files hold Ed25519 public keys as raw bytes, hex or
+//! base64. Keys in a `<vendor>/` subdirectory are only trusted for that
+//! vendor's packages.
+
+use crate::output::warning;
+use base64::Engine;
+use libretto_audit::{SignatureAlgorithm, SignatureVerifier, VerifiedSignature};
+use libretto_config::{SigningConfig, SigningMode};
+use sonic_rs::{JsonValueTrait, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Signing policy and trusted keys for an install run.
+#[derive(Debug, Default)]
+pub struct SigningPolicy {
+    default: SigningMode,
+    vendors: BTreeMap<String, SigningMode>,
+    keys: SignatureVerifier,
+    vendor_keys: HashMap<String, SignatureVerifier>,
+}
+
+/// Outcome of checking a package's signature, recorded in `installed.json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureCheck {
+    /// Signed by a trusted key.
+    Verified {
+        /// Signature algorithm (`pgp` or `ed25519`).
+        algorithm: &'static str,
+        /// Fingerprint or public key of the signer.
+        key: String,
+        /// Signer identity, for `OpenPGP` keys with a user ID.
+        signer: Option<String>,
+    },
+    /// No signature was published.
+    Unsigned,
+    /// A signature was published but did not verify.
+    Invalid(String),
+    /// Installed from the local cache without re-checking.
+    Unchecked,
+}
+
+impl SigningPolicy {
+    /// Load the policy from composer.json and the global config, project first.
+    ///
+    /// Relative keyring paths are resolved against `base_dir`; without a
+    /// configured keyring, `keys/` in the global config directory is used.
+    pub fn load(composer: &Value, base_dir: &Path) -> Self {
+        let global_path = crate::commands::config::get_global_config_path().ok();
+        let global = global_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| sonic_rs::from_str::<Value>(&content).ok());
+        let configs = parse_signing_configs(
+            &[Some(composer), global.as_ref()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>(),
+        );
+
+        let mut policy = Self::from_signing_configs(&configs);
+        if policy.is_off() {
+            return policy;
+        }
+
+        let keyring = configs
+            .iter()
+            .find_map(|config| config.keyring.as_deref())
+            .map(|dir| base_dir.join(dir))
+            .or_else(|| Some(global_path?.parent()?.join("keys")));
+        if let Some(keyring) = keyring {
+            policy.load_keyring(&keyring);
+        }
+        policy
+    }
+
+    fn from_signing_configs(configs: &[SigningConfig]) -> Self {
+        let mut vendors = BTreeMap::new();
+        // Later (global) entries are overridden by earlier (project) ones
+        for config in configs.iter().rev() {
+            vendors.extend(config.vendors.clone().unwrap_or_default());
+        }
+        Self {
+            default: configs
+                .iter()
+                .find_map(|config| config.default)
+                .unwrap_or_default(),
+            vendors: vendors
+                .into_iter()
+                .map(|(name, mode)| (name.to_ascii_lowercase(), mode))
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether signatures are never checked.
+    pub fn is_off(&self) -> bool {
+        self.default == SigningMode::Off && self.vendors.values().all(|m| *m == SigningMode::Off)
+    }
+
+    /// Mode for a package: its own entry, then its vendor's, then the default.
+    pub fn mode_for(&self, package: &str) -> SigningMode {
+        let package = package.to_ascii_lowercase();
+        let vendor = package.split('/').next().unwrap_or_default();
+        self.vendors
+            .get(&package)
+            .or_else(|| self.vendors.get(vendor))
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// Trust every key in `dir`, and vendor-scoped keys in its subdirectories.
+    fn load_keyring(&mut self, dir: &Path) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            debug!(keyring = %dir.display(), "no signing keyring");
+            return;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_dir() {
+                let Some(vendor) = path.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                let keys = self
+                    .vendor_keys
+                    .entry(vendor.to_ascii_lowercase())
+                    .or_default();
+                for key in key_files(&path) {
+                    add_key(keys, &key);
+                }
+            } else if is_key_file(&path) {
+                add_key(&mut self.keys, &path);
+            }
+        }
+    }
+
+    /// Verify `signature` over `archive` for `package` with the trusted keys.
+    pub fn verify(&self, package: &str, archive: &[u8], signature: &[u8]) -> SignatureCheck {
+        let signature = decode_signature(signature);
+        let vendor = package.split('/').next().unwrap_or_default();
+        let result = match self.vendor_keys.get(&vendor.to_ascii_lowercase()) {
+            Some(keys) => keys
+                .verify(archive, &signature)
+                .or_else(|_| self.keys.verify(archive, &signature)),
+            None => self.keys.verify(archive, &signature),
+        };
+        match result {
+            Ok(verified) => SignatureCheck::verified(verified),
+            Err(e) => SignatureCheck::Invalid(e.to_string()),
+        }
+    }
+}
+
+impl SignatureCheck {
+    fn verified(verified: VerifiedSignature) -> Self {
+        Self::Verified {
+            algorithm: match verified.sig_type {
+                SignatureAlgorithm::Pgp => "pgp",
+                SignatureAlgorithm::Ed25519 => "ed25519",
+            },
+            key: verified.key_id,
+            signer: verified.signer,
+        }
+    }
+
+    /// Status name recorded in `installed.json`.
+    pub const fn status(&self) -> &'static str {
+        match self {
+            Self::Verified { .. } => "verified",
+            Self::Unsigned => "unsigned",
+            Self::Invalid(_) => "invalid",
+            Self::Unchecked => "unchecked",
+        }
+    }
+
+    /// JSON object recorded for the package.
+    pub fn to_value(&self) -> Value {
+        let mut record = BTreeMap::new();
+        record.insert("status".to_string(), Value::from(self.status()));
+        match self {
+            Self::Verified {
+                algorithm,
+                key,
+                signer,
+            } => {
+                record.insert("algorithm".to_string(), Value::from(*algorithm));
+                record.insert("key".to_string(), Value::from(key.as_str()));
+                if let Some(signer) = signer {
+                    record.insert("signer".to_string(), Value::from(signer.as_str()));
+                }
+            }
+            Self::Invalid(reason) => {
+                record.insert("reason".to_string(), Value::from(reason.as_str()));
+            }
+            Self::Unsigned | Self::Unchecked => {}
+        }
+        sonic_rs::to_value(&record).unwrap_or_default()
+    }
+
+    /// Whether a recorded signature object shows a verified signature.
+    pub fn is_verified_record(record: Option<&Value>) -> bool {
+        record
+            .and_then(|r| r.get("status"))
+            .and_then(JsonValueTrait::as_str)
+            == Some("verified")
+    }
+}
+
+/// Where the detached signature of a dist archive is published.
+pub fn signature_url(dist_signature: Option<&str>, dist_url: &str) -> String {
+    dist_signature.map_or_else(
+        || {
+            let (path, query) = dist_url
+                .split_once('?')
+                .map_or((dist_url, None), |(path, query)| (path, Some(query)));
+            match query {
+                Some(query) => format!("{path}.sig?{query}"),
+                None => format!("{path}.sig"),
+            }
+        },
+        String::from,
+    )
+}
+
+fn parse_signing_configs(configs: &[&Value]) -> Vec<SigningConfig> {
+    configs
+        .iter()
+        .filter_map(|config| config.get("config")?.get("signing"))
+        .filter_map(|signing| sonic_rs::from_value(signing).ok())
+        .collect()
+}
+
+fn is_key_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("asc" | "gpg" | "pgp" | "pub")
+    )
+}
+
+fn key_files(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && is_key_file(path))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn add_key(keys: &mut SignatureVerifier, path: &Path) {
+    let Ok(data) = std::fs::read(path) else {
+        return;
+    };
+    let result = if path.extension().and_then(|e| e.to_str()) == Some("pub") {
+        keys.add_ed25519_key(&decode_binary(&data, 32))
+    } else {
+        keys.add_pgp_key(&data)
+    };
+    if let Err(e) = result {
+        warning(&format!("Ignoring signing key {}: {e}", path.display()));
+    }
+}
+
+/// Ed25519 signatures may be published as hex or base64 text.
+fn decode_signature(signature: &[u8]) -> Vec<u8> {
+    decode_binary(signature, 64)
+}
+
+/// Decode `data` as hex or base64 when that yields exactly `len` bytes.
+fn decode_binary(data: &[u8], len: usize) -> Vec<u8> {
+    if data.len() != len
+        && let Ok(text) = std::str::from_utf8(data)
+    {
+        let text = text.trim();
+        let decoded = hex::decode(text)
+            .ok()
+            .or_else(|| base64::engine::general_purpose::STANDARD.decode(text).ok());
+        if let Some(decoded) = decoded.filter(|d| d.len() == len) {
+            return decoded;
+        }
+    }
+    data.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn config(json: &str) -> Value {
+        sonic_rs::from_str(json).unwrap()
+    }
+
+    fn policy(configs: &[&Value]) -> SigningPolicy {
+        SigningPolicy::from_signing_configs(&parse_signing_configs(configs))
+    }
+
+    #[test]
+    fn signing_is_off_by_default() {
+        let policy = policy(&[&config("{}")]);
+        assert!(policy.is_off());
+        assert_eq!(policy.mode_for("acme/tool"), SigningMode::Off);
+    }
+
+    #[test]
+    fn package_and_vendor_modes_override_the_default() {
+        let project = config(
+            r#"{"config": {"signing": {"default": "warn", "vendors": {"Acme": "require", "acme/legacy": "off"}}}}"#,
+        );
+        let global = config(
+            r#"{"config": {"signing": {"default": "require", "vendors": {"other": "require"}}}}"#,
+        );
+        let policy = policy(&[&project, &global]);
+
+        assert!(!policy.is_off());
+        assert_eq!(policy.mode_for("acme/tool"), SigningMode::Require);
+        assert_eq!(policy.mode_for("acme/legacy"), SigningMode::Off);
+        assert_eq!(policy.mode_for("other/lib"), SigningMode::Require);
+        assert_eq!(policy.mode_for("third/lib"), SigningMode::Warn);
+    }
+
+    #[test]
+    fn sidecar_signature_url_keeps_the_query() {
+        assert_eq!(
+            signature_url(None, "https://example.com/dist/tool-1.0.zip"),
+            "https://example.com/dist/tool-1.0.zip.sig"
+        );
+        assert_eq!(
+            signature_url(None, "https://example.com/dist/tool.zip?token=abc"),
+            "https://example.com/dist/tool.zip.sig?token=abc"
+        );
+        assert_eq!(
+            signature_url(
+                Some("https://example.com/sig/tool"),
+                "https://example.com/tool.zip"
+            ),
+            "https://example.com/sig/tool"
+        );
+    }
+
+    #[test]
+    fn keyring_keys_are_scoped_to_their_vendor() {
+        let signing_key = SigningKey::from_bytes(&[3u8; 32]);
+        let public = hex::encode(signing_key.verifying_key().as_bytes());
+        let keyring = tempfile::tempdir().unwrap();
+        std::fs::create_dir(keyring.path().join("acme")).unwrap();
+        std::fs::write(keyring.path().join("acme/release.pub"), &public).unwrap();
+
+        let mut policy = policy(&[&config(
+            r#"{"config": {"signing": {"default": "require"}}}"#,
+        )]);
+        policy.load_keyring(keyring.path());
+
+        let signature = signing_key.sign(b"archive");
+        let encoded = base64::engine::general_purpose::STANDARD.encode(signature.to_bytes());
+        assert_eq!(
+            policy.verify("acme/tool", b"archive", encoded.as_bytes()),
+            SignatureCheck::Verified {
+                algorithm: "ed25519",
+                key: public,
+                signer: None,
+            }
+        );
+        assert_eq!(
+            policy
+                .verify("acme/tool", b"tampered", &signature.to_bytes())
+                .status(),
+            "invalid"
+        );
+        assert_eq!(
+            policy
+                .verify("other/tool", b"archive", &signature.to_bytes())
+                .status(),
+            "invalid"
+        );
+    }
+}
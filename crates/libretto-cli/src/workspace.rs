@@ -0,0 +1,612 @@
+//! Workspaces: several composer.json projects installed as one monorepo.
+//!
+//! A root manifest lists its member projects with `extra.libretto.workspaces`
+//! globs. The requirements of the root and every member are resolved
+//! together, so each package gets a single version, recorded in one
+//! `composer.lock` at the root. Each project then gets its own `vendor/` with
+//! only the packages it needs, and members it requires are symlinked in the
+//! way path repositories are.
+//!
+//! # Example Configuration
+//!
+//! ```json
+//! {
+//!     "extra": {
+//!         "libretto": {
+//!             "workspaces": ["packages/*", "apps/*"]
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::fetcher::matches_pattern;
+use anyhow::{Context, Result, bail};
+use libretto_core::is_platform_package_name;
+use libretto_resolver::{ComposerConstraint, Dependency, PackageName};
+use sonic_rs::{JsonContainerTrait, JsonValueMutTrait, JsonValueTrait, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+/// A root project and its member projects.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    /// The root project.
+    pub root: Project,
+    /// Member projects matched by the workspace globs, sorted by directory.
+    pub members: Vec<Project>,
+}
+
+/// A project taking part in a workspace.
+#[derive(Debug, Clone)]
+pub struct Project {
+    /// Package name from composer.json; unnamed projects cannot be required.
+    pub name: Option<String>,
+    /// Project directory.
+    pub dir: PathBuf,
+    /// Parsed composer.json.
+    pub manifest: Value,
+}
+
+impl Project {
+    fn load(dir: PathBuf) -> Result<Self> {
+        let path = dir.join("composer.json");
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let manifest: Value = sonic_rs::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let name = manifest
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(str::to_ascii_lowercase);
+        Ok(Self {
+            name,
+            dir,
+            manifest,
+        })
+    }
+
+    /// Name for messages: the package name, or the directory name.
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            self.dir.file_name().map_or_else(
+                || self.dir.display().to_string(),
+                |name| name.to_string_lossy().to_string(),
+            )
+        })
+    }
+
+    /// Requirements from `require`, and from `require-dev` when `dev` is set.
+    pub fn requires(&self, dev: bool) -> Vec<(String, String)> {
+        let sections: &[&str] = if dev {
+            &["require", "require-dev"]
+        } else {
+            &["require"]
+        };
+        let mut requires = Vec::new();
+        for section in sections {
+            if let Some(map) = self.manifest.get(section).and_then(|v| v.as_object()) {
+                for (name, constraint) in map {
+                    if let Some(constraint) = constraint.as_str() {
+                        requires.push((name.to_ascii_lowercase(), constraint.to_string()));
+                    }
+                }
+            }
+        }
+        requires
+    }
+
+    /// Whether the project declares any requirement.
+    pub fn has_requirements(&self) -> bool {
+        !self.requires(true).is_empty()
+    }
+}
+
+/// A package required by one or more projects of the workspace.
+#[derive(Debug, Clone, Default)]
+pub struct Requirement {
+    /// Constraints by the label of the project declaring them.
+    pub constraints: BTreeMap<String, String>,
+    /// Whether every project requires the package only for development.
+    pub dev: bool,
+}
+
+impl Workspace {
+    /// Load the workspace rooted at `root`, or `None` when `composer`
+    /// declares no `extra.libretto.workspaces`.
+    pub fn load(root: &Path, composer: &Value) -> Result<Option<Self>> {
+        let Some(patterns) = composer
+            .get("extra")
+            .and_then(|e| e.get("libretto"))
+            .and_then(|l| l.get("workspaces"))
+            .and_then(|w| w.as_array())
+        else {
+            return Ok(None);
+        };
+
+        let mut dirs = BTreeSet::new();
+        for pattern in patterns.iter().filter_map(|p| p.as_str()) {
+            dirs.extend(expand_pattern(root, pattern));
+        }
+
+        let mut members = Vec::new();
+        let mut names: HashMap<String, PathBuf> = HashMap::new();
+        for dir in dirs {
+            if dir == root || !dir.join("composer.json").is_file() {
+                continue;
+            }
+            let member = Project::load(dir)?;
+            if let Some(name) = &member.name
+                && let Some(other) = names.insert(name.clone(), member.dir.clone())
+            {
+                bail!(
+                    "Workspace members {} and {} are both named {name}",
+                    other.display(),
+                    member.dir.display()
+                );
+            }
+            members.push(member);
+        }
+
+        let root = Project {
+            name: composer
+                .get("name")
+                .and_then(|v| v.as_str())
+                .map(str::to_ascii_lowercase),
+            dir: root.to_path_buf(),
+            manifest: composer.clone(),
+        };
+        Ok(Some(Self { root, members }))
+    }
+
+    /// The member named `name`.
+    pub fn member(&self, name: &str) -> Option<&Project> {
+        self.members
+            .iter()
+            .find(|m| m.name.as_deref() == Some(name))
+    }
+
+    /// Projects to install: the members, after the root when it has requirements.
+    pub fn projects(&self) -> impl Iterator<Item = &Project> {
+        std::iter::once(&self.root)
+            .filter(|root| root.has_requirements())
+            .chain(&self.members)
+    }
+
+    /// Requirements of all projects on packages outside the workspace.
+    pub fn requirements(&self) -> BTreeMap<String, Requirement> {
+        let mut requirements: BTreeMap<String, Requirement> = BTreeMap::new();
+        for project in std::iter::once(&self.root).chain(&self.members) {
+            let prod: BTreeSet<String> = project
+                .requires(false)
+                .into_iter()
+                .map(|(n, _)| n)
+                .collect();
+            for (name, constraint) in project.requires(true) {
+                if self.member(&name).is_some() {
+                    continue;
+                }
+                let requirement = requirements.entry(name.clone()).or_insert(Requirement {
+                    constraints: BTreeMap::new(),
+                    dev: true,
+                });
+                requirement.dev &= !prod.contains(&name);
+                requirement.constraints.insert(project.label(), constraint);
+            }
+        }
+        requirements
+    }
+
+    /// Root dependencies for a single resolver run, split into production
+    /// and development, with each package's constraints intersected.
+    pub fn dependencies(&self) -> Result<(Vec<Dependency>, Vec<Dependency>)> {
+        let mut root_deps = Vec::new();
+        let mut dev_deps = Vec::new();
+        for (name, requirement) in self.requirements() {
+            if is_platform_package_name(&name) {
+                continue;
+            }
+            let Some(package) = PackageName::parse(&name) else {
+                continue;
+            };
+            let mut merged = ComposerConstraint::any();
+            for constraint in requirement.constraints.values() {
+                if let Some(parsed) = ComposerConstraint::parse(constraint) {
+                    merged = merged.intersection(&parsed);
+                }
+            }
+            if merged.is_empty() {
+                let declared: Vec<String> = requirement
+                    .constraints
+                    .iter()
+                    .map(|(project, constraint)| format!("{project} requires {constraint}"))
+                    .collect();
+                bail!(
+                    "Workspace projects require incompatible versions of {name}: {}",
+                    declared.join(", ")
+                );
+            }
+            let dependency = Dependency::new(package, merged);
+            if requirement.dev {
+                dev_deps.push(dependency);
+            } else {
+                root_deps.push(dependency);
+            }
+        }
+        Ok((root_deps, dev_deps))
+    }
+
+    /// The root manifest with the requirements and repositories of every
+    /// project merged in.
+    ///
+    /// Its content hash stamps the shared lock, so editing any project's
+    /// requirements marks the lock stale.
+    pub fn manifest(&self) -> Value {
+        let mut require = BTreeMap::new();
+        let mut require_dev = BTreeMap::new();
+        for (name, requirement) in self.requirements() {
+            let constraints: BTreeSet<&str> = requirement
+                .constraints
+                .values()
+                .map(String::as_str)
+                .collect();
+            let merged = constraints.into_iter().collect::<Vec<_>>().join(", ");
+            if requirement.dev {
+                require_dev.insert(name, merged);
+            } else {
+                require.insert(name, merged);
+            }
+        }
+
+        let mut manifest = self.root.manifest.clone();
+        if let Some(obj) = manifest.as_object_mut() {
+            obj.insert("require", sonic_rs::to_value(&require).unwrap_or_default());
+            obj.insert(
+                "require-dev",
+                sonic_rs::to_value(&require_dev).unwrap_or_default(),
+            );
+            let repositories = self.repositories();
+            if !repositories.is_empty() {
+                obj.insert("repositories", Value::from(repositories));
+            }
+        }
+        manifest
+    }
+
+    /// Repositories declared by the root and then by each member, without
+    /// duplicates. Relative path repository URLs of members are rebased onto
+    /// the root, where the shared lock is resolved.
+    fn repositories(&self) -> Vec<Value> {
+        let mut repositories: Vec<Value> = Vec::new();
+        for project in std::iter::once(&self.root).chain(&self.members) {
+            let declared: Vec<Value> = match project.manifest.get("repositories") {
+                Some(v) if v.is_array() => v.as_array().map_or_else(Vec::new, |a| a.to_vec()),
+                Some(v) => v
+                    .as_object()
+                    .map_or_else(Vec::new, |o| o.iter().map(|(_, r)| r.clone()).collect()),
+                None => Vec::new(),
+            };
+            for mut repository in declared {
+                if project.dir != self.root.dir {
+                    self.rebase_path_repository(&mut repository, &project.dir);
+                }
+                if !repositories.contains(&repository) {
+                    repositories.push(repository);
+                }
+            }
+        }
+        repositories
+    }
+
+    /// Rewrite a relative `path` repository URL of the project in `dir` so it
+    /// is relative to the workspace root.
+    fn rebase_path_repository(&self, repository: &mut Value, dir: &Path) {
+        if repository.get("type").and_then(|v| v.as_str()) != Some("path") {
+            return;
+        }
+        let Some(url) = repository.get("url").and_then(|v| v.as_str()) else {
+            return;
+        };
+        if Path::new(url).is_absolute() {
+            return;
+        }
+        let relative = dir.strip_prefix(&self.root.dir).unwrap_or(dir).join(url);
+        let rebased = relative.to_string_lossy().replace('\\', "/");
+        if let Some(obj) = repository.as_object_mut() {
+            obj.insert("url", Value::from(rebased.as_str()));
+        }
+    }
+
+    /// Locked packages and members `project` needs: its own requirements,
+    /// then those of the packages and members they pull in.
+    ///
+    /// Returns the names of the locked packages to install and of the
+    /// members to link.
+    pub fn closure(
+        &self,
+        project: &Project,
+        locked: &[Value],
+        dev: bool,
+    ) -> (BTreeSet<String>, BTreeSet<String>) {
+        let mut by_name: HashMap<String, &Value> = HashMap::new();
+        for package in locked {
+            let Some(name) = package.get("name").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let name = name.to_ascii_lowercase();
+            for section in ["replace", "provide"] {
+                for alias in package_links(package, section) {
+                    by_name.entry(alias).or_insert(package);
+                }
+            }
+            by_name.insert(name, package);
+        }
+
+        let mut packages = BTreeSet::new();
+        let mut members = BTreeSet::new();
+        let mut seen = BTreeSet::new();
+        let mut queue: Vec<String> = project.requires(dev).into_iter().map(|(n, _)| n).collect();
+
+        while let Some(name) = queue.pop() {
+            if is_platform_package_name(&name) || !seen.insert(name.clone()) {
+                continue;
+            }
+            if let Some(member) = self.member(&name) {
+                if project.name.as_deref() != Some(name.as_str()) {
+                    members.insert(name);
+                    queue.extend(member.requires(false).into_iter().map(|(n, _)| n));
+                }
+            } else if let Some(package) = by_name.get(&name)
+                && let Some(real) = package.get("name").and_then(|v| v.as_str())
+            {
+                packages.insert(real.to_ascii_lowercase());
+                queue.extend(package_links(package, "require"));
+            }
+        }
+
+        (packages, members)
+    }
+}
+
+/// Package names listed in a link section (`require`, `replace`, ...) of a lock entry.
+fn package_links(package: &Value, section: &str) -> Vec<String> {
+    package
+        .get(section)
+        .and_then(|v| v.as_object())
+        .map(|map| map.iter().map(|(n, _)| n.to_ascii_lowercase()).collect())
+        .unwrap_or_default()
+}
+
+/// Directories matching a workspace glob relative to `root`; `*` matches
+/// within one path segment.
+fn expand_pattern(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![root.to_path_buf()];
+    for segment in pattern.trim_end_matches('/').split('/') {
+        if segment.is_empty() || segment == "." {
+            continue;
+        }
+        let mut next = Vec::new();
+        for dir in &dirs {
+            if segment.contains('*') {
+                let Ok(entries) = std::fs::read_dir(dir) else {
+                    continue;
+                };
+                for entry in entries.filter_map(Result::ok) {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if !name.starts_with('.')
+                        && entry.path().is_dir()
+                        && matches_pattern(segment, &name)
+                    {
+                        next.push(entry.path());
+                    }
+                }
+            } else if dir.join(segment).is_dir() {
+                next.push(dir.join(segment));
+            }
+        }
+        dirs = next;
+    }
+    dirs
+}
+
+/// Symlink a member project into `vendor_dir` as `vendor/<name>`, replacing
+/// whatever was installed there.
+pub fn link_member(vendor_dir: &Path, name: &str, target: &Path) -> Result<()> {
+    let link = vendor_dir.join(name);
+    let parent = link.parent().unwrap_or(vendor_dir);
+    std::fs::create_dir_all(parent)?;
+
+    if let Ok(meta) = link.symlink_metadata() {
+        if meta.is_dir() {
+            std::fs::remove_dir_all(&link)?;
+        } else {
+            std::fs::remove_file(&link)?;
+        }
+    }
+
+    let parent = parent.canonicalize()?;
+    let target = target.canonicalize()?;
+    let relative = relative_path(&parent, &target);
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&relative, &link)?;
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_dir(&relative, &link)?;
+
+    Ok(())
+}
+
+/// Path to `target` relative to the directory `from`; both must be absolute.
+fn relative_path(from: &Path, target: &Path) -> PathBuf {
+    let from: Vec<Component<'_>> = from.components().collect();
+    let target: Vec<Component<'_>> = target.components().collect();
+    let common = from.iter().zip(&target).take_while(|(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &target[common..] {
+        relative.push(component);
+    }
+    relative
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, json: &str) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join("composer.json"), json).unwrap();
+    }
+
+    fn workspace(temp: &TempDir) -> Workspace {
+        let root = temp.path();
+        write(
+            root,
+            r#"{"name": "acme/monorepo", "extra": {"libretto": {"workspaces": ["packages/*", "apps/*"]}}}"#,
+        );
+        write(
+            &root.join("packages/log"),
+            r#"{"name": "acme/log", "require": {"psr/log": "^3.0"}}"#,
+        );
+        write(
+            &root.join("apps/api"),
+            r#"{"name": "acme/api", "require": {"acme/log": "*", "psr/log": "^3.0.2", "php": ">=8.1"},
+                "require-dev": {"phpunit/phpunit": "^10.0"}}"#,
+        );
+        write(
+            &root.join("apps/web"),
+            r#"{"require": {"psr/container": "^2.0"}, "require-dev": {"psr/log": "^3.0"}}"#,
+        );
+        std::fs::create_dir_all(root.join("apps/empty")).unwrap();
+
+        let composer: Value =
+            sonic_rs::from_str(&std::fs::read_to_string(root.join("composer.json")).unwrap())
+                .unwrap();
+        Workspace::load(root, &composer).unwrap().unwrap()
+    }
+
+    #[test]
+    fn loads_members_and_merges_requirements() {
+        let temp = TempDir::new().unwrap();
+        let workspace = workspace(&temp);
+
+        let labels: Vec<String> = workspace.members.iter().map(Project::label).collect();
+        assert_eq!(labels, vec!["acme/api", "web", "acme/log"]);
+        assert_eq!(workspace.projects().count(), 3);
+
+        let requirements = workspace.requirements();
+        assert!(!requirements.contains_key("acme/log"));
+        assert!(!requirements["psr/log"].dev);
+        assert!(requirements["phpunit/phpunit"].dev);
+        assert_eq!(requirements["psr/log"].constraints.len(), 3);
+
+        let (root_deps, dev_deps) = workspace.dependencies().unwrap();
+        let names: Vec<&str> = root_deps.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["psr/container", "psr/log"]);
+        assert_eq!(dev_deps.len(), 1);
+
+        let manifest = workspace.manifest();
+        assert_eq!(
+            manifest["require"]["psr/log"].as_str(),
+            Some("^3.0, ^3.0.2")
+        );
+    }
+
+    #[test]
+    fn manifest_merges_member_repositories() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        write(
+            root,
+            r#"{"extra": {"libretto": {"workspaces": ["packages/*"]}},
+                "repositories": [{"type": "composer", "url": "https://repo.acme.test"}]}"#,
+        );
+        write(
+            &root.join("packages/a"),
+            r#"{"name": "acme/a", "repositories": [
+                {"type": "composer", "url": "https://repo.acme.test"},
+                {"type": "path", "url": "../../libs/*"}
+            ]}"#,
+        );
+        write(
+            &root.join("packages/b"),
+            r#"{"name": "acme/b", "repositories": {"private": {"type": "vcs", "url": "https://git.acme.test/b.git"}}}"#,
+        );
+        let composer: Value =
+            sonic_rs::from_str(&std::fs::read_to_string(root.join("composer.json")).unwrap())
+                .unwrap();
+        let workspace = Workspace::load(root, &composer).unwrap().unwrap();
+
+        let manifest = workspace.manifest();
+        let repositories = manifest["repositories"].as_array().unwrap();
+        let urls: Vec<&str> = repositories
+            .iter()
+            .filter_map(|r| r["url"].as_str())
+            .collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://repo.acme.test",
+                "packages/a/../../libs/*",
+                "https://git.acme.test/b.git"
+            ]
+        );
+    }
+
+    #[test]
+    fn closure_follows_linked_members() {
+        let temp = TempDir::new().unwrap();
+        let workspace = workspace(&temp);
+        let locked: Vec<Value> = sonic_rs::from_str(
+            r#"[
+                {"name": "psr/log", "version": "3.0.2"},
+                {"name": "psr/container", "version": "2.0.2"},
+                {"name": "phpunit/phpunit", "version": "10.5.0", "require": {"sebastian/diff": "^5.0"}},
+                {"name": "sebastian/diff", "version": "5.1.0"}
+            ]"#,
+        )
+        .unwrap();
+
+        let api = workspace.member("acme/api").unwrap();
+        let (packages, members) = workspace.closure(api, &locked, true);
+        assert_eq!(
+            packages.into_iter().collect::<Vec<_>>(),
+            vec!["phpunit/phpunit", "psr/log", "sebastian/diff"]
+        );
+        assert_eq!(members.into_iter().collect::<Vec<_>>(), vec!["acme/log"]);
+
+        let (packages, _) = workspace.closure(api, &locked, false);
+        assert_eq!(packages.into_iter().collect::<Vec<_>>(), vec!["psr/log"]);
+    }
+
+    #[test]
+    fn incompatible_constraints_fail() {
+        let temp = TempDir::new().unwrap();
+        let workspace = workspace(&temp);
+        write(
+            &temp.path().join("apps/empty"),
+            r#"{"name": "acme/old", "require": {"psr/log": "^1.0"}}"#,
+        );
+        let workspace = Workspace::load(temp.path(), &workspace.root.manifest)
+            .unwrap()
+            .unwrap();
+
+        let err = workspace.dependencies().unwrap_err().to_string();
+        assert!(err.contains("incompatible versions of psr/log"));
+        assert!(err.contains("acme/old requires ^1.0"));
+    }
+
+    #[test]
+    fn relative_links() {
+        assert_eq!(
+            relative_path(
+                Path::new("/repo/apps/api/vendor/acme"),
+                Path::new("/repo/packages/log")
+            ),
+            PathBuf::from("../../../../packages/log")
+        );
+    }
+}
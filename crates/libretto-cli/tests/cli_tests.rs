@@ -38,6 +38,17 @@ fn test_version_output() {
         .stdout(predicate::str::contains("libretto"));
 }
 
+#[test]
+fn test_log_format_rejected_on_other_commands() {
+    libretto()
+        .args(["--log-format", "ndjson", "search", "psr/log"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "only supported by install, update, require and remove",
+        ));
+}
+
 #[test]
 fn test_install_help() {
     libretto()
@@ -272,6 +283,43 @@ fn test_validate_missing_name() {
     let _ = libretto().arg("validate").current_dir(temp.path()).assert();
 }
 
+#[test]
+fn test_validate_check_lock_stale() {
+    let temp = TempDir::new().expect("Failed to create temp dir");
+
+    std::fs::write(
+        temp.path().join("composer.json"),
+        r#"{"name": "test/project", "require": {"psr/log": "^3.0"}}"#,
+    )
+    .expect("Failed to write composer.json");
+    std::fs::write(
+        temp.path().join("composer.lock"),
+        r#"{
+            "_readme": [],
+            "content-hash": "d751713988987e9331980363e24189ce",
+            "packages": [],
+            "packages-dev": [],
+            "aliases": [],
+            "minimum-stability": "stable",
+            "stability-flags": [],
+            "prefer-stable": false,
+            "prefer-lowest": false,
+            "platform": [],
+            "platform-dev": [],
+            "plugin-api-version": "2.6.0"
+        }"#,
+    )
+    .expect("Failed to write composer.lock");
+
+    libretto()
+        .args(["validate", "--check-lock"])
+        .current_dir(temp.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("not up to date"))
+        .stdout(predicate::str::contains("psr/log"));
+}
+
 // ========== Show Command Tests ==========
 
 #[test]
@@ -412,6 +460,108 @@ fn test_quiet_flag() {
     libretto().args(["--quiet", "--help"]).assert().success();
 }
 
+// ========== Script Subcommand Tests ==========
+
+#[test]
+fn test_script_as_subcommand() {
+    let temp = TempDir::new().expect("Failed to create temp dir");
+    std::fs::write(
+        temp.path().join("composer.json"),
+        r#"{
+            "name": "test/project",
+            "scripts": {
+                "greet": ["Composer\\Config::disableProcessTimeout", "echo hello @additional_args done"],
+                "fail": "exit 3"
+            },
+            "scripts-aliases": { "greet": ["hi"] },
+            "scripts-descriptions": { "greet": "Say hello" }
+        }"#,
+    )
+    .expect("Failed to write composer.json");
+
+    libretto()
+        .args(["hi", "--", "--name", "world"])
+        .current_dir(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hello --name world done"));
+
+    libretto()
+        .arg("fail")
+        .current_dir(temp.path())
+        .assert()
+        .code(3);
+
+    libretto()
+        .arg("--help")
+        .current_dir(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Say hello [aliases: hi]"));
+
+    libretto()
+        .arg("instal")
+        .current_dir(temp.path())
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("unrecognized subcommand 'instal'"))
+        .stderr(predicate::str::contains("'install'"));
+}
+
+#[test]
+fn test_workspace_list_and_run() {
+    let temp = TempDir::new().expect("Failed to create temp dir");
+    let write = |dir: &str, json: &str| {
+        let dir = temp.path().join(dir);
+        std::fs::create_dir_all(&dir).expect("Failed to create member dir");
+        std::fs::write(dir.join("composer.json"), json).expect("Failed to write composer.json");
+    };
+    write(
+        ".",
+        r#"{"name": "acme/monorepo", "extra": {"libretto": {"workspaces": ["packages/*"]}}}"#,
+    );
+    write(
+        "packages/api",
+        r#"{"name": "acme/api", "require": {"acme/log": "*"}, "scripts": {"check": "echo api @additional_args"}}"#,
+    );
+    write(
+        "packages/log",
+        r#"{"name": "acme/log", "autoload": {"psr-4": {"Acme\\Log\\": "src/"}}}"#,
+    );
+
+    libretto()
+        .args(["workspace", "list"])
+        .current_dir(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("acme/api"))
+        .stdout(predicate::str::contains("packages/log"));
+
+    libretto()
+        .args(["workspace", "run", "check", "--", "--strict"])
+        .current_dir(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("api --strict"));
+
+    libretto()
+        .arg("install")
+        .current_dir(temp.path())
+        .assert()
+        .success();
+    assert!(temp.path().join("composer.lock").exists());
+    assert!(
+        temp.path()
+            .join("packages/api/vendor/acme/log/composer.json")
+            .exists()
+    );
+    assert!(
+        temp.path()
+            .join("packages/api/vendor/autoload.php")
+            .exists()
+    );
+}
+
 // ========== Working Directory Tests ==========
 
 #[test]
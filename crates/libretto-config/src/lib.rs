@@ -96,10 +96,12 @@ pub use env::{ComposerEnvVar, EnvConfig, parse_byte_size, parse_duration_secs};
 pub use error::{ConfigError, Result};
 pub use loader::{CliOverrides, ConfigLoader, ConfigSource};
 pub use types::{
-    AllowPlugins, ArchiveFormat, AutoloadConfig, AutoloadPath, BinCompat, ComposerConfig,
+    AbandonedHandling, AllowPlugins, ArchiveFormat, AuditConfig, AuditIgnore, AuditIgnoreApply,
+    AuditIgnoreDetails, AuditIgnoreEntry, AutoloadConfig, AutoloadPath, BinCompat, ComposerConfig,
     ComposerManifest, DiscardChanges, GitHubProtocol, PlatformCheck, PreferredInstall,
-    PreferredInstallConfig, Repositories, RepositoryConfig, RepositoryDefinition, RepositoryType,
-    ResolvedConfig, Scripts, ScriptsConfig, Stability, StoreAuths,
+    PreferredInstallConfig, Repositories, RepositoryConfig, RepositoryDefinition, RepositoryMirror,
+    RepositoryType, ResolvedConfig, SandboxSettings, Scripts, ScriptsConfig, SigningConfig,
+    SigningMode, Stability, StoreAuths,
 };
 pub use validate::{Severity, ValidationIssue, ValidationResult, Validator};
 
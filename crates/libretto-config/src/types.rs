@@ -290,6 +290,14 @@ pub struct ComposerConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub audit: Option<AuditConfig>,
 
+    /// Package signature verification policy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningConfig>,
+
+    /// OS-level isolation of scripts and PHP plugins.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxSettings>,
+
     /// Extra configuration for extensions.
     #[serde(flatten)]
     pub extra: BTreeMap<String, sonic_rs::Value>,
@@ -360,12 +368,66 @@ impl Default for AllowPlugins {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case", default)]
 pub struct AuditConfig {
-    /// Ignored advisories by package name.
+    /// Ignored advisories, by advisory ID, CVE or package name.
+    #[serde(alias = "ignored", skip_serializing_if = "Option::is_none")]
+    pub ignore: Option<AuditIgnore>,
+    /// Severities ignored in audit reports (e.g. `["low"]`).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub ignored: Option<Vec<String>>,
+    pub ignore_severity: Option<Vec<String>>,
     /// Abandoned package handling.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub abandoned: Option<AbandonedHandling>,
+    /// Skip versions with security advisories when resolving (default: true).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_insecure: Option<bool>,
+}
+
+/// Ignored advisories: a list of IDs, or a map of ID to a reason or details.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AuditIgnore {
+    /// Advisory IDs, CVEs or package names.
+    List(Vec<String>),
+    /// Advisory ID, CVE or package name to reason or details.
+    Map(BTreeMap<String, AuditIgnoreEntry>),
+}
+
+/// Reason or details for an ignored advisory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AuditIgnoreEntry {
+    /// Reason only.
+    Reason(String),
+    /// Reason, expiry and scope.
+    Details(AuditIgnoreDetails),
+}
+
+/// Details for an ignored advisory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditIgnoreDetails {
+    /// Why the risk is accepted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Last day the ignore applies (`YYYY-MM-DD`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// Where the ignore applies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apply: Option<AuditIgnoreApply>,
+}
+
+/// Where an ignored advisory is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditIgnoreApply {
+    /// Only in audit reports.
+    Audit,
+    /// Only when blocking insecure versions during resolution.
+    Block,
+    /// Everywhere.
+    #[default]
+    All,
 }
 
 /// Abandoned package handling.
@@ -381,6 +443,58 @@ pub enum AbandonedHandling {
     Fail,
 }
 
+/// Package signature verification policy.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct SigningConfig {
+    /// Mode for vendors without their own entry (default: `off`).
+    #[serde(alias = "mode", skip_serializing_if = "Option::is_none")]
+    pub default: Option<SigningMode>,
+    /// Mode per vendor (e.g. `acme`) or per package (e.g. `acme/tool`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendors: Option<BTreeMap<String, SigningMode>>,
+    /// Directory of trusted keys; keys in a `<vendor>/` subdirectory are
+    /// only trusted for that vendor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyring: Option<String>,
+}
+
+/// OS-level isolation of scripts and PHP plugins.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct SandboxSettings {
+    /// Run lifecycle scripts in the sandbox (default: false).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scripts: Option<bool>,
+    /// Address space limit of sandboxed processes, e.g. `512M`; `0` removes
+    /// the limit (default: 2G).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_limit: Option<String>,
+    /// Extra paths sandboxed processes may read, relative to the project.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_paths: Option<Vec<String>>,
+    /// Extra paths sandboxed processes may write, relative to the project.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub write_paths: Option<Vec<String>>,
+    /// Fail instead of warning when the system cannot enforce an isolation
+    /// feature (default: false).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require: Option<bool>,
+}
+
+/// How dist archive signatures are enforced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningMode {
+    /// Do not check signatures.
+    #[default]
+    Off,
+    /// Verify signatures and warn when missing or invalid.
+    Warn,
+    /// Refuse to install packages without a valid signature.
+    Require,
+}
+
 /// Scripts configuration section.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
@@ -505,6 +619,21 @@ pub struct RepositoryDefinition {
     /// Exclude packages matching these patterns.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exclude: Option<Vec<String>>,
+    /// Dist mirrors to use for this repository's packages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirrors: Option<Vec<RepositoryMirror>>,
+}
+
+/// Dist mirror of a Composer repository.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RepositoryMirror {
+    /// Dist URL template (`%package%`, `%version%`, `%reference%`, `%type%`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dist_url: Option<String>,
+    /// Try this mirror before the canonical dist URL.
+    #[serde(default)]
+    pub preferred: bool,
 }
 
 /// Repository options.
@@ -19,6 +19,7 @@ pub struct HttpClient {
     client: Client,
     config: Arc<DownloadConfig>,
     auth: Option<Arc<AuthConfig>>,
+    extra_headers: Option<Arc<HeaderMap>>,
 }
 
 impl std::fmt::Debug for HttpClient {
@@ -27,6 +28,7 @@ impl std::fmt::Debug for HttpClient {
             .field("client", &"reqwest::Client")
             .field("config", &self.config)
             .field("auth", &self.auth.is_some())
+            .field("extra_headers", &self.extra_headers.is_some())
             .finish()
     }
 }
@@ -80,9 +82,20 @@ impl HttpClient {
             client,
             config: Arc::new(config),
             auth: auth.map(Arc::new),
+            extra_headers: None,
         })
     }
 
+    /// Send additional headers with every request.
+    ///
+    /// Used for credentials resolved outside of auth.json; these headers
+    /// take precedence over any auth.json credentials for the same name.
+    #[must_use]
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.extra_headers = Some(Arc::new(headers));
+        self
+    }
+
     /// Create a client with default configuration.
     ///
     /// # Errors
@@ -108,8 +121,7 @@ impl HttpClient {
     /// # Errors
     /// Returns error if request fails.
     pub async fn get(&self, url: &Url) -> Result<Response> {
-        let mut headers = self.default_headers();
-        self.add_auth_headers(&mut headers, url);
+        let headers = self.request_headers(url);
 
         debug!(url = %url, "GET request starting");
 
@@ -146,8 +158,7 @@ impl HttpClient {
     /// # Errors
     /// Returns error if request fails.
     pub async fn get_range(&self, url: &Url, start: u64, end: Option<u64>) -> Result<Response> {
-        let mut headers = self.default_headers();
-        self.add_auth_headers(&mut headers, url);
+        let mut headers = self.request_headers(url);
 
         let range_value = end.map_or_else(
             || format!("bytes={start}-"),
@@ -177,8 +188,7 @@ impl HttpClient {
     /// # Errors
     /// Returns error if request fails.
     pub async fn head(&self, url: &Url) -> Result<Response> {
-        let mut headers = self.default_headers();
-        self.add_auth_headers(&mut headers, url);
+        let headers = self.request_headers(url);
 
         trace!(url = %url, "HEAD request");
 
@@ -233,6 +243,17 @@ impl HttpClient {
         headers
     }
 
+    fn request_headers(&self, url: &Url) -> HeaderMap {
+        let mut headers = self.default_headers();
+        self.add_auth_headers(&mut headers, url);
+        if let Some(ref extra) = self.extra_headers {
+            for (name, value) in extra.iter() {
+                headers.insert(name, value.clone());
+            }
+        }
+        headers
+    }
+
     fn add_auth_headers(&self, headers: &mut HeaderMap, url: &Url) {
         let Some(ref auth) = self.auth else {
             return;
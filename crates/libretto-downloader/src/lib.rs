@@ -10,6 +10,7 @@
 //! - **Streaming decompression** for ZIP, tar.gz, tar.bz2, tar.xz, tar.zst
 //! - **Progress tracking** with multi-progress bars
 //! - **Retry with exponential backoff** and mirror fallback
+//! - **Dist mirror templates** with per-mirror success and latency stats
 //! - **Bandwidth throttling** using token bucket algorithm
 //! - **VCS support** for Git, SVN, and Mercurial
 //! - **Authentication** from auth.json (HTTP Basic, Bearer, OAuth)
@@ -88,6 +89,7 @@ mod client;
 pub mod config;
 mod error;
 mod extract;
+mod mirror;
 mod parallel;
 mod progress;
 mod retry;
@@ -108,6 +110,7 @@ pub use config::{
 };
 pub use error::{DownloadError, Result};
 pub use extract::{ExtractOptions, ExtractionResult, Extractor, extract, extract_with_strip};
+pub use mirror::{DistMirror, MirrorStats, MirrorStatsSnapshot};
 pub use parallel::{DownloadStats, ParallelDownloader, ParallelDownloaderBuilder, StatsSnapshot};
 pub use progress::{DownloadProgress, ProgressStats, ProgressTracker};
 pub use retry::{CircuitBreaker, RetryConfig, with_mirrors, with_retry};
@@ -0,0 +1,202 @@
+//! Dist mirrors advertised by Composer repositories.
+//!
+//! Repositories list mirrors in `packages.json` as URL templates using
+//! `%package%`, `%version%`, `%reference%` and `%type%` placeholders.
+//! Preferred mirrors are tried before the canonical dist URL, the others
+//! only after it failed.
+
+use crate::checksum::bytes_to_hex;
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// A dist mirror URL template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistMirror {
+    /// URL template with Composer placeholders.
+    pub url: String,
+    /// Try this mirror before the canonical dist URL.
+    pub preferred: bool,
+}
+
+impl DistMirror {
+    /// Create a new mirror.
+    #[must_use]
+    pub fn new(url: impl Into<String>, preferred: bool) -> Self {
+        Self {
+            url: url.into(),
+            preferred,
+        }
+    }
+
+    /// Expand the template for a package, following Composer's `ComposerMirror::processUrl`.
+    ///
+    /// References that are not plain hex and versions containing `/` are
+    /// replaced by their MD5 so they stay URL-safe.
+    #[must_use]
+    pub fn process_url(
+        &self,
+        package: &str,
+        version: &str,
+        reference: Option<&str>,
+        dist_type: &str,
+    ) -> String {
+        use md5::{Digest, Md5};
+
+        let md5 = |value: &str| bytes_to_hex(&Md5::digest(value.as_bytes()));
+        let reference = reference.map_or_else(String::new, |reference| {
+            if reference
+                .bytes()
+                .all(|b| matches!(b, b'a'..=b'f' | b'0'..=b'9'))
+            {
+                reference.to_string()
+            } else {
+                md5(reference)
+            }
+        });
+        let version = if version.contains('/') {
+            md5(version)
+        } else {
+            version.to_string()
+        };
+
+        self.url
+            .replace("%package%", package)
+            .replace("%version%", &version)
+            .replace("%reference%", &reference)
+            .replace("%type%", dist_type)
+    }
+}
+
+/// Success and latency counters for one mirror.
+#[derive(Debug, Default)]
+struct MirrorCounters {
+    attempts: AtomicU64,
+    successes: AtomicU64,
+    latency_micros: AtomicU64,
+}
+
+/// Per-mirror download statistics.
+///
+/// Entries are keyed by mirror host so every package served by the same
+/// mirror is accounted together.
+#[derive(Debug, Default)]
+pub struct MirrorStats {
+    mirrors: DashMap<String, MirrorCounters>,
+}
+
+impl MirrorStats {
+    /// Create empty statistics.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one download attempt against `mirror`.
+    pub fn record(&self, mirror: &str, success: bool, elapsed: Duration) {
+        let counters = self.mirrors.entry(mirror.to_string()).or_default();
+        counters.attempts.fetch_add(1, Ordering::Relaxed);
+        if success {
+            counters.successes.fetch_add(1, Ordering::Relaxed);
+        }
+        counters.latency_micros.fetch_add(
+            u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Snapshot of all mirrors, sorted by name.
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<MirrorStatsSnapshot> {
+        let mut snapshot: Vec<_> = self
+            .mirrors
+            .iter()
+            .map(|entry| MirrorStatsSnapshot {
+                mirror: entry.key().clone(),
+                attempts: entry.attempts.load(Ordering::Relaxed),
+                successes: entry.successes.load(Ordering::Relaxed),
+                total_latency: Duration::from_micros(entry.latency_micros.load(Ordering::Relaxed)),
+            })
+            .collect();
+        snapshot.sort_by(|a, b| a.mirror.cmp(&b.mirror));
+        snapshot
+    }
+}
+
+/// Statistics for a single mirror.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MirrorStatsSnapshot {
+    /// Mirror host.
+    pub mirror: String,
+    /// Download attempts.
+    pub attempts: u64,
+    /// Successful downloads.
+    pub successes: u64,
+    /// Time spent on all attempts.
+    pub total_latency: Duration,
+}
+
+impl MirrorStatsSnapshot {
+    /// Mean latency per attempt.
+    #[must_use]
+    pub fn average_latency(&self) -> Duration {
+        u32::try_from(self.attempts)
+            .ok()
+            .filter(|&attempts| attempts > 0)
+            .map_or(Duration::ZERO, |attempts| self.total_latency / attempts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use md5::{Digest, Md5};
+
+    #[test]
+    fn process_url_expands_placeholders() {
+        let mirror = DistMirror::new(
+            "https://cache.example/dists/%package%/%version%/r%reference%.%type%",
+            true,
+        );
+        assert_eq!(
+            mirror.process_url(
+                "psr/log",
+                "3.0.0",
+                Some("f16e1d5863e37f8d8c2a01719f5b34baa2b714d3"),
+                "zip"
+            ),
+            "https://cache.example/dists/psr/log/3.0.0/rf16e1d5863e37f8d8c2a01719f5b34baa2b714d3.zip"
+        );
+    }
+
+    #[test]
+    fn process_url_hashes_unsafe_segments() {
+        let mirror = DistMirror::new("https://m.example/%package%/%version%/%reference%", false);
+        assert_eq!(
+            mirror.process_url("acme/lib", "dev-feature/x", Some("trunk@42"), "zip"),
+            format!(
+                "https://m.example/acme/lib/{}/{}",
+                bytes_to_hex(&Md5::digest(b"dev-feature/x")),
+                bytes_to_hex(&Md5::digest(b"trunk@42"))
+            )
+        );
+        assert_eq!(
+            mirror.process_url("acme/lib", "1.0.0", None, "zip"),
+            "https://m.example/acme/lib/1.0.0/"
+        );
+    }
+
+    #[test]
+    fn stats_accumulate_per_mirror() {
+        let stats = MirrorStats::new();
+        stats.record("b.example", true, Duration::from_millis(30));
+        stats.record("a.example", false, Duration::from_millis(10));
+        stats.record("b.example", false, Duration::from_millis(10));
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[0].mirror, "a.example");
+        assert_eq!((snapshot[1].attempts, snapshot[1].successes), (2, 1));
+        assert_eq!(snapshot[1].average_latency(), Duration::from_millis(20));
+    }
+}
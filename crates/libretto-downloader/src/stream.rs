@@ -84,6 +84,15 @@ impl StreamDownloader {
         state.total_size = total_size;
         state.resume_supported = resume_supported && self.config.resume_downloads;
 
+        // A partial file at least as large as the remote one cannot be resumed
+        if let (Some(size), Some(partial)) = (total_size, state.temp_path.as_ref())
+            && state.downloaded >= size
+        {
+            debug!(url = %url, partial = %partial.display(), "discarding stale partial download");
+            let _ = std::fs::remove_file(partial);
+            state.downloaded = 0;
+        }
+
         if let Some(size) = total_size {
             progress.set_total(size);
         }
@@ -97,8 +106,9 @@ impl StreamDownloader {
             debug!(url = %url, "using mmap download");
             self.download_mmap(url, dest, &state, checksums, progress)
                 .await
-        } else if state.downloaded > 0 && state.resume_supported {
-            debug!(url = %url, "using resume download");
+        } else if state.resume_supported {
+            // Stream into the `.partial` file so an interrupted transfer can be resumed
+            debug!(url = %url, offset = state.downloaded, "using resumable download");
             self.download_resume(url, dest, &state, checksums, progress)
                 .await
         } else {
@@ -212,32 +222,49 @@ impl StreamDownloader {
     ) -> Result<DownloadedFile> {
         debug!(url = %url, offset = state.downloaded, "resuming download");
 
-        // Open existing file for appending
         let temp_path = state
             .temp_path
             .as_ref()
             .map_or_else(|| dest.with_extension("partial"), std::clone::Clone::clone);
+        if let Some(parent) = temp_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| DownloadError::io(parent, e))?;
+        }
 
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
+        let mut offset = state.downloaded;
+        let response = if offset > 0 {
+            self.client.get_range(url, offset, None).await?
+        } else {
+            self.client.get(url).await?
+        };
+
+        // Servers may ignore the Range header and send the whole body
+        if offset > 0 && response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            debug!(url = %url, status = %response.status(), "range ignored, restarting download");
+            offset = 0;
+        }
+
+        // Append to the partial file when resuming, otherwise start it fresh
+        let mut options = OpenOptions::new();
+        if offset > 0 {
+            options.create(true).append(true);
+        } else {
+            options.create(true).write(true).truncate(true);
+        }
+        let mut file = options
             .open(&temp_path)
             .map_err(|e| DownloadError::io(&temp_path, e))?;
 
-        // Get Range response
-        let response = self.client.get_range(url, state.downloaded, None).await?;
-
         let mut stream = response.bytes_stream();
-        let mut downloaded = state.downloaded;
+        let mut downloaded = offset;
         let mut hasher = MultiHasher::for_checksums(checksums);
 
         // Hash already downloaded portion
-        if state.downloaded > 0 {
+        if offset > 0 {
             let existing =
                 std::fs::File::open(&temp_path).map_err(|e| DownloadError::io(&temp_path, e))?;
             let mut reader = std::io::BufReader::with_capacity(128 * 1024, existing);
             let mut buf = [0u8; 128 * 1024];
-            let mut to_read = state.downloaded as usize;
+            let mut to_read = offset as usize;
 
             while to_read > 0 {
                 let read_len = to_read.min(buf.len());
@@ -281,7 +308,7 @@ impl StreamDownloader {
             path: dest.to_path_buf(),
             size: downloaded,
             checksums: checksums_result,
-            resumed: state.downloaded > 0,
+            resumed: offset > 0,
         })
     }
 
@@ -400,7 +427,12 @@ impl StreamDownloader {
 
         match tokio::time::timeout(timeout_duration, head_future).await {
             Ok(Ok(response)) => {
-                let size = response.content_length();
+                // `Response::content_length` reports the (empty) HEAD body, so read the header
+                let size = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_LENGTH)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok());
                 let resume = response
                     .headers()
                     .get("accept-ranges")
@@ -472,4 +504,94 @@ mod tests {
         let debug = format!("{file:?}");
         assert!(debug.contains("DownloadedFile"));
     }
+
+    const BODY: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    fn downloader() -> StreamDownloader {
+        StreamDownloader::new(
+            HttpClient::with_defaults().unwrap(),
+            BandwidthThrottler::unlimited(),
+        )
+    }
+
+    fn progress() -> DownloadProgress {
+        crate::progress::ProgressTracker::disabled().start_download("test", "test", None)
+    }
+
+    async fn server_with_ranges() -> wiremock::MockServer {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/pkg.zip"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("accept-ranges", "bytes")
+                    .insert_header("content-length", BODY.len().to_string().as_str()),
+            )
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn resumes_partial_download() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = server_with_ranges().await;
+        Mock::given(method("GET"))
+            .and(path("/pkg.zip"))
+            .and(header("range", "bytes=10-"))
+            .respond_with(ResponseTemplate::new(206).set_body_bytes(&BODY[10..]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("pkg.zip");
+        std::fs::write(dest.with_extension("partial"), &BODY[..10]).unwrap();
+
+        let url = Url::parse(&format!("{}/pkg.zip", server.uri())).unwrap();
+        let expected = [ExpectedChecksum::sha1(crate::checksum::bytes_to_hex(
+            &<sha1::Sha1 as sha1::Digest>::digest(BODY),
+        ))];
+        let file = downloader()
+            .download(&url, &dest, &expected, &progress())
+            .await
+            .unwrap();
+
+        assert!(file.resumed);
+        assert_eq!(file.size, BODY.len() as u64);
+        file.verify(&expected, "test").unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), BODY);
+        assert!(!dest.with_extension("partial").exists());
+    }
+
+    #[tokio::test]
+    async fn restarts_when_range_is_ignored() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = server_with_ranges().await;
+        Mock::given(method("GET"))
+            .and(path("/pkg.zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(BODY))
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("pkg.zip");
+        std::fs::write(dest.with_extension("partial"), b"garbage").unwrap();
+
+        let url = Url::parse(&format!("{}/pkg.zip", server.uri())).unwrap();
+        let file = downloader()
+            .download(&url, &dest, &[], &progress())
+            .await
+            .unwrap();
+
+        assert!(!file.resumed);
+        assert_eq!(std::fs::read(&dest).unwrap(), BODY);
+    }
 }
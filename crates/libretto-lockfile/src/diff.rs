@@ -442,8 +442,7 @@ pub fn compute_diff(old: &ComposerLock, new: &ComposerLock) -> LockDiff {
     diff.packages.extend(dev_changes);
 
     // Sort changes by name
-    diff.packages
-        .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    diff.packages.sort_by_key(|c| c.name.to_lowercase());
 
     diff
 }
@@ -11,6 +11,7 @@ use digest::Digest;
 use md5::Md5;
 use moka::sync::Cache;
 use rayon::prelude::*;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
 use std::collections::BTreeMap;
 use std::io::Read;
 use std::sync::LazyLock;
@@ -70,9 +71,56 @@ impl ContentHasher {
         bytes_to_hex(&result)
     }
 
+    /// Compute the content-hash of a composer.json document.
+    ///
+    /// Matches Composer's `Locker::getContentHash` exactly:
+    /// - Keep the relevant top-level keys plus `config.platform`, skipping nulls
+    /// - Sort them by key, leaving nested values in manifest order
+    /// - Encode like PHP's `json_encode` with default flags
+    /// - MD5 hash the result
+    #[must_use]
+    pub fn compute_manifest_hash(manifest: &Value) -> String {
+        let mut relevant: Vec<(&str, String)> = CONTENT_HASH_KEYS
+            .iter()
+            .filter_map(|&key| {
+                // Composer only hashes keys that pass `isset()`
+                let value = manifest.get(key).filter(|value| !value.is_null())?;
+                Some((key, php_json_encode(value)))
+            })
+            .collect();
+        if let Some(platform) = manifest
+            .get("config")
+            .and_then(|c| c.get("platform"))
+            .filter(|platform| !platform.is_null())
+        {
+            relevant.push((
+                "config",
+                format!("{{\"platform\":{}}}", php_json_encode(platform)),
+            ));
+        }
+        relevant.sort_by(|a, b| a.0.cmp(b.0));
+
+        let json = if relevant.is_empty() {
+            // PHP encodes an empty array as a list
+            "[]".to_string()
+        } else {
+            let pairs: Vec<String> = relevant
+                .iter()
+                .map(|(key, value)| format!("\"{key}\":{value}"))
+                .collect();
+            format!("{{{}}}", pairs.join(","))
+        };
+
+        let mut hasher = Self::new();
+        hasher.update(json.as_bytes());
+        hasher.finalize()
+    }
+
     /// Compute content-hash from composer.json dependencies.
     ///
-    /// Matches Composer's exact algorithm:
+    /// Only covers the dependency settings, so it differs from Composer's
+    /// hash for manifests with a name, repositories or extra; use
+    /// [`Self::compute_manifest_hash`] when the manifest is available.
     /// - Sort require/require-dev alphabetically
     /// - Serialize to JSON without whitespace
     /// - MD5 hash the result
@@ -224,6 +272,77 @@ impl ContentHasher {
     }
 }
 
+/// Top-level composer.json keys that feed Composer's content-hash.
+const CONTENT_HASH_KEYS: [&str; 11] = [
+    "name",
+    "version",
+    "require",
+    "require-dev",
+    "conflict",
+    "replace",
+    "provide",
+    "minimum-stability",
+    "prefer-stable",
+    "repositories",
+    "extra",
+];
+
+/// Encode a JSON value like PHP's `json_encode` with default flags.
+///
+/// Objects decoded by PHP as associative arrays encode as `[]` when empty,
+/// slashes are escaped and non-ASCII characters become `\uXXXX` escapes.
+fn php_json_encode(value: &Value) -> String {
+    if let Some(s) = value.as_str() {
+        return php_escape_string(s);
+    }
+    if let Some(items) = value.as_array() {
+        let items: Vec<String> = items.iter().map(php_json_encode).collect();
+        return format!("[{}]", items.join(","));
+    }
+    if let Some(object) = value.as_object() {
+        if object.is_empty() {
+            return "[]".to_string();
+        }
+        let pairs: Vec<String> = object
+            .iter()
+            .map(|(key, value)| format!("{}:{}", php_escape_string(key), php_json_encode(value)))
+            .collect();
+        return format!("{{{}}}", pairs.join(","));
+    }
+    // null, booleans and numbers encode the same way in both
+    sonic_rs::to_string(value).unwrap_or_default()
+}
+
+/// Quote a string the way PHP's `json_encode` does with default flags.
+fn php_escape_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '/' => result.push_str("\\/"),
+            '\u{8}' => result.push_str("\\b"),
+            '\u{c}' => result.push_str("\\f"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_ascii_control() && c != '\u{7f}' => {
+                result.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c if c.is_ascii() => result.push(c),
+            c => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    result.push_str(&format!("\\u{unit:04x}"));
+                }
+            }
+        }
+    }
+    result.push('"');
+    result
+}
+
 /// Convert `BTreeMap` to minimal JSON.
 fn btree_to_json(map: &BTreeMap<String, String>) -> String {
     let pairs: Vec<String> = map
@@ -498,6 +617,57 @@ pub fn verify_hash(actual: &str, expected: &str) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_manifest_hash_empty() {
+        // Composer's hash for an empty composer.json: MD5 of "[]"
+        let manifest: Value = sonic_rs::from_str("{}").unwrap();
+        assert_eq!(
+            ContentHasher::compute_manifest_hash(&manifest),
+            "d751713988987e9331980363e24189ce"
+        );
+    }
+
+    #[test]
+    fn test_manifest_hash_matches_php_encoding() {
+        let manifest: Value = sonic_rs::from_str(
+            r#"{
+                "require": {"php": ">=8.1", "psr/log": "^3.0"},
+                "name": "acme/café",
+                "description": "ignored",
+                "extra": {},
+                "config": {"sort-packages": true, "platform": {"php": "8.1.0"}}
+            }"#,
+        )
+        .unwrap();
+        let expected = r#"{"config":{"platform":{"php":"8.1.0"}},"extra":[],"name":"acme\/caf\u00e9","require":{"php":">=8.1","psr\/log":"^3.0"}}"#;
+
+        let mut hasher = ContentHasher::new();
+        hasher.update(expected.as_bytes());
+        assert_eq!(
+            ContentHasher::compute_manifest_hash(&manifest),
+            hasher.finalize()
+        );
+    }
+
+    #[test]
+    fn test_manifest_hash_skips_null_keys() {
+        // Composer's hash for this manifest, whose null keys fail `isset()`
+        let manifest: Value = sonic_rs::from_str(
+            r#"{
+                "name": "acme/app",
+                "version": null,
+                "require": {"psr/log": "^3.0"},
+                "extra": null,
+                "config": {"platform": null}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            ContentHasher::compute_manifest_hash(&manifest),
+            "50b0cbd9aa7709b77c3613677834b3b6"
+        );
+    }
+
     #[test]
     fn test_content_hash_empty() {
         let hash = ContentHasher::compute_content_hash(
@@ -264,27 +264,13 @@ impl LockfileManager {
         ))
     }
 
-    /// Check for drift between lock file and composer.json.
+    /// Check for drift between lock file and a parsed composer.json.
     ///
     /// # Errors
     /// Returns error if lock file cannot be read.
-    pub fn check_drift(
-        &self,
-        require: &BTreeMap<String, String>,
-        require_dev: &BTreeMap<String, String>,
-        minimum_stability: Option<&str>,
-        prefer_stable: Option<bool>,
-        platform: &BTreeMap<String, String>,
-    ) -> Result<DriftResult> {
+    pub fn check_drift(&self, manifest: &sonic_rs::Value) -> Result<DriftResult> {
         let lock = self.read()?;
-        Ok(DriftDetector::check_drift(
-            &lock,
-            require,
-            require_dev,
-            minimum_stability,
-            prefer_stable,
-            platform,
-        ))
+        Ok(DriftDetector::check_drift(&lock, manifest))
     }
 
     /// Compute diff between current and another lock file.
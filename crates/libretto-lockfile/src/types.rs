@@ -35,7 +35,7 @@ pub struct ComposerLock {
     pub minimum_stability: String,
 
     /// Per-package stability flags.
-    #[serde(rename = "stability-flags")]
+    #[serde(rename = "stability-flags", deserialize_with = "map_or_empty_list")]
     pub stability_flags: BTreeMap<String, u8>,
 
     /// Prefer stable versions.
@@ -47,10 +47,11 @@ pub struct ComposerLock {
     pub prefer_lowest: bool,
 
     /// PHP and extension versions.
+    #[serde(deserialize_with = "map_or_empty_list")]
     pub platform: BTreeMap<String, String>,
 
     /// Dev platform requirements.
-    #[serde(rename = "platform-dev")]
+    #[serde(rename = "platform-dev", deserialize_with = "map_or_empty_list")]
     pub platform_dev: BTreeMap<String, String>,
 
     /// Plugin API version.
@@ -614,6 +615,26 @@ impl StabilityFlag {
     }
 }
 
+/// Deserialize a map that PHP may have encoded as `[]` when empty.
+fn map_or_empty_list<'de, D, V>(deserializer: D) -> Result<BTreeMap<String, V>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    V: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MapOrList<V> {
+        Map(BTreeMap<String, V>),
+        List(Vec<serde::de::IgnoredAny>),
+    }
+
+    match MapOrList::deserialize(deserializer)? {
+        MapOrList::Map(map) => Ok(map),
+        MapOrList::List(list) if list.is_empty() => Ok(BTreeMap::new()),
+        MapOrList::List(_) => Err(serde::de::Error::custom("expected an object")),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -638,6 +659,28 @@ mod tests {
         assert_eq!(StabilityFlag::from_u8(20), Some(StabilityFlag::Dev));
     }
 
+    #[test]
+    fn test_empty_maps_encoded_as_lists() {
+        let lock: ComposerLock = sonic_rs::from_str(
+            r#"{"_readme":[],"content-hash":"abc","packages":[],"packages-dev":[],
+            "minimum-stability":"stable","stability-flags":[],"prefer-stable":false,
+            "prefer-lowest":false,"platform":[],"platform-dev":{"ext-json":"*"},
+            "plugin-api-version":"2.6.0"}"#,
+        )
+        .unwrap();
+        assert!(lock.stability_flags.is_empty());
+        assert!(lock.platform.is_empty());
+        assert_eq!(lock.platform_dev["ext-json"], "*");
+
+        let err = sonic_rs::from_str::<ComposerLock>(
+            r#"{"_readme":[],"content-hash":"abc","packages":[],"packages-dev":[],
+            "minimum-stability":"stable","stability-flags":[],"prefer-stable":false,
+            "prefer-lowest":false,"platform":["php"],"platform-dev":[],
+            "plugin-api-version":"2.6.0"}"#,
+        );
+        assert!(err.is_err());
+    }
+
     #[test]
     fn test_source_info() {
         let source = PackageSourceInfo::git("https://github.com/foo/bar", "abc123");
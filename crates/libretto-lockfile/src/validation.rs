@@ -9,6 +9,7 @@
 use crate::hash::{ContentHasher, verify_hash};
 use crate::types::{ComposerLock, LockedPackage};
 use ahash::AHashSet;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
 use std::collections::BTreeMap;
 
 /// Validation result with warnings.
@@ -483,36 +484,30 @@ fn find_cycle<'a>(
 pub struct DriftDetector;
 
 impl DriftDetector {
-    /// Check if lock file is out of date.
+    /// Check if lock file is out of date with a composer.json document.
+    ///
+    /// The content hash is computed the way Composer does, so locks written
+    /// by either tool are compared on equal terms.
     #[must_use]
-    pub fn check_drift(
-        lock: &ComposerLock,
-        require: &BTreeMap<String, String>,
-        require_dev: &BTreeMap<String, String>,
-        minimum_stability: Option<&str>,
-        prefer_stable: Option<bool>,
-        platform: &BTreeMap<String, String>,
-    ) -> DriftResult {
-        let expected_hash = ContentHasher::compute_content_hash(
-            require,
-            require_dev,
-            minimum_stability,
-            prefer_stable,
-            if lock.prefer_lowest { Some(true) } else { None },
-            platform,
-            &BTreeMap::new(),
-        );
-
+    pub fn check_drift(lock: &ComposerLock, manifest: &Value) -> DriftResult {
+        let expected_hash = ContentHasher::compute_manifest_hash(manifest);
         let hash_matches = verify_hash(&lock.content_hash, &expected_hash);
 
+        let require = manifest_requirements(manifest, "require");
+        let require_dev = manifest_requirements(manifest, "require-dev");
+        let all_packages = || lock.packages.iter().chain(&lock.packages_dev);
+        let reachable = reachable_packages(require.keys(), all_packages());
+        let reachable_dev =
+            reachable_packages(require.keys().chain(require_dev.keys()), all_packages());
+
         DriftResult {
             is_current: hash_matches,
             expected_hash,
             actual_hash: lock.content_hash.clone(),
-            added_deps: find_missing_in_lock(require, &lock.packages),
-            removed_deps: find_extra_in_lock(require, &lock.packages),
-            added_dev_deps: find_missing_in_lock(require_dev, &lock.packages_dev),
-            removed_dev_deps: find_extra_in_lock(require_dev, &lock.packages_dev),
+            added_deps: find_missing_in_lock(&require, &lock.packages),
+            removed_deps: find_extra_in_lock(&reachable, &lock.packages),
+            added_dev_deps: find_missing_in_lock(&require_dev, &lock.packages_dev),
+            removed_dev_deps: find_extra_in_lock(&reachable_dev, &lock.packages_dev),
         }
     }
 }
@@ -605,19 +600,63 @@ fn find_missing_in_lock(
         .collect()
 }
 
-/// Find packages in lock file but not in composer.json.
-fn find_extra_in_lock(
-    require: &BTreeMap<String, String>,
-    packages: &[LockedPackage],
-) -> Vec<String> {
-    // Note: This only checks direct dependencies, not transitive
+/// Find locked packages that nothing in composer.json requires anymore.
+fn find_extra_in_lock(reachable: &AHashSet<String>, packages: &[LockedPackage]) -> Vec<String> {
     packages
         .iter()
-        .filter(|pkg| !require.contains_key(&pkg.name))
+        .filter(|pkg| !reachable.contains(&pkg.name.to_ascii_lowercase()))
         .map(|pkg| pkg.name.clone())
         .collect()
 }
 
+/// Lowercased names of locked packages reachable from `roots` through their requirements.
+fn reachable_packages<'a>(
+    roots: impl Iterator<Item = &'a String>,
+    packages: impl Iterator<Item = &'a LockedPackage>,
+) -> AHashSet<String> {
+    let mut by_name: BTreeMap<String, Vec<&LockedPackage>> = BTreeMap::new();
+    for pkg in packages {
+        by_name
+            .entry(pkg.name.to_ascii_lowercase())
+            .or_default()
+            .push(pkg);
+        // Replacing or providing a requirement satisfies it too
+        for name in pkg.replace.keys().chain(pkg.provide.keys()) {
+            by_name
+                .entry(name.to_ascii_lowercase())
+                .or_default()
+                .push(pkg);
+        }
+    }
+
+    let mut reachable = AHashSet::new();
+    let mut queue: Vec<String> = roots.map(|name| name.to_ascii_lowercase()).collect();
+    while let Some(name) = queue.pop() {
+        for pkg in by_name.get(&name).into_iter().flatten() {
+            if reachable.insert(pkg.name.to_ascii_lowercase()) {
+                queue.extend(pkg.require.keys().map(|dep| dep.to_ascii_lowercase()));
+            }
+        }
+    }
+    reachable
+}
+
+/// String-valued requirements from a composer.json section.
+fn manifest_requirements(manifest: &Value, key: &str) -> BTreeMap<String, String> {
+    manifest
+        .get(key)
+        .and_then(|section| section.as_object())
+        .map(|section| {
+            section
+                .iter()
+                .filter_map(|(name, constraint)| {
+                    Some((name.to_string(), constraint.as_str()?.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Detect manual edits in lock file.
 #[derive(Debug)]
 pub struct ManualEditDetector;
@@ -754,22 +793,45 @@ mod tests {
             ..Default::default()
         };
 
-        let mut require = BTreeMap::new();
-        require.insert("vendor/pkg".to_string(), "^1.0".to_string());
+        let manifest: Value = sonic_rs::from_str(r#"{"require": {"vendor/pkg": "^1.0"}}"#).unwrap();
 
-        let result = DriftDetector::check_drift(
-            &lock,
-            &require,
-            &BTreeMap::new(),
-            None,
-            None,
-            &BTreeMap::new(),
-        );
+        let result = DriftDetector::check_drift(&lock, &manifest);
 
         assert!(!result.is_current);
         assert!(!result.added_deps.is_empty());
     }
 
+    #[test]
+    fn test_drift_ignores_transitive_dependencies() {
+        let manifest: Value = sonic_rs::from_str(
+            r#"{"require": {"php": ">=8.1", "vendor/app": "^1.0"}, "require-dev": {"vendor/test": "^2.0"}}"#,
+        )
+        .unwrap();
+        let mut app = LockedPackage::new("vendor/app", "1.0.0");
+        app.require
+            .insert("vendor/lib".to_string(), "^1.0".to_string());
+        let mut test = LockedPackage::new("vendor/test", "2.0.0");
+        test.require
+            .insert("vendor/app".to_string(), "^1.0".to_string());
+        let lock = ComposerLock {
+            content_hash: ContentHasher::compute_manifest_hash(&manifest),
+            packages: vec![
+                app,
+                LockedPackage::new("vendor/lib", "1.0.0"),
+                LockedPackage::new("vendor/stale", "1.0.0"),
+            ],
+            packages_dev: vec![test],
+            ..Default::default()
+        };
+
+        let result = DriftDetector::check_drift(&lock, &manifest);
+
+        assert!(result.is_current);
+        assert!(result.added_deps.is_empty());
+        assert_eq!(result.removed_deps, ["vendor/stale"]);
+        assert!(result.removed_dev_deps.is_empty());
+    }
+
     #[test]
     fn test_manual_edit_detector() {
         let lock = ComposerLock {
@@ -19,6 +19,8 @@ pub enum PluginType {
     Native,
     /// PHP plugin (Composer-compatible).
     Php,
+    /// WebAssembly component (WASI preview 2).
+    Wasm,
 }
 
 /// Plugin capability flags.
@@ -46,6 +48,23 @@ pub enum PluginCapability {
 }
 
 impl PluginCapability {
+    /// Parse a capability from its manifest name (e.g. `install`).
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "install" => Some(Self::Install),
+            "resolve" => Some(Self::Resolve),
+            "command" => Some(Self::Command),
+            "event" => Some(Self::Event),
+            "repository" => Some(Self::Repository),
+            "autoload" => Some(Self::Autoload),
+            "download" => Some(Self::Download),
+            "source" => Some(Self::Source),
+            "script" => Some(Self::Script),
+            _ => None,
+        }
+    }
+
     /// Get the hooks supported by this capability.
     #[must_use]
     pub fn supported_hooks(&self) -> Option<Vec<Hook>> {
@@ -126,6 +145,9 @@ pub struct PluginMetadata {
     /// Plugin configuration from composer.json "extra".
     #[serde(skip)]
     pub config: Option<crate::config::PluginConfig>,
+    /// WebAssembly component from `extra.libretto-wasm-plugin`.
+    #[serde(skip)]
+    pub wasm: Option<WasmManifest>,
     /// Authors.
     #[serde(default)]
     pub authors: Vec<Author>,
@@ -155,6 +177,23 @@ impl PluginMetadata {
     }
 }
 
+/// WebAssembly plugin declaration from `extra.libretto-wasm-plugin`.
+///
+/// The extra value is either the component path or an object:
+///
+/// ```json
+/// "extra": {
+///     "libretto-wasm-plugin": { "component": "plugin.wasm", "fuel": 1000000000 }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmManifest {
+    /// Absolute path to the component (`.wasm`).
+    pub component: PathBuf,
+    /// Fuel budget per event, overriding the default.
+    pub fuel: Option<u64>,
+}
+
 /// Author information.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Author {
@@ -0,0 +1,832 @@
+//! Minimal native replacement for `symfony/flex`.
+//!
+//! Applies Symfony Flex recipes for newly installed packages and records the
+//! applied state in `symfony.lock`. Only the configurators needed to boot a
+//! Symfony application are supported: `bundles`, `copy-from-recipe`, `env`
+//! and `composer-scripts`. Recipes come from the endpoints listed in
+//! `extra.symfony.endpoint`, which may be Flex HTTP endpoints or local recipe
+//! directories.
+
+use super::recipes::{
+    CONTRIB_ENDPOINT, DEFAULT_ENDPOINT, Recipe, RecipeSource, sources_from_endpoints,
+};
+use super::{read_installed_packages, read_root_manifest, write_if_changed};
+use crate::api::{EventContext, EventResult, Plugin, PluginCapability, PluginInfo};
+use crate::error::{PluginError, Result};
+use crate::hooks::Hook;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sonic_rs::JsonValueMutTrait;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Composer package replaced by this plugin.
+pub const PACKAGE_NAME: &str = "symfony/flex";
+
+/// Name of the lock file written next to composer.json.
+const LOCK_FILE: &str = "symfony.lock";
+
+/// Native implementation of the `symfony/flex` recipe applier.
+#[derive(Debug, Default)]
+pub struct SymfonyFlexPlugin {
+    sources: Option<Vec<Box<dyn RecipeSource>>>,
+}
+
+/// Outcome of a [`SymfonyFlexPlugin::apply`] run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FlexReport {
+    /// Packages whose recipe was applied.
+    pub configured: Vec<String>,
+    /// Packages whose recipe was reverted because they were removed.
+    pub unconfigured: Vec<String>,
+}
+
+impl SymfonyFlexPlugin {
+    /// Create a plugin reading recipes from the project's configured endpoints.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { sources: None }
+    }
+
+    /// Create a plugin reading recipes from explicit sources.
+    #[must_use]
+    pub fn with_sources(sources: Vec<Box<dyn RecipeSource>>) -> Self {
+        Self {
+            sources: Some(sources),
+        }
+    }
+
+    /// Apply recipes for packages missing from `symfony.lock` and revert
+    /// recipes of packages that are no longer installed.
+    ///
+    /// # Errors
+    /// Returns error if `installed.json` or `symfony.lock` cannot be read, a
+    /// recipe cannot be fetched, or project files cannot be written.
+    pub async fn apply(&self, project_root: &Path, vendor_dir: &Path) -> Result<FlexReport> {
+        let packages = read_installed_packages(vendor_dir)?;
+        let root = read_root_manifest(project_root).unwrap_or(Value::Null);
+        let dirs = ProjectDirs::from_root(project_root, &root);
+        let lock_path = project_root.join(LOCK_FILE);
+        let mut lock = read_lock(&lock_path)?;
+        let mut report = FlexReport::default();
+
+        let configured_sources;
+        let sources = if let Some(sources) = &self.sources {
+            sources
+        } else {
+            configured_sources = sources_from_endpoints(&endpoints(&root), project_root);
+            &configured_sources
+        };
+
+        for package in &packages {
+            if lock.contains_key(&package.name) {
+                continue;
+            }
+
+            let mut entry = Map::new();
+            entry.insert(
+                "version".into(),
+                Value::from(lock_version(&package.version)),
+            );
+
+            if let Some(recipe) = find_recipe(sources, &package.name, &package.version).await? {
+                let files = configure(&recipe, &dirs)?;
+                let mut recipe_info = Map::new();
+                recipe_info.insert("repo".into(), Value::from(recipe.origin.clone()));
+                recipe_info.insert("branch".into(), Value::from("main"));
+                recipe_info.insert("version".into(), Value::from(recipe.version.clone()));
+                if let Some(reference) = &recipe.reference {
+                    recipe_info.insert("ref".into(), Value::from(reference.clone()));
+                }
+                entry.insert("recipe".into(), Value::Object(recipe_info));
+                if !files.is_empty() {
+                    entry.insert(
+                        "files".into(),
+                        Value::Array(files.into_iter().map(Value::from).collect()),
+                    );
+                }
+                debug!(package = %package.name, version = %recipe.version, "applied Flex recipe");
+                report.configured.push(package.name.clone());
+            }
+
+            lock.insert(package.name.clone(), Value::Object(entry));
+        }
+
+        let removed: Vec<String> = lock
+            .keys()
+            .filter(|name| !packages.iter().any(|p| &p.name == *name))
+            .cloned()
+            .collect();
+        for name in removed {
+            let recipe_version = lock
+                .get(&name)
+                .and_then(|e| e.get("recipe"))
+                .map(|r| r.get("version").and_then(Value::as_str).unwrap_or("*"));
+            if let Some(version) = recipe_version {
+                // Like Flex, fetch the recipe again to learn what it configured
+                let recipe = find_recipe(sources, &name, version).await?;
+                unconfigure(&name, recipe.as_ref(), &dirs)?;
+                report.unconfigured.push(name.clone());
+            }
+            lock.remove(&name);
+        }
+
+        write_if_changed(&lock_path, &render_lock(&lock)?)?;
+        Ok(report)
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for SymfonyFlexPlugin {
+    fn info(&self) -> PluginInfo {
+        PluginInfo::builder(PACKAGE_NAME, crate::PLUGIN_API_VERSION)
+            .description("Native Symfony Flex recipe applier")
+            .capability(PluginCapability::Event)
+            .build()
+    }
+
+    fn capabilities(&self) -> Vec<PluginCapability> {
+        vec![PluginCapability::Event]
+    }
+
+    async fn on_event(&self, event: Hook, context: &EventContext) -> Result<EventResult> {
+        if !matches!(event, Hook::PostInstallCmd | Hook::PostUpdateCmd) {
+            return Ok(EventResult::ok());
+        }
+        let (Some(project_root), Some(vendor_dir)) = (&context.project_root, &context.vendor_dir)
+        else {
+            return Ok(EventResult::ok());
+        };
+
+        let report = self.apply(project_root, vendor_dir).await?;
+        let mut result = EventResult::ok();
+        for name in &report.configured {
+            result = result.message(format!("Configuring {name} (Flex recipe)"));
+        }
+        for name in &report.unconfigured {
+            result = result.message(format!("Unconfiguring {name} (Flex recipe)"));
+        }
+        Ok(result)
+    }
+}
+
+/// Project directories referenced by recipe placeholders.
+#[derive(Debug, Clone)]
+struct ProjectDirs {
+    root: PathBuf,
+    placeholders: Vec<(&'static str, String)>,
+}
+
+impl ProjectDirs {
+    fn from_root(project_root: &Path, root: &Value) -> Self {
+        let extra = root.get("extra");
+        let dir = |key: &str, default: &str| {
+            extra
+                .and_then(|e| e.get(key))
+                .and_then(Value::as_str)
+                .unwrap_or(default)
+                .trim_end_matches('/')
+                .to_string()
+        };
+        Self {
+            root: project_root.to_path_buf(),
+            placeholders: vec![
+                ("%BIN_DIR%", dir("bin-dir", "bin")),
+                ("%CONFIG_DIR%", dir("config-dir", "config")),
+                ("%SRC_DIR%", dir("src-dir", "src")),
+                ("%VAR_DIR%", dir("var-dir", "var")),
+                ("%PUBLIC_DIR%", dir("public-dir", "public")),
+                ("%ROOT_DIR%", ".".to_string()),
+            ],
+        }
+    }
+
+    fn expand(&self, target: &str) -> String {
+        let mut expanded = target.to_string();
+        for (placeholder, value) in &self.placeholders {
+            expanded = expanded.replace(placeholder, value);
+        }
+        expanded.trim_start_matches("./").to_string()
+    }
+
+    fn config_dir(&self) -> PathBuf {
+        self.root.join(self.expand("%CONFIG_DIR%"))
+    }
+
+    fn env_file(&self) -> PathBuf {
+        let dist = self.root.join(".env.dist");
+        if dist.exists() {
+            dist
+        } else {
+            self.root.join(".env")
+        }
+    }
+}
+
+/// Endpoints from `extra.symfony.endpoint`, defaulting to the official
+/// recipes (plus contrib when `extra.symfony.allow-contrib` is set).
+fn endpoints(root: &Value) -> Vec<String> {
+    let symfony = root.get("extra").and_then(|e| e.get("symfony"));
+    let configured: Vec<String> = match symfony.and_then(|s| s.get("endpoint")) {
+        Some(Value::String(endpoint)) => vec![endpoint.clone()],
+        Some(Value::Array(endpoints)) => endpoints
+            .iter()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect(),
+        _ => Vec::new(),
+    };
+    if !configured.is_empty() {
+        return configured;
+    }
+
+    let mut defaults = vec![DEFAULT_ENDPOINT.to_string()];
+    if symfony
+        .and_then(|s| s.get("allow-contrib"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+    {
+        defaults.push(CONTRIB_ENDPOINT.to_string());
+    }
+    defaults
+}
+
+async fn find_recipe(
+    sources: &[Box<dyn RecipeSource>],
+    package: &str,
+    version: &str,
+) -> Result<Option<Recipe>> {
+    for source in sources {
+        match source.find(package, version).await {
+            Ok(Some(recipe)) => return Ok(Some(recipe)),
+            Ok(None) => {}
+            Err(e) => warn!(package, error = %e, "failed to look up Flex recipe"),
+        }
+    }
+    Ok(None)
+}
+
+/// Apply all supported configurators of a recipe, returning the copied files.
+fn configure(recipe: &Recipe, dirs: &ProjectDirs) -> Result<Vec<String>> {
+    let manifest = &recipe.manifest;
+
+    if let Some(bundles) = manifest.get("bundles").and_then(Value::as_object) {
+        configure_bundles(bundles, dirs)?;
+    }
+
+    let copied = match manifest.get("copy-from-recipe").and_then(Value::as_object) {
+        Some(mapping) => copy_from_recipe(recipe, mapping, dirs)?,
+        None => Vec::new(),
+    };
+
+    if let Some(env) = manifest.get("env").and_then(Value::as_object) {
+        configure_env(&recipe.package, env, dirs)?;
+    }
+
+    if let Some(scripts) = manifest.get("composer-scripts").and_then(Value::as_object) {
+        configure_composer_scripts(scripts, dirs)?;
+    }
+
+    Ok(copied)
+}
+
+/// Revert the env block, bundles and auto-scripts a recipe added.
+///
+/// Without the recipe only the marked env block can be found. Copied files
+/// are left in place since they are usually edited by the user.
+fn unconfigure(package: &str, recipe: Option<&Recipe>, dirs: &ProjectDirs) -> Result<()> {
+    let env_path = dirs.env_file();
+    if let Ok(content) = std::fs::read_to_string(&env_path) {
+        let stripped = remove_env_block(&content, package);
+        if stripped != content {
+            std::fs::write(&env_path, stripped)?;
+        }
+    }
+
+    let Some(recipe) = recipe else {
+        return Ok(());
+    };
+    if let Some(bundles) = recipe.manifest.get("bundles").and_then(Value::as_object) {
+        unconfigure_bundles(bundles, dirs)?;
+    }
+    if let Some(scripts) = recipe
+        .manifest
+        .get("composer-scripts")
+        .and_then(Value::as_object)
+    {
+        unconfigure_composer_scripts(scripts, dirs)?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// bundles
+// ---------------------------------------------------------------------------
+
+fn configure_bundles(bundles: &Map<String, Value>, dirs: &ProjectDirs) -> Result<()> {
+    let path = dirs.config_dir().join("bundles.php");
+    let mut registered = std::fs::read_to_string(&path)
+        .map(|content| parse_bundles(&content))
+        .unwrap_or_default();
+
+    for (class, envs) in bundles {
+        let class = class.trim_start_matches('\\').to_string();
+        if registered.iter().any(|(c, _)| *c == class) {
+            continue;
+        }
+        let envs = envs
+            .as_array()
+            .map(|envs| {
+                envs.iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_else(|| vec!["all".to_string()]);
+        registered.push((class, envs));
+    }
+
+    write_if_changed(&path, &render_bundles(&registered))?;
+    Ok(())
+}
+
+fn unconfigure_bundles(bundles: &Map<String, Value>, dirs: &ProjectDirs) -> Result<()> {
+    let path = dirs.config_dir().join("bundles.php");
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let mut registered = parse_bundles(&content);
+    registered.retain(|(class, _)| {
+        !bundles
+            .keys()
+            .any(|removed| removed.trim_start_matches('\\') == class)
+    });
+    write_if_changed(&path, &render_bundles(&registered))?;
+    Ok(())
+}
+
+/// Parse `config/bundles.php` into `(class, envs)` pairs, in file order.
+fn parse_bundles(content: &str) -> Vec<(String, Vec<String>)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (class, envs) = line.trim().split_once("::class")?;
+            let envs = envs.split_once('[')?.1.rsplit_once(']')?.0;
+            let envs = envs
+                .split(',')
+                .filter_map(|pair| {
+                    let (env, enabled) = pair.split_once("=>")?;
+                    (enabled.trim() == "true")
+                        .then(|| env.trim().trim_matches(['\'', '"']).to_string())
+                })
+                .collect();
+            Some((class.trim_start_matches('\\').to_string(), envs))
+        })
+        .collect()
+}
+
+fn render_bundles(bundles: &[(String, Vec<String>)]) -> String {
+    let mut out = String::from("<?php\n\nreturn [\n");
+    for (class, envs) in bundles {
+        let envs = envs
+            .iter()
+            .map(|env| format!("'{env}' => true"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = writeln!(out, "    {class}::class => [{envs}],");
+    }
+    out.push_str("];\n");
+    out
+}
+
+// ---------------------------------------------------------------------------
+// copy-from-recipe
+// ---------------------------------------------------------------------------
+
+fn copy_from_recipe(
+    recipe: &Recipe,
+    mapping: &Map<String, Value>,
+    dirs: &ProjectDirs,
+) -> Result<Vec<String>> {
+    let mut copied = Vec::new();
+
+    for (source, target) in mapping {
+        let Some(target) = target.as_str() else {
+            continue;
+        };
+        let target = dirs.expand(target);
+
+        for (file, content) in &recipe.files {
+            let relative = if source.ends_with('/') {
+                match file.strip_prefix(source.as_str()) {
+                    Some(rest) => format!("{}/{rest}", target.trim_end_matches('/')),
+                    None => continue,
+                }
+            } else if file == source {
+                target.clone()
+            } else {
+                continue;
+            };
+
+            let relative = relative.trim_start_matches('/').to_string();
+            if relative.split('/').any(|part| part == "..") {
+                return Err(PluginError::InvalidMetadata(format!(
+                    "recipe for {} copies outside the project: {relative}",
+                    recipe.package
+                )));
+            }
+
+            let destination = dirs.root.join(&relative);
+            if !destination.exists() {
+                if let Some(parent) = destination.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&destination, &content.contents)?;
+                if content.executable {
+                    set_executable(&destination)?;
+                }
+            }
+            copied.push(relative);
+        }
+    }
+
+    copied.sort();
+    copied.dedup();
+    Ok(copied)
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o111);
+    std::fs::set_permissions(path, permissions)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// env
+// ---------------------------------------------------------------------------
+
+fn configure_env(package: &str, env: &Map<String, Value>, dirs: &ProjectDirs) -> Result<()> {
+    let path = dirs.env_file();
+    let mut content = std::fs::read_to_string(&path).unwrap_or_default();
+    if content.contains(&env_marker_start(package)) {
+        return Ok(());
+    }
+
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    let _ = writeln!(content, "{}", env_marker_start(package));
+    for (key, value) in env {
+        let value = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        if key.starts_with('#') {
+            let _ = writeln!(content, "# {value}");
+        } else {
+            let _ = writeln!(content, "{key}={}", expand_env_value(&value));
+        }
+    }
+    let _ = writeln!(content, "{}", env_marker_end(package));
+
+    std::fs::write(&path, content)?;
+    Ok(())
+}
+
+fn env_marker_start(package: &str) -> String {
+    format!("###> {package} ###")
+}
+
+fn env_marker_end(package: &str) -> String {
+    format!("###< {package} ###")
+}
+
+/// Replace `%generate(secret)%` with a random 32-character hex string.
+fn expand_env_value(value: &str) -> String {
+    if value.contains("%generate(secret)%") {
+        let secret =
+            rand::random::<[u8; 16]>()
+                .iter()
+                .fold(String::with_capacity(32), |mut hex, b| {
+                    let _ = write!(hex, "{b:02x}");
+                    hex
+                });
+        value.replace("%generate(secret)%", &secret)
+    } else {
+        value.to_string()
+    }
+}
+
+fn remove_env_block(content: &str, package: &str) -> String {
+    let start = env_marker_start(package);
+    let end = env_marker_end(package);
+    let mut out = String::with_capacity(content.len());
+    let mut skipping = false;
+    for line in content.lines() {
+        if line.trim() == start {
+            skipping = true;
+            continue;
+        }
+        if skipping {
+            if line.trim() == end {
+                skipping = false;
+            }
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    // Collapse the blank line left behind by the removed block
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// composer-scripts
+// ---------------------------------------------------------------------------
+
+/// Add recipe scripts to `scripts.auto-scripts` in composer.json, preserving
+/// key order of the existing document.
+fn configure_composer_scripts(scripts: &Map<String, Value>, dirs: &ProjectDirs) -> Result<()> {
+    let path = dirs.root.join("composer.json");
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let mut composer: sonic_rs::Value = sonic_rs::from_str(&content)?;
+    let Some(root) = composer.as_object_mut() else {
+        return Ok(());
+    };
+
+    if root.get(&"scripts").is_none() {
+        root.insert("scripts", sonic_rs::json!({}));
+    }
+    let Some(section) = root.get_mut(&"scripts").and_then(|s| s.as_object_mut()) else {
+        return Ok(());
+    };
+    if section.get(&"auto-scripts").is_none() {
+        section.insert("auto-scripts", sonic_rs::json!({}));
+    }
+    let Some(auto_scripts) = section
+        .get_mut(&"auto-scripts")
+        .and_then(|s| s.as_object_mut())
+    else {
+        return Ok(());
+    };
+
+    let mut changed = false;
+    for (command, kind) in scripts {
+        if auto_scripts.get(&command.as_str()).is_none() {
+            let kind = kind.as_str().unwrap_or("script");
+            auto_scripts.insert(command.as_str(), sonic_rs::json!(kind));
+            changed = true;
+        }
+    }
+
+    if changed {
+        let output = sonic_rs::to_string_pretty(&composer)?;
+        std::fs::write(&path, format!("{output}\n"))?;
+    }
+    Ok(())
+}
+
+/// Remove recipe scripts from `scripts.auto-scripts` in composer.json.
+fn unconfigure_composer_scripts(scripts: &Map<String, Value>, dirs: &ProjectDirs) -> Result<()> {
+    let path = dirs.root.join("composer.json");
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let mut composer: sonic_rs::Value = sonic_rs::from_str(&content)?;
+    let Some(auto_scripts) = composer
+        .get_mut("scripts")
+        .and_then(|s| s.get_mut("auto-scripts"))
+        .and_then(|s| s.as_object_mut())
+    else {
+        return Ok(());
+    };
+
+    let mut changed = false;
+    for command in scripts.keys() {
+        changed |= auto_scripts.remove(&command.as_str()).is_some();
+    }
+
+    if changed {
+        let output = sonic_rs::to_string_pretty(&composer)?;
+        std::fs::write(&path, format!("{output}\n"))?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// symfony.lock
+// ---------------------------------------------------------------------------
+
+fn read_lock(path: &Path) -> Result<BTreeMap<String, Value>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| PluginError::InvalidMetadata(format!("{}: {e}", path.display()))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Serialize `symfony.lock` with 4-space indentation, as Flex does.
+fn render_lock(lock: &BTreeMap<String, Value>) -> Result<String> {
+    let mut buf = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buf, formatter);
+    lock.serialize(&mut serializer)
+        .map_err(|e| PluginError::ExecutionFailed(format!("failed to encode {LOCK_FILE}: {e}")))?;
+    let mut out = String::from_utf8_lossy(&buf).into_owned();
+    out.push('\n');
+    Ok(out)
+}
+
+/// The `major.minor` version recorded in `symfony.lock`.
+fn lock_version(version: &str) -> String {
+    let version = version.trim_start_matches(['v', 'V']);
+    if version.starts_with("dev-") {
+        return version.to_string();
+    }
+    version.split('.').take(2).collect::<Vec<_>>().join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builtin::recipes::LocalRecipeSource;
+    use tempfile::TempDir;
+
+    fn setup(temp: &TempDir) -> (PathBuf, PathBuf) {
+        let vendor = temp.path().join("vendor");
+        std::fs::create_dir_all(vendor.join("composer")).unwrap();
+        std::fs::write(
+            vendor.join("composer/installed.json"),
+            r#"{"packages": [
+                {"name": "symfony/flex", "version": "v2.4.6", "type": "composer-plugin"},
+                {"name": "symfony/framework-bundle", "version": "v7.1.2", "type": "symfony-bundle"},
+                {"name": "psr/log", "version": "3.0.0", "type": "library"}
+            ]}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join("composer.json"),
+            "{\n  \"name\": \"acme/app\",\n  \"require\": {}\n}\n",
+        )
+        .unwrap();
+
+        let recipes = temp.path().join("recipes");
+        let recipe = recipes.join("symfony/framework-bundle/7.0");
+        std::fs::create_dir_all(recipe.join("config/packages")).unwrap();
+        std::fs::write(
+            recipe.join("manifest.json"),
+            r##"{
+                "bundles": {"Symfony\\Bundle\\FrameworkBundle\\FrameworkBundle": ["all"]},
+                "copy-from-recipe": {"config/": "%CONFIG_DIR%/"},
+                "env": {"#1": "Framework settings", "APP_ENV": "dev", "APP_SECRET": "%generate(secret)%"},
+                "composer-scripts": {"cache:clear": "symfony-cmd"}
+            }"##,
+        )
+        .unwrap();
+        std::fs::write(
+            recipe.join("config/packages/framework.yaml"),
+            "framework:\n    secret: '%env(APP_SECRET)%'\n",
+        )
+        .unwrap();
+
+        (vendor, recipes)
+    }
+
+    #[tokio::test]
+    async fn applies_recipe_and_writes_lock() {
+        let temp = TempDir::new().unwrap();
+        let (vendor, recipes) = setup(&temp);
+        let plugin =
+            SymfonyFlexPlugin::with_sources(vec![Box::new(LocalRecipeSource::new(&recipes))]);
+
+        let report = plugin.apply(temp.path(), &vendor).await.unwrap();
+        assert_eq!(
+            report.configured,
+            vec!["symfony/framework-bundle".to_string()]
+        );
+
+        let bundles = std::fs::read_to_string(temp.path().join("config/bundles.php")).unwrap();
+        assert!(bundles.contains(
+            "    Symfony\\Bundle\\FrameworkBundle\\FrameworkBundle::class => ['all' => true],"
+        ));
+        assert!(temp.path().join("config/packages/framework.yaml").exists());
+
+        let env = std::fs::read_to_string(temp.path().join(".env")).unwrap();
+        assert!(env.starts_with("###> symfony/framework-bundle ###\n# Framework settings\n"));
+        assert!(env.contains("APP_ENV=dev\n"));
+        let secret_line = env.lines().find(|l| l.starts_with("APP_SECRET=")).unwrap();
+        assert_eq!(secret_line.len(), "APP_SECRET=".len() + 32);
+
+        let composer = std::fs::read_to_string(temp.path().join("composer.json")).unwrap();
+        let composer: Value = serde_json::from_str(&composer).unwrap();
+        assert_eq!(
+            composer["scripts"]["auto-scripts"]["cache:clear"],
+            Value::from("symfony-cmd")
+        );
+
+        let lock = std::fs::read_to_string(temp.path().join(LOCK_FILE)).unwrap();
+        assert!(lock.contains("\n    \"psr/log\": {\n        \"version\": \"3.0\"\n    }"));
+        let lock: Value = serde_json::from_str(&lock).unwrap();
+        assert_eq!(lock["symfony/framework-bundle"]["recipe"]["version"], "7.0");
+        assert_eq!(
+            lock["symfony/framework-bundle"]["files"][0],
+            "config/packages/framework.yaml"
+        );
+    }
+
+    #[tokio::test]
+    async fn second_run_is_a_no_op() {
+        let temp = TempDir::new().unwrap();
+        let (vendor, recipes) = setup(&temp);
+        let plugin =
+            SymfonyFlexPlugin::with_sources(vec![Box::new(LocalRecipeSource::new(&recipes))]);
+
+        plugin.apply(temp.path(), &vendor).await.unwrap();
+        let env = std::fs::read_to_string(temp.path().join(".env")).unwrap();
+
+        let report = plugin.apply(temp.path(), &vendor).await.unwrap();
+        assert!(report.configured.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join(".env")).unwrap(),
+            env
+        );
+    }
+
+    #[tokio::test]
+    async fn unconfigures_removed_packages() {
+        let temp = TempDir::new().unwrap();
+        let (vendor, recipes) = setup(&temp);
+        let plugin =
+            SymfonyFlexPlugin::with_sources(vec![Box::new(LocalRecipeSource::new(&recipes))]);
+        plugin.apply(temp.path(), &vendor).await.unwrap();
+
+        std::fs::write(
+            vendor.join("composer/installed.json"),
+            r#"{"packages": [{"name": "symfony/flex", "version": "v2.4.6"}]}"#,
+        )
+        .unwrap();
+        let report = plugin.apply(temp.path(), &vendor).await.unwrap();
+        assert_eq!(
+            report.unconfigured,
+            vec!["symfony/framework-bundle".to_string()]
+        );
+
+        let env = std::fs::read_to_string(temp.path().join(".env")).unwrap();
+        assert!(!env.contains("APP_ENV"));
+        let bundles = std::fs::read_to_string(temp.path().join("config/bundles.php")).unwrap();
+        assert!(!bundles.contains("FrameworkBundle"));
+        let composer: Value = serde_json::from_str(
+            &std::fs::read_to_string(temp.path().join("composer.json")).unwrap(),
+        )
+        .unwrap();
+        assert!(
+            composer["scripts"]["auto-scripts"]
+                .get("cache:clear")
+                .is_none()
+        );
+        let lock: Value =
+            serde_json::from_str(&std::fs::read_to_string(temp.path().join(LOCK_FILE)).unwrap())
+                .unwrap();
+        assert!(lock.get("symfony/framework-bundle").is_none());
+    }
+
+    #[test]
+    fn bundles_round_trip() {
+        let content = "<?php\n\nreturn [\n    Foo\\FooBundle::class => ['dev' => true, 'test' => true],\n];\n";
+        let parsed = parse_bundles(content);
+        assert_eq!(
+            parsed,
+            vec![(
+                "Foo\\FooBundle".to_string(),
+                vec!["dev".to_string(), "test".to_string()]
+            )]
+        );
+        assert_eq!(render_bundles(&parsed), content);
+    }
+
+    #[test]
+    fn lock_version_formats() {
+        assert_eq!(lock_version("v7.1.2"), "7.1");
+        assert_eq!(lock_version("3.0.0"), "3.0");
+        assert_eq!(lock_version("dev-main"), "dev-main");
+    }
+}
@@ -0,0 +1,314 @@
+//! Built-in native replacements for popular Composer installer plugins.
+//!
+//! Some widely used Composer plugins only generate a configuration file after
+//! packages are installed. Running their PHP code is unnecessary: the output is
+//! a pure function of `vendor/composer/installed.json` and the root manifest
+//! (plus, for Symfony Flex, the recipe repository). These plugins are
+//! re-implemented here as regular [`Plugin`] trait objects so that
+//! [`crate::PluginManager::register_builtins`] can load them like any other
+//! native plugin.
+//!
+//! Each built-in plugin is named after the Composer package it replaces and is
+//! subject to the same `allow-plugins` rules as the original.
+
+mod flex;
+mod phpcs;
+mod phpstan;
+pub mod recipes;
+mod symfony_runtime;
+
+pub use flex::{FlexReport, SymfonyFlexPlugin};
+pub use phpcs::PhpcsInstallerPlugin;
+pub use phpstan::PhpstanExtensionInstallerPlugin;
+pub use symfony_runtime::SymfonyRuntimePlugin;
+
+use crate::api::Plugin;
+use crate::error::{PluginError, Result};
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// Get all built-in plugins.
+#[must_use]
+pub fn builtin_plugins() -> Vec<Arc<dyn Plugin>> {
+    vec![
+        Arc::new(PhpstanExtensionInstallerPlugin::new()),
+        Arc::new(PhpcsInstallerPlugin::new()),
+        Arc::new(SymfonyRuntimePlugin::new()),
+        Arc::new(SymfonyFlexPlugin::new()),
+    ]
+}
+
+/// Check if a Composer package is replaced by a built-in plugin.
+#[must_use]
+pub fn is_builtin(package: &str) -> bool {
+    builtin_plugins()
+        .iter()
+        .any(|plugin| plugin.info().name.eq_ignore_ascii_case(package))
+}
+
+/// A package entry from `vendor/composer/installed.json`.
+#[derive(Debug, Clone)]
+pub(crate) struct InstalledPackage {
+    /// Package name.
+    pub name: String,
+    /// Pretty version.
+    pub version: String,
+    /// Package type.
+    pub package_type: String,
+    /// Absolute install path.
+    pub install_path: PathBuf,
+    /// The raw package entry.
+    pub raw: Value,
+}
+
+impl InstalledPackage {
+    /// Get a key from the package's `extra` section.
+    pub fn extra(&self, key: &str) -> Option<&Value> {
+        self.raw.get("extra").and_then(|extra| extra.get(key))
+    }
+}
+
+/// Read the installed packages from `vendor/composer/installed.json`.
+///
+/// Both the Composer 1 (plain array) and Composer 2 (`packages` key) formats
+/// are supported.
+pub(crate) fn read_installed_packages(vendor_dir: &Path) -> Result<Vec<InstalledPackage>> {
+    let composer_dir = vendor_dir.join("composer");
+    let installed_path = composer_dir.join("installed.json");
+    if !installed_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&installed_path)?;
+    let installed: Value = serde_json::from_str(&content)
+        .map_err(|e| PluginError::InvalidMetadata(format!("{}: {e}", installed_path.display())))?;
+
+    let entries = match &installed {
+        Value::Array(entries) => entries.as_slice(),
+        Value::Object(map) => map
+            .get("packages")
+            .and_then(Value::as_array)
+            .map_or(&[][..], Vec::as_slice),
+        _ => &[],
+    };
+
+    let packages = entries
+        .iter()
+        .filter_map(|entry| {
+            let name = entry.get("name")?.as_str()?.to_string();
+            let install_path = entry
+                .get("install-path")
+                .and_then(Value::as_str)
+                .map_or_else(
+                    || vendor_dir.join(&name),
+                    |p| normalize_path(&composer_dir.join(p)),
+                );
+            Some(InstalledPackage {
+                version: entry
+                    .get("version")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string(),
+                package_type: entry
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .unwrap_or("library")
+                    .to_string(),
+                install_path,
+                raw: entry.clone(),
+                name,
+            })
+        })
+        .collect();
+
+    Ok(packages)
+}
+
+/// Read the root `composer.json` of a project, if present.
+pub(crate) fn read_root_manifest(project_root: &Path) -> Option<Value> {
+    let content = std::fs::read_to_string(project_root.join("composer.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Lexically normalize a path, resolving `.` and `..` components.
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// Compute the relative path from directory `from` to `to`.
+///
+/// Both paths must be absolute (or both relative to the same base). The
+/// result always uses `/` separators, matching Composer's
+/// `Filesystem::findShortestPath`.
+pub(crate) fn relative_path(from: &Path, to: &Path) -> String {
+    let from = normalize_path(from);
+    let to = normalize_path(to);
+    let from_parts: Vec<_> = from.components().collect();
+    let to_parts: Vec<_> = to.components().collect();
+
+    let common = from_parts
+        .iter()
+        .zip(&to_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<String> =
+        std::iter::repeat_n("..".to_string(), from_parts.len() - common).collect();
+    parts.extend(
+        to_parts[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+/// Export a JSON value as a PHP literal in `var_export` style.
+pub(crate) fn php_export(value: &Value) -> String {
+    let mut out = String::new();
+    write_php_value(&mut out, value, 0);
+    out
+}
+
+fn write_php_value(out: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Null => out.push_str("NULL"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&n.to_string()),
+        Value::String(s) => {
+            out.push('\'');
+            out.push_str(&php_single_quote_escape(s));
+            out.push('\'');
+        }
+        Value::Array(items) => {
+            out.push_str("array (\n");
+            for (i, item) in items.iter().enumerate() {
+                write_php_entry(out, &i.to_string(), item, indent + 2);
+            }
+            out.push_str(&" ".repeat(indent));
+            out.push(')');
+        }
+        Value::Object(map) => {
+            out.push_str("array (\n");
+            for (key, item) in map {
+                let key = format!("'{}'", php_single_quote_escape(key));
+                write_php_entry(out, &key, item, indent + 2);
+            }
+            out.push_str(&" ".repeat(indent));
+            out.push(')');
+        }
+    }
+}
+
+fn write_php_entry(out: &mut String, key: &str, value: &Value, indent: usize) {
+    out.push_str(&" ".repeat(indent));
+    out.push_str(key);
+    out.push_str(" => ");
+    if value.is_array() || value.is_object() {
+        out.push('\n');
+        out.push_str(&" ".repeat(indent));
+    }
+    write_php_value(out, value, indent);
+    out.push_str(",\n");
+}
+
+/// Escape a string for use inside single-quoted PHP literals.
+pub(crate) fn php_single_quote_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+/// Write a file only if its content changed, creating parent directories.
+pub(crate) fn write_if_changed(path: &Path, content: &str) -> Result<bool> {
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn relative_path_between_siblings() {
+        let from = Path::new("/project/vendor/phpstan/extension-installer/src");
+        let to = Path::new("/project/vendor/phpstan/phpstan-strict-rules");
+        assert_eq!(relative_path(from, to), "../../phpstan-strict-rules");
+    }
+
+    #[test]
+    fn relative_path_same_dir() {
+        let dir = Path::new("/project/vendor");
+        assert_eq!(relative_path(dir, dir), ".");
+    }
+
+    #[test]
+    fn normalize_resolves_parent_components() {
+        let path = Path::new("/project/vendor/composer/../acme/lib");
+        assert_eq!(
+            normalize_path(path),
+            PathBuf::from("/project/vendor/acme/lib")
+        );
+    }
+
+    #[test]
+    fn php_export_nested() {
+        let value = serde_json::json!({
+            "includes": ["rules.neon"],
+            "name": "it's"
+        });
+        let exported = php_export(&value);
+        assert!(exported.starts_with("array (\n"));
+        assert!(exported.contains("0 => 'rules.neon',"));
+        assert!(exported.contains("'name' => 'it\\'s',"));
+    }
+
+    #[test]
+    fn read_installed_composer2_format() {
+        let temp = TempDir::new().unwrap();
+        let vendor = temp.path().join("vendor");
+        std::fs::create_dir_all(vendor.join("composer")).unwrap();
+        std::fs::write(
+            vendor.join("composer/installed.json"),
+            r#"{"packages": [
+                {"name": "acme/lib", "version": "1.0.0", "type": "library", "install-path": "../acme/lib"},
+                {"name": "acme/other", "version": "2.0.0"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let packages = read_installed_packages(&vendor).unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].install_path, vendor.join("acme/lib"));
+        assert_eq!(packages[1].package_type, "library");
+        assert_eq!(packages[1].install_path, vendor.join("acme/other"));
+    }
+
+    #[test]
+    fn read_installed_missing_file() {
+        let temp = TempDir::new().unwrap();
+        assert!(read_installed_packages(temp.path()).unwrap().is_empty());
+    }
+}
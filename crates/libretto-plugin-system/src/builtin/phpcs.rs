@@ -0,0 +1,296 @@
+//! Native replacement for `dealerdirect/phpcodesniffer-composer-installer`.
+//!
+//! Registers every installed `phpcodesniffer-standard` package in `PHP_CodeSniffer`'s
+//! `installed_paths` setting by rewriting `CodeSniffer.conf`, which is what
+//! `phpcs --config-set installed_paths` does.
+
+use super::{
+    InstalledPackage, php_single_quote_escape, read_installed_packages, read_root_manifest,
+    relative_path, write_if_changed,
+};
+use crate::api::{EventContext, EventResult, Plugin, PluginCapability, PluginInfo};
+use crate::error::Result;
+use crate::hooks::Hook;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+use walkdir::WalkDir;
+
+/// Composer package replaced by this plugin.
+pub const PACKAGE_NAME: &str = "dealerdirect/phpcodesniffer-composer-installer";
+
+/// The `PHP_CodeSniffer` package.
+const PHPCS_PACKAGE: &str = "squizlabs/php_codesniffer";
+
+/// Package type of coding standards.
+const STANDARD_TYPE: &str = "phpcodesniffer-standard";
+
+/// Default depth searched for `ruleset.xml` files.
+const DEFAULT_SEARCH_DEPTH: usize = 3;
+
+/// The config key managed by this plugin.
+const INSTALLED_PATHS_KEY: &str = "installed_paths";
+
+/// Native implementation of `dealerdirect/phpcodesniffer-composer-installer`.
+#[derive(Debug, Default)]
+pub struct PhpcsInstallerPlugin;
+
+impl PhpcsInstallerPlugin {
+    /// Create a new plugin instance.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Update `installed_paths` in `PHP_CodeSniffer`'s `CodeSniffer.conf`.
+    ///
+    /// Returns the configured paths, or `None` if `PHP_CodeSniffer` is not installed.
+    ///
+    /// # Errors
+    /// Returns error if `installed.json` cannot be read or the config cannot be written.
+    pub fn configure(&self, project_root: &Path, vendor_dir: &Path) -> Result<Option<Vec<String>>> {
+        let packages = read_installed_packages(vendor_dir)?;
+        let Some(phpcs) = packages.iter().find(|p| p.name == PHPCS_PACKAGE) else {
+            return Ok(None);
+        };
+
+        let root = read_root_manifest(project_root);
+        let search_depth = root
+            .as_ref()
+            .and_then(|r| r.get("extra")?.get("phpcodesniffer-search-depth")?.as_u64())
+            .map_or(DEFAULT_SEARCH_DEPTH, |d| d as usize);
+
+        let mut standard_dirs: Vec<PathBuf> = packages
+            .iter()
+            .filter(|p| p.package_type == STANDARD_TYPE)
+            .flat_map(|p| standard_paths(p, search_depth))
+            .collect();
+
+        let root_is_standard = root
+            .as_ref()
+            .and_then(|r| r.get("type")?.as_str().map(|t| t == STANDARD_TYPE))
+            .unwrap_or(false);
+        if root_is_standard {
+            let root_package = InstalledPackage {
+                name: String::new(),
+                version: String::new(),
+                package_type: STANDARD_TYPE.to_string(),
+                install_path: project_root.to_path_buf(),
+                raw: serde_json::Value::Null,
+            };
+            standard_dirs.extend(standard_paths(&root_package, search_depth));
+        }
+
+        let config_path = phpcs.install_path.join("CodeSniffer.conf");
+        let mut config = read_config(&config_path);
+
+        // Keep user-configured paths that still exist, drop stale ones.
+        let mut paths: Vec<String> = config
+            .get(INSTALLED_PATHS_KEY)
+            .map(|existing| {
+                existing
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|p| !p.is_empty())
+                    .filter(|p| phpcs.install_path.join(p).is_dir())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for dir in &standard_dirs {
+            if *dir == phpcs.install_path {
+                continue;
+            }
+            let relative = relative_path(&phpcs.install_path, dir);
+            if !paths.contains(&relative) {
+                paths.push(relative);
+            }
+        }
+        paths.sort();
+        paths.dedup();
+
+        if paths.is_empty() {
+            config.remove(INSTALLED_PATHS_KEY);
+        } else {
+            config.insert(INSTALLED_PATHS_KEY.to_string(), paths.join(","));
+        }
+
+        if config.is_empty() {
+            if config_path.exists() {
+                std::fs::remove_file(&config_path)?;
+            }
+        } else if write_if_changed(&config_path, &render_config(&config))? {
+            debug!(path = %config_path.display(), "wrote PHP_CodeSniffer installed_paths");
+        }
+
+        Ok(Some(paths))
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for PhpcsInstallerPlugin {
+    fn info(&self) -> PluginInfo {
+        PluginInfo::builder(PACKAGE_NAME, crate::PLUGIN_API_VERSION)
+            .description("Native PHP_CodeSniffer standards installer")
+            .capability(PluginCapability::Event)
+            .build()
+    }
+
+    fn capabilities(&self) -> Vec<PluginCapability> {
+        vec![PluginCapability::Event]
+    }
+
+    async fn on_event(&self, event: Hook, context: &EventContext) -> Result<EventResult> {
+        if !matches!(event, Hook::PostInstallCmd | Hook::PostUpdateCmd) {
+            return Ok(EventResult::ok());
+        }
+        let (Some(project_root), Some(vendor_dir)) = (&context.project_root, &context.vendor_dir)
+        else {
+            return Ok(EventResult::ok());
+        };
+
+        match self.configure(project_root, vendor_dir)? {
+            Some(paths) if !paths.is_empty() => Ok(EventResult::with_message(format!(
+                "PHP_CodeSniffer installed_paths set to {}",
+                paths.join(",")
+            ))),
+            _ => Ok(EventResult::ok()),
+        }
+    }
+}
+
+/// Directories to add to `installed_paths` for a standard package.
+///
+/// A standard is a directory containing `ruleset.xml`; `PHP_CodeSniffer` expects
+/// the directory *containing* the standard directories.
+fn standard_paths(package: &InstalledPackage, max_depth: usize) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = WalkDir::new(&package.install_path)
+        .max_depth(max_depth + 1)
+        .into_iter()
+        .filter_map(std::result::Result::ok)
+        .filter(|e| e.file_type().is_file() && e.file_name() == "ruleset.xml")
+        .filter_map(|e| Some(e.path().parent()?.parent()?.to_path_buf()))
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+/// Parse the `$phpCodeSnifferConfig` array from `CodeSniffer.conf`.
+fn read_config(path: &Path) -> BTreeMap<String, String> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return BTreeMap::new();
+    };
+
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.trim().trim_end_matches(',').split_once("=>")?;
+            Some((unquote(key)?, unquote(value)?))
+        })
+        .collect()
+}
+
+fn unquote(s: &str) -> Option<String> {
+    let s = s.trim();
+    let inner = s.strip_prefix('\'')?.strip_suffix('\'')?;
+    Some(inner.replace("\\'", "'").replace("\\\\", "\\"))
+}
+
+fn render_config(config: &BTreeMap<String, String>) -> String {
+    let mut out = String::from("<?php\n $phpCodeSnifferConfig = array (\n");
+    for (key, value) in config {
+        out.push_str(&format!(
+            "  '{}' => '{}',\n",
+            php_single_quote_escape(key),
+            php_single_quote_escape(value)
+        ));
+    }
+    out.push_str(")\n?>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup(temp: &TempDir) -> PathBuf {
+        let vendor = temp.path().join("vendor");
+        std::fs::create_dir_all(vendor.join("composer")).unwrap();
+        std::fs::create_dir_all(vendor.join("squizlabs/php_codesniffer")).unwrap();
+        std::fs::create_dir_all(vendor.join("wp-coding-standards/wpcs/WordPress")).unwrap();
+        std::fs::write(
+            vendor.join("wp-coding-standards/wpcs/WordPress/ruleset.xml"),
+            "<ruleset/>",
+        )
+        .unwrap();
+        std::fs::write(
+            vendor.join("composer/installed.json"),
+            serde_json::json!({"packages": [
+                {"name": "squizlabs/php_codesniffer", "version": "3.9.0", "type": "library",
+                 "install-path": "../squizlabs/php_codesniffer"},
+                {"name": "wp-coding-standards/wpcs", "version": "3.1.0", "type": "phpcodesniffer-standard",
+                 "install-path": "../wp-coding-standards/wpcs"}
+            ]})
+            .to_string(),
+        )
+        .unwrap();
+        vendor
+    }
+
+    #[test]
+    fn configures_installed_paths() {
+        let temp = TempDir::new().unwrap();
+        let vendor = setup(&temp);
+
+        let plugin = PhpcsInstallerPlugin::new();
+        let paths = plugin.configure(temp.path(), &vendor).unwrap().unwrap();
+        assert_eq!(paths, vec!["../../wp-coding-standards/wpcs".to_string()]);
+
+        let conf =
+            std::fs::read_to_string(vendor.join("squizlabs/php_codesniffer/CodeSniffer.conf"))
+                .unwrap();
+        assert!(conf.contains("'installed_paths' => '../../wp-coding-standards/wpcs',"));
+    }
+
+    #[test]
+    fn preserves_other_settings() {
+        let temp = TempDir::new().unwrap();
+        let vendor = setup(&temp);
+        std::fs::write(
+            vendor.join("squizlabs/php_codesniffer/CodeSniffer.conf"),
+            "<?php\n $phpCodeSnifferConfig = array (\n  'default_standard' => 'PSR12',\n  'installed_paths' => '../../gone/away',\n)\n?>",
+        )
+        .unwrap();
+
+        let plugin = PhpcsInstallerPlugin::new();
+        plugin.configure(temp.path(), &vendor).unwrap();
+
+        let conf = read_config(&vendor.join("squizlabs/php_codesniffer/CodeSniffer.conf"));
+        assert_eq!(
+            conf.get("default_standard").map(String::as_str),
+            Some("PSR12")
+        );
+        assert_eq!(
+            conf.get("installed_paths").map(String::as_str),
+            Some("../../wp-coding-standards/wpcs")
+        );
+    }
+
+    #[test]
+    fn skips_without_phpcs() {
+        let temp = TempDir::new().unwrap();
+        let vendor = temp.path().join("vendor");
+        std::fs::create_dir_all(vendor.join("composer")).unwrap();
+        std::fs::write(
+            vendor.join("composer/installed.json"),
+            r#"{"packages": []}"#,
+        )
+        .unwrap();
+
+        let plugin = PhpcsInstallerPlugin::new();
+        assert!(plugin.configure(temp.path(), &vendor).unwrap().is_none());
+    }
+}
@@ -0,0 +1,291 @@
+//! Native replacement for `phpstan/extension-installer`.
+//!
+//! Generates `GeneratedConfig.php` listing every installed package of type
+//! `phpstan-extension`, so their configuration files are included
+//! automatically.
+
+use super::{
+    InstalledPackage, php_export, read_installed_packages, read_root_manifest, relative_path,
+    write_if_changed,
+};
+use crate::api::{EventContext, EventResult, Plugin, PluginCapability, PluginInfo};
+use crate::error::{PluginError, Result};
+use crate::hooks::Hook;
+use serde_json::{Map, Value};
+use std::path::Path;
+use tracing::debug;
+
+/// Composer package replaced by this plugin.
+pub const PACKAGE_NAME: &str = "phpstan/extension-installer";
+
+/// Package type of `phpstan` extensions.
+const EXTENSION_TYPE: &str = "phpstan-extension";
+
+/// Location of the generated file, relative to the installer package.
+const GENERATED_CONFIG_PATH: &str = "src/GeneratedConfig.php";
+
+/// Native implementation of `phpstan/extension-installer`.
+#[derive(Debug, Default)]
+pub struct PhpstanExtensionInstallerPlugin;
+
+impl PhpstanExtensionInstallerPlugin {
+    /// Create a new plugin instance.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Generate `GeneratedConfig.php` for the given vendor directory.
+    ///
+    /// Returns the number of registered extensions, or `None` if the
+    /// extension installer itself is not installed.
+    ///
+    /// # Errors
+    /// Returns error if `installed.json` cannot be read or the file cannot be written.
+    pub fn generate(&self, project_root: &Path, vendor_dir: &Path) -> Result<Option<usize>> {
+        let packages = read_installed_packages(vendor_dir)?;
+        let Some(installer) = packages.iter().find(|p| p.name == PACKAGE_NAME) else {
+            return Ok(None);
+        };
+
+        let ignored = ignored_packages(project_root);
+        let generated_path = installer.install_path.join(GENERATED_CONFIG_PATH);
+        let generated_dir = generated_path
+            .parent()
+            .ok_or_else(|| PluginError::ExecutionFailed("invalid install path".into()))?;
+
+        let mut extensions: Vec<&InstalledPackage> = Vec::new();
+        let mut not_installed = Map::new();
+        for package in &packages {
+            if ignored.iter().any(|name| name == &package.name) {
+                continue;
+            }
+            if package.package_type == EXTENSION_TYPE {
+                extensions.push(package);
+            } else if package.extra("phpstan").is_some() {
+                not_installed.insert(package.name.clone(), Value::from(package.version.clone()));
+            }
+        }
+        extensions.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut data = Map::new();
+        for package in &extensions {
+            let mut entry = Map::new();
+            entry.insert(
+                "install_path".into(),
+                Value::from(package.install_path.to_string_lossy().into_owned()),
+            );
+            entry.insert(
+                "relative_install_path".into(),
+                Value::from(relative_path(generated_dir, &package.install_path)),
+            );
+            entry.insert(
+                "extra".into(),
+                package.extra("phpstan").cloned().unwrap_or(Value::Null),
+            );
+            entry.insert("version".into(), Value::from(package.version.clone()));
+            entry.insert(
+                "phpstanVersionConstraint".into(),
+                phpstan_constraint(package).map_or(Value::Null, Value::from),
+            );
+            data.insert(package.name.clone(), Value::Object(entry));
+        }
+
+        let constraints: Vec<String> = extensions
+            .iter()
+            .filter_map(|p| phpstan_constraint(p))
+            .collect();
+        let version_constraint = if constraints.is_empty() {
+            "null".to_string()
+        } else {
+            format!(
+                "'{}'",
+                super::php_single_quote_escape(&constraints.join(", "))
+            )
+        };
+
+        let content = render(
+            &php_export(&Value::Object(data)),
+            &php_export(&Value::Object(not_installed)),
+            &version_constraint,
+        );
+
+        if write_if_changed(&generated_path, &content)? {
+            debug!(path = %generated_path.display(), "wrote PHPStan GeneratedConfig.php");
+        }
+
+        Ok(Some(extensions.len()))
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for PhpstanExtensionInstallerPlugin {
+    fn info(&self) -> PluginInfo {
+        PluginInfo::builder(PACKAGE_NAME, crate::PLUGIN_API_VERSION)
+            .description("Native PHPStan extension installer")
+            .capability(PluginCapability::Event)
+            .build()
+    }
+
+    fn capabilities(&self) -> Vec<PluginCapability> {
+        vec![PluginCapability::Event]
+    }
+
+    async fn on_event(&self, event: Hook, context: &EventContext) -> Result<EventResult> {
+        if !matches!(event, Hook::PostInstallCmd | Hook::PostUpdateCmd) {
+            return Ok(EventResult::ok());
+        }
+        let (Some(project_root), Some(vendor_dir)) = (&context.project_root, &context.vendor_dir)
+        else {
+            return Ok(EventResult::ok());
+        };
+
+        match self.generate(project_root, vendor_dir)? {
+            Some(count) => Ok(EventResult::with_message(format!(
+                "phpstan/extension-installer: {count} extension(s) installed"
+            ))),
+            None => Ok(EventResult::ok()),
+        }
+    }
+}
+
+/// Packages listed in the root `extra.phpstan/extension-installer.ignore`.
+fn ignored_packages(project_root: &Path) -> Vec<String> {
+    read_root_manifest(project_root)
+        .and_then(|root| {
+            root.get("extra")?
+                .get(PACKAGE_NAME)?
+                .get("ignore")?
+                .as_array()
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(String::from)
+                        .collect()
+                })
+        })
+        .unwrap_or_default()
+}
+
+/// The `phpstan/phpstan` requirement of an extension, if any.
+fn phpstan_constraint(package: &InstalledPackage) -> Option<String> {
+    package
+        .raw
+        .get("require")?
+        .get("phpstan/phpstan")?
+        .as_str()
+        .map(String::from)
+}
+
+fn render(extensions: &str, not_installed: &str, version_constraint: &str) -> String {
+    format!(
+        "<?php declare(strict_types = 1);
+
+namespace PHPStan\\ExtensionInstaller;
+
+/**
+ * This class is generated by phpstan/extension-installer.
+ * @internal
+ */
+final class GeneratedConfig
+{{
+
+\tpublic const EXTENSIONS = {extensions};
+
+\tpublic const NOT_INSTALLED = {not_installed};
+
+\t/** @var string|null */
+\tpublic const PHPSTAN_VERSION_CONSTRAINT = {version_constraint};
+
+\tprivate function __construct()
+\t{{
+\t}}
+
+}}
+"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_installed(vendor: &Path, packages: &Value) {
+        std::fs::create_dir_all(vendor.join("composer")).unwrap();
+        std::fs::write(
+            vendor.join("composer/installed.json"),
+            serde_json::json!({ "packages": packages }).to_string(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn generates_config_for_extensions() {
+        let temp = TempDir::new().unwrap();
+        let vendor = temp.path().join("vendor");
+        write_installed(
+            &vendor,
+            &serde_json::json!([
+                {"name": "phpstan/extension-installer", "version": "1.4.3", "type": "composer-plugin", "install-path": "../phpstan/extension-installer"},
+                {"name": "phpstan/phpstan-strict-rules", "version": "1.6.0", "type": "phpstan-extension",
+                 "install-path": "../phpstan/phpstan-strict-rules",
+                 "require": {"phpstan/phpstan": "^1.10"},
+                 "extra": {"phpstan": {"includes": ["rules.neon"]}}},
+                {"name": "acme/not-an-extension", "version": "0.1.0", "type": "library",
+                 "extra": {"phpstan": {"includes": ["x.neon"]}}}
+            ]),
+        );
+
+        let plugin = PhpstanExtensionInstallerPlugin::new();
+        let count = plugin.generate(temp.path(), &vendor).unwrap();
+        assert_eq!(count, Some(1));
+
+        let generated = std::fs::read_to_string(
+            vendor.join("phpstan/extension-installer/src/GeneratedConfig.php"),
+        )
+        .unwrap();
+        assert!(generated.contains("'phpstan/phpstan-strict-rules' => "));
+        assert!(generated.contains("'relative_install_path' => '../../phpstan-strict-rules',"));
+        assert!(generated.contains("0 => 'rules.neon',"));
+        assert!(generated.contains("'acme/not-an-extension' => '0.1.0',"));
+        assert!(generated.contains("PHPSTAN_VERSION_CONSTRAINT = '^1.10';"));
+    }
+
+    #[test]
+    fn honors_ignore_list() {
+        let temp = TempDir::new().unwrap();
+        let vendor = temp.path().join("vendor");
+        std::fs::write(
+            temp.path().join("composer.json"),
+            r#"{"extra": {"phpstan/extension-installer": {"ignore": ["phpstan/phpstan-strict-rules"]}}}"#,
+        )
+        .unwrap();
+        write_installed(
+            &vendor,
+            &serde_json::json!([
+                {"name": "phpstan/extension-installer", "version": "1.4.3", "type": "composer-plugin"},
+                {"name": "phpstan/phpstan-strict-rules", "version": "1.6.0", "type": "phpstan-extension"}
+            ]),
+        );
+
+        let plugin = PhpstanExtensionInstallerPlugin::new();
+        assert_eq!(plugin.generate(temp.path(), &vendor).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn skips_when_installer_missing() {
+        let temp = TempDir::new().unwrap();
+        let vendor = temp.path().join("vendor");
+        write_installed(
+            &vendor,
+            &serde_json::json!([
+                {"name": "phpstan/phpstan-strict-rules", "version": "1.6.0", "type": "phpstan-extension"}
+            ]),
+        );
+
+        let plugin = PhpstanExtensionInstallerPlugin::new();
+        assert_eq!(plugin.generate(temp.path(), &vendor).unwrap(), None);
+    }
+}
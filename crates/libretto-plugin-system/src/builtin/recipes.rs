@@ -0,0 +1,423 @@
+//! Symfony Flex recipe sources.
+//!
+//! Recipes are looked up either in a local checkout laid out like the
+//! `symfony/recipes` main branch (`vendor/name/version/manifest.json`) or
+//! through a Flex HTTP endpoint serving the `flex/main` branch format
+//! (`index.json` plus one JSON document per recipe).
+
+use crate::error::{PluginError, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use walkdir::WalkDir;
+
+/// Official Symfony recipes endpoint.
+pub const DEFAULT_ENDPOINT: &str =
+    "https://raw.githubusercontent.com/symfony/recipes/flex/main/index.json";
+
+/// Community recipes endpoint, used when `extra.symfony.allow-contrib` is set.
+pub const CONTRIB_ENDPOINT: &str =
+    "https://raw.githubusercontent.com/symfony/recipes-contrib/flex/main/index.json";
+
+/// A file shipped with a recipe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecipeFile {
+    /// File contents.
+    pub contents: Vec<u8>,
+    /// Whether the file should be executable.
+    pub executable: bool,
+}
+
+/// A resolved recipe for one package.
+#[derive(Debug, Clone)]
+pub struct Recipe {
+    /// Package the recipe configures.
+    pub package: String,
+    /// Recipe version (e.g. `5.3`).
+    pub version: String,
+    /// Where the recipe came from, recorded in `symfony.lock`.
+    pub origin: String,
+    /// Recipe reference (commit hash), if known.
+    pub reference: Option<String>,
+    /// The `manifest.json` contents.
+    pub manifest: Value,
+    /// Files shipped with the recipe, keyed by path relative to the recipe root.
+    pub files: BTreeMap<String, RecipeFile>,
+}
+
+/// A place recipes can be loaded from.
+#[async_trait::async_trait]
+pub trait RecipeSource: Send + Sync + Debug {
+    /// Find the best recipe for a package version.
+    async fn find(&self, package: &str, version: &str) -> Result<Option<Recipe>>;
+}
+
+/// Build recipe sources from a list of endpoints.
+///
+/// Endpoints that are not `http(s)://` URLs are treated as local recipe
+/// directories, resolved relative to the project root.
+#[must_use]
+pub fn sources_from_endpoints(
+    endpoints: &[String],
+    project_root: &Path,
+) -> Vec<Box<dyn RecipeSource>> {
+    endpoints
+        .iter()
+        .map(|endpoint| -> Box<dyn RecipeSource> {
+            if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+                Box::new(HttpRecipeSource::new(endpoint.clone()))
+            } else {
+                let path = endpoint.strip_prefix("file://").unwrap_or(endpoint);
+                Box::new(LocalRecipeSource::new(project_root.join(path)))
+            }
+        })
+        .collect()
+}
+
+/// Recipes from a local directory.
+#[derive(Debug, Clone)]
+pub struct LocalRecipeSource {
+    root: PathBuf,
+}
+
+impl LocalRecipeSource {
+    /// Create a source reading recipes below `root`.
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn load(&self, package: &str, version: &str, dir: &Path) -> Result<Recipe> {
+        let manifest_content = std::fs::read_to_string(dir.join("manifest.json"))?;
+        let manifest: Value = serde_json::from_str(&manifest_content).map_err(|e| {
+            PluginError::InvalidMetadata(format!("{}/manifest.json: {e}", dir.display()))
+        })?;
+
+        let mut files = BTreeMap::new();
+        for entry in WalkDir::new(dir)
+            .into_iter()
+            .filter_map(std::result::Result::ok)
+            .filter(|e| e.file_type().is_file())
+        {
+            let Ok(relative) = entry.path().strip_prefix(dir) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if relative == "manifest.json" || relative == "post-install.txt" {
+                continue;
+            }
+            files.insert(
+                relative,
+                RecipeFile {
+                    contents: std::fs::read(entry.path())?,
+                    executable: is_executable(entry.path()),
+                },
+            );
+        }
+
+        Ok(Recipe {
+            package: package.to_string(),
+            version: version.to_string(),
+            origin: self.root.to_string_lossy().into_owned(),
+            reference: None,
+            manifest,
+            files,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl RecipeSource for LocalRecipeSource {
+    async fn find(&self, package: &str, version: &str) -> Result<Option<Recipe>> {
+        let package_dir = self.root.join(package);
+        let Ok(entries) = std::fs::read_dir(&package_dir) else {
+            return Ok(None);
+        };
+
+        let available: Vec<String> = entries
+            .filter_map(std::result::Result::ok)
+            .filter(|e| e.path().join("manifest.json").is_file())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+
+        match select_recipe_version(&available, version) {
+            Some(recipe_version) => {
+                let dir = package_dir.join(&recipe_version);
+                self.load(package, &recipe_version, &dir).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Timeout for a single recipe endpoint request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Recipes from a Flex HTTP endpoint.
+#[derive(Debug)]
+pub struct HttpRecipeSource {
+    index_url: String,
+    client: reqwest::Client,
+    index: OnceCell<Value>,
+}
+
+impl HttpRecipeSource {
+    /// Create a source for the given `index.json` URL.
+    #[must_use]
+    pub fn new(index_url: impl Into<String>) -> Self {
+        Self {
+            index_url: index_url.into(),
+            client: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(10))
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            index: OnceCell::new(),
+        }
+    }
+
+    async fn fetch_json(&self, url: &str) -> Result<Value> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| PluginError::ExecutionFailed(format!("failed to fetch {url}: {e}")))?;
+        if !response.status().is_success() {
+            return Err(PluginError::ExecutionFailed(format!(
+                "failed to fetch {url}: HTTP {}",
+                response.status()
+            )));
+        }
+        let body = response
+            .text()
+            .await
+            .map_err(|e| PluginError::ExecutionFailed(format!("failed to read {url}: {e}")))?;
+        serde_json::from_str(&body).map_err(|e| PluginError::InvalidMetadata(format!("{url}: {e}")))
+    }
+
+    async fn index(&self) -> Result<&Value> {
+        self.index
+            .get_or_try_init(|| self.fetch_json(&self.index_url))
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl RecipeSource for HttpRecipeSource {
+    async fn find(&self, package: &str, version: &str) -> Result<Option<Recipe>> {
+        let index = self.index().await?;
+
+        let available: Vec<String> = index
+            .get("recipes")
+            .and_then(|r| r.get(package))
+            .and_then(Value::as_array)
+            .map(|versions| {
+                versions
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let Some(recipe_version) = select_recipe_version(&available, version) else {
+            return Ok(None);
+        };
+
+        let template = index
+            .get("_links")
+            .and_then(|l| l.get("recipe_template"))
+            .and_then(Value::as_str)
+            .map_or_else(
+                || {
+                    let base = self
+                        .index_url
+                        .rsplit_once('/')
+                        .map_or(self.index_url.as_str(), |(base, _)| base);
+                    format!("{base}/{{package_dotted}}.{{version}}.json")
+                },
+                String::from,
+            );
+        let url = template
+            .replace("{package_dotted}", &package.replace('/', "."))
+            .replace("{package}", package)
+            .replace("{version}", &recipe_version);
+
+        let document = self.fetch_json(&url).await?;
+        let Some(entry) = document.get("manifests").and_then(|m| m.get(package)) else {
+            return Ok(None);
+        };
+
+        let mut files = BTreeMap::new();
+        if let Some(entries) = entry.get("files").and_then(Value::as_object) {
+            for (path, file) in entries {
+                let contents = match file.get("contents") {
+                    Some(Value::Array(lines)) => lines
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                        .into_bytes(),
+                    Some(Value::String(s)) => s.clone().into_bytes(),
+                    _ => continue,
+                };
+                files.insert(
+                    path.clone(),
+                    RecipeFile {
+                        contents,
+                        executable: file
+                            .get("executable")
+                            .and_then(Value::as_bool)
+                            .unwrap_or(false),
+                    },
+                );
+            }
+        }
+
+        let origin = index
+            .get("_links")
+            .and_then(|l| l.get("repository"))
+            .and_then(Value::as_str)
+            .map_or_else(|| self.index_url.clone(), String::from);
+
+        Ok(Some(Recipe {
+            package: package.to_string(),
+            version: recipe_version,
+            origin,
+            reference: entry.get("ref").and_then(Value::as_str).map(String::from),
+            manifest: entry.get("manifest").cloned().unwrap_or(Value::Null),
+            files,
+        }))
+    }
+}
+
+/// Pick the highest recipe version not newer than the installed version.
+///
+/// Recipe versions are `major.minor` strings. Development versions of the
+/// package (`dev-*`) use the newest recipe.
+#[must_use]
+pub fn select_recipe_version(available: &[String], installed: &str) -> Option<String> {
+    let installed = parse_major_minor(installed);
+    available
+        .iter()
+        .filter_map(|v| parse_major_minor(v).map(|parsed| (parsed, v)))
+        .filter(|(parsed, _)| installed.is_none_or(|installed| *parsed <= installed))
+        .max_by_key(|(parsed, _)| *parsed)
+        .map(|(_, v)| v.clone())
+}
+
+/// Parse the `major.minor` prefix of a version string.
+fn parse_major_minor(version: &str) -> Option<(u64, u64)> {
+    let version = version.trim_start_matches(['v', 'V']);
+    let mut parts = version.split(['.', '-', '+']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().and_then(|m| m.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn selects_highest_compatible_version() {
+        let available = vec!["3.3".to_string(), "5.3".to_string(), "6.4".to_string()];
+        assert_eq!(
+            select_recipe_version(&available, "v5.4.12"),
+            Some("5.3".to_string())
+        );
+        assert_eq!(
+            select_recipe_version(&available, "6.4.0"),
+            Some("6.4".to_string())
+        );
+        assert_eq!(
+            select_recipe_version(&available, "dev-main"),
+            Some("6.4".to_string())
+        );
+        assert_eq!(select_recipe_version(&available, "2.0.0"), None);
+    }
+
+    #[tokio::test]
+    async fn local_source_loads_recipe() {
+        let temp = TempDir::new().unwrap();
+        let recipe_dir = temp.path().join("acme/bundle/1.0");
+        std::fs::create_dir_all(recipe_dir.join("config/packages")).unwrap();
+        std::fs::write(
+            recipe_dir.join("manifest.json"),
+            r#"{"bundles": {"Acme\\Bundle\\AcmeBundle": ["all"]}}"#,
+        )
+        .unwrap();
+        std::fs::write(recipe_dir.join("config/packages/acme.yaml"), "acme: ~\n").unwrap();
+
+        let source = LocalRecipeSource::new(temp.path());
+        let recipe = source.find("acme/bundle", "1.2.0").await.unwrap().unwrap();
+        assert_eq!(recipe.version, "1.0");
+        assert!(recipe.files.contains_key("config/packages/acme.yaml"));
+        assert!(recipe.manifest.get("bundles").is_some());
+
+        assert!(source.find("acme/other", "1.0.0").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn http_source_uses_index_and_recipe_template() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/index.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "recipes": {"acme/bundle": ["1.0", "2.0"]},
+                "_links": {
+                    "repository": "github.com/acme/recipes",
+                    "recipe_template": format!("{}/{{package_dotted}}.{{version}}.json", server.uri())
+                }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/acme.bundle.1.0.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "manifests": {"acme/bundle": {
+                    "manifest": {"env": {"ACME_DSN": "null://"}},
+                    "files": {"config/packages/acme.yaml": {"contents": ["acme:", "    dsn: '%env(ACME_DSN)%'"], "executable": false}},
+                    "ref": "abc123"
+                }}
+            })))
+            .mount(&server)
+            .await;
+
+        let source = HttpRecipeSource::new(format!("{}/index.json", server.uri()));
+        let recipe = source.find("acme/bundle", "1.5.0").await.unwrap().unwrap();
+        assert_eq!(recipe.version, "1.0");
+        assert_eq!(recipe.origin, "github.com/acme/recipes");
+        assert_eq!(recipe.reference.as_deref(), Some("abc123"));
+        assert_eq!(
+            recipe.files["config/packages/acme.yaml"].contents,
+            b"acme:\n    dsn: '%env(ACME_DSN)%'".to_vec()
+        );
+
+        assert!(
+            source
+                .find("acme/unknown", "1.0.0")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Native replacement for the `symfony/runtime` Composer plugin.
+//!
+//! Generates `vendor/autoload_runtime.php`, the front controller bootstrap
+//! required by `public/index.php` in Symfony applications. The runtime class
+//! and options come from `extra.runtime` in the root composer.json.
+
+use super::{
+    php_export, read_installed_packages, read_root_manifest, relative_path, write_if_changed,
+};
+use crate::api::{EventContext, EventResult, Plugin, PluginCapability, PluginInfo};
+use crate::error::Result;
+use crate::hooks::Hook;
+use serde_json::{Map, Value};
+use std::path::Path;
+use tracing::debug;
+
+/// Composer package replaced by this plugin.
+pub const PACKAGE_NAME: &str = "symfony/runtime";
+
+/// Runtime used when `extra.runtime.class` is not set.
+const DEFAULT_RUNTIME_CLASS: &str = "Symfony\\Component\\Runtime\\SymfonyRuntime";
+
+/// Name of the generated file inside the vendor directory.
+const RUNTIME_FILE: &str = "autoload_runtime.php";
+
+/// Template shipped with `symfony/runtime` (`autoload_runtime.template`).
+const DEFAULT_TEMPLATE: &str = r#"<?php
+
+// autoload_runtime.php @generated by Symfony Runtime
+
+if (true === (require_once __DIR__.'/autoload.php') || empty($_SERVER['SCRIPT_FILENAME'])) {
+    return;
+}
+
+$app = require $_SERVER['SCRIPT_FILENAME'];
+
+if (!is_object($app)) {
+    throw new TypeError(sprintf('Invalid return value: callable object expected, "%s" returned from "%s".', get_debug_type($app), $_SERVER['SCRIPT_FILENAME']));
+}
+
+$runtime = $_SERVER['APP_RUNTIME'] ?? $_ENV['APP_RUNTIME'] ?? %runtime_class%;
+$runtime = new $runtime(($_SERVER['APP_RUNTIME_OPTIONS'] ?? $_ENV['APP_RUNTIME_OPTIONS'] ?? []) + %runtime_options%);
+
+[$app, $args] = $runtime
+    ->getResolver($app)
+    ->resolve();
+
+$app = $app(...$args);
+
+exit(
+    $runtime
+        ->getRunner($app)
+        ->run()
+);
+"#;
+
+/// Native implementation of the `symfony/runtime` plugin.
+#[derive(Debug, Default)]
+pub struct SymfonyRuntimePlugin;
+
+impl SymfonyRuntimePlugin {
+    /// Create a new plugin instance.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Generate `autoload_runtime.php` in the vendor directory.
+    ///
+    /// Returns `false` if `symfony/runtime` is not installed.
+    ///
+    /// # Errors
+    /// Returns error if `installed.json`, a custom template or the output cannot be accessed.
+    pub fn generate(&self, project_root: &Path, vendor_dir: &Path) -> Result<bool> {
+        let packages = read_installed_packages(vendor_dir)?;
+        if !packages.iter().any(|p| p.name == PACKAGE_NAME) {
+            return Ok(false);
+        }
+
+        let mut options = read_root_manifest(project_root)
+            .and_then(|root| root.get("extra")?.get("runtime")?.as_object().cloned())
+            .unwrap_or_default();
+
+        let runtime_class = options
+            .remove("class")
+            .and_then(|c| c.as_str().map(String::from))
+            .unwrap_or_else(|| DEFAULT_RUNTIME_CLASS.to_string());
+
+        let template = match options.remove("autoload_template") {
+            Some(Value::String(path)) => std::fs::read_to_string(project_root.join(path))?,
+            _ => DEFAULT_TEMPLATE.to_string(),
+        };
+
+        let project_dir = project_dir_expression(vendor_dir, project_root);
+        let code = template
+            .replace("%project_dir%", &project_dir)
+            .replace(
+                "%runtime_class%",
+                &php_export(&Value::from(runtime_class.as_str())),
+            )
+            .replace(
+                "%runtime_options%",
+                &runtime_options(&options, &project_dir),
+            );
+
+        let path = vendor_dir.join(RUNTIME_FILE);
+        if write_if_changed(&path, &code)? {
+            debug!(path = %path.display(), "wrote Symfony autoload_runtime.php");
+        }
+        Ok(true)
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for SymfonyRuntimePlugin {
+    fn info(&self) -> PluginInfo {
+        PluginInfo::builder(PACKAGE_NAME, crate::PLUGIN_API_VERSION)
+            .description("Native Symfony Runtime autoloader generator")
+            .capability(PluginCapability::Autoload)
+            .build()
+    }
+
+    fn capabilities(&self) -> Vec<PluginCapability> {
+        vec![PluginCapability::Autoload]
+    }
+
+    async fn on_event(&self, event: Hook, context: &EventContext) -> Result<EventResult> {
+        if event != Hook::PostAutoloadDump {
+            return Ok(EventResult::ok());
+        }
+        let (Some(project_root), Some(vendor_dir)) = (&context.project_root, &context.vendor_dir)
+        else {
+            return Ok(EventResult::ok());
+        };
+
+        if self.generate(project_root, vendor_dir)? {
+            Ok(EventResult::with_message(format!(
+                "Generated {}",
+                vendor_dir.join(RUNTIME_FILE).display()
+            )))
+        } else {
+            Ok(EventResult::ok())
+        }
+    }
+}
+
+/// PHP expression for the project directory, relative to the vendor directory.
+fn project_dir_expression(vendor_dir: &Path, project_root: &Path) -> String {
+    let relative = relative_path(vendor_dir, project_root);
+    let mut rest = relative.as_str();
+    let mut nesting = 0;
+    while let Some(stripped) = rest.strip_prefix("..") {
+        nesting += 1;
+        rest = stripped.strip_prefix('/').unwrap_or(stripped);
+    }
+    if rest == "." {
+        rest = "";
+    }
+
+    if rest.is_empty() {
+        format!("dirname(__DIR__, {nesting})")
+    } else if nesting > 0 {
+        format!("dirname(__DIR__, {nesting}).'/{rest}'")
+    } else {
+        format!("__DIR__.'/{rest}'")
+    }
+}
+
+/// Render the options array literal, appending `project_dir`.
+fn runtime_options(options: &Map<String, Value>, project_dir: &str) -> String {
+    let exported = php_export(&Value::Object(options.clone()));
+    // Strip the leading "array (" and trailing ")" like Symfony does
+    let inner = &exported["array (".len()..exported.len() - 1];
+    format!("[{inner}  'project_dir' => {project_dir},\n]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn install_runtime(vendor: &Path) {
+        std::fs::create_dir_all(vendor.join("composer")).unwrap();
+        std::fs::write(
+            vendor.join("composer/installed.json"),
+            r#"{"packages": [{"name": "symfony/runtime", "version": "v7.1.1", "type": "composer-plugin"}]}"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn generates_default_runtime() {
+        let temp = TempDir::new().unwrap();
+        let vendor = temp.path().join("vendor");
+        install_runtime(&vendor);
+
+        assert!(
+            SymfonyRuntimePlugin::new()
+                .generate(temp.path(), &vendor)
+                .unwrap()
+        );
+
+        let code = std::fs::read_to_string(vendor.join(RUNTIME_FILE)).unwrap();
+        assert!(code.contains("?? 'Symfony\\\\Component\\\\Runtime\\\\SymfonyRuntime';"));
+        assert!(code.contains("+ [\n  'project_dir' => dirname(__DIR__, 1),\n]);"));
+    }
+
+    #[test]
+    fn honors_extra_runtime() {
+        let temp = TempDir::new().unwrap();
+        let vendor = temp.path().join("vendor");
+        install_runtime(&vendor);
+        std::fs::write(
+            temp.path().join("composer.json"),
+            r#"{"extra": {"runtime": {"class": "Runtime\\Swoole\\Runtime", "dotenv_path": ".env.local"}}}"#,
+        )
+        .unwrap();
+
+        SymfonyRuntimePlugin::new()
+            .generate(temp.path(), &vendor)
+            .unwrap();
+
+        let code = std::fs::read_to_string(vendor.join(RUNTIME_FILE)).unwrap();
+        assert!(code.contains("?? 'Runtime\\\\Swoole\\\\Runtime';"));
+        assert!(code.contains(
+            "  'dotenv_path' => '.env.local',\n  'project_dir' => dirname(__DIR__, 1),\n]"
+        ));
+    }
+
+    #[test]
+    fn skips_without_runtime_package() {
+        let temp = TempDir::new().unwrap();
+        let vendor = temp.path().join("vendor");
+        std::fs::create_dir_all(vendor.join("composer")).unwrap();
+        std::fs::write(
+            vendor.join("composer/installed.json"),
+            r#"{"packages": []}"#,
+        )
+        .unwrap();
+
+        assert!(
+            !SymfonyRuntimePlugin::new()
+                .generate(temp.path(), &vendor)
+                .unwrap()
+        );
+        assert!(!vendor.join(RUNTIME_FILE).exists());
+    }
+
+    #[test]
+    fn project_dir_for_nested_vendor() {
+        let expr = project_dir_expression(Path::new("/app/deps/vendor"), Path::new("/app"));
+        assert_eq!(expr, "dirname(__DIR__, 2)");
+        let expr = project_dir_expression(Path::new("/app/vendor"), Path::new("/app/site"));
+        assert_eq!(expr, "dirname(__DIR__, 1).'/site'");
+    }
+}
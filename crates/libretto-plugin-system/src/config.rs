@@ -4,6 +4,7 @@
 //! including per-plugin configuration from composer.json "extra" section.
 
 use crate::sandbox::SandboxConfig;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
@@ -231,7 +232,6 @@ pub struct PluginSandboxConfig {
 
 /// Configuration loaded from composer.json.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[allow(dead_code)]
 pub struct ComposerPluginConfig {
     /// Global plugin configuration.
     #[serde(default, rename = "plugin-config")]
@@ -246,14 +246,24 @@ pub struct ComposerPluginConfig {
     pub disabled: Vec<String>,
 
     /// Allowed plugins (if set, only these are allowed).
-    #[serde(default, rename = "allow-plugins")]
-    pub allowed: Option<HashMap<String, bool>>,
+    ///
+    /// Keys may contain `*` wildcards and are kept in declaration order. A
+    /// boolean `allow-plugins` value is stored as a single `*` entry.
+    #[serde(
+        default,
+        rename = "allow-plugins",
+        deserialize_with = "deserialize_allow_plugins"
+    )]
+    pub allowed: Option<IndexMap<String, bool>>,
 }
 
-#[allow(dead_code)]
 impl ComposerPluginConfig {
     /// Load from a composer.json "config" section.
     ///
+    /// `serde_json` sorts object keys unless its `preserve_order` feature is
+    /// enabled, which loses the `allow-plugins` declaration order; parse the
+    /// manifest with `sonic_rs` where that order matters.
+    ///
     /// # Errors
     /// Returns error if parsing fails.
     pub fn from_json(json: &serde_json::Value) -> Result<Self, sonic_rs::Error> {
@@ -266,14 +276,29 @@ impl ComposerPluginConfig {
     #[must_use]
     pub fn is_plugin_allowed(&self, plugin_id: &str) -> bool {
         // If allowed list is set, check it
-        if let Some(ref allowed) = self.allowed {
-            return allowed.get(plugin_id).copied().unwrap_or(false);
+        if self.allowed.is_some() {
+            return self.plugin_allowance(plugin_id).unwrap_or(false);
         }
 
         // Otherwise check disabled list
         !self.disabled.contains(&plugin_id.to_string())
     }
 
+    /// Look up a plugin in `allow-plugins`.
+    ///
+    /// Returns `None` if `allow-plugins` is unset or no entry matches the
+    /// plugin, which is when Composer asks the user. Like Composer, the first
+    /// matching entry in declaration order wins.
+    #[must_use]
+    pub fn plugin_allowance(&self, plugin_id: &str) -> Option<bool> {
+        let plugin_id = plugin_id.to_lowercase();
+        self.allowed
+            .as_ref()?
+            .iter()
+            .find(|(pattern, _)| wildcard_match(&pattern.to_lowercase(), &plugin_id))
+            .map(|(_, allow)| *allow)
+    }
+
     /// Get configuration for a specific plugin.
     #[must_use]
     pub fn get_plugin_config(&self, plugin_id: &str) -> Option<&PluginConfig> {
@@ -281,6 +306,51 @@ impl ComposerPluginConfig {
     }
 }
 
+/// Match a package name against a Composer `*` wildcard pattern.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return false;
+    };
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+/// Deserialize `allow-plugins`, which may be a boolean or a map.
+fn deserialize_allow_plugins<'de, D>(
+    deserializer: D,
+) -> Result<Option<IndexMap<String, bool>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum AllowPlugins {
+        All(bool),
+        Map(IndexMap<String, bool>),
+    }
+
+    Ok(
+        Option::<AllowPlugins>::deserialize(deserializer)?.map(|value| match value {
+            AllowPlugins::All(allow) => IndexMap::from([("*".to_string(), allow)]),
+            AllowPlugins::Map(map) => map,
+        }),
+    )
+}
+
 /// Duration serialization for optional Duration fields.
 mod option_duration_serde {
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -421,7 +491,7 @@ mod tests {
     #[test]
     fn composer_plugin_config_allowed() {
         let config = ComposerPluginConfig {
-            allowed: Some(HashMap::from([
+            allowed: Some(IndexMap::from([
                 ("vendor/allowed".to_string(), true),
                 ("vendor/denied".to_string(), false),
             ])),
@@ -433,6 +503,57 @@ mod tests {
         assert!(!config.is_plugin_allowed("vendor/unknown"));
     }
 
+    #[test]
+    fn composer_plugin_config_wildcards() {
+        let config = ComposerPluginConfig {
+            allowed: Some(IndexMap::from([
+                ("phpstan/blocked-but-listed".to_string(), true),
+                ("phpstan/blocked-*".to_string(), false),
+                ("phpstan/*".to_string(), true),
+            ])),
+            ..Default::default()
+        };
+
+        assert!(config.is_plugin_allowed("phpstan/extension-installer"));
+        assert!(config.is_plugin_allowed("PHPStan/Extension-Installer"));
+        assert!(!config.is_plugin_allowed("phpstan/blocked-plugin"));
+        assert!(config.is_plugin_allowed("phpstan/blocked-but-listed"));
+        assert_eq!(config.plugin_allowance("other/plugin"), None);
+    }
+
+    #[test]
+    fn composer_plugin_config_first_match_wins() {
+        let config: ComposerPluginConfig = sonic_rs::from_str(
+            r#"{"allow-plugins": {
+                "vendor/*": false,
+                "vendor/foo-*": true,
+                "vendor/foo-bar": true,
+                "other/foo-bar": true,
+                "other/*": false
+            }}"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.plugin_allowance("vendor/foo-bar"), Some(false));
+        assert_eq!(config.plugin_allowance("other/foo-bar"), Some(true));
+        assert_eq!(config.plugin_allowance("other/baz"), Some(false));
+    }
+
+    #[test]
+    fn composer_plugin_config_boolean_allow_plugins() {
+        let json = serde_json::json!({"config": {"allow-plugins": true}});
+        let config = ComposerPluginConfig::from_json(&json).unwrap();
+        assert!(config.is_plugin_allowed("any/plugin"));
+
+        let json = serde_json::json!({"config": {"allow-plugins": false}});
+        let config = ComposerPluginConfig::from_json(&json).unwrap();
+        assert_eq!(config.plugin_allowance("any/plugin"), Some(false));
+
+        let json = serde_json::json!({"config": {}});
+        let config = ComposerPluginConfig::from_json(&json).unwrap();
+        assert_eq!(config.plugin_allowance("any/plugin"), None);
+    }
+
     #[test]
     fn composer_plugin_config_disabled() {
         let config = ComposerPluginConfig {
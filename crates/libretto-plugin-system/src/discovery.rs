@@ -3,7 +3,7 @@
 //! This module handles scanning the vendor directory for Composer plugins,
 //! parsing their metadata from composer.json files.
 
-use crate::api::PluginMetadata;
+use crate::api::{PluginMetadata, WasmManifest};
 use crate::config::PluginConfig;
 use crate::error::Result;
 use dashmap::DashMap;
@@ -140,18 +140,7 @@ impl PluginDiscovery {
             .and_then(|e| e.capabilities.as_ref())
             .map(|caps| {
                 caps.iter()
-                    .filter_map(|c| match c.as_str() {
-                        "install" => Some(crate::api::PluginCapability::Install),
-                        "resolve" => Some(crate::api::PluginCapability::Resolve),
-                        "command" => Some(crate::api::PluginCapability::Command),
-                        "event" => Some(crate::api::PluginCapability::Event),
-                        "repository" => Some(crate::api::PluginCapability::Repository),
-                        "autoload" => Some(crate::api::PluginCapability::Autoload),
-                        "download" => Some(crate::api::PluginCapability::Download),
-                        "source" => Some(crate::api::PluginCapability::Source),
-                        "script" => Some(crate::api::PluginCapability::Script),
-                        _ => None,
-                    })
+                    .filter_map(|c| crate::api::PluginCapability::parse(c))
                     .collect()
             })
             .unwrap_or_default();
@@ -175,6 +164,20 @@ impl PluginDiscovery {
         // Get plugin directory (parent of composer.json)
         let plugin_path = path.parent().map(Path::to_path_buf);
 
+        // WebAssembly components are resolved relative to the package
+        let wasm = extra.and_then(|e| e.wasm_plugin.as_ref()).map(|entry| {
+            let (component, fuel) = match entry {
+                WasmPluginEntry::Path(component) => (component, None),
+                WasmPluginEntry::Detailed { component, fuel } => (component, *fuel),
+            };
+            WasmManifest {
+                component: plugin_path
+                    .as_deref()
+                    .map_or_else(|| PathBuf::from(component), |dir| dir.join(component)),
+                fuel,
+            }
+        });
+
         Ok(PluginMetadata {
             name: composer.name.clone().unwrap_or_else(|| "unknown".into()),
             version: composer.version.clone().unwrap_or_else(|| "0.0.0".into()),
@@ -184,6 +187,7 @@ impl PluginDiscovery {
             capabilities,
             path: plugin_path,
             config,
+            wasm,
             authors,
             license: composer.license.clone(),
             homepage: composer.homepage.clone(),
@@ -294,6 +298,23 @@ struct ComposerExtra {
     /// Required Libretto version.
     #[serde(rename = "require-libretto")]
     require_libretto: Option<String>,
+    /// WebAssembly component declaration.
+    #[serde(rename = "libretto-wasm-plugin")]
+    wasm_plugin: Option<WasmPluginEntry>,
+}
+
+/// `extra.libretto-wasm-plugin` value.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum WasmPluginEntry {
+    /// Component path relative to the package.
+    Path(String),
+    /// Component path with limits.
+    Detailed {
+        component: String,
+        #[serde(default)]
+        fuel: Option<u64>,
+    },
 }
 
 /// Installed packages from composer.lock or installed.json.
@@ -425,6 +446,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_wasm_plugin_declaration() {
+        let temp = TempDir::new().unwrap();
+        let discovery = PluginDiscovery::new();
+
+        let short = temp.path().join("short");
+        std::fs::create_dir_all(&short).unwrap();
+        std::fs::write(
+            short.join("composer.json"),
+            r#"{
+                "name": "acme/short",
+                "type": "libretto-plugin",
+                "extra": { "libretto-wasm-plugin": "dist/plugin.wasm" }
+            }"#,
+        )
+        .unwrap();
+
+        let metadata = discovery.scan_package(&short).unwrap().unwrap();
+        let wasm = metadata.wasm.unwrap();
+        assert_eq!(wasm.component, short.join("dist/plugin.wasm"));
+        assert_eq!(wasm.fuel, None);
+
+        let detailed = temp.path().join("detailed");
+        std::fs::create_dir_all(&detailed).unwrap();
+        std::fs::write(
+            detailed.join("composer.json"),
+            r#"{
+                "name": "acme/detailed",
+                "type": "libretto-plugin",
+                "extra": {
+                    "libretto-wasm-plugin": { "component": "plugin.wasm", "fuel": 1000 }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let metadata = discovery.scan_package(&detailed).unwrap().unwrap();
+        let wasm = metadata.wasm.unwrap();
+        assert_eq!(wasm.component, detailed.join("plugin.wasm"));
+        assert_eq!(wasm.fuel, Some(1000));
+    }
+
     #[test]
     fn parse_composer_json_non_plugin() {
         let temp = TempDir::new().unwrap();
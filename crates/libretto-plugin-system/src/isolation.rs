@@ -0,0 +1,1084 @@
+//! OS-level isolation for plugin and script processes.
+//!
+//! [`Sandbox`](crate::Sandbox) decides what a plugin may do; this module
+//! enforces those decisions for child processes on Linux:
+//!
+//! - **Filesystem**: Landlock rules derived from the allowed read/write paths
+//! - **Network**: a fresh (empty) network namespace when network is blocked
+//! - **Memory**: `RLIMIT_AS` from the memory limit
+//! - **Exec**: a seccomp filter handing `execve`/`execveat` to a supervisor
+//!   thread when exec is not allowed. The supervisor lets exactly one call
+//!   through, the one starting the program, and denies every later one, so
+//!   neither shells nor the dynamic loader can start another binary.
+//!
+//! Every feature degrades gracefully: when the kernel lacks support, the
+//! process still starts and the missing feature is listed in the returned
+//! [`IsolationReport`]. On other platforms nothing is enforced.
+
+#![allow(unsafe_code)]
+
+use crate::sandbox::SandboxConfig;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tracing::{debug, warn};
+
+/// System directories that must stay readable for interpreters to run.
+pub const SYSTEM_READ_PATHS: &[&str] = &[
+    "/usr",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/bin",
+    "/sbin",
+    "/etc",
+    "/opt",
+    "/nix/store",
+    "/proc",
+    "/dev",
+];
+
+/// Device files that stay writable.
+const DEVICE_WRITE_PATHS: &[&str] = &["/dev/null", "/dev/tty"];
+
+/// Features already reported as unavailable (bitmask), to warn only once.
+static WARNED: AtomicU8 = AtomicU8::new(0);
+
+/// An isolation feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IsolationFeature {
+    /// Landlock filesystem restrictions.
+    Filesystem,
+    /// Network namespace.
+    Network,
+    /// Address space limit.
+    Memory,
+    /// seccomp `execve` filter.
+    Exec,
+}
+
+impl IsolationFeature {
+    const fn bit(self) -> u8 {
+        match self {
+            Self::Filesystem => 1,
+            Self::Network => 2,
+            Self::Memory => 4,
+            Self::Exec => 8,
+        }
+    }
+}
+
+impl std::fmt::Display for IsolationFeature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Filesystem => write!(f, "filesystem (Landlock)"),
+            Self::Network => write!(f, "network namespace"),
+            Self::Memory => write!(f, "memory limit (RLIMIT_AS)"),
+            Self::Exec => write!(f, "exec filter (seccomp)"),
+        }
+    }
+}
+
+/// Outcome of applying isolation to a command.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IsolationReport {
+    /// Features that will be enforced on the child.
+    pub enforced: Vec<IsolationFeature>,
+    /// Requested features that could not be enforced, with the reason.
+    pub unavailable: Vec<(IsolationFeature, String)>,
+}
+
+impl IsolationReport {
+    /// Whether every requested feature is enforced.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.unavailable.is_empty()
+    }
+
+    /// Check if a feature is enforced.
+    #[must_use]
+    pub fn is_enforced(&self, feature: IsolationFeature) -> bool {
+        self.enforced.contains(&feature)
+    }
+
+    fn missing(&mut self, feature: IsolationFeature, reason: impl Into<String>) {
+        let reason = reason.into();
+        if WARNED.fetch_or(feature.bit(), Ordering::Relaxed) & feature.bit() == 0 {
+            warn!(feature = %feature, reason = %reason, "sandbox feature unavailable");
+        } else {
+            debug!(feature = %feature, reason = %reason, "sandbox feature unavailable");
+        }
+        self.unavailable.push((feature, reason));
+    }
+}
+
+/// Kernel support for isolation features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IsolationSupport {
+    /// Landlock ABI version, if Landlock is enabled.
+    pub landlock_abi: Option<i32>,
+    /// Whether a network namespace can be created (directly or via a user namespace).
+    pub network_namespace: bool,
+    /// Whether seccomp can pass `execve` to a supervisor (user notification).
+    pub seccomp: bool,
+}
+
+impl IsolationSupport {
+    /// Detect support on the running kernel (cached).
+    #[must_use]
+    pub fn detect() -> Self {
+        static SUPPORT: std::sync::OnceLock<IsolationSupport> = std::sync::OnceLock::new();
+        *SUPPORT.get_or_init(imp::detect)
+    }
+}
+
+/// Isolation settings for a child process.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessIsolation {
+    read_paths: Vec<PathBuf>,
+    write_paths: Vec<PathBuf>,
+    block_network: bool,
+    memory_limit: Option<u64>,
+    deny_exec: bool,
+    required: bool,
+}
+
+impl ProcessIsolation {
+    /// Create isolation that only restricts the filesystem to system paths.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Derive isolation for a plugin from the sandbox configuration.
+    ///
+    /// Returns `None` if the sandbox is disabled globally or for this plugin.
+    #[must_use]
+    pub fn from_config(config: &SandboxConfig, plugin_id: &str) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let override_config = config.plugin_overrides.get(plugin_id);
+        if override_config.is_some_and(|o| o.disable) {
+            return None;
+        }
+
+        let mut isolation = Self {
+            read_paths: config.allowed_read_paths.clone(),
+            write_paths: config.allowed_write_paths.clone(),
+            block_network: config.block_network,
+            memory_limit: override_config
+                .and_then(|o| o.memory_limit)
+                .or(Some(config.memory_limit))
+                .filter(|limit| *limit > 0)
+                .and_then(|limit| u64::try_from(limit).ok()),
+            deny_exec: !config.allow_exec,
+            required: config.require_isolation,
+        };
+        if let Some(o) = override_config {
+            isolation
+                .read_paths
+                .extend(o.additional_read_paths.iter().cloned());
+            isolation
+                .write_paths
+                .extend(o.additional_write_paths.iter().cloned());
+        }
+        Some(isolation)
+    }
+
+    /// Allow reading below a path.
+    #[must_use]
+    pub fn with_read_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.read_paths.push(path.into());
+        self
+    }
+
+    /// Allow reading and writing below a path.
+    #[must_use]
+    pub fn with_write_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.write_paths.push(path.into());
+        self
+    }
+
+    /// Run the process without network access.
+    #[must_use]
+    pub const fn with_block_network(mut self, block: bool) -> Self {
+        self.block_network = block;
+        self
+    }
+
+    /// Limit the address space of the process (bytes).
+    #[must_use]
+    pub const fn with_memory_limit(mut self, limit: Option<u64>) -> Self {
+        self.memory_limit = limit;
+        self
+    }
+
+    /// Deny executing other programs from the process.
+    #[must_use]
+    pub const fn with_deny_exec(mut self, deny: bool) -> Self {
+        self.deny_exec = deny;
+        self
+    }
+
+    /// Require every requested feature to be enforced.
+    #[must_use]
+    pub const fn with_required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Whether the process must not start unless every feature is enforced.
+    #[must_use]
+    pub const fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Paths readable by the process, including system paths.
+    #[must_use]
+    pub fn read_paths(&self) -> Vec<PathBuf> {
+        SYSTEM_READ_PATHS
+            .iter()
+            .map(PathBuf::from)
+            .chain(self.read_paths.iter().cloned())
+            .collect()
+    }
+
+    /// Paths writable by the process, including the temp directory.
+    #[must_use]
+    pub fn write_paths(&self) -> Vec<PathBuf> {
+        DEVICE_WRITE_PATHS
+            .iter()
+            .map(PathBuf::from)
+            .chain(std::iter::once(std::env::temp_dir()))
+            .chain(self.write_paths.iter().cloned())
+            .collect()
+    }
+
+    /// Install the isolation on a command before it is spawned.
+    ///
+    /// Must be called after the program, arguments and environment are set.
+    pub fn apply(&self, command: &mut Command) -> IsolationReport {
+        imp::apply(self, command)
+    }
+
+    /// Install the isolation on a Tokio command before it is spawned.
+    pub fn apply_tokio(&self, command: &mut tokio::process::Command) -> IsolationReport {
+        self.apply(command.as_std_mut())
+    }
+}
+
+/// Resolve a program the way `execvp` would, using the command's `PATH`.
+fn resolve_program(command: &Command) -> Option<PathBuf> {
+    let program = Path::new(command.get_program());
+    if program.components().count() > 1 {
+        return Some(
+            command
+                .get_current_dir()
+                .map_or_else(|| program.to_path_buf(), |dir| dir.join(program)),
+        );
+    }
+
+    let path = command
+        .get_envs()
+        .find(|(key, _)| *key == "PATH")
+        .map_or_else(
+            || std::env::var_os("PATH"),
+            |(_, value)| value.map(Into::into),
+        )?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::{IsolationFeature, IsolationReport, IsolationSupport, ProcessIsolation};
+    use landlock::{
+        ABI, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreated, RulesetCreatedAttr,
+        path_beneath_rules,
+    };
+    use std::ffi::{CString, OsStr};
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::FileExt;
+    use std::os::unix::process::CommandExt;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+    use std::sync::{Arc, Mutex};
+
+    /// Landlock ABI requested from the kernel (best effort).
+    const LANDLOCK_ABI: ABI = ABI::V3;
+
+    /// `LANDLOCK_CREATE_RULESET_VERSION`.
+    const LANDLOCK_CREATE_RULESET_VERSION: libc::c_uint = 1;
+
+    pub fn detect() -> IsolationSupport {
+        // SAFETY: querying the ABI version takes no pointers.
+        let abi = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<u8>(),
+                0usize,
+                LANDLOCK_CREATE_RULESET_VERSION,
+            )
+        };
+        IsolationSupport {
+            landlock_abi: i32::try_from(abi).ok().filter(|v| *v > 0),
+            network_namespace: probe_network_namespace(),
+            seccomp: AUDIT_ARCH.is_some() && probe_seccomp_notify(),
+        }
+    }
+
+    /// Check whether the kernel supports `SECCOMP_RET_USER_NOTIF`.
+    fn probe_seccomp_notify() -> bool {
+        let action = libc::SECCOMP_RET_USER_NOTIF;
+        // SAFETY: the action is read from a valid u32 on the stack.
+        unsafe {
+            libc::syscall(
+                libc::SYS_seccomp,
+                libc::SECCOMP_GET_ACTION_AVAIL,
+                0,
+                &raw const action,
+            ) == 0
+        }
+    }
+
+    /// Check whether a network namespace can be created, in a throwaway child.
+    fn probe_network_namespace() -> bool {
+        // SAFETY: the child only performs raw syscalls before `_exit`.
+        match unsafe { libc::fork() } {
+            -1 => false,
+            0 => {
+                let ok = enter_network_namespace().is_ok();
+                // SAFETY: terminating the forked child without running destructors.
+                unsafe { libc::_exit(i32::from(!ok)) }
+            }
+            pid => {
+                let mut status = 0;
+                // SAFETY: waiting for the child forked above.
+                let waited = unsafe { libc::waitpid(pid, &raw mut status, 0) };
+                waited == pid && libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0
+            }
+        }
+    }
+
+    /// Unshare the network namespace, using a user namespace when unprivileged.
+    ///
+    /// Returns `true` if a user namespace was created.
+    fn enter_network_namespace() -> io::Result<bool> {
+        // SAFETY: unshare only affects the calling (single-threaded child) process.
+        if unsafe { libc::unshare(libc::CLONE_NEWNET) } == 0 {
+            return Ok(false);
+        }
+        // SAFETY: as above.
+        if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) } == 0 {
+            return Ok(true);
+        }
+        Err(io::Error::last_os_error())
+    }
+
+    /// Write a prepared buffer to a file without allocating.
+    fn write_file(path: &CString, content: &[u8]) {
+        // SAFETY: both buffers outlive the calls; the fd is closed before returning.
+        unsafe {
+            let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+            if fd >= 0 {
+                let _ = libc::write(fd, content.as_ptr().cast(), content.len());
+                libc::close(fd);
+            }
+        }
+    }
+
+    /// Identity uid/gid maps for a user namespace, prepared before fork.
+    struct IdMaps {
+        setgroups: CString,
+        uid_map_path: CString,
+        gid_map_path: CString,
+        uid_map: Vec<u8>,
+        gid_map: Vec<u8>,
+    }
+
+    impl IdMaps {
+        fn new() -> Self {
+            // SAFETY: getuid/getgid cannot fail.
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            Self {
+                setgroups: c"/proc/self/setgroups".to_owned(),
+                uid_map_path: c"/proc/self/uid_map".to_owned(),
+                gid_map_path: c"/proc/self/gid_map".to_owned(),
+                uid_map: format!("{uid} {uid} 1\n").into_bytes(),
+                gid_map: format!("{gid} {gid} 1\n").into_bytes(),
+            }
+        }
+
+        fn write(&self) {
+            write_file(&self.setgroups, b"deny");
+            write_file(&self.uid_map_path, &self.uid_map);
+            write_file(&self.gid_map_path, &self.gid_map);
+        }
+    }
+
+    /// Audit architecture the seccomp filter accepts syscalls from.
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: Option<u32> = Some(0xC000_003E);
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: Option<u32> = Some(0xC000_00B7);
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    const AUDIT_ARCH: Option<u32> = None;
+
+    /// `SECCOMP_IOCTL_NOTIF_RECV`: `_IOWR('!', 0, struct seccomp_notif)`.
+    const SECCOMP_IOCTL_NOTIF_RECV: libc::c_ulong = 0xC050_2100;
+    /// `SECCOMP_IOCTL_NOTIF_SEND`: `_IOWR('!', 1, struct seccomp_notif_resp)`.
+    const SECCOMP_IOCTL_NOTIF_SEND: libc::c_ulong = 0xC018_2101;
+    /// `SECCOMP_IOCTL_NOTIF_ID_VALID`: `_IOW('!', 2, __u64)`.
+    const SECCOMP_IOCTL_NOTIF_ID_VALID: libc::c_ulong = 0x4008_2102;
+
+    /// Filter passing `execve` and `execveat` to the supervisor. Syscalls from
+    /// other ABIs (x32, 32-bit compat) fail with `EPERM`.
+    fn exec_filter(arch: u32) -> Vec<libc::sock_filter> {
+        const LD_W_ABS: u16 = (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16;
+        const JEQ_K: u16 = (libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16;
+        const JGE_K: u16 = (libc::BPF_JMP | libc::BPF_JGE | libc::BPF_K) as u16;
+        const RET_K: u16 = (libc::BPF_RET | libc::BPF_K) as u16;
+        // Offsets of `nr` and `arch` in `struct seccomp_data`
+        const NR: u32 = 0;
+        const ARCH: u32 = 4;
+        const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+        let op = |code, jt, jf, k| libc::sock_filter { code, jt, jf, k };
+        let syscall = |nr: libc::c_long| u32::try_from(nr).unwrap_or(u32::MAX);
+
+        vec![
+            op(LD_W_ABS, 0, 0, ARCH),
+            op(JEQ_K, 1, 0, arch),
+            op(
+                RET_K,
+                0,
+                0,
+                libc::SECCOMP_RET_ERRNO | libc::EPERM.cast_unsigned(),
+            ),
+            op(LD_W_ABS, 0, 0, NR),
+            op(JGE_K, 4, 0, X32_SYSCALL_BIT),
+            op(JEQ_K, 2, 0, syscall(libc::SYS_execve)),
+            op(JEQ_K, 1, 0, syscall(libc::SYS_execveat)),
+            op(RET_K, 0, 0, libc::SECCOMP_RET_ALLOW),
+            op(RET_K, 0, 0, libc::SECCOMP_RET_USER_NOTIF),
+            op(
+                RET_K,
+                0,
+                0,
+                libc::SECCOMP_RET_ERRNO | libc::EPERM.cast_unsigned(),
+            ),
+        ]
+    }
+
+    /// Child side of the exec filter: installs it and hands the listener to
+    /// the supervisor.
+    struct ExecGuard {
+        filter: Vec<libc::sock_filter>,
+        socket: OwnedFd,
+    }
+
+    // SAFETY: the filter is plain data, only read after fork.
+    unsafe impl Send for ExecGuard {}
+    // SAFETY: as above.
+    unsafe impl Sync for ExecGuard {}
+
+    impl ExecGuard {
+        /// Create the guard and start a supervisor that allows one `execve`
+        /// of `program` (canonical path) per spawned child.
+        fn new(arch: u32, program: PathBuf, cwd: PathBuf) -> io::Result<Self> {
+            let mut fds = [0; 2];
+            // SAFETY: fds is a valid array of two descriptors.
+            if unsafe {
+                libc::socketpair(
+                    libc::AF_UNIX,
+                    libc::SOCK_SEQPACKET | libc::SOCK_CLOEXEC,
+                    0,
+                    fds.as_mut_ptr(),
+                )
+            } != 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+            // SAFETY: socketpair returned two new descriptors we own.
+            let (supervisor, socket) =
+                unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+
+            std::thread::Builder::new()
+                .name("exec-supervisor".into())
+                .spawn(move || {
+                    // Ends once every copy of the child's end is closed
+                    while let Some(listener) = receive_fd(&supervisor) {
+                        let program = program.clone();
+                        let cwd = cwd.clone();
+                        let _ = std::thread::Builder::new()
+                            .name("exec-supervisor".into())
+                            .spawn(move || supervise(&listener, &program, &cwd));
+                    }
+                })?;
+
+            Ok(Self {
+                filter: exec_filter(arch),
+                socket,
+            })
+        }
+
+        /// Install the filter in the child and send its listener to the supervisor.
+        fn enter(&self) -> io::Result<()> {
+            let program = libc::sock_fprog {
+                len: u16::try_from(self.filter.len()).unwrap_or(u16::MAX),
+                filter: self.filter.as_ptr().cast_mut(),
+            };
+            // SAFETY: prctl and seccomp only read the filter, which outlives the calls.
+            let listener = unsafe {
+                if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                libc::syscall(
+                    libc::SYS_seccomp,
+                    libc::SECCOMP_SET_MODE_FILTER,
+                    libc::SECCOMP_FILTER_FLAG_NEW_LISTENER,
+                    &raw const program,
+                )
+            };
+            let listener = RawFd::try_from(listener)
+                .ok()
+                .filter(|fd| *fd >= 0)
+                .ok_or_else(io::Error::last_os_error)?;
+            let sent = send_fd(self.socket.as_raw_fd(), listener);
+            // SAFETY: the listener was created above and is not used afterwards.
+            unsafe { libc::close(listener) };
+            sent
+        }
+    }
+
+    /// Room for one `SCM_RIGHTS` control message carrying a descriptor.
+    type FdControl = [u64; 4];
+
+    /// Send a descriptor over a Unix socket without allocating.
+    fn send_fd(socket: RawFd, fd: RawFd) -> io::Result<()> {
+        let mut byte = [0u8; 1];
+        let mut iov = libc::iovec {
+            iov_base: byte.as_mut_ptr().cast(),
+            iov_len: byte.len(),
+        };
+        let mut control: FdControl = [0; 4];
+        // SAFETY: the message points at live stack buffers sized for one descriptor.
+        unsafe {
+            let mut message: libc::msghdr = std::mem::zeroed();
+            message.msg_iov = &raw mut iov;
+            message.msg_iovlen = 1;
+            message.msg_control = control.as_mut_ptr().cast();
+            message.msg_controllen = libc::CMSG_SPACE(size_of::<RawFd>() as u32) as _;
+            let header = libc::CMSG_FIRSTHDR(&raw const message);
+            (*header).cmsg_level = libc::SOL_SOCKET;
+            (*header).cmsg_type = libc::SCM_RIGHTS;
+            (*header).cmsg_len = libc::CMSG_LEN(size_of::<RawFd>() as u32) as _;
+            libc::CMSG_DATA(header).cast::<RawFd>().write_unaligned(fd);
+            if libc::sendmsg(socket, &raw const message, 0) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    /// Receive a descriptor sent with [`send_fd`]; `None` once the peer is gone.
+    fn receive_fd(socket: &OwnedFd) -> Option<OwnedFd> {
+        let mut byte = [0u8; 1];
+        let mut iov = libc::iovec {
+            iov_base: byte.as_mut_ptr().cast(),
+            iov_len: byte.len(),
+        };
+        let mut control: FdControl = [0; 4];
+        // SAFETY: the message points at live stack buffers; a received
+        // descriptor is owned by the caller.
+        unsafe {
+            let mut message: libc::msghdr = std::mem::zeroed();
+            message.msg_iov = &raw mut iov;
+            message.msg_iovlen = 1;
+            message.msg_control = control.as_mut_ptr().cast();
+            message.msg_controllen = size_of::<FdControl>() as _;
+            loop {
+                let received =
+                    libc::recvmsg(socket.as_raw_fd(), &raw mut message, libc::MSG_CMSG_CLOEXEC);
+                if received > 0 {
+                    break;
+                }
+                if received == 0 || io::Error::last_os_error().kind() != io::ErrorKind::Interrupted
+                {
+                    return None;
+                }
+            }
+            let header = libc::CMSG_FIRSTHDR(&raw const message);
+            if header.is_null() || (*header).cmsg_type != libc::SCM_RIGHTS {
+                return None;
+            }
+            let fd = libc::CMSG_DATA(header).cast::<RawFd>().read_unaligned();
+            Some(OwnedFd::from_raw_fd(fd))
+        }
+    }
+
+    /// Answer the `execve` calls of a child until it and its descendants exit.
+    ///
+    /// The first call for `program` is the child starting it and proceeds;
+    /// calls before it (other `PATH` entries) fail with `ENOENT`, every call
+    /// after it with `EPERM`.
+    fn supervise(listener: &OwnedFd, program: &Path, cwd: &Path) {
+        let fd = listener.as_raw_fd();
+        let mut started = false;
+        loop {
+            let mut poll = libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: polling one valid descriptor.
+            if unsafe { libc::poll(&raw mut poll, 1, -1) } < 0 {
+                if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return;
+            }
+            // POLLHUP: no process uses the filter anymore
+            if poll.revents & libc::POLLIN == 0 {
+                return;
+            }
+
+            // SAFETY: the kernel requires a zeroed request buffer.
+            let mut request: libc::seccomp_notif = unsafe { std::mem::zeroed() };
+            // SAFETY: the request buffer matches the ioctl's struct.
+            if unsafe { libc::ioctl(fd, SECCOMP_IOCTL_NOTIF_RECV, &raw mut request) } != 0 {
+                // The calling process died before the request was read
+                continue;
+            }
+
+            let allow = !started
+                && exec_target(&request, cwd).is_some_and(|target| target == program)
+                // The path was read from a process that still made this call
+                // SAFETY: the id is read from a valid u64.
+                && unsafe {
+                    libc::ioctl(fd, SECCOMP_IOCTL_NOTIF_ID_VALID, &raw const request.id)
+                } == 0;
+            let error = if allow {
+                0
+            } else if started {
+                -libc::EPERM
+            } else {
+                -libc::ENOENT
+            };
+            started |= allow;
+            let response = libc::seccomp_notif_resp {
+                id: request.id,
+                val: 0,
+                error,
+                flags: if allow {
+                    libc::SECCOMP_USER_NOTIF_FLAG_CONTINUE as u32
+                } else {
+                    0
+                },
+            };
+            // SAFETY: the response matches the ioctl's struct.
+            unsafe { libc::ioctl(fd, SECCOMP_IOCTL_NOTIF_SEND, &raw const response) };
+        }
+    }
+
+    /// Canonical path of the file an `execve`/`execveat` call would run.
+    fn exec_target(request: &libc::seccomp_notif, cwd: &Path) -> Option<PathBuf> {
+        const PAGE: u64 = 4096;
+        let address = match i64::from(request.data.nr) {
+            libc::SYS_execve => request.data.args[0],
+            libc::SYS_execveat => request.data.args[1],
+            _ => return None,
+        };
+        let memory = std::fs::File::open(format!("/proc/{}/mem", request.pid)).ok()?;
+
+        let mut path = Vec::new();
+        let mut offset = address;
+        while path.len() < libc::PATH_MAX as usize {
+            // Read up to the end of the page, the next one may not be mapped
+            let mut chunk = vec![0; usize::try_from(PAGE - offset % PAGE).ok()?];
+            let read = memory.read_at(&mut chunk, offset).ok()?;
+            if read == 0 {
+                return None;
+            }
+            if let Some(end) = chunk[..read].iter().position(|b| *b == 0) {
+                path.extend_from_slice(&chunk[..end]);
+                let path = cwd.join(OsStr::from_bytes(&path));
+                return path.canonicalize().ok();
+            }
+            path.extend_from_slice(&chunk[..read]);
+            offset += read as u64;
+        }
+        None
+    }
+
+    /// Everything the child applies between fork and exec.
+    #[derive(Default)]
+    struct ChildPlan {
+        memory_limit: Option<u64>,
+        network: Option<IdMaps>,
+        ruleset: Mutex<Option<RulesetCreated>>,
+        exec: Option<ExecGuard>,
+    }
+
+    impl ChildPlan {
+        fn enter(&self) -> io::Result<()> {
+            if let Some(limit) = self.memory_limit {
+                let rlimit = libc::rlimit {
+                    rlim_cur: limit,
+                    rlim_max: limit,
+                };
+                // SAFETY: rlimit is a valid struct on the stack.
+                if unsafe { libc::setrlimit(libc::RLIMIT_AS, &raw const rlimit) } != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+
+            if let Some(maps) = &self.network
+                && enter_network_namespace()?
+            {
+                maps.write();
+            }
+
+            let ruleset = self
+                .ruleset
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .take();
+            if let Some(ruleset) = ruleset {
+                ruleset
+                    .restrict_self()
+                    .map_err(|_| io::Error::from_raw_os_error(libc::EPERM))?;
+            }
+
+            if let Some(exec) = &self.exec {
+                exec.enter()?;
+            }
+            Ok(())
+        }
+
+        fn is_empty(&self) -> bool {
+            self.memory_limit.is_none()
+                && self.network.is_none()
+                && self.exec.is_none()
+                && self
+                    .ruleset
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .is_none()
+        }
+    }
+
+    fn landlock_ruleset(
+        isolation: &ProcessIsolation,
+        program_dir: Option<PathBuf>,
+    ) -> Result<RulesetCreated, landlock::RulesetError> {
+        let existing = |paths: Vec<PathBuf>| {
+            paths
+                .into_iter()
+                .filter(|path| path.exists())
+                .collect::<Vec<_>>()
+        };
+        let read_paths = existing(
+            isolation
+                .read_paths()
+                .into_iter()
+                .chain(program_dir)
+                .collect(),
+        );
+        let write_paths = existing(isolation.write_paths());
+
+        Ruleset::default()
+            .handle_access(AccessFs::from_all(LANDLOCK_ABI))?
+            .create()?
+            .add_rules(path_beneath_rules(
+                read_paths,
+                AccessFs::from_read(LANDLOCK_ABI),
+            ))?
+            .add_rules(path_beneath_rules(
+                write_paths,
+                AccessFs::from_all(LANDLOCK_ABI),
+            ))
+    }
+
+    pub fn apply(isolation: &ProcessIsolation, command: &mut Command) -> IsolationReport {
+        let support = IsolationSupport::detect();
+        let mut report = IsolationReport::default();
+        let mut plan = ChildPlan::default();
+        let program = super::resolve_program(command).map(|p| p.canonicalize().unwrap_or(p));
+
+        if let Some(limit) = isolation.memory_limit {
+            plan.memory_limit = Some(limit);
+            report.enforced.push(IsolationFeature::Memory);
+        }
+
+        if isolation.block_network {
+            if support.network_namespace {
+                plan.network = Some(IdMaps::new());
+                report.enforced.push(IsolationFeature::Network);
+            } else {
+                report.missing(
+                    IsolationFeature::Network,
+                    "cannot create a network namespace (user namespaces disabled?)",
+                );
+            }
+        }
+
+        if support.landlock_abi.is_some() {
+            let program_dir = program.as_ref().and_then(|p| p.parent()).map(PathBuf::from);
+            match landlock_ruleset(isolation, program_dir) {
+                Ok(ruleset) => {
+                    plan.ruleset = Mutex::new(Some(ruleset));
+                    report.enforced.push(IsolationFeature::Filesystem);
+                }
+                Err(e) => report.missing(IsolationFeature::Filesystem, e.to_string()),
+            }
+        } else {
+            report.missing(
+                IsolationFeature::Filesystem,
+                "Landlock is not supported or not enabled by the kernel",
+            );
+        }
+
+        if isolation.deny_exec {
+            match (AUDIT_ARCH.filter(|_| support.seccomp), &program) {
+                (None, _) => report.missing(
+                    IsolationFeature::Exec,
+                    "seccomp user notification is not supported on this system",
+                ),
+                (Some(_), None) => report.missing(
+                    IsolationFeature::Exec,
+                    format!(
+                        "cannot resolve `{}` in PATH",
+                        command.get_program().display()
+                    ),
+                ),
+                (Some(arch), Some(program)) => {
+                    let cwd = command.get_current_dir().map_or_else(
+                        || std::env::current_dir().unwrap_or_default(),
+                        Path::to_path_buf,
+                    );
+                    match ExecGuard::new(arch, program.clone(), cwd) {
+                        Ok(guard) => {
+                            plan.exec = Some(guard);
+                            report.enforced.push(IsolationFeature::Exec);
+                        }
+                        Err(e) => report.missing(IsolationFeature::Exec, e.to_string()),
+                    }
+                }
+            }
+        }
+
+        if !plan.is_empty() {
+            let plan = Arc::new(plan);
+            // SAFETY: the hook only performs syscalls on data prepared before fork.
+            unsafe {
+                command.pre_exec(move || plan.enter());
+            }
+        }
+        report
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use super::{IsolationFeature, IsolationReport, IsolationSupport, ProcessIsolation};
+    use std::process::Command;
+
+    pub const fn detect() -> IsolationSupport {
+        IsolationSupport {
+            landlock_abi: None,
+            network_namespace: false,
+            seccomp: false,
+        }
+    }
+
+    pub fn apply(isolation: &ProcessIsolation, _command: &mut Command) -> IsolationReport {
+        let mut report = IsolationReport::default();
+        let requested = [
+            (IsolationFeature::Filesystem, true),
+            (IsolationFeature::Network, isolation.block_network),
+            (IsolationFeature::Memory, isolation.memory_limit.is_some()),
+            (IsolationFeature::Exec, isolation.deny_exec),
+        ];
+        for (feature, wanted) in requested {
+            if wanted {
+                report.missing(feature, "process isolation is only supported on Linux");
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::SandboxOverride;
+
+    #[test]
+    fn from_config_merges_overrides() {
+        let mut config = SandboxConfig {
+            allowed_read_paths: vec![PathBuf::from("/project")],
+            block_network: true,
+            ..SandboxConfig::default()
+        };
+        config.plugin_overrides.insert(
+            "acme/plugin".into(),
+            SandboxOverride {
+                memory_limit: Some(1024),
+                additional_write_paths: vec![PathBuf::from("/project/var")],
+                ..SandboxOverride::default()
+            },
+        );
+
+        let isolation = ProcessIsolation::from_config(&config, "acme/plugin").unwrap();
+        assert!(isolation.read_paths().contains(&PathBuf::from("/project")));
+        assert!(
+            isolation
+                .write_paths()
+                .contains(&PathBuf::from("/project/var"))
+        );
+        assert_eq!(isolation.memory_limit, Some(1024));
+        assert!(isolation.block_network);
+        assert!(isolation.deny_exec);
+    }
+
+    #[test]
+    fn from_config_disabled() {
+        let config = SandboxConfig {
+            enabled: false,
+            ..SandboxConfig::default()
+        };
+        assert!(ProcessIsolation::from_config(&config, "acme/plugin").is_none());
+    }
+
+    #[test]
+    fn resolve_program_uses_command_path() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut command = Command::new("definitely-not-a-real-program");
+        command.env("PATH", temp.path());
+        assert!(resolve_program(&command).is_none());
+
+        let command = Command::new("/bin/sh");
+        assert_eq!(resolve_program(&command), Some(PathBuf::from("/bin/sh")));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn apply_reports_every_requested_feature() {
+        let isolation = ProcessIsolation::new()
+            .with_block_network(true)
+            .with_memory_limit(Some(1 << 30))
+            .with_deny_exec(true);
+        let mut command = Command::new("/bin/sh");
+        let report = isolation.apply(&mut command);
+
+        for feature in [
+            IsolationFeature::Filesystem,
+            IsolationFeature::Network,
+            IsolationFeature::Memory,
+            IsolationFeature::Exec,
+        ] {
+            let unavailable = report.unavailable.iter().any(|(f, _)| *f == feature);
+            assert!(report.is_enforced(feature) != unavailable, "{feature}");
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn memory_limit_is_applied_to_child() {
+        let isolation = ProcessIsolation::new().with_memory_limit(Some(1 << 30));
+        let mut command = Command::new("/bin/sh");
+        command.args(["-c", "ulimit -v"]);
+        let report = isolation.apply(&mut command);
+        if !report.is_enforced(IsolationFeature::Filesystem) {
+            // The shell must still start without Landlock
+            assert!(report.is_enforced(IsolationFeature::Memory));
+        }
+
+        let output = command.output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "1048576");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn exec_is_denied_inside_child() {
+        let isolation = ProcessIsolation::new().with_deny_exec(true);
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo started; /bin/true && echo ran"]);
+        let report = isolation.apply(&mut command);
+        if !report.is_enforced(IsolationFeature::Exec) {
+            return;
+        }
+
+        // The program itself still runs (found through PATH); anything it executes does not
+        let output = command.output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("started"));
+        assert!(!stdout.contains("ran"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn dynamic_loader_cannot_start_other_binaries() {
+        let Some(loader) = [
+            "/lib64/ld-linux-x86-64.so.2",
+            "/lib/ld-linux-aarch64.so.1",
+            "/lib/x86_64-linux-gnu/ld-linux-x86-64.so.2",
+        ]
+        .into_iter()
+        .find(|path| Path::new(path).exists()) else {
+            return;
+        };
+        let isolation = ProcessIsolation::new().with_deny_exec(true);
+        let mut command = Command::new("/bin/sh");
+        command.args([
+            "-c",
+            &format!("echo started; {loader} /bin/true && echo loaded"),
+        ]);
+        let report = isolation.apply(&mut command);
+        if !report.is_enforced(IsolationFeature::Exec) {
+            return;
+        }
+
+        let output = command.output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("started"));
+        assert!(!stdout.contains("loaded"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn filesystem_outside_allowed_paths_is_denied() {
+        let allowed = tempfile::TempDir::new_in(".").unwrap();
+        let denied = tempfile::TempDir::new_in(".").unwrap();
+        std::fs::write(denied.path().join("secret"), "token").unwrap();
+
+        let isolation = ProcessIsolation::new().with_write_path(allowed.path());
+        let mut command = Command::new("/bin/cat");
+        command.arg(denied.path().join("secret"));
+        let report = isolation.apply(&mut command);
+        if !report.is_enforced(IsolationFeature::Filesystem) {
+            return;
+        }
+
+        let output = command.output().unwrap();
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+    }
+}
@@ -5,11 +5,13 @@
 //!
 //! - **Native Rust Plugins**: Dynamic library loading via `libloading` with stable C ABI
 //! - **PHP Plugin Support**: IPC via Unix sockets (Unix) or named pipes (Windows)
+//! - **WebAssembly Plugins**: WASI preview 2 components with fuel and epoch limits
 //! - **Plugin Discovery**: Automatic scanning of vendor directory for composer plugins
 //! - **Composer-Compatible Hooks**: Full support for all Composer plugin events
 //! - **Plugin Sandboxing**: File system restrictions, network monitoring, timeouts
 //! - **Event Bus**: Plugin-to-plugin communication via crossbeam channels
 //! - **Hot Reloading**: Development mode support for native plugins
+//! - **Built-in Plugins**: Native replacements for popular installer plugins
 //!
 //! # Performance Targets
 //!
@@ -40,29 +42,38 @@
 #![allow(clippy::module_name_repetitions)]
 
 mod api;
+pub mod builtin;
 mod config;
 mod discovery;
 mod error;
 mod event_bus;
 mod hooks;
+mod isolation;
 mod lifecycle;
 mod native;
 mod php;
 mod sandbox;
+#[cfg(feature = "wasm")]
+mod wasm;
 
 pub use api::{
     EventContext, EventResult, Plugin, PluginApi, PluginCapability, PluginInfo, PluginMetadata,
-    PluginType,
+    PluginType, WasmManifest,
 };
-pub use config::{PluginConfig, PluginManagerConfig};
+pub use config::{ComposerPluginConfig, PluginConfig, PluginManagerConfig};
 pub use discovery::PluginDiscovery;
 pub use error::{PluginError, Result};
 pub use event_bus::{EventBus, EventMessage, EventSubscription, MessagePayload};
 pub use hooks::{Hook, HookPriority, HookRegistry};
+pub use isolation::{
+    IsolationFeature, IsolationReport, IsolationSupport, ProcessIsolation, SYSTEM_READ_PATHS,
+};
 pub use lifecycle::{PluginLifecycle, PluginState};
 pub use native::{NativePlugin, NativePluginLoader};
 pub use php::{PhpPlugin, PhpPluginBridge};
-pub use sandbox::{Sandbox, SandboxConfig, SandboxViolation};
+pub use sandbox::{DEFAULT_MEMORY_LIMIT, Sandbox, SandboxConfig, SandboxViolation};
+#[cfg(feature = "wasm")]
+pub use wasm::{DEFAULT_WASM_FUEL, WasmCapabilities, WasmPlugin, WasmPluginLoader};
 
 use dashmap::DashMap;
 use moka::sync::Cache;
@@ -99,7 +110,7 @@ pub struct PluginHandle {
     pub metadata: Arc<PluginMetadata>,
     /// Current state.
     pub state: Arc<RwLock<PluginState>>,
-    /// Plugin type (native or PHP).
+    /// Plugin type (native, PHP or WebAssembly).
     pub plugin_type: PluginType,
     /// Priority for hook ordering.
     pub priority: i32,
@@ -156,6 +167,9 @@ pub struct PluginManager {
     native_loader: NativePluginLoader,
     /// PHP plugin bridge.
     php_bridge: PhpPluginBridge,
+    /// WebAssembly plugin loader.
+    #[cfg(feature = "wasm")]
+    wasm_loader: WasmPluginLoader,
     /// Plugin discovery.
     discovery: PluginDiscovery,
     /// Hook registry.
@@ -172,6 +186,9 @@ pub struct PluginManager {
     native_instances: DashMap<String, Arc<dyn Plugin>>,
     /// PHP plugin instances.
     php_instances: DashMap<String, Arc<PhpPlugin>>,
+    /// WebAssembly plugin instances.
+    #[cfg(feature = "wasm")]
+    wasm_instances: DashMap<String, Arc<WasmPlugin>>,
 }
 
 impl std::fmt::Debug for PluginManager {
@@ -202,6 +219,8 @@ impl PluginManager {
             plugins: DashMap::new(),
             native_loader: NativePluginLoader::new(config.hot_reload),
             php_bridge: PhpPluginBridge::new(config.php_timeout),
+            #[cfg(feature = "wasm")]
+            wasm_loader: WasmPluginLoader::new(),
             discovery: PluginDiscovery::new(),
             hook_registry,
             event_bus,
@@ -210,11 +229,16 @@ impl PluginManager {
             plugin_configs: DashMap::new(),
             native_instances: DashMap::new(),
             php_instances: DashMap::new(),
+            #[cfg(feature = "wasm")]
+            wasm_instances: DashMap::new(),
         }
     }
 
     /// Discover plugins in the vendor directory.
     ///
+    /// Uses `vendor/composer/installed.json` when present and falls back to
+    /// scanning the vendor directory.
+    ///
     /// # Errors
     /// Returns error if discovery fails.
     #[instrument(skip(self), fields(vendor_path = %vendor_path.as_ref().display()))]
@@ -222,7 +246,7 @@ impl PluginManager {
         let vendor_path = vendor_path.as_ref();
         info!("discovering plugins in vendor directory");
 
-        let discovered = self.discovery.scan_vendor(vendor_path).await?;
+        let discovered = self.discovery.scan_installed_json(vendor_path).await?;
 
         let mut handles = Vec::with_capacity(discovered.len());
 
@@ -237,7 +261,9 @@ impl PluginManager {
                 continue;
             }
 
-            let plugin_type = if metadata.class.is_some() {
+            let plugin_type = if metadata.wasm.is_some() {
+                PluginType::Wasm
+            } else if metadata.class.is_some() {
                 PluginType::Php
             } else {
                 PluginType::Native
@@ -285,6 +311,7 @@ impl PluginManager {
         let result = match handle.plugin_type {
             PluginType::Native => self.load_native_plugin(&handle).await,
             PluginType::Php => self.load_php_plugin(&handle).await,
+            PluginType::Wasm => self.load_wasm_plugin(&handle).await,
         };
 
         match result {
@@ -343,6 +370,10 @@ impl PluginManager {
                     plugin.stop().await?;
                 }
             }
+            PluginType::Wasm => {
+                #[cfg(feature = "wasm")]
+                self.wasm_instances.remove(plugin_id);
+            }
         }
 
         // Unregister hooks
@@ -415,6 +446,59 @@ impl PluginManager {
         Ok(results)
     }
 
+    /// Dispatch [`Hook::PreFileDownload`] and return the URL to download.
+    ///
+    /// Handlers see the current URL as the only entry of
+    /// [`EventContext::urls`] and may replace it by returning a `url` entry in
+    /// [`EventResult::modified_data`]. Rewrites are chained in priority order.
+    ///
+    /// # Errors
+    /// Returns error if event processing fails.
+    #[instrument(skip(self, context))]
+    pub async fn rewrite_download_url(&self, url: &str, context: &EventContext) -> Result<String> {
+        let mut url = url.to_string();
+        let sandbox = Sandbox::new(self.sandbox_config.clone());
+
+        for handler in self.hook_registry.get_handlers(&Hook::PreFileDownload) {
+            if let Some(handle) = self.plugins.get(&handler.plugin_id)
+                && (!handle.is_enabled() || !handle.is_loaded())
+            {
+                continue;
+            }
+
+            let mut handler_context = context.clone();
+            handler_context.urls = vec![url.clone()];
+
+            let result = sandbox
+                .execute_with_timeout(
+                    self.config.default_timeout,
+                    self.invoke_handler(&handler, Hook::PreFileDownload, &handler_context),
+                )
+                .await;
+
+            match result {
+                Ok(event_result) => {
+                    if let Some(rewritten) = event_result.modified_data.get("url") {
+                        debug!(plugin = %handler.plugin_id, from = %url, to = %rewritten, "download URL rewritten");
+                        url.clone_from(rewritten);
+                    }
+                    if !event_result.continue_processing {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        plugin = %handler.plugin_id,
+                        error = %e,
+                        "pre-file-download handler failed"
+                    );
+                }
+            }
+        }
+
+        Ok(url)
+    }
+
     /// Register a native plugin manually.
     pub fn register_native(&self, plugin: Arc<dyn Plugin>) {
         let info = plugin.info();
@@ -434,10 +518,56 @@ impl PluginManager {
             }
         }
 
+        // The instance already exists, so the plugin is ready to receive events
+        *handle.state.write() = PluginState::Loaded;
+
         self.native_instances.insert(handle.id.clone(), plugin);
         self.plugins.insert(handle.id.clone(), handle);
     }
 
+    /// Register the built-in native replacements for installed Composer plugins.
+    ///
+    /// A built-in plugin is registered only when the package it replaces is
+    /// listed in `vendor/composer/installed.json` and `allowed` accepts its
+    /// name. Callers pass the same `allow-plugins` check they apply to
+    /// discovered plugins, so a built-in is gated exactly like the original.
+    ///
+    /// Returns the names of the registered plugins.
+    ///
+    /// # Errors
+    /// Returns error if `installed.json` cannot be read.
+    pub fn register_builtins(
+        &self,
+        vendor_dir: &Path,
+        mut allowed: impl FnMut(&str) -> bool,
+    ) -> Result<Vec<String>> {
+        if !self.config.plugins_enabled {
+            return Ok(Vec::new());
+        }
+
+        let installed = builtin::read_installed_packages(vendor_dir)?;
+        let mut registered = Vec::new();
+
+        for plugin in builtin::builtin_plugins() {
+            let name = plugin.info().name;
+            if !installed.iter().any(|p| p.name == name) {
+                continue;
+            }
+            if !allowed(&name) {
+                debug!(plugin = %name, "built-in plugin blocked by allow-plugins");
+                continue;
+            }
+            if self.config.is_plugin_disabled(&name) {
+                continue;
+            }
+
+            self.register_native(plugin);
+            registered.push(name);
+        }
+
+        Ok(registered)
+    }
+
     /// Get all loaded plugins.
     #[must_use]
     pub fn plugins(&self) -> Vec<PluginHandle> {
@@ -555,7 +685,10 @@ impl PluginManager {
             .as_ref()
             .ok_or_else(|| PluginError::InvalidMetadata("missing plugin path".into()))?;
 
-        let php_plugin = self.php_bridge.create_plugin(class, path).await?;
+        let mut php_plugin = self.php_bridge.create_plugin(class, path).await?;
+        if let Some(isolation) = ProcessIsolation::from_config(&self.sandbox_config, &handle.id) {
+            php_plugin.set_isolation(isolation.with_read_path(path));
+        }
 
         // Start the PHP process
         php_plugin.start().await?;
@@ -576,6 +709,47 @@ impl PluginManager {
         Ok(())
     }
 
+    #[cfg(feature = "wasm")]
+    async fn load_wasm_plugin(&self, handle: &PluginHandle) -> Result<()> {
+        let manifest = handle
+            .metadata
+            .wasm
+            .as_ref()
+            .ok_or_else(|| PluginError::InvalidMetadata("missing wasm component".into()))?;
+
+        let mut capabilities = WasmCapabilities::from_config(&self.sandbox_config, &handle.id);
+        if let Some(dir) = &handle.metadata.path {
+            capabilities = capabilities.with_read_path(dir);
+        }
+
+        let plugin = self
+            .wasm_loader
+            .load(&handle.id, manifest, capabilities)
+            .await?;
+
+        for capability in plugin.capabilities() {
+            if let Some(hooks) = capability.supported_hooks() {
+                for hook in hooks {
+                    self.hook_registry
+                        .register(hook, handle.id.clone(), handle.priority);
+                }
+            }
+        }
+
+        self.wasm_instances
+            .insert(handle.id.clone(), Arc::new(plugin));
+        Ok(())
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[allow(clippy::unused_async)]
+    async fn load_wasm_plugin(&self, handle: &PluginHandle) -> Result<()> {
+        Err(PluginError::InvalidOperation(format!(
+            "{} is a WebAssembly plugin, but libretto was built without the `wasm` feature",
+            handle.id
+        )))
+    }
+
     async fn invoke_handler(
         &self,
         handler: &hooks::HookHandler,
@@ -592,6 +766,12 @@ impl PluginManager {
             return plugin.invoke(event, context).await;
         }
 
+        // Try WebAssembly
+        #[cfg(feature = "wasm")]
+        if let Some(plugin) = self.wasm_instances.get(&handler.plugin_id) {
+            return plugin.invoke(event, context).await;
+        }
+
         Err(PluginError::NotLoaded(handler.plugin_id.clone()))
     }
 
@@ -674,6 +854,94 @@ mod tests {
         assert!(!manager.is_api_compatible(&incompatible));
     }
 
+    #[tokio::test]
+    async fn builtins_respect_allow_plugins() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let vendor = temp.path().join("vendor");
+        std::fs::create_dir_all(vendor.join("composer")).unwrap();
+        std::fs::write(
+            vendor.join("composer/installed.json"),
+            r#"{"packages": [
+                {"name": "phpstan/extension-installer", "version": "1.4.3", "type": "composer-plugin"},
+                {"name": "dealerdirect/phpcodesniffer-composer-installer", "version": "1.0.0", "type": "composer-plugin"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let json = serde_json::json!({"config": {"allow-plugins": {
+            "phpstan/extension-installer": true,
+            "dealerdirect/phpcodesniffer-composer-installer": false
+        }}});
+        let composer_config = ComposerPluginConfig::from_json(&json).unwrap();
+
+        let manager = PluginManager::new();
+        let registered = manager
+            .register_builtins(&vendor, |name| {
+                composer_config.plugin_allowance(name).unwrap_or(false)
+            })
+            .unwrap();
+        assert_eq!(registered, vec!["phpstan/extension-installer".to_string()]);
+
+        // Without an allow-plugins entry nothing runs unless the caller says so
+        let unset = ComposerPluginConfig::default();
+        let registered = PluginManager::new()
+            .register_builtins(&vendor, |name| {
+                unset.plugin_allowance(name).unwrap_or(false)
+            })
+            .unwrap();
+        assert!(registered.is_empty());
+
+        let context = EventContext::new()
+            .with_project_root(temp.path())
+            .with_vendor_dir(&vendor);
+        let results = manager.emit(Hook::PostInstallCmd, &context).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(
+            vendor
+                .join("phpstan/extension-installer/src/GeneratedConfig.php")
+                .exists()
+        );
+    }
+
+    struct MirrorPlugin;
+
+    #[async_trait::async_trait]
+    impl Plugin for MirrorPlugin {
+        fn info(&self) -> PluginInfo {
+            PluginInfo::builder("acme/mirror", "1.0.0").build()
+        }
+
+        fn capabilities(&self) -> Vec<PluginCapability> {
+            vec![PluginCapability::Download]
+        }
+
+        async fn on_event(&self, _event: Hook, context: &EventContext) -> Result<EventResult> {
+            let url = context.urls.first().cloned().unwrap_or_default();
+            Ok(EventResult::ok().data(
+                "url",
+                url.replace("https://codeload.github.com", "https://mirror.example"),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn rewrite_download_url() {
+        let manager = PluginManager::new();
+        let context = EventContext::new().with_operation("install");
+
+        let url = "https://codeload.github.com/acme/lib/legacy.zip/abc";
+        assert_eq!(
+            manager.rewrite_download_url(url, &context).await.unwrap(),
+            url
+        );
+
+        manager.register_native(Arc::new(MirrorPlugin));
+        assert_eq!(
+            manager.rewrite_download_url(url, &context).await.unwrap(),
+            "https://mirror.example/acme/lib/legacy.zip/abc"
+        );
+    }
+
     #[tokio::test]
     async fn event_emission() {
         let manager = PluginManager::new();
@@ -6,6 +6,7 @@
 use crate::api::{EventContext, EventResult, PluginCapability};
 use crate::error::{PluginError, Result};
 use crate::hooks::Hook;
+use crate::isolation::ProcessIsolation;
 use bytes::{BufMut, BytesMut};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
@@ -165,6 +166,7 @@ impl PhpPluginBridge {
             message_id: std::sync::atomic::AtomicU64::new(1),
             sender: RwLock::new(None),
             receiver: RwLock::new(None),
+            isolation: None,
         })
     }
 
@@ -395,9 +397,16 @@ pub struct PhpPlugin {
     sender: RwLock<Option<mpsc::Sender<IpcMessage>>>,
     /// Message receiver channel.
     receiver: RwLock<Option<mpsc::Receiver<IpcMessage>>>,
+    /// OS-level isolation applied to the PHP process.
+    isolation: Option<ProcessIsolation>,
 }
 
 impl PhpPlugin {
+    /// Run the PHP process with OS-level isolation.
+    pub fn set_isolation(&mut self, isolation: ProcessIsolation) {
+        self.isolation = Some(isolation);
+    }
+
     /// Start the PHP plugin process.
     ///
     /// # Errors
@@ -416,6 +425,31 @@ impl PhpPlugin {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
+        if let Some(isolation) = &self.isolation {
+            let mut isolation = isolation.clone().with_read_path(&self.path);
+            if let Some(bootstrap_dir) = self.bootstrap_script.parent() {
+                isolation = isolation.with_read_path(bootstrap_dir);
+            }
+            let report = isolation.apply_tokio(&mut cmd);
+            debug!(
+                class = %self.class,
+                enforced = ?report.enforced,
+                unavailable = ?report.unavailable,
+                "PHP plugin process isolation"
+            );
+            if isolation.is_required()
+                && let Some((feature, reason)) = report.unavailable.first()
+            {
+                return Err(PluginError::PhpRuntime {
+                    plugin: self.class.clone(),
+                    message: format!("required sandbox feature {feature} is unavailable: {reason}"),
+                });
+            }
+            for (feature, reason) in &report.unavailable {
+                warn!(class = %self.class, "sandbox feature {feature} is unavailable: {reason}");
+            }
+        }
+
         let mut child = cmd.spawn().map_err(|e| PluginError::PhpRuntime {
             plugin: self.class.clone(),
             message: format!("failed to spawn PHP process: {e}"),
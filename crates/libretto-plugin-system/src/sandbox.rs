@@ -5,8 +5,12 @@
 //! - Network call monitoring
 //! - Timeout enforcement
 //! - Memory limits
+//!
+//! The checks here are advisory; [`ProcessIsolation`] enforces them for
+//! child processes on Linux.
 
 use crate::error::{PluginError, Result};
+use crate::isolation::ProcessIsolation;
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use std::future::Future;
@@ -18,8 +22,11 @@ use tracing::warn;
 /// Default timeout for plugin operations.
 pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
 
-/// Default memory limit (256 MB).
-pub const DEFAULT_MEMORY_LIMIT: usize = 256 * 1024 * 1024;
+/// Default memory limit (2 GiB).
+///
+/// Enforced as an address space limit on PHP processes, which reserve far more
+/// than they use for opcache, the JIT buffer and malloc arenas.
+pub const DEFAULT_MEMORY_LIMIT: usize = 2 * 1024 * 1024 * 1024;
 
 /// Sandbox configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +67,11 @@ pub struct SandboxConfig {
     #[serde(default)]
     pub allowed_commands: Vec<String>,
 
+    /// Refuse to start a process when an isolation feature is unavailable,
+    /// instead of warning.
+    #[serde(default)]
+    pub require_isolation: bool,
+
     /// Plugin-specific overrides.
     #[serde(default)]
     pub plugin_overrides: std::collections::HashMap<String, SandboxOverride>,
@@ -89,6 +101,7 @@ impl Default for SandboxConfig {
             block_network: false,
             allow_exec: false,
             allowed_commands: Vec::new(),
+            require_isolation: false,
             plugin_overrides: std::collections::HashMap::new(),
         }
     }
@@ -503,6 +516,14 @@ impl Sandbox {
         self.config.timeout
     }
 
+    /// Get the OS-level isolation for a plugin's child processes.
+    ///
+    /// Returns `None` if the sandbox is disabled for the plugin.
+    #[must_use]
+    pub fn process_isolation(&self, plugin_id: &str) -> Option<ProcessIsolation> {
+        ProcessIsolation::from_config(&self.config, plugin_id)
+    }
+
     /// Get memory limit for a plugin.
     #[must_use]
     pub fn get_memory_limit(&self, plugin_id: &str) -> usize {
@@ -0,0 +1,830 @@
+//! WebAssembly component plugins.
+//!
+//! Plugins compiled for WASI preview 2 implement the `libretto:plugin` world
+//! defined in `wit/plugin.wit`, which mirrors [`Plugin::on_event`] and
+//! [`EventContext`]. Components run inside wasmtime with only the
+//! capabilities granted by [`SandboxConfig`]:
+//!
+//! - Allowed read paths are preopened read-only and write paths read-write,
+//!   under their host path. Nothing else on the filesystem is reachable.
+//! - Sockets are unavailable when the network is blocked, limited to the
+//!   addresses of `allowed_hosts` when hosts are listed, and unrestricted
+//!   otherwise.
+//! - Linear memory is capped at the sandbox memory limit.
+//! - Each event runs with a fuel budget and an epoch deadline derived from the
+//!   sandbox timeout, so runaway plugins trap instead of hanging the install.
+//! - WASI has no process API, so plugins cannot execute commands.
+
+use crate::api::{EventContext, EventResult, Plugin, PluginCapability, PluginInfo, WasmManifest};
+use crate::error::{PluginError, Result};
+use crate::hooks::Hook;
+use crate::sandbox::SandboxConfig;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use wasmtime::component::{Component, Linker, ResourceTable};
+use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder, Trap};
+use wasmtime_wasi::{DirPerms, FilePerms, IoView, WasiCtx, WasiCtxBuilder, WasiView};
+
+// Generated code; not subject to the workspace lint set.
+#[allow(clippy::pedantic, clippy::nursery)]
+mod bindings {
+    wasmtime::component::bindgen!({
+        path: "wit",
+        world: "plugin",
+        async: true,
+    });
+}
+
+use bindings::libretto::plugin::{host, types};
+
+/// Default fuel budget per event (roughly one unit per instruction).
+pub const DEFAULT_WASM_FUEL: u64 = 10_000_000_000;
+
+/// Interval at which the engine epoch advances.
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Capabilities granted to a WebAssembly plugin.
+#[derive(Debug, Clone)]
+pub struct WasmCapabilities {
+    /// Directories preopened read-only.
+    pub read_paths: Vec<PathBuf>,
+    /// Directories preopened read-write.
+    pub write_paths: Vec<PathBuf>,
+    /// Deny all socket access.
+    pub block_network: bool,
+    /// Hosts sockets may connect to (empty = any).
+    pub allowed_hosts: Vec<String>,
+    /// Linear memory limit in bytes.
+    pub memory_limit: Option<usize>,
+    /// Wall-clock limit per event.
+    pub timeout: Duration,
+    /// Fuel budget per event.
+    pub fuel: u64,
+}
+
+impl Default for WasmCapabilities {
+    fn default() -> Self {
+        Self {
+            read_paths: Vec::new(),
+            write_paths: Vec::new(),
+            block_network: true,
+            allowed_hosts: Vec::new(),
+            memory_limit: None,
+            timeout: crate::sandbox::DEFAULT_TIMEOUT,
+            fuel: DEFAULT_WASM_FUEL,
+        }
+    }
+}
+
+impl WasmCapabilities {
+    /// Derive the capabilities of a plugin from the sandbox configuration.
+    ///
+    /// When the sandbox is disabled for the plugin the configured paths stay
+    /// the only reachable ones, but the network and memory are unrestricted.
+    #[must_use]
+    pub fn from_config(config: &SandboxConfig, plugin_id: &str) -> Self {
+        let override_config = config.plugin_overrides.get(plugin_id);
+        let enforced = config.enabled && !override_config.is_some_and(|o| o.disable);
+
+        let mut capabilities = Self {
+            read_paths: config.allowed_read_paths.clone(),
+            write_paths: config.allowed_write_paths.clone(),
+            block_network: enforced && config.block_network,
+            allowed_hosts: if enforced {
+                config.allowed_hosts.clone()
+            } else {
+                Vec::new()
+            },
+            memory_limit: override_config
+                .and_then(|o| o.memory_limit)
+                .or(Some(config.memory_limit))
+                .filter(|limit| enforced && *limit > 0),
+            timeout: override_config
+                .and_then(|o| o.timeout)
+                .unwrap_or(config.timeout),
+            fuel: DEFAULT_WASM_FUEL,
+        };
+        if let Some(o) = override_config {
+            capabilities
+                .read_paths
+                .extend(o.additional_read_paths.iter().cloned());
+            capabilities
+                .write_paths
+                .extend(o.additional_write_paths.iter().cloned());
+            capabilities
+                .allowed_hosts
+                .extend(o.additional_hosts.iter().cloned());
+        }
+        capabilities
+    }
+
+    /// Allow reading below a path.
+    #[must_use]
+    pub fn with_read_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.read_paths.push(path.into());
+        self
+    }
+
+    /// Set the fuel budget per event.
+    #[must_use]
+    pub const fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    /// Set the wall-clock limit per event.
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn wasi_ctx(&self) -> Result<WasiCtx> {
+        let mut builder = WasiCtxBuilder::new();
+        builder.inherit_stderr();
+
+        for (paths, dir_perms, file_perms) in [
+            (&self.read_paths, DirPerms::READ, FilePerms::READ),
+            (&self.write_paths, DirPerms::all(), FilePerms::all()),
+        ] {
+            for path in paths.iter().filter(|p| p.is_dir()) {
+                builder
+                    .preopened_dir(path, path.to_string_lossy(), dir_perms, file_perms)
+                    .map_err(|e| {
+                        PluginError::InitializationFailed(format!(
+                            "cannot preopen {}: {e}",
+                            path.display()
+                        ))
+                    })?;
+            }
+        }
+
+        if !self.block_network {
+            builder.allow_ip_name_lookup(true);
+            if self.allowed_hosts.is_empty() {
+                builder.inherit_network();
+            } else {
+                let allowed: Arc<[IpAddr]> = resolve_hosts(&self.allowed_hosts).into();
+                builder.socket_addr_check(move |addr, _| {
+                    let permitted = allowed.contains(&addr.ip());
+                    Box::pin(async move { permitted })
+                });
+            }
+        }
+
+        Ok(builder.build())
+    }
+
+    fn store_limits(&self) -> StoreLimits {
+        let mut limits = StoreLimitsBuilder::new().trap_on_grow_failure(true);
+        if let Some(limit) = self.memory_limit {
+            limits = limits.memory_size(limit);
+        }
+        limits.build()
+    }
+
+    /// Epoch ticks corresponding to the timeout.
+    fn epoch_deadline(&self) -> u64 {
+        let ticks = self.timeout.as_millis().div_ceil(EPOCH_TICK.as_millis());
+        u64::try_from(ticks).unwrap_or(u64::MAX).max(1)
+    }
+}
+
+/// Resolve host names to the addresses sockets may use.
+fn resolve_hosts(hosts: &[String]) -> Vec<IpAddr> {
+    let mut addresses = Vec::new();
+    for host in hosts {
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            addresses.push(ip);
+            continue;
+        }
+        match (host.as_str(), 0).to_socket_addrs() {
+            Ok(resolved) => addresses.extend(resolved.map(|a| a.ip())),
+            Err(e) => {
+                warn!(host = %host, error = %e, "cannot resolve allowed host for wasm plugin")
+            }
+        }
+    }
+    addresses
+}
+
+/// Per-instance host state.
+struct WasmState {
+    plugin_id: String,
+    wasi: WasiCtx,
+    table: ResourceTable,
+    limits: StoreLimits,
+}
+
+impl IoView for WasmState {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+
+impl WasiView for WasmState {
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.wasi
+    }
+}
+
+impl types::Host for WasmState {}
+
+impl host::Host for WasmState {
+    async fn log(&mut self, level: host::LogLevel, message: String) {
+        let plugin = self.plugin_id.as_str();
+        match level {
+            host::LogLevel::Trace => tracing::trace!(plugin, "{message}"),
+            host::LogLevel::Debug => tracing::debug!(plugin, "{message}"),
+            host::LogLevel::Info => tracing::info!(plugin, "{message}"),
+            host::LogLevel::Warn => tracing::warn!(plugin, "{message}"),
+            host::LogLevel::Error => tracing::error!(plugin, "{message}"),
+        }
+    }
+}
+
+/// WebAssembly plugin loader.
+///
+/// The wasmtime engine is created on first use so projects without
+/// WebAssembly plugins pay nothing for it.
+#[derive(Default)]
+pub struct WasmPluginLoader {
+    engine: OnceLock<Engine>,
+}
+
+impl std::fmt::Debug for WasmPluginLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmPluginLoader")
+            .field("initialized", &self.engine.get().is_some())
+            .finish()
+    }
+}
+
+impl WasmPluginLoader {
+    /// Create a new loader.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compile and instantiate a plugin component.
+    ///
+    /// # Errors
+    /// Returns error if the component cannot be compiled, does not implement
+    /// the `libretto:plugin` world, or traps while describing itself.
+    pub async fn load(
+        &self,
+        plugin_id: &str,
+        manifest: &WasmManifest,
+        capabilities: WasmCapabilities,
+    ) -> Result<WasmPlugin> {
+        info!(path = %manifest.component.display(), "loading wasm plugin");
+
+        let engine = self.engine()?;
+        let component = Component::from_file(engine, &manifest.component)
+            .map_err(|e| PluginError::library_load(&manifest.component, format!("{e:#}")))?;
+
+        let capabilities = match manifest.fuel {
+            Some(fuel) => capabilities.with_fuel(fuel),
+            None => capabilities,
+        };
+        WasmPlugin::instantiate(engine, &component, plugin_id, capabilities).await
+    }
+
+    fn engine(&self) -> Result<&Engine> {
+        if let Some(engine) = self.engine.get() {
+            return Ok(engine);
+        }
+
+        let mut config = Config::new();
+        config
+            .async_support(true)
+            .wasm_component_model(true)
+            .consume_fuel(true)
+            .epoch_interruption(true);
+        let engine = Engine::new(&config)
+            .map_err(|e| PluginError::InitializationFailed(format!("wasm engine: {e:#}")))?;
+
+        let engine = self.engine.get_or_init(|| engine);
+        spawn_epoch_ticker(engine);
+        Ok(engine)
+    }
+}
+
+/// Advance the engine epoch until the engine is dropped.
+fn spawn_epoch_ticker(engine: &Engine) {
+    let weak = engine.weak();
+    let spawned = std::thread::Builder::new()
+        .name("libretto-wasm-epoch".into())
+        .spawn(move || {
+            while let Some(engine) = weak.upgrade() {
+                engine.increment_epoch();
+                drop(engine);
+                std::thread::sleep(EPOCH_TICK);
+            }
+        });
+    if let Err(e) = spawned {
+        warn!(error = %e, "cannot start wasm epoch ticker; plugin timeouts rely on fuel only");
+    }
+}
+
+/// A loaded WebAssembly plugin.
+pub struct WasmPlugin {
+    id: String,
+    info: PluginInfo,
+    capabilities: WasmCapabilities,
+    instance: tokio::sync::Mutex<(Store<WasmState>, bindings::Plugin)>,
+}
+
+impl std::fmt::Debug for WasmPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmPlugin")
+            .field("id", &self.id)
+            .field("info", &self.info)
+            .field("capabilities", &self.capabilities)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WasmPlugin {
+    async fn instantiate(
+        engine: &Engine,
+        component: &Component,
+        plugin_id: &str,
+        capabilities: WasmCapabilities,
+    ) -> Result<Self> {
+        let mut linker = Linker::new(engine);
+        wasmtime_wasi::add_to_linker_async(&mut linker).map_err(|e| link_error(plugin_id, &e))?;
+        bindings::Plugin::add_to_linker(&mut linker, |state: &mut WasmState| state)
+            .map_err(|e| link_error(plugin_id, &e))?;
+
+        let state = WasmState {
+            plugin_id: plugin_id.to_string(),
+            wasi: capabilities.wasi_ctx()?,
+            table: ResourceTable::new(),
+            limits: capabilities.store_limits(),
+        };
+        let mut store = Store::new(engine, state);
+        store.limiter(|state| &mut state.limits);
+        arm(&mut store, &capabilities, plugin_id)?;
+
+        let instance = bindings::Plugin::instantiate_async(&mut store, component, &linker)
+            .await
+            .map_err(|e| trap_error(plugin_id, &capabilities, &e))?;
+
+        arm(&mut store, &capabilities, plugin_id)?;
+        let info = instance
+            .call_info(&mut store)
+            .await
+            .map_err(|e| trap_error(plugin_id, &capabilities, &e))?;
+
+        let mut builder = PluginInfo::builder(info.name, info.version)
+            .description(info.description)
+            .capabilities(
+                info.capabilities
+                    .iter()
+                    .filter_map(|c| PluginCapability::parse(c))
+                    .collect(),
+            );
+        if let Some(priority) = info.priority {
+            builder = builder.priority(priority);
+        }
+        let info = builder.build();
+        debug!(plugin = plugin_id, capabilities = ?info.capabilities, "wasm plugin instantiated");
+
+        Ok(Self {
+            id: plugin_id.to_string(),
+            info,
+            capabilities,
+            instance: tokio::sync::Mutex::new((store, instance)),
+        })
+    }
+
+    /// Get the capabilities granted to this plugin.
+    #[must_use]
+    pub const fn granted(&self) -> &WasmCapabilities {
+        &self.capabilities
+    }
+
+    /// Invoke the plugin's `on-event` export.
+    ///
+    /// # Errors
+    /// Returns error if the plugin traps, runs out of fuel or exceeds its
+    /// timeout. Errors reported by the plugin itself become
+    /// [`EventResult::error`].
+    pub async fn invoke(&self, hook: Hook, context: &EventContext) -> Result<EventResult> {
+        let mut guard = self.instance.lock().await;
+        let (store, instance) = &mut *guard;
+        arm(store, &self.capabilities, &self.id)?;
+
+        let context = to_wit_context(context);
+        match instance
+            .call_on_event(&mut *store, hook.as_str(), &context)
+            .await
+        {
+            Ok(Ok(result)) => Ok(from_wit_result(result)),
+            Ok(Err(message)) => Ok(EventResult::error(message)),
+            Err(e) => Err(trap_error(&self.id, &self.capabilities, &e)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for WasmPlugin {
+    fn info(&self) -> PluginInfo {
+        self.info.clone()
+    }
+
+    fn capabilities(&self) -> Vec<PluginCapability> {
+        self.info.capabilities.clone()
+    }
+
+    async fn on_event(&self, event: Hook, context: &EventContext) -> Result<EventResult> {
+        self.invoke(event, context).await
+    }
+}
+
+/// Refill fuel and reset the epoch deadline before entering the guest.
+fn arm(
+    store: &mut Store<WasmState>,
+    capabilities: &WasmCapabilities,
+    plugin_id: &str,
+) -> Result<()> {
+    store
+        .set_fuel(capabilities.fuel)
+        .map_err(|e| PluginError::ExecutionFailed(format!("{plugin_id}: {e:#}")))?;
+    store.set_epoch_deadline(capabilities.epoch_deadline());
+    Ok(())
+}
+
+fn link_error(plugin_id: &str, error: &wasmtime::Error) -> PluginError {
+    PluginError::InitializationFailed(format!("{plugin_id}: {error:#}"))
+}
+
+fn trap_error(
+    plugin_id: &str,
+    capabilities: &WasmCapabilities,
+    error: &wasmtime::Error,
+) -> PluginError {
+    match error.downcast_ref::<Trap>() {
+        Some(Trap::Interrupt) => PluginError::timeout(plugin_id, capabilities.timeout.as_secs()),
+        Some(Trap::OutOfFuel) => PluginError::ExecutionFailed(format!(
+            "{plugin_id} exhausted its fuel budget of {}",
+            capabilities.fuel
+        )),
+        _ => match capabilities.memory_limit {
+            // `trap_on_grow_failure` turns a denied `memory.grow` into a trap
+            Some(limit) if format!("{error:#}").contains("growing memory") => {
+                PluginError::memory_limit(plugin_id, limit / (1024 * 1024))
+            }
+            _ => PluginError::crashed(plugin_id, format!("{error:#}")),
+        },
+    }
+}
+
+fn to_wit_context(context: &EventContext) -> types::EventContext {
+    types::EventContext {
+        packages: context.packages.iter().map(ToString::to_string).collect(),
+        operation: context.operation.clone(),
+        project_root: context.project_root.as_deref().map(path_string),
+        vendor_dir: context.vendor_dir.as_deref().map(path_string),
+        data: sorted_pairs(&context.data),
+        args: context.args.clone(),
+        dev_mode: context.dev_mode,
+        verbose: context.verbose,
+        urls: context.urls.clone(),
+        versions: sorted_pairs(&context.versions),
+    }
+}
+
+fn from_wit_result(result: types::EventResult) -> EventResult {
+    EventResult {
+        continue_processing: result.continue_processing,
+        messages: result.messages,
+        modified_data: result.modified_data.into_iter().collect(),
+        error: result.error,
+        warnings: result.warnings,
+    }
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn sorted_pairs(map: &std::collections::HashMap<String, String>) -> Vec<(String, String)> {
+    let mut pairs: Vec<_> = map.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+    pairs.sort();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::SandboxOverride;
+    use tempfile::TempDir;
+
+    /// A component implementing the plugin world by hand.
+    ///
+    /// `info` describes a download plugin; `on-event` runs `body`, which must
+    /// leave a pointer to the result on the stack.
+    fn component(body: &str) -> String {
+        format!(
+            r#"(component
+  (core module $m
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 8192))
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get 2) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get 2))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get 3)))
+      (local.get $ptr))
+    (data (i32.const 100) "acme/echo")
+    (data (i32.const 120) "1.0.0")
+    (data (i32.const 130) "Echoes hooks")
+    (data (i32.const 150) "download")
+    (data (i32.const 160) "url")
+    (data (i32.const 170) "https://mirror.test/dist.zip")
+    (func (export "info") (result i32)
+      (i32.store (i32.const 1000) (i32.const 100))
+      (i32.store (i32.const 1004) (i32.const 9))
+      (i32.store (i32.const 1008) (i32.const 120))
+      (i32.store (i32.const 1012) (i32.const 5))
+      (i32.store (i32.const 1016) (i32.const 130))
+      (i32.store (i32.const 1020) (i32.const 12))
+      (i32.store (i32.const 1024) (i32.const 1100))
+      (i32.store (i32.const 1028) (i32.const 1))
+      (i32.store8 (i32.const 1032) (i32.const 1))
+      (i32.store (i32.const 1036) (i32.const -10))
+      (i32.store (i32.const 1100) (i32.const 150))
+      (i32.store (i32.const 1104) (i32.const 8))
+      (i32.const 1000))
+    (func (export "on-event") (param $args i32) (result i32)
+      {body}))
+  (core instance $i (instantiate $m))
+  (type $plugin-info (record
+    (field "name" string)
+    (field "version" string)
+    (field "description" string)
+    (field "capabilities" (list string))
+    (field "priority" (option s32))))
+  (export $pi "plugin-info" (type $plugin-info))
+  (type $event-context (record
+    (field "packages" (list string))
+    (field "operation" (option string))
+    (field "project-root" (option string))
+    (field "vendor-dir" (option string))
+    (field "data" (list (tuple string string)))
+    (field "args" (list string))
+    (field "dev-mode" bool)
+    (field "verbose" bool)
+    (field "urls" (list string))
+    (field "versions" (list (tuple string string)))))
+  (export $ec "event-context" (type $event-context))
+  (type $event-result (record
+    (field "continue-processing" bool)
+    (field "messages" (list string))
+    (field "modified-data" (list (tuple string string)))
+    (field "error" (option string))
+    (field "warnings" (list string))))
+  (export $er "event-result" (type $event-result))
+  (func $info (result $pi)
+    (canon lift (core func $i "info") (memory $i "memory")))
+  (func $on-event (param "hook" string) (param "context" $ec) (result (result $er (error string)))
+    (canon lift (core func $i "on-event") (memory $i "memory") (realloc (func $i "realloc"))))
+  (export "info" (func $info))
+  (export "on-event" (func $on-event)))"#
+        )
+    }
+
+    /// Returns `ok` with the hook name as message and a rewritten `url`.
+    const ECHO: &str = r"
+      (i32.store (i32.const 2100) (i32.load (local.get $args)))
+      (i32.store (i32.const 2104) (i32.load offset=4 (local.get $args)))
+      (i32.store (i32.const 2200) (i32.const 160))
+      (i32.store (i32.const 2204) (i32.const 3))
+      (i32.store (i32.const 2208) (i32.const 170))
+      (i32.store (i32.const 2212) (i32.const 28))
+      (i32.store8 (i32.const 2000) (i32.const 0))
+      (i32.store8 (i32.const 2004) (i32.const 1))
+      (i32.store (i32.const 2008) (i32.const 2100))
+      (i32.store (i32.const 2012) (i32.const 1))
+      (i32.store (i32.const 2016) (i32.const 2200))
+      (i32.store (i32.const 2020) (i32.const 1))
+      (i32.store8 (i32.const 2024) (i32.const 0))
+      (i32.store (i32.const 2036) (i32.const 0))
+      (i32.store (i32.const 2040) (i32.const 0))
+      (i32.const 2000)";
+
+    /// Never returns.
+    const SPIN: &str = "(loop $l (br $l)) (unreachable)";
+
+    /// Grows memory well past a 1 MiB limit.
+    const GROW: &str = "(drop (memory.grow (i32.const 100))) (i32.const 2000)";
+
+    fn write_component(dir: &TempDir, body: &str) -> WasmManifest {
+        let bytes = wat::parse_str(component(body)).unwrap();
+        let path = dir.path().join("plugin.wasm");
+        std::fs::write(&path, bytes).unwrap();
+        WasmManifest {
+            component: path,
+            fuel: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn component_receives_events() {
+        let temp = TempDir::new().unwrap();
+        let manifest = write_component(&temp, ECHO);
+
+        let plugin = WasmPluginLoader::new()
+            .load("acme/echo", &manifest, WasmCapabilities::default())
+            .await
+            .unwrap();
+
+        let info = Plugin::info(&plugin);
+        assert_eq!(info.name, "acme/echo");
+        assert_eq!(info.version, "1.0.0");
+        assert_eq!(info.priority, Some(-10));
+        assert_eq!(plugin.capabilities(), vec![PluginCapability::Download]);
+
+        let context = EventContext::new().with_url("https://example.com/dist.zip");
+        let result = plugin
+            .on_event(Hook::PreFileDownload, &context)
+            .await
+            .unwrap();
+        assert!(result.continue_processing);
+        assert_eq!(result.messages, vec!["pre-file-download".to_string()]);
+        assert_eq!(
+            result.modified_data.get("url").map(String::as_str),
+            Some("https://mirror.test/dist.zip")
+        );
+        assert!(result.error.is_none());
+
+        // The instance stays usable across events.
+        let result = plugin
+            .on_event(Hook::PostInstallCmd, &context)
+            .await
+            .unwrap();
+        assert_eq!(result.messages, vec!["post-install-cmd".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn manager_discovers_and_loads_component() {
+        let temp = TempDir::new().unwrap();
+        let vendor = temp.path().join("vendor");
+        let package = vendor.join("acme/echo");
+        std::fs::create_dir_all(vendor.join("composer")).unwrap();
+        std::fs::create_dir_all(&package).unwrap();
+        std::fs::write(
+            package.join("plugin.wasm"),
+            wat::parse_str(component(ECHO)).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(
+            vendor.join("composer/installed.json"),
+            r#"{"packages": [{
+                "name": "acme/echo",
+                "version": "1.0.0",
+                "type": "libretto-plugin",
+                "extra": { "libretto-wasm-plugin": "plugin.wasm" }
+            }]}"#,
+        )
+        .unwrap();
+
+        let mut manager = crate::PluginManager::new();
+        let handles = manager.discover(&vendor).await.unwrap();
+        assert_eq!(handles.len(), 1);
+        assert_eq!(handles[0].plugin_type, crate::PluginType::Wasm);
+
+        manager.load("acme/echo").await.unwrap();
+        let url = manager
+            .rewrite_download_url("https://example.com/dist.zip", &EventContext::new())
+            .await
+            .unwrap();
+        assert_eq!(url, "https://mirror.test/dist.zip");
+
+        manager.unload("acme/echo").await.unwrap();
+        let url = manager
+            .rewrite_download_url("https://example.com/dist.zip", &EventContext::new())
+            .await
+            .unwrap();
+        assert_eq!(url, "https://example.com/dist.zip");
+    }
+
+    #[tokio::test]
+    async fn fuel_exhaustion_traps() {
+        let temp = TempDir::new().unwrap();
+        let mut manifest = write_component(&temp, SPIN);
+        manifest.fuel = Some(1_000_000);
+
+        let plugin = WasmPluginLoader::new()
+            .load("acme/spin", &manifest, WasmCapabilities::default())
+            .await
+            .unwrap();
+        let err = plugin
+            .invoke(Hook::PostInstallCmd, &EventContext::new())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("fuel"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn epoch_deadline_interrupts() {
+        let temp = TempDir::new().unwrap();
+        let manifest = write_component(&temp, SPIN);
+
+        let capabilities = WasmCapabilities::default()
+            .with_fuel(u64::MAX)
+            .with_timeout(Duration::from_millis(50));
+        let plugin = WasmPluginLoader::new()
+            .load("acme/spin", &manifest, capabilities)
+            .await
+            .unwrap();
+        let err = plugin
+            .invoke(Hook::PostInstallCmd, &EventContext::new())
+            .await
+            .unwrap_err();
+        assert!(err.is_timeout(), "{err}");
+    }
+
+    #[tokio::test]
+    async fn memory_limit_traps() {
+        let temp = TempDir::new().unwrap();
+        let manifest = write_component(&temp, GROW);
+
+        let capabilities = WasmCapabilities {
+            memory_limit: Some(1 << 20),
+            ..WasmCapabilities::default()
+        };
+        let plugin = WasmPluginLoader::new()
+            .load("acme/grow", &manifest, capabilities)
+            .await
+            .unwrap();
+        let err = plugin
+            .invoke(Hook::PostInstallCmd, &EventContext::new())
+            .await
+            .unwrap_err();
+        assert!(err.is_sandbox_violation(), "{err}");
+    }
+
+    #[tokio::test]
+    async fn invalid_component_is_rejected() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("plugin.wasm");
+        std::fs::write(&path, b"not wasm").unwrap();
+        let manifest = WasmManifest {
+            component: path,
+            fuel: None,
+        };
+
+        let err = WasmPluginLoader::new()
+            .load("acme/broken", &manifest, WasmCapabilities::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PluginError::LibraryLoad { .. }), "{err}");
+    }
+
+    #[test]
+    fn capabilities_from_config() {
+        let mut config = SandboxConfig {
+            allowed_read_paths: vec![PathBuf::from("/project")],
+            allowed_hosts: vec!["repo.packagist.org".into()],
+            timeout: Duration::from_secs(5),
+            ..SandboxConfig::default()
+        };
+        config.plugin_overrides.insert(
+            "acme/plugin".into(),
+            SandboxOverride {
+                timeout: Some(Duration::from_secs(1)),
+                additional_write_paths: vec![PathBuf::from("/project/var")],
+                ..SandboxOverride::default()
+            },
+        );
+
+        let capabilities = WasmCapabilities::from_config(&config, "acme/plugin");
+        assert_eq!(capabilities.read_paths, vec![PathBuf::from("/project")]);
+        assert_eq!(
+            capabilities.write_paths,
+            vec![PathBuf::from("/project/var")]
+        );
+        assert_eq!(capabilities.allowed_hosts, vec!["repo.packagist.org"]);
+        assert_eq!(capabilities.timeout, Duration::from_secs(1));
+        assert_eq!(capabilities.epoch_deadline(), 100);
+        assert_eq!(
+            capabilities.memory_limit,
+            Some(crate::sandbox::DEFAULT_MEMORY_LIMIT)
+        );
+
+        config.enabled = false;
+        let capabilities = WasmCapabilities::from_config(&config, "other/plugin");
+        assert!(!capabilities.block_network);
+        assert!(capabilities.allowed_hosts.is_empty());
+        assert!(capabilities.memory_limit.is_none());
+    }
+}
@@ -313,7 +313,7 @@ impl RepositoryManager {
     fn sort_repositories(&self) {
         self.repositories
             .write()
-            .sort_by(|a, b| b.priority.cmp(&a.priority));
+            .sort_by_key(|r| std::cmp::Reverse(r.priority));
     }
 
     /// Get package from repositories.
@@ -6,8 +6,10 @@ use libretto_resolver::{
     PackageFetcher, PackageIndex, PackageName, ResolutionMode, Resolver, ResolverConfig, Stability,
 };
 use rand::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::pin::Pin;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
 /// A mock async fetcher that wraps a synchronous `MemorySource` for benchmarking.
@@ -58,6 +60,11 @@ impl PackageFetcher for MockFetcher {
                         .iter()
                         .map(|d| (d.name.as_str().to_string(), d.constraint.to_string()))
                         .collect(),
+                    conflict: v
+                        .conflicts
+                        .iter()
+                        .map(|d| (d.name.as_str().to_string(), d.constraint.to_string()))
+                        .collect(),
                     suggest: v
                         .suggests
                         .iter()
@@ -66,6 +73,7 @@ impl PackageFetcher for MockFetcher {
                     dist_url: v.dist_url.as_ref().map(ToString::to_string),
                     dist_type: v.dist_type.as_ref().map(ToString::to_string),
                     dist_shasum: v.dist_shasum.as_ref().map(ToString::to_string),
+                    dist_signature: v.dist_signature.as_ref().map(ToString::to_string),
                     source_url: v.source_url.as_ref().map(ToString::to_string),
                     source_type: v.source_type.as_ref().map(ToString::to_string),
                     source_reference: v.source_reference.as_ref().map(ToString::to_string),
@@ -83,6 +91,7 @@ impl PackageFetcher for MockFetcher {
                     funding: v.funding.clone(),
                     notification_url: v.notification_url.as_ref().map(ToString::to_string),
                     bin: v.bin.clone(),
+                    abandoned: v.abandoned.clone(),
                 })
                 .collect();
 
@@ -94,6 +103,86 @@ impl PackageFetcher for MockFetcher {
     }
 }
 
+/// A fetcher that answers after a fixed delay, like a remote repository,
+/// and counts the requests it serves.
+struct LatencyFetcher {
+    inner: MockFetcher,
+    latency: Duration,
+    requests: AtomicUsize,
+}
+
+impl LatencyFetcher {
+    fn new(source: MemorySource, latency: Duration) -> Self {
+        Self {
+            inner: MockFetcher::new(source),
+            latency,
+            requests: AtomicUsize::new(0),
+        }
+    }
+
+    /// Take the number of requests served so far.
+    fn take_requests(&self) -> usize {
+        self.requests.swap(0, Ordering::Relaxed)
+    }
+}
+
+impl PackageFetcher for LatencyFetcher {
+    fn fetch(
+        &self,
+        name: String,
+    ) -> Pin<Box<dyn std::future::Future<Output = Option<FetchedPackage>> + Send + '_>> {
+        Box::pin(async move {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(self.latency).await;
+            self.inner.fetch(name).await
+        })
+    }
+}
+
+/// A fetcher serving packages that were already downloaded.
+struct PreloadedFetcher {
+    packages: HashMap<String, FetchedPackage>,
+}
+
+impl PackageFetcher for PreloadedFetcher {
+    fn fetch(
+        &self,
+        name: String,
+    ) -> Pin<Box<dyn std::future::Future<Output = Option<FetchedPackage>> + Send + '_>> {
+        Box::pin(async move { self.packages.get(&name).cloned() })
+    }
+}
+
+/// Fetch every package reachable through the requirements of any version,
+/// one dependency level at a time, before resolving.
+async fn resolve_eagerly(
+    fetcher: &LatencyFetcher,
+    deps: &[Dependency],
+    config: ResolverConfig,
+) -> usize {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut packages = HashMap::new();
+    let mut wave: Vec<String> = deps.iter().map(|d| d.name.as_str().to_string()).collect();
+    while !wave.is_empty() {
+        wave.retain(|name| seen.insert(name.clone()));
+        let fetched = futures::future::join_all(
+            std::mem::take(&mut wave)
+                .into_iter()
+                .map(|name| fetcher.fetch(name)),
+        )
+        .await;
+        for package in fetched.into_iter().flatten() {
+            for version in &package.versions {
+                wave.extend(version.require.iter().map(|(name, _)| name.clone()));
+            }
+            packages.insert(package.name.clone(), package);
+        }
+    }
+
+    let resolver = Resolver::new(Arc::new(PreloadedFetcher { packages }), config);
+    resolver.resolve(deps, &[]).await.map_or(0, |r| r.len())
+}
+
 /// Generate a synthetic package registry with the specified number of packages.
 fn generate_registry(
     num_packages: usize,
@@ -233,6 +322,7 @@ fn bench_resolution(c: &mut Criterion) {
             mode: ResolutionMode::PreferStable,
             min_stability: Stability::Stable,
             include_dev: false,
+            block_abandoned: false,
         };
         let resolver = Resolver::new(Arc::clone(&fetcher), config);
 
@@ -257,6 +347,64 @@ fn bench_resolution(c: &mut Criterion) {
     group.finish();
 }
 
+/// Compare fetching all reachable metadata up front with fetching it on
+/// demand, against a repository with per-request latency.
+fn bench_fetch_avoidance(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fetch_avoidance");
+    group.sample_size(10);
+
+    for size in [50, 100] {
+        let fetcher = Arc::new(LatencyFetcher::new(
+            generate_registry(size, 5, 2),
+            Duration::from_millis(2),
+        ));
+        let config = ResolverConfig {
+            max_concurrent: 32,
+            request_timeout: Duration::from_secs(10),
+            mode: ResolutionMode::PreferStable,
+            min_stability: Stability::Stable,
+            include_dev: false,
+            block_abandoned: false,
+        };
+        let deps: Vec<_> = (0..3)
+            .map(|i| {
+                Dependency::new(
+                    PackageName::parse(&format!("vendor{}/package{}", i / 100, i % 100)).unwrap(),
+                    ComposerConstraint::parse(">=1.0").unwrap(),
+                )
+            })
+            .collect();
+
+        let rt = create_runtime();
+
+        // Report how many requests each strategy makes
+        rt.block_on(resolve_eagerly(&fetcher, &deps, config.clone()));
+        let eager = fetcher.take_requests();
+        let probe = Resolver::new(Arc::clone(&fetcher), config.clone());
+        let _ = rt.block_on(probe.resolve(&deps, &[]));
+        let on_demand = fetcher.take_requests();
+        eprintln!(
+            "fetch_avoidance/{size}: eager fetched {eager} packages, on-demand fetched \
+             {on_demand} ({} avoided, {} solver rounds)",
+            probe.stats().packages_avoided.load(Ordering::Relaxed),
+            probe.stats().solver_rounds.load(Ordering::Relaxed),
+        );
+
+        group.bench_with_input(BenchmarkId::new("eager", size), &size, |b, _| {
+            b.iter(|| rt.block_on(resolve_eagerly(&fetcher, &deps, config.clone())));
+        });
+        group.bench_with_input(BenchmarkId::new("on_demand", size), &size, |b, _| {
+            b.iter(|| {
+                // A fresh resolver, so no metadata is reused between iterations
+                let resolver = Resolver::new(Arc::clone(&fetcher), config.clone());
+                rt.block_on(async { black_box(resolver.resolve(&deps, &[]).await) })
+            });
+        });
+    }
+
+    group.finish();
+}
+
 /// Benchmark warm cache resolution.
 fn bench_warm_resolution(c: &mut Criterion) {
     let source = generate_registry(100, 5, 2);
@@ -267,6 +415,7 @@ fn bench_warm_resolution(c: &mut Criterion) {
         mode: ResolutionMode::PreferStable,
         min_stability: Stability::Stable,
         include_dev: false,
+        block_abandoned: false,
     };
     let resolver = Resolver::new(Arc::clone(&fetcher), config);
 
@@ -299,6 +448,7 @@ fn bench_prefer_lowest(c: &mut Criterion) {
         mode: ResolutionMode::PreferLowest,
         min_stability: Stability::Stable,
         include_dev: false,
+        block_abandoned: false,
     };
     let resolver = Resolver::new(fetcher, config);
 
@@ -325,6 +475,7 @@ criterion_group!(
     bench_constraint_matching,
     bench_index_lookup,
     bench_resolution,
+    bench_fetch_avoidance,
     bench_warm_resolution,
     bench_prefer_lowest,
 );
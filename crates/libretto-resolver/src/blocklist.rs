@@ -0,0 +1,119 @@
+//! Versions the solver must not select.
+//!
+//! Security advisories and abandoned packages are turned into a
+//! [`VersionBlocklist`] before solving. The solver picks the best version that
+//! is not blocked; when only blocked versions fit, resolution fails with the
+//! recorded reason so the conflict report names the advisory.
+
+use crate::version::{ComposerConstraint, ComposerVersion};
+use ahash::AHashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Blocked version ranges per package, each with a human-readable reason.
+#[derive(Debug, Clone, Default)]
+pub struct VersionBlocklist {
+    rules: AHashMap<String, Vec<BlockRule>>,
+}
+
+#[derive(Debug, Clone)]
+struct BlockRule {
+    constraint: ComposerConstraint,
+    reason: Arc<str>,
+}
+
+impl VersionBlocklist {
+    /// Create an empty blocklist.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Block the versions of `package` matching `constraint`.
+    ///
+    /// `reason` completes "... are unavailable", e.g. "because it is affected
+    /// by CVE-2024-1234".
+    pub fn block(
+        &mut self,
+        package: &str,
+        constraint: ComposerConstraint,
+        reason: impl Into<Arc<str>>,
+    ) {
+        self.rules
+            .entry(package.to_ascii_lowercase())
+            .or_default()
+            .push(BlockRule {
+                constraint,
+                reason: reason.into(),
+            });
+    }
+
+    /// Why `version` of `package` is blocked, if it is.
+    #[must_use]
+    pub fn reason(&self, package: &str, version: &ComposerVersion) -> Option<&str> {
+        self.rules
+            .get(package)?
+            .iter()
+            .find(|rule| rule.constraint.matches(version))
+            .map(|rule| &*rule.reason)
+    }
+
+    /// Add the rules of `other`.
+    pub fn merge(&mut self, other: Self) {
+        for (package, rules) in other.rules {
+            self.rules.entry(package).or_default().extend(rules);
+        }
+    }
+
+    /// Check whether nothing is blocked.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Number of blocked packages.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+}
+
+/// Source of version blocks, consulted as package metadata is fetched.
+///
+/// Implement this to keep versions out of the resolution, e.g. those affected
+/// by security advisories.
+pub trait VersionFilter: Send + Sync + 'static {
+    /// Build the blocklist for newly fetched packages.
+    ///
+    /// Called after every on-demand fetch, each time with packages not seen
+    /// before.
+    fn blocklist(
+        &self,
+        packages: Vec<String>,
+    ) -> Pin<Box<dyn std::future::Future<Output = VersionBlocklist> + Send + '_>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reason_matches_constraint() {
+        let mut blocklist = VersionBlocklist::new();
+        blocklist.block(
+            "Vendor/Pkg",
+            ComposerConstraint::parse(">=1.0,<1.2.3").unwrap(),
+            "because it is affected by CVE-2024-1",
+        );
+
+        let affected = ComposerVersion::parse("1.2.0").unwrap();
+        let fixed = ComposerVersion::parse("1.2.3").unwrap();
+        assert_eq!(
+            blocklist.reason("vendor/pkg", &affected),
+            Some("because it is affected by CVE-2024-1")
+        );
+        assert_eq!(blocklist.reason("vendor/pkg", &fixed), None);
+        assert_eq!(blocklist.reason("other/pkg", &affected), None);
+        assert_eq!(blocklist.len(), 1);
+    }
+}
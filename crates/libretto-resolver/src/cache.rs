@@ -0,0 +1,166 @@
+//! Persistent resolution cache.
+//!
+//! A resolution is stored in the tiered cache under a hash of everything the
+//! solver reads besides package metadata: the root requirements, the resolver
+//! configuration, the platform and the configured repositories. The entry
+//! records the validator (`ETag` or `Last-Modified`) of every metadata
+//! document the solver consumed, and is only reused while all of them still
+//! match. Otherwise it seeds the next solve with the previously fetched
+//! packages and selected versions.
+
+use crate::package::Dependency;
+use crate::resolver::ResolverConfig;
+use crate::version::ComposerVersion;
+use ahash::AHashMap;
+use libretto_cache::{CacheEntryType, TieredCache};
+use libretto_core::{ContentHash, ContentHasher};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Bumped whenever the stored format or the key inputs change.
+const FORMAT_VERSION: &str = "2";
+
+/// Resolutions persisted in a [`TieredCache`].
+#[derive(Clone)]
+pub struct ResolutionCache {
+    cache: Arc<TieredCache>,
+    platform: String,
+    repositories: String,
+}
+
+impl std::fmt::Debug for ResolutionCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResolutionCache")
+            .field("platform", &self.platform)
+            .field("repositories", &self.repositories)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ResolutionCache {
+    /// Persist resolutions in `cache`, keyed for the current OS and architecture.
+    #[must_use]
+    pub fn new(cache: Arc<TieredCache>) -> Self {
+        Self {
+            cache,
+            platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+            repositories: String::new(),
+        }
+    }
+
+    /// Include platform overrides (such as `config.platform`) in the key.
+    #[must_use]
+    pub fn with_platform(mut self, platform: &str) -> Self {
+        self.platform = format!(
+            "{}-{} {platform}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        );
+        self
+    }
+
+    /// Include the repository configuration in the key.
+    #[must_use]
+    pub fn with_repositories(mut self, repositories: impl Into<String>) -> Self {
+        self.repositories = repositories.into();
+        self
+    }
+
+    /// Hash of the resolver inputs other than package metadata.
+    pub(crate) fn key(
+        &self,
+        root_deps: &[Dependency],
+        dev_deps: &[Dependency],
+        config: &ResolverConfig,
+        filtered: bool,
+        platform: &AHashMap<String, ComposerVersion>,
+    ) -> ContentHash {
+        let mut hasher = ContentHasher::new();
+        let mut field = |label: &str, value: &str| {
+            hasher.update(label.as_bytes());
+            hasher.update(b"=");
+            hasher.update(value.as_bytes());
+            hasher.update(b"\n");
+        };
+
+        field("format", FORMAT_VERSION);
+        for dep in root_deps {
+            field(
+                "require",
+                &format!("{} {}", dep.name, dep.constraint.as_str()),
+            );
+        }
+        if config.include_dev {
+            for dep in dev_deps {
+                field(
+                    "require-dev",
+                    &format!("{} {}", dep.name, dep.constraint.as_str()),
+                );
+            }
+        }
+        field("mode", &format!("{:?}", config.mode));
+        field("minimum-stability", &config.min_stability.to_string());
+        field("block-abandoned", &config.block_abandoned.to_string());
+        field("filtered", &filtered.to_string());
+        field("platform", &self.platform);
+        let mut packages: Vec<_> = platform.iter().collect();
+        packages.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, version) in packages {
+            field("platform-package", &format!("{name} {version}"));
+        }
+        field("repositories", &self.repositories);
+
+        hasher.finalize()
+    }
+
+    /// Load the resolution stored under `key`.
+    pub(crate) fn load(&self, key: &ContentHash) -> Option<CachedResolution> {
+        let data = match self.cache.get(key) {
+            Ok(data) => data?,
+            Err(e) => {
+                debug!(error = %e, "resolution cache read failed");
+                return None;
+            }
+        };
+        match sonic_rs::from_slice(&data) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                debug!(error = %e, "ignoring unreadable cached resolution");
+                None
+            }
+        }
+    }
+
+    /// Replace the resolution stored under `key`.
+    pub(crate) fn store(&self, key: ContentHash, entry: &CachedResolution) {
+        let data = match sonic_rs::to_vec(entry) {
+            Ok(data) => data,
+            Err(e) => {
+                debug!(error = %e, "failed to serialize resolution");
+                return;
+            }
+        };
+        // Entries are write-once per hash, so drop the previous resolution first
+        let stored = self.cache.remove(&key).and_then(|_| {
+            self.cache
+                .put_with_hash(key, &data, CacheEntryType::Resolution, None, None)
+        });
+        if let Err(e) = stored {
+            debug!(error = %e, "failed to cache resolution");
+        }
+    }
+}
+
+/// A stored resolution and the metadata it was computed from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct CachedResolution {
+    /// Selected version of every resolved package.
+    pub packages: BTreeMap<String, String>,
+    /// Validator of every fetched metadata document, `None` when the
+    /// fetcher could not provide one.
+    pub metadata: BTreeMap<String, Option<String>>,
+    /// Packages the solver looked up that do not exist.
+    pub unavailable: Vec<String>,
+}
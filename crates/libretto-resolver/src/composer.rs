@@ -628,6 +628,23 @@ pub enum AbandonedField {
     Replacement(String),
 }
 
+impl AbandonedField {
+    /// Check whether the package is abandoned.
+    #[must_use]
+    pub const fn is_abandoned(&self) -> bool {
+        !matches!(self, Self::Bool(false))
+    }
+
+    /// Suggested replacement package, if any.
+    #[must_use]
+    pub fn replacement(&self) -> Option<&str> {
+        match self {
+            Self::Replacement(name) if !name.is_empty() => Some(name),
+            _ => None,
+        }
+    }
+}
+
 /// Error when parsing a manifest.
 #[derive(Debug, thiserror::Error)]
 pub enum ManifestError {
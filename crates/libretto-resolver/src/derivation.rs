@@ -0,0 +1,417 @@
+//! Structured explanation of a failed resolution.
+//!
+//! When no solution exists, `PubGrub` proves it with a derivation tree: every
+//! leaf is a fact taken from the inputs (a root requirement, a package's
+//! requirement or conflict, a missing or unusable version) and every inner
+//! node is an
+//! incompatibility derived from its two causes. [`Derivation`] mirrors that
+//! tree with plain names and constraints so callers can render or serialize
+//! it without depending on the solver.
+
+use crate::package::PackageName;
+use crate::resolver::{guarded_package, not_installed};
+use crate::version::ComposerVersion;
+use ahash::{AHashMap, AHashSet};
+use pubgrub::{DerivationTree, External, Term};
+use serde::Serialize;
+use std::fmt::Write;
+use std::ops::Bound;
+use version_ranges::Ranges;
+
+/// Name of the virtual package the solver resolves the root requirements for.
+const ROOT: &str = "__root__/__root__";
+
+/// What a node of a [`Derivation`] states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DerivationKind {
+    /// The root project requires a package.
+    RootRequires,
+    /// A package version requires another package.
+    Requires,
+    /// A package version conflicts with versions of another package.
+    Conflicts,
+    /// No known version of a package matches.
+    NoVersions,
+    /// Matching versions exist but cannot be used, e.g. blocked by an advisory.
+    Unavailable,
+    /// Conclusion derived from the node's two causes.
+    Derived,
+}
+
+/// A package and version range an incompatibility is about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DerivationTerm {
+    /// Package name, `root` for the root project.
+    pub package: String,
+    /// Version range, `*` for any version.
+    pub constraint: String,
+    /// Whether the term is about versions outside the constraint.
+    pub negated: bool,
+}
+
+/// One incompatibility of a failed resolution with the facts it follows from.
+#[derive(Debug, Clone, Serialize)]
+pub struct Derivation {
+    /// What the incompatibility states.
+    pub kind: DerivationKind,
+    /// Human-readable statement, e.g. `acme/a ==1.0.0 requires acme/b >=2.0.0, <3.0.0`.
+    pub message: String,
+    /// Packages and ranges that cannot all hold at once.
+    pub terms: Vec<DerivationTerm>,
+    /// Set when the same incompatibility is referenced more than once; only
+    /// its first occurrence carries causes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shared_id: Option<usize>,
+    /// The incompatibilities this one was derived from.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub causes: Vec<Self>,
+}
+
+impl Derivation {
+    /// Convert a solver derivation tree, naming the installed version of
+    /// the `platform` packages it mentions.
+    ///
+    /// Conflict guards are shown as the package they mirror, and the fact
+    /// that a package is mirrored by its guard is left out.
+    pub(crate) fn from_tree(
+        tree: &DerivationTree<PackageName, Ranges<ComposerVersion>, String>,
+        platform: &AHashMap<String, ComposerVersion>,
+    ) -> Self {
+        Self::convert(tree, platform, &mut AHashSet::new())
+    }
+
+    fn convert(
+        tree: &DerivationTree<PackageName, Ranges<ComposerVersion>, String>,
+        platform: &AHashMap<String, ComposerVersion>,
+        seen: &mut AHashSet<usize>,
+    ) -> Self {
+        match tree {
+            DerivationTree::External(external) => Self::external(external, platform),
+            DerivationTree::Derived(derived) => {
+                let mut terms: Vec<_> = derived
+                    .terms
+                    .iter()
+                    .filter(|(package, _)| package.as_str() != ROOT)
+                    .map(|(package, term)| match term {
+                        Term::Positive(range) => term_for(package, range, false),
+                        Term::Negative(range) => term_for(package, range, true),
+                    })
+                    .collect();
+                terms.sort_by(|a, b| a.package.cmp(&b.package));
+
+                let first = derived.shared_id.is_none_or(|id| seen.insert(id));
+                let causes = if first {
+                    [&derived.cause1, &derived.cause2]
+                        .into_iter()
+                        .filter(|cause| !is_guard_mirror(cause))
+                        .map(|cause| Self::convert(cause, platform, seen))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+
+                Self {
+                    kind: DerivationKind::Derived,
+                    message: derived_message(&terms),
+                    terms,
+                    shared_id: derived.shared_id,
+                    causes,
+                }
+            }
+        }
+    }
+
+    fn external(
+        external: &External<PackageName, Ranges<ComposerVersion>, String>,
+        platform: &AHashMap<String, ComposerVersion>,
+    ) -> Self {
+        let installed = |package: &PackageName| {
+            platform
+                .get(package.as_str())
+                .map_or_else(String::new, |version| {
+                    format!(" (the platform provides {version})")
+                })
+        };
+        let (kind, message, terms) = match external {
+            External::NotRoot(package, version) => (
+                DerivationKind::RootRequires,
+                format!("{} {version} is the root project", display_name(package)),
+                Vec::new(),
+            ),
+            External::NoVersions(package, range) => {
+                let message = if package.is_platform() {
+                    format!(
+                        "the platform does not provide {}{}",
+                        describe(package, range),
+                        installed(package)
+                    )
+                } else if *range == Ranges::full() {
+                    format!("no versions of {package} are available")
+                } else {
+                    format!("no versions of {package} match {range}")
+                };
+                (
+                    DerivationKind::NoVersions,
+                    message,
+                    vec![term_for(package, range, false)],
+                )
+            }
+            External::FromDependencyOf(package, range, dependency, dependency_range) => {
+                let target = describe(dependency, dependency_range);
+                if package.as_str() == ROOT {
+                    (
+                        DerivationKind::RootRequires,
+                        format!("root requires {target}{}", installed(dependency)),
+                        vec![term_for(dependency, dependency_range, true)],
+                    )
+                } else if guarded_package(dependency.as_str()).is_some() {
+                    (
+                        DerivationKind::Conflicts,
+                        format!(
+                            "{} conflicts with {}",
+                            describe(package, range),
+                            describe(dependency, &dependency_range.complement())
+                        ),
+                        vec![
+                            term_for(package, range, false),
+                            term_for(dependency, dependency_range, true),
+                        ],
+                    )
+                } else {
+                    (
+                        DerivationKind::Requires,
+                        format!(
+                            "{} requires {target}{}",
+                            describe(package, range),
+                            installed(dependency)
+                        ),
+                        vec![
+                            term_for(package, range, false),
+                            term_for(dependency, dependency_range, true),
+                        ],
+                    )
+                }
+            }
+            External::Custom(package, range, reason) => (
+                DerivationKind::Unavailable,
+                format!("{} cannot be used {reason}", describe(package, range)),
+                vec![term_for(package, range, false)],
+            ),
+        };
+        Self {
+            kind,
+            message,
+            terms,
+            shared_id: None,
+            causes: Vec::new(),
+        }
+    }
+
+    /// Whether this node was referenced before and its causes omitted.
+    #[must_use]
+    pub fn is_repeated(&self) -> bool {
+        self.shared_id.is_some() && self.causes.is_empty()
+    }
+
+    /// Facts (leaves) the derivation rests on, in order of first appearance.
+    #[must_use]
+    pub fn facts(&self) -> Vec<&Self> {
+        let mut facts = Vec::new();
+        self.collect_facts(&mut facts);
+        facts
+    }
+
+    fn collect_facts<'a>(&'a self, facts: &mut Vec<&'a Self>) {
+        if self.kind == DerivationKind::Derived {
+            for cause in &self.causes {
+                cause.collect_facts(facts);
+            }
+        } else if !facts.iter().any(|fact| fact.message == self.message) {
+            facts.push(self);
+        }
+    }
+}
+
+fn display_name(package: &PackageName) -> &str {
+    if package.as_str() == ROOT {
+        "root"
+    } else {
+        guarded_package(package.as_str()).unwrap_or_else(|| package.as_str())
+    }
+}
+
+fn describe(package: &PackageName, range: &Ranges<ComposerVersion>) -> String {
+    format!(
+        "{} {}",
+        display_name(package),
+        display_range(package, range)
+    )
+}
+
+fn term_for(
+    package: &PackageName,
+    range: &Ranges<ComposerVersion>,
+    negated: bool,
+) -> DerivationTerm {
+    DerivationTerm {
+        package: display_name(package).to_string(),
+        constraint: display_range(package, range).to_string(),
+        negated,
+    }
+}
+
+/// `range` without the "not installed" version of conflict guards.
+fn display_range(
+    package: &PackageName,
+    range: &Ranges<ComposerVersion>,
+) -> Ranges<ComposerVersion> {
+    if guarded_package(package.as_str()).is_none() {
+        return range.clone();
+    }
+    // The marker sorts below every version, so it only ever starts a segment
+    let absent = not_installed();
+    range
+        .iter()
+        .filter(|segment| {
+            *segment
+                != (
+                    &Bound::Included(absent.clone()),
+                    &Bound::Included(absent.clone()),
+                )
+        })
+        .map(|(start, end)| {
+            let start = match start {
+                Bound::Included(v) | Bound::Excluded(v) if *v == absent => Bound::Unbounded,
+                start => start.clone(),
+            };
+            Ranges::from_range_bounds((start, end.clone()))
+        })
+        .fold(Ranges::empty(), |shown, segment| shown.union(&segment))
+}
+
+/// Whether `tree` is the fact that a package version depends on the same
+/// version of its conflict guard.
+fn is_guard_mirror(tree: &DerivationTree<PackageName, Ranges<ComposerVersion>, String>) -> bool {
+    matches!(
+        tree,
+        DerivationTree::External(External::FromDependencyOf(package, _, guard, _))
+            if guarded_package(guard.as_str()) == Some(package.as_str())
+    )
+}
+
+/// Statement for a derived incompatibility over `terms`.
+fn derived_message(terms: &[DerivationTerm]) -> String {
+    let positive: Vec<_> = terms.iter().filter(|t| !t.negated).collect();
+    let negative: Vec<_> = terms.iter().filter(|t| t.negated).collect();
+    let list = |terms: &[&DerivationTerm]| {
+        let mut out = String::new();
+        for (i, term) in terms.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            let _ = write!(out, "{} {}", term.package, term.constraint);
+        }
+        out
+    };
+
+    match (positive.as_slice(), negative.as_slice()) {
+        ([], []) => "the root requirements cannot be satisfied".to_string(),
+        ([], negative) => format!("root requires {}", list(negative)),
+        (positive, []) => format!("{} cannot be installed", list(positive)),
+        (positive, negative) => format!("{} requires {}", list(positive), list(negative)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pubgrub::Derived;
+    use std::sync::Arc;
+
+    fn name(s: &str) -> PackageName {
+        PackageName::parse(s)
+            .or_else(|| PackageName::platform(s))
+            .unwrap()
+    }
+
+    fn root() -> PackageName {
+        PackageName::new("__root__", "__root__")
+    }
+
+    fn range(low: u64, high: u64) -> Ranges<ComposerVersion> {
+        Ranges::between(
+            ComposerVersion::new(low, 0, 0),
+            ComposerVersion::new(high, 0, 0),
+        )
+    }
+
+    #[test]
+    fn converts_root_requirement_and_transitive_requirer() {
+        let root_requires: DerivationTree<_, _, String> =
+            DerivationTree::External(External::FromDependencyOf(
+                root(),
+                Ranges::singleton(ComposerVersion::new(1, 0, 0)),
+                name("acme/a"),
+                range(1, 2),
+            ));
+        let a_requires = DerivationTree::External(External::FromDependencyOf(
+            name("acme/a"),
+            range(1, 2),
+            name("php"),
+            range(9, 10),
+        ));
+        let mut terms = pubgrub::Map::default();
+        terms.insert(root(), Term::Positive(Ranges::full()));
+        terms.insert(name("php"), Term::Negative(range(9, 10)));
+        let tree = DerivationTree::Derived(Derived {
+            terms,
+            shared_id: None,
+            cause1: Arc::new(root_requires),
+            cause2: Arc::new(a_requires),
+        });
+
+        let platform = AHashMap::from([("php".to_string(), ComposerVersion::new(8, 1, 0))]);
+        let derivation = Derivation::from_tree(&tree, &platform);
+        assert_eq!(derivation.kind, DerivationKind::Derived);
+        assert_eq!(derivation.message, "root requires php >=9.0.0, <10.0.0");
+        assert_eq!(derivation.causes[0].kind, DerivationKind::RootRequires);
+        assert_eq!(
+            derivation.causes[0].message,
+            "root requires acme/a >=1.0.0, <2.0.0"
+        );
+        assert_eq!(
+            derivation.causes[1].message,
+            "acme/a >=1.0.0, <2.0.0 requires php >=9.0.0, <10.0.0 (the platform provides 8.1.0)"
+        );
+        assert_eq!(derivation.facts().len(), 2);
+    }
+
+    #[test]
+    fn repeated_shared_incompatibilities_omit_their_causes() {
+        let leaf: Arc<DerivationTree<_, _, String>> = Arc::new(DerivationTree::External(
+            External::NoVersions(name("acme/a"), range(3, 4)),
+        ));
+        let shared = Arc::new(DerivationTree::Derived(Derived {
+            terms: pubgrub::Map::default(),
+            shared_id: Some(7),
+            cause1: Arc::clone(&leaf),
+            cause2: leaf,
+        }));
+        let tree = DerivationTree::Derived(Derived {
+            terms: pubgrub::Map::default(),
+            shared_id: None,
+            cause1: Arc::clone(&shared),
+            cause2: shared,
+        });
+
+        let derivation = Derivation::from_tree(&tree, &AHashMap::new());
+        assert!(!derivation.causes[0].is_repeated());
+        assert!(derivation.causes[1].is_repeated());
+        assert_eq!(
+            derivation.causes[0].causes[0].message,
+            "no versions of acme/a match >=3.0.0, <4.0.0"
+        );
+        assert_eq!(derivation.facts().len(), 1);
+    }
+}
@@ -3,6 +3,7 @@
 //! This module defines the interface for fetching package metadata from
 //! repositories like Packagist.
 
+use crate::composer::AbandonedField;
 use std::pin::Pin;
 use std::sync::Arc;
 
@@ -18,6 +19,34 @@ pub trait PackageFetcher: Send + Sync + 'static {
         &self,
         name: String,
     ) -> Pin<Box<dyn std::future::Future<Output = Option<FetchedPackage>> + Send + '_>>;
+
+    /// Validator (`ETag` or `Last-Modified`) of the metadata document last
+    /// fetched for `name`.
+    ///
+    /// The resolution cache compares validators to tell whether metadata
+    /// changed. Fetchers that cannot tell return `None`, which keeps cached
+    /// resolutions depending on the package from being reused.
+    fn validator(&self, _name: &str) -> Option<String> {
+        None
+    }
+
+    /// Whether the metadata document for `name` still has `validator`.
+    ///
+    /// The resolution cache revalidates the documents a cached resolution
+    /// was computed from before fetching any of them, so remote fetchers
+    /// should answer with a conditional request and serve documents found
+    /// unchanged from their local copy on the next [`fetch`](Self::fetch).
+    /// The default compares against [`validator`](Self::validator).
+    fn revalidate(
+        &self,
+        name: String,
+        validator: String,
+    ) -> Pin<Box<dyn std::future::Future<Output = bool> + Send + '_>> {
+        let unchanged = self
+            .validator(&name)
+            .is_some_and(|current| current == validator);
+        Box::pin(std::future::ready(unchanged))
+    }
 }
 
 // Implement for Arc<T> where T: PackageFetcher
@@ -28,6 +57,18 @@ impl<T: PackageFetcher> PackageFetcher for Arc<T> {
     ) -> Pin<Box<dyn std::future::Future<Output = Option<FetchedPackage>> + Send + '_>> {
         (**self).fetch(name)
     }
+
+    fn validator(&self, name: &str) -> Option<String> {
+        (**self).validator(name)
+    }
+
+    fn revalidate(
+        &self,
+        name: String,
+        validator: String,
+    ) -> Pin<Box<dyn std::future::Future<Output = bool> + Send + '_>> {
+        (**self).revalidate(name, validator)
+    }
 }
 
 /// Package data returned by the fetcher.
@@ -52,6 +93,8 @@ pub struct FetchedVersion {
     pub replace: Vec<(String, String)>,
     /// Virtual packages this provides.
     pub provide: Vec<(String, String)>,
+    /// Packages this conflicts with.
+    pub conflict: Vec<(String, String)>,
     /// Suggested packages.
     pub suggest: Vec<(String, String)>,
     /// Distribution URL.
@@ -60,6 +103,8 @@ pub struct FetchedVersion {
     pub dist_type: Option<String>,
     /// Distribution checksum.
     pub dist_shasum: Option<String>,
+    /// Detached signature URL of the distribution.
+    pub dist_signature: Option<String>,
     /// Source repository URL.
     pub source_url: Option<String>,
     /// Source type (git, hg).
@@ -94,6 +139,8 @@ pub struct FetchedVersion {
     pub notification_url: Option<String>,
     /// Binary files.
     pub bin: Option<Vec<String>>,
+    /// Abandoned notice.
+    pub abandoned: Option<AbandonedField>,
 }
 
 // ============================================================================
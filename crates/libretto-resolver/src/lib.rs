@@ -13,6 +13,10 @@
 //! - **Full Composer compatibility**: Supports all version constraint formats
 //!   including exact, range, wildcard, tilde, caret, and OR constraints
 //! - **Stability flags**: Supports `@dev`, `@alpha`, `@beta`, `@RC`, `@stable`
+//! - **Resolution cache**: Reuses the previous resolution while no metadata
+//!   it depends on has changed
+//! - **Version blocking**: Skips versions with security advisories or abandoned
+//!   packages, naming the reason when nothing else fits
 //! - **HTTP/2 multiplexing**: Efficient network utilization
 //!
 //! # Example
@@ -50,8 +54,10 @@
 //! The resolver is organized into several modules:
 //!
 //! - [`resolver`]: Main resolver implementation
+//! - [`cache`]: Persistent resolution cache
 //! - [`fetcher`]: Package fetching trait
 //! - [`types`]: Resolution result types
+//! - [`derivation`]: Structured explanation of resolution failures
 //! - [`package`]: Package and dependency types
 //! - [`version`]: Version parsing and constraints
 //! - [`provider`]: `PubGrub` provider configuration
@@ -64,7 +70,10 @@
 #![allow(clippy::module_name_repetitions)]
 
 // Core modules
+pub mod blocklist;
+pub mod cache;
 pub mod composer;
+pub mod derivation;
 pub mod fetcher;
 pub mod index;
 pub mod package;
@@ -74,7 +83,10 @@ pub mod types;
 pub mod version;
 
 // Re-export main types
-pub use composer::{ComposerManifest, ManifestError};
+pub use blocklist::{VersionBlocklist, VersionFilter};
+pub use cache::ResolutionCache;
+pub use composer::{AbandonedField, ComposerManifest, ManifestError};
+pub use derivation::{Derivation, DerivationKind, DerivationTerm};
 pub use fetcher::{FetchedPackage, FetchedVersion, PackageFetcher};
 pub use index::{CacheSummary, IndexConfig, MemorySource, PackageIndex, PackageSource};
 pub use package::{Dependency, PackageEntry, PackageName, PackageNameError, PackageVersion};
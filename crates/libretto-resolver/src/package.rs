@@ -5,6 +5,7 @@
 //! - `PackageId`: A package with a specific version
 //! - `Dependency`: A dependency requirement with constraint
 
+use crate::composer::AbandonedField;
 use crate::version::{ComposerConstraint, ComposerVersion, Stability};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use smallvec::SmallVec;
@@ -73,6 +74,21 @@ impl PackageName {
         })
     }
 
+    /// Name of a platform package (`php`, `ext-*`, `lib-*`), which has no vendor.
+    ///
+    /// Returns `None` if the string is not a platform package name.
+    #[must_use]
+    pub fn platform(s: &str) -> Option<Self> {
+        let s = s.trim().to_ascii_lowercase();
+        if !libretto_core::is_platform_package_name(&s) || s.contains('/') {
+            return None;
+        }
+        Some(Self {
+            full: Arc::from(s),
+            separator_idx: 0,
+        })
+    }
+
     /// Get the vendor part.
     #[must_use]
     #[inline]
@@ -84,7 +100,8 @@ impl PackageName {
     #[must_use]
     #[inline]
     pub fn name(&self) -> &str {
-        &self.full[self.separator_idx + 1..]
+        // Platform names have no separator
+        self.full[self.separator_idx..].trim_start_matches('/')
     }
 
     /// Get the full name.
@@ -216,6 +233,8 @@ pub struct PackageVersion {
     pub dist_type: Option<Arc<str>>,
     /// Distribution checksum.
     pub dist_shasum: Option<Arc<str>>,
+    /// Detached signature URL of the distribution.
+    pub dist_signature: Option<Arc<str>>,
     /// Source URL (git, etc.).
     pub source_url: Option<Arc<str>>,
     /// Source type.
@@ -251,6 +270,8 @@ pub struct PackageVersion {
     pub notification_url: Option<Arc<str>>,
     /// Binary files.
     pub bin: Option<Vec<String>>,
+    /// Abandoned notice.
+    pub abandoned: Option<AbandonedField>,
 }
 
 impl PackageVersion {
@@ -271,6 +292,7 @@ impl PackageVersion {
             dist_url: None,
             dist_type: None,
             dist_shasum: None,
+            dist_signature: None,
             source_url: None,
             source_type: None,
             source_reference: None,
@@ -288,6 +310,7 @@ impl PackageVersion {
             funding: None,
             notification_url: None,
             bin: None,
+            abandoned: None,
         }
     }
 
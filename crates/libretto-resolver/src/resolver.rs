@@ -1,14 +1,15 @@
 //! High-performance dependency resolver using `PubGrub` algorithm.
 //!
-//! This is the main resolver implementation for Libretto. It uses streaming
-//! parallel fetching combined with the `PubGrub` algorithm for efficient and
+//! This is the main resolver implementation for Libretto. It interleaves
+//! on-demand parallel fetching with the `PubGrub` algorithm for efficient and
 //! correct dependency resolution.
 //!
 //! # Key Features
 //!
-//! - **Streaming fetch**: Process packages as they arrive, don't wait for batches
-//! - **Parallel prefetch**: Start fetching dependencies before parent completes
+//! - **On-demand fetch**: Only fetch metadata for packages the solver reaches
+//! - **Speculative prefetch**: Fetch the likely candidate's requirements ahead of the solver
 //! - **Request deduplication**: Never fetch the same package twice
+//! - **Resolution cache**: Reuse or warm-start from the previous resolution
 //! - **HTTP/2 multiplexing**: Reuse connections aggressively
 //! - **`PubGrub` solver**: Battle-tested algorithm with conflict-driven learning
 //!
@@ -24,25 +25,27 @@
 //! let resolution = resolver.resolve(&root_deps, &dev_deps).await?;
 //! ```
 
+use crate::blocklist::{VersionBlocklist, VersionFilter};
+use crate::cache::{CachedResolution, ResolutionCache};
+use crate::composer::AbandonedField;
+use crate::derivation::Derivation;
 use crate::fetcher::{FetchedPackage, PackageFetcher};
 use crate::package::{Dependency, PackageEntry, PackageName, PackageVersion};
 use crate::provider::ResolutionMode;
 use crate::types::{Resolution, ResolveError, ResolvedPackage};
 use crate::version::{ComposerConstraint, ComposerVersion, Stability};
 use ahash::{AHashMap, AHashSet};
-use dashmap::DashSet;
 use futures::stream::{FuturesUnordered, StreamExt};
 use petgraph::Direction;
 use petgraph::graph::{DiGraph, NodeIndex};
 use pubgrub::{
     DefaultStringReporter, Dependencies, DependencyConstraints, DependencyProvider,
-    PackageResolutionStatistics, PubGrubError, Reporter, resolve,
+    PackageResolutionStatistics, PubGrubError, Reporter, SelectedDependencies, resolve,
 };
-use std::convert::Infallible;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
-use tracing::info;
+use tracing::{debug, info};
 use version_ranges::Ranges;
 
 /// Resolver statistics for monitoring and debugging.
@@ -56,6 +59,14 @@ pub struct ResolverStats {
     pub fetch_time_ms: AtomicU64,
     /// Time spent in `PubGrub` solver (ms).
     pub solver_time_ms: AtomicU64,
+    /// Packages fetched speculatively, ahead of the solver asking for them.
+    pub packages_prefetched: AtomicU64,
+    /// Packages required by some fetched version that never had to be fetched.
+    pub packages_avoided: AtomicU64,
+    /// Solver runs; every run after the first follows an on-demand fetch.
+    pub solver_rounds: AtomicU64,
+    /// Resolutions reused from the resolution cache without solving.
+    pub cache_hits: AtomicU64,
     /// Total HTTP requests made.
     pub requests_total: AtomicU64,
     /// Failed HTTP requests.
@@ -75,6 +86,8 @@ pub struct ResolverConfig {
     pub min_stability: Stability,
     /// Whether to include dev dependencies.
     pub include_dev: bool,
+    /// Treat abandoned packages as unavailable.
+    pub block_abandoned: bool,
 }
 
 impl Default for ResolverConfig {
@@ -85,18 +98,22 @@ impl Default for ResolverConfig {
             mode: ResolutionMode::PreferStable,
             min_stability: Stability::Stable,
             include_dev: true,
+            block_abandoned: false,
         }
     }
 }
 
 /// The main dependency resolver.
 ///
-/// Uses streaming parallel fetching combined with `PubGrub` for fast,
+/// Uses on-demand parallel fetching combined with `PubGrub` for fast,
 /// correct dependency resolution.
 pub struct Resolver<F: PackageFetcher> {
     fetcher: Arc<F>,
     config: ResolverConfig,
     stats: Arc<ResolverStats>,
+    filter: Option<Arc<dyn VersionFilter>>,
+    cache: Option<ResolutionCache>,
+    platform: AHashMap<String, ComposerVersion>,
 }
 
 impl<F: PackageFetcher> std::fmt::Debug for Resolver<F> {
@@ -115,9 +132,42 @@ impl<F: PackageFetcher> Resolver<F> {
             fetcher,
             config,
             stats: Arc::new(ResolverStats::default()),
+            filter: None,
+            cache: None,
+            platform: AHashMap::new(),
         }
     }
 
+    /// Keep versions blocked by `filter` out of the resolution.
+    #[must_use]
+    pub fn with_version_filter(mut self, filter: Arc<dyn VersionFilter>) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Persist resolutions in `cache` and reuse them while their inputs and
+    /// metadata are unchanged.
+    #[must_use]
+    pub fn with_cache(mut self, cache: ResolutionCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Resolve platform packages (`php`, `ext-*`, ...) against these
+    /// installed versions. Requirements on platform packages not listed
+    /// here are ignored.
+    #[must_use]
+    pub fn with_platform(
+        mut self,
+        packages: impl IntoIterator<Item = (String, ComposerVersion)>,
+    ) -> Self {
+        self.platform = packages
+            .into_iter()
+            .map(|(name, version)| (name.to_ascii_lowercase(), version))
+            .collect();
+        self
+    }
+
     /// Get resolver statistics.
     #[must_use]
     pub fn stats(&self) -> &ResolverStats {
@@ -129,6 +179,17 @@ impl<F: PackageFetcher> Resolver<F> {
     /// Takes root dependencies and dev dependencies, returns a complete
     /// resolution with all transitive dependencies in topological order.
     ///
+    /// Metadata is fetched on demand: the solver runs on what is loaded and,
+    /// when it reaches a package that is not, stops so the missing packages
+    /// can be fetched before it runs again. Packages only required by
+    /// versions the solver never considers are never fetched.
+    ///
+    /// With a [`ResolutionCache`], the metadata a previous resolution of the
+    /// same inputs consumed is revalidated first. When none of it changed,
+    /// only the selected packages are loaded and the resolution is returned
+    /// without solving. Otherwise its packages are fetched in a single batch
+    /// up front and its selected versions guide the speculative prefetch.
+    ///
     /// # Errors
     ///
     /// Returns `ResolveError::Conflict` if dependencies cannot be satisfied,
@@ -141,75 +202,128 @@ impl<F: PackageFetcher> Resolver<F> {
     ) -> Result<Resolution, ResolveError> {
         let start = Instant::now();
 
-        // Phase 1: Stream-fetch all reachable packages
-        let fetch_start = Instant::now();
-        let packages = self.fetch_all_packages(root_deps, dev_deps).await?;
-        self.stats.fetch_time_ms.store(
-            u64::try_from(fetch_start.elapsed().as_millis()).unwrap_or(u64::MAX),
-            Ordering::Relaxed,
-        );
+        let all_deps: Vec<_> = if self.config.include_dev {
+            root_deps.iter().chain(dev_deps.iter()).cloned().collect()
+        } else {
+            root_deps.to_vec()
+        };
 
-        info!(
-            packages = packages.len(),
-            fetch_ms = fetch_start.elapsed().as_millis(),
-            requests = self.stats.requests_total.load(Ordering::Relaxed),
-            failed = self.stats.requests_failed.load(Ordering::Relaxed),
-            "fetch complete"
-        );
+        // Composer-style per-package stability flags from explicit root constraints
+        // (e.g., dev-master, ^1.0@beta), overriding global minimum-stability.
+        let mut package_min_stability: AHashMap<String, Stability> = AHashMap::new();
+        for dep in &all_deps {
+            let dep_min_stability = dep.constraint.min_stability();
+            if dep_min_stability < self.config.min_stability {
+                package_min_stability
+                    .entry(dep.name.as_str().to_string())
+                    .and_modify(|current| *current = (*current).min(dep_min_stability))
+                    .or_insert(dep_min_stability);
+            }
+        }
 
-        // Phase 2: Run PubGrub solver
-        let solver_start = Instant::now();
-        let resolution = self.solve(root_deps, dev_deps, packages)?;
-        self.stats.solver_time_ms.store(
-            u64::try_from(solver_start.elapsed().as_millis()).unwrap_or(u64::MAX),
-            Ordering::Relaxed,
+        let root_dep_ranges: Vec<_> = all_deps
+            .iter()
+            .filter(|d| {
+                !is_platform_package(d.name.as_str()) || self.platform.contains_key(d.name.as_str())
+            })
+            .map(|d| (d.name.clone(), d.constraint.ranges().clone()))
+            .collect();
+
+        let mut provider = PubGrubProvider::new(
+            self.config.mode,
+            self.config.min_stability,
+            package_min_stability,
         );
+        provider.platform.clone_from(&self.platform);
+        provider.set_root_deps(root_dep_ranges.clone());
+
+        let cache_key = self.cache.as_ref().map(|cache| {
+            cache.key(
+                root_deps,
+                dev_deps,
+                &self.config,
+                self.filter.is_some(),
+                &self.platform,
+            )
+        });
+        let previous = self
+            .cache
+            .as_ref()
+            .zip(cache_key.as_ref())
+            .and_then(|(cache, key)| cache.load(key));
+
+        let mut wanted: Vec<_> = root_dep_ranges
+            .into_iter()
+            .filter(|(name, _)| !name.is_platform())
+            .map(|(name, range)| (name.as_str().to_string(), range))
+            .collect();
+        let reusable = match &previous {
+            Some(previous) => self.warm_start(previous, &mut provider, &mut wanted).await,
+            None => false,
+        };
+
+        info!(initial = wanted.len(), "fetch starting");
+        self.fetch_metadata(&mut provider, wanted).await;
+
+        if let Some(solution) = previous
+            .as_ref()
+            .filter(|_| reusable)
+            .and_then(|previous| Self::reusable_solution(previous, &provider))
+        {
+            self.stats.cache_hits.fetch_add(1, Ordering::Relaxed);
+            let resolution = Self::build_resolution(solution, &provider, dev_deps);
+            info!(
+                total_ms = start.elapsed().as_millis(),
+                packages = resolution.packages.len(),
+                "resolution reused from cache"
+            );
+            return Ok(resolution);
+        }
+
+        let resolution = self.solve(&mut provider, dev_deps).await?;
+
+        if let (Some(cache), Some(key)) = (&self.cache, cache_key) {
+            cache.store(key, &self.cache_entry(&resolution, &provider));
+        }
+
+        self.stats
+            .packages_avoided
+            .store(provider.unfetched_references() as u64, Ordering::Relaxed);
 
         info!(
             total_ms = start.elapsed().as_millis(),
             packages = resolution.packages.len(),
+            fetched = self.stats.packages_fetched.load(Ordering::Relaxed),
+            avoided = self.stats.packages_avoided.load(Ordering::Relaxed),
+            rounds = self.stats.solver_rounds.load(Ordering::Relaxed),
             "resolution complete"
         );
 
         Ok(resolution)
     }
 
-    /// Fetch all reachable packages using streaming parallel requests.
-    async fn fetch_all_packages(
+    /// Fetch metadata for `wanted` packages into the provider.
+    ///
+    /// Each fetched package speculatively queues the requirements of the
+    /// version the solver is most likely to pick within the requested range,
+    /// so the next solver run rarely stops for them. Requirements of other
+    /// versions are left until the solver actually considers those versions.
+    async fn fetch_metadata(
         &self,
-        root_deps: &[Dependency],
-        dev_deps: &[Dependency],
-    ) -> Result<AHashMap<String, PackageEntry>, ResolveError> {
-        let packages: Arc<dashmap::DashMap<String, PackageEntry>> =
-            Arc::new(dashmap::DashMap::new());
-        let seen: Arc<DashSet<String>> = Arc::new(DashSet::new());
-
-        // Collect initial dependencies
-        let all_deps: Vec<_> = root_deps
-            .iter()
-            .chain(if self.config.include_dev {
-                dev_deps
-            } else {
-                &[]
-            })
-            .collect();
-
-        let mut pending: Vec<String> = Vec::new();
-        for dep in &all_deps {
-            let name = dep.name.as_str().to_string();
-            if !is_platform_package(&name) && seen.insert(name.clone()) {
-                pending.push(name);
-            }
-        }
-
-        info!(initial = pending.len(), "fetch starting");
-
+        provider: &mut PubGrubProvider,
+        wanted: Vec<(String, Ranges<ComposerVersion>)>,
+    ) {
+        let fetch_start = Instant::now();
+        let mut queued: AHashSet<String> = wanted.iter().map(|(name, _)| name.clone()).collect();
+        let mut pending = wanted;
+        let mut fetched_names = Vec::new();
         let mut in_flight = FuturesUnordered::new();
 
         loop {
             // Launch new requests up to max_concurrent
-            while in_flight.len() < self.config.max_concurrent && !pending.is_empty() {
-                let name = pending.pop().unwrap();
+            while in_flight.len() < self.config.max_concurrent
+                && let Some((name, range)) = pending.pop()
+            {
                 let fetcher = Arc::clone(&self.fetcher);
                 let timeout = self.config.request_timeout;
                 let stats = Arc::clone(&self.stats);
@@ -219,44 +333,51 @@ impl<F: PackageFetcher> Resolver<F> {
                     if let Ok(result) =
                         tokio::time::timeout(timeout, fetcher.fetch(name.clone())).await
                     {
-                        (name, result)
+                        (name, range, result)
                     } else {
                         stats.requests_failed.fetch_add(1, Ordering::Relaxed);
-                        (name, None)
+                        (name, range, None)
                     }
                 });
             }
 
             // Done when nothing in flight and nothing pending
-            if in_flight.is_empty() {
+            let Some((name, range, result)) = in_flight.next().await else {
                 break;
-            }
+            };
 
-            // Process next completed request
-            if let Some((name, result)) = in_flight.next().await
-                && let Some(fetched) = result
-            {
+            if result.is_some() {
                 self.stats.packages_fetched.fetch_add(1, Ordering::Relaxed);
-
-                if let Some(entry) = convert_fetched_package(&fetched, self.config.min_stability) {
-                    self.stats
-                        .versions_total
-                        .fetch_add(entry.versions.len() as u64, Ordering::Relaxed);
-
-                    // Queue newly discovered dependencies
-                    for version in &entry.versions {
-                        for dep in &version.dependencies {
-                            let dep_name = dep.name.as_str().to_string();
-                            if !is_platform_package(&dep_name) && seen.insert(dep_name.clone()) {
-                                pending.push(dep_name);
-                            }
-                        }
+            }
+            let Some(entry) = result
+                .and_then(|fetched| convert_fetched_package(&fetched, self.config.min_stability))
+            else {
+                provider.unavailable.insert(name);
+                continue;
+            };
+            self.stats
+                .versions_total
+                .fetch_add(entry.versions.len() as u64, Ordering::Relaxed);
+
+            // Prefetch what the likely candidate requires
+            if let Some(likely) = provider.likely_version(&entry, &range) {
+                for dep in &likely.dependencies {
+                    let dep_name = dep.name.as_str();
+                    if !is_platform_package(dep_name)
+                        && !provider.is_loaded(dep_name)
+                        && queued.insert(dep_name.to_string())
+                    {
+                        self.stats
+                            .packages_prefetched
+                            .fetch_add(1, Ordering::Relaxed);
+                        pending.push((dep_name.to_string(), dep.constraint.ranges().clone()));
                     }
-
-                    packages.insert(name, entry);
                 }
             }
 
+            fetched_names.push(name.clone());
+            provider.packages.insert(name, entry);
+
             // Log progress periodically
             let fetched = self.stats.packages_fetched.load(Ordering::Relaxed);
             if fetched.is_multiple_of(50) && fetched > 0 {
@@ -269,20 +390,175 @@ impl<F: PackageFetcher> Resolver<F> {
             }
         }
 
-        info!(
-            total = packages.len(),
-            requests = self.stats.requests_total.load(Ordering::Relaxed),
-            "fetch complete"
+        let blocklist = self.blocklist(fetched_names, &provider.packages).await;
+        if !blocklist.is_empty() {
+            info!(packages = blocklist.len(), "versions blocked");
+        }
+        provider.blocklist.merge(blocklist);
+
+        self.stats.fetch_time_ms.fetch_add(
+            u64::try_from(fetch_start.elapsed().as_millis()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Queue the packages of a previous resolution and let its selected
+    /// versions guide the speculative prefetch.
+    ///
+    /// A reusable solution only needs its own packages loaded; otherwise
+    /// everything the previous solve consumed is fetched in one batch.
+    /// Returns whether the solution is reusable.
+    async fn warm_start(
+        &self,
+        previous: &CachedResolution,
+        provider: &mut PubGrubProvider,
+        wanted: &mut Vec<(String, Ranges<ComposerVersion>)>,
+    ) -> bool {
+        let reusable = self.metadata_unchanged(previous).await;
+        let consumed: Vec<&String> = if reusable {
+            previous.packages.keys().collect()
+        } else {
+            previous.metadata.keys().collect()
+        };
+        let roots: AHashSet<String> = wanted.iter().map(|(name, _)| name.clone()).collect();
+        wanted.extend(
+            consumed
+                .into_iter()
+                .chain(&previous.unavailable)
+                .filter(|name| !roots.contains(*name))
+                .map(|name| (name.clone(), Ranges::full())),
         );
+        provider.previous = previous
+            .packages
+            .iter()
+            .map(|(n, v)| (n.clone(), v.clone()))
+            .collect();
+        reusable
+    }
+
+    /// Whether every metadata document `previous` was computed from still
+    /// has the validator it was stored with.
+    ///
+    /// Validators are checked with conditional requests, so unchanged
+    /// documents are not downloaded again.
+    async fn metadata_unchanged(&self, previous: &CachedResolution) -> bool {
+        let timeout = self.config.request_timeout;
+        let changed = futures::stream::iter(&previous.metadata)
+            .map(|(name, validator)| async move {
+                let Some(validator) = validator else {
+                    return Some(name);
+                };
+                self.stats.requests_total.fetch_add(1, Ordering::Relaxed);
+                let check = self.fetcher.revalidate(name.clone(), validator.clone());
+                match tokio::time::timeout(timeout, check).await {
+                    Ok(true) => None,
+                    Ok(false) => Some(name),
+                    Err(_) => {
+                        self.stats.requests_failed.fetch_add(1, Ordering::Relaxed);
+                        Some(name)
+                    }
+                }
+            })
+            .buffer_unordered(self.config.max_concurrent)
+            .filter_map(std::future::ready)
+            .collect::<Vec<_>>()
+            .await;
+        for name in &changed {
+            debug!(package = %name, "cached resolution is stale");
+        }
+        changed.is_empty()
+    }
 
-        // Convert DashMap to HashMap
-        Ok(packages
+    /// The previous solution, if its packages are loaded, the packages it
+    /// found missing still are, and none of its versions has been blocked
+    /// since.
+    fn reusable_solution(
+        previous: &CachedResolution,
+        provider: &PubGrubProvider,
+    ) -> Option<Vec<(PackageName, ComposerVersion)>> {
+        if !previous
+            .unavailable
+            .iter()
+            .all(|name| provider.unavailable.contains(name))
+        {
+            return None;
+        }
+
+        previous
+            .packages
             .iter()
-            .map(|r| (r.key().clone(), r.value().clone()))
-            .collect())
+            .map(|(name, version)| {
+                let version = provider
+                    .packages
+                    .get(name)?
+                    .versions
+                    .iter()
+                    .find(|v| v.version.to_string() == *version)?;
+                if provider.blocklist.reason(name, &version.version).is_some() {
+                    return None;
+                }
+                Some((version.name.clone(), version.version.clone()))
+            })
+            .collect()
+    }
+
+    /// What the resolution cache records for `resolution`.
+    fn cache_entry(&self, resolution: &Resolution, provider: &PubGrubProvider) -> CachedResolution {
+        let mut unavailable: Vec<_> = provider.unavailable.iter().cloned().collect();
+        unavailable.sort();
+        CachedResolution {
+            packages: resolution
+                .packages
+                .iter()
+                .map(|p| (p.name.as_str().to_string(), p.version.to_string()))
+                .collect(),
+            metadata: provider
+                .packages
+                .keys()
+                .map(|name| (name.clone(), self.fetcher.validator(name)))
+                .collect(),
+            unavailable,
+        }
+    }
+
+    /// Collect blocked versions for newly fetched packages.
+    ///
+    /// Composer marks a whole package abandoned, so any abandoned version
+    /// blocks all of them.
+    async fn blocklist(
+        &self,
+        names: Vec<String>,
+        packages: &AHashMap<String, PackageEntry>,
+    ) -> VersionBlocklist {
+        if names.is_empty() {
+            return VersionBlocklist::new();
+        }
+
+        let mut blocklist = match &self.filter {
+            Some(filter) => filter.blocklist(names.clone()).await,
+            None => VersionBlocklist::new(),
+        };
+
+        if self.config.block_abandoned {
+            for name in &names {
+                let Some(abandoned) = packages
+                    .get(name)
+                    .and_then(|entry| entry.versions.iter().find_map(|v| v.abandoned.as_ref()))
+                else {
+                    continue;
+                };
+                let reason = abandoned.replacement().map_or_else(
+                    || "because it is abandoned".to_string(),
+                    |replacement| format!("because it is abandoned, use {replacement} instead"),
+                );
+                blocklist.block(name, ComposerConstraint::any(), reason);
+            }
+        }
+
+        blocklist
     }
 
-    /// Run `PubGrub` solver on fetched packages.
+    /// Run the `PubGrub` solver, fetching metadata on demand.
     ///
     /// Uses a two-pass approach to handle Composer's `replace` semantics:
     ///
@@ -294,66 +570,17 @@ impl<F: PackageFetcher> Resolver<F> {
     /// dependency graph. This mirrors Composer's behavior where replaced
     /// packages are never independently selected by the solver.
     ///
-    /// Pass 2 is near-instant since all metadata is already in memory.
-    fn solve(
+    /// Pass 2 is near-instant since nearly all metadata is already in memory.
+    ///
+    /// After each pass, `conflict` declarations that the solution violates
+    /// are enforced and the pass is repeated (see [`Self::solve_with_conflicts`]).
+    async fn solve(
         &self,
-        root_deps: &[Dependency],
+        provider: &mut PubGrubProvider,
         dev_deps: &[Dependency],
-        packages: AHashMap<String, PackageEntry>,
     ) -> Result<Resolution, ResolveError> {
-        let all_deps: Vec<_> = if self.config.include_dev {
-            root_deps.iter().chain(dev_deps.iter()).cloned().collect()
-        } else {
-            root_deps.to_vec()
-        };
-
-        // Composer-style per-package stability flags from explicit root constraints
-        // (e.g., dev-master, ^1.0@beta), overriding global minimum-stability.
-        let mut package_min_stability: AHashMap<String, Stability> = AHashMap::new();
-        for dep in &all_deps {
-            let dep_min_stability = dep.constraint.min_stability();
-            if dep_min_stability < self.config.min_stability {
-                package_min_stability
-                    .entry(dep.name.as_str().to_string())
-                    .and_modify(|current| *current = (*current).min(dep_min_stability))
-                    .or_insert(dep_min_stability);
-            }
-        }
-
-        let root_dep_ranges: Vec<_> = all_deps
-            .iter()
-            .filter(|d| !is_platform_package(d.name.as_str()))
-            .map(|d| (d.name.clone(), d.constraint.ranges().clone()))
-            .collect();
-
-        let root = PackageName::new("__root__", "__root__");
-        let root_ver = ComposerVersion::new(1, 0, 0);
-
         // --- Pass 1: Resolve to discover replacements ---
-        let provider = PubGrubProvider::new(
-            packages.clone(),
-            self.config.mode,
-            self.config.min_stability,
-            package_min_stability.clone(),
-            AHashSet::new(),
-        );
-        provider.set_root_deps(root_dep_ranges.clone());
-
-        let solution = match resolve(&provider, root.clone(), root_ver.clone()) {
-            Ok(s) => s,
-            Err(PubGrubError::NoSolution(mut tree)) => {
-                tree.collapse_no_versions();
-                return Err(ResolveError::Conflict {
-                    explanation: DefaultStringReporter::report(&tree),
-                });
-            }
-            Err(PubGrubError::ErrorChoosingVersion { package, .. }) => {
-                return Err(ResolveError::PackageNotFound {
-                    name: package.to_string(),
-                });
-            }
-            Err(_) => return Err(ResolveError::Cancelled),
-        };
+        let solution = self.solve_with_conflicts(provider).await?;
 
         // Collect replacements from the *selected* versions only
         let mut replaced: AHashSet<String> = AHashSet::new();
@@ -370,7 +597,7 @@ impl<F: PackageFetcher> Resolver<F> {
 
         // If nothing is replaced, skip pass 2 — use pass 1 result directly
         if replaced.is_empty() {
-            return Ok(Self::build_resolution(solution, &provider, dev_deps));
+            return Ok(Self::build_resolution(solution, provider, dev_deps));
         }
 
         info!(
@@ -379,29 +606,76 @@ impl<F: PackageFetcher> Resolver<F> {
         );
 
         // --- Pass 2: Re-resolve with replaced packages excluded ---
-        let provider2 = PubGrubProvider::new(
-            packages,
-            self.config.mode,
-            self.config.min_stability,
-            package_min_stability,
-            replaced,
-        );
-        provider2.set_root_deps(root_dep_ranges);
-
-        match resolve(&provider2, root, root_ver) {
-            Ok(solution) => Ok(Self::build_resolution(solution, &provider2, dev_deps)),
-            Err(PubGrubError::NoSolution(mut tree)) => {
-                tree.collapse_no_versions();
-                Err(ResolveError::Conflict {
-                    explanation: DefaultStringReporter::report(&tree),
-                })
+        provider.replaced_packages = replaced;
+        let solution = self.solve_with_conflicts(provider).await?;
+        Ok(Self::build_resolution(solution, provider, dev_deps))
+    }
+
+    /// Solve until no selected package conflicts with another.
+    ///
+    /// Conflicts are enforced through a guard package per target (see
+    /// [`conflict_guard`]). Guards are only added for targets a solution
+    /// selected in a conflicting version, since mirroring every package
+    /// anything conflicts with would slow down each solver round. Each round
+    /// guards at least one more target, so this terminates.
+    async fn solve_with_conflicts(
+        &self,
+        provider: &mut PubGrubProvider,
+    ) -> Result<SelectedDependencies<PubGrubProvider>, ResolveError> {
+        loop {
+            let solution = self.solve_on_demand(provider).await?;
+            let targets = provider.unenforced_conflicts(&solution);
+            if targets.is_empty() {
+                return Ok(solution);
             }
-            Err(PubGrubError::ErrorChoosingVersion { package, .. }) => {
-                Err(ResolveError::PackageNotFound {
-                    name: package.to_string(),
-                })
+            info!(
+                conflict_count = targets.len(),
+                "re-resolving with conflicts enforced"
+            );
+            provider.conflict_targets.extend(targets);
+        }
+    }
+
+    /// Run the solver until it no longer stops for missing metadata.
+    async fn solve_on_demand(
+        &self,
+        provider: &mut PubGrubProvider,
+    ) -> Result<SelectedDependencies<PubGrubProvider>, ResolveError> {
+        let root = PackageName::new("__root__", "__root__");
+        let root_ver = ComposerVersion::new(1, 0, 0);
+
+        loop {
+            self.stats.solver_rounds.fetch_add(1, Ordering::Relaxed);
+            let solver_start = Instant::now();
+            let result = resolve(&*provider, root.clone(), root_ver.clone());
+            self.stats.solver_time_ms.fetch_add(
+                u64::try_from(solver_start.elapsed().as_millis()).unwrap_or(u64::MAX),
+                Ordering::Relaxed,
+            );
+
+            match result {
+                Ok(solution) => return Ok(solution),
+                Err(PubGrubError::NoSolution(mut tree)) => {
+                    tree.collapse_no_versions();
+                    return Err(ResolveError::Conflict {
+                        explanation: DefaultStringReporter::report(&tree),
+                        derivation: Box::new(Derivation::from_tree(&tree, &provider.platform)),
+                    });
+                }
+                Err(PubGrubError::ErrorChoosingVersion {
+                    source: MetadataPending,
+                    ..
+                }) => {
+                    let missing = provider.take_missing();
+                    // Fetching always loads or rules out each package, so this cannot loop forever
+                    if missing.is_empty() {
+                        return Err(ResolveError::Cancelled);
+                    }
+                    debug!(missing = missing.len(), "solver needs more metadata");
+                    self.fetch_metadata(provider, missing).await;
+                }
+                Err(_) => return Err(ResolveError::Cancelled),
             }
-            Err(_) => Err(ResolveError::Cancelled),
         }
     }
 
@@ -421,7 +695,10 @@ impl<F: PackageFetcher> Resolver<F> {
         let mut pkg_map: AHashMap<String, (PackageName, ComposerVersion)> = AHashMap::new();
 
         for (name, version) in solution {
-            if name.as_str() == "__root__/__root__" {
+            if name.as_str() == "__root__/__root__"
+                || name.is_platform()
+                || guarded_package(name.as_str()).is_some()
+            {
                 continue;
             }
             let key = name.as_str().to_string();
@@ -561,8 +838,26 @@ impl<F: PackageFetcher> Resolver<F> {
 // PubGrub Provider
 // ============================================================================
 
+/// Raised by the provider when the solver reaches a package whose metadata
+/// has not been fetched yet.
+#[derive(Debug)]
+struct MetadataPending;
+
+impl std::fmt::Display for MetadataPending {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("package metadata not fetched yet")
+    }
+}
+
+impl std::error::Error for MetadataPending {}
+
 struct PubGrubProvider {
     packages: AHashMap<String, PackageEntry>,
+    /// Packages that were fetched but do not exist or have no usable versions.
+    unavailable: AHashSet<String>,
+    /// Packages the solver reached before their metadata was fetched, with
+    /// the range it was looking for.
+    missing: parking_lot::Mutex<AHashMap<String, Ranges<ComposerVersion>>>,
     mode: ResolutionMode,
     min_stability: Stability,
     package_min_stability: AHashMap<String, Stability>,
@@ -571,23 +866,36 @@ struct PubGrubProvider {
     /// In pass 1 this is empty; in pass 2 it contains the names of packages
     /// that are replaced by the versions selected in pass 1.
     replaced_packages: AHashSet<String>,
+    /// Versions only selected when nothing else fits, then reported as unavailable.
+    blocklist: VersionBlocklist,
+    /// Versions selected by the previous resolution of the same inputs.
+    previous: AHashMap<String, String>,
+    /// Installed platform packages; requirements on any other are ignored.
+    platform: AHashMap<String, ComposerVersion>,
+    /// Packages whose version is mirrored by a conflict guard, because an
+    /// earlier pass selected them where a `conflict` declaration applies.
+    conflict_targets: AHashSet<String>,
 }
 
 impl PubGrubProvider {
     fn new(
-        packages: AHashMap<String, PackageEntry>,
         mode: ResolutionMode,
         min_stability: Stability,
         package_min_stability: AHashMap<String, Stability>,
-        replaced_packages: AHashSet<String>,
     ) -> Self {
         Self {
-            packages,
+            packages: AHashMap::new(),
+            unavailable: AHashSet::new(),
+            missing: parking_lot::Mutex::new(AHashMap::new()),
             mode,
             min_stability,
             package_min_stability,
             root_deps: parking_lot::Mutex::new(DependencyConstraints::default()),
-            replaced_packages,
+            replaced_packages: AHashSet::new(),
+            blocklist: VersionBlocklist::new(),
+            previous: AHashMap::new(),
+            platform: AHashMap::new(),
+            conflict_targets: AHashSet::new(),
         }
     }
 
@@ -602,6 +910,49 @@ impl PubGrubProvider {
         }
     }
 
+    /// Whether metadata for `name` was fetched, found or not.
+    fn is_loaded(&self, name: &str) -> bool {
+        self.packages.contains_key(name) || self.unavailable.contains(name)
+    }
+
+    /// Record `pkg` as missing if the solver needs metadata that is not loaded.
+    fn note_missing(&self, pkg: &PackageName, range: &Ranges<ComposerVersion>) -> bool {
+        let name = pkg.as_str();
+        if name == "__root__/__root__"
+            || is_platform_package(name)
+            || guarded_package(name).is_some()
+            || self.replaced_packages.contains(name)
+            || self.is_loaded(name)
+        {
+            return false;
+        }
+        self.missing
+            .lock()
+            .entry(name.to_string())
+            .and_modify(|wanted| *wanted = wanted.union(range))
+            .or_insert_with(|| range.clone());
+        true
+    }
+
+    /// Packages to fetch before the solver runs again.
+    fn take_missing(&self) -> Vec<(String, Ranges<ComposerVersion>)> {
+        self.missing.lock().drain().collect()
+    }
+
+    /// Number of packages required by some loaded version that were never fetched.
+    fn unfetched_references(&self) -> usize {
+        let mut referenced: AHashSet<&str> = AHashSet::new();
+        for entry in self.packages.values() {
+            for version in &entry.versions {
+                referenced.extend(version.dependencies.iter().map(|dep| dep.name.as_str()));
+            }
+        }
+        referenced
+            .into_iter()
+            .filter(|name| !is_platform_package(name) && !self.is_loaded(name))
+            .count()
+    }
+
     fn deps_for(&self, name: &PackageName, version: &ComposerVersion) -> Option<Vec<Dependency>> {
         self.packages
             .get(name.as_str())?
@@ -623,12 +974,112 @@ impl PubGrubProvider {
             .find(|v| &v.version == version)
     }
 
+    /// Packages `solution` selects in a version another selected package
+    /// conflicts with, and whose version is not mirrored by a guard yet.
+    fn unenforced_conflicts(&self, solution: &SelectedDependencies<Self>) -> AHashSet<String> {
+        let mut targets = AHashSet::new();
+        for (name, version) in solution {
+            let Some(info) = self.version_info(name, version) else {
+                continue;
+            };
+            for conflict in &info.conflicts {
+                for (other, other_version) in solution {
+                    if other != name
+                        && !self.conflict_targets.contains(other.as_str())
+                        && self
+                            .version_info(other, other_version)
+                            .is_some_and(|other| supplies(other, conflict))
+                    {
+                        targets.insert(other.as_str().to_string());
+                    }
+                }
+            }
+        }
+        targets
+    }
+
+    /// Versions of the guarded packages that `conflict`, declared by
+    /// `package`, rules out, keyed by the guarded package.
+    fn conflicting_versions(
+        &self,
+        package: &PackageName,
+        conflict: &Dependency,
+    ) -> Vec<(&PackageName, Ranges<ComposerVersion>)> {
+        self.conflict_targets
+            .iter()
+            .filter(|target| target.as_str() != package.as_str())
+            .filter_map(|target| self.packages.get(target.as_str()))
+            .filter_map(|entry| {
+                let forbidden = if entry.name == conflict.name {
+                    conflict.constraint.ranges().clone()
+                } else {
+                    entry
+                        .versions
+                        .iter()
+                        .filter(|version| supplies(version, conflict))
+                        .fold(Ranges::empty(), |forbidden, version| {
+                            forbidden.union(&Ranges::singleton(version.version.clone()))
+                        })
+                };
+                (!forbidden.is_empty()).then_some((&entry.name, forbidden))
+            })
+            .collect()
+    }
+
     fn package_min_stability(&self, pkg: &PackageName) -> Stability {
         self.package_min_stability
             .get(pkg.as_str())
             .copied()
             .unwrap_or(self.min_stability)
     }
+
+    /// Versions of `entry` within `range` that meet the stability floor.
+    fn matching<'a>(
+        &self,
+        entry: &'a PackageEntry,
+        range: &Ranges<ComposerVersion>,
+    ) -> Vec<&'a PackageVersion> {
+        let min_stability = self.package_min_stability(&entry.name);
+        entry
+            .versions
+            .iter()
+            .filter(|v| range.contains(&v.version) && v.version.stability >= min_stability)
+            .collect()
+    }
+
+    /// The version the solver will most likely pick from `entry` for `range`.
+    ///
+    /// The previous resolution's choice wins while it is still in range.
+    fn likely_version<'a>(
+        &self,
+        entry: &'a PackageEntry,
+        range: &Ranges<ComposerVersion>,
+    ) -> Option<&'a PackageVersion> {
+        let matching = self.matching(entry, range);
+        if let Some(previous) = self.previous.get(entry.name.as_str())
+            && let Some(version) = matching.iter().find(|v| v.version.to_string() == *previous)
+        {
+            return Some(version);
+        }
+        self.pick(&matching)
+    }
+
+    /// Pick the preferred version among `candidates` for the resolution mode.
+    fn pick<'a>(&self, candidates: &[&'a PackageVersion]) -> Option<&'a PackageVersion> {
+        match self.mode {
+            ResolutionMode::PreferStable => {
+                // Prefer stable versions, then highest
+                candidates
+                    .iter()
+                    .filter(|v| v.version.stability == Stability::Stable)
+                    .max_by(|a, b| a.version.cmp(&b.version))
+                    .copied()
+                    .or_else(|| candidates.first().copied())
+            }
+            ResolutionMode::PreferHighest => candidates.first().copied(),
+            ResolutionMode::PreferLowest => candidates.last().copied(),
+        }
+    }
 }
 
 impl DependencyProvider for PubGrubProvider {
@@ -636,7 +1087,7 @@ impl DependencyProvider for PubGrubProvider {
     type V = ComposerVersion;
     type VS = Ranges<ComposerVersion>;
     type M = String;
-    type Err = Infallible;
+    type Err = MetadataPending;
     type Priority = std::cmp::Reverse<usize>;
 
     fn prioritize(
@@ -645,6 +1096,14 @@ impl DependencyProvider for PubGrubProvider {
         range: &Ranges<ComposerVersion>,
         _: &PackageResolutionStatistics,
     ) -> Self::Priority {
+        // Missing packages get the highest priority so the solver stops for them early
+        if self.note_missing(pkg, range) {
+            return std::cmp::Reverse(0);
+        }
+        // Guards follow their package, so decide them last
+        if guarded_package(pkg.as_str()).is_some() {
+            return std::cmp::Reverse(usize::MAX);
+        }
         let count = self.packages.get(pkg.as_str()).map_or(0, |e| {
             e.versions
                 .iter()
@@ -658,14 +1117,34 @@ impl DependencyProvider for PubGrubProvider {
         &self,
         pkg: &PackageName,
         range: &Ranges<ComposerVersion>,
-    ) -> Result<Option<ComposerVersion>, Infallible> {
+    ) -> Result<Option<ComposerVersion>, MetadataPending> {
         if pkg.as_str() == "__root__/__root__" {
             let v = ComposerVersion::new(1, 0, 0);
             return Ok(if range.contains(&v) { Some(v) } else { None });
         }
 
         if is_platform_package(pkg.as_str()) {
-            return Ok(None);
+            return Ok(self
+                .platform
+                .get(pkg.as_str())
+                .filter(|version| range.contains(version))
+                .cloned());
+        }
+
+        // A guard is "not installed" unless its package was selected
+        if let Some(target) = guarded_package(pkg.as_str()) {
+            let absent = not_installed();
+            if range.contains(&absent) {
+                return Ok(Some(absent));
+            }
+            return Ok(self.packages.get(target).and_then(|entry| {
+                entry
+                    .versions
+                    .iter()
+                    .map(|v| &v.version)
+                    .find(|version| range.contains(version))
+                    .cloned()
+            }));
         }
 
         // In pass 2, replaced packages return no versions so PubGrub
@@ -674,32 +1153,25 @@ impl DependencyProvider for PubGrubProvider {
             return Ok(None);
         }
 
+        if self.note_missing(pkg, range) {
+            return Err(MetadataPending);
+        }
+
         let Some(entry) = self.packages.get(pkg.as_str()) else {
             return Ok(None);
         };
 
-        let min_stability = self.package_min_stability(pkg);
-
         // Filter by range and stability
-        let matching: Vec<_> = entry
-            .versions
+        let matching = self.matching(entry, range);
+
+        // Blocked versions are a last resort: choosing one lets
+        // `get_dependencies` report why it cannot be used.
+        let allowed: Vec<_> = matching
             .iter()
-            .filter(|v| range.contains(&v.version) && v.version.stability >= min_stability)
+            .copied()
+            .filter(|v| self.blocklist.reason(pkg.as_str(), &v.version).is_none())
             .collect();
-
-        let best = match self.mode {
-            ResolutionMode::PreferStable => {
-                // Prefer stable versions, then highest
-                matching
-                    .iter()
-                    .filter(|v| v.version.stability == Stability::Stable)
-                    .max_by(|a, b| a.version.cmp(&b.version))
-                    .copied()
-                    .or_else(|| matching.first().copied())
-            }
-            ResolutionMode::PreferHighest => matching.first().copied(),
-            ResolutionMode::PreferLowest => matching.last().copied(),
-        };
+        let best = self.pick(&allowed).or_else(|| self.pick(&matching));
 
         Ok(best.map(|v| v.version.clone()))
     }
@@ -708,7 +1180,7 @@ impl DependencyProvider for PubGrubProvider {
         &self,
         pkg: &PackageName,
         ver: &ComposerVersion,
-    ) -> Result<Dependencies<PackageName, Ranges<ComposerVersion>, String>, Infallible> {
+    ) -> Result<Dependencies<PackageName, Ranges<ComposerVersion>, String>, MetadataPending> {
         if pkg.as_str() == "__root__/__root__" {
             return Ok(Dependencies::Available(self.root_deps.lock().clone()));
         }
@@ -717,6 +1189,10 @@ impl DependencyProvider for PubGrubProvider {
             return Ok(Dependencies::Available(DependencyConstraints::default()));
         }
 
+        if let Some(reason) = self.blocklist.reason(pkg.as_str(), ver) {
+            return Ok(Dependencies::Unavailable(reason.to_string()));
+        }
+
         let Some(entry) = self.packages.get(pkg.as_str()) else {
             return Ok(Dependencies::Available(DependencyConstraints::default()));
         };
@@ -730,10 +1206,27 @@ impl DependencyProvider for PubGrubProvider {
             let dep_name = dep.name.as_str();
             // In pass 2, skip dependencies on replaced packages — the
             // replacer already satisfies them.
-            if !is_platform_package(dep_name) && !self.replaced_packages.contains(dep_name) {
+            if (!is_platform_package(dep_name) || self.platform.contains_key(dep_name))
+                && !self.replaced_packages.contains(dep_name)
+            {
                 deps.insert(dep.name.clone(), dep.constraint.ranges().clone());
             }
         }
+        // A conflict rules out its versions of the guarded packages, while
+        // leaving them free not to be installed at all.
+        if self.conflict_targets.contains(pkg.as_str()) {
+            deps.insert(conflict_guard(pkg), Ranges::singleton(ver.clone()));
+        }
+        for conflict in &version.conflicts {
+            for (target, forbidden) in self.conflicting_versions(pkg, conflict) {
+                let allowed = forbidden
+                    .complement()
+                    .union(&Ranges::singleton(not_installed()));
+                deps.entry(conflict_guard(target))
+                    .and_modify(|range| *range = range.intersection(&allowed))
+                    .or_insert(allowed);
+            }
+        }
 
         Ok(Dependencies::Available(deps))
     }
@@ -749,6 +1242,45 @@ fn is_platform_package(name: &str) -> bool {
     libretto_core::is_platform_package_name(name)
 }
 
+/// Marks the virtual package mirroring the version of a conflict target.
+const GUARD_SUFFIX: &str = " (if installed)";
+
+/// Virtual package that mirrors the selected version of `target`.
+///
+/// `PubGrub` dependencies always require their target, so a conflict cannot
+/// be a dependency on the target itself. Instead every version of a guarded
+/// target depends on exactly that version of its guard, and a conflicting
+/// package depends on the guard being [`not_installed`] or outside the
+/// conflict. The guard itself has no dependencies.
+pub(crate) fn conflict_guard(target: &PackageName) -> PackageName {
+    PackageName::new(target.vendor(), &format!("{}{GUARD_SUFFIX}", target.name()))
+}
+
+/// The package a conflict guard mirrors, if `name` is a guard.
+pub(crate) fn guarded_package(name: &str) -> Option<&str> {
+    name.strip_suffix(GUARD_SUFFIX)
+}
+
+/// Guard version standing for a package that is not installed.
+///
+/// An empty dev branch, which no package has and which sorts below every
+/// other version.
+pub(crate) fn not_installed() -> ComposerVersion {
+    ComposerVersion::dev_branch("")
+}
+
+/// Whether `version` installs a package `conflict` applies to, itself or
+/// through its `replace` and `provide` declarations.
+fn supplies(version: &PackageVersion, conflict: &Dependency) -> bool {
+    let range = conflict.constraint.ranges();
+    (version.name == conflict.name && range.contains(&version.version))
+        || version
+            .replaces
+            .iter()
+            .chain(&version.provides)
+            .any(|link| link.name == conflict.name && !link.constraint.ranges().is_disjoint(range))
+}
+
 /// Parse a constraint string from replace/provide declarations.
 ///
 /// Handles the special `"self.version"` value used in Composer's `replace` and
@@ -784,7 +1316,7 @@ fn convert_fetched_package(
         // Dependencies
         for (dep_name, constraint) in &v.require {
             if let (Some(n), Some(c)) = (
-                PackageName::parse(dep_name),
+                PackageName::parse(dep_name).or_else(|| PackageName::platform(dep_name)),
                 ComposerConstraint::parse(constraint),
             ) {
                 pv.add_dependency(Dependency::new(n, c));
@@ -811,10 +1343,21 @@ fn convert_fetched_package(
             }
         }
 
+        // Conflicts
+        for (dep_name, constraint) in &v.conflict {
+            if let (Some(n), Some(c)) = (
+                PackageName::parse(dep_name),
+                ComposerConstraint::parse(constraint),
+            ) {
+                pv.add_conflict(Dependency::new(n, c));
+            }
+        }
+
         // Distribution info
         pv.dist_url = v.dist_url.as_ref().map(|s| Arc::from(s.as_str()));
         pv.dist_type = v.dist_type.as_ref().map(|s| Arc::from(s.as_str()));
         pv.dist_shasum = v.dist_shasum.as_ref().map(|s| Arc::from(s.as_str()));
+        pv.dist_signature = v.dist_signature.as_ref().map(|s| Arc::from(s.as_str()));
         pv.source_url = v.source_url.as_ref().map(|s| Arc::from(s.as_str()));
         pv.source_type = v.source_type.as_ref().map(|s| Arc::from(s.as_str()));
         pv.source_reference = v.source_reference.as_ref().map(|s| Arc::from(s.as_str()));
@@ -834,6 +1377,7 @@ fn convert_fetched_package(
         pv.funding.clone_from(&v.funding);
         pv.notification_url = v.notification_url.as_ref().map(|s| Arc::from(s.as_str()));
         pv.bin.clone_from(&v.bin);
+        pv.abandoned = v.abandoned.clone().filter(AbandonedField::is_abandoned);
 
         entry.add_version(pv);
     }
@@ -863,12 +1407,13 @@ fn build_resolved_package(
 
     let pkg_info = provider.version_info(&pkg_name, &version);
 
-    let (dist_url, dist_type, dist_shasum, src_url, src_type, src_ref) =
-        pkg_info.map_or((None, None, None, None, None, None), |v| {
+    let (dist_url, dist_type, dist_shasum, dist_signature, src_url, src_type, src_ref) = pkg_info
+        .map_or((None, None, None, None, None, None, None), |v| {
             (
                 v.dist_url.as_ref().map(ToString::to_string),
                 v.dist_type.as_ref().map(ToString::to_string),
                 v.dist_shasum.as_ref().map(ToString::to_string),
+                v.dist_signature.as_ref().map(ToString::to_string),
                 v.source_url.as_ref().map(ToString::to_string),
                 v.source_type.as_ref().map(ToString::to_string),
                 v.source_reference.as_ref().map(ToString::to_string),
@@ -933,6 +1478,7 @@ fn build_resolved_package(
         dist_url,
         dist_type,
         dist_shasum,
+        dist_signature,
         source_url: src_url,
         source_type: src_type,
         source_reference: src_ref,
@@ -1002,6 +1548,18 @@ mod tests {
         ) -> Pin<Box<dyn Future<Output = Option<FetchedPackage>> + Send + '_>> {
             Box::pin(async move { self.packages.get(&name).cloned() })
         }
+
+        fn validator(&self, name: &str) -> Option<String> {
+            let package = self.packages.get(name)?;
+            Some(
+                package
+                    .versions
+                    .iter()
+                    .map(|v| v.version.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            )
+        }
     }
 
     fn fetched_version(version: &str, require: Vec<(&str, &str)>) -> FetchedVersion {
@@ -1014,10 +1572,12 @@ mod tests {
             require_dev: Vec::new(),
             replace: Vec::new(),
             provide: Vec::new(),
+            conflict: Vec::new(),
             suggest: Vec::new(),
             dist_url: None,
             dist_type: None,
             dist_shasum: None,
+            dist_signature: None,
             source_url: None,
             source_type: None,
             source_reference: None,
@@ -1035,9 +1595,49 @@ mod tests {
             funding: None,
             notification_url: None,
             bin: None,
+            abandoned: None,
+        }
+    }
+
+    struct StaticFilter(VersionBlocklist);
+
+    impl VersionFilter for StaticFilter {
+        fn blocklist(
+            &self,
+            _packages: Vec<String>,
+        ) -> Pin<Box<dyn Future<Output = VersionBlocklist> + Send + '_>> {
+            Box::pin(async move { self.0.clone() })
         }
     }
 
+    fn advisory_blocklist(constraint: &str) -> Arc<StaticFilter> {
+        let mut blocklist = VersionBlocklist::new();
+        blocklist.block(
+            "vendor/lib",
+            ComposerConstraint::parse(constraint).expect("valid constraint"),
+            "because it is affected by CVE-2024-0001",
+        );
+        Arc::new(StaticFilter(blocklist))
+    }
+
+    fn lib_fetcher() -> StaticFetcher {
+        StaticFetcher::new(vec![FetchedPackage {
+            name: "vendor/lib".to_string(),
+            versions: vec![
+                fetched_version("1.2.0", vec![]),
+                fetched_version("1.1.0", vec![]),
+                fetched_version("1.0.0", vec![]),
+            ],
+        }])
+    }
+
+    fn lib_dependency() -> Vec<Dependency> {
+        vec![Dependency::new(
+            PackageName::parse("vendor/lib").expect("valid package name"),
+            ComposerConstraint::parse("^1.0").expect("valid constraint"),
+        )]
+    }
+
     #[tokio::test]
     async fn explicit_dev_constraint_overrides_global_minimum_stability() {
         let fetcher = StaticFetcher::new(vec![FetchedPackage {
@@ -1100,4 +1700,596 @@ mod tests {
             "expected conflict, got: {err:?}"
         );
     }
+
+    #[tokio::test]
+    async fn blocked_versions_fall_back_to_newest_safe_version() {
+        let resolver = Resolver::new(Arc::new(lib_fetcher()), ResolverConfig::default())
+            .with_version_filter(advisory_blocklist(">=1.1.0"));
+
+        let resolution = resolver
+            .resolve(&lib_dependency(), &[])
+            .await
+            .expect("an unaffected version exists");
+        let pkg = resolution
+            .get("vendor/lib")
+            .expect("package should be resolved");
+        assert_eq!(pkg.version.to_string(), "1.0.0");
+    }
+
+    #[tokio::test]
+    async fn fully_blocked_package_names_the_reason() {
+        let resolver = Resolver::new(Arc::new(lib_fetcher()), ResolverConfig::default())
+            .with_version_filter(advisory_blocklist(">=1.0.0"));
+
+        let err = resolver
+            .resolve(&lib_dependency(), &[])
+            .await
+            .expect_err("every version is affected");
+        let ResolveError::Conflict { explanation, .. } = err else {
+            panic!("expected conflict, got: {err:?}");
+        };
+        assert!(
+            explanation.contains("CVE-2024-0001"),
+            "explanation should name the advisory: {explanation}"
+        );
+    }
+
+    fn package(name: &str, versions: Vec<FetchedVersion>) -> FetchedPackage {
+        FetchedPackage {
+            name: name.to_string(),
+            versions,
+        }
+    }
+
+    fn dependency(name: &str, constraint: &str) -> Dependency {
+        Dependency::new(
+            PackageName::parse(name).expect("valid package name"),
+            ComposerConstraint::parse(constraint).expect("valid constraint"),
+        )
+    }
+
+    #[tokio::test]
+    async fn requirements_of_unconsidered_versions_are_never_fetched() {
+        let fetcher = StaticFetcher::new(vec![
+            package(
+                "vendor/app",
+                vec![
+                    fetched_version("2.0.0", vec![("vendor/new", "^1.0")]),
+                    fetched_version("1.0.0", vec![("vendor/ancient", "^1.0")]),
+                ],
+            ),
+            package("vendor/new", vec![fetched_version("1.0.0", vec![])]),
+            package("vendor/ancient", vec![fetched_version("1.0.0", vec![])]),
+        ]);
+        let resolver = Resolver::new(Arc::new(fetcher), ResolverConfig::default());
+
+        let resolution = resolver
+            .resolve(&[dependency("vendor/app", "*")], &[])
+            .await
+            .expect("resolvable");
+
+        assert!(resolution.get("vendor/new").is_some());
+        let stats = resolver.stats();
+        assert_eq!(stats.packages_fetched.load(Ordering::Relaxed), 2);
+        assert_eq!(stats.packages_prefetched.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.packages_avoided.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.solver_rounds.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn older_version_requirements_are_counted_as_avoided() {
+        let mut packages = vec![package(
+            "vendor/app",
+            (0..5)
+                .rev()
+                .map(|minor| {
+                    let lib = format!("vendor/lib{minor}");
+                    fetched_version(&format!("1.{minor}.0"), vec![(lib.as_str(), "^1.0")])
+                })
+                .collect(),
+        )];
+        packages.extend((0..5).map(|minor| {
+            package(
+                &format!("vendor/lib{minor}"),
+                vec![fetched_version("1.0.0", vec![])],
+            )
+        }));
+        let resolver = Resolver::new(
+            Arc::new(StaticFetcher::new(packages)),
+            ResolverConfig::default(),
+        );
+
+        let resolution = resolver
+            .resolve(&[dependency("vendor/app", "^1.0")], &[])
+            .await
+            .expect("resolvable");
+
+        assert!(resolution.get("vendor/lib4").is_some());
+        let stats = resolver.stats();
+        assert_eq!(stats.packages_fetched.load(Ordering::Relaxed), 2);
+        assert_eq!(stats.packages_avoided.load(Ordering::Relaxed), 4);
+        assert_eq!(stats.solver_rounds.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn dist_signature_is_carried_into_resolution() {
+        let mut signed = fetched_version("1.0.0", vec![]);
+        signed.dist_signature = Some("https://example.com/lib-1.0.0.zip.sig".to_string());
+        let resolver = Resolver::new(
+            Arc::new(StaticFetcher::new(vec![package(
+                "vendor/lib",
+                vec![signed],
+            )])),
+            ResolverConfig::default(),
+        );
+
+        let resolution = resolver
+            .resolve(&[dependency("vendor/lib", "^1.0")], &[])
+            .await
+            .expect("resolvable");
+
+        let lib = resolution.get("vendor/lib").expect("lib resolved");
+        assert_eq!(
+            lib.dist_signature.as_deref(),
+            Some("https://example.com/lib-1.0.0.zip.sig")
+        );
+    }
+
+    #[tokio::test]
+    async fn backtracking_fetches_metadata_on_demand() {
+        let fetcher = StaticFetcher::new(vec![
+            package(
+                "vendor/app",
+                vec![
+                    fetched_version("2.0.0", vec![("vendor/pin", "^2.0")]),
+                    fetched_version("1.0.0", vec![("vendor/old", "^1.0")]),
+                ],
+            ),
+            package(
+                "vendor/pin",
+                vec![
+                    fetched_version("2.0.0", vec![]),
+                    fetched_version("1.0.0", vec![]),
+                ],
+            ),
+            package("vendor/old", vec![fetched_version("1.0.0", vec![])]),
+        ]);
+        let resolver = Resolver::new(Arc::new(fetcher), ResolverConfig::default());
+
+        let resolution = resolver
+            .resolve(
+                &[
+                    dependency("vendor/app", "*"),
+                    dependency("vendor/pin", "^1.0"),
+                ],
+                &[],
+            )
+            .await
+            .expect("app 1.0.0 fits");
+
+        let app = resolution.get("vendor/app").expect("app resolved");
+        assert_eq!(app.version.to_string(), "1.0.0");
+        assert!(resolution.get("vendor/old").is_some());
+        let stats = resolver.stats();
+        assert_eq!(stats.packages_fetched.load(Ordering::Relaxed), 3);
+        assert_eq!(stats.solver_rounds.load(Ordering::Relaxed), 2);
+        assert_eq!(stats.packages_avoided.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn abandoned_packages_are_blocked_when_configured() {
+        let mut abandoned = fetched_version("1.0.0", vec![]);
+        abandoned.abandoned = Some(AbandonedField::Replacement("vendor/new".to_string()));
+        let fetcher = StaticFetcher::new(vec![FetchedPackage {
+            name: "vendor/lib".to_string(),
+            versions: vec![abandoned],
+        }]);
+
+        let resolver = Resolver::new(
+            Arc::new(fetcher.clone()),
+            ResolverConfig {
+                block_abandoned: true,
+                ..Default::default()
+            },
+        );
+        let err = resolver
+            .resolve(&lib_dependency(), &[])
+            .await
+            .expect_err("abandoned package is blocked");
+        assert!(err.to_string().contains("use vendor/new instead"), "{err}");
+
+        let resolver = Resolver::new(Arc::new(fetcher), ResolverConfig::default());
+        assert!(resolver.resolve(&lib_dependency(), &[]).await.is_ok());
+    }
+
+    fn resolution_cache(dir: &std::path::Path) -> ResolutionCache {
+        let cache = libretto_cache::TieredCache::at_path(
+            dir.to_path_buf(),
+            libretto_cache::CacheConfig::default(),
+        )
+        .expect("cache opens");
+        ResolutionCache::new(Arc::new(cache))
+    }
+
+    fn app_fetcher(lib_versions: &[&str]) -> StaticFetcher {
+        StaticFetcher::new(vec![
+            package(
+                "vendor/app",
+                vec![fetched_version("1.0.0", vec![("vendor/lib", "^1.0")])],
+            ),
+            package(
+                "vendor/lib",
+                lib_versions
+                    .iter()
+                    .map(|version| fetched_version(version, vec![]))
+                    .collect(),
+            ),
+        ])
+    }
+
+    #[tokio::test]
+    async fn unchanged_inputs_reuse_the_cached_resolution() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cache = resolution_cache(dir.path());
+        let root = [dependency("vendor/app", "^1.0")];
+
+        let cold = Resolver::new(
+            Arc::new(app_fetcher(&["1.1.0", "1.0.0"])),
+            ResolverConfig::default(),
+        )
+        .with_cache(cache.clone());
+        cold.resolve(&root, &[]).await.expect("resolvable");
+        assert_eq!(cold.stats().cache_hits.load(Ordering::Relaxed), 0);
+
+        let warm = Resolver::new(
+            Arc::new(app_fetcher(&["1.1.0", "1.0.0"])),
+            ResolverConfig::default(),
+        )
+        .with_cache(cache);
+        let resolution = warm.resolve(&root, &[]).await.expect("resolvable");
+
+        assert_eq!(
+            resolution
+                .get("vendor/lib")
+                .expect("lib")
+                .version
+                .to_string(),
+            "1.1.0"
+        );
+        let stats = warm.stats();
+        assert_eq!(stats.cache_hits.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.solver_rounds.load(Ordering::Relaxed), 0);
+    }
+
+    /// Records the packages fetched through it.
+    struct RecordingFetcher {
+        inner: StaticFetcher,
+        fetched: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl PackageFetcher for RecordingFetcher {
+        fn fetch(
+            &self,
+            name: String,
+        ) -> Pin<Box<dyn Future<Output = Option<FetchedPackage>> + Send + '_>> {
+            self.fetched.lock().expect("lock").push(name.clone());
+            self.inner.fetch(name)
+        }
+
+        fn validator(&self, name: &str) -> Option<String> {
+            self.inner.validator(name)
+        }
+    }
+
+    #[tokio::test]
+    async fn cache_hits_only_load_the_selected_packages() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cache = resolution_cache(dir.path());
+        let root = [dependency("vendor/app", "^1.0")];
+        // lib 1.1.0 needs a version of extra that does not exist
+        let recording_fetcher = || RecordingFetcher {
+            inner: StaticFetcher::new(vec![
+                package(
+                    "vendor/app",
+                    vec![fetched_version("1.0.0", vec![("vendor/lib", "^1.0")])],
+                ),
+                package(
+                    "vendor/lib",
+                    vec![
+                        fetched_version("1.1.0", vec![("vendor/extra", "^2.0")]),
+                        fetched_version("1.0.0", vec![]),
+                    ],
+                ),
+                package("vendor/extra", vec![fetched_version("1.0.0", vec![])]),
+            ]),
+            fetched: std::sync::Mutex::default(),
+        };
+
+        let cold = Arc::new(recording_fetcher());
+        Resolver::new(Arc::clone(&cold), ResolverConfig::default())
+            .with_cache(cache.clone())
+            .resolve(&root, &[])
+            .await
+            .expect("resolvable");
+        assert!(
+            cold.fetched
+                .lock()
+                .expect("lock")
+                .contains(&"vendor/extra".to_string())
+        );
+
+        let warm = Arc::new(recording_fetcher());
+        let resolver =
+            Resolver::new(Arc::clone(&warm), ResolverConfig::default()).with_cache(cache);
+        let resolution = resolver.resolve(&root, &[]).await.expect("resolvable");
+
+        assert_eq!(
+            resolution
+                .get("vendor/lib")
+                .expect("lib")
+                .version
+                .to_string(),
+            "1.0.0"
+        );
+        assert_eq!(resolver.stats().cache_hits.load(Ordering::Relaxed), 1);
+        let mut fetched = warm.fetched.lock().expect("lock").clone();
+        fetched.sort();
+        assert_eq!(fetched, ["vendor/app", "vendor/lib"]);
+    }
+
+    #[tokio::test]
+    async fn changed_metadata_invalidates_the_cached_resolution() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cache = resolution_cache(dir.path());
+        let root = [dependency("vendor/app", "^1.0")];
+
+        Resolver::new(
+            Arc::new(app_fetcher(&["1.1.0", "1.0.0"])),
+            ResolverConfig::default(),
+        )
+        .with_cache(cache.clone())
+        .resolve(&root, &[])
+        .await
+        .expect("resolvable");
+
+        let resolver = Resolver::new(
+            Arc::new(app_fetcher(&["1.2.0", "1.1.0", "1.0.0"])),
+            ResolverConfig::default(),
+        )
+        .with_cache(cache.clone());
+        let resolution = resolver.resolve(&root, &[]).await.expect("resolvable");
+
+        assert_eq!(
+            resolution
+                .get("vendor/lib")
+                .expect("lib")
+                .version
+                .to_string(),
+            "1.2.0"
+        );
+        let stats = resolver.stats();
+        assert_eq!(stats.cache_hits.load(Ordering::Relaxed), 0);
+        // The previous packages were fetched up front, so one solver run suffices
+        assert_eq!(stats.solver_rounds.load(Ordering::Relaxed), 1);
+
+        // A different root requirement never sees another project's entry
+        let other = Resolver::new(
+            Arc::new(app_fetcher(&["1.2.0", "1.1.0", "1.0.0"])),
+            ResolverConfig::default(),
+        )
+        .with_cache(cache);
+        other
+            .resolve(&[dependency("vendor/lib", "^1.0")], &[])
+            .await
+            .expect("resolvable");
+        assert_eq!(other.stats().cache_hits.load(Ordering::Relaxed), 0);
+    }
+
+    fn php_fetcher() -> StaticFetcher {
+        StaticFetcher::new(vec![FetchedPackage {
+            name: "vendor/lib".to_string(),
+            versions: vec![
+                fetched_version("2.0.0", vec![("php", ">=8.2")]),
+                fetched_version("1.0.0", vec![("php", ">=7.4")]),
+            ],
+        }])
+    }
+
+    #[tokio::test]
+    async fn platform_requirements_are_ignored_without_a_platform() {
+        let resolver = Resolver::new(Arc::new(php_fetcher()), ResolverConfig::default());
+        let root_deps = vec![Dependency::new(
+            PackageName::parse("vendor/lib").expect("valid package name"),
+            ComposerConstraint::parse("*").expect("valid constraint"),
+        )];
+
+        let resolution = resolver.resolve(&root_deps, &[]).await.expect("resolvable");
+        let pkg = resolution.get("vendor/lib").expect("resolved");
+        assert_eq!(pkg.version.to_string(), "2.0.0");
+    }
+
+    #[tokio::test]
+    async fn platform_version_limits_the_selected_versions() {
+        let resolver = Resolver::new(Arc::new(php_fetcher()), ResolverConfig::default())
+            .with_platform([(
+                "php".to_string(),
+                ComposerVersion::parse("8.1.0").expect("valid version"),
+            )]);
+        let root_deps = vec![Dependency::new(
+            PackageName::parse("vendor/lib").expect("valid package name"),
+            ComposerConstraint::parse("*").expect("valid constraint"),
+        )];
+
+        let resolution = resolver.resolve(&root_deps, &[]).await.expect("resolvable");
+        let pkg = resolution.get("vendor/lib").expect("resolved");
+        assert_eq!(pkg.version.to_string(), "1.0.0");
+        assert!(resolution.get("php").is_none());
+
+        let pinned = vec![Dependency::new(
+            PackageName::parse("vendor/lib").expect("valid package name"),
+            ComposerConstraint::parse("2.0.0").expect("valid constraint"),
+        )];
+        let err = resolver
+            .resolve(&pinned, &[])
+            .await
+            .expect_err("php 8.1 cannot install 2.0.0");
+        let ResolveError::Conflict { derivation, .. } = err else {
+            panic!("expected conflict, got: {err:?}");
+        };
+        let facts: Vec<_> = derivation.facts().iter().map(|f| f.kind).collect();
+        assert!(facts.contains(&crate::DerivationKind::RootRequires));
+        assert!(facts.contains(&crate::DerivationKind::Requires));
+        assert!(
+            derivation.facts().iter().any(|f| f
+                .message
+                .ends_with("requires php >=8.2 (the platform provides 8.1.0)")),
+            "{derivation:#?}"
+        );
+    }
+
+    fn conflict_fetcher() -> StaticFetcher {
+        let mut app = fetched_version("1.0.0", vec![]);
+        app.conflict = vec![
+            ("vendor/lib".to_string(), ">=2.0".to_string()),
+            ("vendor/other".to_string(), "*".to_string()),
+        ];
+        StaticFetcher::new(vec![
+            FetchedPackage {
+                name: "vendor/app".to_string(),
+                versions: vec![app],
+            },
+            FetchedPackage {
+                name: "vendor/lib".to_string(),
+                versions: vec![
+                    fetched_version("2.0.0", vec![]),
+                    fetched_version("1.0.0", vec![]),
+                ],
+            },
+            FetchedPackage {
+                name: "vendor/other".to_string(),
+                versions: vec![fetched_version("1.0.0", vec![])],
+            },
+        ])
+    }
+
+    fn app_and_lib(lib: &str) -> Vec<Dependency> {
+        vec![
+            Dependency::new(
+                PackageName::parse("vendor/app").expect("valid package name"),
+                ComposerConstraint::parse("*").expect("valid constraint"),
+            ),
+            Dependency::new(
+                PackageName::parse("vendor/lib").expect("valid package name"),
+                ComposerConstraint::parse(lib).expect("valid constraint"),
+            ),
+        ]
+    }
+
+    #[tokio::test]
+    async fn conflicts_rule_out_versions_without_installing_their_target() {
+        let resolver = Resolver::new(Arc::new(conflict_fetcher()), ResolverConfig::default());
+
+        let resolution = resolver
+            .resolve(&app_and_lib("*"), &[])
+            .await
+            .expect("resolvable");
+        let lib = resolution.get("vendor/lib").expect("resolved");
+        assert_eq!(lib.version.to_string(), "1.0.0");
+        assert!(resolution.get("vendor/other").is_none());
+    }
+
+    #[tokio::test]
+    async fn conflicts_appear_in_the_derivation() {
+        let resolver = Resolver::new(Arc::new(conflict_fetcher()), ResolverConfig::default());
+
+        let err = resolver
+            .resolve(&app_and_lib("2.0.0"), &[])
+            .await
+            .expect_err("vendor/app conflicts with vendor/lib 2.0.0");
+        let ResolveError::Conflict { derivation, .. } = err else {
+            panic!("expected conflict, got: {err:?}");
+        };
+        assert!(
+            derivation
+                .facts()
+                .iter()
+                .any(|f| f.kind == crate::DerivationKind::Conflicts
+                    && f.message == "vendor/app ==1.0.0 conflicts with vendor/lib >=2.0"),
+            "{derivation:#?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn conflicts_do_not_require_their_target() {
+        let mut app = fetched_version("1.0.0", vec![]);
+        app.conflict = vec![("vendor/lib".to_string(), ">=2.0".to_string())];
+        let fetcher = StaticFetcher::new(vec![
+            package("vendor/app", vec![app]),
+            package(
+                "vendor/tool",
+                vec![
+                    fetched_version("2.0.0", vec![("vendor/lib", "^2.0")]),
+                    fetched_version("1.0.0", vec![]),
+                ],
+            ),
+            package(
+                "vendor/lib",
+                vec![
+                    fetched_version("2.0.0", vec![]),
+                    fetched_version("1.0.0", vec![]),
+                ],
+            ),
+        ]);
+        let resolver = Resolver::new(Arc::new(fetcher), ResolverConfig::default());
+
+        let resolution = resolver
+            .resolve(
+                &[
+                    dependency("vendor/app", "*"),
+                    dependency("vendor/tool", "*"),
+                ],
+                &[],
+            )
+            .await
+            .expect("resolvable");
+        let tool = resolution.get("vendor/tool").expect("resolved");
+        assert_eq!(tool.version.to_string(), "1.0.0");
+        assert!(resolution.get("vendor/lib").is_none());
+        assert_eq!(resolution.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn conflicts_apply_to_replaced_and_provided_names() {
+        let mut app = fetched_version("1.0.0", vec![]);
+        app.conflict = vec![("vendor/lib".to_string(), ">=2.0".to_string())];
+        let mut bundle = fetched_version("2.0.0", vec![]);
+        bundle.replace = vec![("vendor/lib".to_string(), "2.0.0".to_string())];
+        let mut kit = fetched_version("2.0.0", vec![]);
+        kit.provide = vec![("vendor/lib".to_string(), "self.version".to_string())];
+        let fetcher = StaticFetcher::new(vec![
+            package("vendor/app", vec![app]),
+            package(
+                "vendor/bundle",
+                vec![bundle, fetched_version("1.0.0", vec![])],
+            ),
+            package("vendor/kit", vec![kit, fetched_version("1.0.0", vec![])]),
+        ]);
+        let resolver = Resolver::new(Arc::new(fetcher), ResolverConfig::default());
+
+        let resolution = resolver
+            .resolve(
+                &[
+                    dependency("vendor/app", "*"),
+                    dependency("vendor/bundle", "*"),
+                    dependency("vendor/kit", "*"),
+                ],
+                &[],
+            )
+            .await
+            .expect("resolvable");
+        for name in ["vendor/bundle", "vendor/kit"] {
+            let package = resolution.get(name).expect("resolved");
+            assert_eq!(package.version.to_string(), "1.0.0", "{name}");
+        }
+    }
 }
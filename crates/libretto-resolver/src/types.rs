@@ -5,6 +5,7 @@
 //! - `ResolvedPackage`: A package selected during resolution with its metadata
 //! - `ResolveError`: Errors that can occur during resolution
 
+use crate::derivation::Derivation;
 use crate::package::PackageName;
 use crate::version::ComposerVersion;
 use ahash::AHashMap;
@@ -30,6 +31,8 @@ pub struct ResolvedPackage {
     pub dist_type: Option<String>,
     /// Distribution checksum.
     pub dist_shasum: Option<String>,
+    /// Detached signature URL of the distribution.
+    pub dist_signature: Option<String>,
     /// Source URL (git repository).
     pub source_url: Option<String>,
     /// Source type (git, hg, etc.).
@@ -144,6 +147,8 @@ pub enum ResolveError {
     Conflict {
         /// Human-readable explanation of the conflict.
         explanation: String,
+        /// The derivation the explanation was rendered from.
+        derivation: Box<Derivation>,
     },
 
     /// Package not found in any repository.
@@ -147,8 +147,7 @@ pub fn class_name_strategy() -> impl Strategy<Value = String> {
 
 /// Strategy for generating full qualified class names.
 pub fn fqcn_strategy() -> impl Strategy<Value = String> {
-    (namespace_strategy(), class_name_strategy())
-        .prop_map(|(ns, class)| format!("{ns}\\{class}"))
+    (namespace_strategy(), class_name_strategy()).prop_map(|(ns, class)| format!("{ns}\\{class}"))
 }
 
 /// Strategy for generating valid composer.json structure.